@@ -170,6 +170,8 @@ impl ExonumCommand for GenerateConfig {
             thread_pool_size: None,
             connect_list: ConnectListConfig::default(),
             consensus_public_key: keys.consensus_pk(),
+            consensus_master_key_path: None,
+            next_master_key_path: None,
         };
 
         save_config_file(&private_config, &private_config_path)?;
@@ -187,7 +189,7 @@ fn get_master_key_path(path: Option<PathBuf>) -> Result<PathBuf, Error> {
     Ok(path.join(MASTER_KEY_FILE_NAME))
 }
 
-fn create_keys_and_files(
+pub(super) fn create_keys_and_files(
     secret_key_path: impl AsRef<Path>,
     passphrase: impl AsRef<[u8]>,
 ) -> anyhow::Result<Keys> {
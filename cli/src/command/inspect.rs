@@ -0,0 +1,617 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only inspection of the database contents.
+
+use anyhow::{bail, format_err, Error};
+use exonum::{
+    blockchain::{config::ConsensusConfig, Block},
+    crypto::Hash,
+    merkledb::{
+        access::CopyAccessExt, BinaryValue, IndexAddress, IndexType, Snapshot, SystemSchema,
+    },
+    messages::{AnyTx, Verified},
+    runtime::{ArtifactId, DispatcherSchema},
+};
+use exonum_explorer_service::api::ServiceIndexInfo;
+use exonum_rust_runtime::ArtifactProtobufSpec;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use structopt::StructOpt;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::Write,
+};
+
+#[cfg(feature = "protobuf-decoding")]
+use crate::proto_json::MessageDecoder;
+
+/// Query performed by the `inspect` maintenance action.
+///
+/// The query results are output to the standard output, one JSON value per line.
+#[derive(StructOpt, Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum InspectQuery {
+    /// List namespaces, i.e., distinct parts of index names before the first dot
+    /// (`core`, service names, etc.).
+    #[structopt(name = "namespaces")]
+    Namespaces,
+
+    /// List indexes with the name starting with the specified prefix.
+    #[structopt(name = "indexes")]
+    Indexes {
+        /// Prefix of the index names, e.g. "core." or "my-service.".
+        #[structopt(default_value = "")]
+        prefix: String,
+    },
+
+    /// Get a single entry of an index.
+    #[structopt(name = "get")]
+    Get {
+        /// Name of the index, e.g. "core.blocks".
+        index: String,
+        /// Key of the entry: a decimal number for lists, a hex-encoded string for maps and sets.
+        /// Should be omitted for entries.
+        key: Option<String>,
+        /// Hex-encoded key of the index in a group.
+        #[structopt(long)]
+        group_key: Option<String>,
+        /// Fully qualified name of the Protobuf message used to decode values of a service index,
+        /// e.g. "exonum.examples.Wallet". Requires the `protobuf-decoding` feature
+        /// (enabled by default).
+        /// Values of core indexes are decoded automatically; other values are output
+        /// as hex-encoded strings.
+        #[structopt(long)]
+        message: Option<String>,
+    },
+
+    /// Iterate over entries of an index.
+    #[structopt(name = "iter")]
+    Iter {
+        /// Name of the index, e.g. "core.blocks".
+        index: String,
+        /// Hex-encoded key of the index in a group.
+        #[structopt(long)]
+        group_key: Option<String>,
+        /// Fully qualified name of the Protobuf message used to decode values of a service index,
+        /// e.g. "exonum.examples.Wallet". Requires the `protobuf-decoding` feature
+        /// (enabled by default).
+        /// Values of core indexes are decoded automatically; other values are output
+        /// as hex-encoded strings.
+        #[structopt(long)]
+        message: Option<String>,
+        /// Key to start the iteration from (inclusive).
+        #[structopt(long)]
+        from: Option<String>,
+        /// Key to end the iteration at (exclusive).
+        #[structopt(long)]
+        to: Option<String>,
+        /// Maximum number of entries to output.
+        #[structopt(long, default_value = "100")]
+        limit: usize,
+    },
+}
+
+/// Range of keys queried from an index.
+#[derive(Debug)]
+struct KeyRange<'a> {
+    from: Option<&'a str>,
+    to: Option<&'a str>,
+    limit: usize,
+}
+
+impl InspectQuery {
+    pub(super) fn execute(
+        &self,
+        snapshot: &dyn Snapshot,
+        protobuf_specs: &HashMap<ArtifactId, ArtifactProtobufSpec>,
+        out: &mut dyn Write,
+    ) -> Result<(), Error> {
+        match self {
+            Self::Namespaces => {
+                let namespaces: BTreeSet<_> = SystemSchema::new(snapshot)
+                    .indexes_by_prefix("")
+                    .into_iter()
+                    .filter_map(|info| info.address.name().split('.').next().map(str::to_owned))
+                    .collect();
+                for namespace in namespaces {
+                    write_json(out, &namespace)?;
+                }
+            }
+
+            Self::Indexes { prefix } => {
+                for info in SystemSchema::new(snapshot).indexes_by_prefix(prefix) {
+                    write_json(out, &ServiceIndexInfo::from(info))?;
+                }
+            }
+
+            Self::Get {
+                index,
+                key,
+                group_key,
+                message,
+            } => {
+                let address = index_address(index, group_key.as_deref())?;
+                let decoder =
+                    ValueDecoder::new(snapshot, protobuf_specs, index, message.as_deref())?;
+                get_entry(snapshot, address, key.as_deref(), &decoder, out)?;
+            }
+
+            Self::Iter {
+                index,
+                group_key,
+                message,
+                from,
+                to,
+                limit,
+            } => {
+                let address = index_address(index, group_key.as_deref())?;
+                let decoder =
+                    ValueDecoder::new(snapshot, protobuf_specs, index, message.as_deref())?;
+                let range = KeyRange {
+                    from: from.as_deref(),
+                    to: to.as_deref(),
+                    limit: *limit,
+                };
+                iter_entries(snapshot, address, &range, &decoder, out)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decoder of index values into JSON.
+enum ValueDecoder {
+    /// Values are output as hex-encoded strings.
+    Raw,
+    /// Values are decoded with a built-in type.
+    Builtin(fn(&[u8]) -> Result<Value, Error>),
+    /// Values are decoded based on the Protobuf declarations of a service artifact.
+    #[cfg(feature = "protobuf-decoding")]
+    Protobuf {
+        decoder: MessageDecoder,
+        message: String,
+    },
+}
+
+impl ValueDecoder {
+    fn new(
+        snapshot: &dyn Snapshot,
+        protobuf_specs: &HashMap<ArtifactId, ArtifactProtobufSpec>,
+        index_name: &str,
+        message: Option<&str>,
+    ) -> Result<Self, Error> {
+        let message = match message {
+            Some(message) => message,
+            None => return Ok(Self::builtin(index_name).unwrap_or(Self::Raw)),
+        };
+
+        let namespace = index_name.split('.').next().unwrap_or_default();
+        let instance = DispatcherSchema::new(snapshot)
+            .get_instance(namespace)
+            .ok_or_else(|| {
+                format_err!(
+                    "Index `{}` does not belong to a service, Protobuf messages cannot be used \
+                     to decode its values",
+                    index_name
+                )
+            })?;
+
+        // Service data corresponds to the data version rather than the artifact version
+        // if the service has been migrated.
+        let artifact = &instance.spec.artifact;
+        let data_version = instance.data_version();
+        let spec = protobuf_specs
+            .iter()
+            .find(|(id, _)| id.name == artifact.name && id.version == *data_version)
+            .map(|(_, spec)| spec)
+            .ok_or_else(|| {
+                format_err!(
+                    "Protobuf specification for artifact {}:{} is not available",
+                    artifact.name,
+                    data_version
+                )
+            })?;
+
+        let artifact = format!("{}:{}", artifact.name, data_version);
+        Self::protobuf(spec, &artifact, message)
+    }
+
+    #[cfg(feature = "protobuf-decoding")]
+    fn protobuf(spec: &ArtifactProtobufSpec, artifact: &str, message: &str) -> Result<Self, Error> {
+        let decoder = MessageDecoder::from_spec(spec)?;
+        if !decoder.contains_message(message) {
+            bail!(
+                "Protobuf message `{}` is not declared in artifact {}",
+                message,
+                artifact
+            );
+        }
+        Ok(Self::Protobuf {
+            decoder,
+            message: message.to_owned(),
+        })
+    }
+
+    #[cfg(not(feature = "protobuf-decoding"))]
+    fn protobuf(
+        _spec: &ArtifactProtobufSpec,
+        artifact: &str,
+        message: &str,
+    ) -> Result<Self, Error> {
+        bail!(
+            "Cannot decode values with Protobuf message `{}` from artifact {}: \
+             `exonum-cli` is built without the `protobuf-decoding` feature",
+            message,
+            artifact
+        )
+    }
+
+    fn builtin(index_name: &str) -> Option<Self> {
+        let decode = match index_name {
+            "core.blocks" | "core.block_skip" => decode_binary_value::<Block>,
+            "core.transactions" => decode_transaction,
+            "core.consensus_config" => decode_binary_value::<ConsensusConfig>,
+            _ => return None,
+        };
+        Some(Self::Builtin(decode))
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Value, Error> {
+        match self {
+            Self::Raw => Ok(Value::String(hex::encode(bytes))),
+            Self::Builtin(decode) => decode(bytes),
+            #[cfg(feature = "protobuf-decoding")]
+            Self::Protobuf { decoder, message } => decoder.decode(message, bytes),
+        }
+    }
+}
+
+fn decode_binary_value<T: BinaryValue + serde::Serialize>(bytes: &[u8]) -> Result<Value, Error> {
+    let value = T::from_bytes(bytes.into())?;
+    serde_json::to_value(value).map_err(Error::from)
+}
+
+fn decode_transaction(bytes: &[u8]) -> Result<Value, Error> {
+    let transaction = Verified::<AnyTx>::from_bytes(bytes.into())?;
+    Ok(json!({
+        "author": transaction.author(),
+        "payload": transaction.payload(),
+    }))
+}
+
+fn index_address(name: &str, group_key: Option<&str>) -> Result<IndexAddress, Error> {
+    let address = IndexAddress::from_root(name);
+    Ok(match group_key {
+        Some(key) => address.append_key(&parse_bytes(key)?[..]),
+        None => address,
+    })
+}
+
+fn parse_position(key: &str) -> Result<u64, Error> {
+    key.parse()
+        .map_err(|e| format_err!("Invalid list index `{}`: {}", key, e))
+}
+
+fn parse_bytes(key: &str) -> Result<Vec<u8>, Error> {
+    hex::decode(key).map_err(|e| format_err!("Invalid hex-encoded key `{}`: {}", key, e))
+}
+
+fn parse_hash(key: &str) -> Result<Hash, Error> {
+    Hash::from_slice(&parse_bytes(key)?).ok_or_else(|| format_err!("Invalid hash `{}`", key))
+}
+
+fn write_json(out: &mut dyn Write, value: &impl serde::Serialize) -> Result<(), Error> {
+    serde_json::to_writer(&mut *out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+fn write_entry(
+    out: &mut dyn Write,
+    decoder: &ValueDecoder,
+    key: Value,
+    value: Option<&[u8]>,
+) -> Result<(), Error> {
+    let value = value.map(|bytes| decoder.decode(bytes)).transpose()?;
+    write_json(out, &json!({ "key": key, "value": value }))
+}
+
+fn index_type(snapshot: &dyn Snapshot, address: &IndexAddress) -> Result<IndexType, Error> {
+    snapshot
+        .index_type(address.clone())
+        .ok_or_else(|| format_err!("Index `{}` does not exist", address.name()))
+}
+
+fn get_entry(
+    snapshot: &dyn Snapshot,
+    address: IndexAddress,
+    key: Option<&str>,
+    decoder: &ValueDecoder,
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    let index_type = index_type(snapshot, &address)?;
+    let key = match (index_type, key) {
+        (IndexType::Entry, None) | (IndexType::ProofEntry, None) => "",
+        (IndexType::Entry, Some(_)) | (IndexType::ProofEntry, Some(_)) => {
+            bail!("Entries do not have keys")
+        }
+        (_, Some(key)) => key,
+        (_, None) => bail!("Key is required for indexes of type {:?}", index_type),
+    };
+
+    match index_type {
+        IndexType::Entry => {
+            let value = snapshot.get_entry::<_, Vec<u8>>(address).get();
+            write_entry(out, decoder, Value::Null, value.as_deref())
+        }
+        IndexType::ProofEntry => {
+            let value = snapshot.get_proof_entry::<_, Vec<u8>>(address).get();
+            write_entry(out, decoder, Value::Null, value.as_deref())
+        }
+        IndexType::List => {
+            let position = parse_position(key)?;
+            let value = snapshot.get_list::<_, Vec<u8>>(address).get(position);
+            write_entry(out, decoder, position.into(), value.as_deref())
+        }
+        IndexType::ProofList => {
+            let position = parse_position(key)?;
+            let value = snapshot.get_proof_list::<_, Vec<u8>>(address).get(position);
+            write_entry(out, decoder, position.into(), value.as_deref())
+        }
+        IndexType::SparseList => {
+            let position = parse_position(key)?;
+            let value = snapshot
+                .get_sparse_list::<_, Vec<u8>>(address)
+                .get(position);
+            write_entry(out, decoder, position.into(), value.as_deref())
+        }
+        IndexType::Map => {
+            let bytes = parse_bytes(key)?;
+            let value = snapshot.get_map::<_, Vec<u8>, Vec<u8>>(address).get(&bytes);
+            write_entry(out, decoder, key.into(), value.as_deref())
+        }
+        IndexType::ProofMap => {
+            let bytes = parse_bytes(key)?;
+            let value = snapshot
+                .get_proof_map::<_, Vec<u8>, Vec<u8>>(address)
+                .get(&bytes);
+            write_entry(out, decoder, key.into(), value.as_deref())
+        }
+        IndexType::KeySet => {
+            let bytes = parse_bytes(key)?;
+            let set = snapshot.get_key_set::<_, Vec<u8>>(address);
+            if set.contains(&bytes) {
+                write_json(out, &json!({ "key": key }))?;
+            }
+            Ok(())
+        }
+        IndexType::ValueSet => {
+            let hash = parse_hash(key)?;
+            let value = snapshot
+                .get_value_set::<_, Vec<u8>>(address)
+                .iter_from(&hash)
+                .next()
+                .filter(|(entry_hash, _)| *entry_hash == hash)
+                .map(|(_, value)| value);
+            write_entry(out, decoder, key.into(), value.as_deref())
+        }
+        IndexType::Tombstone | IndexType::Unknown => {
+            bail!("Cannot read index of type {:?}", index_type)
+        }
+    }
+}
+
+fn iter_entries(
+    snapshot: &dyn Snapshot,
+    address: IndexAddress,
+    range: &KeyRange<'_>,
+    decoder: &ValueDecoder,
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    let index_type = index_type(snapshot, &address)?;
+    match index_type {
+        IndexType::Entry | IndexType::ProofEntry => {
+            get_entry(snapshot, address, None, decoder, out)
+        }
+
+        IndexType::List | IndexType::ProofList | IndexType::SparseList => {
+            let from = range.from.map(parse_position).transpose()?.unwrap_or(0);
+            let to = range
+                .to
+                .map(parse_position)
+                .transpose()?
+                .unwrap_or(u64::max_value());
+
+            let entries: Vec<(u64, Vec<u8>)> = match index_type {
+                IndexType::List => {
+                    let list = snapshot.get_list::<_, Vec<u8>>(address);
+                    let values = list.iter_from(from);
+                    (from..to).zip(values).take(range.limit).collect()
+                }
+                IndexType::ProofList => {
+                    let list = snapshot.get_proof_list::<_, Vec<u8>>(address);
+                    let values = list.iter_from(from);
+                    (from..to).zip(values).take(range.limit).collect()
+                }
+                _ => snapshot
+                    .get_sparse_list::<_, Vec<u8>>(address)
+                    .iter_from(from)
+                    .take_while(|(position, _)| *position < to)
+                    .take(range.limit)
+                    .collect(),
+            };
+            for (position, value) in entries {
+                write_entry(out, decoder, position.into(), Some(&value))?;
+            }
+            Ok(())
+        }
+
+        IndexType::Map | IndexType::ProofMap | IndexType::KeySet => {
+            let from = range.from.map(parse_bytes).transpose()?.unwrap_or_default();
+            let to = range.to.map(parse_bytes).transpose()?;
+            let is_before_end = |key: &Vec<u8>| to.as_ref().map_or(true, |to| key < to);
+
+            let entries: Vec<(Vec<u8>, Option<Vec<u8>>)> = match index_type {
+                IndexType::Map => snapshot
+                    .get_map::<_, Vec<u8>, Vec<u8>>(address)
+                    .iter_from(&from)
+                    .take_while(|(key, _)| is_before_end(key))
+                    .take(range.limit)
+                    .map(|(key, value)| (key, Some(value)))
+                    .collect(),
+                IndexType::ProofMap => snapshot
+                    .get_proof_map::<_, Vec<u8>, Vec<u8>>(address)
+                    .iter_from(&from)
+                    .take_while(|(key, _)| is_before_end(key))
+                    .take(range.limit)
+                    .map(|(key, value)| (key, Some(value)))
+                    .collect(),
+                _ => snapshot
+                    .get_key_set::<_, Vec<u8>>(address)
+                    .iter_from(&from)
+                    .take_while(|key| is_before_end(key))
+                    .take(range.limit)
+                    .map(|key| (key, None))
+                    .collect(),
+            };
+            for (key, value) in entries {
+                let key = Value::String(hex::encode(key));
+                if let Some(value) = value {
+                    write_entry(out, decoder, key, Some(&value))?;
+                } else {
+                    write_json(out, &json!({ "key": key }))?;
+                }
+            }
+            Ok(())
+        }
+
+        IndexType::ValueSet => {
+            let from = range.from.map(parse_hash).transpose()?.unwrap_or_default();
+            let to = range.to.map(parse_hash).transpose()?;
+            let entries: Vec<_> = snapshot
+                .get_value_set::<_, Vec<u8>>(address)
+                .iter_from(&from)
+                .take_while(|(hash, _)| to.map_or(true, |to| *hash < to))
+                .take(range.limit)
+                .collect();
+            for (hash, value) in entries {
+                write_entry(out, decoder, hex::encode(hash).into(), Some(&value))?;
+            }
+            Ok(())
+        }
+
+        IndexType::Tombstone | IndexType::Unknown => {
+            bail!("Cannot read index of type {:?}", index_type)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use exonum::{
+        blockchain::{config::GenesisConfigBuilder, Blockchain, BlockchainBuilder},
+        merkledb::{Database, TemporaryDB},
+    };
+
+    fn query_output(snapshot: &dyn Snapshot, query: InspectQuery) -> Vec<Value> {
+        let mut out = vec![];
+        query.execute(snapshot, &HashMap::new(), &mut out).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn inspecting_core_indexes() {
+        let blockchain = Blockchain::build_for_tests();
+        let (consensus_config, _) = ConsensusConfig::for_tests(1);
+        let genesis_config = GenesisConfigBuilder::with_consensus_config(consensus_config).build();
+        let blockchain = BlockchainBuilder::new(blockchain)
+            .with_genesis_config(genesis_config)
+            .build();
+        let snapshot = blockchain.snapshot();
+
+        let namespaces = query_output(snapshot.as_ref(), InspectQuery::Namespaces);
+        assert!(namespaces.contains(&json!("core")));
+
+        let indexes = query_output(
+            snapshot.as_ref(),
+            InspectQuery::Indexes {
+                prefix: "core.".to_owned(),
+            },
+        );
+        assert!(indexes
+            .iter()
+            .any(|index| index["name"] == "core.consensus_config"));
+
+        let config = query_output(
+            snapshot.as_ref(),
+            InspectQuery::Get {
+                index: "core.consensus_config".to_owned(),
+                key: None,
+                group_key: None,
+                message: None,
+            },
+        );
+        assert_eq!(config.len(), 1);
+        assert!(config[0]["value"]["validator_keys"].is_array());
+
+        let hashes = query_output(
+            snapshot.as_ref(),
+            InspectQuery::Iter {
+                index: "core.block_hashes_by_height".to_owned(),
+                group_key: None,
+                message: None,
+                from: None,
+                to: None,
+                limit: 10,
+            },
+        );
+        assert_eq!(hashes.len(), 1);
+        assert_eq!(hashes[0]["key"], 0);
+        let block_hash = hashes[0]["value"].as_str().unwrap().to_owned();
+
+        let blocks = query_output(
+            snapshot.as_ref(),
+            InspectQuery::Get {
+                index: "core.blocks".to_owned(),
+                key: Some(block_hash),
+                group_key: None,
+                message: None,
+            },
+        );
+        assert_eq!(blocks[0]["value"]["height"], 0);
+    }
+
+    #[test]
+    fn inspecting_unknown_index() {
+        let db = TemporaryDB::new();
+        let snapshot = db.snapshot();
+        let query = InspectQuery::Get {
+            index: "core.unknown".to_owned(),
+            key: None,
+            group_key: None,
+            message: None,
+        };
+        let err = query
+            .execute(snapshot.as_ref(), &HashMap::new(), &mut vec![])
+            .unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+}
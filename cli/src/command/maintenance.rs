@@ -14,17 +14,29 @@
 
 //! Standard Exonum CLI command used to perform different maintenance actions.
 
-use anyhow::Error;
-use exonum::merkledb::{migration::rollback_migration, Database, RocksDB};
-use exonum::runtime::remove_local_migration_result;
+use anyhow::{format_err, Error};
+use exonum::blockchain::{
+    config::GenesisConfig, export_genesis, replay_blocks, ApiSender, Blockchain,
+};
+use exonum::crypto::KeyPair;
+use exonum::helpers::Height;
+use exonum::merkledb::{migration::rollback_migration, Database, RocksDB, TemporaryDB};
+use exonum::runtime::{remove_local_migration_result, ArtifactId, RuntimeInstance};
 use exonum_node::helpers::clear_consensus_messages_cache;
+use exonum_rust_runtime::ArtifactProtobufSpec;
 use serde_derive::{Deserialize, Serialize};
 use structopt::StructOpt;
 
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
-    command::{ExonumCommand, StandardResult},
+    command::{inspect::InspectQuery, ExonumCommand, StandardResult},
     config::NodeConfig,
     io::load_config_file,
 };
@@ -44,6 +56,44 @@ pub struct Maintenance {
     /// Action to be performed.
     #[structopt(subcommand)]
     pub action: MaintenanceAction,
+
+    /// Protobuf specifications of the artifacts used to decode service data.
+    #[structopt(skip)]
+    #[serde(skip)]
+    protobuf_specs: HashMap<ArtifactId, ArtifactProtobufSpec>,
+
+    /// Genesis config of the blockchain used to re-execute blocks.
+    #[structopt(skip)]
+    #[serde(skip)]
+    genesis_config: Option<GenesisConfig>,
+
+    /// Runtimes used to re-execute blocks.
+    #[structopt(skip)]
+    #[serde(skip)]
+    runtimes: Vec<RuntimeInstance>,
+}
+
+impl Maintenance {
+    /// Sets Protobuf specifications of the artifacts, which are used to decode service data
+    /// in the [`inspect`] action.
+    ///
+    /// [`inspect`]: enum.MaintenanceAction.html#variant.Inspect
+    pub fn set_protobuf_specs(&mut self, specs: HashMap<ArtifactId, ArtifactProtobufSpec>) {
+        self.protobuf_specs = specs;
+    }
+
+    /// Sets the genesis config and the runtimes of the blockchain, which are used to re-execute
+    /// blocks in the [`replay`] action.
+    ///
+    /// [`replay`]: enum.MaintenanceAction.html#variant.Replay
+    pub fn set_blockchain_components(
+        &mut self,
+        genesis_config: GenesisConfig,
+        runtimes: Vec<RuntimeInstance>,
+    ) {
+        self.genesis_config = Some(genesis_config);
+        self.runtimes = runtimes;
+    }
 }
 
 /// Available maintenance actions.
@@ -60,6 +110,41 @@ pub enum MaintenanceAction {
         /// Name of the service for migration restart, e.g. "explorer" or "my-service".
         service_name: String,
     },
+
+    /// Inspect database contents without modifying the database.
+    #[structopt(name = "inspect")]
+    Inspect {
+        /// Query to perform.
+        #[structopt(subcommand)]
+        query: InspectQuery,
+    },
+
+    /// Export the blockchain state into a genesis config and a genesis state, which can be
+    /// used to start a new blockchain with the same services and their data.
+    #[structopt(name = "export-genesis")]
+    ExportGenesis {
+        /// Height to export the state at. Must be equal to the latest committed height.
+        #[structopt(long)]
+        height: u64,
+        /// Path to the JSON file to write the genesis config to.
+        #[structopt(long)]
+        genesis_config: PathBuf,
+        /// Path to the JSON file to write the genesis state to.
+        #[structopt(long)]
+        genesis_state: PathBuf,
+    },
+
+    /// Re-execute all blocks of the blockchain starting from the genesis block and check
+    /// that the re-executed blocks coincide with the stored ones. The replay stops
+    /// at the first mismatch.
+    #[structopt(name = "replay")]
+    Replay {
+        /// Path to a database directory to store re-executed blocks to. If the directory
+        /// contains blocks from a previous replay, the replay is continued from the latest
+        /// of these blocks. If not specified, re-executed blocks are stored in memory.
+        #[structopt(long)]
+        target_db_path: Option<PathBuf>,
+    },
 }
 
 impl MaintenanceAction {
@@ -92,6 +177,71 @@ impl MaintenanceAction {
 
         Ok(())
     }
+
+    fn inspect(
+        node_config: &Path,
+        db_path: &Path,
+        protobuf_specs: &HashMap<ArtifactId, ArtifactProtobufSpec>,
+        query: &InspectQuery,
+    ) -> Result<(), Error> {
+        let node_config: NodeConfig = load_config_file(node_config)?;
+        let snapshot = RocksDB::open_snapshot(db_path, &node_config.private_config.database)?;
+        let stdout = io::stdout();
+        query.execute(snapshot.as_ref(), protobuf_specs, &mut stdout.lock())
+    }
+
+    fn export_genesis(
+        node_config: &Path,
+        db_path: &Path,
+        height: Height,
+        genesis_config_path: &Path,
+        genesis_state_path: &Path,
+    ) -> Result<(), Error> {
+        let node_config: NodeConfig = load_config_file(node_config)?;
+        let snapshot = RocksDB::open_snapshot(db_path, &node_config.private_config.database)?;
+        let (genesis_config, genesis_state) = export_genesis(snapshot.as_ref(), height)?;
+
+        for path in &[genesis_config_path, genesis_state_path] {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        serde_json::to_writer_pretty(File::create(genesis_config_path)?, &genesis_config)?;
+        serde_json::to_writer(File::create(genesis_state_path)?, &genesis_state)?;
+        Ok(())
+    }
+
+    fn replay(
+        node_config: &Path,
+        db_path: &Path,
+        target_db_path: Option<&Path>,
+        genesis_config: Option<GenesisConfig>,
+        runtimes: Vec<RuntimeInstance>,
+    ) -> Result<(), Error> {
+        let genesis_config = genesis_config
+            .ok_or_else(|| format_err!("Genesis config for the blockchain replay is not set"))?;
+        let node_config: NodeConfig = load_config_file(node_config)?;
+        let db_options = &node_config.private_config.database;
+        let snapshot = RocksDB::open_snapshot(db_path, db_options)?;
+        let target_db: Arc<dyn Database> = if let Some(path) = target_db_path {
+            Arc::new(RocksDB::open(path, db_options)?)
+        } else {
+            Arc::new(TemporaryDB::new())
+        };
+
+        // The keypair is random, so that services do not act on behalf of a validator
+        // during the replay.
+        let blockchain = Blockchain::new(target_db, KeyPair::random(), ApiSender::closed());
+        let mut builder = blockchain.into_mut(genesis_config);
+        for runtime in runtimes {
+            builder = builder.with_runtime(runtime);
+        }
+        let mut blockchain = builder.build();
+
+        let height = replay_blocks(snapshot.as_ref(), &mut blockchain)?;
+        log::info!("Successfully replayed blocks up to height {}", height);
+        Ok(())
+    }
 }
 
 impl ExonumCommand for Maintenance {
@@ -107,6 +257,30 @@ impl ExonumCommand for Maintenance {
                     service_name,
                 )?
             }
+            MaintenanceAction::Inspect { ref query } => MaintenanceAction::inspect(
+                &self.node_config,
+                &self.db_path,
+                &self.protobuf_specs,
+                query,
+            )?,
+            MaintenanceAction::ExportGenesis {
+                height,
+                ref genesis_config,
+                ref genesis_state,
+            } => MaintenanceAction::export_genesis(
+                &self.node_config,
+                &self.db_path,
+                Height(height),
+                genesis_config,
+                genesis_state,
+            )?,
+            MaintenanceAction::Replay { ref target_db_path } => MaintenanceAction::replay(
+                &self.node_config,
+                &self.db_path,
+                target_db_path.as_deref(),
+                self.genesis_config,
+                self.runtimes,
+            )?,
         }
 
         Ok(StandardResult::Maintenance {
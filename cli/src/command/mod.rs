@@ -21,7 +21,9 @@ pub use self::{
         PUBLIC_CONFIG_FILE_NAME,
     },
     generate_template::GenerateTemplate,
+    inspect::InspectQuery,
     maintenance::{Maintenance, MaintenanceAction},
+    rotate_key::{RotateKey, NEXT_MASTER_KEY_FILE_NAME},
     run::{NodeRunConfig, Run},
     run_dev::RunDev,
 };
@@ -29,11 +31,14 @@ pub use self::{
 mod finalize;
 mod generate_config;
 mod generate_template;
+mod inspect;
 mod maintenance;
+mod rotate_key;
 mod run;
 mod run_dev;
 
 use anyhow::Error;
+use exonum::crypto::PublicKey;
 use serde_derive::{Deserialize, Serialize};
 use structopt::StructOpt;
 
@@ -73,6 +78,11 @@ pub enum Command {
     /// Perform different maintenance actions.
     #[structopt(name = "maintenance")]
     Maintenance(Maintenance),
+
+    /// Generate a new consensus key of the node and stage it for rotation,
+    /// or complete the staged rotation.
+    #[structopt(name = "rotate-key")]
+    RotateKey(RotateKey),
 }
 
 impl Command {
@@ -91,6 +101,7 @@ impl ExonumCommand for Command {
             Self::Run(command) => command.execute(),
             Self::RunDev(command) => command.execute(),
             Self::Maintenance(command) => command.execute(),
+            Self::RotateKey(command) => command.execute(),
         }
     }
 }
@@ -133,4 +144,24 @@ pub enum StandardResult {
         /// Performed action.
         performed_action: MaintenanceAction,
     },
+
+    /// `rotate-key` command output.
+    RotateKey {
+        /// Path to a node configuration file.
+        node_config_path: PathBuf,
+        /// Path to a master key file with the staged consensus key.
+        next_master_key_path: PathBuf,
+        /// Staged consensus public key of the node.
+        consensus_public_key: PublicKey,
+    },
+
+    /// `rotate-key --complete` command output.
+    CompleteKeyRotation {
+        /// Path to a node configuration file.
+        node_config_path: PathBuf,
+        /// Path to a master key file with the new consensus key.
+        consensus_master_key_path: PathBuf,
+        /// New consensus public key of the node.
+        consensus_public_key: PublicKey,
+    },
 }
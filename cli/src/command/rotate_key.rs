@@ -0,0 +1,168 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Standard Exonum CLI command used to generate and stage a new consensus key
+//! of the node.
+
+use anyhow::{bail, format_err, Error};
+use serde_derive::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+use std::path::PathBuf;
+
+use crate::{
+    command::{
+        generate_config::create_keys_and_files,
+        run::{read_node_keys, read_secret_keys, resolve_key_path},
+        ExonumCommand, StandardResult,
+    },
+    config::NodeConfig,
+    io::{load_config_file, save_config_file},
+    password::{PassInputMethod, PassphraseUsage},
+};
+
+/// Name for a encrypted file containing the staged master key of the node.
+pub const NEXT_MASTER_KEY_FILE_NAME: &str = "next_master.key.toml";
+
+/// Generate a new consensus key of the node and stage it in the node configuration,
+/// or complete a staged rotation.
+///
+/// A rotation is performed in the following steps:
+///
+/// 1. The command generates a new master key, which is encrypted with the same passphrase
+///    as the current master key, and stages the consensus key derived from it. Only
+///    the consensus key is rotated; the service key is still derived from the current
+///    master key. The node should be restarted to load the staged key.
+/// 2. The new key is included into the consensus config, e.g., with the key rotation change
+///    of the supervisor service. The node switches to signing consensus messages
+///    with the new key once the change is applied.
+/// 3. The command is invoked with the `--complete` flag, which makes the staged key
+///    the main consensus key of the node. After the node is restarted, the network
+///    identity of the node changes to the new key as well.
+///
+/// Peers of the node accept connections only from the keys in their connect lists.
+/// Before the node is restarted after the last step, the operators of other nodes
+/// should add the new key of the node to their connect lists, e.g., with a `POST` request
+/// to the `/api/system/v1/peers` endpoint of the private API with the node address
+/// and the new key, or by editing the `connect_list` of the node configuration files
+/// and restarting the nodes.
+#[derive(StructOpt, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RotateKey {
+    /// Path to a node configuration file.
+    #[structopt(long, short = "c")]
+    pub node_config: PathBuf,
+    /// Passphrase entry method for master key.
+    ///
+    /// Possible values are: `stdin`, `env{:ENV_VAR_NAME}`, `pass:PASSWORD`.
+    /// Default Value is `stdin`.
+    /// If `ENV_VAR_NAME` is not specified `$EXONUM_MASTER_PASS` is used
+    /// by default.
+    #[structopt(long)]
+    pub master_key_pass: Option<PassInputMethod>,
+    /// Path to the new master key file. If empty, file will be placed
+    /// next to the current master key file.
+    #[structopt(long)]
+    pub next_master_key_path: Option<PathBuf>,
+    /// Complete the staged rotation, making the staged consensus key the main one.
+    ///
+    /// The rotation should be completed only after the new key is included
+    /// into the consensus config.
+    #[structopt(long, conflicts_with = "next-master-key-path")]
+    pub complete: bool,
+}
+
+impl RotateKey {
+    fn stage(
+        self,
+        mut config: NodeConfig,
+        master_passphrase: &[u8],
+    ) -> Result<StandardResult, Error> {
+        let config_path = &self.node_config;
+        if let Some(path) = &config.private_config.next_master_key_path {
+            bail!(
+                "Consensus key rotation is already staged with the master key file {}",
+                path.to_string_lossy()
+            );
+        }
+
+        let next_master_key_path = self.next_master_key_path.unwrap_or_else(|| {
+            config
+                .private_config
+                .master_key_path
+                .with_file_name(NEXT_MASTER_KEY_FILE_NAME)
+        });
+        let next_keys = create_keys_and_files(
+            resolve_key_path(config_path, &next_master_key_path),
+            master_passphrase,
+        )?;
+
+        config.private_config.next_master_key_path = Some(next_master_key_path.clone());
+        save_config_file(&config, config_path)?;
+
+        Ok(StandardResult::RotateKey {
+            node_config_path: self.node_config,
+            next_master_key_path,
+            consensus_public_key: next_keys.consensus_pk(),
+        })
+    }
+
+    fn complete(
+        self,
+        mut config: NodeConfig,
+        master_passphrase: &[u8],
+    ) -> Result<StandardResult, Error> {
+        let config_path = &self.node_config;
+        let private_config = &mut config.private_config;
+        let consensus_master_key_path = private_config
+            .next_master_key_path
+            .take()
+            .ok_or_else(|| format_err!("Consensus key rotation is not staged"))?;
+        let next_keys =
+            read_secret_keys(config_path, &consensus_master_key_path, master_passphrase)?;
+
+        private_config.consensus_public_key = next_keys.consensus_pk();
+        private_config.consensus_master_key_path = Some(consensus_master_key_path.clone());
+        save_config_file(&config, config_path)?;
+
+        Ok(StandardResult::CompleteKeyRotation {
+            node_config_path: self.node_config,
+            consensus_master_key_path,
+            consensus_public_key: next_keys.consensus_pk(),
+        })
+    }
+}
+
+impl ExonumCommand for RotateKey {
+    fn execute(mut self) -> Result<StandardResult, Error> {
+        let config: NodeConfig = load_config_file(&self.node_config)?;
+        let master_passphrase = self
+            .master_key_pass
+            .take()
+            .unwrap_or_default()
+            .get_passphrase(PassphraseUsage::Using)?;
+        // Check that the passphrase matches the current master key.
+        read_node_keys(
+            &self.node_config,
+            &config.private_config,
+            master_passphrase.as_bytes(),
+        )?;
+
+        if self.complete {
+            self.complete(config, master_passphrase.as_bytes())
+        } else {
+            self.stage(config, master_passphrase.as_bytes())
+        }
+    }
+}
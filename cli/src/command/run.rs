@@ -15,19 +15,25 @@
 //! Standard Exonum CLI command used to run the node using prepared node
 //! configuration file.
 
-use anyhow::Error;
-use exonum::keys::{read_keys_from_file, Keys};
+use anyhow::{Context, Error};
+use exonum::{
+    blockchain::{config::GenesisConfig, GenesisState},
+    keys::{read_keys_from_file, Keys},
+};
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 use structopt::StructOpt;
 
 use std::{
+    fs::File,
+    io::BufReader,
     net::SocketAddr,
     path::{Path, PathBuf},
 };
 
 use crate::{
     command::{ExonumCommand, StandardResult},
-    config::NodeConfig,
+    config::{NodeConfig, NodePrivateConfig},
     io::load_config_file,
     password::{PassInputMethod, PassphraseUsage},
 };
@@ -40,10 +46,17 @@ pub struct NodeRunConfig {
     pub node_config: NodeConfig,
     /// Node keys.
     pub node_keys: Keys,
+    /// Staged node keys used for consensus key rotation.
+    pub next_node_keys: Option<Keys>,
     /// Path to a directory containing database files, provided by user.
     pub db_path: PathBuf,
     /// User-provided path to the node configuration file.
     pub node_config_path: PathBuf,
+    /// Genesis config exported from another blockchain. If specified, it replaces
+    /// the genesis config assembled from the services of the node.
+    pub genesis_config: Option<GenesisConfig>,
+    /// Genesis state exported from another blockchain.
+    pub genesis_state: Option<GenesisState>,
 }
 
 /// Run the node with provided node config.
@@ -74,6 +87,19 @@ pub struct Run {
     /// by default.
     #[structopt(long)]
     pub master_key_pass: Option<PassInputMethod>,
+    /// Path to the genesis config produced by the `maintenance export-genesis` command.
+    ///
+    /// If specified, the config is used instead of the genesis config assembled from
+    /// the services of the node; the consensus config is still taken from the node
+    /// configuration file. The option has effect only if the database does not contain
+    /// the genesis block yet.
+    #[structopt(long)]
+    pub genesis_config: Option<PathBuf>,
+    /// Path to the genesis state produced by the `maintenance export-genesis` command.
+    ///
+    /// The state can only be used together with the exported genesis config.
+    #[structopt(long, requires = "genesis-config")]
+    pub genesis_state: Option<PathBuf>,
 }
 
 impl ExonumCommand for Run {
@@ -96,36 +122,94 @@ impl ExonumCommand for Run {
             .master_key_pass
             .unwrap_or_default()
             .get_passphrase(PassphraseUsage::Using)?;
-        let node_keys = read_secret_keys(
+        let node_keys = read_node_keys(
             config_path,
-            &config.private_config.master_key_path,
+            &config.private_config,
             master_passphrase.as_bytes(),
-        );
+        )?;
+        let next_node_keys = config
+            .private_config
+            .next_master_key_path
+            .as_ref()
+            .map(|path| -> Result<_, Error> {
+                let next_keys = read_secret_keys(config_path, path, master_passphrase.as_bytes())?;
+                // Only the consensus key is rotated.
+                Ok(Keys::from_keys(
+                    next_keys.consensus,
+                    node_keys.service.clone(),
+                ))
+            })
+            .transpose()?;
+
+        let genesis_config = self
+            .genesis_config
+            .as_deref()
+            .map(load_json_file)
+            .transpose()?;
+        let genesis_state = self
+            .genesis_state
+            .as_deref()
+            .map(load_json_file)
+            .transpose()?;
 
         let run_config = NodeRunConfig {
             node_config: config,
             node_keys,
+            next_node_keys,
             db_path: self.db_path,
             node_config_path: self.node_config,
+            genesis_config,
+            genesis_state,
         };
 
         Ok(StandardResult::Run(Box::new(run_config)))
     }
 }
 
+/// Loads a JSON-encoded file produced by the `maintenance export-genesis` command.
+fn load_json_file<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("loading {}", path.display()))
+}
+
 /// Reads validator keys from the encrypted file.
-fn read_secret_keys(
+pub(super) fn read_secret_keys(
     config_file_path: impl AsRef<Path>,
     master_key_path: &Path,
     master_key_passphrase: &[u8],
-) -> Keys {
-    let config_folder = config_file_path.as_ref().parent().unwrap();
-    let master_key_path = if master_key_path.is_absolute() {
-        master_key_path.to_owned()
+) -> Result<Keys, Error> {
+    let master_key_path = resolve_key_path(config_file_path, master_key_path);
+    read_keys_from_file(&master_key_path, master_key_passphrase)
+        .with_context(|| format!("reading master key from {}", master_key_path.display()))
+}
+
+/// Reads the main keys of the node, taking into account completed consensus key rotations.
+pub(super) fn read_node_keys(
+    config_file_path: impl AsRef<Path>,
+    private_config: &NodePrivateConfig,
+    master_key_passphrase: &[u8],
+) -> Result<Keys, Error> {
+    let config_file_path = config_file_path.as_ref();
+    let keys = read_secret_keys(
+        config_file_path,
+        &private_config.master_key_path,
+        master_key_passphrase,
+    )?;
+    if let Some(path) = &private_config.consensus_master_key_path {
+        let consensus_keys = read_secret_keys(config_file_path, path, master_key_passphrase)?;
+        Ok(Keys::from_keys(consensus_keys.consensus, keys.service))
     } else {
-        config_folder.join(&master_key_path)
-    };
+        Ok(keys)
+    }
+}
 
-    read_keys_from_file(&master_key_path, master_key_passphrase)
-        .expect("Could not read master_key_path from file")
+/// Resolves the path to a master key file relative to the node configuration file.
+pub(super) fn resolve_key_path(config_file_path: impl AsRef<Path>, key_path: &Path) -> PathBuf {
+    if key_path.is_absolute() {
+        key_path.to_owned()
+    } else {
+        let config_folder = config_file_path.as_ref().parent().unwrap();
+        config_folder.join(key_path)
+    }
 }
@@ -118,6 +118,8 @@ impl ExonumCommand for RunDev {
             public_api_address: None,
             private_api_address: None,
             master_key_pass: Some(FromStr::from_str("pass:").unwrap()),
+            genesis_config: None,
+            genesis_state: None,
         };
         run.execute()
     }
@@ -78,6 +78,19 @@ pub struct NodePrivateConfig {
     pub connect_list: ConnectListConfig,
     /// Consensus public key of the node.
     pub consensus_public_key: PublicKey,
+    /// Path to the master key file the consensus key of the node is derived from.
+    ///
+    /// The path is set once a consensus key rotation is completed. If the path is not set,
+    /// the consensus key is derived from the master key at `master_key_path`. The service key
+    /// is always derived from the latter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consensus_master_key_path: Option<PathBuf>,
+    /// Path to the master key file with the staged consensus key of the node.
+    ///
+    /// The staged key is used to sign consensus messages once it is included
+    /// into the consensus configuration. See the `rotate-key` command for details.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_master_key_path: Option<PathBuf>,
 }
 
 /// Configuration for the `Node`.
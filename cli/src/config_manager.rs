@@ -107,6 +107,8 @@ mod tests {
                 thread_pool_size: None,
                 connect_list: ConnectListConfig::default(),
                 consensus_public_key: KeyPair::random().public_key(),
+                consensus_master_key_path: None,
+                next_master_key_path: None,
             },
             public_config: NodePublicConfig {
                 consensus: ConsensusConfig::default(),
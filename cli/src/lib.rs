@@ -140,24 +140,30 @@ pub use structopt;
 use exonum::{
     blockchain::config::{GenesisConfig, GenesisConfigBuilder},
     merkledb::RocksDB,
-    runtime::{RuntimeInstance, WellKnownRuntime},
+    runtime::{ArtifactId, RuntimeInstance, WellKnownRuntime},
 };
 use exonum_explorer_service::ExplorerFactory;
 use exonum_node::{Node, NodeBuilder as CoreNodeBuilder};
-use exonum_rust_runtime::{spec::Deploy, RustRuntimeBuilder};
+use exonum_rust_runtime::{spec::Deploy, ArtifactProtobufSpec, RustRuntimeBuilder, ServiceFactory};
 use exonum_supervisor::{Supervisor, SupervisorConfig};
 use exonum_system_api::SystemApiPlugin;
+use futures::channel::mpsc;
 use structopt::StructOpt;
 use tempfile::TempDir;
 
-use std::{env, ffi::OsString, iter, path::PathBuf};
+use std::{collections::HashMap, env, ffi::OsString, iter, path::PathBuf};
 
-use crate::command::{Command, ExonumCommand, NodeRunConfig, StandardResult};
+use crate::{
+    command::{Command, ExonumCommand, MaintenanceAction, StandardResult},
+    config::NodeConfig,
+};
 
 pub mod command;
 pub mod config;
 mod io;
 pub mod password;
+#[cfg(feature = "protobuf-decoding")]
+mod proto_json;
 
 mod config_manager;
 
@@ -250,21 +256,33 @@ impl NodeBuilder {
     /// - `Err(_)` if an error occurred during command execution
     #[doc(hidden)] // unstable
     pub fn execute_command(mut self) -> anyhow::Result<Option<Node>> {
-        let command = if let Some(args) = self.args {
+        let mut command = if let Some(args) = self.args.take() {
             Command::from_iter(args)
         } else {
             Command::from_args()
         };
+        if let Command::Maintenance(ref mut maintenance) = command {
+            maintenance.set_protobuf_specs(self.protobuf_specs());
+            if let MaintenanceAction::Replay { .. } = maintenance.action {
+                let node_config: NodeConfig = load_config_file(&maintenance.node_config)?;
+                let (genesis_config, runtimes) = self.into_blockchain_components(&node_config);
+                maintenance.set_blockchain_components(genesis_config, runtimes);
+                command.execute()?;
+                return Ok(None);
+            }
+        }
 
         if let StandardResult::Run(run_config) = command.execute()? {
-            // Deploy "default" services (supervisor and the explorer).
-            let supervisor = Self::supervisor_service(&run_config);
-            supervisor.deploy(&mut self.genesis_config, &mut self.rust_runtime);
-            Spec::new(ExplorerFactory)
-                .with_default_instance()
-                .deploy(&mut self.genesis_config, &mut self.rust_runtime);
-
-            let genesis_config = Self::genesis_config(&run_config, self.genesis_config);
+            self.deploy_default_services(&run_config.node_config);
+            let genesis_config = if let Some(mut genesis_config) = run_config.genesis_config {
+                // Artifacts and services are taken from the exported config, but the consensus
+                // config of the new blockchain is defined by the node configuration.
+                genesis_config.consensus_config =
+                    run_config.node_config.public_config.consensus.clone();
+                genesis_config
+            } else {
+                Self::genesis_config(&run_config.node_config, self.genesis_config)
+            };
             let db_options = &run_config.node_config.private_config.database;
             let database = RocksDB::open(run_config.db_path, db_options)?;
 
@@ -280,6 +298,12 @@ impl NodeBuilder {
                 .with_config_manager(config_manager)
                 .with_plugin(SystemApiPlugin)
                 .with_runtime_fn(|channel| rust_runtime.build(channel.endpoints_sender()));
+            if let Some(genesis_state) = run_config.genesis_state {
+                node_builder = node_builder.with_genesis_state(genesis_state);
+            }
+            if let Some(next_node_keys) = run_config.next_node_keys {
+                node_builder = node_builder.with_next_keys(next_node_keys);
+            }
             for runtime in self.external_runtimes {
                 node_builder = node_builder.with_runtime(runtime);
             }
@@ -300,21 +324,56 @@ impl NodeBuilder {
         }
     }
 
-    fn genesis_config(run_config: &NodeRunConfig, builder: GenesisConfigBuilder) -> GenesisConfig {
+    /// Returns Protobuf specifications of all artifacts known to the builder,
+    /// including the built-in ones.
+    fn protobuf_specs(&self) -> HashMap<ArtifactId, ArtifactProtobufSpec> {
+        let mut specs = self.rust_runtime.artifact_protobuf_specs();
+        specs.insert(
+            Supervisor.artifact_id(),
+            Supervisor.artifact_protobuf_spec(),
+        );
+        specs.insert(
+            ExplorerFactory.artifact_id(),
+            ExplorerFactory.artifact_protobuf_spec(),
+        );
+        specs
+    }
+
+    /// Converts this builder into the genesis config and the runtimes of the blockchain
+    /// without starting a node. Used to re-execute blocks in the maintenance `replay` action.
+    fn into_blockchain_components(
+        mut self,
+        node_config: &NodeConfig,
+    ) -> (GenesisConfig, Vec<RuntimeInstance>) {
+        self.deploy_default_services(node_config);
+        let genesis_config = Self::genesis_config(node_config, self.genesis_config);
+        // API endpoints are not served during the replay, so the receiver is dropped.
+        let rust_runtime = self.rust_runtime.build(mpsc::channel(1).0);
+        let runtimes = iter::once(rust_runtime.into())
+            .chain(self.external_runtimes)
+            .collect();
+        (genesis_config, runtimes)
+    }
+
+    /// Deploys "default" services (supervisor and the explorer).
+    fn deploy_default_services(&mut self, node_config: &NodeConfig) {
+        let supervisor = Self::supervisor_service(node_config);
+        supervisor.deploy(&mut self.genesis_config, &mut self.rust_runtime);
+        Spec::new(ExplorerFactory)
+            .with_default_instance()
+            .deploy(&mut self.genesis_config, &mut self.rust_runtime);
+    }
+
+    fn genesis_config(node_config: &NodeConfig, builder: GenesisConfigBuilder) -> GenesisConfig {
         // Add builtin services to genesis config.
         let mut config = builder.build();
         // Override consensus config.
-        config.consensus_config = run_config.node_config.public_config.consensus.clone();
+        config.consensus_config = node_config.public_config.consensus.clone();
         config
     }
 
-    fn supervisor_service(run_config: &NodeRunConfig) -> impl Deploy {
-        let mode = run_config
-            .node_config
-            .public_config
-            .general
-            .supervisor_mode
-            .clone();
+    fn supervisor_service(node_config: &NodeConfig) -> impl Deploy {
+        let mode = node_config.public_config.general.supervisor_mode.clone();
         Supervisor::builtin_instance(SupervisorConfig::new(mode))
     }
 }
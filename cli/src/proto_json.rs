@@ -0,0 +1,368 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of Protobuf messages into JSON based on the message descriptors
+//! rather than on the generated code.
+
+use anyhow::{bail, format_err, Error};
+use exonum_rust_runtime::{ArtifactProtobufSpec, ProtoSourceFile};
+use protobuf::{
+    descriptor::{
+        DescriptorProto, EnumDescriptorProto, FieldDescriptorProto,
+        FieldDescriptorProto_Label as Label, FieldDescriptorProto_Type as FieldType,
+    },
+    wire_format::WireType,
+    CodedInputStream,
+};
+use serde_json::{Map, Value};
+use tempfile::TempDir;
+
+use std::{collections::HashMap, fs, path::Path};
+
+/// Decoder of Protobuf messages declared in an artifact Protobuf specification.
+#[derive(Debug, Default)]
+pub(crate) struct MessageDecoder {
+    /// Message descriptors keyed by the fully qualified message name without the leading dot,
+    /// e.g., `exonum.examples.Wallet`.
+    messages: HashMap<String, DescriptorProto>,
+    /// Enum descriptors keyed by the fully qualified enum name without the leading dot.
+    enums: HashMap<String, EnumDescriptorProto>,
+}
+
+impl MessageDecoder {
+    /// Parses Protobuf sources from the artifact specification.
+    pub fn from_spec(spec: &ArtifactProtobufSpec) -> Result<Self, Error> {
+        // The Protobuf parser works with the file system, so we dump the sources
+        // into a temporary directory.
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        for file in spec.includes.iter().chain(&spec.sources) {
+            write_source(root, file)?;
+        }
+
+        let inputs: Vec<_> = spec
+            .sources
+            .iter()
+            .map(|file| root.join(&file.name))
+            .collect();
+        let inputs: Vec<&Path> = inputs.iter().map(AsRef::as_ref).collect();
+        let parsed = protobuf_codegen_pure::parse_and_typecheck(&[root], &inputs)?;
+
+        let mut this = Self::default();
+        for file in &parsed.file_descriptors {
+            let package = file.get_package();
+            for message in file.get_message_type() {
+                this.add_message(package, message);
+            }
+            for enum_type in file.get_enum_type() {
+                this.enums.insert(
+                    qualified_name(package, enum_type.get_name()),
+                    enum_type.clone(),
+                );
+            }
+        }
+        Ok(this)
+    }
+
+    fn add_message(&mut self, scope: &str, message: &DescriptorProto) {
+        let name = qualified_name(scope, message.get_name());
+        for nested in message.get_nested_type() {
+            self.add_message(&name, nested);
+        }
+        for enum_type in message.get_enum_type() {
+            self.enums.insert(
+                qualified_name(&name, enum_type.get_name()),
+                enum_type.clone(),
+            );
+        }
+        self.messages.insert(name, message.clone());
+    }
+
+    /// Checks if the decoder knows about a message with the specified fully qualified name.
+    pub fn contains_message(&self, message_name: &str) -> bool {
+        self.messages.contains_key(message_name)
+    }
+
+    /// Decodes a message with the specified fully qualified name into JSON.
+    pub fn decode(&self, message_name: &str, bytes: &[u8]) -> Result<Value, Error> {
+        let message = self
+            .messages
+            .get(message_name)
+            .ok_or_else(|| format_err!("Unknown Protobuf message `{}`", message_name))?;
+
+        let mut fields = Map::new();
+        let mut input = CodedInputStream::from_bytes(bytes);
+        while !input.eof()? {
+            let (number, wire_type) = input.read_tag_unpack()?;
+            let field = message
+                .get_field()
+                .iter()
+                .find(|field| field.get_number() as u32 == number);
+            let field = match field {
+                Some(field) => field,
+                None => {
+                    input.skip_field(wire_type)?;
+                    continue;
+                }
+            };
+
+            let is_packed = wire_type == WireType::WireTypeLengthDelimited
+                && field.get_label() == Label::LABEL_REPEATED
+                && is_packable(field.get_field_type());
+            let values = if is_packed {
+                let bytes = input.read_bytes()?;
+                let mut packed_input = CodedInputStream::from_bytes(&bytes);
+                let mut values = vec![];
+                while !packed_input.eof()? {
+                    values.push(self.decode_scalar(field, &mut packed_input)?);
+                }
+                values
+            } else {
+                vec![self.decode_field(field, &mut input)?]
+            };
+
+            if field.get_label() == Label::LABEL_REPEATED {
+                let entry = fields
+                    .entry(field.get_name())
+                    .or_insert_with(|| Value::Array(vec![]));
+                if let Value::Array(array) = entry {
+                    array.extend(values);
+                }
+            } else if let Some(value) = values.into_iter().last() {
+                fields.insert(field.get_name().to_owned(), value);
+            }
+        }
+
+        if message.get_options().get_map_entry() {
+            return Ok(Value::Object(fields));
+        }
+        for field in message.get_field() {
+            if self.is_map_field(field) {
+                let entries = fields.remove(field.get_name());
+                if let Some(Value::Array(entries)) = entries {
+                    fields.insert(field.get_name().to_owned(), map_from_entries(entries));
+                }
+            }
+        }
+        Ok(Value::Object(fields))
+    }
+
+    fn decode_field(
+        &self,
+        field: &FieldDescriptorProto,
+        input: &mut CodedInputStream<'_>,
+    ) -> Result<Value, Error> {
+        Ok(match field.get_field_type() {
+            FieldType::TYPE_STRING => Value::String(input.read_string()?),
+            FieldType::TYPE_BYTES => Value::String(hex::encode(input.read_bytes()?)),
+            FieldType::TYPE_MESSAGE => {
+                let bytes = input.read_bytes()?;
+                self.decode(type_name(field), &bytes)?
+            }
+            FieldType::TYPE_GROUP => bail!("Groups are not supported"),
+            _ => self.decode_scalar(field, input)?,
+        })
+    }
+
+    fn decode_scalar(
+        &self,
+        field: &FieldDescriptorProto,
+        input: &mut CodedInputStream<'_>,
+    ) -> Result<Value, Error> {
+        Ok(match field.get_field_type() {
+            FieldType::TYPE_DOUBLE => input.read_double()?.into(),
+            FieldType::TYPE_FLOAT => input.read_float()?.into(),
+            FieldType::TYPE_INT64 => input.read_int64()?.into(),
+            FieldType::TYPE_UINT64 => input.read_uint64()?.into(),
+            FieldType::TYPE_INT32 => input.read_int32()?.into(),
+            FieldType::TYPE_FIXED64 => input.read_fixed64()?.into(),
+            FieldType::TYPE_FIXED32 => input.read_fixed32()?.into(),
+            FieldType::TYPE_BOOL => input.read_bool()?.into(),
+            FieldType::TYPE_UINT32 => input.read_uint32()?.into(),
+            FieldType::TYPE_SFIXED32 => input.read_sfixed32()?.into(),
+            FieldType::TYPE_SFIXED64 => input.read_sfixed64()?.into(),
+            FieldType::TYPE_SINT32 => input.read_sint32()?.into(),
+            FieldType::TYPE_SINT64 => input.read_sint64()?.into(),
+            FieldType::TYPE_ENUM => {
+                let number = input.read_int32()?;
+                self.enum_value_name(type_name(field), number)
+                    .map_or_else(|| number.into(), Value::from)
+            }
+            FieldType::TYPE_STRING
+            | FieldType::TYPE_BYTES
+            | FieldType::TYPE_MESSAGE
+            | FieldType::TYPE_GROUP => unreachable!("Not a scalar field"),
+        })
+    }
+
+    fn enum_value_name(&self, enum_name: &str, number: i32) -> Option<&str> {
+        self.enums
+            .get(enum_name)?
+            .get_value()
+            .iter()
+            .find(|value| value.get_number() == number)
+            .map(|value| value.get_name())
+    }
+
+    fn is_map_field(&self, field: &FieldDescriptorProto) -> bool {
+        field.get_field_type() == FieldType::TYPE_MESSAGE
+            && self
+                .messages
+                .get(type_name(field))
+                .map_or(false, |message| message.get_options().get_map_entry())
+    }
+}
+
+fn write_source(root: &Path, file: &ProtoSourceFile) -> Result<(), Error> {
+    let path = root.join(&file.name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, &file.content)?;
+    Ok(())
+}
+
+fn qualified_name(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+/// Returns the referenced type name of a message or enum field without the leading dot.
+fn type_name(field: &FieldDescriptorProto) -> &str {
+    field.get_type_name().trim_start_matches('.')
+}
+
+fn is_packable(field_type: FieldType) -> bool {
+    match field_type {
+        FieldType::TYPE_STRING
+        | FieldType::TYPE_BYTES
+        | FieldType::TYPE_MESSAGE
+        | FieldType::TYPE_GROUP => false,
+        _ => true,
+    }
+}
+
+/// Converts decoded map entries into a JSON object. Non-string keys are converted
+/// to strings in the same way as in the Protobuf JSON mapping.
+fn map_from_entries(entries: Vec<Value>) -> Value {
+    let map = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let mut entry = match entry {
+                Value::Object(entry) => entry,
+                _ => return None,
+            };
+            let key = match entry.remove("key").unwrap_or(Value::Null) {
+                Value::String(key) => key,
+                key => key.to_string(),
+            };
+            let value = entry.remove("value").unwrap_or(Value::Null);
+            Some((key, value))
+        })
+        .collect();
+    Value::Object(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SERVICE_PROTO: &str = r#"
+        syntax = "proto3";
+
+        package exonum.examples;
+
+        import "exonum/crypto/types.proto";
+
+        message Wallet {
+            enum Status {
+                ACTIVE = 0;
+                FROZEN = 1;
+            }
+
+            exonum.crypto.PublicKey owner = 1;
+            string name = 2;
+            uint64 balance = 3;
+            repeated uint32 history = 4;
+            Status status = 5;
+            map<string, sint64> limits = 6;
+            bytes memo = 7;
+        }
+    "#;
+
+    const CRYPTO_PROTO: &str = r#"
+        syntax = "proto3";
+
+        package exonum.crypto;
+
+        message PublicKey { bytes data = 1; }
+    "#;
+
+    fn decoder() -> MessageDecoder {
+        let spec = ArtifactProtobufSpec::new(
+            vec![ProtoSourceFile::new("service.proto", SERVICE_PROTO)],
+            vec![ProtoSourceFile::new(
+                "exonum/crypto/types.proto",
+                CRYPTO_PROTO,
+            )],
+        );
+        MessageDecoder::from_spec(&spec).unwrap()
+    }
+
+    #[test]
+    fn decoding_message_with_various_fields() {
+        let decoder = decoder();
+        assert!(decoder.contains_message("exonum.examples.Wallet"));
+        assert!(decoder.contains_message("exonum.crypto.PublicKey"));
+
+        let bytes = [
+            // owner = { data = [1, 2] }
+            0x0a, 0x04, 0x0a, 0x02, 0x01, 0x02, //
+            // name = "Alice"
+            0x12, 0x05, b'A', b'l', b'i', b'c', b'e', //
+            // balance = 300
+            0x18, 0xac, 0x02, //
+            // history = [1, 2] (packed)
+            0x22, 0x02, 0x01, 0x02, //
+            // status = FROZEN
+            0x28, 0x01, //
+            // limits = { "day": -1 }
+            0x32, 0x07, 0x0a, 0x03, b'd', b'a', b'y', 0x10, 0x01, //
+            // Unknown field #10 = 5
+            0x50, 0x05,
+        ];
+        let value = decoder.decode("exonum.examples.Wallet", &bytes).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "owner": { "data": "0102" },
+                "name": "Alice",
+                "balance": 300,
+                "history": [1, 2],
+                "status": "FROZEN",
+                "limits": { "day": -1 },
+            })
+        );
+    }
+
+    #[test]
+    fn decoding_unknown_message() {
+        let err = decoder().decode("exonum.examples.Other", &[]).unwrap_err();
+        assert!(err.to_string().contains("Unknown Protobuf message"));
+    }
+}
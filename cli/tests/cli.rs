@@ -404,6 +404,132 @@ fn test_finalize_run_with_pass() {
     assert!(is_run_node_config(feedback.unwrap()));
 }
 
+#[test]
+fn test_rotate_key() {
+    let env = ConfigSpec::new_without_pass();
+    env.copy_node_config_to_output(0);
+    let node_config = env.output_node_config(0);
+    env.command("finalize")
+        .with_arg(env.output_private_config(0))
+        .with_arg(&node_config)
+        .with_arg("--public-configs")
+        .with_args(env.expected_pub_configs())
+        .run()
+        .unwrap();
+
+    let feedback = env
+        .command("rotate-key")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("--master-key-pass", "pass:")
+        .run()
+        .unwrap();
+    let consensus_key = if let StandardResult::RotateKey {
+        next_master_key_path,
+        consensus_public_key,
+        ..
+    } = feedback
+    {
+        assert_eq!(next_master_key_path, PathBuf::from("next_master.key.toml"));
+        consensus_public_key
+    } else {
+        panic!("Unexpected command output: {:?}", feedback);
+    };
+    assert!(env
+        .output_node_config_dir(0)
+        .join("next_master.key.toml")
+        .exists());
+
+    // Another rotation cannot be staged until the first one is completed.
+    let err = env
+        .command("rotate-key")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("--master-key-pass", "pass:")
+        .run()
+        .unwrap_err();
+    assert!(err.to_string().contains("already staged"));
+
+    let feedback = env
+        .command("run")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("-d", env.output_dir().join("foo"))
+        .with_named_arg("--master-key-pass", "pass:")
+        .run()
+        .unwrap();
+    let service_key = if let StandardResult::Run(run_config) = feedback {
+        let next_keys = run_config.next_node_keys.unwrap();
+        assert_eq!(next_keys.consensus_pk(), consensus_key);
+        assert_eq!(next_keys.service_pk(), run_config.node_keys.service_pk());
+        assert_ne!(
+            next_keys.consensus_pk(),
+            run_config.node_keys.consensus_pk()
+        );
+        run_config.node_keys.service_pk()
+    } else {
+        panic!("Unexpected command output: {:?}", feedback);
+    };
+
+    // A wrong passphrase is reported as an error.
+    let err = env
+        .command("rotate-key")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("--master-key-pass", "pass:wrong")
+        .with_arg("--complete")
+        .run()
+        .unwrap_err();
+    assert!(err.to_string().contains("reading master key"));
+
+    let feedback = env
+        .command("rotate-key")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("--master-key-pass", "pass:")
+        .with_arg("--complete")
+        .run()
+        .unwrap();
+    if let StandardResult::CompleteKeyRotation {
+        consensus_master_key_path,
+        consensus_public_key,
+        ..
+    } = feedback
+    {
+        assert_eq!(
+            consensus_master_key_path,
+            PathBuf::from("next_master.key.toml")
+        );
+        assert_eq!(consensus_public_key, consensus_key);
+    } else {
+        panic!("Unexpected command output: {:?}", feedback);
+    }
+
+    let err = env
+        .command("rotate-key")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("--master-key-pass", "pass:")
+        .with_arg("--complete")
+        .run()
+        .unwrap_err();
+    assert!(err.to_string().contains("not staged"));
+
+    // The new consensus key is the main one now; the service key is not changed.
+    let feedback = env
+        .command("run")
+        .with_named_arg("-c", &node_config)
+        .with_named_arg("-d", env.output_dir().join("foo"))
+        .with_named_arg("--master-key-pass", "pass:")
+        .run()
+        .unwrap();
+    if let StandardResult::Run(run_config) = feedback {
+        assert!(run_config.next_node_keys.is_none());
+        assert_eq!(run_config.node_keys.consensus_pk(), consensus_key);
+        assert_eq!(run_config.node_keys.service_pk(), service_key);
+        assert_eq!(
+            run_config.node_config.private_config.consensus_public_key,
+            consensus_key
+        );
+    } else {
+        panic!("Unexpected command output: {:?}", feedback);
+    }
+}
+
 #[test]
 #[should_panic(
     expected = "The number of validators (3) does not match the number of validators keys (4)."
@@ -536,6 +662,61 @@ fn test_restart_migration() {
         .unwrap();
 }
 
+#[test]
+fn test_inspect() {
+    let env = ConfigSpec::new_without_pass();
+    let db_path = env.output_dir().join("db0");
+
+    // The database is not created by the `inspect` action.
+    let res = env
+        .command("maintenance")
+        .with_named_arg("--node-config", &env.expected_node_config_file(0))
+        .with_named_arg("--db-path", &db_path)
+        .with_arg("inspect")
+        .with_arg("namespaces")
+        .run();
+    assert!(res.is_err());
+
+    env.command("maintenance")
+        .with_named_arg("--node-config", &env.expected_node_config_file(0))
+        .with_named_arg("--db-path", &db_path)
+        .with_arg("clear-cache")
+        .run()
+        .unwrap();
+    env.command("maintenance")
+        .with_named_arg("--node-config", &env.expected_node_config_file(0))
+        .with_named_arg("--db-path", &db_path)
+        .with_arg("inspect")
+        .with_named_arg("iter", "core.consensus_messages_cache")
+        .run()
+        .unwrap();
+}
+
+#[test]
+fn test_export_genesis() {
+    let env = ConfigSpec::new_without_pass();
+    let db_path = env.output_dir().join("db0");
+
+    env.command("maintenance")
+        .with_named_arg("--node-config", &env.expected_node_config_file(0))
+        .with_named_arg("--db-path", &db_path)
+        .with_arg("clear-cache")
+        .run()
+        .unwrap();
+    // The database does not contain the genesis block, so the export fails.
+    let res = env
+        .command("maintenance")
+        .with_named_arg("--node-config", &env.expected_node_config_file(0))
+        .with_named_arg("--db-path", &db_path)
+        .with_arg("export-genesis")
+        .with_named_arg("--height", "0")
+        .with_named_arg("--genesis-config", env.output_dir().join("genesis.json"))
+        .with_named_arg("--genesis-state", env.output_dir().join("state.json"))
+        .run();
+    let err = res.unwrap_err();
+    assert!(err.to_string().contains("uninitialized blockchain"));
+}
+
 #[test]
 fn run_node_with_simple_supervisor() {
     run_node_with_supervisor(&SupervisorMode::Simple).unwrap();
@@ -561,6 +742,8 @@ fn different_supervisor_modes_in_public_configs() -> anyhow::Result<()> {
         thread_pool_size: None,
         connect_list: Default::default(),
         consensus_public_key: KeyPair::random().public_key(),
+        consensus_master_key_path: None,
+        next_master_key_path: None,
     };
 
     let testnet_dir = tempfile::tempdir()?;
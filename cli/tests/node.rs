@@ -15,7 +15,9 @@
 //! Tests node creation with the help of the `run-dev` command.
 
 use exonum::{
+    blockchain::Schema,
     helpers::Height,
+    merkledb::{DbOptions, RocksDB},
     runtime::{versioning::Version, InstanceStatus, SUPERVISOR_INSTANCE_ID},
 };
 use exonum_derive::*;
@@ -161,6 +163,82 @@ async fn node_basic_workflow() -> anyhow::Result<()> {
     let answer: u64 = send_request(client.get(&url)).await?;
     assert_eq!(answer, 42);
 
+    shutdown_handle.shutdown().await?;
+    node_task.await??;
+
+    // Re-execute committed blocks and check that they coincide with the stored ones.
+    let node_config_path = dir.path().join("config").join("node.toml");
+    let db_path = dir.path().join("db");
+    let args = vec![
+        "maintenance".as_ref(),
+        "--node-config".as_ref(),
+        node_config_path.as_os_str(),
+        "--db-path".as_ref(),
+        db_path.as_os_str(),
+        "replay".as_ref(),
+    ];
+    let node = NodeBuilder::with_args(args)
+        .with(
+            Spec::new(SimpleService)
+                .with_default_instance()
+                .with_instance(200, "other", ()),
+        )
+        .execute_command()?;
+    assert!(node.is_none());
+
+    // Export the blockchain state and start a new blockchain from it.
+    let height = {
+        let snapshot = RocksDB::open_snapshot(&db_path, &DbOptions::default())?;
+        Schema::new(snapshot.as_ref()).height()
+    };
+    let height = height.0.to_string();
+    let genesis_config_path = dir.path().join("genesis.json");
+    let genesis_state_path = dir.path().join("state.json");
+    let args = vec![
+        "maintenance".as_ref(),
+        "--node-config".as_ref(),
+        node_config_path.as_os_str(),
+        "--db-path".as_ref(),
+        db_path.as_os_str(),
+        "export-genesis".as_ref(),
+        "--height".as_ref(),
+        height.as_ref(),
+        "--genesis-config".as_ref(),
+        genesis_config_path.as_os_str(),
+        "--genesis-state".as_ref(),
+        genesis_state_path.as_os_str(),
+    ];
+    let node = NodeBuilder::with_args(args).execute_command()?;
+    assert!(node.is_none());
+
+    let new_db_path = dir.path().join("new_db");
+    let args = vec![
+        "run".as_ref(),
+        "--node-config".as_ref(),
+        node_config_path.as_os_str(),
+        "--db-path".as_ref(),
+        new_db_path.as_os_str(),
+        "--master-key-pass".as_ref(),
+        "pass:".as_ref(),
+        "--genesis-config".as_ref(),
+        genesis_config_path.as_os_str(),
+        "--genesis-state".as_ref(),
+        genesis_state_path.as_os_str(),
+    ];
+    // Services are not added to the builder explicitly; their instances are taken
+    // from the exported genesis config.
+    let node = NodeBuilder::with_args(args)
+        .with(Spec::new(SimpleService))
+        .execute_command()?
+        .unwrap();
+    let shutdown_handle = node.shutdown_handle();
+    let node_task = tokio::spawn(node.run());
+    delay_for(Duration::from_secs(2)).await;
+
+    let url = format!("{}/services/other/answer", public_api_root);
+    let answer: u64 = send_request(client.get(&url)).await?;
+    assert_eq!(answer, 42);
+
     shutdown_handle.shutdown().await?;
     node_task.await??;
     Ok(())
@@ -29,8 +29,12 @@
 
 // spell-checker:ignore DIGESTBYTES, PUBLICKEYBYTES, SECRETKEYBYTES, SEEDBYTES, SIGNATUREBYTES
 
+use curve25519_dalek::{edwards::CompressedEdwardsY, scalar::Scalar};
+use ed25519_dalek as dalek;
 use exonum_sodiumoxide as sodiumoxide;
 
+use std::convert::TryFrom;
+
 /// Digest type for sodiumoxide-based implementation.
 pub use self::sha256::Digest as Hash;
 
@@ -105,6 +109,62 @@ pub fn verify(sig: &Signature, data: &[u8], pub_key: &PublicKey) -> bool {
     ed25519::verify_detached(sig, data, pub_key)
 }
 
+/// Verifies a batch of signatures. Returns `true` if all signatures in the batch are valid.
+///
+/// libsodium does not support batch verification, so the batch is checked
+/// with `ed25519-dalek`. The batch equation may accept signatures rejected by `verify`
+/// if public keys or signatures have non-canonical encodings or torsion components.
+/// Thus, only signatures passing `is_batchable` are checked in the batch; other signatures
+/// are checked with `verify`.
+pub fn verify_batch(batch: &[(&Signature, &[u8], &PublicKey)]) -> bool {
+    let mut messages = Vec::with_capacity(batch.len());
+    let mut signatures = Vec::with_capacity(batch.len());
+    let mut public_keys = Vec::with_capacity(batch.len());
+    for &(signature, data, public_key) in batch {
+        let parsed = if is_batchable(signature, public_key) {
+            let signature = dalek::Signature::try_from(&signature[..]);
+            let public_key = dalek::PublicKey::from_bytes(&public_key[..]);
+            signature.ok().and_then(|sig| Some((sig, public_key.ok()?)))
+        } else {
+            None
+        };
+
+        if let Some((signature, public_key)) = parsed {
+            messages.push(data);
+            signatures.push(signature);
+            public_keys.push(public_key);
+        } else if !verify(signature, data, public_key) {
+            return false;
+        }
+    }
+    messages.is_empty() || dalek::verify_batch(&messages, &signatures, &public_keys).is_ok()
+}
+
+/// Checks whether the batch equation gives the same result for the signature as `verify`.
+/// This is the case if the public key and the `R` component of the signature are canonical
+/// encodings of torsion-free points other than the identity, and the `S` component
+/// of the signature is a canonical scalar.
+fn is_batchable(signature: &Signature, public_key: &PublicKey) -> bool {
+    let mut s = [0_u8; 32];
+    s.copy_from_slice(&signature[32..]);
+    is_canonical_prime_order_point(&public_key[..])
+        && is_canonical_prime_order_point(&signature[..32])
+        && Scalar::from_canonical_bytes(s).is_some()
+}
+
+/// Checks whether the bytes are a canonical encoding of a point of the prime order.
+fn is_canonical_prime_order_point(bytes: &[u8]) -> bool {
+    let mut encoding = [0_u8; 32];
+    encoding.copy_from_slice(bytes);
+    CompressedEdwardsY(encoding)
+        .decompress()
+        .map_or(false, |point| {
+            point.compress().to_bytes() == encoding
+                && !point.is_small_order()
+                && point.is_torsion_free()
+        })
+}
+
 /// Calculates hash of a bytes slice.
 pub fn hash(data: &[u8]) -> Hash {
     sha256::hash(data)
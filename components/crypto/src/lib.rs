@@ -165,6 +165,43 @@ pub fn verify(sig: &Signature, data: &[u8], pubkey: &PublicKey) -> bool {
     crypto_impl::verify(&sig.0, data, &pubkey.0)
 }
 
+/// Verifies a batch of signatures, each given together with the signed data
+/// and the public key of the signer. Returns `true` if all signatures in the batch are valid.
+///
+/// Batch verification is considerably faster than verifying signatures one by one.
+/// The result is the same as if each signature were checked with [`verify`]; signatures
+/// with non-canonical encodings or small-order components, for which the batch check
+/// could give a different result, are checked with [`verify`] by the backend. If the batch
+/// fails, the result does not indicate which signatures are invalid; such batches
+/// should be checked with [`verify`].
+///
+/// [`verify`]: fn.verify.html
+///
+/// # Examples
+///
+/// ```
+/// # exonum_crypto::init();
+/// let keys: Vec<_> = (0..4).map(|_| exonum_crypto::gen_keypair()).collect();
+/// let data = [1, 2, 3];
+/// let signatures: Vec<_> = keys
+///     .iter()
+///     .map(|(_, secret_key)| exonum_crypto::sign(&data, secret_key))
+///     .collect();
+/// let batch: Vec<_> = signatures
+///     .iter()
+///     .zip(&keys)
+///     .map(|(signature, (public_key, _))| (signature, &data[..], public_key))
+///     .collect();
+/// assert!(exonum_crypto::verify_batch(&batch));
+/// ```
+pub fn verify_batch(batch: &[(&Signature, &[u8], &PublicKey)]) -> bool {
+    let impl_batch: Vec<_> = batch
+        .iter()
+        .map(|&(sig, data, pubkey)| (&sig.0, data, &pubkey.0))
+        .collect();
+    crypto_impl::verify_batch(&impl_batch)
+}
+
 /// Calculates a hash of a bytes slice.
 ///
 /// Type of a hash depends on a chosen crypto backend (via `...-crypto` cargo feature).
@@ -690,6 +727,67 @@ mod tests {
         assert_eq!(h1, h2);
     }
 
+    #[test]
+    fn verify_batch_of_signatures() {
+        let data: Vec<_> = (0_u8..8).map(|i| vec![i; 16]).collect();
+        let keys: Vec<_> = (0..8).map(|_| gen_keypair()).collect();
+        let mut signatures: Vec<_> = data
+            .iter()
+            .zip(&keys)
+            .map(|(data, (_, secret_key))| sign(data, secret_key))
+            .collect();
+
+        let batch = |signatures: &[Signature]| -> Vec<(Signature, Vec<u8>, PublicKey)> {
+            signatures
+                .iter()
+                .zip(&data)
+                .zip(&keys)
+                .map(|((sig, data), (public_key, _))| (*sig, data.clone(), *public_key))
+                .collect()
+        };
+        let check = |batch: &[(Signature, Vec<u8>, PublicKey)]| {
+            let batch: Vec<_> = batch
+                .iter()
+                .map(|(sig, data, public_key)| (sig, &data[..], public_key))
+                .collect();
+            verify_batch(&batch)
+        };
+
+        assert!(check(&[]));
+        assert!(check(&batch(&signatures)));
+
+        // Signature for other data.
+        signatures[3] = signatures[2];
+        assert!(!check(&batch(&signatures)));
+        // Malformed signature.
+        signatures[3] = Signature::zero();
+        assert!(!check(&batch(&signatures)));
+    }
+
+    #[test]
+    fn verify_batch_rejects_small_order_forgery() {
+        // The public key and the `R` component of the signature are the identity point,
+        // and `S` is zero. Such a signature satisfies the batch equation for any data,
+        // but is rejected by `verify`.
+        let mut identity = [0; PUBLIC_KEY_LENGTH];
+        identity[0] = 1;
+        let public_key = PublicKey::new(identity);
+        let mut signature = [0; SIGNATURE_LENGTH];
+        signature[0] = 1;
+        let signature = Signature::new(signature);
+
+        let data = [1, 2, 3];
+        assert!(!verify(&signature, &data, &public_key));
+        assert!(!verify_batch(&[(&signature, &data[..], &public_key)]));
+
+        let (valid_key, secret_key) = gen_keypair();
+        let valid_signature = sign(&data, &secret_key);
+        assert!(!verify_batch(&[
+            (&valid_signature, &data[..], &valid_key),
+            (&signature, &data[..], &public_key),
+        ]));
+    }
+
     #[test]
     fn sign_streaming_zero() {
         let (pk, sk) = gen_keypair();
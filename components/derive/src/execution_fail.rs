@@ -16,7 +16,7 @@ use darling::{FromDeriveInput, FromMeta};
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::{quote, ToTokens};
-use syn::{Attribute, Data, DeriveInput, Expr, Lit, Meta, MetaNameValue, Variant};
+use syn::{Attribute, Data, DeriveInput, Expr, Lit, Meta, MetaNameValue, Path, Variant};
 
 use std::convert::TryFrom;
 
@@ -28,6 +28,7 @@ struct ExecutionFailAttrs {
     #[darling(rename = "crate")]
     cr: MainCratePath,
     kind: String,
+    payload: Option<Path>,
 }
 
 impl Default for ExecutionFailAttrs {
@@ -35,6 +36,7 @@ impl Default for ExecutionFailAttrs {
         Self {
             cr: MainCratePath::default(),
             kind: "Service".to_owned(),
+            payload: None,
         }
     }
 }
@@ -236,6 +238,31 @@ impl ExecutionFail {
             }
         }
     }
+
+    fn implement_with_payload(&self) -> Option<impl ToTokens> {
+        let payload = self.attrs.payload.as_ref()?;
+        let type_tag = payload
+            .segments
+            .last()
+            .expect("ExecutionFail: Payload type should not be empty")
+            .ident
+            .to_string();
+        let name = &self.name;
+        let cr = &self.attrs.cr;
+        let module = quote!(#cr::runtime);
+
+        Some(quote! {
+            impl #name {
+                /// Creates an execution error with the specified structured payload.
+                pub fn with_payload(&self, payload: #payload) -> #module::ExecutionError {
+                    let kind = #module::ExecutionFail::kind(self);
+                    let description = #module::ExecutionFail::description(self);
+                    #module::ExecutionError::new(kind, description)
+                        .with_payload(#module::ErrorPayload::new(#type_tag, payload))
+                }
+            }
+        })
+    }
 }
 
 impl ToTokens for ExecutionFail {
@@ -243,11 +270,13 @@ impl ToTokens for ExecutionFail {
         let display_impl = self.implement_display();
         let service_fail_impl = self.implement_service_fail();
         let into_execution_error_impl = self.implement_into_execution_error();
+        let with_payload_impl = self.implement_with_payload();
 
         tokens.extend(quote! {
             #display_impl
             #service_fail_impl
             #into_execution_error_impl
+            #with_payload_impl
         })
     }
 }
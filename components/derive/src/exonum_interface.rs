@@ -32,6 +32,8 @@ struct ServiceMethodDescriptor {
     name: Ident,
     arg_type: Box<Type>,
     id: u32,
+    /// Type of the value returned by the method to the calling service, if any.
+    returns: Option<Type>,
 }
 
 const INVALID_METHOD_MSG: &str =
@@ -52,6 +54,7 @@ impl ServiceMethodDescriptor {
     /// where `Ctx` is the context type param defined in the trait.
     fn try_from(
         method_id: u32,
+        returns: Option<Type>,
         ctx: &Ident,
         method: &TraitItemMethod,
     ) -> Result<Self, darling::Error> {
@@ -133,8 +136,9 @@ impl ServiceMethodDescriptor {
 
         Ok(ServiceMethodDescriptor {
             name: method.sig.ident.clone(),
-            id: method_id, // TODO: allow to parse `method_id` from attrs
+            id: method_id,
             arg_type,
+            returns,
         })
     }
 }
@@ -187,6 +191,7 @@ struct ExonumInterfaceAttrs {
     auto_ids: bool,
     interface: Option<String>,
     removed_method_ids: RemovedMethods,
+    query: bool,
 }
 
 impl Default for ExonumInterfaceAttrs {
@@ -196,6 +201,7 @@ impl Default for ExonumInterfaceAttrs {
             auto_ids: false,
             interface: None,
             removed_method_ids: RemovedMethods::default(),
+            query: false,
         }
     }
 }
@@ -210,23 +216,42 @@ impl TryFrom<&[Attribute]> for ExonumInterfaceAttrs {
     }
 }
 
-#[derive(Debug, FromMeta)]
+/// Type of the value returned by an interface method, parsed from a string literal.
+#[derive(Debug)]
+struct ReturnedType(Type);
+
+impl FromMeta for ReturnedType {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        syn::parse_str(value)
+            .map(Self)
+            .map_err(|_| darling::Error::unknown_value(value))
+    }
+}
+
+#[derive(Debug, Default, FromMeta)]
+#[darling(default)]
 struct InterfaceMethodAttrs {
     /// Numeric identifier of the method.
-    id: u32,
+    id: Option<u32>,
+    /// Type of the value returned by the method to the calling service.
+    returns: Option<ReturnedType>,
 }
 
-impl TryFrom<&[Attribute]> for InterfaceMethodAttrs {
-    type Error = darling::Error;
-
-    fn try_from(args: &[Attribute]) -> Result<Self, Self::Error> {
-        find_meta_attrs("interface_method", args)
+impl InterfaceMethodAttrs {
+    /// Parses the attributes of the method. Unlike the method ID, the attributes are optional
+    /// if method IDs are assigned automatically.
+    fn parse(args: &[Attribute], auto_ids: bool) -> Result<Self, darling::Error> {
+        let attrs = find_meta_attrs("interface_method", args)
             .map(|meta| Self::from_nested_meta(&meta))
-            .unwrap_or_else(|| {
-                let msg = "Unable to find method ID mapping for method. \
-                           It should be specified, e.g. `#[interface_method(id = 0)]`";
-                Err(darling::Error::custom(msg.to_string()))
-            })
+            .transpose()?
+            .unwrap_or_default();
+
+        if !auto_ids && attrs.id.is_none() {
+            let msg = "Unable to find method ID mapping for method. \
+                       It should be specified, e.g. `#[interface_method(id = 0)]`";
+            return Err(darling::Error::custom(msg.to_string()));
+        }
+        Ok(attrs)
     }
 }
 
@@ -248,6 +273,11 @@ impl ExonumInterface {
             let msg = "`auto_ids` and `removed_method_ids` attributes cannot be used together";
             return Err(darling::Error::custom(msg).with_span(&item_trait));
         }
+        if attrs.query && attrs.interface.is_some() {
+            let msg = "Query interfaces cannot be named; `query` and `interface` attributes \
+                       cannot be used together";
+            return Err(darling::Error::custom(msg).with_span(&item_trait));
+        }
 
         // Extract context type param from the trait generics.
         let params = &item_trait.generics.params;
@@ -278,10 +308,17 @@ impl ExonumInterface {
         for trait_item in &item_trait.items {
             match trait_item {
                 TraitItem::Method(method) => {
+                    let method_attrs =
+                        InterfaceMethodAttrs::parse(method.attrs.as_ref(), attrs.auto_ids)?;
+                    if attrs.query && method_attrs.returns.is_some() {
+                        let msg = "Query methods cannot declare returned types; \
+                                   queries return serialized values";
+                        return Err(darling::Error::custom(msg).with_span(&method.sig.ident));
+                    }
+
                     let method_id = if !attrs.auto_ids {
-                        // Auto-increment disabled, parse ID from attribute.
-                        let id_attr = InterfaceMethodAttrs::try_from(method.attrs.as_ref())?;
-                        let method_id = id_attr.id;
+                        // Auto-increment disabled, use ID from attribute.
+                        let method_id = method_attrs.id.unwrap();
 
                         if removed_method_ids.contains(&method_id) {
                             let msg = format!(
@@ -303,7 +340,9 @@ impl ExonumInterface {
                         method_id
                     };
 
-                    let method = ServiceMethodDescriptor::try_from(method_id, ctx_ident, method)?;
+                    let returns = method_attrs.returns.map(|ty| ty.0);
+                    let method =
+                        ServiceMethodDescriptor::try_from(method_id, returns, ctx_ident, method)?;
                     methods.push(method);
                 }
                 TraitItem::Type(ty) if ty.ident == "Output" => {
@@ -341,10 +380,18 @@ impl ExonumInterface {
         Ident::new(&name, Span::call_site())
     }
 
+    fn returning_trait_name(&self) -> Ident {
+        let name = format!("{}Returning", self.item_trait.ident);
+        Ident::new(&name, Span::call_site())
+    }
+
     /// Generates `Interface` implementation for the trait object with matching params
     /// (`ExecutionContext` context and `Result<(), ExecutionError>` output). This will allow to call
     /// implementation methods from the dispatcher.
-    fn impl_interface(&self) -> impl ToTokens {
+    ///
+    /// For query interfaces, `QueryInterface` is implemented instead for the trait object
+    /// with `QueryContext` context and `Result<Vec<u8>, ExecutionError>` output.
+    fn impl_interface(&self) -> proc_macro2::TokenStream {
         let cr = &self.attrs.cr;
         let trait_name = &self.item_trait.ident;
         let interface_name = self.interface_name();
@@ -353,7 +400,9 @@ impl ExonumInterface {
         // an input argument using `BinaryValue` trait, and then invokes the corresponding
         // method of interface trait.
         let impl_match_arm_for_method = |descriptor: &ServiceMethodDescriptor| {
-            let ServiceMethodDescriptor { name, arg_type, id } = descriptor;
+            let ServiceMethodDescriptor {
+                name, arg_type, id, ..
+            } = descriptor;
 
             quote! {
                 #id => {
@@ -381,6 +430,27 @@ impl ExonumInterface {
             .iter()
             .map(impl_match_arm_for_removed_method);
 
+        if self.attrs.query {
+            let ctx = quote!(#cr::QueryContext<'a>);
+            let res = quote!(std::result::Result<Vec<u8>, exonum::runtime::ExecutionError>);
+            return quote! {
+                impl<'a> #cr::QueryInterface<'a> for dyn #trait_name<#ctx, Output = #res> {
+                    fn dispatch_query(
+                        &self,
+                        context: #ctx,
+                        method: exonum::runtime::MethodId,
+                        payload: &[u8],
+                    ) -> #res {
+                        match method {
+                            #( #match_arms )*
+                            #( #removed_match_arms )*
+                            _ => Err(exonum::runtime::CommonError::NoSuchMethod.into()),
+                        }
+                    }
+                }
+            };
+        }
+
         let ctx = quote!(#cr::_reexports::ExecutionContext<'a>);
         let res = quote!(std::result::Result<(), exonum::runtime::ExecutionError>);
         quote! {
@@ -413,7 +483,9 @@ impl ExonumInterface {
         let interface_name = self.interface_name();
 
         let impl_method = |descriptor: &ServiceMethodDescriptor| {
-            let ServiceMethodDescriptor { name, arg_type, id } = descriptor;
+            let ServiceMethodDescriptor {
+                name, arg_type, id, ..
+            } = descriptor;
             let descriptor = quote! {
                 #cr::MethodDescriptor::new(
                     #interface_name,
@@ -460,6 +532,74 @@ impl ExonumInterface {
         }
     }
 
+    /// Creates a trait with methods returning the values declared with the `returns` attribute
+    /// and implements it for any type implementing `GenericCallReturning`. The trait includes
+    /// only methods with the declared returned types; if there are no such methods, the trait
+    /// is not generated.
+    fn returning_trait(&self) -> impl ToTokens {
+        let cr = &self.attrs.cr;
+        let vis = &self.item_trait.vis;
+        let trait_name = self.returning_trait_name();
+        let interface_name = self.interface_name();
+
+        let returning_methods: Vec<_> = self
+            .methods
+            .iter()
+            .filter_map(|descriptor| {
+                descriptor
+                    .returns
+                    .as_ref()
+                    .map(|returns| (descriptor, returns))
+            })
+            .collect();
+        if returning_methods.is_empty() {
+            return quote!();
+        }
+
+        let res =
+            |returns: &Type| quote!(std::result::Result<#returns, exonum::runtime::ExecutionError>);
+        let declare_method = |(descriptor, returns): &(&ServiceMethodDescriptor, &Type)| {
+            let ServiceMethodDescriptor { name, arg_type, .. } = descriptor;
+            let res = res(returns);
+            quote! {
+                fn #name(&mut self, context: Ctx, arg: #arg_type) -> #res;
+            }
+        };
+        let impl_method = |(descriptor, returns): &(&ServiceMethodDescriptor, &Type)| {
+            let ServiceMethodDescriptor {
+                name, arg_type, id, ..
+            } = descriptor;
+            let res = res(returns);
+            quote! {
+                fn #name(&mut self, context: Ctx, arg: #arg_type) -> #res {
+                    #cr::GenericCallReturning::generic_call_returning(
+                        self,
+                        context,
+                        #cr::MethodDescriptor::new(#interface_name, #id),
+                        exonum::merkledb::BinaryValue::into_bytes(arg),
+                    )
+                }
+            }
+        };
+
+        let declared_methods = returning_methods.iter().map(declare_method);
+        let methods = returning_methods.iter().map(impl_method);
+        let doc = format!(
+            "Version of `{}` with methods returning values to the calling service.",
+            self.item_trait.ident
+        );
+        quote! {
+            #[doc = #doc]
+            #vis trait #trait_name<Ctx> {
+                #( #declared_methods )*
+            }
+
+            impl<Ctx, T: #cr::GenericCallReturning<Ctx>> #trait_name<Ctx> for T {
+                #( #methods )*
+            }
+        }
+    }
+
     /// Creates a mutable version of the trait by appending `Mut` to the trait name and changing
     /// `&self` receivers in the trait methods to `&mut self`. No other changes are performed.
     fn mut_trait(&self) -> impl ToTokens {
@@ -484,11 +624,13 @@ impl ToTokens for ExonumInterface {
         let mut_trait = self.mut_trait();
         let impl_interface = self.impl_interface();
         let impl_trait = self.impl_trait_for_generic_stub();
+        let returning_trait = self.returning_trait();
 
         let expanded = quote! {
             #mut_trait
             #item_trait
             #impl_trait
+            #returning_trait
             #impl_interface
         };
         tokens.extend(expanded);
@@ -170,6 +170,15 @@ pub fn from_access(input: TokenStream) -> TokenStream {
 /// List of the interfaces which have been implemented by the service. If omitted, it's implied
 /// that the service does not implement interfaces.
 ///
+/// ## `queries`
+///
+/// ```text
+/// #[service_dispatcher(queries = "path")]
+/// ```
+///
+/// Query interface (i.e., an interface declared with `#[exonum_interface(query)]`) implemented
+/// by the service. If omitted, the service does not support read-only queries.
+///
 /// ## `crate`
 ///
 /// ```text
@@ -222,6 +231,34 @@ pub fn service_dispatcher(input: TokenStream) -> TokenStream {
 ///
 /// Overrides the artifact version, which is set to the crate version by default.
 ///
+/// ## `requires`
+///
+/// ```text
+/// #[service_factory(requires = "name@version_req")]
+/// ```
+///
+/// Declares a dependency of the artifact on another artifact, such as `exonum.Token@^1.3.0`.
+/// The attribute can be specified multiple times. If omitted, the artifact has no dependencies.
+///
+/// ## `requires_interface`
+///
+/// ```text
+/// #[service_factory(requires_interface = "name@version_req")]
+/// ```
+///
+/// Declares a requirement of the artifact on an interface, such as `Token@^1.3.0`.
+/// The requirement is satisfied by any active artifact providing a matching version
+/// of the interface. The attribute can be specified multiple times.
+///
+/// ## `provides_interface`
+///
+/// ```text
+/// #[service_factory(provides_interface = "name@version")]
+/// ```
+///
+/// Declares a versioned interface provided by the artifact, such as `Token@1.4.0`.
+/// The attribute can be specified multiple times.
+///
 /// ## `with_constructor`
 ///
 /// ```text
@@ -276,6 +313,17 @@ pub fn service_factory(input: TokenStream) -> TokenStream {
 /// Enables automatic ID assignment for interface methods. This may be useful for writing tests,
 /// but not recommended for production code.
 ///
+/// ## `query`
+///
+/// ```text
+/// #[exonum_interface(query)]
+/// ```
+///
+/// Declares a read-only query interface. Query interfaces are dispatched with a `QueryContext`
+/// and return serialized values; they are implemented by services with
+/// `Output = Result<Vec<u8>, ExecutionError>`. Query interfaces cannot be named with
+/// the `interface` attribute.
+///
 /// # Method attributes
 ///
 /// ## `interface_method`
@@ -286,6 +334,15 @@ pub fn service_factory(input: TokenStream) -> TokenStream {
 ///
 /// All the method in the trait with `exonum_interface` attribute should have `interface_method`
 /// attribute with unsigned integer value. All the method IDs should be unique.
+///
+/// ```text
+/// #[interface_method(id = 0, returns = "u64")]
+/// ```
+///
+/// Methods may declare the type of the value returned to the calling service. For interfaces
+/// with such methods, the macro generates a trait with the `Returning` suffix, in which
+/// these methods return the declared type. The trait is implemented for the `Returning` stub
+/// from the Rust runtime.
 #[proc_macro_attribute]
 pub fn exonum_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
     exonum_interface::impl_exonum_interface(attr, item)
@@ -302,9 +359,19 @@ pub fn exonum_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ```
 ///
 /// Numeric identifier of the method. Should be unique for every method in the trait.
+/// The identifier may be omitted if the interface uses `auto_ids`.
 ///
 /// Using this attribute is a recommended way to remove methods from interface, since it
 /// guarantees that method ID won't be reused.
+///
+/// ## `returns`
+///
+/// ```text
+/// #[interface_method(id = 0, returns = "u64")]
+/// ```
+///
+/// Type of the value returned by the method to the calling service. The type should
+/// implement `BinaryValue`. Cannot be used in query interfaces.
 #[proc_macro_attribute]
 pub fn interface_method(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // We don't modify the input stream, since `interface_method` attribute only
@@ -341,6 +408,16 @@ pub fn interface_method(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// Error kind with the following possible values: `service`, `runtime`. The default value is
 /// `service`.
+///
+/// ## `payload`
+///
+/// ```text
+/// #[execution_fail(payload = "path::to::Type")]
+/// ```
+///
+/// Type of the structured payload attached to the errors, which must implement `BinaryValue`.
+/// If specified, the enum gets a `with_payload(&self, payload: Type) -> ExecutionError` method.
+/// The payload is tagged with the name of the type (i.e., the last segment of the path).
 #[proc_macro_derive(ExecutionFail, attributes(execution_fail))]
 pub fn execution_fail(input: TokenStream) -> TokenStream {
     execution_fail::impl_execution_fail(input)
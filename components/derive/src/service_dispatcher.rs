@@ -85,6 +85,8 @@ struct ServiceDispatcher {
     #[darling(default)]
     implements: ServiceInterfaces,
     #[darling(default)]
+    queries: Option<Path>,
+    #[darling(default)]
     generics: Generics,
 }
 
@@ -112,6 +114,25 @@ impl ToTokens for ServiceDispatcher {
             }
         });
 
+        let query_fn = self.queries.as_ref().map(|trait_name| {
+            let query_ctx = quote!(#cr::QueryContext<'_>);
+            let query_res = quote!(std::result::Result<Vec<u8>, #cr::_reexports::ExecutionError>);
+            let interface_trait = quote! {
+                <dyn #trait_name<#query_ctx, Output = #query_res> as #cr::QueryInterface>
+            };
+
+            quote! {
+                fn query(
+                    &self,
+                    ctx: #query_ctx,
+                    method: #cr::_reexports::MethodId,
+                    payload: &[u8],
+                ) -> #query_res {
+                    #interface_trait::dispatch_query(self, ctx, method, payload)
+                }
+            }
+        });
+
         let expanded = quote! {
             impl #impl_generics #cr::ServiceDispatcher for #service_name #ty_generics #where_clause  {
                 fn call(
@@ -125,6 +146,8 @@ impl ToTokens for ServiceDispatcher {
                         other => Err(#cr::_reexports::CommonError::NoSuchInterface.into()),
                     }
                 }
+
+                #query_fn
             }
         };
         tokens.extend(expanded);
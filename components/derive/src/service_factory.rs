@@ -15,7 +15,7 @@
 use darling::FromDeriveInput;
 use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
-use semver::Version;
+use semver::{Version, VersionReq};
 use syn::{DeriveInput, Generics, Ident, Path};
 
 use super::RustRuntimeCratePath;
@@ -53,6 +53,12 @@ struct ServiceFactory {
     proto_sources: Option<Path>,
     #[darling(default)]
     service_constructor: Option<Path>,
+    #[darling(multiple, rename = "requires")]
+    dependencies: Vec<String>,
+    #[darling(multiple, rename = "requires_interface")]
+    required_interfaces: Vec<String>,
+    #[darling(multiple, rename = "provides_interface")]
+    provided_interfaces: Vec<String>,
     #[darling(default)]
     generics: Generics,
 }
@@ -92,6 +98,113 @@ impl ServiceFactory {
         }
     }
 
+    fn artifact_dependencies(&self) -> Option<impl ToTokens> {
+        if self.dependencies.is_empty() {
+            return None;
+        }
+
+        for dependency in &self.dependencies {
+            // Check that the requirement has the `name@version` format.
+            let parts: Vec<_> = dependency.splitn(2, '@').collect();
+            match &parts[..] {
+                [name, version] if check_artifact_name(name) && !name.is_empty() => {
+                    VersionReq::parse(version)
+                        .expect("Unable to parse artifact version requirement");
+                }
+                _ => panic!(
+                    "Invalid artifact requirement `{}`. Use `name@version` format, \
+                     e.g., `exonum.Token@^1.3.0`",
+                    dependency
+                ),
+            }
+        }
+
+        let cr = &self.cr;
+        let dependencies = &self.dependencies;
+        Some(quote! {
+            fn artifact_dependencies(&self) -> Vec<#cr::_reexports::ArtifactReq> {
+                vec![
+                    #(
+                        #dependencies
+                            .parse()
+                            .expect("Cannot parse artifact requirement"),
+                    )*
+                ]
+            }
+        })
+    }
+
+    fn required_interfaces(&self) -> Option<impl ToTokens> {
+        if self.required_interfaces.is_empty() {
+            return None;
+        }
+
+        for requirement in &self.required_interfaces {
+            // Check that the requirement has the `name@version` format.
+            let parts: Vec<_> = requirement.splitn(2, '@').collect();
+            match &parts[..] {
+                [name, version] if !name.is_empty() => {
+                    VersionReq::parse(version)
+                        .expect("Unable to parse interface version requirement");
+                }
+                _ => panic!(
+                    "Invalid interface requirement `{}`. Use `name@version` format, \
+                     e.g., `Token@^1.3.0`",
+                    requirement
+                ),
+            }
+        }
+
+        let cr = &self.cr;
+        let required_interfaces = &self.required_interfaces;
+        Some(quote! {
+            fn required_interfaces(&self) -> Vec<#cr::_reexports::InterfaceReq> {
+                vec![
+                    #(
+                        #required_interfaces
+                            .parse()
+                            .expect("Cannot parse interface requirement"),
+                    )*
+                ]
+            }
+        })
+    }
+
+    fn provided_interfaces(&self) -> Option<impl ToTokens> {
+        if self.provided_interfaces.is_empty() {
+            return None;
+        }
+
+        for interface in &self.provided_interfaces {
+            // Check that the interface has the `name@version` format.
+            let parts: Vec<_> = interface.splitn(2, '@').collect();
+            match &parts[..] {
+                [name, version] if !name.is_empty() => {
+                    Version::parse(version).expect("Unable to parse interface version");
+                }
+                _ => panic!(
+                    "Invalid provided interface `{}`. Use `name@version` format, \
+                     e.g., `Token@1.4.0`",
+                    interface
+                ),
+            }
+        }
+
+        let cr = &self.cr;
+        let provided_interfaces = &self.provided_interfaces;
+        Some(quote! {
+            fn provided_interfaces(&self) -> Vec<#cr::_reexports::ProvidedInterface> {
+                vec![
+                    #(
+                        #provided_interfaces
+                            .parse()
+                            .expect("Cannot parse provided interface"),
+                    )*
+                ]
+            }
+        })
+    }
+
     fn artifact_protobuf_spec(&self) -> impl ToTokens {
         let cr = &self.cr;
         if let Some(ref proto_sources_mod) = self.proto_sources {
@@ -119,6 +232,9 @@ impl ToTokens for ServiceFactory {
         let artifact_version = self.artifact_version();
         let artifact_protobuf_spec = self.artifact_protobuf_spec();
         let service_constructor = self.service_constructor();
+        let artifact_dependencies = self.artifact_dependencies();
+        let required_interfaces = self.required_interfaces();
+        let provided_interfaces = self.provided_interfaces();
         let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
 
         let expanded = quote! {
@@ -139,6 +255,10 @@ impl ToTokens for ServiceFactory {
                 fn create_instance(&self) -> Box<dyn #cr::Service> {
                     #service_constructor
                 }
+
+                #artifact_dependencies
+                #required_interfaces
+                #provided_interfaces
             }
         };
         tokens.extend(expanded)
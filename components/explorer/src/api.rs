@@ -19,16 +19,18 @@
 
 use chrono::{DateTime, Utc};
 use exonum::{
-    blockchain::{Block, CallProof},
-    crypto::Hash,
+    blockchain::{
+        Block, CallProof, EventProof, IndexHashChange, ServiceEvent, SimulationResult, TxLocation,
+    },
+    crypto::{Hash, PublicKey},
     helpers::Height,
-    merkledb::BinaryValue,
+    merkledb::{BinaryValue, IndexAddress, IndexInfo, IndexType},
     messages::{Precommit, Verified},
-    runtime::{AnyTx, CallInfo, ExecutionStatus, InstanceId},
+    runtime::{AnyTx, CallInfo, CallTrace, ExecutionStatus, InstanceId, MethodId},
 };
 use serde_derive::{Deserialize, Serialize};
 
-use std::ops::Range;
+use std::{collections::BTreeMap, ops::Range};
 
 use crate::median_precommits_time;
 
@@ -217,6 +219,201 @@ impl TransactionResponse {
     }
 }
 
+/// Query parameters to simulate a transaction against the latest blockchain state.
+///
+/// Exactly one of `tx_body` and `unsigned_tx` should be specified. Unsigned transactions
+/// must be accompanied by the `author` key and are simulated only if the runtime
+/// of the target service permits it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct SimulationQuery {
+    /// The hex value of a signed transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_body: Option<String>,
+    /// The hex value of an unsigned transaction (i.e., a serialized `AnyTx`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unsigned_tx: Option<String>,
+    /// Author of the unsigned transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<PublicKey>,
+    /// Whether to return changes in the hashes of indexes touched by the transaction.
+    #[serde(default)]
+    pub with_diff: bool,
+    /// Whether to return the trace of calls made by the transaction.
+    #[serde(default)]
+    pub with_trace: bool,
+}
+
+impl SimulationQuery {
+    /// Creates a query to simulate a signed transaction.
+    pub fn signed(transaction: &Verified<AnyTx>) -> Self {
+        Self {
+            tx_body: Some(hex::encode(transaction.to_bytes())),
+            unsigned_tx: None,
+            author: None,
+            with_diff: false,
+            with_trace: false,
+        }
+    }
+
+    /// Creates a query to simulate an unsigned transaction on behalf of `author`.
+    pub fn unsigned(transaction: &AnyTx, author: PublicKey) -> Self {
+        Self {
+            tx_body: None,
+            unsigned_tx: Some(hex::encode(transaction.to_bytes())),
+            author: Some(author),
+            with_diff: false,
+            with_trace: false,
+        }
+    }
+
+    /// Requests to return changes in the hashes of touched indexes.
+    pub fn with_diff(mut self) -> Self {
+        self.with_diff = true;
+        self
+    }
+
+    /// Requests to return the trace of calls made by the transaction.
+    pub fn with_trace(mut self) -> Self {
+        self.with_trace = true;
+        self
+    }
+}
+
+/// Address of an index touched by a simulated transaction.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct TouchedIndex {
+    /// Full name of the index.
+    pub name: String,
+    /// Hex-encoded key of the index within a group, or `None` if the index
+    /// does not belong to a group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_in_group: Option<String>,
+}
+
+impl From<&IndexAddress> for TouchedIndex {
+    fn from(address: &IndexAddress) -> Self {
+        Self {
+            name: address.name().to_owned(),
+            id_in_group: address.id_in_group().map(hex::encode),
+        }
+    }
+}
+
+/// Response to a transaction simulation request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct SimulationResponse {
+    /// The hash digest of the simulated transaction. For unsigned transactions, the hash
+    /// is calculated over the transaction payload.
+    pub tx_hash: Hash,
+    /// Execution status of the transaction.
+    pub status: ExecutionStatus,
+    /// Indexes accessed by the transaction, in the order of the first access.
+    pub touched_indexes: Vec<TouchedIndex>,
+    /// Changes in the hashes of aggregated indexes keyed by the full index name,
+    /// if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<BTreeMap<String, IndexHashChange>>,
+    /// Trace of calls made by the transaction, if requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<CallTrace>,
+}
+
+impl SimulationResponse {
+    /// Creates a response from the simulation result.
+    #[doc(hidden)] // not stabilized; used in the explorer service
+    pub fn new(tx_hash: Hash, result: SimulationResult) -> Self {
+        Self {
+            tx_hash,
+            status: result.status,
+            touched_indexes: result.touched_indexes.iter().map(From::from).collect(),
+            diff: result.diff,
+            trace: result.trace,
+        }
+    }
+}
+
+/// Query parameters for a read-only query to a service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct ServiceQuery {
+    /// Identifier of the queried service instance.
+    pub instance_id: InstanceId,
+    /// Identifier of the query method.
+    pub method_id: MethodId,
+    /// Hex-encoded query arguments.
+    #[serde(default)]
+    pub arguments: String,
+}
+
+impl ServiceQuery {
+    /// Creates a query with the specified arguments.
+    pub fn new(instance_id: InstanceId, method_id: MethodId, arguments: impl BinaryValue) -> Self {
+        Self {
+            instance_id,
+            method_id,
+            arguments: hex::encode(arguments.into_bytes()),
+        }
+    }
+}
+
+/// Response to a read-only query to a service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct ServiceQueryResponse {
+    /// Hex-encoded value returned by the service.
+    pub result: String,
+}
+
+impl ServiceQueryResponse {
+    /// Creates a response from the serialized value returned by the service.
+    #[doc(hidden)] // not stabilized; used in the explorer service
+    pub fn new(result: &[u8]) -> Self {
+        Self {
+            result: hex::encode(result),
+        }
+    }
+}
+
+/// Trace of a committed transaction obtained by re-executing the transaction.
+///
+/// The transaction is re-executed against the state preceding it in its block, which
+/// is restored by re-executing the preceding blocks. Thus, `status` matches `committed_status`
+/// unless the services behave non-deterministically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TransactionTrace {
+    /// Location of the transaction in the blockchain.
+    pub location: TxLocation,
+    /// Execution status of the transaction recorded when the transaction was committed.
+    pub committed_status: ExecutionStatus,
+    /// Execution status of the transaction on re-execution.
+    pub status: ExecutionStatus,
+    /// Trace of calls made by the transaction on re-execution, or `None` if re-execution
+    /// has failed before reaching the service.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<CallTrace>,
+}
+
+impl TransactionTrace {
+    /// Creates a response from the result of transaction re-execution.
+    #[doc(hidden)] // not stabilized; used in the explorer service
+    pub fn new(
+        location: TxLocation,
+        committed_status: ExecutionStatus,
+        result: SimulationResult,
+    ) -> Self {
+        Self {
+            location,
+            committed_status,
+            status: result.status,
+            trace: result.trace,
+        }
+    }
+}
+
 /// Transaction query parameters.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[non_exhaustive]
@@ -302,3 +499,92 @@ pub enum CallStatusResponse {
     /// Response with a cryptographic proof of authenticity.
     Proof(CallProof),
 }
+
+/// Response with events emitted during a call within a block.
+///
+/// This enum is serialized in JSON untagged. Hence, if the consumer knows the type of the response,
+/// they may parse it directly to `Vec<ServiceEvent>` or `EventProof`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum CallEventsResponse {
+    /// Simple response.
+    Simple(Vec<ServiceEvent>),
+    /// Response with a cryptographic proof of authenticity.
+    Proof(EventProof),
+}
+
+/// Query parameters to list indexes of a service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct ServiceIndexesQuery {
+    /// Numerical service identifier.
+    pub service_id: InstanceId,
+}
+
+impl ServiceIndexesQuery {
+    /// Creates a new query.
+    pub fn new(service_id: InstanceId) -> Self {
+        Self { service_id }
+    }
+}
+
+/// Information about an index of a service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct ServiceIndexInfo {
+    /// Full name of the index, including the service name prefix.
+    pub name: String,
+    /// Hex-encoded key of the index within a group, or `None` if the index
+    /// does not belong to a group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_in_group: Option<String>,
+    /// Type of the index.
+    pub index_type: IndexType,
+    /// Whether the index is Merkelized, i.e., supports proofs.
+    pub is_proof: bool,
+    /// Hash of the index, or `None` if the index is not Merkelized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_hash: Option<Hash>,
+    /// Approximate number of entries in the index. See [`IndexInfo`] for details how
+    /// this number is computed.
+    ///
+    /// [`IndexInfo`]: https://docs.rs/exonum-merkledb/latest/exonum_merkledb/struct.IndexInfo.html
+    pub approx_len: u64,
+}
+
+impl From<IndexInfo> for ServiceIndexInfo {
+    fn from(info: IndexInfo) -> Self {
+        Self {
+            name: info.address.name().to_owned(),
+            id_in_group: info.address.id_in_group().map(hex::encode),
+            index_type: info.index_type,
+            is_proof: info.is_merkelized(),
+            object_hash: info.object_hash,
+            approx_len: info.approx_len,
+        }
+    }
+}
+
+/// Indexes of a service together with the service identifiers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[non_exhaustive]
+pub struct ServiceIndexes {
+    /// Numerical service identifier.
+    pub service_id: InstanceId,
+    /// Service name.
+    pub service_name: String,
+    /// Indexes of the service ordered by their names.
+    pub indexes: Vec<ServiceIndexInfo>,
+}
+
+impl ServiceIndexes {
+    /// Creates a response from the given service indexes.
+    #[doc(hidden)] // not stabilized; used in the explorer service
+    pub fn new(service_id: InstanceId, service_name: String, indexes: Vec<IndexInfo>) -> Self {
+        Self {
+            service_id,
+            service_name,
+            indexes: indexes.into_iter().map(From::from).collect(),
+        }
+    }
+}
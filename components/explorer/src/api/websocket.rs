@@ -16,8 +16,9 @@
 
 use chrono::{DateTime, Utc};
 use exonum::{
-    blockchain::{Block, Schema, TxLocation},
+    blockchain::{Block, CallInBlock, Schema, ServiceEvent, TxLocation},
     crypto::Hash,
+    helpers::Height,
     merkledb::{access::Access, ListProof},
     runtime::{ExecutionStatus, InstanceId, MethodId},
 };
@@ -52,6 +53,11 @@ pub enum SubscriptionType {
         /// Optional filter for the subscription.
         filter: Option<TransactionFilter>,
     },
+    /// Subscription to events emitted by services.
+    Events {
+        /// Optional filter for the subscription.
+        filter: Option<EventFilter>,
+    },
 }
 
 /// Filter for transactions by service instance and (optionally) method identifier
@@ -76,6 +82,24 @@ impl TransactionFilter {
     }
 }
 
+/// Filter for events by service instance and (optionally) the event topic.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize)]
+pub struct EventFilter {
+    /// ID of the service.
+    pub instance_id: InstanceId,
+    /// Optional event topic. If not set, events with all topics emitted by the service
+    /// will be sent.
+    pub topic: Option<String>,
+}
+
+impl EventFilter {
+    /// Creates a new event filter.
+    pub fn new(instance_id: InstanceId, topic: Option<String>) -> Self {
+        Self { instance_id, topic }
+    }
+}
+
 /// Response to a WebSocket client. Roughly equivalent to `Result<T, String>`.
 #[serde(tag = "result", rename_all = "snake_case")]
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -172,6 +196,40 @@ impl CommittedTransactionSummary {
     }
 }
 
+/// Event emitted by a service together with its location in the blockchain.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventSummary {
+    /// Height of the block in which the event was emitted.
+    pub height: Height,
+    /// Location of the call within the block.
+    pub location: CallInBlock,
+    /// Event data.
+    pub event: ServiceEvent,
+}
+
+impl EventSummary {
+    /// Loads summaries of all events emitted within a block from the core schema.
+    /// Events are ordered by the call location and then by the order of emission.
+    pub fn all_in_block(schema: &Schema<impl Access>, height: Height) -> Vec<Self> {
+        let records = match schema.call_records(height) {
+            Some(records) => records,
+            None => return vec![],
+        };
+        let mut events: Vec<_> = records.events().collect();
+        events.sort_unstable_by_key(|(location, _)| *location);
+        events
+            .into_iter()
+            .flat_map(|(location, call_events)| {
+                call_events.events.into_iter().map(move |event| Self {
+                    height,
+                    location,
+                    event,
+                })
+            })
+            .collect()
+    }
+}
+
 /// Notification message passed to WebSocket clients.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -180,4 +238,6 @@ pub enum Notification {
     Block(Block),
     /// Notification about new transaction.
     Transaction(CommittedTransactionSummary),
+    /// Notification about an event emitted by a service.
+    Event(EventSummary),
 }
@@ -52,10 +52,12 @@
 
 use chrono::{DateTime, Utc};
 use exonum::{
-    blockchain::{Block, CallInBlock, CallProof, Schema, TxLocation},
+    blockchain::{
+        Block, CallInBlock, CallProof, CallRecords, EventProof, Schema, ServiceEvent, TxLocation,
+    },
     crypto::Hash,
     helpers::Height,
-    merkledb::{ListProof, ObjectHash, Snapshot},
+    merkledb::{access::Access, ListProof, ObjectHash, Snapshot},
     messages::{AnyTx, Precommit, Verified},
     runtime::{ExecutionError, ExecutionStatus},
 };
@@ -200,6 +202,20 @@ impl<'a> BlockInfo<'a> {
             .get_proof(call_location)
     }
 
+    /// Returns the proof for events emitted during a call within this block.
+    ///
+    /// Similar to [`call_proof`], the returned proof will not contain entries if the call
+    /// has not emitted any events or did not happen at all.
+    ///
+    /// [`call_proof`]: #method.call_proof
+    pub fn events_proof(&self, call_location: CallInBlock) -> EventProof {
+        self.explorer
+            .schema
+            .call_records(self.header.height)
+            .unwrap() // safe: we know that the block exists
+            .get_events_proof(call_location)
+    }
+
     /// Iterates over transactions in the block.
     pub fn iter(&self) -> Transactions<'_, '_> {
         Transactions {
@@ -209,7 +225,7 @@ impl<'a> BlockInfo<'a> {
         }
     }
 
-    /// Loads transactions, errors and precommits for the block.
+    /// Loads transactions, errors, events and precommits for the block.
     pub fn with_transactions(self) -> BlockWithTransactions {
         let (explorer, header, precommits, transactions) =
             (self.explorer, self.header, self.precommits, self.txs);
@@ -223,21 +239,23 @@ impl<'a> BlockInfo<'a> {
             .iter()
             .map(|tx_hash| explorer.committed_transaction(tx_hash, None))
             .collect();
-        let errors = self
+        let records = self
             .explorer
             .schema
             .call_records(header.height)
             .expect("No call record for a committed block");
-        let errors: Vec<_> = errors
+        let errors: Vec<_> = records
             .errors()
             .map(|(location, error)| ErrorWithLocation { location, error })
             .collect();
+        let events = EventsWithLocation::collect(&records);
 
         BlockWithTransactions {
             header,
             precommits,
             transactions,
             errors,
+            events,
         }
     }
 }
@@ -298,6 +316,9 @@ pub struct BlockWithTransactions {
     pub transactions: Vec<CommittedTransaction>,
     /// Errors that have occurred within the block.
     pub errors: Vec<ErrorWithLocation>,
+    /// Events emitted by services within the block, ordered by the call location.
+    #[serde(default)]
+    pub events: Vec<EventsWithLocation>,
 }
 
 /// Execution error together with its location within the block.
@@ -316,6 +337,30 @@ impl fmt::Display for ErrorWithLocation {
     }
 }
 
+/// Events emitted by services during a call together with the call location within the block.
+#[derive(Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EventsWithLocation {
+    /// Location of the call.
+    pub location: CallInBlock,
+    /// Events in the order of their emission.
+    pub events: Vec<ServiceEvent>,
+}
+
+impl EventsWithLocation {
+    fn collect<T: Access>(records: &CallRecords<T>) -> Vec<Self> {
+        let mut events: Vec<_> = records
+            .events()
+            .map(|(location, call_events)| Self {
+                location,
+                events: call_events.events,
+            })
+            .collect();
+        events.sort_unstable_by_key(|events| events.location);
+        events
+    }
+}
+
 impl BlockWithTransactions {
     /// Returns the height of this block.
     ///
@@ -343,6 +388,18 @@ impl BlockWithTransactions {
     pub fn error_map(&self) -> BTreeMap<CallInBlock, &ExecutionError> {
         self.errors.iter().map(|e| (e.location, &e.error)).collect()
     }
+
+    /// Iterates over events emitted by services within the block together with
+    /// their call locations.
+    pub fn events(&self) -> impl Iterator<Item = (CallInBlock, &ServiceEvent)> {
+        self.events.iter().flat_map(|call_events| {
+            let location = call_events.location;
+            call_events
+                .events
+                .iter()
+                .map(move |event| (location, event))
+        })
+    }
 }
 
 /// Iterator over transactions in [`BlockWithTransactions`].
@@ -655,6 +712,23 @@ impl<'a> BlockchainExplorer<'a> {
         }
     }
 
+    /// Returns events emitted during a call in a block in the order of their emission.
+    ///
+    /// # Return value
+    ///
+    /// This method will return an empty vector if the call has not emitted any events,
+    /// or if it was not performed at all.
+    pub fn call_events(
+        &self,
+        block_height: Height,
+        call_location: CallInBlock,
+    ) -> Vec<ServiceEvent> {
+        match self.schema.call_records(block_height) {
+            Some(records) => records.get_events(call_location),
+            None => vec![],
+        }
+    }
+
     /// Return transaction message without proof.
     pub fn transaction_without_proof(&self, tx_hash: &Hash) -> Option<Verified<AnyTx>> {
         self.schema.transactions().get(tx_hash)
@@ -731,7 +805,7 @@ impl<'a> BlockchainExplorer<'a> {
     pub fn block_with_txs(&self, height: Height) -> Option<BlockWithTransactions> {
         let txs_table = self.schema.block_transactions(height);
         let block_proof = self.schema.block_and_precommits(height)?;
-        let errors = self.schema.call_records(height)?;
+        let records = self.schema.call_records(height)?;
 
         Some(BlockWithTransactions {
             header: block_proof.block,
@@ -740,10 +814,11 @@ impl<'a> BlockchainExplorer<'a> {
                 .iter()
                 .map(|tx_hash| self.committed_transaction(&tx_hash, None))
                 .collect(),
-            errors: errors
+            errors: records
                 .errors()
                 .map(|(location, error)| ErrorWithLocation { location, error })
                 .collect(),
+            events: EventsWithLocation::collect(&records),
         })
     }
 
@@ -0,0 +1,360 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Light client for Exonum blockchains.
+//!
+//! [`BlockProof`]s, [`IndexProof`]s and [`CallProof`]s can only be verified if the verifier
+//! knows the consensus keys of the validators at the height of the proven block. Since
+//! the validator set may change over time, a client following the blockchain needs a verified
+//! way to track these changes. [`LightClient`] provides such a way:
+//!
+//! - The client is initialized with a trusted [`ConsensusConfig`], for example, the one
+//!   from the genesis block.
+//! - Block headers and proofs are verified with the validator set active at the height
+//!   of the corresponding block. Verified headers are tracked by the client, so that
+//!   conflicting headers for the same height are detected.
+//! - A change of the consensus config is accepted if it is proven by [`IndexProof`]s
+//!   for the `core.consensus_config` index at the height of the change and at the previous
+//!   height, which are authenticated by the validators active at these heights.
+//!   The proof for the previous height pins the change to the exact height.
+//!   The new config applies to all blocks after the height of the change.
+//!
+//! The client does not perform any I/O; retrieving proofs from the nodes is up to the client app.
+//! The client state can be persisted with the help of `serde`.
+//!
+//! # Examples
+//!
+//! ```
+//! use exonum::blockchain::{BlockProof, ConsensusConfig, IndexProof};
+//! use exonum::helpers::Height;
+//! use exonum_light_client::{Error, LightClient};
+//!
+//! fn follow_chain(
+//!     genesis_config: ConsensusConfig,
+//!     config_changes: Vec<(IndexProof, IndexProof, ConsensusConfig)>,
+//!     latest_block: &BlockProof,
+//! ) -> Result<(), Error> {
+//!     let mut client = LightClient::new(genesis_config, Height(0));
+//!     for (previous_proof, proof, config) in config_changes {
+//!         client.apply_config_change(&previous_proof, &proof, config)?;
+//!     }
+//!     client.verify_block(latest_block)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`BlockProof`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.BlockProof.html
+//! [`IndexProof`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.IndexProof.html
+//! [`CallProof`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.CallProof.html
+//! [`ConsensusConfig`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.ConsensusConfig.html
+//! [`LightClient`]: struct.LightClient.html
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+#![warn(clippy::pedantic)]
+#![allow(
+    // Next lints produce too much noise/false positives.
+    clippy::module_name_repetitions, clippy::must_use_candidate,
+    // Too much work to fix.
+    clippy::missing_errors_doc
+)]
+
+use exonum::{
+    blockchain::{
+        Block, BlockProof, CallInBlock, CallProof, ConsensusConfig, IndexProof, ProofError,
+    },
+    crypto::{Hash, PublicKey},
+    helpers::Height,
+    merkledb::ObjectHash,
+    runtime::ExecutionError,
+};
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+use std::collections::BTreeMap;
+
+/// Full name of the core index storing the consensus configuration.
+pub const CONSENSUS_CONFIG_INDEX: &str = "core.consensus_config";
+
+/// Errors that can occur during verification of proofs by the light client.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The block precedes the height from which the client trusts the validator set.
+    #[error(
+        "Block at height {} precedes the trusted height {}",
+        height,
+        trusted_height
+    )]
+    UntrustedHeight {
+        /// Height of the block.
+        height: Height,
+        /// Height from which the client trusts the validator set.
+        trusted_height: Height,
+    },
+
+    /// The proof is not authenticated by the validator set active at the block height.
+    #[error("Invalid proof: {}", _0)]
+    Proof(#[from] ProofError),
+
+    /// The proven block is a block skip, which cannot be tracked by the client.
+    #[error("Block skips cannot be tracked by the light client")]
+    BlockSkip,
+
+    /// The proven block differs from the block previously verified at the same height.
+    /// This means that the validator set has signed two different blocks at the same height.
+    #[error("Block at height {} conflicts with the previously verified block", _0)]
+    ConflictingHeader(Height),
+
+    /// The proof of a consensus config change does not refer to the consensus config index.
+    #[error(
+        "Expected a proof for `{}` index, got a proof for `{}`",
+        CONSENSUS_CONFIG_INDEX,
+        _0
+    )]
+    UnexpectedIndex(String),
+
+    /// The proven hash of the consensus config does not match the provided config.
+    #[error("Provided consensus config does not match the proven one")]
+    ConfigMismatch,
+
+    /// The proof of the consensus config before the change does not refer to the height
+    /// preceding the change.
+    #[error(
+        "Expected a proof of the previous consensus config at height preceding {}, got {}",
+        height,
+        previous_height
+    )]
+    PreviousHeightMismatch {
+        /// Height of the block with the config change.
+        height: Height,
+        /// Height of the block from the proof of the previous config.
+        previous_height: Height,
+    },
+
+    /// The consensus config at the height of the change is the same as at the previous height,
+    /// i.e., the config has not changed at this height.
+    #[error("Consensus config has not changed at height {}", _0)]
+    UnchangedConfig(Height),
+
+    /// The consensus config change occurs before the latest known change.
+    #[error(
+        "Consensus config change at height {} precedes the latest known change at height {}",
+        height,
+        latest_height
+    )]
+    OutdatedConfigChange {
+        /// Height of the block with the config change.
+        height: Height,
+        /// Height of the block with the latest known config change.
+        latest_height: Height,
+    },
+}
+
+/// Light client tracking block headers and validator set changes of an Exonum blockchain.
+///
+/// See [crate docs](index.html) for an overview.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LightClient {
+    /// Consensus configs keyed by the first height at which they are active.
+    configs: BTreeMap<Height, ConsensusConfig>,
+    /// Verified block headers keyed by height.
+    headers: BTreeMap<Height, Block>,
+}
+
+impl LightClient {
+    /// Creates a client which trusts the validator set from the `config` for blocks
+    /// starting from the specified `height`. To follow the blockchain from its start,
+    /// use the consensus config from the genesis config and `Height(0)`.
+    pub fn new(config: ConsensusConfig, height: Height) -> Self {
+        let mut configs = BTreeMap::new();
+        configs.insert(height, config);
+        Self {
+            configs,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the height from which the client trusts the validator set.
+    pub fn trusted_height(&self) -> Height {
+        *self
+            .configs
+            .keys()
+            .next()
+            .expect("No trusted consensus config")
+    }
+
+    /// Returns the latest known consensus config.
+    pub fn consensus_config(&self) -> &ConsensusConfig {
+        self.configs
+            .values()
+            .next_back()
+            .expect("No trusted consensus config")
+    }
+
+    /// Returns the consensus config active at the specified height, or `None` if the height
+    /// precedes the trusted height. Note that the returned config may be outdated if the client
+    /// was not notified about config changes before `height`.
+    pub fn consensus_config_at(&self, height: Height) -> Option<&ConsensusConfig> {
+        self.configs
+            .range(..=height)
+            .next_back()
+            .map(|(_, config)| config)
+    }
+
+    /// Returns consensus keys of the validators active at the specified height.
+    pub fn validator_keys_at(&self, height: Height) -> Option<Vec<PublicKey>> {
+        let config = self.consensus_config_at(height)?;
+        Some(
+            config
+                .validator_keys
+                .iter()
+                .map(|keys| keys.consensus_key)
+                .collect(),
+        )
+    }
+
+    /// Returns the verified block header at the specified height.
+    pub fn header(&self, height: Height) -> Option<&Block> {
+        self.headers.get(&height)
+    }
+
+    /// Returns the latest verified block header.
+    pub fn latest_header(&self) -> Option<&Block> {
+        self.headers.values().next_back()
+    }
+
+    /// Verifies a block proof and tracks the proven block header.
+    pub fn verify_block(&mut self, proof: &BlockProof) -> Result<&Block, Error> {
+        let height = proof.block.height;
+        let validator_keys = self.validator_keys_for(&proof.block)?;
+        proof.verify(&validator_keys)?;
+        self.track_header(&proof.block)?;
+        Ok(&self.headers[&height])
+    }
+
+    /// Verifies a proof for an index and tracks the block header from the proof.
+    /// Returns the full name of the index and its hash.
+    pub fn verify_index_proof<'a>(
+        &mut self,
+        proof: &'a IndexProof,
+    ) -> Result<(&'a str, Hash), Error> {
+        let validator_keys = self.validator_keys_for(&proof.block_proof.block)?;
+        let entry = proof.verify(&validator_keys)?;
+        self.track_header(&proof.block_proof.block)?;
+        Ok(entry)
+    }
+
+    /// Verifies a proof for a call within a block and tracks the block header from the proof.
+    /// Returns the call location together with its status.
+    pub fn verify_call_proof(
+        &mut self,
+        proof: &CallProof,
+    ) -> Result<(CallInBlock, Result<(), ExecutionError>), Error> {
+        let validator_keys = self.validator_keys_for(&proof.block_proof.block)?;
+        let call_status = proof.verify(&validator_keys)?;
+        self.track_header(&proof.block_proof.block)?;
+        Ok(call_status)
+    }
+
+    /// Accepts a change of the consensus config. `proof` should be a proof for
+    /// the `core.consensus_config` index at the height at which the config has changed,
+    /// and `config` the new config. `previous_proof` should be a proof for the same index
+    /// at the preceding height; the config proven by it must differ from `config`.
+    /// Otherwise, a proof taken at any height after the change would move the change
+    /// to that height.
+    ///
+    /// The new config is active for all blocks after the height of the change. Headers
+    /// of such blocks verified with the previous validator set are no longer tracked
+    /// and need to be verified anew.
+    ///
+    /// # Return value
+    ///
+    /// Returns the height from which the new config is active.
+    pub fn apply_config_change(
+        &mut self,
+        previous_proof: &IndexProof,
+        proof: &IndexProof,
+        config: ConsensusConfig,
+    ) -> Result<Height, Error> {
+        let height = proof.block_proof.block.height;
+        let latest_height = *self
+            .configs
+            .keys()
+            .next_back()
+            .expect("No trusted consensus config");
+        if height < latest_height {
+            return Err(Error::OutdatedConfigChange {
+                height,
+                latest_height,
+            });
+        }
+        let previous_height = previous_proof.block_proof.block.height;
+        if previous_height.next() != height {
+            return Err(Error::PreviousHeightMismatch {
+                height,
+                previous_height,
+            });
+        }
+
+        let config_hash = self.verify_config_proof(proof)?;
+        if config_hash != config.object_hash() {
+            return Err(Error::ConfigMismatch);
+        }
+        if self.verify_config_proof(previous_proof)? == config_hash {
+            return Err(Error::UnchangedConfig(height));
+        }
+
+        let activation_height = height.next();
+        self.configs.insert(activation_height, config);
+        self.headers.split_off(&activation_height);
+        Ok(activation_height)
+    }
+
+    /// Verifies a proof for the consensus config index and returns the hash of the config.
+    fn verify_config_proof(&mut self, proof: &IndexProof) -> Result<Hash, Error> {
+        let (index_name, index_hash) = self.verify_index_proof(proof)?;
+        if index_name == CONSENSUS_CONFIG_INDEX {
+            Ok(index_hash)
+        } else {
+            Err(Error::UnexpectedIndex(index_name.to_owned()))
+        }
+    }
+
+    fn validator_keys_for(&self, block: &Block) -> Result<Vec<PublicKey>, Error> {
+        if block.is_skip() {
+            return Err(Error::BlockSkip);
+        }
+        self.validator_keys_at(block.height)
+            .ok_or_else(|| Error::UntrustedHeight {
+                height: block.height,
+                trusted_height: self.trusted_height(),
+            })
+    }
+
+    fn track_header(&mut self, block: &Block) -> Result<(), Error> {
+        if let Some(known_block) = self.headers.get(&block.height) {
+            if known_block != block {
+                return Err(Error::ConflictingHeader(block.height));
+            }
+        } else {
+            self.headers.insert(block.height, block.clone());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the light client.
+
+use chrono::Utc;
+use exonum::{
+    blockchain::{
+        AdditionalHeaders, Block, BlockProof, ConsensusConfig, Epoch, IndexProof, ProofError,
+        ProposerId, Schema, ValidatorKeys,
+    },
+    crypto::{hash, Hash, KeyPair},
+    helpers::{Height, Round, ValidatorId},
+    merkledb::{access::CopyAccessExt, Database, ObjectHash, SystemSchema, TemporaryDB},
+    messages::{Precommit, Verified},
+};
+use exonum_light_client::{Error, LightClient, CONSENSUS_CONFIG_INDEX};
+use pretty_assertions::assert_eq;
+
+fn create_keys() -> Vec<KeyPair> {
+    (0..4).map(|_| KeyPair::random()).collect()
+}
+
+fn create_config(keys: &[KeyPair]) -> ConsensusConfig {
+    let mut config = ConsensusConfig::default();
+    config.validator_keys = keys
+        .iter()
+        .map(|keys| ValidatorKeys::new(keys.public_key(), KeyPair::random().public_key()))
+        .collect();
+    config
+}
+
+fn create_block_proof(keys: &[KeyPair], height: Height, state_hash: Hash) -> BlockProof {
+    let mut block = Block {
+        height,
+        tx_count: 0,
+        prev_hash: Hash::zero(),
+        tx_hash: Hash::zero(),
+        state_hash,
+        error_hash: Hash::zero(),
+        additional_headers: AdditionalHeaders::new(),
+    };
+    block.add_header::<ProposerId>(ValidatorId(0));
+    block.add_header::<Epoch>(height);
+
+    let precommits = keys.iter().enumerate().map(|(i, keys)| {
+        let precommit = Precommit::new(
+            ValidatorId(i as u16),
+            height,
+            Round(1),
+            Hash::zero(),
+            block.object_hash(),
+            Utc::now(),
+        );
+        Verified::from_value(precommit, keys.public_key(), keys.secret_key())
+    });
+    BlockProof::new(block, precommits.collect())
+}
+
+/// Creates a proof for the consensus config entry in a block authorized by `keys`.
+fn create_config_proof(keys: &[KeyPair], height: Height, config: &ConsensusConfig) -> IndexProof {
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    Schema::new(&fork)
+        .consensus_config_entry()
+        .set(config.clone());
+    let patch = fork.into_patch();
+    let system_schema = SystemSchema::new(&patch);
+    let index_proof = system_schema
+        .state_aggregator()
+        .get_proof(CONSENSUS_CONFIG_INDEX.to_owned());
+    let block_proof = create_block_proof(keys, height, system_schema.state_hash());
+    IndexProof::new(block_proof, index_proof)
+}
+
+/// Creates proofs for a change of the consensus config at `height` authorized by `keys`.
+fn create_config_change(
+    keys: &[KeyPair],
+    height: Height,
+    old_config: &ConsensusConfig,
+    new_config: &ConsensusConfig,
+) -> (IndexProof, IndexProof) {
+    let previous_proof = create_config_proof(keys, height.previous(), old_config);
+    (
+        previous_proof,
+        create_config_proof(keys, height, new_config),
+    )
+}
+
+#[test]
+fn following_validator_set_changes() {
+    let (old_keys, new_keys) = (create_keys(), create_keys());
+    let (old_config, new_config) = (create_config(&old_keys), create_config(&new_keys));
+    let mut client = LightClient::new(old_config.clone(), Height(0));
+
+    let block_proof = create_block_proof(&old_keys, Height(5), Hash::zero());
+    let block = client.verify_block(&block_proof).unwrap();
+    assert_eq!(*block, block_proof.block);
+    assert_eq!(client.header(Height(5)), Some(&block_proof.block));
+
+    // The new validators are unknown to the client yet.
+    let new_block_proof = create_block_proof(&new_keys, Height(10), Hash::zero());
+    let err = client.verify_block(&new_block_proof).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Proof(ProofError::ValidatorKeyMismatch)
+    ));
+
+    let (previous_proof, config_proof) =
+        create_config_change(&old_keys, Height(7), &old_config, &new_config);
+    let activation_height = client
+        .apply_config_change(&previous_proof, &config_proof, new_config.clone())
+        .unwrap();
+    assert_eq!(activation_height, Height(8));
+    assert_eq!(*client.consensus_config(), new_config);
+    assert_eq!(client.consensus_config_at(Height(7)), Some(&old_config));
+    assert_eq!(client.consensus_config_at(Height(8)), Some(&new_config));
+    assert_eq!(
+        client.latest_header(),
+        Some(&config_proof.block_proof.block)
+    );
+
+    client.verify_block(&new_block_proof).unwrap();
+    assert_eq!(client.latest_header(), Some(&new_block_proof.block));
+    // Blocks before the change are still verified with the old validator set.
+    let block_proof = create_block_proof(&old_keys, Height(6), Hash::zero());
+    client.verify_block(&block_proof).unwrap();
+    let block_proof = create_block_proof(&old_keys, Height(9), Hash::zero());
+    let err = client.verify_block(&block_proof).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Proof(ProofError::ValidatorKeyMismatch)
+    ));
+
+    // Changes preceding the latest known one are rejected.
+    let (previous_proof, config_proof) =
+        create_config_change(&old_keys, Height(6), &new_config, &old_config);
+    let err = client
+        .apply_config_change(&previous_proof, &config_proof, old_config)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::OutdatedConfigChange {
+            height: Height(6),
+            latest_height: Height(8),
+        }
+    ));
+}
+
+#[test]
+fn config_change_drops_outdated_headers() {
+    let (old_keys, new_keys) = (create_keys(), create_keys());
+    let (old_config, new_config) = (create_config(&old_keys), create_config(&new_keys));
+    let mut client = LightClient::new(old_config.clone(), Height(0));
+
+    let block_proof = create_block_proof(&old_keys, Height(9), Hash::zero());
+    client.verify_block(&block_proof).unwrap();
+    let (previous_proof, config_proof) =
+        create_config_change(&old_keys, Height(7), &old_config, &new_config);
+    client
+        .apply_config_change(&previous_proof, &config_proof, new_config)
+        .unwrap();
+    assert_eq!(client.header(Height(9)), None);
+    assert_eq!(
+        client.latest_header(),
+        Some(&config_proof.block_proof.block)
+    );
+}
+
+#[test]
+fn invalid_config_changes() {
+    let (old_keys, new_keys) = (create_keys(), create_keys());
+    let (old_config, new_config) = (create_config(&old_keys), create_config(&new_keys));
+    let mut client = LightClient::new(old_config.clone(), Height(0));
+
+    // Config is not authorized by the current validators.
+    let (previous_proof, config_proof) =
+        create_config_change(&new_keys, Height(7), &old_config, &new_config);
+    let err = client
+        .apply_config_change(&previous_proof, &config_proof, new_config.clone())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::Proof(ProofError::ValidatorKeyMismatch)
+    ));
+
+    // Config differs from the proven one.
+    let (previous_proof, config_proof) =
+        create_config_change(&old_keys, Height(7), &old_config, &new_config);
+    let other_config = create_config(&create_keys());
+    let err = client
+        .apply_config_change(&previous_proof, &config_proof, other_config)
+        .unwrap_err();
+    assert!(matches!(err, Error::ConfigMismatch));
+
+    // Proof of the previous config refers to a wrong height.
+    let previous_proof = create_config_proof(&old_keys, Height(5), &old_config);
+    let err = client
+        .apply_config_change(&previous_proof, &config_proof, new_config.clone())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::PreviousHeightMismatch {
+            height: Height(7),
+            previous_height: Height(5),
+        }
+    ));
+
+    // Proof refers to another index.
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    fork.get_proof_list("test.list").extend(vec![1_u8, 2, 3]);
+    let patch = fork.into_patch();
+    let system_schema = SystemSchema::new(&patch);
+    let index_proof = system_schema
+        .state_aggregator()
+        .get_proof("test.list".to_owned());
+    let block_proof = create_block_proof(&old_keys, Height(8), system_schema.state_hash());
+    let config_proof = IndexProof::new(block_proof, index_proof);
+    let previous_proof = create_config_proof(&old_keys, Height(7), &old_config);
+    let err = client
+        .apply_config_change(&previous_proof, &config_proof, new_config)
+        .unwrap_err();
+    assert!(matches!(err, Error::UnexpectedIndex(ref name) if name == "test.list"));
+}
+
+#[test]
+fn stale_config_change() {
+    let (old_keys, new_keys) = (create_keys(), create_keys());
+    let (old_config, new_config) = (create_config(&old_keys), create_config(&new_keys));
+    let mut client = LightClient::new(old_config.clone(), Height(0));
+
+    // The config has been changed before height 9, so the proof for height 9
+    // must not be accepted as a change at this height.
+    let (previous_proof, config_proof) =
+        create_config_change(&old_keys, Height(9), &new_config, &new_config);
+    let err = client
+        .apply_config_change(&previous_proof, &config_proof, new_config)
+        .unwrap_err();
+    assert!(matches!(err, Error::UnchangedConfig(Height(9))));
+    assert_eq!(client.consensus_config_at(Height(10)), Some(&old_config));
+}
+
+#[test]
+fn conflicting_headers() {
+    let keys = create_keys();
+    let mut client = LightClient::new(create_config(&keys), Height(0));
+
+    let block_proof = create_block_proof(&keys, Height(5), Hash::zero());
+    client.verify_block(&block_proof).unwrap();
+    client.verify_block(&block_proof).unwrap();
+    let other_block_proof = create_block_proof(&keys, Height(5), hash(&[1, 2, 3]));
+    let err = client.verify_block(&other_block_proof).unwrap_err();
+    assert!(matches!(err, Error::ConflictingHeader(Height(5))));
+}
+
+#[test]
+fn untrusted_height() {
+    let keys = create_keys();
+    let mut client = LightClient::new(create_config(&keys), Height(10));
+    assert_eq!(client.trusted_height(), Height(10));
+
+    let block_proof = create_block_proof(&keys, Height(5), Hash::zero());
+    let err = client.verify_block(&block_proof).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::UntrustedHeight {
+            height: Height(5),
+            trusted_height: Height(10),
+        }
+    ));
+}
+
+#[test]
+fn client_serialization() {
+    let (old_keys, new_keys) = (create_keys(), create_keys());
+    let (old_config, new_config) = (create_config(&old_keys), create_config(&new_keys));
+    let mut client = LightClient::new(old_config.clone(), Height(0));
+    let (previous_proof, config_proof) =
+        create_config_change(&old_keys, Height(7), &old_config, &new_config);
+    client
+        .apply_config_change(&previous_proof, &config_proof, new_config)
+        .unwrap();
+
+    let json = serde_json::to_string(&client).unwrap();
+    let restored_client: LightClient = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored_client, client);
+}
@@ -18,8 +18,9 @@ use super::{Access, FromAccess};
 use crate::{
     indexes::proof_map::{Raw, ToProofPath},
     views::IndexType,
-    BinaryKey, BinaryValue, Entry, Group, IndexAddress, KeySetIndex, ListIndex, MapIndex,
-    ObjectHash, ProofEntry, ProofListIndex, ProofMapIndex, SparseListIndex, ValueSetIndex,
+    BinaryKey, BinaryValue, Entry, ExpiringMapIndex, Group, IndexAddress, KeySetIndex, ListIndex,
+    MapIndex, ObjectHash, ProofEntry, ProofListIndex, ProofMapIndex, SparseListIndex,
+    ValueSetIndex,
 };
 
 /// Extension trait allowing for easy access to indexes from any type implementing
@@ -233,6 +234,21 @@ pub trait CopyAccessExt: Access + Copy {
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
     }
 
+    /// Gets an expiring map index with the specified address.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not an expiring map.
+    fn get_expiring_map<I, K, V>(self, addr: I) -> ExpiringMapIndex<Self::Base, K, V>
+    where
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+        V: BinaryValue,
+    {
+        ExpiringMapIndex::from_access(self, addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
     /// Gets a key set index with the specified address.
     ///
     /// # Panics
@@ -456,6 +472,21 @@ pub trait AccessExt: Access {
             .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
     }
 
+    /// Gets an expiring map index with the specified address.
+    ///
+    /// # Panics
+    ///
+    /// If the index exists, but is not an expiring map.
+    fn get_expiring_map<I, K, V>(&self, addr: I) -> ExpiringMapIndex<Self::Base, K, V>
+    where
+        I: Into<IndexAddress>,
+        K: BinaryKey + ?Sized,
+        V: BinaryValue,
+    {
+        ExpiringMapIndex::from_access(self.clone(), addr.into())
+            .unwrap_or_else(|e| panic!("MerkleDB error: {}", e))
+    }
+
     /// Gets a key set index with the specified address.
     ///
     /// # Panics
@@ -60,7 +60,7 @@ use thiserror::Error;
 use std::fmt;
 
 pub use self::extensions::{AccessExt, CopyAccessExt};
-pub use crate::views::{AsReadonly, RawAccess, RawAccessMut};
+pub use crate::views::{AccessObserver, AsReadonly, RawAccess, RawAccessMut};
 
 use crate::{
     validation::assert_valid_name_component,
@@ -135,6 +135,9 @@ impl<T: RawAccess> Access for T {
         addr: IndexAddress,
         index_type: IndexType,
     ) -> Result<ViewWithMetadata<Self::Base>, AccessError> {
+        if let Some(observer) = self.observer() {
+            observer.on_index_access(&addr);
+        }
         ViewWithMetadata::get_or_create(self, &addr, index_type)
     }
 
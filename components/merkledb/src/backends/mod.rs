@@ -13,4 +13,5 @@
 // limitations under the License.
 
 pub mod rocksdb;
+pub(crate) mod spill;
 pub mod temporarydb;
@@ -35,8 +35,9 @@ use std::{
 };
 
 use crate::{
-    db::{check_database, Change},
-    Database, DbOptions, Iter, Iterator, Patch, ResolvedAddress, Snapshot,
+    db::{check_database, check_snapshot, Change},
+    hashing_pool::HashingPool,
+    Database, DbOptions, Fork, Iter, Iterator, Patch, ResolvedAddress, Snapshot,
 };
 
 /// Size of a byte representation of an index ID, which is used to prefix index keys
@@ -59,7 +60,7 @@ pub(super) struct NoDropOnShutdown<T> {
 }
 
 impl<T> NoDropOnShutdown<T> {
-    fn new(inner: T) -> Self {
+    pub(super) fn new(inner: T) -> Self {
         Self {
             inner: mem::ManuallyDrop::new(inner),
         }
@@ -103,6 +104,9 @@ type InnerDB = NoDropOnShutdown<rocksdb::DB>;
 pub struct RocksDB {
     db: Arc<ShardedLock<InnerDB>>,
     options: DbOptions,
+    /// Thread pool shared by all forks of the database to recompute hashes
+    /// of changed proof indexes.
+    hashing_pool: Option<Arc<HashingPool>>,
 }
 
 impl From<DbOptions> for RocksDbOptions {
@@ -151,14 +155,49 @@ impl RocksDB {
                 rocksdb::DB::open(&options.into(), path)?
             }
         };
+        let hashing_pool = if options.hashing_threads > 1 {
+            Some(Arc::new(HashingPool::new(options.hashing_threads)))
+        } else {
+            None
+        };
         let mut db = Self {
             db: Arc::new(ShardedLock::new(NoDropOnShutdown::new(inner))),
             options: *options,
+            hashing_pool,
         };
         check_database(&mut db)?;
         Ok(db)
     }
 
+    /// Opens an existing database stored at the specified path and returns a snapshot
+    /// of its current state.
+    ///
+    /// Unlike [`open`](#method.open), this method never creates a database and does not
+    /// write any MerkleDB data (e.g., the database version) to it; the `create_if_missing`
+    /// option is ignored. Note that RocksDB still opens the database in the read-write mode,
+    /// so it may write its own service files (logs, manifest, etc.) and may replay
+    /// the write-ahead log. The database remains locked while the snapshot is alive,
+    /// so it cannot be used by another process (e.g., a running node) at the same time.
+    pub fn open_snapshot<P: AsRef<Path>>(
+        path: P,
+        options: &DbOptions,
+    ) -> crate::Result<Box<dyn Snapshot>> {
+        let mut options = *options;
+        options.create_if_missing = false;
+
+        let names = rocksdb::DB::list_cf(&RocksDbOptions::default(), &path)?;
+        let cf_names = names.iter().map(String::as_str).collect::<Vec<_>>();
+        let inner = rocksdb::DB::open_cf(&options.into(), path, cf_names)?;
+        let db = Self {
+            db: Arc::new(ShardedLock::new(NoDropOnShutdown::new(inner))),
+            options,
+            hashing_pool: None,
+        };
+        let snapshot = db.snapshot();
+        check_snapshot(snapshot.as_ref())?;
+        Ok(snapshot)
+    }
+
     /// Creates checkpoint of this database in the given directory. See [`RocksDB` docs] for
     /// details.
     ///
@@ -328,6 +367,13 @@ impl Database for RocksDB {
         Box::new(self.rocksdb_snapshot())
     }
 
+    fn fork(&self) -> Fork {
+        let mut fork = Fork::new(self.snapshot());
+        fork.set_hashing_pool(self.hashing_pool.clone());
+        fork.set_spill_threshold(self.options.spill_threshold);
+        fork
+    }
+
     fn merge(&self, patch: Patch) -> crate::Result<()> {
         let w_opts = RocksDBWriteOptions::default();
         self.do_merge(patch, &w_opts)
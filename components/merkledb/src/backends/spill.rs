@@ -0,0 +1,329 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Temporary on-disk storage for `Patch` changes that are too large to be kept in memory.
+
+use rocksdb::{Direction, IteratorMode, WriteBatch};
+use tempfile::TempDir;
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    iter::{Iterator as StdIterator, Peekable},
+    mem,
+    result::Result as StdResult,
+};
+
+use super::rocksdb::NoDropOnShutdown;
+use crate::{
+    db::{Change, ViewChanges},
+    Error, Iter, Iterator, ResolvedAddress, Result,
+};
+
+/// Size of the prefix identifying a view in the spill storage.
+const ID_SIZE: usize = mem::size_of::<u64>();
+/// Tag of a serialized `Change::Delete`.
+const DELETE_TAG: u8 = 0;
+/// Tag of a serialized `Change::Put(_)`.
+const PUT_TAG: u8 = 1;
+
+/// Information about the spilled changes of a single view.
+#[derive(Debug, Clone, Copy)]
+struct SpilledView {
+    /// Prefix of the view keys in the spill storage.
+    id: u64,
+    /// Was the view cleared as a part of the spilled changes?
+    is_cleared: bool,
+}
+
+impl SpilledView {
+    fn key(self, key: &[u8]) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(ID_SIZE + key.len());
+        buffer.extend_from_slice(&self.id.to_be_bytes());
+        buffer.extend_from_slice(key);
+        buffer
+    }
+}
+
+/// Changes moved out of a `Patch` into a temporary RocksDB instance.
+///
+/// The spilled changes form a layer between the in-memory changes of the patch
+/// and the underlying snapshot; their semantics are the same as of `ViewChanges`.
+pub(crate) struct SpilledChanges {
+    db: NoDropOnShutdown<rocksdb::DB>,
+    views: HashMap<ResolvedAddress, SpilledView>,
+    next_id: u64,
+    // The directory is removed on drop, so it must be declared after `db`.
+    _dir: TempDir,
+}
+
+impl fmt::Debug for SpilledChanges {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("SpilledChanges")
+            .field("path", &self._dir.path())
+            .field("views", &self.views.len())
+            .finish()
+    }
+}
+
+impl SpilledChanges {
+    /// Creates an empty spill storage in a new temporary directory.
+    pub fn new() -> Result<Self> {
+        let dir = TempDir::new().map_err(|e| Error::new(e.to_string()))?;
+        let mut options = rocksdb::Options::default();
+        options.create_if_missing(true);
+        let db = rocksdb::DB::open(&options, dir.path())?;
+        Ok(Self {
+            db: NoDropOnShutdown::new(db),
+            views: HashMap::new(),
+            next_id: 0,
+            _dir: dir,
+        })
+    }
+
+    /// Moves `changes` to the storage. The changes are considered newer than the ones
+    /// already in the storage.
+    pub fn spill(&mut self, changes: HashMap<ResolvedAddress, ViewChanges>) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        for (address, changes) in changes {
+            let view = if changes.is_cleared() {
+                // Older changes do not matter; we allocate a new prefix for the view
+                // and remove records with the old one.
+                if let Some(old_view) = self.views.get(&address) {
+                    let start = old_view.id.to_be_bytes();
+                    let end = (old_view.id + 1).to_be_bytes();
+                    batch.delete_range(&start[..], &end[..])?;
+                }
+                let view = self.allocate_view(true);
+                self.views.insert(address, view);
+                view
+            } else if let Some(view) = self.views.get(&address) {
+                *view
+            } else {
+                let view = self.allocate_view(false);
+                self.views.insert(address, view);
+                view
+            };
+
+            for (key, change) in changes.into_data() {
+                batch.put(view.key(&key), encode_change(&change))?;
+            }
+        }
+        self.db.write(batch).map_err(Into::into)
+    }
+
+    fn allocate_view(&mut self, is_cleared: bool) -> SpilledView {
+        let id = self.next_id;
+        self.next_id += 1;
+        SpilledView { id, is_cleared }
+    }
+
+    /// Returns a value for the specified key, or an `Err(_)` if the value should be determined
+    /// by the underlying snapshot.
+    pub fn get(&self, address: &ResolvedAddress, key: &[u8]) -> StdResult<Option<Vec<u8>>, ()> {
+        let view = self.views.get(address).ok_or(())?;
+        let record = self
+            .db
+            .get(view.key(key))
+            .unwrap_or_else(|e| panic!("Cannot read spilled changes: {}", e));
+        match record {
+            Some(record) => Ok(match decode_change(&record) {
+                Change::Put(value) => Some(value),
+                Change::Delete => None,
+            }),
+            None if view.is_cleared => Ok(None),
+            None => Err(()),
+        }
+    }
+
+    /// Returns whether the view contains the specified `key`. An `Err(_)` is returned if this
+    /// is determined by the underlying snapshot.
+    pub fn contains(&self, address: &ResolvedAddress, key: &[u8]) -> StdResult<bool, ()> {
+        self.get(address, key).map(|value| value.is_some())
+    }
+
+    /// Returns an iterator over the view merging the spilled changes with the iterator
+    /// produced by `base`. `base` is not called if the view was cleared.
+    pub fn iter<'a>(
+        &'a self,
+        address: &ResolvedAddress,
+        from: &[u8],
+        base: impl FnOnce() -> Iter<'a>,
+    ) -> Iter<'a> {
+        let view = match self.views.get(address) {
+            Some(view) => *view,
+            None => return base(),
+        };
+
+        let prefix = view.id.to_be_bytes();
+        let records = self
+            .db
+            .iterator(IteratorMode::From(&view.key(from), Direction::Forward))
+            .take_while(move |(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| (key[ID_SIZE..].to_vec(), decode_change(&value)));
+
+        Box::new(SpilledIter {
+            base: if view.is_cleared { None } else { Some(base()) },
+            records: (Box::new(records) as Box<dyn StdIterator<Item = _> + 'a>).peekable(),
+            current: None,
+        })
+    }
+
+    /// Forgets spilled changes for the specified view.
+    pub fn remove(&mut self, address: &ResolvedAddress) {
+        self.views.remove(address);
+    }
+
+    /// Removes spilled changes for the specified view from the storage and loads them
+    /// into memory.
+    pub fn take(&mut self, address: &ResolvedAddress) -> Option<ViewChanges> {
+        let view = self.views.remove(address)?;
+        let prefix = view.id.to_be_bytes();
+        let data: BTreeMap<_, _> = self
+            .db
+            .iterator(IteratorMode::From(&prefix, Direction::Forward))
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .map(|(key, value)| (key[ID_SIZE..].to_vec(), decode_change(&value)))
+            .collect();
+        Some(ViewChanges::from_data(data, view.is_cleared))
+    }
+
+    /// Removes spilled changes for an arbitrary view and loads them into memory.
+    /// Returns `None` if the storage is empty.
+    pub fn take_any(&mut self) -> Option<(ResolvedAddress, ViewChanges)> {
+        let address = self.views.keys().next()?.clone();
+        let changes = self.take(&address)?;
+        Some((address, changes))
+    }
+}
+
+fn encode_change(change: &Change) -> Vec<u8> {
+    match change {
+        Change::Put(value) => {
+            let mut buffer = Vec::with_capacity(1 + value.len());
+            buffer.push(PUT_TAG);
+            buffer.extend_from_slice(value);
+            buffer
+        }
+        Change::Delete => vec![DELETE_TAG],
+    }
+}
+
+fn decode_change(bytes: &[u8]) -> Change {
+    match bytes.split_first() {
+        Some((&PUT_TAG, value)) => Change::Put(value.to_vec()),
+        Some((&DELETE_TAG, _)) => Change::Delete,
+        _ => panic!("Invalid spilled change: {:?}", bytes),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    /// The next entry is taken from the base iterator.
+    Base,
+    /// The next entry is taken from the spilled records. If `overrides_base` is set,
+    /// the record shadows the current entry of the base iterator.
+    Record {
+        overrides_base: bool,
+    },
+    Finished,
+}
+
+/// Iterator merging spilled changes with the underlying iterator.
+struct SpilledIter<'a> {
+    base: Option<Iter<'a>>,
+    records: Peekable<Box<dyn StdIterator<Item = (Vec<u8>, Change)> + 'a>>,
+    /// Storage for the latest entry returned from `records` by `next()`.
+    current: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl SpilledIter<'_> {
+    fn step(&mut self) -> Step {
+        let record_key = self.records.peek().map(|(key, _)| key.as_slice());
+        let base_key = self
+            .base
+            .as_mut()
+            .and_then(|base| base.peek().map(|(key, _)| key));
+
+        match (record_key, base_key) {
+            (None, None) => Step::Finished,
+            (None, Some(_)) => Step::Base,
+            (Some(_), None) => Step::Record {
+                overrides_base: false,
+            },
+            (Some(record_key), Some(base_key)) => match record_key.cmp(base_key) {
+                Ordering::Less => Step::Record {
+                    overrides_base: false,
+                },
+                Ordering::Equal => Step::Record {
+                    overrides_base: true,
+                },
+                Ordering::Greater => Step::Base,
+            },
+        }
+    }
+
+    fn skip_base(&mut self, overrides_base: bool) {
+        if overrides_base {
+            self.base.as_mut().unwrap().next();
+        }
+    }
+}
+
+impl Iterator for SpilledIter<'_> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        loop {
+            match self.step() {
+                Step::Base => return self.base.as_mut().unwrap().next(),
+                Step::Record { overrides_base } => {
+                    self.skip_base(overrides_base);
+                    if let Some((key, Change::Put(value))) = self.records.next() {
+                        self.current = Some((key, value));
+                        return self
+                            .current
+                            .as_ref()
+                            .map(|(key, value)| (key.as_slice(), value.as_slice()));
+                    }
+                }
+                Step::Finished => return None,
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        loop {
+            match self.step() {
+                Step::Base => return self.base.as_mut().unwrap().peek(),
+                Step::Record { overrides_base } => {
+                    let is_put = match self.records.peek() {
+                        Some((_, Change::Put(_))) => true,
+                        _ => false,
+                    };
+                    if is_put {
+                        return self.records.peek().map(|(key, change)| match change {
+                            Change::Put(value) => (key.as_slice(), value.as_slice()),
+                            Change::Delete => unreachable!(),
+                        });
+                    }
+                    // The record is a deletion; skip it together with the shadowed base entry.
+                    self.skip_base(overrides_base);
+                    self.records.next();
+                }
+                Step::Finished => return None,
+            }
+        }
+    }
+}
@@ -22,9 +22,14 @@ use std::{
     ops::{Bound, Deref, DerefMut},
     rc::Rc,
     result::Result as StdResult,
+    sync::Arc,
 };
 
+use exonum_crypto::Hash;
+
 use crate::{
+    backends::spill::SpilledChanges,
+    hashing_pool::HashingPool,
     validation::assert_valid_name_component,
     views::{
         get_object_hash, AsReadonly, ChangesIter, IndexesPool, RawAccess, ResolvedAddress, View,
@@ -68,10 +73,40 @@ impl ViewChanges {
         self.namespace = namespace;
     }
 
+    pub(crate) fn from_data(data: BTreeMap<Vec<u8>, Change>, is_cleared: bool) -> Self {
+        Self {
+            data,
+            is_cleared,
+            namespace: None,
+        }
+    }
+
     pub(crate) fn into_data(self) -> BTreeMap<Vec<u8>, Change> {
         self.data
     }
 
+    /// Returns the approximate size of the changes in bytes.
+    fn data_size(&self) -> usize {
+        self.data
+            .iter()
+            .map(|(key, change)| match change {
+                Change::Put(value) => key.len() + value.len(),
+                Change::Delete => key.len(),
+            })
+            .sum()
+    }
+
+    /// Extends these changes with newer `changes`, relying on the fact that newer changes
+    /// override older ones. If the view was cleared as a part of newer changes, the old changes
+    /// do not matter and are forgotten.
+    fn merge(&mut self, changes: Self) {
+        if changes.is_cleared() {
+            *self = changes;
+        } else {
+            self.data.extend(changes.data);
+        }
+    }
+
     /// Returns a value for the specified key, or an `Err(_)` if the value should be determined
     /// by the underlying snapshot.
     pub fn get(&self, key: &[u8]) -> StdResult<Option<Vec<u8>>, ()> {
@@ -278,18 +313,13 @@ impl WorkingPatch {
             }
 
             // The patch may already contain changes related to the `address`. If it does,
-            // we extend these changes with the new changes (relying on the fact that
-            // newer changes override older ones), unless the view was cleared (in which case,
-            // the old changes do not matter and should be forgotten).
-            let patch_changes = patch
+            // we extend these changes with the new changes.
+            patch.in_memory_size += changes.data_size();
+            patch
                 .changes
                 .entry(address)
-                .or_insert_with(ViewChanges::new);
-            if changes.is_cleared() {
-                *patch_changes = changes;
-            } else {
-                patch_changes.data.extend(changes.data);
-            }
+                .or_insert_with(ViewChanges::new)
+                .merge(changes);
         }
     }
 }
@@ -427,6 +457,17 @@ pub struct Patch {
     changed_aggregated_addrs: HashMap<ResolvedAddress, String>,
     /// Names of removed aggregated indexes.
     removed_aggregated_addrs: HashSet<String>,
+    /// Thread pool used to recompute hashes of changed aggregated indexes
+    /// in `Fork::into_patch()`. If not set, hashes are computed in the calling thread.
+    hashing_pool: Option<Arc<HashingPool>>,
+    /// Changes moved to the temporary on-disk storage. These changes are older than
+    /// the ones in `changes`.
+    spilled: Option<SpilledChanges>,
+    /// Approximate size of `changes` in bytes, after which they are spilled to disk
+    /// in `Fork::flush()`.
+    spill_threshold: Option<usize>,
+    /// Approximate size of `changes` in bytes.
+    in_memory_size: usize,
 }
 
 pub(super) struct ForkIter<'a, T: StdIterator> {
@@ -480,15 +521,7 @@ pub trait Database: Send + Sync + 'static {
 
     /// Creates a new fork of the database from its current state.
     fn fork(&self) -> Fork {
-        Fork {
-            patch: Patch {
-                snapshot: self.snapshot(),
-                changes: HashMap::new(),
-                changed_aggregated_addrs: HashMap::new(),
-                removed_aggregated_addrs: HashSet::new(),
-            },
-            working_patch: WorkingPatch::new(),
-        }
+        Fork::new(self.snapshot())
     }
 
     /// Atomically applies a sequence of patch changes to the database.
@@ -568,9 +601,11 @@ pub trait DatabaseExt: Database {
     /// # Errors
     ///
     /// Returns an error in the same situations as `Database::merge()`.
-    fn merge_with_backup(&self, patch: Patch) -> Result<Patch> {
+    fn merge_with_backup(&self, mut patch: Patch) -> Result<Patch> {
         // FIXME: does this work with migrations? (ECR-4005)
 
+        // Reverse changes are computed in memory, so we need to load spilled changes as well.
+        patch.load_spilled();
         let snapshot = self.snapshot();
         let changed_aggregated_addrs = patch.changed_aggregated_addrs.clone();
         let mut rev_changes = HashMap::with_capacity(patch.changes.len());
@@ -603,12 +638,18 @@ pub trait DatabaseExt: Database {
             );
         }
 
+        let hashing_pool = patch.hashing_pool.clone();
+        let spill_threshold = patch.spill_threshold;
         self.merge(patch)?;
         Ok(Patch {
             snapshot: self.snapshot(),
             changes: rev_changes,
             changed_aggregated_addrs,
             removed_aggregated_addrs: HashSet::new(),
+            hashing_pool,
+            spilled: None,
+            spill_threshold,
+            in_memory_size: 0,
         })
     }
 }
@@ -649,9 +690,104 @@ pub trait Iterator {
 }
 
 impl Patch {
-    /// Iterates over changes in this patch.
-    pub(crate) fn into_changes(self) -> HashMap<ResolvedAddress, ViewChanges> {
-        self.changes
+    /// Iterates over changes in this patch. Spilled changes are loaded into memory
+    /// one view at a time.
+    pub(crate) fn into_changes(self) -> PatchChanges {
+        PatchChanges {
+            in_memory: self.changes.into_iter(),
+            spilled: self.spilled,
+        }
+    }
+
+    /// Moves in-memory changes to the temporary on-disk storage.
+    fn spill(&mut self) -> Result<()> {
+        if self.spilled.is_none() {
+            self.spilled = Some(SpilledChanges::new()?);
+        }
+        let spilled = self.spilled.as_mut().unwrap();
+        spilled.spill(mem::replace(&mut self.changes, HashMap::new()))?;
+        self.in_memory_size = 0;
+        Ok(())
+    }
+
+    /// Loads all spilled changes into memory.
+    fn load_spilled(&mut self) {
+        let mut spilled = match self.spilled.take() {
+            Some(spilled) => spilled,
+            None => return,
+        };
+        while let Some((address, mut changes)) = spilled.take_any() {
+            if let Some(newer_changes) = self.changes.remove(&address) {
+                changes.merge(newer_changes);
+            }
+            self.in_memory_size += changes.data_size();
+            self.changes.insert(address, changes);
+        }
+    }
+
+    /// Computes the hash of a single changed aggregated index in the form
+    /// `(namespace, index_name, index_hash)`.
+    fn hash_index(&self, (addr, ns): &(ResolvedAddress, String)) -> (String, String, Hash) {
+        let index_name = addr.name.clone();
+        let is_in_migration = !ns.is_empty();
+        let index_hash = get_object_hash(self, addr.clone(), is_in_migration);
+        (ns.clone(), index_name, index_hash)
+    }
+
+    /// Computes hashes of the changed aggregated indexes. The returned entries have
+    /// the form `(namespace, index_name, index_hash)` and are sorted by the index address,
+    /// so the output does not depend on the number of threads.
+    ///
+    /// If the patch has a hashing pool, indexes are split into contiguous chunks
+    /// which are hashed in parallel on the pool threads. Each index is hashed independently
+    /// of others, since hashing only reads from the patch. The patch is shared with
+    /// the pool for the duration of hashing and is returned back afterwards.
+    fn hash_aggregated_indexes(
+        self,
+        addrs: HashMap<ResolvedAddress, String>,
+    ) -> (Self, Vec<(String, String, Hash)>) {
+        /// Minimum number of changed indexes for which parallel hashing makes sense.
+        /// For smaller patches, the overhead of dispatching jobs to the pool dominates.
+        const MIN_PARALLEL_INDEXES: usize = 8;
+
+        let mut addrs: Vec<_> = addrs.into_iter().collect();
+        addrs.sort_unstable_by(|(x, _), (y, _)| (&x.name, x.id).cmp(&(&y.name, y.id)));
+
+        let pool = match self.hashing_pool {
+            Some(ref pool) if pool.threads() > 1 && addrs.len() >= MIN_PARALLEL_INDEXES => {
+                Arc::clone(pool)
+            }
+            _ => {
+                let entries = addrs.iter().map(|addr| self.hash_index(addr)).collect();
+                return (self, entries);
+            }
+        };
+
+        let threads = pool.threads().min(addrs.len());
+        let chunk_size = (addrs.len() + threads - 1) / threads;
+        let patch = Arc::new(self);
+        let receivers: Vec<_> = addrs
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let patch = Arc::clone(&patch);
+                let chunk = chunk.to_vec();
+                pool.execute(move || {
+                    chunk
+                        .iter()
+                        .map(|addr| patch.hash_index(addr))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        // Receiving outputs in the order of dispatching retains the sorted order of entries.
+        let entries = receivers
+            .into_iter()
+            .flat_map(|receiver| receiver.recv().expect("Hashing thread panicked"))
+            .collect();
+
+        // Jobs release their references to the patch before sending outputs.
+        let patch = Arc::try_unwrap(patch).expect("Patch is still shared with hashing threads");
+        (patch, entries)
     }
 }
 
@@ -660,6 +796,11 @@ impl Snapshot for Patch {
         self.changes
             .get(name)
             .map_or(Err(()), |changes| changes.get(key))
+            .or_else(|()| {
+                self.spilled
+                    .as_ref()
+                    .map_or(Err(()), |spilled| spilled.get(name, key))
+            })
             // At this point, `Err(_)` signifies that we need to retrieve data from the snapshot.
             .unwrap_or_else(|()| self.snapshot.get(name, key))
     }
@@ -668,6 +809,11 @@ impl Snapshot for Patch {
         self.changes
             .get(name)
             .map_or(Err(()), |changes| changes.contains(key))
+            .or_else(|()| {
+                self.spilled
+                    .as_ref()
+                    .map_or(Err(()), |spilled| spilled.contains(name, key))
+            })
             // At this point, `Err(_)` signifies that we need to retrieve data from the snapshot.
             .unwrap_or_else(|()| self.snapshot.contains(name, key))
     }
@@ -685,8 +831,42 @@ impl Snapshot for Patch {
             // Ignore all changes from the snapshot.
             Box::new(ChangesIter::new(changes_iter.unwrap()))
         } else {
-            Box::new(ForkIter::new(self.snapshot.iter(name, from), changes_iter))
+            let base_iter = match self.spilled {
+                Some(ref spilled) => spilled.iter(name, from, || self.snapshot.iter(name, from)),
+                None => self.snapshot.iter(name, from),
+            };
+            Box::new(ForkIter::new(base_iter, changes_iter))
+        }
+    }
+}
+
+/// Iterator over changes in a `Patch` grouped by view.
+#[derive(Debug)]
+pub(crate) struct PatchChanges {
+    in_memory: std::collections::hash_map::IntoIter<ResolvedAddress, ViewChanges>,
+    spilled: Option<SpilledChanges>,
+}
+
+impl StdIterator for PatchChanges {
+    type Item = (ResolvedAddress, ViewChanges);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((address, changes)) = self.in_memory.next() {
+            let spilled_changes = self
+                .spilled
+                .as_mut()
+                .and_then(|spilled| spilled.take(&address));
+            let changes = match spilled_changes {
+                Some(mut spilled_changes) => {
+                    spilled_changes.merge(changes);
+                    spilled_changes
+                }
+                None => changes,
+            };
+            return Some((address, changes));
         }
+        // All remaining spilled views have no in-memory changes.
+        self.spilled.as_mut()?.take_any()
     }
 }
 
@@ -709,12 +889,134 @@ impl AsReadonly for &'_ Patch {
 }
 
 impl Fork {
+    /// Creates a fork with no changes on top of the provided snapshot.
+    pub(crate) fn new(snapshot: Box<dyn Snapshot>) -> Self {
+        Self {
+            patch: Patch {
+                snapshot,
+                changes: HashMap::new(),
+                changed_aggregated_addrs: HashMap::new(),
+                removed_aggregated_addrs: HashSet::new(),
+                hashing_pool: None,
+                spilled: None,
+                spill_threshold: None,
+                in_memory_size: 0,
+            },
+            working_patch: WorkingPatch::new(),
+        }
+    }
+
+    /// Sets the number of threads used to recompute hashes of changed proof indexes
+    /// when the fork is converted into a `Patch`. Values `0` and `1` mean that the hashes
+    /// are computed sequentially in the calling thread.
+    ///
+    /// This method spawns a dedicated thread pool for the fork. Forks created by `RocksDB`
+    /// instead share a single pool sized by [`DbOptions::hashing_threads`], so this method
+    /// is only needed to override the database setting.
+    ///
+    /// The setting is retained when the fork is converted into a `Patch` and back.
+    /// It does not influence the resulting state hash, only the time required to compute it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum_merkledb::{access::CopyAccessExt, Database, ObjectHash, SystemSchema};
+    /// # use exonum_merkledb::TemporaryDB;
+    /// let db = TemporaryDB::new();
+    /// let mut fork = db.fork();
+    /// fork.set_hashing_threads(4);
+    /// for i in 0_u32..16 {
+    ///     fork.get_proof_map(format!("map_{}", i)).put(&i, i.to_string());
+    /// }
+    /// let patch = fork.into_patch();
+    /// let aggregator = SystemSchema::new(&patch).state_aggregator();
+    /// let map = patch.get_proof_map::<_, u32, String>("map_3");
+    /// assert_eq!(aggregator.get("map_3"), Some(map.object_hash()));
+    /// ```
+    ///
+    /// [`DbOptions::hashing_threads`]: struct.DbOptions.html#structfield.hashing_threads
+    pub fn set_hashing_threads(&mut self, threads: usize) {
+        let pool = if threads > 1 {
+            Some(Arc::new(HashingPool::new(threads)))
+        } else {
+            None
+        };
+        self.set_hashing_pool(pool);
+    }
+
+    /// Sets the thread pool shared with other forks, which is used to recompute hashes
+    /// of changed proof indexes in [`into_patch`](#method.into_patch).
+    pub(crate) fn set_hashing_pool(&mut self, pool: Option<Arc<HashingPool>>) {
+        self.patch.hashing_pool = pool;
+    }
+
+    /// Returns the number of threads used to recompute hashes of changed proof indexes
+    /// in [`into_patch`](#method.into_patch).
+    pub fn hashing_threads(&self) -> usize {
+        self.patch
+            .hashing_pool
+            .as_ref()
+            .map_or(1, |pool| pool.threads())
+    }
+
+    /// Sets the approximate size of finalized changes (in bytes) after which the changes
+    /// are moved from memory to a temporary on-disk storage. `None` (the default) means
+    /// that the changes are always kept in memory.
+    ///
+    /// Changes are only moved to disk in [`flush`](#method.flush), so the changes made
+    /// after the latest flush are kept in memory regardless of their size. Spilled changes
+    /// remain readable via the fork and via the `Patch` produced by it; they are written
+    /// to the database in the same atomic batch as the in-memory changes.
+    ///
+    /// The setting is retained when the fork is converted into a `Patch` and back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum_merkledb::{access::CopyAccessExt, Database, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let mut fork = db.fork();
+    /// fork.set_spill_threshold(Some(1_024));
+    /// fork.get_list("list").extend(vec![[0_u8; 64]; 100]);
+    /// // Changes are moved to disk here.
+    /// fork.flush();
+    /// assert_eq!(fork.get_list::<_, [u8; 64]>("list").len(), 100);
+    ///
+    /// db.merge(fork.into_patch()).unwrap();
+    /// let snapshot = db.snapshot();
+    /// assert_eq!(snapshot.get_list::<_, [u8; 64]>("list").len(), 100);
+    /// ```
+    pub fn set_spill_threshold(&mut self, threshold: Option<usize>) {
+        self.patch.spill_threshold = threshold;
+    }
+
+    /// Returns the size of changes after which they are moved to a temporary on-disk storage.
+    pub fn spill_threshold(&self) -> Option<usize> {
+        self.patch.spill_threshold
+    }
+
     /// Finalizes all changes that were made after previous execution of the `flush` method.
     /// If no `flush` method had been called before, finalizes all changes that were
     /// made after creation of `Fork`.
+    ///
+    /// If the size of finalized changes exceeds the [spill threshold], the changes are
+    /// moved to a temporary on-disk storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the changes cannot be written to the temporary storage.
+    ///
+    /// [spill threshold]: #method.set_spill_threshold
     pub fn flush(&mut self) {
         let working_patch = mem::replace(&mut self.working_patch, WorkingPatch::new());
         working_patch.merge_into(&mut self.patch);
+
+        let threshold = self.patch.spill_threshold;
+        if threshold.map_or(false, |threshold| self.patch.in_memory_size > threshold) {
+            self.patch
+                .spill()
+                .unwrap_or_else(|e| panic!("Cannot spill fork changes to disk: {}", e));
+        }
     }
 
     /// Finishes a migration of indexes with the specified prefix.
@@ -761,6 +1063,9 @@ impl Fork {
         for addr in &removed_addrs {
             self.patch.changed_aggregated_addrs.remove(addr);
             self.patch.changes.remove(addr);
+            if let Some(ref mut spilled) = self.patch.spilled {
+                spilled.remove(addr);
+            }
         }
     }
 
@@ -773,12 +1078,8 @@ impl Fork {
         // its state aggregator unless the *new* changes in the `Fork` concern aggregated indexes.
         let changed_aggregated_addrs =
             mem::replace(&mut self.patch.changed_aggregated_addrs, HashMap::new());
-        let updated_entries = changed_aggregated_addrs.into_iter().map(|(addr, ns)| {
-            let index_name = addr.name.clone();
-            let is_in_migration = !ns.is_empty();
-            let index_hash = get_object_hash(&self.patch, addr, is_in_migration);
-            (ns, index_name, index_hash)
-        });
+        let (patch, updated_entries) = self.patch.hash_aggregated_indexes(changed_aggregated_addrs);
+        self.patch = patch;
         SystemSchema::new(&self).update_state_aggregators(updated_entries);
 
         let removed_aggregated_addrs =
@@ -1146,14 +1447,7 @@ pub fn check_database(db: &mut dyn Database) -> Result<()> {
         let addr = ResolvedAddress::system(DB_METADATA);
         let mut view = View::new(&fork, addr);
         if let Some(saved_version) = view.get::<_, u8>(VERSION_NAME) {
-            if saved_version != DB_VERSION {
-                return Err(Error::new(format!(
-                    "Database version doesn't match: actual {}, expected {}",
-                    saved_version, DB_VERSION
-                )));
-            }
-
-            return Ok(());
+            return check_version(saved_version);
         } else {
             view.put(VERSION_NAME, DB_VERSION);
         }
@@ -1161,10 +1455,33 @@ pub fn check_database(db: &mut dyn Database) -> Result<()> {
     db.merge(fork.into_patch())
 }
 
+/// Checks that the database snapshot is compatible with the current `MerkleDB` version.
+/// Unlike `check_database`, this function never writes to the database and returns an error
+/// if the version is not recorded in the snapshot.
+pub(crate) fn check_snapshot(snapshot: &dyn Snapshot) -> Result<()> {
+    let addr = ResolvedAddress::system(DB_METADATA);
+    let view = View::new(snapshot, addr);
+    let saved_version = view
+        .get::<_, u8>(VERSION_NAME)
+        .ok_or_else(|| Error::new("Database is not initialized"))?;
+    check_version(saved_version)
+}
+
+fn check_version(saved_version: u8) -> Result<()> {
+    if saved_version == DB_VERSION {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "Database version doesn't match: actual {}, expected {}",
+            saved_version, DB_VERSION
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{access::CopyAccessExt, ObjectHash, TemporaryDB};
+    use crate::{access::CopyAccessExt, DbOptions, ObjectHash, RocksDB, TemporaryDB};
 
     use std::{collections::HashSet, iter::FromIterator};
 
@@ -1402,6 +1719,187 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parallel_hashing_is_deterministic() {
+        fn fill_fork(fork: &Fork) {
+            for i in 0_u64..50 {
+                let mut map = fork.get_proof_map(format!("map_{}", i));
+                for j in 0..i {
+                    map.put(&j, i * j);
+                }
+                fork.get_proof_list(format!("list_{}", i)).extend(0..i);
+            }
+        }
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fill_fork(&fork);
+        let sequential_patch = fork.into_patch();
+        let sequential_hash = SystemSchema::new(&sequential_patch).state_hash();
+
+        for &threads in &[2, 3, 8, 64] {
+            let mut fork = db.fork();
+            fork.set_hashing_threads(threads);
+            fill_fork(&fork);
+            let patch = fork.into_patch();
+            assert_eq!(patch.hashing_pool.as_ref().unwrap().threads(), threads);
+            assert_eq!(SystemSchema::new(&patch).state_hash(), sequential_hash);
+        }
+
+        let aggregator = SystemSchema::new(&sequential_patch).state_aggregator();
+        assert_eq!(
+            aggregator.get("map_10").unwrap(),
+            sequential_patch
+                .get_proof_map::<_, u64, u64>("map_10")
+                .object_hash()
+        );
+    }
+
+    #[test]
+    fn database_forks_share_hashing_pool() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut options = DbOptions::default();
+        options.hashing_threads = 4;
+        let db = RocksDB::open(dir.path(), &options).unwrap();
+
+        let pool = Arc::clone(db.fork().patch.hashing_pool.as_ref().unwrap());
+        assert_eq!(pool.threads(), 4);
+        for i in 0_u64..3 {
+            let fork = db.fork();
+            for j in 0..16 {
+                fork.get_proof_list(format!("list_{}", j)).push(i);
+            }
+            let patch = fork.into_patch();
+            assert!(Arc::ptr_eq(patch.hashing_pool.as_ref().unwrap(), &pool));
+            db.merge(patch).unwrap();
+        }
+
+        let snapshot = db.snapshot();
+        let aggregator = SystemSchema::new(&snapshot).state_aggregator();
+        let list = snapshot.get_proof_list::<_, u64>("list_7");
+        assert_eq!(list.len(), 3);
+        assert_eq!(aggregator.get("list_7"), Some(list.object_hash()));
+    }
+
+    fn fill_fork_in_steps(fork: &mut Fork) {
+        {
+            let mut map = fork.get_proof_map::<_, u64, String>("map");
+            for i in 0..20 {
+                map.put(&i, i.to_string());
+            }
+            fork.get_list("list").extend(0_u64..10);
+            fork.get_entry("entry").set(1_u64);
+        }
+        fork.flush();
+
+        {
+            let mut map = fork.get_proof_map::<_, u64, String>("map");
+            map.remove(&3);
+            map.put(&5, "five".to_owned());
+            map.put(&25, "25".to_owned());
+            let mut list = fork.get_list::<_, u64>("list");
+            list.clear();
+            list.extend(vec![100, 101]);
+        }
+        fork.flush();
+
+        fork.get_proof_map::<_, u64, String>("map").remove(&7);
+        fork.get_list::<_, u64>("list").push(102);
+        fork.get_entry::<_, u64>("entry").set(2);
+    }
+
+    #[test]
+    fn spilled_changes_are_readable() {
+        let db = TemporaryDB::new();
+        let mut fork = db.fork();
+        fork.set_spill_threshold(Some(0));
+        fill_fork_in_steps(&mut fork);
+        assert!(fork.patch.spilled.is_some());
+        assert_eq!(fork.patch.in_memory_size, 0);
+
+        let map = fork.get_proof_map::<_, u64, String>("map");
+        assert_eq!(map.get(&0).unwrap(), "0");
+        assert!(!map.contains(&3));
+        assert_eq!(map.get(&5).unwrap(), "five");
+        assert!(!map.contains(&7));
+        assert_eq!(map.get(&25).unwrap(), "25");
+        let keys: HashSet<_> = map.keys().collect();
+        let expected_keys: HashSet<_> = (0..20)
+            .chain(Some(25))
+            .filter(|&i| i != 3 && i != 7)
+            .collect();
+        assert_eq!(keys, expected_keys);
+
+        let list = fork.get_list::<_, u64>("list");
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![100, 101, 102]);
+        assert_eq!(list.iter_from(1).collect::<Vec<_>>(), vec![101, 102]);
+        assert_eq!(fork.get_entry::<_, u64>("entry").get(), Some(2));
+        drop((map, list));
+
+        // Clear the list after its changes are spilled.
+        fork.flush();
+        fork.set_spill_threshold(None);
+        fork.get_list::<_, u64>("list").clear();
+        fork.flush();
+        assert!(fork.get_list::<_, u64>("list").is_empty());
+        assert_eq!(fork.get_list::<_, u64>("list").iter().count(), 0);
+    }
+
+    #[test]
+    fn spilled_patch_is_merged_correctly() {
+        let reference_db = TemporaryDB::new();
+        let mut fork = reference_db.fork();
+        fill_fork_in_steps(&mut fork);
+        let reference_patch = fork.into_patch();
+        let reference_hash = SystemSchema::new(&reference_patch).state_hash();
+        reference_db.merge(reference_patch).unwrap();
+
+        for &threshold in &[0, 100, 1_000_000] {
+            let db = TemporaryDB::new();
+            let mut fork = db.fork();
+            fork.set_spill_threshold(Some(threshold));
+            fill_fork_in_steps(&mut fork);
+            let patch = fork.into_patch();
+            assert_eq!(patch.spill_threshold, Some(threshold));
+            assert_eq!(SystemSchema::new(&patch).state_hash(), reference_hash);
+            db.merge(patch).unwrap();
+
+            let snapshot = db.snapshot();
+            let reference_snapshot = reference_db.snapshot();
+            let map = snapshot.get_proof_map::<_, u64, String>("map");
+            let reference_map = reference_snapshot.get_proof_map::<_, u64, String>("map");
+            assert_eq!(
+                map.iter().collect::<Vec<_>>(),
+                reference_map.iter().collect::<Vec<_>>()
+            );
+            let list = snapshot.get_list::<_, u64>("list");
+            assert_eq!(list.iter().collect::<Vec<_>>(), vec![100, 101, 102]);
+            assert_eq!(snapshot.get_entry::<_, u64>("entry").get(), Some(2));
+        }
+    }
+
+    #[test]
+    fn backup_for_spilled_patch() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u64, 2, 3]);
+        db.merge(fork.into_patch()).unwrap();
+        let initial_hash = SystemSchema::new(&db.snapshot()).state_hash();
+
+        let mut fork = db.fork();
+        fork.set_spill_threshold(Some(0));
+        fill_fork_in_steps(&mut fork);
+        let backup = db.merge_with_backup(fork.into_patch()).unwrap();
+        assert_eq!(backup.spill_threshold, Some(0));
+        db.merge(backup).unwrap();
+
+        let snapshot = db.snapshot();
+        let list = snapshot.get_list::<_, u64>("list");
+        assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(snapshot.get_proof_map::<_, u64, String>("map").is_empty());
+        assert_eq!(SystemSchema::new(&snapshot).state_hash(), initial_hash);
+    }
+
     #[test]
     fn borrows_from_owned_forks() {
         use crate::{access::AccessExt, Entry};
@@ -0,0 +1,89 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pool of long-lived threads recomputing hashes of changed aggregated indexes.
+
+use crossbeam::channel::{self, Receiver, Sender};
+
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Fixed-size pool of worker threads used by `Fork::into_patch()` to hash changed
+/// aggregated indexes in parallel.
+///
+/// The pool is created once per database and shared by all forks created by it,
+/// so that converting a fork into a patch does not spawn threads. Workers are detached;
+/// they exit once the pool is dropped and the queued jobs are finished.
+pub(crate) struct HashingPool {
+    threads: usize,
+    sender: Sender<Job>,
+}
+
+impl HashingPool {
+    /// Spawns a pool with the specified number of worker threads.
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = channel::unbounded::<Job>();
+        for i in 0..threads {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("merkledb-hashing-{}", i))
+                .spawn(move || {
+                    for job in receiver {
+                        // A panicking job drops its output sender, which is detected
+                        // by the caller; the worker itself stays alive.
+                        panic::catch_unwind(AssertUnwindSafe(job)).ok();
+                    }
+                })
+                .expect("Cannot spawn hashing thread");
+        }
+        Self { threads, sender }
+    }
+
+    /// Returns the number of worker threads in the pool.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Executes `job` on one of the worker threads. The returned receiver yields
+    /// the job output, or an error if the job has panicked.
+    ///
+    /// The job is dropped before its output is sent, so that any data captured
+    /// by the job is released by the time the output is received.
+    pub fn execute<F, R>(&self, job: F) -> Receiver<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (output_sender, output_receiver) = channel::bounded(1);
+        let job = Box::new(move || {
+            let output = job();
+            output_sender.send(output).ok();
+        });
+        self.sender.send(job).expect("Hashing threads have exited");
+        output_receiver
+    }
+}
+
+impl fmt::Debug for HashingPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashingPool")
+            .field("threads", &self.threads)
+            .finish()
+    }
+}
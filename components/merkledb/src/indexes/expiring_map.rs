@@ -0,0 +1,356 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An implementation of a key-value map with entries expiring at a certain height.
+//!
+//! `ExpiringMapIndex` requires that keys implement the [`BinaryKey`] trait and values implement
+//! the [`BinaryValue`] trait. The given section contains methods related to
+//! `ExpiringMapIndex` and iterators over the items of this map.
+//!
+//! [`BinaryKey`]: ../trait.BinaryKey.html
+//! [`BinaryValue`]: ../trait.BinaryValue.html
+
+use anyhow::ensure;
+
+use std::{borrow::Cow, marker::PhantomData, mem};
+
+use crate::{
+    access::{Access, AccessError, FromAccess},
+    indexes::iter::{Entries, Keys},
+    views::{IndexAddress, IndexType, RawAccess, RawAccessMut, View},
+    BinaryKey, BinaryValue,
+};
+
+/// Size of the expiration height in the serialized entries.
+const HEIGHT_SIZE: usize = mem::size_of::<u64>();
+
+/// A map of keys and values, in which each entry has an expiration height.
+///
+/// Expired entries are not removed automatically; they remain accessible until
+/// [`purge_expired`] is called with a height greater or equal to their expiration height.
+/// Purging is efficient: the map keeps a secondary index of entries ordered
+/// by the expiration height, so purging only touches expired entries.
+///
+/// The secondary index is stored as a key set at the address of the map with the appended
+/// `expiry_queue` name component (e.g., `challenges.expiry_queue` for the `challenges` map).
+///
+/// `ExpiringMapIndex` requires that keys implement the [`BinaryKey`] trait and values implement
+/// the [`BinaryValue`] trait.
+///
+/// # Examples
+///
+/// ```
+/// use exonum_merkledb::{access::CopyAccessExt, Database, ExpiringMapIndex, TemporaryDB};
+///
+/// let db = TemporaryDB::new();
+/// let fork = db.fork();
+/// let mut index: ExpiringMapIndex<_, u32, String> = fork.get_expiring_map("nonces");
+/// index.put(&1, "first".to_owned(), 10);
+/// index.put(&2, "second".to_owned(), 20);
+/// assert_eq!(index.expiration_height(&1), Some(10));
+///
+/// assert_eq!(index.purge_expired(15), 1);
+/// assert!(!index.contains(&1));
+/// assert_eq!(index.get(&2).unwrap(), "second");
+/// ```
+///
+/// [`purge_expired`]: #method.purge_expired
+/// [`BinaryKey`]: ../trait.BinaryKey.html
+/// [`BinaryValue`]: ../trait.BinaryValue.html
+#[derive(Debug)]
+pub struct ExpiringMapIndex<T: RawAccess, K: ?Sized, V> {
+    /// Map from keys to values together with their expiration heights.
+    base: View<T>,
+    /// Key set with elements of the form `(expiration_height, key)`.
+    expiry_queue: View<T>,
+    _k: PhantomData<K>,
+    _v: PhantomData<V>,
+}
+
+/// Value stored in the map together with its expiration height.
+#[derive(Debug)]
+struct Expiring<V> {
+    expires_at: u64,
+    value: V,
+}
+
+impl<V: BinaryValue> BinaryValue for Expiring<V> {
+    fn to_bytes(&self) -> Vec<u8> {
+        let value = self.value.to_bytes();
+        let mut buffer = Vec::with_capacity(HEIGHT_SIZE + value.len());
+        buffer.extend_from_slice(&self.expires_at.to_le_bytes());
+        buffer.extend_from_slice(&value);
+        buffer
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> anyhow::Result<Self> {
+        ensure!(
+            bytes.len() >= HEIGHT_SIZE,
+            "Expiring entry is too short: {} bytes",
+            bytes.len()
+        );
+        let mut height_bytes = [0; HEIGHT_SIZE];
+        height_bytes.copy_from_slice(&bytes[..HEIGHT_SIZE]);
+        let value = V::from_bytes(Cow::Borrowed(&bytes[HEIGHT_SIZE..]))?;
+        Ok(Self {
+            expires_at: u64::from_le_bytes(height_bytes),
+            value,
+        })
+    }
+}
+
+/// Returns the key of the expiry queue for the specified map key.
+fn queue_key<K: BinaryKey + ?Sized>(expires_at: u64, key: &K) -> Vec<u8> {
+    let mut buffer = vec![0; HEIGHT_SIZE + key.size()];
+    buffer[..HEIGHT_SIZE].copy_from_slice(&expires_at.to_be_bytes());
+    key.write(&mut buffer[HEIGHT_SIZE..]);
+    buffer
+}
+
+impl<T, K, V> FromAccess<T> for ExpiringMapIndex<T::Base, K, V>
+where
+    T: Access,
+    K: BinaryKey + ?Sized,
+    V: BinaryValue,
+{
+    fn from_access(access: T, addr: IndexAddress) -> Result<Self, AccessError> {
+        let queue_addr = addr.clone().append_name("expiry_queue");
+        let base = access.clone().get_or_create_view(addr, IndexType::Map)?;
+        let expiry_queue = access.get_or_create_view(queue_addr, IndexType::KeySet)?;
+        Ok(Self {
+            base: base.into(),
+            expiry_queue: expiry_queue.into(),
+            _k: PhantomData,
+            _v: PhantomData,
+        })
+    }
+}
+
+impl<T, K, V> ExpiringMapIndex<T, K, V>
+where
+    T: RawAccess,
+    K: BinaryKey + ?Sized,
+    V: BinaryValue,
+{
+    /// Returns a value corresponding to the key. The value is returned even if it is expired,
+    /// but not yet purged.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.base
+            .get::<_, Expiring<V>>(key)
+            .map(|entry| entry.value)
+    }
+
+    /// Returns `true` if the map contains a value corresponding to the specified key.
+    pub fn contains(&self, key: &K) -> bool {
+        self.base.contains(key)
+    }
+
+    /// Returns the height at which the entry with the specified key expires.
+    pub fn expiration_height(&self, key: &K) -> Option<u64> {
+        self.base
+            .get::<_, Expiring<V>>(key)
+            .map(|entry| entry.expires_at)
+    }
+
+    /// Returns an iterator over the entries of the map in ascending order of keys.
+    pub fn iter(&self) -> impl Iterator<Item = (K::Owned, V)> + '_ {
+        Entries::<K, Expiring<V>>::new(&self.base, None).map(|(key, entry)| (key, entry.value))
+    }
+
+    /// Returns an iterator over the entries of the map in ascending order of keys starting
+    /// from the specified key.
+    pub fn iter_from(&self, from: &K) -> impl Iterator<Item = (K::Owned, V)> + '_ {
+        Entries::<K, Expiring<V>>::new(&self.base, Some(from))
+            .map(|(key, entry)| (key, entry.value))
+    }
+
+    /// Returns an iterator over the keys of the map in ascending order.
+    pub fn keys(&self) -> Keys<'_, K> {
+        Entries::<K, Expiring<V>>::new(&self.base, None).skip_values()
+    }
+
+    /// Returns an iterator over the keys of the map in the ascending order of their
+    /// expiration heights, together with these heights. Entries with the same expiration height
+    /// are ordered by their keys.
+    pub fn keys_by_expiration(&self) -> impl Iterator<Item = (u64, K::Owned)> + '_ {
+        Entries::<[u8], ()>::new(&self.expiry_queue, None).map(|(queue_key, ())| {
+            let mut height_bytes = [0; HEIGHT_SIZE];
+            height_bytes.copy_from_slice(&queue_key[..HEIGHT_SIZE]);
+            let key = K::read(&queue_key[HEIGHT_SIZE..]);
+            (u64::from_be_bytes(height_bytes), key)
+        })
+    }
+}
+
+impl<T, K, V> ExpiringMapIndex<T, K, V>
+where
+    T: RawAccessMut,
+    K: BinaryKey + ?Sized,
+    V: BinaryValue,
+{
+    /// Inserts a key-value pair into the map. The entry expires at the specified height.
+    /// If the map already contains the key, both the value and the expiration height
+    /// are overwritten.
+    pub fn put(&mut self, key: &K, value: V, expires_at: u64) {
+        if let Some(old_height) = self.expiration_height(key) {
+            self.expiry_queue.remove(&queue_key(old_height, key)[..]);
+        }
+        self.expiry_queue.put(&queue_key(expires_at, key)[..], ());
+        self.base.put(key, Expiring { expires_at, value });
+    }
+
+    /// Removes a key from the map.
+    pub fn remove(&mut self, key: &K) {
+        if let Some(height) = self.expiration_height(key) {
+            self.expiry_queue.remove(&queue_key(height, key)[..]);
+            self.base.remove(key);
+        }
+    }
+
+    /// Removes all entries with the expiration height less or equal to `current_height`.
+    /// Returns the number of removed entries.
+    ///
+    /// The method is linear w.r.t. the number of removed entries.
+    pub fn purge_expired(&mut self, current_height: u64) -> usize {
+        self.purge_expired_limited(current_height, usize::max_value())
+    }
+
+    /// Removes at most `limit` entries with the expiration height less or equal
+    /// to `current_height`, starting from the entries expiring earliest. Returns the number
+    /// of removed entries.
+    ///
+    /// This method allows to bound the work performed on each purge if many entries
+    /// may expire at once; the remaining expired entries can be purged later.
+    pub fn purge_expired_limited(&mut self, current_height: u64, limit: usize) -> usize {
+        let expired: Vec<_> = Entries::<[u8], ()>::new(&self.expiry_queue, None)
+            .map(|(queue_key, ())| queue_key)
+            .take_while(|queue_key| {
+                let mut height_bytes = [0; HEIGHT_SIZE];
+                height_bytes.copy_from_slice(&queue_key[..HEIGHT_SIZE]);
+                u64::from_be_bytes(height_bytes) <= current_height
+            })
+            .take(limit)
+            .collect();
+
+        for queue_key in &expired {
+            self.expiry_queue.remove(&queue_key[..]);
+            // Keys are serialized in the same way in both views.
+            self.base.remove(&queue_key[HEIGHT_SIZE..]);
+        }
+        expired.len()
+    }
+
+    /// Clears the map, removing all entries.
+    ///
+    /// # Notes
+    ///
+    /// Currently, this method is not optimized to delete a large set of data. During the execution of
+    /// this method, the amount of allocated memory is linearly dependent on the number of elements
+    /// in the index.
+    pub fn clear(&mut self) {
+        self.base.clear();
+        self.expiry_queue.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{access::CopyAccessExt, Database, ExpiringMapIndex, TemporaryDB};
+
+    #[test]
+    fn expiring_map_basics() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut index: ExpiringMapIndex<_, str, u64> = fork.get_expiring_map("map");
+        index.put("foo", 1, 10);
+        index.put("bar", 2, 5);
+        index.put("baz", 3, 10);
+        assert_eq!(index.get("foo"), Some(1));
+        assert_eq!(index.expiration_height("bar"), Some(5));
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![
+                ("bar".to_owned(), 2),
+                ("baz".to_owned(), 3),
+                ("foo".to_owned(), 1)
+            ]
+        );
+        assert_eq!(
+            index.keys_by_expiration().collect::<Vec<_>>(),
+            vec![
+                (5, "bar".to_owned()),
+                (10, "baz".to_owned()),
+                (10, "foo".to_owned())
+            ]
+        );
+
+        // Overwriting an entry updates its expiration height.
+        index.put("bar", 4, 20);
+        assert_eq!(index.get("bar"), Some(4));
+        assert_eq!(index.purge_expired(9), 0);
+        assert_eq!(index.purge_expired(10), 2);
+        assert_eq!(index.keys().collect::<Vec<_>>(), vec!["bar".to_owned()]);
+
+        index.remove("bar");
+        assert!(!index.contains("bar"));
+        assert_eq!(index.keys_by_expiration().count(), 0);
+        assert_eq!(index.purge_expired(100), 0);
+    }
+
+    #[test]
+    fn expiring_map_after_merge() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        {
+            let mut index = fork.get_expiring_map::<_, u32, String>("map");
+            for i in 0..10 {
+                index.put(&i, i.to_string(), u64::from(i % 3));
+            }
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let fork = db.fork();
+        let mut index = fork.get_expiring_map::<_, u32, String>("map");
+        assert_eq!(index.purge_expired(1), 7);
+        assert_eq!(
+            index.iter().collect::<Vec<_>>(),
+            vec![
+                (2, "2".to_owned()),
+                (5, "5".to_owned()),
+                (8, "8".to_owned())
+            ]
+        );
+        index.clear();
+        assert_eq!(index.iter().count(), 0);
+        assert_eq!(index.keys_by_expiration().count(), 0);
+    }
+
+    #[test]
+    fn limited_purging() {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut index = fork.get_expiring_map::<_, u32, String>("map");
+        for i in 0..10 {
+            index.put(&i, i.to_string(), u64::from(10 - i));
+        }
+
+        assert_eq!(index.purge_expired_limited(5, 3), 3);
+        assert_eq!(index.keys().collect::<Vec<_>>(), (0..7).collect::<Vec<_>>());
+        assert_eq!(index.purge_expired_limited(5, 3), 2);
+        assert_eq!(index.purge_expired_limited(5, 3), 0);
+        assert_eq!(index.keys().collect::<Vec<_>>(), (0..5).collect::<Vec<_>>());
+        assert_eq!(index.purge_expired_limited(100, 0), 0);
+        assert_eq!(index.iter().count(), 5);
+    }
+}
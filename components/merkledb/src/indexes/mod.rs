@@ -16,6 +16,7 @@
 
 pub use self::{
     entry::Entry,
+    expiring_map::ExpiringMapIndex,
     group::Group,
     iter::{Entries, IndexIterator, Keys, Values},
     key_set::KeySetIndex,
@@ -26,7 +27,10 @@ pub use self::{
     value_set::ValueSetIndex,
 };
 
+pub(crate) use self::sparse_list::SparseListSize;
+
 mod entry;
+mod expiring_map;
 mod group;
 mod iter;
 mod key_set;
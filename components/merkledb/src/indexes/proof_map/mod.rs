@@ -14,7 +14,7 @@
 
 //! An implementation of a Merkelized version of a map (Merkle Patricia tree).
 
-pub(crate) use self::key::{BitsRange, ProofPath};
+pub(crate) use self::key::{BitsRange, ProofPath, VALUE_KEY_PREFIX};
 pub use self::{
     key::{Hashed, Raw, RawKey, ToProofPath, KEY_SIZE as PROOF_MAP_KEY_SIZE, PROOF_PATH_SIZE},
     proof::{CheckedMapProof, MapProof, MapProofError, ValidationError},
@@ -25,7 +25,7 @@ use exonum_crypto::Hash;
 use std::{fmt, io, marker::PhantomData};
 
 use self::{
-    key::{ChildKind, VALUE_KEY_PREFIX},
+    key::ChildKind,
     node::{BranchNode, Node},
     proof_builder::{BuildProof, MerklePatriciaTree},
 };
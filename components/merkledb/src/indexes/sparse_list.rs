@@ -32,11 +32,11 @@ use crate::{
 };
 
 #[derive(Debug, Default, Clone, Copy)]
-struct SparseListSize {
+pub(crate) struct SparseListSize {
     /// Total list's length including spaces. In fact points to the next index for a new element.
     capacity: u64,
     /// Amount of non-empty elements.
-    length: u64,
+    pub(crate) length: u64,
 }
 
 impl BinaryAttribute for SparseListSize {
@@ -74,6 +74,8 @@
 //! - [`SparseListIndex`] is a list of items stored in a sequential order. Similar to `ListIndex`,
 //!   but may contain indexes without elements.
 //! - [`MapIndex`] is a map of keys and values. Similar to [`BTreeMap`].
+//! - [`ExpiringMapIndex`] is a map, in which each entry has an expiration height. Expired
+//!   entries can be efficiently purged from the map.
 //! - [`ProofEntry`] is a Merkelized version of `Entry`.
 //! - [`ProofListIndex`] is a Merkelized version of `ListIndex` that supports cryptographic
 //!   proofs of existence and is implemented as a Merkle tree.
@@ -123,6 +125,7 @@
 //! [`ListIndex`]: indexes/struct.ListIndex.html
 //! [`SparseListIndex`]: indexes/struct.SparseListIndex.html
 //! [`MapIndex`]: indexes/struct.MapIndex.html
+//! [`ExpiringMapIndex`]: indexes/struct.ExpiringMapIndex.html
 //! [`ProofListIndex`]: indexes/proof_list/struct.ProofListIndex.html
 //! [`ProofMapIndex`]: indexes/proof_map/struct.ProofMapIndex.html
 //! [`KeySetIndex`]: indexes/struct.KeySetIndex.html
@@ -181,7 +184,9 @@ pub use self::{
     lazy::Lazy,
     options::DbOptions,
     values::BinaryValue,
-    views::{AsReadonly, IndexAddress, IndexType, ResolvedAddress, SystemSchema},
+    views::{
+        AsReadonly, IndexAddress, IndexDump, IndexInfo, IndexType, ResolvedAddress, SystemSchema,
+    },
 };
 // Workaround for 'Linked file at path {exonum_merkledb_path}/struct.ProofMapIndex.html
 // does not exist!'
@@ -189,7 +194,8 @@ pub use self::{
 pub use self::indexes::{
     proof_list::{self, ListProof, ProofListIndex},
     proof_map::{self, MapProof, ProofMapIndex, RawProofMapIndex},
-    Entry, Group, KeySetIndex, ListIndex, MapIndex, ProofEntry, SparseListIndex, ValueSetIndex,
+    Entry, ExpiringMapIndex, Group, KeySetIndex, ListIndex, MapIndex, ProofEntry, SparseListIndex,
+    ValueSetIndex,
 };
 
 #[macro_use]
@@ -200,6 +206,7 @@ mod db;
 mod error;
 pub mod generic;
 mod hash;
+mod hashing_pool;
 pub mod indexes;
 mod keys;
 mod lazy;
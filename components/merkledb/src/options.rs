@@ -46,6 +46,28 @@ pub struct DbOptions {
     ///
     /// Defaults to `CompressionType::None`, meaning there is no compression.
     pub compression_type: CompressionType,
+    /// Number of threads used to recompute hashes of changed proof indexes when
+    /// a `Fork` created by the database is converted into a `Patch`.
+    ///
+    /// Parallel hashing does not influence the resulting state hash; it may
+    /// speed up processing of blocks that change many independent proof indexes.
+    ///
+    /// Defaults to `1`, meaning that hashes are computed sequentially.
+    #[serde(default = "default_hashing_threads")]
+    pub hashing_threads: usize,
+    /// Approximate size of finalized changes (in bytes) in a `Fork` created by the database,
+    /// after which the changes are moved to a temporary on-disk storage.
+    ///
+    /// Spilling changes to disk bounds memory consumption of very large transactions
+    /// and migrations at the cost of slower access to the spilled changes.
+    ///
+    /// Defaults to `None`, meaning that all changes are kept in memory.
+    #[serde(default)]
+    pub spill_threshold: Option<usize>,
+}
+
+fn default_hashing_threads() -> usize {
+    1
 }
 
 impl DbOptions {
@@ -59,6 +81,8 @@ impl DbOptions {
             max_open_files,
             create_if_missing,
             compression_type,
+            hashing_threads: default_hashing_threads(),
+            spill_threshold: None,
         }
     }
 }
@@ -25,7 +25,7 @@ use super::{IndexAddress, RawAccess, RawAccessMut, ResolvedAddress, View};
 use crate::{
     access::{AccessError, AccessErrorKind},
     validation::check_index_valid_full_name,
-    BinaryKey, BinaryValue,
+    BinaryKey, BinaryValue, Fork,
 };
 
 /// Name of the column family used to store `IndexesPool`.
@@ -574,6 +574,245 @@ pub fn get_object_hash<T: RawAccess>(
     }
 }
 
+/// Information about an index retrieved from the index metadata.
+///
+/// See [`SystemSchema::indexes_by_prefix`] for an example of usage.
+///
+/// [`SystemSchema::indexes_by_prefix`]: struct.SystemSchema.html#method.indexes_by_prefix
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct IndexInfo {
+    /// Address of the index.
+    pub address: IndexAddress,
+    /// Type of the index.
+    pub index_type: IndexType,
+    /// Hash of the index as returned by `ObjectHash`, or `None` if the index is not Merkelized.
+    pub object_hash: Option<Hash>,
+    /// Approximate number of entries in the index.
+    ///
+    /// The number is exact for lists, sparse lists and entries, since their length is stored
+    /// in the index metadata. For other index types, entries are counted up
+    /// to [`MAX_COUNTED_ENTRIES`].
+    ///
+    /// [`MAX_COUNTED_ENTRIES`]: #associatedconstant.MAX_COUNTED_ENTRIES
+    pub approx_len: u64,
+}
+
+impl IndexInfo {
+    /// Maximum number of entries counted when estimating the length of an index which
+    /// does not store its length (e.g., a map or a set).
+    pub const MAX_COUNTED_ENTRIES: u64 = 10_000;
+
+    /// Checks if the index is Merkelized.
+    pub fn is_merkelized(&self) -> bool {
+        self.index_type.is_merkelized()
+    }
+
+    fn new<T: RawAccess>(access: T, address: IndexAddress, metadata: IndexMetadata) -> Self {
+        use crate::{
+            indexes::{proof_map::VALUE_KEY_PREFIX, SparseListSize},
+            ObjectHash, ProofListIndex, ProofMapIndex,
+        };
+
+        fn count_entries<T: RawAccess, P: BinaryKey + ?Sized>(
+            view: &View<T>,
+            subprefix: &P,
+        ) -> u64 {
+            view.iter::<_, Vec<u8>, Vec<u8>>(subprefix)
+                .take(IndexInfo::MAX_COUNTED_ENTRIES as usize)
+                .count() as u64
+        }
+
+        let index_type = metadata.index_type;
+        let resolved = ResolvedAddress::new(address.name(), Some(metadata.identifier));
+        let index_full_name = address.fully_qualified_name();
+
+        let (object_hash, approx_len) = match index_type {
+            IndexType::Entry => {
+                let view = View::new(access, resolved);
+                (None, u64::from(view.contains(&())))
+            }
+            IndexType::ProofEntry => {
+                let hash = metadata.convert::<Hash>().state;
+                (Some(hash.unwrap_or_default()), u64::from(hash.is_some()))
+            }
+            IndexType::List => (None, metadata.convert::<u64>().state.unwrap_or_default()),
+            IndexType::SparseList => {
+                let size = metadata.convert::<SparseListSize>().state;
+                (None, size.map_or(0, |size| size.length))
+            }
+            IndexType::Map | IndexType::KeySet | IndexType::ValueSet => {
+                let view = View::new(access, resolved);
+                (None, count_entries(&view, &()))
+            }
+            IndexType::ProofList => {
+                let view_with_metadata = ViewWithMetadata {
+                    view: View::new(access, resolved),
+                    metadata,
+                    index_full_name,
+                    is_phantom: false,
+                };
+                // We don't access list elements, so the element type doesn't matter.
+                let list = ProofListIndex::<_, ()>::new(view_with_metadata);
+                (Some(list.object_hash()), list.len())
+            }
+            IndexType::ProofMap => {
+                let view = View::new(access, resolved);
+                let len = count_entries(&view, &VALUE_KEY_PREFIX);
+                let view_with_metadata = ViewWithMetadata {
+                    view,
+                    metadata,
+                    index_full_name,
+                    is_phantom: false,
+                };
+                // We don't access map elements, so the key / value types don't matter.
+                let map = ProofMapIndex::<_, (), ()>::new(view_with_metadata);
+                (Some(map.object_hash()), len)
+            }
+            IndexType::Tombstone | IndexType::Unknown => (None, 0),
+        };
+
+        Self {
+            address,
+            index_type,
+            object_hash,
+            approx_len,
+        }
+    }
+}
+
+/// Raw contents of an index together with its metadata, which allow to copy the index
+/// into another database.
+///
+/// The dump contains entries of the index exactly as they are stored in the database
+/// (including auxiliary entries, such as Merkle tree nodes), so a restored index
+/// is indistinguishable from the original, and has the same object hash.
+///
+/// See [`SystemSchema::dump_indexes`] for an example of usage.
+///
+/// [`SystemSchema::dump_indexes`]: struct.SystemSchema.html#method.dump_indexes
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct IndexDump {
+    /// Address of the index.
+    pub address: IndexAddress,
+    /// Type of the index.
+    pub index_type: IndexType,
+    /// Raw index state stored in the index metadata (e.g., the length of a list).
+    pub state: Option<Vec<u8>>,
+    /// Raw key-value entries of the index in the order of keys.
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl IndexDump {
+    /// Creates a dump from the constituent parts.
+    pub fn new(
+        address: IndexAddress,
+        index_type: IndexType,
+        state: Option<Vec<u8>>,
+        entries: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Self {
+        Self {
+            address,
+            index_type,
+            state,
+            entries,
+        }
+    }
+
+    fn read<T: RawAccess>(access: T, address: IndexAddress, metadata: IndexMetadata) -> Self {
+        let resolved = ResolvedAddress::new(address.name(), Some(metadata.identifier));
+        let entries = View::new(access, resolved)
+            .iter::<_, Vec<u8>, Vec<u8>>(&())
+            .collect();
+        Self {
+            address,
+            index_type: metadata.index_type,
+            state: metadata.state,
+            entries,
+        }
+    }
+
+    /// Restores the index in the provided `fork`. If the index already exists, its contents
+    /// are replaced with the contents of the dump.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index address is invalid, or if the index exists and has
+    /// a different type.
+    pub fn restore(&self, fork: &Fork) -> Result<(), AccessError> {
+        let view_with_metadata =
+            ViewWithMetadata::get_or_create(fork, &self.address, self.index_type)?;
+        let (mut view, mut state) = view_with_metadata.into_parts::<Vec<u8>>();
+        view.clear();
+        for (key, value) in &self.entries {
+            view.put(key.as_slice(), value.clone());
+        }
+        if let Some(index_state) = &self.state {
+            state.set(index_state.clone());
+        } else {
+            state.unset();
+        }
+        Ok(())
+    }
+}
+
+/// Iterates over metadata of all non-migrated indexes with the name starting with
+/// the specified `prefix`. Indexes are ordered by their fully qualified names.
+fn index_metadata_by_prefix<T: RawAccess>(
+    access: T,
+    prefix: &str,
+) -> Vec<(IndexAddress, IndexMetadata)> {
+    let indexes: Vec<_> = IndexesPool::new(access)
+        .0
+        .iter::<_, Vec<u8>, IndexMetadata>(prefix)
+        .collect();
+
+    indexes
+        .into_iter()
+        .filter_map(|(full_name, metadata)| {
+            if full_name.first() == Some(&b'^') {
+                // The index is in migration.
+                return None;
+            }
+
+            let (name, is_in_group) =
+                IndexAddress::parse_fully_qualified_name(&full_name, prefix.len());
+            let id_in_group = if is_in_group {
+                // Skip the separator between the name and the key in the group.
+                Some(full_name[name.len() + 1..].to_vec())
+            } else {
+                None
+            };
+            let address = IndexAddress {
+                name,
+                id_in_group,
+                in_migration: false,
+            };
+            Some((address, metadata))
+        })
+        .collect()
+}
+
+/// Collects information about all non-migrated indexes with the name starting with
+/// the specified `prefix`. Indexes are ordered by their fully qualified names.
+pub(super) fn indexes_by_prefix<T: RawAccess>(access: T, prefix: &str) -> Vec<IndexInfo> {
+    index_metadata_by_prefix(access.clone(), prefix)
+        .into_iter()
+        .map(|(address, metadata)| IndexInfo::new(access.clone(), address, metadata))
+        .collect()
+}
+
+/// Dumps all non-migrated indexes with the name starting with the specified `prefix`.
+/// Indexes are ordered by their fully qualified names.
+pub(super) fn dump_indexes<T: RawAccess>(access: T, prefix: &str) -> Vec<IndexDump> {
+    index_metadata_by_prefix(access.clone(), prefix)
+        .into_iter()
+        .filter(|(_, metadata)| metadata.index_type != IndexType::Tombstone)
+        .map(|(address, metadata)| IndexDump::read(access.clone(), address, metadata))
+        .collect()
+}
+
 /// Wrapper struct to manipulate `IndexMetadata` for an index with provided `index_name`.
 #[derive(Debug)]
 pub struct ViewWithMetadata<T: RawAccess> {
@@ -803,6 +1042,68 @@ mod tests {
         assert_eq!(pool.len(), index_count);
     }
 
+    #[test]
+    fn indexes_by_prefix_info() {
+        use crate::{access::AccessExt, migration::Migration, ObjectHash, SystemSchema};
+
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        fork.get_entry("test.entry").set(1_u32);
+        fork.get_proof_entry("test.proof_entry").set(2_u32);
+        fork.get_sparse_list("test.sparse")
+            .extend(vec![1_u32, 2, 3]);
+        fork.get_sparse_list::<_, u32>("test.sparse").remove(1);
+        fork.get_key_set(("test.set", &0_u8)).insert(&1_u8);
+        {
+            let mut map = fork.get_proof_map("test.map");
+            for i in 0..5_u64 {
+                map.put(&i, i.to_string());
+            }
+        }
+        fork.get_list("test_not_matched").push(1_u8);
+        Migration::new("test", &fork)
+            .get_list("migrated")
+            .push(1_u8);
+
+        let patch = fork.into_patch();
+        let indexes = SystemSchema::new(&patch).indexes_by_prefix("test.");
+        let names: Vec<_> = indexes
+            .iter()
+            .map(|info| (info.address.name(), info.index_type, info.approx_len))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("test.entry", IndexType::Entry, 1),
+                ("test.map", IndexType::ProofMap, 5),
+                ("test.proof_entry", IndexType::ProofEntry, 1),
+                ("test.set", IndexType::KeySet, 1),
+                ("test.sparse", IndexType::SparseList, 2),
+            ]
+        );
+
+        assert_eq!(indexes[3].address.id_in_group(), Some(&[0_u8] as &[u8]));
+        assert_eq!(
+            indexes[1].object_hash,
+            Some(
+                patch
+                    .get_proof_map::<_, u64, String>("test.map")
+                    .object_hash()
+            )
+        );
+        assert_eq!(
+            indexes[2].object_hash,
+            Some(
+                patch
+                    .get_proof_entry::<_, u32>("test.proof_entry")
+                    .object_hash()
+            )
+        );
+        assert!(indexes
+            .iter()
+            .all(|info| info.is_merkelized() == info.object_hash.is_some()));
+    }
+
     #[test]
     fn group_keys_edge_cases() {
         let db = TemporaryDB::new();
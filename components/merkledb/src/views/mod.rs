@@ -15,8 +15,8 @@
 pub use self::{
     address::{IndexAddress, ResolvedAddress},
     metadata::{
-        get_object_hash, BinaryAttribute, GroupKeys, IndexMetadata, IndexState, IndexType,
-        IndexesPool, ViewWithMetadata,
+        get_object_hash, BinaryAttribute, GroupKeys, IndexDump, IndexInfo, IndexMetadata,
+        IndexState, IndexType, IndexesPool, ViewWithMetadata,
     },
     system_schema::{get_state_aggregator, SystemSchema},
 };
@@ -113,6 +113,38 @@ pub trait RawAccess: Clone {
     fn snapshot(&self) -> &dyn Snapshot;
     /// Returns changes related to specific `address` compared to the `snapshot()`.
     fn changes(&self, address: &ResolvedAddress) -> Self::Changes;
+
+    /// Returns an observer of operations performed by views created from this access.
+    /// By default, operations are not observed.
+    fn observer(&self) -> Option<&dyn AccessObserver> {
+        None
+    }
+}
+
+/// Observer of low-level operations performed by index views.
+///
+/// An observer can be attached to a wrapper around [`RawAccess`] via the `observer()` method,
+/// e.g., to account for resources consumed by the code using the access. The observer is notified
+/// about each entry read from the view or written to it; this includes entries read
+/// or written by indexes internally (e.g., Merkle tree nodes in `ProofMapIndex`).
+/// Entries are identified by the resolved address of the view and the key within the view.
+///
+/// [`RawAccess`]: trait.RawAccess.html
+pub trait AccessObserver {
+    /// Is called when a single entry is read from the view. This includes getting a value,
+    /// checking key presence, and advancing an iterator over the view.
+    fn on_read(&self, address: &ResolvedAddress, key: &[u8]);
+
+    /// Is called when a single entry is written to the view or removed from it.
+    /// `bytes` is the total length of the written key and value. Clearing the view
+    /// is reported with the `None` key as a write of zero bytes.
+    fn on_write(&self, address: &ResolvedAddress, key: Option<&[u8]>, bytes: usize);
+
+    /// Is called when an index with the specified address is created or opened via
+    /// the [`Access`] trait. The default implementation does nothing.
+    ///
+    /// [`Access`]: ../access/trait.Access.html
+    fn on_index_access(&self, _address: &IndexAddress) {}
 }
 
 /// Allows to mutate data in indexes.
@@ -185,6 +217,12 @@ impl<T: RawAccess> ViewInner<T> {
         self.index_access.snapshot()
     }
 
+    fn observe_read(&self, key: &[u8]) {
+        if let Some(observer) = self.index_access.observer() {
+            observer.on_read(&self.address, key);
+        }
+    }
+
     fn get_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
         self.changes
             .as_ref()
@@ -250,21 +288,38 @@ impl<T: RawAccess> View<T> {
 
     fn get_bytes(&self, key: &[u8]) -> Option<Vec<u8>> {
         match self {
-            Self::Real(inner) => inner.get_bytes(key),
+            Self::Real(inner) => {
+                inner.observe_read(key);
+                inner.get_bytes(key)
+            }
             Self::Phantom => None,
         }
     }
 
     fn contains_raw_key(&self, key: &[u8]) -> bool {
         match self {
-            Self::Real(inner) => inner.contains_raw_key(key),
+            Self::Real(inner) => {
+                inner.observe_read(key);
+                inner.contains_raw_key(key)
+            }
             Self::Phantom => false,
         }
     }
 
     fn iter_bytes(&self, from: &[u8]) -> BytesIter<'_> {
         match self {
-            Self::Real(inner) => inner.iter_bytes(from),
+            Self::Real(inner) => {
+                let iter = inner.iter_bytes(from);
+                if let Some(observer) = inner.index_access.observer() {
+                    Box::new(ObservedIterator {
+                        inner: iter,
+                        address: &inner.address,
+                        observer,
+                    })
+                } else {
+                    iter
+                }
+            }
             Self::Phantom => Box::new(EmptyIterator),
         }
     }
@@ -403,15 +458,29 @@ impl<T: RawAccessMut> View<T> {
         }
     }
 
+    fn observe_write(&self, key: Option<&[u8]>, bytes: usize) {
+        if let Self::Real(ViewInner {
+            address,
+            index_access,
+            ..
+        }) = self
+        {
+            if let Some(observer) = index_access.observer() {
+                observer.on_write(address, key, bytes);
+            }
+        }
+    }
+
     /// Inserts a key-value pair into the fork.
     pub fn put<K, V>(&mut self, key: &K, value: V)
     where
         K: BinaryKey + ?Sized,
         V: BinaryValue,
     {
-        self.changes_mut()
-            .data
-            .insert(concat_keys!(key), Change::Put(value.into_bytes()));
+        let key = concat_keys!(key);
+        let value = value.into_bytes();
+        self.observe_write(Some(&key), key.len() + value.len());
+        self.changes_mut().data.insert(key, Change::Put(value));
     }
 
     /// Removes a key from the view.
@@ -419,17 +488,39 @@ impl<T: RawAccessMut> View<T> {
     where
         K: BinaryKey + ?Sized,
     {
-        self.changes_mut()
-            .data
-            .insert(concat_keys!(key), Change::Delete);
+        let key = concat_keys!(key);
+        self.observe_write(Some(&key), key.len());
+        self.changes_mut().data.insert(key, Change::Delete);
     }
 
     /// Clears the view removing all its elements.
     pub fn clear(&mut self) {
+        self.observe_write(None, 0);
         self.changes_mut().clear();
     }
 }
 
+/// Bytes iterator notifying an `AccessObserver` about each read entry.
+struct ObservedIterator<'a> {
+    inner: BytesIter<'a>,
+    address: &'a ResolvedAddress,
+    observer: &'a dyn AccessObserver,
+}
+
+impl BytesIterator for ObservedIterator<'_> {
+    fn next(&mut self) -> Option<(&[u8], &[u8])> {
+        let entry = self.inner.next();
+        if let Some((key, _)) = entry {
+            self.observer.on_read(self.address, key);
+        }
+        entry
+    }
+
+    fn peek(&mut self) -> Option<(&[u8], &[u8])> {
+        self.inner.peek()
+    }
+}
+
 /// A bytes iterator implementation that has no items.
 struct EmptyIterator;
 
@@ -14,7 +14,10 @@
 
 use exonum_crypto::Hash;
 
-use super::{AsReadonly, IndexType, RawAccess, ViewWithMetadata};
+use super::{
+    metadata::{dump_indexes, indexes_by_prefix},
+    AsReadonly, IndexDump, IndexInfo, IndexType, RawAccess, ViewWithMetadata,
+};
 use crate::{Fork, ObjectHash, ProofMapIndex};
 
 /// Name of the state aggregator proof map.
@@ -96,6 +99,82 @@ impl<T: RawAccess + AsReadonly> SystemSchema<T> {
     pub fn state_aggregator(&self) -> ProofMapIndex<T::Readonly, str, Hash> {
         get_state_aggregator(self.0.as_readonly(), "")
     }
+
+    /// Returns information about all indexes with the name starting with the specified `prefix`.
+    /// Indexes are ordered by their fully qualified names; indexes in migrations
+    /// are not included.
+    ///
+    /// The information is taken from the index metadata. For Merkelized indexes,
+    /// the object hash is computed in the same way as for the state aggregation.
+    /// For `Fork`s, the returned info reflects changes in the fork; this may lead to a runtime
+    /// error if one of the indexes is concurrently borrowed mutably.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum_merkledb::{access::CopyAccessExt, Database, IndexType, SystemSchema, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_proof_list("token.balances").extend(vec![1_u64, 2, 3]);
+    /// fork.get_map(("token.wallets", &1_u8)).put(&1_u32, "Alice".to_owned());
+    /// fork.get_entry("other.entry").set(42_u64);
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let snapshot = db.snapshot();
+    /// let indexes = SystemSchema::new(&snapshot).indexes_by_prefix("token.");
+    /// assert_eq!(indexes.len(), 2);
+    /// assert_eq!(indexes[0].address.name(), "token.balances");
+    /// assert_eq!(indexes[0].index_type, IndexType::ProofList);
+    /// assert_eq!(indexes[0].approx_len, 3);
+    /// assert!(indexes[0].object_hash.is_some());
+    /// assert_eq!(indexes[1].address.name(), "token.wallets");
+    /// assert_eq!(indexes[1].address.id_in_group(), Some(&[1_u8] as &[u8]));
+    /// assert_eq!(indexes[1].object_hash, None);
+    /// ```
+    pub fn indexes_by_prefix(&self, prefix: &str) -> Vec<IndexInfo> {
+        indexes_by_prefix(self.0.as_readonly(), prefix)
+    }
+
+    /// Dumps raw contents of all indexes with the name starting with the specified `prefix`.
+    /// Indexes are ordered by their fully qualified names; indexes in migrations
+    /// are not included.
+    ///
+    /// The dumps can be restored in another database via [`IndexDump::restore()`];
+    /// restored indexes have the same contents and object hashes as the original ones.
+    ///
+    /// [`IndexDump::restore()`]: struct.IndexDump.html#method.restore
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum_merkledb::{access::CopyAccessExt, Database, ObjectHash, SystemSchema, TemporaryDB};
+    /// let db = TemporaryDB::new();
+    /// let fork = db.fork();
+    /// fork.get_proof_list("token.balances").extend(vec![1_u64, 2, 3]);
+    /// fork.get_map(("token.wallets", &1_u8)).put(&1_u32, "Alice".to_owned());
+    /// db.merge(fork.into_patch()).unwrap();
+    ///
+    /// let snapshot = db.snapshot();
+    /// let dumps = SystemSchema::new(&snapshot).dump_indexes("token.");
+    /// assert_eq!(dumps.len(), 2);
+    ///
+    /// let other_db = TemporaryDB::new();
+    /// let fork = other_db.fork();
+    /// for dump in &dumps {
+    ///     dump.restore(&fork).unwrap();
+    /// }
+    /// let list = fork.get_proof_list::<_, u64>("token.balances");
+    /// assert_eq!(list.len(), 3);
+    /// assert_eq!(
+    ///     list.object_hash(),
+    ///     snapshot.get_proof_list::<_, u64>("token.balances").object_hash()
+    /// );
+    /// let wallets = fork.get_map::<_, u32, String>(("token.wallets", &1_u8));
+    /// assert_eq!(wallets.get(&1).unwrap(), "Alice");
+    /// ```
+    pub fn dump_indexes(&self, prefix: &str) -> Vec<IndexDump> {
+        dump_indexes(self.0.as_readonly(), prefix)
+    }
 }
 
 impl SystemSchema<&Fork> {
@@ -15,13 +15,18 @@
 use assert_matches::assert_matches;
 use url::form_urlencoded::byte_serialize;
 
-use std::{num::NonZeroU64, panic, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    num::NonZeroU64,
+    panic,
+    rc::Rc,
+};
 
 use crate::{
     access::CopyAccessExt,
     db,
     validation::is_valid_identifier,
-    views::{IndexAddress, IndexType, RawAccess, View, ViewWithMetadata},
+    views::{AccessObserver, IndexAddress, IndexType, RawAccess, View, ViewWithMetadata},
     Database, DbOptions, Fork, ListIndex, MapIndex, ResolvedAddress, RocksDB, TemporaryDB,
 };
 
@@ -390,6 +395,24 @@ fn test_database_check_incorrect_version() {
     RocksDB::open(&dir, &opts).unwrap();
 }
 
+#[test]
+fn test_database_open_snapshot() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let opts = DbOptions::default();
+    assert!(RocksDB::open_snapshot(&dir, &opts).is_err());
+
+    {
+        let db = RocksDB::open(&dir, &opts).unwrap();
+        let fork = db.fork();
+        fork.get_list("list").extend(vec![1_u32, 2, 3]);
+        db.merge(fork.into_patch()).unwrap();
+    }
+
+    let snapshot = RocksDB::open_snapshot(&dir, &opts).unwrap();
+    let list = snapshot.get_list::<_, u32>("list");
+    assert_eq!(list.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+}
+
 #[test]
 fn fork_iter() {
     test_fork_iter(&TemporaryDB::new(), IDX_NAME);
@@ -1125,3 +1148,88 @@ fn fork_from_patch() {
     db.merge(fork.into_patch())
         .expect("Fork created from patch should be merged successfully");
 }
+
+#[derive(Debug, Default)]
+struct CountingObserver {
+    reads: Cell<usize>,
+    writes: Cell<usize>,
+    bytes_written: Cell<usize>,
+    accessed_indexes: RefCell<Vec<IndexAddress>>,
+}
+
+impl AccessObserver for CountingObserver {
+    fn on_read(&self, _address: &ResolvedAddress, _key: &[u8]) {
+        self.reads.set(self.reads.get() + 1);
+    }
+
+    fn on_write(&self, _address: &ResolvedAddress, _key: Option<&[u8]>, bytes: usize) {
+        self.writes.set(self.writes.get() + 1);
+        self.bytes_written.set(self.bytes_written.get() + bytes);
+    }
+
+    fn on_index_access(&self, address: &IndexAddress) {
+        self.accessed_indexes.borrow_mut().push(address.to_owned());
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ObservedFork<'a> {
+    fork: &'a Fork,
+    observer: &'a CountingObserver,
+}
+
+impl<'a> RawAccess for ObservedFork<'a> {
+    type Changes = <&'a Fork as RawAccess>::Changes;
+
+    fn snapshot(&self) -> &dyn db::Snapshot {
+        self.fork.snapshot()
+    }
+
+    fn changes(&self, address: &ResolvedAddress) -> Self::Changes {
+        self.fork.changes(address)
+    }
+
+    fn observer(&self) -> Option<&dyn AccessObserver> {
+        Some(self.observer)
+    }
+}
+
+#[test]
+fn access_observer() {
+    let db = TemporaryDB::new();
+    let fork = db.fork();
+    let observer = CountingObserver::default();
+    let access = ObservedFork {
+        fork: &fork,
+        observer: &observer,
+    };
+
+    let mut index: MapIndex<_, u8, Vec<u8>> = access.get_map(IDX_NAME);
+    assert_eq!(
+        *observer.accessed_indexes.borrow(),
+        vec![IndexAddress::from_root(IDX_NAME)]
+    );
+    // Index creation may touch metadata; we are only interested in operations with the index.
+    let (reads, writes) = (observer.reads.get(), observer.writes.get());
+    let bytes_written = observer.bytes_written.get();
+
+    index.put(&1, vec![1, 2, 3]);
+    index.put(&2, vec![4]);
+    index.remove(&3);
+    assert_eq!(observer.writes.get() - writes, 3);
+    // Each key is 1 byte long; removal does not write a value.
+    assert_eq!(observer.bytes_written.get() - bytes_written, 4 + 2 + 1);
+
+    assert_eq!(index.get(&1), Some(vec![1, 2, 3]));
+    assert!(!index.contains(&3));
+    assert_eq!(observer.reads.get() - reads, 2);
+    assert_eq!(index.iter().count(), 2);
+    assert_eq!(observer.reads.get() - reads, 4);
+
+    // Views created from an unobserved access do not notify the observer.
+    let total_reads = observer.reads.get();
+    drop(index);
+    let unobserved: MapIndex<_, u8, Vec<u8>> = fork.get_map(IDX_NAME);
+    assert_eq!(unobserved.get(&2), Some(vec![4]));
+    assert_eq!(observer.reads.get(), total_reads);
+}
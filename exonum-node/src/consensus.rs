@@ -16,7 +16,7 @@ use anyhow::{bail, format_err};
 use exonum::{
     blockchain::{
         BlockContents, BlockKind, BlockParams, BlockPatch, Blockchain, BlockchainMut,
-        PersistentPool, ProposerId, Schema,
+        PersistentPool, ProposerId, Schema, TransactionCache,
     },
     crypto::{Hash, PublicKey},
     helpers::{Height, Round, ValidatorId},
@@ -35,7 +35,7 @@ use crate::{
         Prevote, PrevotesRequest, Propose, ProposeRequest, TransactionsRequest,
         TransactionsResponse,
     },
-    pool::{ProposeParams, ProposeTemplate},
+    pool::{transaction_size, ProposeParams, ProposeTemplate},
     schema::NodeSchema,
     state::{IncompleteBlock, ProposeState, RequestData},
     NodeHandler,
@@ -348,8 +348,8 @@ impl NodeHandler {
         }
     }
 
-    /// Checks if propose is correct (doesn't contain invalid transactions), and then
-    /// broadcasts a prevote for this propose.
+    /// Checks if propose is correct (doesn't contain invalid transactions and does not exceed
+    /// the block size limit), and then broadcasts a prevote for this propose.
     ///
     /// Returns `true` if majority of prevotes is achieved, and returns `false` otherwise.
     fn check_propose_and_broadcast_prevote(&mut self, round: Round, propose_hash: Hash) -> bool {
@@ -366,14 +366,17 @@ impl NodeHandler {
         if propose_state.has_invalid_txs() {
             warn!("Denying sending a prevote for a propose which contains incorrect transactions");
             self.state.has_majority_prevotes(round, propose_hash)
+        } else if !self.is_within_block_size_limit(propose_state.message().payload()) {
+            warn!("Denying sending a prevote for a propose which exceeds the block size limit");
+            self.state.has_majority_prevotes(round, propose_hash)
         } else {
             // Propose state is OK, send prevote.
             self.broadcast_prevote(round, propose_hash)
         }
     }
 
-    /// Checks if propose is correct (doesn't contain invalid transactions), and then
-    /// broadcasts a precommit for this propose.
+    /// Checks if propose is correct (doesn't contain invalid transactions and does not exceed
+    /// the block size limit), and then broadcasts a precommit for this propose.
     fn check_propose_and_broadcast_precommit(
         &mut self,
         round: Round,
@@ -394,12 +397,38 @@ impl NodeHandler {
             warn!(
                 "Denying sending a precommit for a propose which contains incorrect transactions"
             );
+        } else if !self.is_within_block_size_limit(propose_state.message().payload()) {
+            warn!("Denying sending a precommit for a propose which exceeds the block size limit");
         } else {
             // Propose state is OK, send precommit.
             self.broadcast_precommit(round, propose_hash, block_hash)
         }
     }
 
+    /// Checks that the total size of transactions in the propose does not exceed
+    /// the `max_block_bytes` limit of the consensus configuration. All transactions
+    /// in the propose must be known to the node.
+    fn is_within_block_size_limit(&self, propose: &Propose) -> bool {
+        let max_block_bytes = match self.state.config().max_block_bytes {
+            Some(limit) => limit,
+            None => return true,
+        };
+
+        let snapshot = self.blockchain.snapshot();
+        let pool = PersistentPool::new(snapshot.as_ref(), self.state.tx_cache());
+        let block_bytes: u64 = propose
+            .transactions
+            .iter()
+            .map(|tx_hash| {
+                let tx = pool
+                    .get_transaction(*tx_hash)
+                    .expect("BUG: Cannot find a transaction of a full propose");
+                transaction_size(&tx)
+            })
+            .sum();
+        block_bytes <= max_block_bytes
+    }
+
     /// Executes and commits block. This function is called when node has full propose information.
     ///
     /// # Panics
@@ -926,9 +955,8 @@ impl NodeHandler {
                 msg.author()
             )
         }
-        for tx in &msg.payload().transactions {
-            self.execute_later(InternalRequest::VerifyMessage(tx.to_owned()));
-        }
+        let transactions = msg.payload().transactions.clone();
+        self.execute_later(InternalRequest::VerifyMessages(transactions));
         Ok(())
     }
 
@@ -1036,7 +1064,7 @@ impl NodeHandler {
                 self.state.last_hash(),
             ),
         };
-        let propose = self.sign_message(propose);
+        let propose = self.sign_consensus_message(propose);
 
         trace!("Broadcast propose: {:?}", propose);
         self.broadcast(propose.clone());
@@ -1234,7 +1262,7 @@ impl NodeHandler {
             .validator_id()
             .expect("called broadcast_prevote in Auditor node.");
         let locked_round = self.state.locked_round();
-        let prevote = self.sign_message(Prevote::new(
+        let prevote = self.sign_consensus_message(Prevote::new(
             validator_id,
             self.state.epoch(),
             round,
@@ -1262,7 +1290,7 @@ impl NodeHandler {
             .state
             .validator_id()
             .expect("called broadcast_precommit in Auditor node.");
-        let precommit = self.sign_message(Precommit::new(
+        let precommit = self.sign_consensus_message(Precommit::new(
             validator_id,
             self.state.epoch(),
             round,
@@ -21,6 +21,9 @@ use std::time::{Duration, SystemTime};
 use super::{InternalEvent, InternalRequest, TimeoutRequest};
 use crate::messages::{ExonumMessage, Message};
 
+/// Maximum number of queued messages collected into a single verification batch.
+const MAX_VERIFICATION_BATCH_SIZE: usize = 512;
+
 /// Processor of `InternalRequest`s that emits `InternalEvent`s as a result.
 #[derive(Debug)]
 pub struct InternalPart {
@@ -49,6 +52,16 @@ impl InternalPart {
         }
     }
 
+    async fn verify_messages(raw: Vec<Vec<u8>>, internal_tx: mpsc::Sender<InternalEvent>) {
+        let task = task::spawn_blocking(|| Message::from_raw_buffers(raw));
+        if let Ok(messages) = task.await {
+            for msg in messages {
+                let event = InternalEvent::message_verified(msg);
+                Self::send_event(internal_tx.clone(), event).await;
+            }
+        }
+    }
+
     /// Represents a task that processes internal requests and produces internal events.
     /// `handle` is used to schedule additional tasks within this task.
     /// `verify_executor` is where transaction verification tasks are executed.
@@ -59,30 +72,70 @@ impl InternalPart {
             if self.internal_tx.is_closed() {
                 return;
             }
-            let internal_tx = self.internal_tx.clone();
 
-            match request {
-                InternalRequest::VerifyMessage(raw) => {
+            let mut next_request = Some(request);
+            while let Some(request) = next_request.take() {
+                next_request = self.handle_request(request);
+            }
+        }
+    }
+
+    /// Handles a single internal request. Returns the request taken from the queue
+    /// while collecting messages for batch verification, if any.
+    fn handle_request(&mut self, request: InternalRequest) -> Option<InternalRequest> {
+        let internal_tx = self.internal_tx.clone();
+        match request {
+            InternalRequest::VerifyMessage(raw) => {
+                let (mut batch, next_request) = self.collect_verification_batch(raw);
+                if batch.len() == 1 {
+                    let raw = batch.pop().unwrap();
                     tokio::spawn(Self::verify_message(raw, internal_tx));
+                } else {
+                    tokio::spawn(Self::verify_messages(batch, internal_tx));
                 }
+                return next_request;
+            }
 
-                InternalRequest::Timeout(TimeoutRequest(time, timeout)) => {
-                    let duration = time
-                        .duration_since(SystemTime::now())
-                        .unwrap_or_else(|_| Duration::from_millis(0));
+            InternalRequest::VerifyMessages(batch) => {
+                tokio::spawn(Self::verify_messages(batch, internal_tx));
+            }
 
-                    tokio::spawn(async move {
-                        delay_for(duration).await;
-                        Self::send_event(internal_tx, InternalEvent::timeout(timeout)).await;
-                    });
-                }
+            InternalRequest::Timeout(TimeoutRequest(time, timeout)) => {
+                let duration = time
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_else(|_| Duration::from_millis(0));
 
-                InternalRequest::JumpToRound(height, round) => {
-                    let event = InternalEvent::jump_to_round(height, round);
-                    tokio::spawn(Self::send_event(internal_tx, event));
-                }
+                tokio::spawn(async move {
+                    delay_for(duration).await;
+                    Self::send_event(internal_tx, InternalEvent::timeout(timeout)).await;
+                });
+            }
+
+            InternalRequest::JumpToRound(height, round) => {
+                let event = InternalEvent::jump_to_round(height, round);
+                tokio::spawn(Self::send_event(internal_tx, event));
             }
         }
+        None
+    }
+
+    /// Collects messages already queued for verification, so that their signatures
+    /// are verified in a single batch. Returns the collected messages and the first queued
+    /// request of another kind, if any.
+    fn collect_verification_batch(
+        &mut self,
+        raw: Vec<u8>,
+    ) -> (Vec<Vec<u8>>, Option<InternalRequest>) {
+        let mut batch = vec![raw];
+        while batch.len() < MAX_VERIFICATION_BATCH_SIZE {
+            match self.internal_requests_rx.try_next() {
+                Ok(Some(InternalRequest::VerifyMessage(raw))) => batch.push(raw),
+                Ok(Some(request)) => return (batch, Some(request)),
+                // The queue is empty or closed.
+                Ok(None) | Err(_) => break,
+            }
+        }
+        (batch, None)
     }
 }
 
@@ -90,7 +143,7 @@ impl InternalPart {
 mod tests {
     use exonum::{
         crypto::{Hash, KeyPair, Signature},
-        helpers::Height,
+        helpers::{Height, Round},
         messages::Verified,
     };
     use pretty_assertions::assert_eq;
@@ -140,4 +193,62 @@ mod tests {
         let event = verify_message(tx.into_bytes()).await;
         assert_eq!(event, None);
     }
+
+    async fn verify_messages(requests: Vec<InternalRequest>) -> Vec<InternalEvent> {
+        let (internal_tx, internal_rx) = mpsc::channel(16);
+        let (mut internal_requests_tx, internal_requests_rx) = mpsc::channel(16);
+
+        let internal_part = InternalPart {
+            internal_tx,
+            internal_requests_rx,
+        };
+        // Queue all requests before the `internal_part` is started.
+        for request in requests {
+            internal_requests_tx.send(request).await.unwrap();
+        }
+        drop(internal_requests_tx); // force the `internal_part` to stop
+        tokio::spawn(internal_part.run());
+        internal_rx.collect().await
+    }
+
+    fn get_messages_with_incorrect_one() -> (Vec<Vec<u8>>, Vec<InternalEvent>) {
+        let messages: Vec<_> = (0..4).map(|_| get_signed_message()).collect();
+        let expected_events = messages
+            .iter()
+            .map(|tx| InternalEvent::message_verified(Message::from_signed(tx.clone()).unwrap()))
+            .collect();
+
+        let mut incorrect_tx = get_signed_message();
+        incorrect_tx.signature = Signature::zero();
+        let mut raw: Vec<_> = messages
+            .into_iter()
+            .map(SignedMessage::into_bytes)
+            .collect();
+        raw.insert(2, incorrect_tx.into_bytes());
+        (raw, expected_events)
+    }
+
+    #[tokio::test]
+    async fn verify_msg_batch() {
+        let (raw, expected_events) = get_messages_with_incorrect_one();
+        let events = verify_messages(vec![InternalRequest::VerifyMessages(raw)]).await;
+        assert_eq!(events, expected_events);
+    }
+
+    #[tokio::test]
+    async fn verify_queued_msgs_in_batch() {
+        let (raw, expected_events) = get_messages_with_incorrect_one();
+        let mut requests: Vec<_> = raw
+            .into_iter()
+            .map(InternalRequest::VerifyMessage)
+            .collect();
+        requests.insert(3, InternalRequest::JumpToRound(Height(1), Round(2)));
+
+        let events = verify_messages(requests).await;
+        assert_eq!(events.len(), expected_events.len() + 1);
+        assert!(events.contains(&InternalEvent::jump_to_round(Height(1), Round(2))));
+        for event in &expected_events {
+            assert!(events.contains(event));
+        }
+    }
 }
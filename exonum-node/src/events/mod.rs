@@ -150,6 +150,8 @@ pub enum InternalRequest {
     JumpToRound(Height, Round),
     /// Verify a message in the thread pool.
     VerifyMessage(Vec<u8>),
+    /// Verify a batch of messages in the thread pool.
+    VerifyMessages(Vec<Vec<u8>>),
 }
 
 #[derive(Debug, PartialEq, Eq)]
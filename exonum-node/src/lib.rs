@@ -64,7 +64,8 @@ use anyhow::{ensure, format_err};
 use exonum::{
     blockchain::{
         config::GenesisConfig, ApiSender, Blockchain, BlockchainBuilder, BlockchainMut,
-        ConsensusConfig, Schema, SendError,
+        ConsensusConfig, GenesisState, QueryRequest, Schema, SendError, SimulationRequest,
+        Simulator,
     },
     crypto::{self, Hash, PublicKey},
     helpers::{user_agent, Height, Milliseconds, Round, ValidateInput, ValidatorId},
@@ -83,7 +84,7 @@ use futures::{
 };
 use log::{info, trace};
 use serde_derive::{Deserialize, Serialize};
-use tokio::time::delay_for;
+use tokio::{task, time::delay_for};
 
 use std::{
     collections::{HashMap, HashSet},
@@ -469,6 +470,9 @@ pub(crate) struct Configuration {
     pub mempool: MemoryPoolConfig,
     /// Validator keys.
     pub keys: Keys,
+    /// Staged validator keys used for signing consensus messages once they are listed
+    /// in the consensus config.
+    pub next_keys: Option<Keys>,
 }
 
 /// Channel for messages, timeouts and api requests. Consumed by the `NodeHandler` constructor.
@@ -592,6 +596,16 @@ impl NodeHandler {
         )
     }
 
+    /// Signs a consensus message (`Propose`, `Prevote` or `Precommit`) with the key listed
+    /// for the node in the current consensus config.
+    fn sign_consensus_message<T>(&self, message: T) -> Verified<T>
+    where
+        T: TryFrom<SignedMessage> + IntoMessage,
+    {
+        let keys = self.state.consensus_keys();
+        Verified::from_value(message, keys.consensus_pk(), keys.consensus_sk())
+    }
+
     /// Return internal `SharedNodeState`
     fn api_state(&self) -> &SharedNodeState {
         &self.api_state
@@ -932,6 +946,17 @@ pub struct NodeChannel {
         mpsc::Receiver<UpdateEndpoints>,
     ),
 
+    /// Channel for transaction simulation requests. The requests are processed
+    /// outside of the node event loop once the node is launched.
+    simulations: (
+        mpsc::Sender<SimulationRequest>,
+        mpsc::Receiver<SimulationRequest>,
+    ),
+
+    /// Channel for read-only service queries. The requests are processed
+    /// outside of the node event loop once the node is launched.
+    queries: (mpsc::Sender<QueryRequest>, mpsc::Receiver<QueryRequest>),
+
     /// Channel for externally generated transactions.
     #[doc(hidden)] // public because of the `transactions` benchmark
     pub transactions: (
@@ -1001,6 +1026,8 @@ impl NodeChannel {
             network_requests: mpsc::channel(buffer_sizes.network_requests_capacity),
             internal_requests: mpsc::channel(buffer_sizes.internal_events_capacity),
             endpoints: mpsc::channel(buffer_sizes.internal_events_capacity),
+            simulations: mpsc::channel(buffer_sizes.api_requests_capacity),
+            queries: mpsc::channel(buffer_sizes.api_requests_capacity),
             transactions: mpsc::channel(buffer_sizes.api_requests_capacity),
             api_requests: mpsc::channel(buffer_sizes.api_requests_capacity),
             network_events: mpsc::channel(buffer_sizes.network_events_capacity),
@@ -1038,6 +1065,7 @@ pub struct NodeBuilder {
     blockchain_builder: BlockchainBuilder,
     node_config: NodeConfig,
     node_keys: Keys,
+    next_node_keys: Option<Keys>,
     config_manager: Option<Box<dyn ConfigManager>>,
     pool_manager: Box<dyn ManagePool>,
     plugins: Vec<Box<dyn NodePlugin>>,
@@ -1067,7 +1095,11 @@ impl NodeBuilder {
             .expect("Node configuration is inconsistent");
 
         let channel = NodeChannel::new(&node_config.mempool.events_pool_capacity);
-        let blockchain = Blockchain::new(database, node_keys.service.clone(), channel.api_sender());
+        let simulation_sender = ApiSender::new(channel.simulations.0.clone());
+        let query_sender = ApiSender::new(channel.queries.0.clone());
+        let blockchain = Blockchain::new(database, node_keys.service.clone(), channel.api_sender())
+            .with_simulation_sender(simulation_sender)
+            .with_query_sender(query_sender);
         let blockchain_builder = BlockchainBuilder::new(blockchain);
 
         Self {
@@ -1075,6 +1107,7 @@ impl NodeBuilder {
             blockchain_builder,
             node_config,
             node_keys,
+            next_node_keys: None,
             config_manager: None,
             plugins: vec![],
             pool_manager: Box::new(StandardPoolManager::default()),
@@ -1088,6 +1121,13 @@ impl NodeBuilder {
         self
     }
 
+    /// Adds a state of services to restore in the genesis block if the blockchain
+    /// is not initialized yet.
+    pub fn with_genesis_state(mut self, genesis_state: GenesisState) -> Self {
+        self.blockchain_builder = self.blockchain_builder.with_genesis_state(genesis_state);
+        self
+    }
+
     /// Adds a runtime to the blockchain.
     pub fn with_runtime<T>(mut self, runtime: T) -> Self
     where
@@ -1108,6 +1148,29 @@ impl NodeBuilder {
         self
     }
 
+    /// Stages keys for rotating the consensus key of the node. The node will sign consensus
+    /// messages with the staged keys as soon as the consensus config lists
+    /// their consensus key for the node, e.g., after a key rotation performed via
+    /// the supervisor service. Until then, the node uses the main keys.
+    ///
+    /// The staged keys are not used for network communication: the network identity
+    /// of the node remains bound to the main consensus key until the node is restarted
+    /// with the new keys as main ones. Since the service key is not rotated, the staged
+    /// keys should have the same service keypair as the main ones.
+    ///
+    /// # Panics
+    ///
+    /// - If the service keypair of the staged keys differs from that of the main keys.
+    pub fn with_next_keys(mut self, next_keys: Keys) -> Self {
+        assert_eq!(
+            next_keys.service_pk(),
+            self.node_keys.service_pk(),
+            "Service key cannot be rotated together with the consensus key"
+        );
+        self.next_node_keys = Some(next_keys);
+        self
+    }
+
     /// Adds the configuration manager.
     pub fn with_config_manager<T: ConfigManager + 'static>(mut self, manager: T) -> Self {
         self.config_manager = Some(Box::new(manager));
@@ -1157,6 +1220,7 @@ impl NodeBuilder {
             self.channel,
             self.node_config,
             self.node_keys,
+            self.next_node_keys,
             self.config_manager,
             self.plugins,
             self.pool_manager,
@@ -1168,11 +1232,13 @@ impl NodeBuilder {
 
 impl Node {
     /// Creates a node for the given blockchain and node configuration.
+    #[allow(clippy::too_many_arguments)]
     fn with_blockchain(
         blockchain: BlockchainMut,
         channel: NodeChannel,
         node_cfg: NodeConfig,
         node_keys: Keys,
+        next_node_keys: Option<Keys>,
         config_manager: Option<Box<dyn ConfigManager>>,
         plugins: Vec<Box<dyn NodePlugin>>,
         pool_manager: Box<dyn ManagePool>,
@@ -1186,6 +1252,7 @@ impl Node {
             network: node_cfg.network,
             peer_discovery: peers,
             keys: node_keys,
+            next_keys: next_node_keys,
         };
 
         let api_state = SharedNodeState::new(node_cfg.api.state_update_timeout as u64);
@@ -1303,11 +1370,19 @@ impl Node {
     }
 }
 
+/// Maximum number of simulation and tracing requests processed concurrently by the node.
+const MAX_CONCURRENT_SIMULATIONS: usize = 2;
+/// Maximum number of service queries processed concurrently by the node.
+const MAX_CONCURRENT_QUERIES: usize = 4;
+
 struct Reactor {
     handler_part: HandlerPart<NodeHandler>,
     network_part: NetworkPart,
     internal_part: InternalPart,
     api_part: oneshot::Receiver<io::Result<()>>,
+    simulations_rx: mpsc::Receiver<SimulationRequest>,
+    simulator: Simulator,
+    queries_rx: mpsc::Receiver<QueryRequest>,
     shutdown_handle: ShutdownHandle,
     // Flag indicating whether the reactor should explicitly handle signals.
     // If there is at least one actix HTTP server, signal handling will be performed by it,
@@ -1352,6 +1427,7 @@ impl Reactor {
         };
 
         let (internal_tx, internal_rx) = node.channel.internal_events;
+        let simulator = node.handler.blockchain.simulator();
         let handler_part = HandlerPart {
             handler: node.handler,
             internal_rx,
@@ -1370,6 +1446,9 @@ impl Reactor {
             network_part,
             internal_part,
             api_part,
+            simulations_rx: node.channel.simulations.1,
+            simulator,
+            queries_rx: node.channel.queries.1,
             shutdown_handle,
             needs_signal_handler,
         }
@@ -1426,6 +1505,32 @@ impl Reactor {
         futures::pin_mut!(handler_task);
         let mut api_task = self.api_part.fuse();
 
+        // Simulations are processed outside of the event loop, so that they do not delay
+        // consensus messages; the number of simulations processed concurrently is bounded.
+        // The task is dropped together with other tasks, which releases the services shared
+        // with the simulator.
+        let simulator = self.simulator;
+        let query_simulator = simulator.clone();
+        let simulation_task = self
+            .simulations_rx
+            .for_each_concurrent(MAX_CONCURRENT_SIMULATIONS, move |request| {
+                let simulator = simulator.clone();
+                task::spawn_blocking(move || request.process(&simulator)).map(drop)
+            })
+            .fuse();
+        futures::pin_mut!(simulation_task);
+
+        // Queries are processed in the same way as simulations, with a bounded number
+        // of queries processed concurrently.
+        let query_task = self
+            .queries_rx
+            .for_each_concurrent(MAX_CONCURRENT_QUERIES, move |request| {
+                let simulator = query_simulator.clone();
+                task::spawn_blocking(move || request.process(&simulator)).map(drop)
+            })
+            .fuse();
+        futures::pin_mut!(query_task);
+
         if self.needs_signal_handler {
             // Send the shutdown signal once we received a signal.
             let shutdown_handle = self.shutdown_handle.clone();
@@ -1443,6 +1548,8 @@ impl Reactor {
             () = internal_task => (Ok(()), true),
             () = network_task => (Ok(()), true),
             () = handler_task => (Ok(()), false),
+            () = simulation_task => (Ok(()), true),
+            () = query_task => (Ok(()), true),
 
             res = api_task => {
                 let res = match res {
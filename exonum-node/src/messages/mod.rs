@@ -165,6 +165,20 @@ impl Message {
         SignedMessage::from_bytes(buffer.into()).and_then(Self::from_signed)
     }
 
+    /// Checks buffers and returns instances of `Message` for the valid ones. Signatures
+    /// of the messages are verified in a single batch; invalid messages are skipped.
+    pub fn from_raw_buffers(buffers: Vec<Vec<u8>>) -> Vec<Self> {
+        let messages = buffers
+            .into_iter()
+            .filter_map(|buffer| SignedMessage::from_bytes(buffer.into()).ok())
+            .collect();
+        SignedMessage::into_verified_batch::<ExonumMessage>(messages)
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(Self::from)
+            .collect()
+    }
+
     /// Get inner `SignedMessage`.
     pub fn as_raw(&self) -> &SignedMessage {
         match self {
@@ -56,10 +56,12 @@
 //! [Exonum white paper]: https://bitfury.com/content/downloads/wp_consensus_181227.pdf
 
 use exonum::{
-    blockchain::{Blockchain, ConsensusConfig, PersistentPool, TransactionCache, TxCheckCache},
+    blockchain::{
+        Blockchain, ConsensusConfig, PersistentPool, Schema, TransactionCache, TxCheckCache,
+    },
     crypto::Hash,
     helpers::{Height, Round},
-    merkledb::Snapshot,
+    merkledb::{BinaryValue, Snapshot},
     messages::{AnyTx, Verified},
 };
 
@@ -70,6 +72,12 @@ use crate::State;
 /// Type alias for the persistent pool.
 pub type Pool<'a> = PersistentPool<'a, BTreeMap<Hash, Verified<AnyTx>>, &'a dyn Snapshot>;
 
+/// Returns the size of the transaction counted against the `max_block_bytes` limit
+/// of the consensus configuration.
+pub fn transaction_size(transaction: &Verified<AnyTx>) -> u64 {
+    transaction.as_raw().to_bytes().len() as u64
+}
+
 /// Block proposal parameters supplied to the proposer from the node.
 #[derive(Debug)]
 pub struct ProposeParams<'a> {
@@ -124,7 +132,12 @@ pub enum ProposeTemplate {
     /// - Transactions with the specified hashes are known to the node
     /// - Transaction hashes do not repeat
     /// - The amount of hashes is not higher than the constraints in the `ConsensusConfig`
-    /// - Transactions with the specified hashes are correct (i.e., pass `Blockchain::check_tx`).
+    /// - The total size of transactions does not exceed `max_block_bytes`
+    ///   from the `ConsensusConfig` (see [`transaction_size`])
+    /// - Transactions with the specified hashes are correct (i.e., pass `Blockchain::check_tx`)
+    ///   and their validity windows contain the next blockchain height.
+    ///
+    /// [`transaction_size`]: fn.transaction_size.html
     Ordinary {
         /// Hashes of the transactions in the proposal.
         tx_hashes: Vec<Hash>,
@@ -201,9 +214,13 @@ impl ManagePool for Box<dyn ManagePool> {
 
 /// Standard pool manager used by the nodes if no other manager is specified.
 ///
-/// The manager will propose correct transactions in no particular order. It will also remove
+/// The manager will propose correct transactions in no particular order, skipping transactions
+/// which do not fit into the `max_block_bytes` limit of the consensus configuration. It will also remove
 /// incorrect transactions from the pool, unless this setting is switched off by using
-/// [`with_removal_limit`]`(0)`.
+/// [`with_removal_limit`]`(0)`. Incorrect transactions include transactions with
+/// an expired validity window, i.e., ones that can no longer be committed. Transactions
+/// with a validity window starting after the next blockchain height are kept in the pool,
+/// but are not proposed until the window starts.
 ///
 /// [`with_removal_limit`]: #method.with_removal_limit
 #[derive(Debug, Clone)]
@@ -249,8 +266,11 @@ impl StandardPoolManager {
 impl ManagePool for StandardPoolManager {
     fn propose_block(&mut self, pool: Pool<'_>, params: ProposeParams<'_>) -> ProposeTemplate {
         let max_transactions = params.consensus_config.txs_block_limit;
+        let max_block_bytes = params.consensus_config.max_block_bytes;
         let snapshot = params.snapshot();
+        let next_height = Schema::new(snapshot).next_height();
         let mut cache = TxCheckCache::new();
+        let mut block_bytes = 0;
 
         let tx_hashes = pool
             .transactions()
@@ -258,11 +278,22 @@ impl ManagePool for StandardPoolManager {
                 // TODO: this is wildly inefficient.
                 // It should be easy to cache tx status within single height; however,
                 // spanning cache across multiple heights would be significantly harder.
-                if Blockchain::check_tx_with_cache(snapshot, tx.as_ref(), &mut cache).is_ok() {
-                    Some(tx_hash)
-                } else {
-                    None
+                if Blockchain::check_tx_with_cache(snapshot, tx.as_ref(), &mut cache).is_err() {
+                    return None;
+                }
+                // Transactions which are not valid yet are retained in the pool.
+                if !tx.payload().validity.contains(next_height) {
+                    return None;
+                }
+
+                if let Some(max_block_bytes) = max_block_bytes {
+                    let tx_size = transaction_size(tx.as_ref());
+                    if block_bytes + tx_size > max_block_bytes {
+                        return None;
+                    }
+                    block_bytes += tx_size;
                 }
+                Some(tx_hash)
             })
             .take(max_transactions as usize);
 
@@ -153,6 +153,17 @@ impl SandboxInner {
                     self.handler
                         .handle_event(InternalEvent::message_verified(msg).into());
                 }
+
+                InternalRequest::VerifyMessages(raw) => {
+                    let count = raw.len();
+                    let messages = Message::from_raw_buffers(raw);
+                    assert_eq!(messages.len(), count, "Some messages failed verification");
+
+                    for msg in messages {
+                        self.handler
+                            .handle_event(InternalEvent::message_verified(msg).into());
+                    }
+                }
             }
         }
     }
@@ -951,6 +962,7 @@ impl Sandbox {
             .map(|(pk, connect)| (*pk, connect.to_owned()));
         let connect_list = ConnectList::from_peers(peers);
         let keys = inner.handler.state().keys().to_owned();
+        let next_keys = inner.handler.state().next_keys().cloned();
 
         let config = Configuration {
             connect_list,
@@ -958,6 +970,7 @@ impl Sandbox {
             peer_discovery: Vec::new(),
             mempool: MemoryPoolConfig::default(),
             keys,
+            next_keys,
         };
 
         let shared_time = SharedTime::new(Mutex::new(time));
@@ -1021,6 +1034,7 @@ pub struct SandboxBuilder {
     instances: Vec<InstanceInitParams>,
     artifacts: HashMap<ArtifactId, Vec<u8>>,
     pool_manager: Box<dyn ManagePool>,
+    next_consensus_keys: Option<KeyPair>,
 }
 
 impl Default for SandboxBuilder {
@@ -1048,6 +1062,7 @@ impl Default for SandboxBuilder {
             instances: Vec::new(),
             artifacts: HashMap::new(),
             pool_manager: Box::new(StandardPoolManager::default()),
+            next_consensus_keys: None,
         }
     }
 }
@@ -1086,6 +1101,12 @@ impl SandboxBuilder {
         self
     }
 
+    /// Stages a new consensus keypair for the sandbox node.
+    pub fn with_next_consensus_keys(mut self, keys: KeyPair) -> Self {
+        self.next_consensus_keys = Some(keys);
+        self
+    }
+
     /// Adds instances descriptions to the testkit that will be used for specification of builtin
     /// services of testing blockchain.
     pub fn with_instance(mut self, instance: impl Into<InstanceInitParams>) -> Self {
@@ -1126,6 +1147,7 @@ impl SandboxBuilder {
             self.instances,
             self.consensus_config,
             self.validators_count,
+            self.next_consensus_keys,
         );
         sandbox.inner.borrow_mut().handler.pool_manager = self.pool_manager;
 
@@ -1170,6 +1192,7 @@ fn sandbox_with_services_uninitialized(
     instances: Vec<InstanceInitParams>,
     consensus: ConsensusConfig,
     validators_count: u8,
+    next_consensus_keys: Option<KeyPair>,
 ) -> Sandbox {
     let keys = (0..validators_count)
         .map(|i| {
@@ -1181,8 +1204,12 @@ fn sandbox_with_services_uninitialized(
         .map(|(consensus, service)| Keys::from_keys(consensus, service))
         .collect::<Vec<_>>();
 
+    let next_keys =
+        next_consensus_keys.map(|consensus| Keys::from_keys(consensus, keys[0].service.clone()));
+
     let validators = keys
         .iter()
+        .chain(&next_keys)
         .map(|keys| (keys.consensus_pk(), keys.consensus_sk().clone()))
         .collect::<Vec<_>>();
 
@@ -1234,6 +1261,7 @@ fn sandbox_with_services_uninitialized(
         peer_discovery: Vec::new(),
         mempool: MemoryPoolConfig::default(),
         keys: keys[0].clone(),
+        next_keys,
     };
 
     let system_state = SandboxSystemStateProvider {
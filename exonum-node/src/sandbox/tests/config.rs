@@ -15,11 +15,15 @@
 //! Tests in this module are designed to test configuration change protocol.
 
 use exonum::{
+    crypto::KeyPair,
     helpers::{Height, ValidatorId},
     merkledb::BinaryValue,
 };
 
-use crate::sandbox::{sandbox_tests_helper::*, supervisor::TxConfig, timestamping_sandbox};
+use crate::sandbox::{
+    sandbox_tests_helper::*, supervisor::TxConfig, timestamping_sandbox,
+    timestamping_sandbox_builder,
+};
 
 /// - exclude validator from consensus
 /// - idea of test is to exclude sandbox validator from consensus
@@ -48,5 +52,49 @@ fn test_exclude_validator_from_consensus() {
     add_one_height_with_transactions_from_other_validator(&sandbox, &sandbox_state, &[]);
 }
 
+/// - rotate consensus key of the sandbox validator
+/// - idea of test is to check that node signs consensus messages with the staged key
+///   once it is listed in the consensus config
+#[test]
+fn test_rotate_consensus_key() {
+    let next_keys = KeyPair::random();
+    let sandbox = timestamping_sandbox_builder()
+        .with_next_consensus_keys(next_keys.clone())
+        .build();
+    let sandbox_state = SandboxState::new();
+    let old_key = sandbox.public_key(ValidatorId(0));
+
+    add_one_height(&sandbox, &sandbox_state);
+    assert_eq!(
+        sandbox.node_state().consensus_keys().consensus_pk(),
+        old_key
+    );
+
+    let tx_cfg = {
+        let mut consensus_cfg = sandbox.cfg();
+        consensus_cfg.validator_keys[0].consensus_key = next_keys.public_key();
+
+        TxConfig::create_signed(
+            old_key,
+            &consensus_cfg.into_bytes(),
+            Height(0),
+            sandbox.secret_key(ValidatorId(0)),
+        )
+    };
+
+    add_one_height_with_transactions(&sandbox, &sandbox_state, &[tx_cfg]);
+    assert_eq!(sandbox.public_key(ValidatorId(0)), next_keys.public_key());
+    assert_eq!(
+        sandbox.node_state().consensus_keys().consensus_pk(),
+        next_keys.public_key()
+    );
+    // Network identity of the node is not changed.
+    assert_eq!(sandbox.node_state().keys().consensus_pk(), old_key);
+
+    // Node continues to participate in consensus using the new key.
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+}
+
 // - include validator to consensus
 // TODO [ECR-3222]
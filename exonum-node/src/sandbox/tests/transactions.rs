@@ -21,13 +21,21 @@ use exonum::{
     helpers::{Height, Round, ValidatorId},
     merkledb::{BinaryValue, ObjectHash, Snapshot},
     messages::{AnyTx, Verified},
+    runtime::{CallInfo, TxValidity},
 };
 
-use std::{collections::BTreeSet, iter::FromIterator, time::Duration};
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet},
+    iter::FromIterator,
+    time::Duration,
+};
 
 use crate::{
     messages::{TX_RES_EMPTY_SIZE, TX_RES_PB_OVERHEAD_PAYLOAD},
-    pool::{ManagePool, Pool, ProposeParams, ProposeTemplate, StandardPoolManager},
+    pool::{
+        transaction_size, ManagePool, Pool, ProposeParams, ProposeTemplate, StandardPoolManager,
+    },
     sandbox::{
         sandbox_tests_helper::*,
         supervisor::{Supervisor, SupervisorService, TxConfig},
@@ -42,6 +50,7 @@ use crate::{
 const MAX_PROPOSE_TIMEOUT: Milliseconds = 200;
 const MIN_PROPOSE_TIMEOUT: Milliseconds = 10;
 const PROPOSE_THRESHOLD: u32 = 3;
+const MAX_MESSAGE_LEN: u32 = 1_024;
 
 fn timestamping_sandbox_with_threshold() -> Sandbox {
     let sandbox = timestamping_sandbox_builder()
@@ -792,6 +801,83 @@ fn propose_with_custom_logic() {
     sandbox.broadcast(&prevote);
 }
 
+/// Creates a sandbox with the block size limit allowing to fit only one transaction created
+/// by `large_timestamping_tx`.
+fn sandbox_with_block_size_limit() -> Sandbox {
+    timestamping_sandbox_builder()
+        .with_consensus(|config| {
+            config.max_message_len = MAX_MESSAGE_LEN;
+            config.max_block_bytes = Some(MAX_MESSAGE_LEN.into());
+        })
+        .build()
+}
+
+fn large_timestamping_tx(data: u8) -> Verified<AnyTx> {
+    let tx = KeyPair::random().timestamp(TimestampingService::ID, vec![data; 600]);
+    assert!(transaction_size(&tx) * 2 > u64::from(MAX_MESSAGE_LEN));
+    tx
+}
+
+#[test]
+fn propose_respects_block_size_limit() {
+    let sandbox = sandbox_with_block_size_limit();
+    let tx = large_timestamping_tx(1);
+    let other_tx = large_timestamping_tx(2);
+    sandbox.recv(&tx);
+    sandbox.recv(&other_tx);
+
+    while !sandbox.is_leader() {
+        sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+    }
+    sandbox.add_time(Duration::from_millis(sandbox.current_round_timeout()));
+
+    // Transactions are taken from the pool in the order of their hashes;
+    // only the first transaction fits into the block.
+    let first_tx_hash = cmp::min(tx.object_hash(), other_tx.object_hash());
+    let propose = sandbox.create_propose(
+        ValidatorId(0),
+        Height(1),
+        Round(3),
+        sandbox.last_hash(),
+        vec![first_tx_hash],
+        sandbox.secret_key(ValidatorId(0)),
+    );
+    sandbox.broadcast(&propose);
+    let prevote = sandbox.create_prevote(
+        ValidatorId(0),
+        Height(1),
+        Round(3),
+        propose.object_hash(),
+        NOT_LOCKED,
+        sandbox.secret_key(ValidatorId(0)),
+    );
+    sandbox.broadcast(&prevote);
+}
+
+#[test]
+fn no_prevote_for_propose_exceeding_block_size_limit() {
+    let sandbox = sandbox_with_block_size_limit();
+    let tx = large_timestamping_tx(1);
+    let other_tx = large_timestamping_tx(2);
+    sandbox.recv(&tx);
+    sandbox.recv(&other_tx);
+
+    let propose = sandbox.create_propose(
+        ValidatorId(2),
+        Height(1),
+        Round(1),
+        sandbox.last_hash(),
+        vec![tx.object_hash(), other_tx.object_hash()],
+        sandbox.secret_key(ValidatorId(2)),
+    );
+    sandbox.recv(&propose);
+
+    // The node does not vote for the propose.
+    sandbox.process_events();
+    assert!(sandbox.pop_sent_message().is_none());
+    sandbox.assert_state(Height(1), Round(1));
+}
+
 #[test]
 fn custom_proposer_does_not_influence_external_proposes() {
     let keypair = KeyPair::random();
@@ -1020,3 +1106,91 @@ fn not_proposing_incorrect_transactions() {
 fn not_proposing_incorrect_transactions_with_no_tx_removal() {
     test_not_proposing_incorrect_transactions(false);
 }
+
+#[test]
+fn expired_transactions_are_removed_from_pool() {
+    let sandbox = timestamping_sandbox();
+    add_one_height(&sandbox, &SandboxState::new());
+    let next_height = sandbox.current_epoch();
+    assert_eq!(next_height, Height(2));
+
+    let keys = KeyPair::random();
+    let tx_with_validity = |validity| {
+        AnyTx::new(CallInfo::new(TimestampingService::ID, 0), vec![])
+            .with_validity(validity)
+            .sign_with_keypair(&keys)
+    };
+    let expired_tx = tx_with_validity(TxValidity::default().with_valid_until(Height(1)));
+    let last_chance_tx = tx_with_validity(TxValidity::default().with_valid_until(next_height));
+    let unbounded_tx = gen_timestamping_tx();
+
+    let snapshot = sandbox.blockchain().snapshot();
+    let tx_cache: BTreeMap<_, _> = vec![&expired_tx, &last_chance_tx, &unbounded_tx]
+        .into_iter()
+        .map(|tx| (tx.object_hash(), tx.clone()))
+        .collect();
+    let pool = Pool::new(snapshot.as_ref(), &tx_cache);
+
+    let removed = StandardPoolManager::default().remove_transactions(pool, snapshot.as_ref());
+    assert_eq!(removed, vec![expired_tx.object_hash()]);
+
+    // The removal limit is respected for expired transactions as well.
+    let pool = Pool::new(snapshot.as_ref(), &tx_cache);
+    let removed =
+        StandardPoolManager::with_removal_limit(0).remove_transactions(pool, snapshot.as_ref());
+    assert!(removed.is_empty());
+}
+
+#[test]
+fn future_transactions_are_kept_in_pool() {
+    let sandbox = timestamping_sandbox();
+    add_one_height(&sandbox, &SandboxState::new());
+    let next_height = sandbox.current_epoch();
+    assert_eq!(next_height, Height(2));
+
+    let keys = KeyPair::random();
+    let tx_with_validity = |validity| {
+        AnyTx::new(CallInfo::new(TimestampingService::ID, 0), vec![])
+            .with_validity(validity)
+            .sign_with_keypair(&keys)
+    };
+    let current_tx = tx_with_validity(TxValidity::default().with_valid_from(next_height));
+    let future_tx = tx_with_validity(TxValidity::default().with_valid_from(next_height.next()));
+
+    let snapshot = sandbox.blockchain().snapshot();
+    Blockchain::check_tx(&snapshot, &future_tx).expect("Future transaction must pass `check_tx`");
+    let tx_cache: BTreeMap<_, _> = vec![&current_tx, &future_tx]
+        .into_iter()
+        .map(|tx| (tx.object_hash(), tx.clone()))
+        .collect();
+
+    // The future transaction is not removed from the pool...
+    let mut manager = StandardPoolManager::default();
+    let pool = Pool::new(snapshot.as_ref(), &tx_cache);
+    let removed = manager.remove_transactions(pool, snapshot.as_ref());
+    assert!(removed.is_empty());
+
+    // ...but it is not proposed either.
+    let pool = Pool::new(snapshot.as_ref(), &tx_cache);
+    let params = ProposeParams::new(&sandbox.node_state(), snapshot.as_ref());
+    match manager.propose_block(pool, params) {
+        ProposeTemplate::Ordinary { tx_hashes } => {
+            assert_eq!(tx_hashes, vec![current_tx.object_hash()]);
+        }
+        ProposeTemplate::Skip => panic!("Unexpected block skip"),
+    }
+
+    // Once the window of the transaction starts, it is proposed.
+    add_one_height(&sandbox, &SandboxState::new());
+    let snapshot = sandbox.blockchain().snapshot();
+    let pool = Pool::new(snapshot.as_ref(), &tx_cache);
+    let params = ProposeParams::new(&sandbox.node_state(), snapshot.as_ref());
+    match manager.propose_block(pool, params) {
+        ProposeTemplate::Ordinary { tx_hashes } => {
+            let tx_hashes: BTreeSet<_> = tx_hashes.into_iter().collect();
+            let expected_hashes: BTreeSet<_> = tx_cache.keys().copied().collect();
+            assert_eq!(tx_hashes, expected_hashes);
+        }
+        ProposeTemplate::Skip => panic!("Unexpected block skip"),
+    }
+}
@@ -27,7 +27,7 @@ use exonum::{
     merkledb::{access::RawAccess, KeySetIndex, MapIndex, ObjectHash, Snapshot},
     messages::{AnyTx, Precommit, Verified},
 };
-use log::{error, trace};
+use log::{error, info, trace};
 
 use std::{
     collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
@@ -169,7 +169,13 @@ pub(crate) struct State {
     // TODO: This may be a vector for DoS attacks by memory exhaustion. [ECR-2067]
     invalid_txs: HashSet<Hash>,
 
+    // Keys used by the node for network communication.
     keys: Keys,
+    // Staged keys used for signing consensus messages once the consensus config
+    // lists their consensus key for this node.
+    next_keys: Option<Keys>,
+    // Whether the consensus config lists `next_keys` rather than `keys` for this node.
+    uses_next_keys: bool,
 }
 
 /// State of a validator node.
@@ -534,6 +540,25 @@ impl SharedConnectList {
     }
 }
 
+/// Finds the validator ID of the node in the consensus config. The staged keys take precedence
+/// over the main ones. Returns the ID and a flag whether it corresponds to the staged keys.
+fn find_validator_id(
+    config: &ConsensusConfig,
+    keys: &Keys,
+    next_keys: Option<&Keys>,
+) -> (Option<ValidatorId>, bool) {
+    let find = |keys: &Keys| {
+        let consensus_key = keys.consensus_pk();
+        config.find_validator(|validator_keys| validator_keys.consensus_key == consensus_key)
+    };
+
+    if let Some(validator_id) = next_keys.and_then(find) {
+        (Some(validator_id), true)
+    } else {
+        (find(keys), false)
+    }
+}
+
 impl State {
     /// Creates state with the given parameters.
     pub fn new(
@@ -545,10 +570,8 @@ impl State {
         last_block_skip: Option<&Block>,
         epoch_start_time: SystemTime,
     ) -> Self {
-        let validator_id = consensus_config
-            .validator_keys
-            .iter()
-            .position(|pk| pk.consensus_key == config.keys.consensus_pk());
+        let (validator_id, uses_next_keys) =
+            find_validator_id(&consensus_config, &config.keys, config.next_keys.as_ref());
 
         let our_connect_message = Verified::from_value(
             connect,
@@ -561,7 +584,7 @@ impl State {
             .expect("No `epoch` recorded in the saved block");
 
         Self {
-            validator_state: validator_id.map(|id| ValidatorState::new(ValidatorId(id as u16))),
+            validator_state: validator_id.map(ValidatorState::new),
             connect_list: SharedConnectList::from_connect_list(config.connect_list),
             peers,
             connections: HashMap::new(),
@@ -597,6 +620,8 @@ impl State {
             invalid_txs: HashSet::default(),
 
             keys: config.keys,
+            next_keys: config.next_keys,
+            uses_next_keys,
         }
     }
 
@@ -663,11 +688,15 @@ impl State {
         }
 
         trace!("Updating node config={:#?}", config);
-        let validator_id = config
-            .validator_keys
-            .iter()
-            .position(|pk| pk.consensus_key == self.keys().consensus_pk())
-            .map(|id| ValidatorId(id as u16));
+        let (validator_id, uses_next_keys) =
+            find_validator_id(&config, &self.keys, self.next_keys.as_ref());
+        if uses_next_keys != self.uses_next_keys {
+            self.uses_next_keys = uses_next_keys;
+            info!(
+                "Switched to signing consensus messages with key {}",
+                self.consensus_keys().consensus_pk()
+            );
+        }
 
         // TODO: update connect list (ECR-1745)
 
@@ -727,11 +756,27 @@ impl State {
         self.validators().get(id).map(|x| x.consensus_key)
     }
 
-    /// Returns the keys of this node.
+    /// Returns the keys of this node used for network communication.
     pub fn keys(&self) -> &Keys {
         &self.keys
     }
 
+    /// Returns the staged keys of this node, if any.
+    #[cfg(test)]
+    pub(crate) fn next_keys(&self) -> Option<&Keys> {
+        self.next_keys.as_ref()
+    }
+
+    /// Returns the keys used to sign consensus messages, i.e., the keys listed
+    /// for this node in the current consensus config. If the config lists neither
+    /// the main nor the staged keys, returns the main keys.
+    pub(crate) fn consensus_keys(&self) -> &Keys {
+        match self.next_keys {
+            Some(ref next_keys) if self.uses_next_keys => next_keys,
+            _ => &self.keys,
+        }
+    }
+
     /// Returns the leader id for the specified round and current height.
     pub fn leader(&self, round: Round) -> ValidatorId {
         let epoch: u64 = self.epoch().into();
@@ -17,9 +17,13 @@
 #![allow(clippy::trivially_copy_pass_by_ref)]
 
 use criterion::{
-    AxisScale, Bencher, Criterion, ParameterizedBenchmark, PlotConfiguration, Throughput,
+    AxisScale, BatchSize, Bencher, Criterion, ParameterizedBenchmark, PlotConfiguration, Throughput,
+};
+use exonum::{
+    crypto::{hash, sign, verify, verify_batch, KeyPair, PublicKey, Signature},
+    messages::{AnyTx, SignedMessage},
+    runtime::CallInfo,
 };
-use exonum::crypto::{hash, sign, verify, KeyPair};
 
 use std::convert::TryInto;
 
@@ -36,6 +40,70 @@ fn bench_verify(b: &mut Bencher<'_>, &count: &usize) {
     b.iter(|| verify(&signature, &data, &keys.public_key()))
 }
 
+/// Creates `count` signatures over 128-byte messages produced by different keys.
+fn gen_signatures(count: usize) -> Vec<(Signature, Vec<u8>, PublicKey)> {
+    (0..count)
+        .map(|i| {
+            let keys = KeyPair::random();
+            let data = (0..128).map(|x| ((x + i) % 255) as u8).collect::<Vec<u8>>();
+            let signature = sign(&data, keys.secret_key());
+            (signature, data, keys.public_key())
+        })
+        .collect()
+}
+
+fn bench_verify_one_by_one(b: &mut Bencher<'_>, &count: &usize) {
+    let signatures = gen_signatures(count);
+    b.iter(|| {
+        signatures
+            .iter()
+            .all(|(signature, data, public_key)| verify(signature, data, public_key))
+    })
+}
+
+fn bench_verify_batch(b: &mut Bencher<'_>, &count: &usize) {
+    let signatures = gen_signatures(count);
+    let batch: Vec<_> = signatures
+        .iter()
+        .map(|(signature, data, public_key)| (signature, &data[..], public_key))
+        .collect();
+    b.iter(|| verify_batch(&batch))
+}
+
+fn gen_transactions(count: usize) -> Vec<SignedMessage> {
+    (0..count)
+        .map(|i| {
+            let keys = KeyPair::random();
+            AnyTx::new(CallInfo::new(1, 0), vec![i as u8; 64])
+                .sign_with_keypair(&keys)
+                .into_raw()
+        })
+        .collect()
+}
+
+fn bench_verify_txs_one_by_one(b: &mut Bencher<'_>, &count: &usize) {
+    let transactions = gen_transactions(count);
+    b.iter_batched(
+        || transactions.clone(),
+        |transactions| {
+            transactions
+                .into_iter()
+                .map(SignedMessage::into_verified::<AnyTx>)
+                .collect::<Vec<_>>()
+        },
+        BatchSize::SmallInput,
+    )
+}
+
+fn bench_verify_txs_batch(b: &mut Bencher<'_>, &count: &usize) {
+    let transactions = gen_transactions(count);
+    b.iter_batched(
+        || transactions.clone(),
+        SignedMessage::into_verified_batch::<AnyTx>,
+        BatchSize::SmallInput,
+    )
+}
+
 fn bench_hash(b: &mut Bencher<'_>, &count: &usize) {
     let data = (0..count).map(|x| (x % 255) as u8).collect::<Vec<u8>>();
     b.iter(|| hash(&data))
@@ -67,4 +135,29 @@ pub fn bench_crypto(c: &mut Criterion) {
             .throughput(|s| Throughput::Bytes((*s).try_into().unwrap()))
             .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic)),
     );
+
+    // Comparing batch verification of signatures with verifying them one by one
+    // for different batch sizes, from 2^2 = 4 to 2^9 = 512 signatures.
+    c.bench(
+        "verify_batch",
+        ParameterizedBenchmark::new(
+            "one_by_one",
+            bench_verify_one_by_one,
+            (2..10).map(|i| 1 << i),
+        )
+        .with_function("batch", bench_verify_batch)
+        .throughput(|s| Throughput::Elements((*s).try_into().unwrap()))
+        .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic)),
+    );
+    c.bench(
+        "verify_transactions",
+        ParameterizedBenchmark::new(
+            "one_by_one",
+            bench_verify_txs_one_by_one,
+            (2..10).map(|i| 1 << i),
+        )
+        .with_function("batch", bench_verify_txs_batch)
+        .throughput(|s| Throughput::Elements((*s).try_into().unwrap()))
+        .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic)),
+    );
 }
@@ -15,7 +15,8 @@
 use exonum_crypto::{Hash, PublicKey};
 use exonum_derive::{BinaryValue, ObjectHash};
 use exonum_merkledb::{
-    proof_map::MapProofError, BinaryValue, MapProof, ObjectHash, ValidationError,
+    proof_list::ListProofError, proof_map::MapProofError, BinaryValue, HashTag, ListProof,
+    MapProof, ObjectHash, ValidationError,
 };
 use exonum_proto::ProtobufConvert;
 use thiserror::Error;
@@ -23,11 +24,11 @@ use thiserror::Error;
 use std::borrow::Cow;
 
 use crate::{
-    blockchain::CallInBlock,
+    blockchain::{CallEvents, CallInBlock, ServiceEvent, TxLocation},
     helpers::{byzantine_quorum, Height, OrderedMap, ValidatorId},
     messages::{Precommit, Verified},
     proto::schema,
-    runtime::{ExecutionError, ExecutionErrorAux},
+    runtime::{ExecutionError, ExecutionErrorAux, ExecutionStatus},
 };
 
 /// Trait that represents a key in block header entry map. Provides
@@ -63,6 +64,19 @@ impl BlockHeaderKey for Epoch {
     type Value = Height;
 }
 
+/// Root hash of the Merkle Patricia tree of events emitted by services within the block.
+/// This header is present only in normal blocks in which at least one event was emitted;
+/// if the header is absent, the block has no events. [Block skips] never have this header.
+///
+/// [Block skips]: enum.BlockContents.html#variant.Skip
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EventsHash(());
+
+impl BlockHeaderKey for EventsHash {
+    const NAME: &'static str = "events_hash";
+    type Value = Hash;
+}
+
 /// Flag indicating a [block skip]. This flag is not set for normal blocks.
 ///
 /// [block skip]: enum.BlockContents.html#variant.Skip
@@ -158,6 +172,17 @@ impl Block {
         self.get_header::<Epoch>().unwrap_or(None)
     }
 
+    /// Adds the root hash of service events to this block.
+    pub(super) fn add_events_hash(&mut self, events_hash: Hash) {
+        self.add_header::<EventsHash>(events_hash);
+    }
+
+    /// Retrieves the root hash of events emitted by services within this block, or `None`
+    /// if the hash is not recorded (i.e., if the block has no events).
+    pub fn events_hash(&self) -> Option<Hash> {
+        self.get_header::<EventsHash>().unwrap_or(None)
+    }
+
     /// Adds the `skip` flag to this block.
     pub(super) fn set_skip(&mut self) {
         self.add_header::<SkipFlag>(());
@@ -300,7 +325,8 @@ impl BlockProof {
     }
 }
 
-/// Errors that can occur during verification of `BlockProof`s, `IndexProof`s and `CallProof`s.
+/// Errors that can occur during verification of `BlockProof`s, `IndexProof`s, `CallProof`s,
+/// `EventProof`s and `TransactionReceipt`s.
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum ProofError {
@@ -349,6 +375,15 @@ pub enum ProofError {
     /// properly functioning Exonum node.
     #[error("Call status embedded in the proof is malformed")]
     MalformedStatus,
+
+    /// Location of a transaction does not correspond to the block or the call proven
+    /// by the accompanying proofs.
+    #[error("Transaction location does not correspond to the proven block or call")]
+    IncorrectLocation,
+
+    /// Proof of transaction inclusion into a block is incorrect.
+    #[error("Proof of transaction inclusion is incorrect: {}", _0)]
+    IncorrectLocationProof(#[source] ValidationError<ListProofError>),
 }
 
 /// Proof of authenticity for a single index within the database.
@@ -523,6 +558,152 @@ impl CallProof {
     }
 }
 
+/// Proof of authenticity for events emitted during a single top-level call in a block.
+///
+/// The proof consists of two parts:
+///
+/// - `block_proof`: block header with the proof of authenticity
+/// - `events_proof`: proof from the events aggregator (i.e., a `ProofMapIndex` the Merkle root
+///   of which is recorded in the block header as the [`EventsHash`] additional header).
+///
+/// If the call has emitted events, `events_proof` will be a proof of existence; otherwise,
+/// it will be a proof of absence. Similar to [`CallProof`]s, discerning a call without events
+/// from a non-existing call requires additional knowledge.
+///
+/// [`EventsHash`]: struct.EventsHash.html
+/// [`CallProof`]: struct.CallProof.html
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue)]
+#[protobuf_convert(source = "schema::proofs::EventProof")]
+#[non_exhaustive]
+pub struct EventProof {
+    /// Proof of authenticity for the block header.
+    #[serde(flatten)]
+    pub block_proof: BlockProof,
+
+    /// Proof of authenticity for the events. Must contain a single key corresponding
+    /// to the `CallInBlock` in question. The root hash of the proof must be equal
+    /// to the `events_hash` additional header of the block in `block_proof`, or to the hash
+    /// of an empty map if the block has no such header.
+    pub events_proof: MapProof<CallInBlock, CallEvents>,
+}
+
+impl EventProof {
+    pub(super) fn new(
+        block_proof: BlockProof,
+        events_proof: MapProof<CallInBlock, CallEvents>,
+    ) -> Self {
+        Self {
+            block_proof,
+            events_proof,
+        }
+    }
+
+    /// Verifies this proof, returning the location of the proven call together with
+    /// the events emitted during it.
+    pub fn verify(
+        &self,
+        validator_keys: &[PublicKey],
+    ) -> Result<(CallInBlock, Vec<ServiceEvent>), ProofError> {
+        self.block_proof.verify(validator_keys)?;
+        let events_hash = self
+            .block_proof
+            .block
+            .events_hash()
+            .unwrap_or_else(HashTag::empty_map_hash);
+
+        // The proof should feature exactly one present or absent entry.
+        let mut unchecked_entries = self.events_proof.all_entries_unchecked();
+        let (call, maybe_events) = unchecked_entries.next().ok_or(ProofError::NoEntry)?;
+        if unchecked_entries.next().is_some() {
+            return Err(ProofError::AmbiguousEntry);
+        }
+        let events = maybe_events.map_or_else(Vec::new, |events| events.events.clone());
+
+        self.events_proof
+            .check_against_hash(events_hash)
+            .map_err(ProofError::IncorrectEntryProof)?;
+        Ok((call.to_owned(), events))
+    }
+}
+
+/// Receipt proving that a transaction was committed to the blockchain with a certain
+/// execution status.
+///
+/// The receipt consists of three parts:
+///
+/// - `location`: location of the transaction in the blockchain
+/// - `location_proof`: proof of inclusion of the transaction hash into the block, i.e.,
+///   a proof from the `block_transactions` index, the Merkle root of which is recorded
+///   in the block header as `tx_hash`
+/// - `call_proof`: proof of the execution status of the transaction, which includes
+///   the block header with its proof of authenticity
+///
+/// Unlike a [`CallProof`], a receipt allows to distinguish successful execution
+/// of a transaction from the absence of the transaction, and ties the status
+/// to the transaction hash. Receipts can be serialized in the Protobuf format
+/// (via the `BinaryValue` trait) for storage in external systems.
+///
+/// [`CallProof`]: struct.CallProof.html
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue)]
+#[protobuf_convert(source = "schema::proofs::TransactionReceipt")]
+#[non_exhaustive]
+pub struct TransactionReceipt {
+    /// Location of the transaction in the blockchain.
+    pub location: TxLocation,
+
+    /// Proof of inclusion of the transaction hash into the block. The root hash of the proof
+    /// must be equal to the `tx_hash` mentioned in the block header.
+    pub location_proof: ListProof<Hash>,
+
+    /// Proof of authenticity for the transaction execution status together with the block header.
+    pub call_proof: CallProof,
+}
+
+impl TransactionReceipt {
+    pub(super) fn new(
+        location: TxLocation,
+        location_proof: ListProof<Hash>,
+        call_proof: CallProof,
+    ) -> Self {
+        Self {
+            location,
+            location_proof,
+            call_proof,
+        }
+    }
+
+    /// Verifies this receipt, returning the hash of the committed transaction
+    /// together with its execution status.
+    pub fn verify(
+        &self,
+        validator_keys: &[PublicKey],
+    ) -> Result<(Hash, ExecutionStatus), ProofError> {
+        let (call, status) = self.call_proof.verify(validator_keys)?;
+        let block = &self.call_proof.block_proof.block;
+        let position = self.location.position_in_block();
+        if block.height != self.location.block_height()
+            || call != CallInBlock::transaction(position)
+        {
+            return Err(ProofError::IncorrectLocation);
+        }
+
+        let checked_proof = self
+            .location_proof
+            .check_against_hash(block.tx_hash)
+            .map_err(ProofError::IncorrectLocationProof)?;
+        // The location proof should feature exactly one entry at the transaction position.
+        let tx_hash = match checked_proof.entries() {
+            [] => return Err(ProofError::NoEntry),
+            [(index, tx_hash)] if *index == u64::from(position) => *tx_hash,
+            [_] => return Err(ProofError::IncorrectLocation),
+            _ => return Err(ProofError::AmbiguousEntry),
+        };
+        Ok((tx_hash, ExecutionStatus(status)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -676,6 +857,15 @@ mod tests {
     }
 
     fn create_block_proof(keys: &[KeyPair], state_hash: Hash, error_hash: Hash) -> BlockProof {
+        create_block_proof_with_headers(keys, state_hash, error_hash, AdditionalHeaders::default())
+    }
+
+    fn create_block_proof_with_headers(
+        keys: &[KeyPair],
+        state_hash: Hash,
+        error_hash: Hash,
+        additional_headers: AdditionalHeaders,
+    ) -> BlockProof {
         let mut block = Block {
             height: Height(1),
             tx_count: 0,
@@ -683,13 +873,16 @@ mod tests {
             tx_hash: Hash::zero(),
             state_hash,
             error_hash,
-            additional_headers: AdditionalHeaders::default(),
+            additional_headers,
         };
         block
             .additional_headers
             .insert::<ProposerId>(ValidatorId(1));
         block.additional_headers.insert::<Epoch>(Height(1));
+        sign_block(keys, block)
+    }
 
+    fn sign_block(keys: &[KeyPair], block: Block) -> BlockProof {
         let precommits = keys.iter().enumerate().map(|(i, keypair)| {
             let precommit = Precommit::new(
                 ValidatorId(i as u16),
@@ -1029,4 +1222,164 @@ mod tests {
         let err = call_proof.verify(&public_keys).unwrap_err();
         assert_matches!(err, ProofError::AmbiguousEntry);
     }
+
+    fn create_events_proof(call: CallInBlock) -> (Hash, MapProof<CallInBlock, CallEvents>) {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut schema = CoreSchema::new(&fork);
+
+        let call_with_events = CallInBlock::transaction(1);
+        schema.save_events(
+            Height(1),
+            call_with_events,
+            vec![
+                ServiceEvent::new(5, "transfer", vec![1, 2]),
+                ServiceEvent::new(5, "fee", vec![3]),
+            ],
+        );
+        schema.save_events(
+            Height(1),
+            CallInBlock::after_transactions(5),
+            vec![ServiceEvent::new(5, "rewards", vec![])],
+        );
+
+        let events_map = schema.call_events_map(Height(1));
+        (events_map.object_hash(), events_map.get_proof(call))
+    }
+
+    fn create_events_block_proof(keys: &[KeyPair], events_hash: Hash) -> BlockProof {
+        let mut headers = AdditionalHeaders::new();
+        headers.insert::<EventsHash>(events_hash);
+        create_block_proof_with_headers(keys, Hash::zero(), Hash::zero(), headers)
+    }
+
+    #[test]
+    fn event_proof() {
+        let (events_hash, events_proof) = create_events_proof(CallInBlock::transaction(1));
+        let keys: Vec<_> = (0..4).map(|_| KeyPair::random()).collect();
+        let public_keys: Vec<_> = keys.iter().map(KeyPair::public_key).collect();
+        let block_proof = create_events_block_proof(&keys, events_hash);
+        let mut event_proof = EventProof::new(block_proof, events_proof);
+        let (call, events) = event_proof.verify(&public_keys).unwrap();
+        assert_eq!(call, CallInBlock::transaction(1));
+        assert_eq!(
+            events,
+            vec![
+                ServiceEvent::new(5, "transfer", vec![1, 2]),
+                ServiceEvent::new(5, "fee", vec![3]),
+            ]
+        );
+
+        // Check that the proof is invalidated if events are mangled.
+        event_proof.events_proof = event_proof.events_proof.map_values(|mut events| {
+            events.events.pop();
+            events
+        });
+        let err = event_proof.verify(&public_keys).unwrap_err();
+        assert_matches!(
+            err,
+            ProofError::IncorrectEntryProof(ValidationError::UnmatchedRootHash)
+        );
+    }
+
+    #[test]
+    fn event_proof_for_call_without_events() {
+        let (events_hash, events_proof) = create_events_proof(CallInBlock::transaction(0));
+        let keys: Vec<_> = (0..3).map(|_| KeyPair::random()).collect();
+        let public_keys: Vec<_> = keys.iter().map(KeyPair::public_key).collect();
+        let block_proof = create_events_block_proof(&keys, events_hash);
+        let event_proof = EventProof::new(block_proof, events_proof);
+        let (call, events) = event_proof.verify(&public_keys).unwrap();
+        assert_eq!(call, CallInBlock::transaction(0));
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn event_proof_without_events_hash() {
+        let keys: Vec<_> = (0..3).map(|_| KeyPair::random()).collect();
+        let public_keys: Vec<_> = keys.iter().map(KeyPair::public_key).collect();
+
+        // A block without the header has no events.
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let events_proof = CoreSchema::new(&fork)
+            .call_events_map(Height(1))
+            .get_proof(CallInBlock::transaction(0));
+        let block_proof = create_block_proof(&keys, Hash::zero(), Hash::zero());
+        let event_proof = EventProof::new(block_proof, events_proof);
+        let (call, events) = event_proof.verify(&public_keys).unwrap();
+        assert_eq!(call, CallInBlock::transaction(0));
+        assert!(events.is_empty());
+
+        // Events cannot be proven for such a block.
+        let (_, events_proof) = create_events_proof(CallInBlock::transaction(1));
+        let block_proof = create_block_proof(&keys, Hash::zero(), Hash::zero());
+        let event_proof = EventProof::new(block_proof, events_proof);
+        let err = event_proof.verify(&public_keys).unwrap_err();
+        assert_matches!(
+            err,
+            ProofError::IncorrectEntryProof(ValidationError::UnmatchedRootHash)
+        );
+    }
+
+    fn create_receipt(keys: &[KeyPair], position: u32, location: TxLocation) -> TransactionReceipt {
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut tx_hashes = fork.get_proof_list("transactions");
+        tx_hashes.extend(vec![hash(&[0]), hash(&[1]), hash(&[2])]);
+        let location_proof = tx_hashes.get_proof(u64::from(position));
+
+        let (error_hash, call_proof) = create_error_proof(CallProofKind::Error);
+        let mut block_proof = create_block_proof(keys, Hash::zero(), error_hash);
+        block_proof.block.tx_count = 3;
+        block_proof.block.tx_hash = tx_hashes.object_hash();
+        let block_proof = sign_block(keys, block_proof.block);
+        let call_proof = CallProof::new(block_proof, call_proof, Some("huh?".to_owned()));
+        TransactionReceipt::new(location, location_proof, call_proof)
+    }
+
+    #[test]
+    fn transaction_receipt() {
+        let keys: Vec<_> = (0..4).map(|_| KeyPair::random()).collect();
+        let public_keys: Vec<_> = keys.iter().map(KeyPair::public_key).collect();
+        let receipt = create_receipt(&keys, 2, TxLocation::new(Height(1), 2));
+        let (tx_hash, status) = receipt.verify(&public_keys).unwrap();
+        assert_eq!(tx_hash, hash(&[2]));
+        assert_eq!(status.0, Err(ExecutionError::service(5, "huh?")));
+
+        // Check that the receipt survives Protobuf serialization.
+        let bytes = receipt.to_bytes();
+        let restored_receipt = TransactionReceipt::from_bytes(bytes.into()).unwrap();
+        let (tx_hash, status) = restored_receipt.verify(&public_keys).unwrap();
+        assert_eq!(tx_hash, hash(&[2]));
+        assert_eq!(status.0, Err(ExecutionError::service(5, "huh?")));
+    }
+
+    #[test]
+    fn transaction_receipt_with_incorrect_location() {
+        let keys: Vec<_> = (0..4).map(|_| KeyPair::random()).collect();
+        let public_keys: Vec<_> = keys.iter().map(KeyPair::public_key).collect();
+
+        // Location mentions another block.
+        let receipt = create_receipt(&keys, 2, TxLocation::new(Height(2), 2));
+        let err = receipt.verify(&public_keys).unwrap_err();
+        assert_matches!(err, ProofError::IncorrectLocation);
+        // Location does not correspond to the proven call.
+        let receipt = create_receipt(&keys, 1, TxLocation::new(Height(1), 1));
+        let err = receipt.verify(&public_keys).unwrap_err();
+        assert_matches!(err, ProofError::IncorrectLocation);
+        // Location proof is given for another transaction.
+        let mut receipt = create_receipt(&keys, 1, TxLocation::new(Height(1), 2));
+        let err = receipt.verify(&public_keys).unwrap_err();
+        assert_matches!(err, ProofError::IncorrectLocation);
+
+        // Location proof does not match the block.
+        let db = TemporaryDB::new();
+        let fork = db.fork();
+        let mut other_tx_hashes = fork.get_proof_list("transactions");
+        other_tx_hashes.extend(vec![hash(&[3]), hash(&[4]), hash(&[5])]);
+        receipt.location_proof = other_tx_hashes.get_proof(2);
+        let err = receipt.verify(&public_keys).unwrap_err();
+        assert_matches!(err, ProofError::IncorrectLocationProof(_));
+    }
 }
@@ -14,8 +14,10 @@
 
 //! The module responsible for the correct Exonum blockchain creation.
 
+use std::sync::{Arc, Mutex};
+
 use crate::{
-    blockchain::{config::GenesisConfig, Blockchain, BlockchainMut, Schema},
+    blockchain::{config::GenesisConfig, Blockchain, BlockchainMut, GenesisState, Schema},
     runtime::{Dispatcher, RuntimeInstance},
 };
 
@@ -31,6 +33,8 @@ pub struct BlockchainBuilder {
     runtimes: Vec<RuntimeInstance>,
     /// Blockchain configuration used to create the genesis block.
     genesis_config: Option<GenesisConfig>,
+    /// State of services restored in the genesis block.
+    genesis_state: Option<GenesisState>,
 }
 
 impl BlockchainBuilder {
@@ -40,6 +44,7 @@ impl BlockchainBuilder {
             blockchain,
             runtimes: vec![],
             genesis_config: None,
+            genesis_state: None,
         }
     }
 
@@ -49,6 +54,17 @@ impl BlockchainBuilder {
         self
     }
 
+    /// Adds the state of services to restore in the genesis block if the blockchain
+    /// is not yet initialized. The state is usually obtained together with the genesis config
+    /// via [`export_genesis()`]; the services from the genesis config are not initialized
+    /// in this case, and their data is taken from the state instead.
+    ///
+    /// [`export_genesis()`]: fn.export_genesis.html
+    pub fn with_genesis_state(mut self, genesis_state: GenesisState) -> Self {
+        self.genesis_state = Some(genesis_state);
+        self
+    }
+
     /// Adds a runtime with the specified identifier and returns a modified `Self` object for
     /// further chaining.
     pub fn with_runtime(mut self, runtime: impl Into<RuntimeInstance>) -> Self {
@@ -69,9 +85,17 @@ impl BlockchainBuilder {
         const NO_GENESIS_CFG: &str =
             "No genesis config was provided for an uninitialized blockchain";
 
+        // The genesis config is retained to re-execute blocks when tracing transactions.
+        // Blockchains created from an exported state cannot be re-executed.
+        let retained_config = self
+            .genesis_config
+            .as_ref()
+            .filter(|_| self.genesis_state.is_none())
+            .map(|config| Arc::new(config.clone()));
         let mut blockchain = BlockchainMut {
-            dispatcher: Dispatcher::new(&self.blockchain, self.runtimes),
+            dispatcher: Arc::new(Mutex::new(Dispatcher::new(&self.blockchain, self.runtimes))),
             inner: self.blockchain,
+            genesis_config: retained_config,
         };
 
         // If genesis block had been already created just restores dispatcher state from database
@@ -79,10 +103,10 @@ impl BlockchainBuilder {
         let snapshot = blockchain.snapshot();
         let has_genesis_block = !Schema::new(&snapshot).block_hashes_by_height().is_empty();
         if has_genesis_block {
-            blockchain.dispatcher.restore_state(&snapshot);
+            blockchain.dispatcher().restore_state(&snapshot);
         } else {
             let genesis_config = self.genesis_config.expect(NO_GENESIS_CFG);
-            blockchain.create_genesis_block(genesis_config);
+            blockchain.create_genesis_block(genesis_config, self.genesis_state);
         };
         blockchain
     }
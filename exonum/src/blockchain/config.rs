@@ -131,6 +131,62 @@ pub struct ConsensusConfig {
     /// in a block if the transaction pool is almost empty, and create blocks faster when there are
     /// enough transactions in the pool.
     pub propose_timeout_threshold: u32,
+    /// Maximum number of entries read from the storage by a single transaction, including
+    /// its nested calls. `None` means no limit.
+    ///
+    /// Transactions exceeding this or other resource limits are aborted and fail with
+    /// [`CoreError::ResourceLimitExceeded`]. The same limits apply to each call of
+    /// `before_transactions` / `after_transactions` service hooks and to each scheduled call.
+    ///
+    /// [`CoreError::ResourceLimitExceeded`]: ../runtime/enum.CoreError.html#variant.ResourceLimitExceeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[protobuf_convert(with = "self::pb_resource_limit")]
+    pub max_call_reads: Option<u64>,
+    /// Maximum number of entries written to the storage or removed from it by a single
+    /// transaction, including its nested calls. `None` means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[protobuf_convert(with = "self::pb_resource_limit")]
+    pub max_call_writes: Option<u64>,
+    /// Maximum total length of keys and values written to the storage by a single transaction,
+    /// including its nested calls. `None` means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[protobuf_convert(with = "self::pb_resource_limit")]
+    pub max_call_bytes_written: Option<u64>,
+    /// Maximum number of nested calls made by a single transaction. `None` means no limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[protobuf_convert(with = "self::pb_resource_limit")]
+    pub max_nested_calls: Option<u64>,
+    /// Maximum total size of transactions in a block (in bytes). `None` means no limit.
+    ///
+    /// The size of a transaction is the length of its serialized signed message.
+    /// The limit is enforced by validators, which do not vote for proposals exceeding it.
+    /// If set, the limit cannot be less than `max_message_len`, so that any transaction
+    /// can fit into a block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[protobuf_convert(with = "self::pb_resource_limit")]
+    pub max_block_bytes: Option<u64>,
+    /// Maximum amount of fuel consumed by a single transaction, including its nested calls.
+    /// `None` means no limit.
+    ///
+    /// Fuel is charged by runtimes executing code in an interpreter (e.g., the Wasm runtime
+    /// charges fuel for executed instructions) via [`ExecutionContext::charge_fuel()`].
+    ///
+    /// [`ExecutionContext::charge_fuel()`]: ../runtime/struct.ExecutionContext.html#method.charge_fuel
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[protobuf_convert(with = "self::pb_resource_limit")]
+    pub max_call_fuel: Option<u64>,
+}
+
+/// Serialization of optional resource limits, in which a zero value denotes an absent limit.
+mod pb_resource_limit {
+    #[allow(clippy::needless_pass_by_value)] // required for work with `protobuf_convert(with)`
+    pub fn from_pb(pb: u64) -> anyhow::Result<Option<u64>> {
+        Ok(if pb == 0 { None } else { Some(pb) })
+    }
+
+    pub fn to_pb(value: &Option<u64>) -> u64 {
+        value.unwrap_or(0)
+    }
 }
 
 impl Default for ConsensusConfig {
@@ -145,6 +201,12 @@ impl Default for ConsensusConfig {
             min_propose_timeout: 10,
             max_propose_timeout: 200,
             propose_timeout_threshold: 500,
+            max_call_reads: None,
+            max_call_writes: None,
+            max_call_bytes_written: None,
+            max_nested_calls: None,
+            max_block_bytes: None,
+            max_call_fuel: None,
         }
     }
 }
@@ -411,6 +473,66 @@ impl ConsensusConfigBuilder {
 
         Self { config }
     }
+
+    /// Sets the `max_call_reads` field of `ConsensusConfig`.
+    pub fn max_call_reads(self, max_call_reads: impl Into<Option<u64>>) -> Self {
+        let config = ConsensusConfig {
+            max_call_reads: max_call_reads.into(),
+            ..self.config
+        };
+
+        Self { config }
+    }
+
+    /// Sets the `max_call_writes` field of `ConsensusConfig`.
+    pub fn max_call_writes(self, max_call_writes: impl Into<Option<u64>>) -> Self {
+        let config = ConsensusConfig {
+            max_call_writes: max_call_writes.into(),
+            ..self.config
+        };
+
+        Self { config }
+    }
+
+    /// Sets the `max_call_bytes_written` field of `ConsensusConfig`.
+    pub fn max_call_bytes_written(self, max_call_bytes_written: impl Into<Option<u64>>) -> Self {
+        let config = ConsensusConfig {
+            max_call_bytes_written: max_call_bytes_written.into(),
+            ..self.config
+        };
+
+        Self { config }
+    }
+
+    /// Sets the `max_nested_calls` field of `ConsensusConfig`.
+    pub fn max_nested_calls(self, max_nested_calls: impl Into<Option<u64>>) -> Self {
+        let config = ConsensusConfig {
+            max_nested_calls: max_nested_calls.into(),
+            ..self.config
+        };
+
+        Self { config }
+    }
+
+    /// Sets the `max_call_fuel` field of `ConsensusConfig`.
+    pub fn max_call_fuel(self, max_call_fuel: impl Into<Option<u64>>) -> Self {
+        let config = ConsensusConfig {
+            max_call_fuel: max_call_fuel.into(),
+            ..self.config
+        };
+
+        Self { config }
+    }
+
+    /// Sets the `max_block_bytes` field of `ConsensusConfig`.
+    pub fn max_block_bytes(self, max_block_bytes: impl Into<Option<u64>>) -> Self {
+        let config = ConsensusConfig {
+            max_block_bytes: max_block_bytes.into(),
+            ..self.config
+        };
+
+        Self { config }
+    }
 }
 
 impl ValidateInput for ConsensusConfig {
@@ -454,6 +576,31 @@ impl ValidateInput for ConsensusConfig {
             );
         }
 
+        // Check resource limits. Zero limits cannot be represented in the serialized form.
+        let resource_limits = [
+            ("max_call_reads", self.max_call_reads),
+            ("max_call_writes", self.max_call_writes),
+            ("max_call_bytes_written", self.max_call_bytes_written),
+            ("max_nested_calls", self.max_nested_calls),
+            ("max_call_fuel", self.max_call_fuel),
+        ];
+        for &(name, limit) in &resource_limits {
+            if limit == Some(0) {
+                bail!("{} should not be equal to zero", name);
+            }
+        }
+
+        // Check that a transaction of the maximum size fits into a block.
+        if let Some(max_block_bytes) = self.max_block_bytes {
+            if max_block_bytes < u64::from(self.max_message_len) {
+                bail!(
+                    "max_block_bytes ({}) must be at least max_message_len ({})",
+                    max_block_bytes,
+                    self.max_message_len
+                );
+            }
+        }
+
         // Print warning if configuration is not optimal
         self.warn_if_nonoptimal();
 
@@ -532,7 +679,7 @@ pub struct GenesisConfigBuilder {
     /// Consensus config.
     consensus_config: ConsensusConfig,
     /// Artifacts specifications for builtin services.
-    artifacts: HashMap<ArtifactId, Vec<u8>>,
+    artifacts: HashMap<ArtifactId, ArtifactSpec>,
     /// Instances of builtin services.
     builtin_instances: Vec<InstanceInitParams>,
 }
@@ -556,14 +703,17 @@ impl GenesisConfigBuilder {
     /// Adds an artifact with corresponding deploy argument. Does nothing in case artifact with
     /// given id is already added.
     pub fn with_parametric_artifact(
-        mut self,
+        self,
         artifact: impl Into<ArtifactId>,
         payload: impl BinaryValue,
     ) -> Self {
-        let artifact = artifact.into();
-        self.artifacts
-            .entry(artifact)
-            .or_insert_with(|| payload.into_bytes());
+        self.with_artifact_spec(ArtifactSpec::new(artifact.into(), payload))
+    }
+
+    /// Adds an artifact with the specification, which may declare dependencies
+    /// of the artifact. Does nothing in case artifact with given id is already added.
+    pub fn with_artifact_spec(mut self, spec: ArtifactSpec) -> Self {
+        self.artifacts.entry(spec.artifact.clone()).or_insert(spec);
         self
     }
 
@@ -578,7 +728,7 @@ impl GenesisConfigBuilder {
         let artifacts = self
             .artifacts
             .into_iter()
-            .map(|(artifact, payload)| ArtifactSpec::new(artifact, payload))
+            .map(|(_, spec)| spec)
             .collect::<Vec<_>>();
         GenesisConfig {
             consensus_config: self.consensus_config,
@@ -713,6 +863,20 @@ mod tests {
                 },
                 "max_message_len (0) must be at least",
             ),
+            (
+                ConsensusConfig {
+                    max_call_writes: Some(0),
+                    ..gen_consensus_config()
+                },
+                "max_call_writes should not be equal to zero",
+            ),
+            (
+                ConsensusConfig {
+                    max_block_bytes: Some(1_000),
+                    ..gen_consensus_config()
+                },
+                "max_block_bytes (1000) must be at least max_message_len (1048576)",
+            ),
         ];
 
         for (cfg, expected_msg) in &cases {
@@ -17,12 +17,22 @@
 pub use self::{
     api_sender::{ApiSender, SendError},
     block::{
-        AdditionalHeaders, Block, BlockHeaderKey, BlockProof, CallProof, Epoch, IndexProof,
-        ProofError, ProposerId, SkipFlag,
+        AdditionalHeaders, Block, BlockHeaderKey, BlockProof, CallProof, Epoch, EventProof,
+        EventsHash, IndexProof, ProofError, ProposerId, SkipFlag, TransactionReceipt,
     },
     builder::BlockchainBuilder,
     config::{ConsensusConfig, ConsensusConfigBuilder, ValidatorKeys},
-    schema::{CallErrorsIter, CallInBlock, CallRecords, Schema, TxLocation},
+    query::{Query, QueryError, QueryRequest},
+    regenesis::{export_genesis, ExportError, GenesisState},
+    replay::{replay_blocks, trace_transaction, BlockMismatch, ReplayError, TraceError},
+    schema::{
+        CallErrorsIter, CallEvents, CallInBlock, CallRecords, ScheduledCall, Schema, ServiceEvent,
+        TxLocation,
+    },
+    simulation::{
+        IndexHashChange, Simulation, SimulationError, SimulationRequest, SimulationResult,
+        Simulator,
+    },
 };
 pub use crate::runtime::TxCheckCache;
 
@@ -36,8 +46,14 @@ use exonum_merkledb::{
     Database, Fork, HashTag, KeySetIndex, MapIndex, ObjectHash, Patch, Result as StorageResult,
     Snapshot, SystemSchema, TemporaryDB,
 };
+use futures::Future;
 
-use std::{borrow::Cow, collections::BTreeMap, iter, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    iter,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
 
 use crate::{
     blockchain::config::GenesisConfig,
@@ -49,7 +65,11 @@ use crate::{
 mod api_sender;
 mod block;
 mod builder;
+mod query;
+mod regenesis;
+mod replay;
 mod schema;
+mod simulation;
 #[cfg(test)]
 pub mod tests;
 
@@ -177,6 +197,8 @@ where
 #[derive(Debug, Clone)]
 pub struct Blockchain {
     api_sender: ApiSender,
+    simulation_sender: ApiSender<SimulationRequest>,
+    query_sender: ApiSender<QueryRequest>,
     db: Arc<dyn Database>,
     service_keypair: KeyPair,
 }
@@ -192,9 +214,31 @@ impl Blockchain {
             db: database.into(),
             service_keypair: service_keypair.into(),
             api_sender,
+            simulation_sender: ApiSender::closed(),
+            query_sender: ApiSender::closed(),
         }
     }
 
+    /// Sets the sender of simulation requests. The requests should be processed by the node
+    /// via [`SimulationRequest::process()`]. If the sender is not set, asynchronous
+    /// [simulations](#method.simulate) fail.
+    ///
+    /// [`SimulationRequest::process()`]: struct.SimulationRequest.html#method.process
+    pub fn with_simulation_sender(mut self, sender: ApiSender<SimulationRequest>) -> Self {
+        self.simulation_sender = sender;
+        self
+    }
+
+    /// Sets the sender of query requests. The requests should be processed by the node
+    /// via [`QueryRequest::process()`]. If the sender is not set, asynchronous
+    /// [queries](#method.query) fail.
+    ///
+    /// [`QueryRequest::process()`]: struct.QueryRequest.html#method.process
+    pub fn with_query_sender(mut self, sender: ApiSender<QueryRequest>) -> Self {
+        self.query_sender = sender;
+        self
+    }
+
     /// Creates a non-persisting blockchain, all data in which is irrevocably lost on drop.
     ///
     /// The created blockchain cannot send transactions; an attempt to do so will result
@@ -262,14 +306,88 @@ impl Blockchain {
         &self.service_keypair
     }
 
+    /// Simulates execution of a transaction against the latest blockchain state without
+    /// committing its results.
+    ///
+    /// The simulation is performed by the node the blockchain is attached to, since only
+    /// the node has access to the services. See [`BlockchainMut::simulate()`] for details.
+    ///
+    /// # Return value
+    ///
+    /// The failure means that the blockchain is not attached to a node, or that the node
+    /// is being shut down.
+    ///
+    /// [`BlockchainMut::simulate()`]: struct.BlockchainMut.html#method.simulate
+    pub fn simulate(
+        &self,
+        simulation: Simulation,
+    ) -> impl Future<Output = Result<SimulationResult, SimulationError>> {
+        let (request, response) = SimulationRequest::new(simulation);
+        let mut sender = self.simulation_sender.clone();
+        async move {
+            sender
+                .send_message(request)
+                .await
+                .map_err(|_| SimulationError::new())?;
+            response.await.map_err(|_| SimulationError::new())
+        }
+    }
+
+    /// Traces execution of a committed transaction with the specified hash.
+    ///
+    /// The tracing is performed by the node the blockchain is attached to, since only
+    /// the node has access to the services. See [`Simulator::trace()`] for details.
+    ///
+    /// [`Simulator::trace()`]: struct.Simulator.html#method.trace
+    pub fn trace(
+        &self,
+        tx_hash: Hash,
+    ) -> impl Future<Output = Result<SimulationResult, TraceError>> {
+        let (request, response) = SimulationRequest::trace(tx_hash);
+        let mut sender = self.simulation_sender.clone();
+        async move {
+            sender
+                .send_message(request)
+                .await
+                .map_err(|_| TraceError::NotAttached)?;
+            response.await.map_err(|_| TraceError::NotAttached)?
+        }
+    }
+
+    /// Performs a read-only query to a service against the latest blockchain state.
+    ///
+    /// The query is performed by the node the blockchain is attached to, since only
+    /// the node has access to the services. See [`BlockchainMut::query()`] for details.
+    ///
+    /// [`BlockchainMut::query()`]: struct.BlockchainMut.html#method.query
+    pub fn query(&self, query: Query) -> impl Future<Output = Result<Vec<u8>, QueryError>> {
+        let (request, response) = QueryRequest::new(query);
+        let mut sender = self.query_sender.clone();
+        async move {
+            sender
+                .send_message(request)
+                .await
+                .map_err(|_| QueryError::NotAttached)?;
+            let result = response.await.map_err(|_| QueryError::NotAttached)?;
+            result.map_err(QueryError::Execution)
+        }
+    }
+
     /// Performs several shallow checks that transaction is correct.
     ///
     /// Returned `Ok(())` value doesn't necessarily mean that transaction is correct and will be
     /// executed successfully, but returned `Err(..)` value means that this transaction is
     /// **obviously** incorrect and should be declined as early as possible.
     ///
+    /// A transaction with a [validity window] ending before the next blockchain height
+    /// is rejected. A transaction which can only be committed at a later height passes
+    /// the check; such a transaction should be kept by the caller (e.g., in the transaction pool),
+    /// but not included into blocks until its window starts.
+    ///
     /// See [`check_tx_with_cache`](#method.check_tx_with_cache) for a more efficient alternative
     /// for repeated checks.
+    ///
+    /// [validity window]: ../runtime/struct.TxValidity.html
     pub fn check_tx(snapshot: &dyn Snapshot, tx: &Verified<AnyTx>) -> Result<(), ExecutionError> {
         Dispatcher::check_tx(snapshot, tx, None)
     }
@@ -441,11 +559,16 @@ impl AsRef<Patch> for BlockPatch {
 /// combination cannot be cloned (unlike `Blockchain`), but can be sent across threads. It is
 /// possible to extract a `Blockchain` reference from `BlockchainMut` via `AsRef` trait.
 ///
+/// The dispatcher is shared with [`Simulator`]s created by the blockchain, so that
+/// transactions can be simulated outside of the thread processing blocks.
+///
 /// [`Blockchain`]: struct.Blockchain.html
+/// [`Simulator`]: struct.Simulator.html
 #[derive(Debug)]
 pub struct BlockchainMut {
     inner: Blockchain,
-    dispatcher: Dispatcher,
+    dispatcher: Arc<Mutex<Dispatcher>>,
+    genesis_config: Option<Arc<GenesisConfig>>,
 }
 
 impl AsRef<Blockchain> for BlockchainMut {
@@ -460,10 +583,33 @@ impl BlockchainMut {
         self.inner.clone()
     }
 
+    /// Returns a simulator sharing services with this blockchain. Unlike `BlockchainMut`,
+    /// the simulator can be cloned and used from other threads.
+    pub fn simulator(&self) -> Simulator {
+        Simulator::new(
+            Arc::clone(&self.inner.db),
+            Arc::clone(&self.dispatcher),
+            self.genesis_config.clone(),
+        )
+    }
+
+    /// Locks the dispatcher. The lock is held by simulations during their execution.
+    ///
+    /// The lock is recovered if it is poisoned by a panic in a simulation or a query,
+    /// which access the dispatcher immutably.
+    pub(crate) fn dispatcher(&self) -> MutexGuard<'_, Dispatcher> {
+        self.dispatcher
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Returns a mutable reference to dispatcher.
     #[cfg(test)]
-    pub(crate) fn dispatcher(&mut self) -> &mut Dispatcher {
-        &mut self.dispatcher
+    pub(crate) fn dispatcher_mut(&mut self) -> &mut Dispatcher {
+        Arc::get_mut(&mut self.dispatcher)
+            .expect("Dispatcher is shared with a simulator")
+            .get_mut()
+            .expect("BUG: Dispatcher lock is poisoned")
     }
 
     /// Creates a read-only snapshot of the current storage state.
@@ -482,12 +628,40 @@ impl BlockchainMut {
         self.inner.db.merge(patch)
     }
 
+    /// Simulates execution of a transaction against the latest blockchain state.
+    ///
+    /// The transaction is executed in the same way as it would be executed in a block
+    /// at the next height (including the validity window check and resource metering),
+    /// but the changes made by the transaction are discarded. See [`Simulator::simulate()`]
+    /// for details.
+    ///
+    /// [`Simulator::simulate()`]: struct.Simulator.html#method.simulate
+    pub fn simulate(&self, simulation: &Simulation) -> SimulationResult {
+        self.simulator().simulate(simulation)
+    }
+
+    /// Performs a read-only query to a service against the latest blockchain state.
+    ///
+    /// The query is dispatched to the runtime of the service via [`Runtime::query()`].
+    /// The query fails if the service does not exist or does not provide read access,
+    /// or if its runtime does not support [`RuntimeFeature::Queries`].
+    ///
+    /// [`Runtime::query()`]: ../runtime/trait.Runtime.html#method.query
+    /// [`RuntimeFeature::Queries`]: ../runtime/enum.RuntimeFeature.html#variant.Queries
+    pub fn query(&self, query: &Query) -> Result<Vec<u8>, ExecutionError> {
+        self.simulator().query(query)
+    }
+
     /// Creates and commits the genesis block with the given genesis configuration.
     ///
     /// # Panics
     ///
     /// Panics if the genesis block cannot be created.
-    fn create_genesis_block(&mut self, genesis_config: GenesisConfig) {
+    fn create_genesis_block(
+        &mut self,
+        genesis_config: GenesisConfig,
+        genesis_state: Option<GenesisState>,
+    ) {
         genesis_config
             .consensus_config
             .validate()
@@ -505,20 +679,36 @@ impl BlockchainMut {
         }
 
         for spec in genesis_config.artifacts {
-            self.dispatcher
-                .add_builtin_artifact(&fork, spec.artifact, spec.payload);
+            self.dispatcher().add_builtin_artifact(&fork, spec);
         }
+        Dispatcher::check_builtin_dependencies(&fork);
 
         // Add service instances.
         // Note that `before_transactions` will not be invoked for services, since
         // they are added within block (and don't appear from nowhere).
-        for inst in genesis_config.builtin_instances {
-            self.dispatcher
-                .add_builtin_service(&mut fork, inst.instance_spec, inst.constructor)
-                .expect("Unable to add a builtin service");
+        if let Some(genesis_state) = genesis_state {
+            // Service data is restored from the exported state instead of
+            // being created by the service constructors.
+            for inst in &genesis_config.builtin_instances {
+                Dispatcher::add_restored_service(&fork, inst.instance_spec.clone())
+                    .expect("Unable to add a restored builtin service");
+            }
+            genesis_state
+                .restore(&fork, &genesis_config.builtin_instances)
+                .expect("Unable to restore the genesis state");
+            log::info!(
+                "Restored service state exported at height {}",
+                genesis_state.source_height
+            );
+        } else {
+            for inst in genesis_config.builtin_instances {
+                self.dispatcher()
+                    .add_builtin_service(&mut fork, inst.instance_spec, inst.constructor)
+                    .expect("Unable to add a builtin service");
+            }
         }
         // Activate services and persist changes.
-        let patch = self.dispatcher.start_builtin_instances(fork);
+        let patch = self.dispatcher().start_builtin_instances(fork);
         self.merge(patch).unwrap();
 
         // Create a new fork to collect the changes from `after_transactions` hook.
@@ -527,7 +717,7 @@ impl BlockchainMut {
         // We need to activate services before calling `create_patch()`; unlike all other blocks,
         // initial services are considered immediately active in the genesis block, i.e.,
         // their state should be included into `patch` created below.
-        let errors = self.dispatcher.after_transactions(&mut fork);
+        let errors = self.dispatcher().after_transactions(&mut fork);
 
         // If there was at least one error during the genesis block creation, the block shouldn't be
         // created at all.
@@ -537,14 +727,14 @@ impl BlockchainMut {
             &errors
         );
 
-        let patch = self.dispatcher.commit_block(fork);
+        let patch = self.dispatcher().commit_block(fork);
         self.merge(patch).unwrap();
 
         let block_params = BlockParams::for_genesis_block();
         let BlockPatch { inner: patch, .. } = self.create_patch(block_params, &());
         // On the other hand, we need to notify runtimes *after* the block has been created.
         // Otherwise, benign operations (e.g., calling `height()` on the core schema) will panic.
-        self.dispatcher.notify_runtimes_about_commit(&patch);
+        self.dispatcher().notify_runtimes_about_commit(&patch);
         self.merge(patch).unwrap();
 
         log::info!(
@@ -623,11 +813,7 @@ impl BlockchainMut {
 
         // Skip execution for genesis block.
         if height > Height(0) {
-            let errors = self.dispatcher.before_transactions(&mut fork);
-            let mut schema = Schema::new(&fork);
-            for (location, error) in errors {
-                schema.save_error(height, location, error);
-            }
+            self.execute_block_prologue(&mut fork, height);
         }
 
         // Save & execute transactions.
@@ -637,7 +823,7 @@ impl BlockchainMut {
 
         // During processing of the genesis block, this hook is already called in another method.
         if height > Height(0) {
-            let errors = self.dispatcher.after_transactions(&mut fork);
+            let errors = self.dispatcher().after_transactions(&mut fork);
             let mut schema = Schema::new(&fork);
             for (location, error) in errors {
                 schema.save_error(height, location, error);
@@ -663,6 +849,16 @@ impl BlockchainMut {
         }
     }
 
+    /// Executes scheduled calls and `before_transactions` hooks for the block
+    /// at the specified height, recording errors.
+    fn execute_block_prologue(&self, fork: &mut Fork, height: Height) {
+        let errors = self.dispatcher().execute_block_prologue(fork);
+        let mut schema = Schema::new(&*fork);
+        for (location, error) in errors {
+            schema.save_error(height, location, error);
+        }
+    }
+
     fn create_block_header(
         &self,
         fork: Fork,
@@ -674,6 +870,7 @@ impl BlockchainMut {
 
         let mut schema = Schema::new(&fork);
         let error_hash = schema.call_errors_map(height).object_hash();
+        let events_hash = schema.call_events_map(height).object_hash();
         let tx_hash = schema.block_transactions(height).object_hash();
         schema.clear_block_skip();
 
@@ -691,6 +888,11 @@ impl BlockchainMut {
         };
         block.add_header::<ProposerId>(block_data.proposer);
         block.add_epoch(block_data.epoch);
+        // The header is omitted for blocks without events, so that such blocks remain
+        // the same as blocks produced by nodes unaware of events.
+        if events_hash != HashTag::empty_map_hash() {
+            block.add_events_hash(events_hash);
+        }
 
         (patch, block)
     }
@@ -710,7 +912,9 @@ impl BlockchainMut {
             .unwrap_or_else(|| panic!("BUG: Cannot find transaction {:?} in database", tx_hash));
         fork.flush();
 
-        let tx_result = self.dispatcher.execute(fork, tx_hash, index, &transaction);
+        let tx_result = self
+            .dispatcher()
+            .execute(fork, tx_hash, index, &transaction);
         let mut schema = Schema::new(&*fork);
 
         if let Err(e) = tx_result {
@@ -742,8 +946,12 @@ impl BlockchainMut {
                 self.merge(fork.into_patch())?;
             }
             BlockKind::Normal => {
-                let patch = self.dispatcher.commit_block_and_notify_runtimes(fork);
-                self.merge(patch)?;
+                // The dispatcher is kept locked until the patch is merged, so that
+                // simulations observe consistent service and storage states.
+                let mut dispatcher = self.dispatcher();
+                let patch = dispatcher.commit_block_and_notify_runtimes(fork);
+                self.inner.db.merge(patch)?;
+                drop(dispatcher);
 
                 // TODO: this makes `commit` non-atomic; can this be avoided? (ECR-4319)
                 let new_fork = self.fork();
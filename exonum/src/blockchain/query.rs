@@ -0,0 +1,86 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only queries to services dispatched via runtimes.
+
+use futures::channel::oneshot;
+use thiserror::Error;
+
+use crate::runtime::{ExecutionError, InstanceId, MethodId};
+
+use super::Simulator;
+
+/// Read-only query to a service instance.
+///
+/// Queries are dispatched by the node to the runtime of the target service via
+/// [`Runtime::query()`] and are executed against the latest blockchain state.
+///
+/// [`Runtime::query()`]: ../runtime/trait.Runtime.html#method.query
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Query {
+    /// Identifier of the queried service instance.
+    pub instance_id: InstanceId,
+    /// Identifier of the query method.
+    pub method_id: MethodId,
+    /// Serialized query arguments.
+    pub arguments: Vec<u8>,
+}
+
+impl Query {
+    /// Creates a new query.
+    pub fn new(instance_id: InstanceId, method_id: MethodId, arguments: Vec<u8>) -> Self {
+        Self {
+            instance_id,
+            method_id,
+            arguments,
+        }
+    }
+}
+
+/// Request to perform a query sent to the node via an [`ApiSender`].
+///
+/// [`ApiSender`]: struct.ApiSender.html
+#[derive(Debug)]
+pub struct QueryRequest {
+    query: Query,
+    response: oneshot::Sender<Result<Vec<u8>, ExecutionError>>,
+}
+
+impl QueryRequest {
+    pub(super) fn new(query: Query) -> (Self, oneshot::Receiver<Result<Vec<u8>, ExecutionError>>) {
+        let (response, receiver) = oneshot::channel();
+        (Self { query, response }, receiver)
+    }
+
+    /// Processes the request with the provided simulator and sends the result back
+    /// to the requester.
+    pub fn process(self, simulator: &Simulator) {
+        let result = simulator.query(&self.query);
+        // The requester may have already gone; this is not an error.
+        self.response.send(result).ok();
+    }
+}
+
+/// Error that can occur during an asynchronous query.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum QueryError {
+    /// The blockchain is not attached to a running node.
+    #[error("Failed to perform query: the blockchain is not attached to a running node")]
+    NotAttached,
+    /// The query was dispatched, but its execution has failed.
+    #[error("Query execution failed: {}", _0)]
+    Execution(#[from] ExecutionError),
+}
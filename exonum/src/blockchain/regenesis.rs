@@ -0,0 +1,262 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Regenesis, i.e., transferring the state of services into the genesis block
+//! of a new blockchain.
+
+use exonum_merkledb::{access::AccessError, Fork, IndexDump, Snapshot, SystemSchema};
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    blockchain::{
+        config::{GenesisConfig, GenesisConfigBuilder, InstanceInitParams},
+        Schema,
+    },
+    helpers::Height,
+    runtime::{index_owner, ArtifactSpec, ArtifactStatus, DispatcherSchema, InstanceStatus},
+};
+
+/// State of service instances exported from an existing blockchain, which can be used
+/// to initialize the genesis block of a new blockchain.
+///
+/// The state is produced by [`export_genesis()`] together with a [`GenesisConfig`]
+/// and is applied by the [`BlockchainBuilder`] via [`with_genesis_state()`]. If the state
+/// is provided, service instances listed in the genesis config are not initialized
+/// via their constructors; instead, their data is restored from the state.
+///
+/// [`export_genesis()`]: fn.export_genesis.html
+/// [`GenesisConfig`]: config/struct.GenesisConfig.html
+/// [`BlockchainBuilder`]: struct.BlockchainBuilder.html
+/// [`with_genesis_state()`]: struct.BlockchainBuilder.html#method.with_genesis_state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct GenesisState {
+    /// Height of the blockchain the state was exported at.
+    pub source_height: Height,
+    /// Raw contents of the service indexes.
+    #[serde(with = "self::index_dumps")]
+    pub indexes: Vec<IndexDump>,
+}
+
+impl GenesisState {
+    /// Creates a state from the constituent parts.
+    pub fn new(source_height: Height, indexes: Vec<IndexDump>) -> Self {
+        Self {
+            source_height,
+            indexes,
+        }
+    }
+
+    /// Restores the state in the provided `fork`.
+    ///
+    /// # Panics
+    ///
+    /// - If an index does not belong to one of the `instances`.
+    pub(super) fn restore(
+        &self,
+        fork: &Fork,
+        instances: &[InstanceInitParams],
+    ) -> Result<(), AccessError> {
+        let names: Vec<_> = instances
+            .iter()
+            .map(|instance| instance.instance_spec.name.as_str())
+            .collect();
+        for dump in &self.indexes {
+            let name = dump.address.name();
+            assert!(
+                index_owner(name, names.iter().copied()).is_some(),
+                "Index `{}` in the genesis state does not belong to a built-in service",
+                name
+            );
+            dump.restore(fork)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors that can occur during the genesis export.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ExportError {
+    /// The blockchain does not have a genesis block.
+    #[error("Cannot export genesis from an uninitialized blockchain")]
+    NotInitialized,
+
+    /// The requested height differs from the latest committed height of the blockchain.
+    #[error(
+        "Genesis can only be exported at the latest committed height {}, requested height: {}",
+        actual,
+        requested
+    )]
+    HeightMismatch {
+        /// Requested height.
+        requested: Height,
+        /// Latest committed height.
+        actual: Height,
+    },
+
+    /// A service instance is not active.
+    #[error("Cannot export service `{}` with status {}", name, status)]
+    InactiveService {
+        /// Name of the service instance.
+        name: String,
+        /// Current status of the service instance.
+        status: InstanceStatus,
+    },
+
+    /// Data of a service instance was migrated, but the instance was not yet associated
+    /// with the corresponding artifact.
+    #[error(
+        "Cannot export service `{}`, which has migrated data not associated with an artifact",
+        name
+    )]
+    UnassociatedData {
+        /// Name of the service instance.
+        name: String,
+    },
+}
+
+/// Exports the state of the blockchain at the specified `height` into a new genesis
+/// configuration and the corresponding genesis state.
+///
+/// The produced `GenesisConfig` contains the current consensus configuration, all active
+/// artifacts and all service instances of the blockchain. The `GenesisState` contains data
+/// of these instances. Since the snapshot only contains the latest blockchain state,
+/// `height` must be equal to the latest committed height.
+///
+/// All service instances must be active and must not have unassociated migrated data.
+pub fn export_genesis(
+    snapshot: &dyn Snapshot,
+    height: Height,
+) -> Result<(GenesisConfig, GenesisState), ExportError> {
+    let schema = Schema::new(snapshot);
+    if schema.block_hashes_by_height().is_empty() {
+        return Err(ExportError::NotInitialized);
+    }
+    let actual = schema.height();
+    if actual != height {
+        return Err(ExportError::HeightMismatch {
+            requested: height,
+            actual,
+        });
+    }
+
+    let mut builder = GenesisConfigBuilder::with_consensus_config(schema.consensus_config());
+    let dispatcher_schema = DispatcherSchema::new(snapshot);
+    for (artifact, state) in &dispatcher_schema.artifacts() {
+        if state.status == ArtifactStatus::Active {
+            let spec = ArtifactSpec::new(artifact, state.deploy_spec)
+                .with_dependencies(state.dependencies);
+            builder = builder.with_artifact_spec(spec);
+        }
+    }
+
+    let system_schema = SystemSchema::new(snapshot);
+    let instances: Vec<_> = dispatcher_schema.instances().values().collect();
+    let names: Vec<_> = instances
+        .iter()
+        .map(|state| state.spec.name.clone())
+        .collect();
+    let mut indexes = vec![];
+    for state in instances {
+        let name = state.spec.name;
+        match state.status {
+            Some(InstanceStatus::Active) => {}
+            Some(status) => return Err(ExportError::InactiveService { name, status }),
+            None => unreachable!("Instance status is absent outside of block execution"),
+        }
+        if state.data_version.is_some() {
+            return Err(ExportError::UnassociatedData { name });
+        }
+
+        // Indexes of other instances may share the prefix with the instance (e.g., indexes
+        // of `token.v2` start with `token.`), so the owner of each index is checked explicitly.
+        let prefix = [&name, "."].concat();
+        let owned_indexes = system_schema
+            .dump_indexes(&prefix)
+            .into_iter()
+            .filter(|dump| {
+                let owner = index_owner(dump.address.name(), names.iter().map(String::as_str));
+                owner == Some(name.as_str())
+            });
+        indexes.extend(owned_indexes);
+        builder = builder.with_instance(InstanceInitParams::new(
+            state.spec.id,
+            name,
+            state.spec.artifact,
+            (),
+        ));
+    }
+
+    Ok((builder.build(), GenesisState::new(height, indexes)))
+}
+
+mod index_dumps {
+    use exonum_merkledb::{IndexAddress, IndexDump, IndexType};
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+    struct SerializedDump {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id_in_group: Option<String>,
+        index_type: IndexType,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        state: Option<String>,
+        entries: Vec<(String, String)>,
+    }
+
+    pub fn serialize<S: Serializer>(dumps: &[IndexDump], serializer: S) -> Result<S::Ok, S::Error> {
+        let dumps: Vec<_> = dumps
+            .iter()
+            .map(|dump| SerializedDump {
+                name: dump.address.name().to_owned(),
+                id_in_group: dump.address.id_in_group().map(hex::encode),
+                index_type: dump.index_type,
+                state: dump.state.as_ref().map(hex::encode),
+                entries: dump
+                    .entries
+                    .iter()
+                    .map(|(key, value)| (hex::encode(key), hex::encode(value)))
+                    .collect(),
+            })
+            .collect();
+        dumps.serialize(serializer)
+    }
+
+    pub fn deserialize<'a, D: Deserializer<'a>>(
+        deserializer: D,
+    ) -> Result<Vec<IndexDump>, D::Error> {
+        let decode = |hex: String| hex::decode(hex).map_err(D::Error::custom);
+
+        Vec::<SerializedDump>::deserialize(deserializer)?
+            .into_iter()
+            .map(|dump| {
+                let address = if let Some(id_in_group) = dump.id_in_group {
+                    IndexAddress::from_root(dump.name).append_key(&decode(id_in_group)?)
+                } else {
+                    IndexAddress::from_root(dump.name)
+                };
+                let state = dump.state.map(decode).transpose()?;
+                let entries = dump
+                    .entries
+                    .into_iter()
+                    .map(|(key, value)| Ok((decode(key)?, decode(value)?)))
+                    .collect::<Result<_, D::Error>>()?;
+                Ok(IndexDump::new(address, dump.index_type, state, entries))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Replay of committed blocks, i.e., re-execution of the blockchain history
+//! in order to verify that the stored state follows from the stored transactions,
+//! or to trace the execution of a committed transaction.
+
+use exonum_crypto::Hash;
+use exonum_merkledb::{access::Access, BinaryValue, Snapshot};
+use thiserror::Error;
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    blockchain::{
+        Block, BlockParams, BlockchainMut, CallInBlock, ProposerId, Schema, Simulation,
+        SimulationResult,
+    },
+    helpers::Height,
+    messages::{AnyTx, Verified},
+    runtime::{Dispatcher, ExecutionError},
+};
+
+/// Errors that can occur during the blockchain replay.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// The source blockchain does not have a genesis block.
+    #[error("Cannot replay an uninitialized blockchain")]
+    NotInitialized,
+
+    /// The target blockchain has more blocks than the source one.
+    #[error(
+        "Target blockchain at height {} is ahead of the source blockchain at height {}",
+        target,
+        source
+    )]
+    TargetAhead {
+        /// Latest committed height of the source blockchain.
+        source: Height,
+        /// Latest committed height of the target blockchain.
+        target: Height,
+    },
+
+    /// A block in the source blockchain does not specify its proposer.
+    #[error("Block at height {} does not specify its proposer", _0)]
+    NoProposer(Height),
+
+    /// A transaction from a block is missing in the source blockchain.
+    #[error(
+        "Transaction {:?} from the block at height {} is missing in the source blockchain",
+        tx_hash,
+        height
+    )]
+    MissingTransaction {
+        /// Height of the block.
+        height: Height,
+        /// Hash of the missing transaction.
+        tx_hash: Hash,
+    },
+
+    /// A re-executed block differs from the stored one.
+    #[error("{}", _0)]
+    Mismatch(Box<BlockMismatch>),
+}
+
+/// Errors that can occur when tracing a committed transaction.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum TraceError {
+    /// The blockchain is not attached to a running node.
+    #[error("Failed to trace transaction: the blockchain is not attached to a running node")]
+    NotAttached,
+
+    /// The transaction is not committed.
+    #[error("Transaction {:?} is not committed", _0)]
+    NotCommitted(Hash),
+
+    /// Blocks cannot be re-executed, e.g., because the genesis config of the blockchain
+    /// is unknown, or because one of the runtimes does not support replication.
+    #[error("Blocks cannot be re-executed: {}", _0)]
+    NotReplayable(&'static str),
+
+    /// Re-execution of the blocks preceding the transaction has failed.
+    #[error("{}", _0)]
+    Replay(#[from] ReplayError),
+}
+
+/// Difference between a block stored in the source blockchain and the same block
+/// obtained by re-executing its transactions.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct BlockMismatch {
+    /// Height of the block.
+    pub height: Height,
+    /// Block stored in the source blockchain.
+    pub expected: Block,
+    /// Block obtained by re-execution.
+    pub actual: Block,
+    /// Calls within the block which have different execution results. The results
+    /// are specified as `(expected, actual)` pairs, with `None` denoting a successful call.
+    pub calls: BTreeMap<CallInBlock, (Option<ExecutionError>, Option<ExecutionError>)>,
+}
+
+impl BlockMismatch {
+    fn new<T, U>(expected: Block, source: &Schema<T>, actual: Block, target: &Schema<U>) -> Self
+    where
+        T: Access,
+        U: Access,
+    {
+        let height = expected.height;
+        let mut calls = BTreeMap::new();
+        for (call, error) in &source.call_errors_map(height) {
+            calls.insert(call, (Some(error), None));
+        }
+        for (call, error) in &target.call_errors_map(height) {
+            calls.entry(call).or_insert((None, None)).1 = Some(error);
+        }
+        calls.retain(|_, (expected, actual)| {
+            expected.as_ref().map(BinaryValue::to_bytes)
+                != actual.as_ref().map(BinaryValue::to_bytes)
+        });
+
+        Self {
+            height,
+            expected,
+            actual,
+            calls,
+        }
+    }
+}
+
+impl fmt::Display for BlockMismatch {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_field<T: fmt::Debug + PartialEq>(
+            formatter: &mut fmt::Formatter<'_>,
+            name: &str,
+            expected: T,
+            actual: T,
+        ) -> fmt::Result {
+            if expected == actual {
+                Ok(())
+            } else {
+                write!(
+                    formatter,
+                    "\n  {}: expected {:?}, got {:?}",
+                    name, expected, actual
+                )
+            }
+        }
+
+        fn display_result(result: &Option<ExecutionError>) -> String {
+            result
+                .as_ref()
+                .map_or_else(|| "success".to_owned(), ToString::to_string)
+        }
+
+        write!(
+            formatter,
+            "Re-executed block at height {} differs from the stored one:",
+            self.height
+        )?;
+        let (expected, actual) = (&self.expected, &self.actual);
+        write_field(formatter, "tx_count", expected.tx_count, actual.tx_count)?;
+        write_field(formatter, "prev_hash", expected.prev_hash, actual.prev_hash)?;
+        write_field(formatter, "tx_hash", expected.tx_hash, actual.tx_hash)?;
+        write_field(
+            formatter,
+            "state_hash",
+            expected.state_hash,
+            actual.state_hash,
+        )?;
+        write_field(
+            formatter,
+            "error_hash",
+            expected.error_hash,
+            actual.error_hash,
+        )?;
+        write_field(
+            formatter,
+            "events_hash",
+            expected.events_hash(),
+            actual.events_hash(),
+        )?;
+
+        for (call, (expected, actual)) in &self.calls {
+            write!(
+                formatter,
+                "\n  result of {}: expected {}, got {}",
+                call,
+                display_result(expected),
+                display_result(actual)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Re-executes blocks stored in the `source` snapshot and commits them to the `target`
+/// blockchain, checking that the re-executed blocks coincide with the stored ones.
+///
+/// The `target` blockchain should use a separate database and be initialized with the same
+/// genesis config and runtimes as the source blockchain. If the `target` already has some
+/// blocks (e.g., from a previous interrupted replay), the replay continues from
+/// the latest committed height of the `target`. In any case, the latest block of the `target`
+/// is compared with the corresponding block in the `source` before re-executing further blocks.
+///
+/// Blocks are re-executed via [`BlockchainMut::create_patch()`] and compared with the stored
+/// ones by all header fields, including `tx_hash`, `state_hash` and `error_hash`. The replay
+/// stops at the first mismatch; the corresponding error describes the difference between
+/// the blocks.
+///
+/// # Return value
+///
+/// Returns the latest verified height, which is equal to the latest committed height
+/// of the `source` blockchain. Block skips are not replayed since they do not influence
+/// the blockchain state.
+///
+/// # Panics
+///
+/// - If the re-executed block cannot be committed to the `target`.
+///
+/// [`BlockchainMut::create_patch()`]: struct.BlockchainMut.html#method.create_patch
+pub fn replay_blocks(
+    source: &dyn Snapshot,
+    target: &mut BlockchainMut,
+) -> Result<Height, ReplayError> {
+    let source_schema = Schema::new(source);
+    if source_schema.block_hashes_by_height().is_empty() {
+        return Err(ReplayError::NotInitialized);
+    }
+    let source_height = source_schema.height();
+    replay_blocks_up_to(&source_schema, target, source_height)?;
+    Ok(source_height)
+}
+
+/// Re-executes the block containing the committed transaction with the specified hash
+/// on the `target` blockchain and traces the execution of the transaction.
+///
+/// The `target` blockchain should satisfy the same requirements as in [`replay_blocks()`].
+/// Blocks preceding the block with the transaction are replayed and committed to the `target`.
+/// The block with the transaction is re-executed up to the transaction (i.e., scheduled calls,
+/// `before_transactions` hooks and preceding transactions are executed), but it is not
+/// committed. Thus, the transaction is traced against the same state as it was executed
+/// against originally.
+///
+/// [`replay_blocks()`]: fn.replay_blocks.html
+pub fn trace_transaction(
+    source: &dyn Snapshot,
+    target: &mut BlockchainMut,
+    tx_hash: Hash,
+) -> Result<SimulationResult, TraceError> {
+    let source_schema = Schema::new(source);
+    let location = source_schema
+        .transactions_locations()
+        .get(&tx_hash)
+        .ok_or(TraceError::NotCommitted(tx_hash))?;
+    // The genesis block never contains transactions, so the height is positive.
+    let height = location.block_height();
+    replay_blocks_up_to(&source_schema, target, height.previous())?;
+
+    let (tx_hashes, tx_cache) = block_transactions(&source_schema, height)?;
+    let tx_index = location.position_in_block();
+    let mut fork = target.fork();
+    target.execute_block_prologue(&mut fork, height);
+    for (index, hash) in (0..tx_index).zip(&tx_hashes) {
+        target.execute_transaction(*hash, height, index, &mut fork, &tx_cache);
+    }
+
+    let mut simulation = Simulation::signed(&tx_cache[&tx_hash]).with_trace();
+    simulation.tx_index = tx_index;
+    // The transaction is traced with the same resource limits as it was executed with.
+    let limits = Dispatcher::resource_limits(&fork);
+    let result = target.dispatcher().simulate(&mut fork, &simulation, limits);
+    Ok(result)
+}
+
+/// Replays blocks stored in the `source` up to and including the block at `last_height`.
+fn replay_blocks_up_to<T: Access>(
+    source_schema: &Schema<T>,
+    target: &mut BlockchainMut,
+    last_height: Height,
+) -> Result<(), ReplayError> {
+    let target_height = target.as_ref().last_block().height;
+    if target_height > last_height {
+        return Err(ReplayError::TargetAhead {
+            source: last_height,
+            target: target_height,
+        });
+    }
+
+    let target_snapshot = target.snapshot();
+    check_block(
+        source_schema,
+        &Schema::new(target_snapshot.as_ref()),
+        target_height,
+    )?;
+
+    for height in (target_height.next().0..=last_height.0).map(Height) {
+        let block_proof = source_schema
+            .block_and_precommits(height)
+            .expect("BUG: no block at height not exceeding the latest committed height");
+        let block = &block_proof.block;
+        let proposer = block
+            .get_header::<ProposerId>()
+            .ok()
+            .flatten()
+            .ok_or(ReplayError::NoProposer(height))?;
+        let epoch = block.epoch().unwrap_or(height);
+
+        let (tx_hashes, tx_cache) = block_transactions(source_schema, height)?;
+        let block_params = BlockParams::new(proposer, epoch, &tx_hashes);
+        let patch = target.create_patch(block_params, &tx_cache);
+        check_block(source_schema, &Schema::new(patch.as_ref()), height)?;
+
+        target
+            .commit(patch, block_proof.precommits)
+            .expect("Cannot commit re-executed block");
+        log::info!("Replayed block at height {}", height);
+    }
+    Ok(())
+}
+
+/// Returns hashes of transactions in the block at the specified height together
+/// with the transactions themselves.
+fn block_transactions<T: Access>(
+    schema: &Schema<T>,
+    height: Height,
+) -> Result<(Vec<Hash>, BTreeMap<Hash, Verified<AnyTx>>), ReplayError> {
+    let tx_hashes: Vec<_> = schema.block_transactions(height).iter().collect();
+    let transactions = schema.transactions();
+    let mut tx_cache = BTreeMap::new();
+    for &tx_hash in &tx_hashes {
+        let transaction = transactions
+            .get(&tx_hash)
+            .ok_or(ReplayError::MissingTransaction { height, tx_hash })?;
+        tx_cache.insert(tx_hash, transaction);
+    }
+    Ok((tx_hashes, tx_cache))
+}
+
+/// Checks that the block at the specified height is the same in both blockchains.
+fn check_block<T, U>(
+    source: &Schema<T>,
+    target: &Schema<U>,
+    height: Height,
+) -> Result<(), ReplayError>
+where
+    T: Access,
+    U: Access,
+{
+    let expected = source
+        .block_hash_by_height(height)
+        .and_then(|hash| source.blocks().get(&hash))
+        .expect("BUG: no block in the source blockchain");
+    let actual = target
+        .block_hash_by_height(height)
+        .and_then(|hash| target.blocks().get(&hash))
+        .expect("BUG: no block in the target blockchain");
+
+    if expected == actual {
+        Ok(())
+    } else {
+        let mismatch = BlockMismatch::new(expected, source, actual, target);
+        Err(ReplayError::Mismatch(Box::new(mismatch)))
+    }
+}
@@ -24,13 +24,13 @@ use exonum_proto::ProtobufConvert;
 
 use std::fmt;
 
-use super::{Block, BlockProof, CallProof, ConsensusConfig};
+use super::{Block, BlockProof, CallProof, ConsensusConfig, EventProof, TransactionReceipt};
 use crate::{
     crypto::{Hash, PublicKey},
     helpers::{Height, ValidatorId},
     messages::{AnyTx, Precommit, Verified},
     proto::schema::blockchain as pb_blockchain,
-    runtime::{ExecutionError, ExecutionErrorAux, InstanceId},
+    runtime::{ExecutionError, ExecutionErrorAux, InstanceId, MethodId},
 };
 
 /// Defines `&str` constants with given name and value.
@@ -48,6 +48,11 @@ define_names!(
     TRANSACTIONS => "transactions";
     CALL_ERRORS => "call_errors";
     CALL_ERRORS_AUX => "call_errors_aux";
+    CALL_EVENTS => "call_events";
+    SCHEDULED_CALLS => "scheduled_calls";
+    SCHEDULED_CALLS_BY_HEIGHT => "scheduled_calls_by_height";
+    NEXT_SCHEDULED_CALL_ID => "next_scheduled_call_id";
+    SCHEDULED_CALLS_COUNT => "scheduled_calls_count";
     TRANSACTIONS_LEN => "transactions_len";
     TRANSACTIONS_POOL => "transactions_pool";
     TRANSACTIONS_POOL_LEN => "transactions_pool_len";
@@ -134,7 +139,40 @@ impl<T: Access> Schema<T> {
         self.access.get_map((CALL_ERRORS_AUX, &block_height.0))
     }
 
-    /// Returns a record of errors that occurred during execution of a particular block.
+    /// Returns events emitted by services during execution of a particular block.
+    pub(crate) fn call_events_map(
+        &self,
+        block_height: Height,
+    ) -> ProofMapIndex<T::Base, CallInBlock, CallEvents> {
+        self.access.get_proof_map((CALL_EVENTS, &block_height.0))
+    }
+
+    /// Returns calls scheduled by services, keyed by the call identifier. Calls are removed
+    /// from the index once executed or cancelled.
+    pub fn scheduled_calls(&self) -> ProofMapIndex<T::Base, u64, ScheduledCall> {
+        self.access.get_proof_map(SCHEDULED_CALLS)
+    }
+
+    /// Returns identifiers of calls scheduled at the specified height.
+    pub(crate) fn scheduled_calls_at(&self, height: Height) -> KeySetIndex<T::Base, u64> {
+        self.access
+            .get_key_set((SCHEDULED_CALLS_BY_HEIGHT, &height.0))
+    }
+
+    fn next_scheduled_call_id_entry(&self) -> Entry<T::Base, u64> {
+        self.access.get_entry(NEXT_SCHEDULED_CALL_ID)
+    }
+
+    fn scheduled_calls_count_entry(&self, height: Height) -> Entry<T::Base, u64> {
+        self.access.get_entry((SCHEDULED_CALLS_COUNT, &height.0))
+    }
+
+    /// Returns the number of calls scheduled at the specified height.
+    pub(crate) fn scheduled_calls_count(&self, height: Height) -> u64 {
+        self.scheduled_calls_count_entry(height).get().unwrap_or(0)
+    }
+
+    /// Returns a record of errors and events that occurred during execution of a particular block.
     /// If the block is not committed, returns `None`.
     pub fn call_records(&self, block_height: Height) -> Option<CallRecords<T>> {
         self.block_hash_by_height(block_height)?;
@@ -142,6 +180,7 @@ impl<T: Access> Schema<T> {
             height: block_height,
             errors: self.call_errors_map(block_height),
             errors_aux: self.call_errors_aux(block_height),
+            events: self.call_events_map(block_height),
             access: self.access.clone(),
         })
     }
@@ -158,6 +197,24 @@ impl<T: Access> Schema<T> {
         Some(status)
     }
 
+    /// Returns a receipt proving that the transaction with the specified hash was committed
+    /// with a certain execution status. If the transaction is not committed, returns `None`.
+    pub fn transaction_receipt(&self, tx_hash: &Hash) -> Option<TransactionReceipt> {
+        let location = self.transactions_locations().get(tx_hash)?;
+        let position = location.position_in_block;
+        let location_proof = self
+            .block_transactions(location.block_height)
+            .get_proof(u64::from(position));
+        let call_proof = self
+            .call_records(location.block_height)?
+            .get_proof(CallInBlock::transaction(position));
+        Some(TransactionReceipt::new(
+            location,
+            location_proof,
+            call_proof,
+        ))
+    }
+
     /// Returns an entry that represents a count of committed transactions in the blockchain.
     fn transactions_len_index(&self) -> Entry<T::Base, u64> {
         self.access.get_entry(TRANSACTIONS_LEN)
@@ -412,6 +469,45 @@ where
         self.call_errors_aux(height).put(&call, aux);
     }
 
+    /// Saves events emitted during the specified call to the blockchain.
+    pub(crate) fn save_events(
+        &mut self,
+        height: Height,
+        call: CallInBlock,
+        events: Vec<ServiceEvent>,
+    ) {
+        self.call_events_map(height)
+            .put(&call, CallEvents { events });
+    }
+
+    /// Stores a scheduled call and returns its identifier.
+    pub(crate) fn schedule_call(&mut self, call: ScheduledCall) -> u64 {
+        let mut id_entry = self.next_scheduled_call_id_entry();
+        let id = id_entry.get().unwrap_or(0);
+        id_entry.set(id + 1);
+
+        let mut count_entry = self.scheduled_calls_count_entry(call.height);
+        count_entry.set(count_entry.get().unwrap_or(0) + 1);
+        self.scheduled_calls_at(call.height).insert(&id);
+        self.scheduled_calls().put(&id, call);
+        id
+    }
+
+    /// Removes a scheduled call with the specified identifier and returns it.
+    pub(crate) fn remove_scheduled_call(&mut self, id: u64) -> Option<ScheduledCall> {
+        let mut calls = self.scheduled_calls();
+        let call = calls.get(&id)?;
+        calls.remove(&id);
+        self.scheduled_calls_at(call.height).remove(&id);
+
+        let mut count_entry = self.scheduled_calls_count_entry(call.height);
+        match count_entry.get().unwrap_or(0) {
+            0 | 1 => count_entry.remove(),
+            count => count_entry.set(count - 1),
+        }
+        Some(call)
+    }
+
     pub(super) fn clear_block_skip(&mut self) {
         if let Some(block_skip) = self.block_skip_entry().take() {
             let block_hash = block_skip.object_hash();
@@ -426,15 +522,16 @@ where
     }
 }
 
-/// Information about call errors within a specific block.
+/// Information about call errors and events within a specific block.
 ///
 /// This data type can be used to get information or build proofs that execution
-/// of a certain call ended up with a particular status.
+/// of a certain call ended up with a particular status or emitted certain events.
 #[derive(Debug)]
 pub struct CallRecords<T: Access> {
     height: Height,
     errors: ProofMapIndex<T::Base, CallInBlock, ExecutionError>,
     errors_aux: MapIndex<T::Base, CallInBlock, ExecutionErrorAux>,
+    events: ProofMapIndex<T::Base, CallInBlock, CallEvents>,
     access: T,
 }
 
@@ -476,6 +573,31 @@ impl<T: Access> CallRecords<T> {
         let call_proof = self.errors.get_proof(call);
         CallProof::new(block_proof, call_proof, error_description)
     }
+
+    /// Iterates over events emitted in a block, grouped by the top-level call.
+    pub fn events(&self) -> Entries<'_, CallInBlock, CallEvents> {
+        self.events.iter()
+    }
+
+    /// Returns events emitted during a top-level call in the order of their emission.
+    /// If the call has not emitted any events or was not performed at all, returns
+    /// an empty vector.
+    pub fn get_events(&self, call: CallInBlock) -> Vec<ServiceEvent> {
+        self.events
+            .get(&call)
+            .map(|call_events| call_events.events)
+            .unwrap_or_default()
+    }
+
+    /// Returns a cryptographic proof of authenticity for events emitted during a top-level call
+    /// within a block.
+    pub fn get_events_proof(&self, call: CallInBlock) -> EventProof {
+        let block_proof = Schema::new(self.access.clone())
+            .block_and_precommits(self.height)
+            .unwrap();
+        let events_proof = self.events.get_proof(call);
+        EventProof::new(block_proof, events_proof)
+    }
 }
 
 /// Iterator over errors in a block returned by `CallRecords::errors()`.
@@ -511,6 +633,7 @@ impl Iterator for CallErrorsIter<'_> {
 ///
 /// ```rust
 /// # use exonum::blockchain::CallInBlock;
+/// assert!(CallInBlock::scheduled_call(10) < CallInBlock::before_transactions(0));
 /// assert!(CallInBlock::before_transactions(3) < CallInBlock::transaction(0));
 /// assert!(CallInBlock::transaction(0) < CallInBlock::transaction(1));
 /// assert!(CallInBlock::transaction(1) < CallInBlock::after_transactions(0));
@@ -534,6 +657,13 @@ impl Iterator for CallErrorsIter<'_> {
 #[serde(tag = "type", rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum CallInBlock {
+    /// Call scheduled by a service with [`ExecutionContext::schedule_call()`].
+    ///
+    /// [`ExecutionContext::schedule_call()`]: ../runtime/struct.ExecutionContext.html#method.schedule_call
+    ScheduledCall {
+        /// Identifier of the scheduled call.
+        id: u64,
+    },
     /// Call of `before_transactions` hook in a service.
     BeforeTransactions {
         /// Numerical service identifier.
@@ -560,6 +690,7 @@ impl ProtobufConvert for CallInBlock {
             Self::BeforeTransactions { id } => pb.set_before_transactions(*id),
             Self::Transaction { index } => pb.set_transaction(*index),
             Self::AfterTransactions { id } => pb.set_after_transactions(*id),
+            Self::ScheduledCall { id } => pb.set_scheduled_call(*id),
         }
         pb
     }
@@ -577,6 +708,10 @@ impl ProtobufConvert for CallInBlock {
             Ok(Self::AfterTransactions {
                 id: pb.get_after_transactions(),
             })
+        } else if pb.has_scheduled_call() {
+            Ok(Self::ScheduledCall {
+                id: pb.get_scheduled_call(),
+            })
         } else {
             Err(format_err!("Invalid location format"))
         }
@@ -584,6 +719,11 @@ impl ProtobufConvert for CallInBlock {
 }
 
 impl CallInBlock {
+    /// Creates a location corresponding to a scheduled call with the specified identifier.
+    pub fn scheduled_call(id: u64) -> Self {
+        Self::ScheduledCall { id }
+    }
+
     /// Creates a location corresponding to a `before_transactions` call.
     pub fn before_transactions(id: InstanceId) -> Self {
         Self::BeforeTransactions { id }
@@ -602,6 +742,96 @@ impl CallInBlock {
 
 impl_binary_key_for_binary_value!(CallInBlock);
 
+/// Event emitted by a service during an isolated call within a block.
+///
+/// Services emit events with the [`ExecutionContext::emit_event()`] method. Events are
+/// recorded in the core schema together with the location of the top-level call,
+/// and can be proven to external clients with an [`EventProof`]. If the call ends
+/// with an error, the events emitted within it are discarded together with other changes
+/// to the blockchain state.
+///
+/// [`ExecutionContext::emit_event()`]: ../runtime/struct.ExecutionContext.html#method.emit_event
+/// [`EventProof`]: struct.EventProof.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "pb_blockchain::ServiceEvent")]
+#[non_exhaustive]
+pub struct ServiceEvent {
+    /// Identifier of the service instance which has emitted the event.
+    pub instance_id: InstanceId,
+    /// Topic of the event, e.g., `transfer`.
+    pub topic: String,
+    /// Service-specific payload of the event.
+    pub payload: Vec<u8>,
+}
+
+impl ServiceEvent {
+    /// Creates a new event.
+    pub fn new(instance_id: InstanceId, topic: impl Into<String>, payload: Vec<u8>) -> Self {
+        Self {
+            instance_id,
+            topic: topic.into(),
+            payload,
+        }
+    }
+}
+
+/// Call of a service method scheduled to be executed at the start of a future block.
+///
+/// Services schedule calls with [`ExecutionContext::schedule_call()`]. The call is performed
+/// with the [`Caller::Blockchain`] authority before `before_transactions` hooks of the target
+/// block, and its result is recorded in [`CallRecords`] under [`CallInBlock::ScheduledCall`].
+///
+/// [`ExecutionContext::schedule_call()`]: ../runtime/struct.ExecutionContext.html#method.schedule_call
+/// [`Caller::Blockchain`]: ../runtime/enum.Caller.html#variant.Blockchain
+/// [`CallRecords`]: struct.CallRecords.html
+/// [`CallInBlock::ScheduledCall`]: enum.CallInBlock.html#variant.ScheduledCall
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "pb_blockchain::ScheduledCall")]
+#[non_exhaustive]
+pub struct ScheduledCall {
+    /// Identifier of the service instance which has scheduled the call. The call is addressed
+    /// to the same service.
+    pub instance_id: InstanceId,
+    /// Identifier of the method in the default interface of the service.
+    pub method_id: MethodId,
+    /// Serialized method arguments.
+    pub arguments: Vec<u8>,
+    /// Height of the block at the start of which the call is executed.
+    pub height: Height,
+}
+
+impl ScheduledCall {
+    /// Creates a new scheduled call.
+    pub fn new(
+        instance_id: InstanceId,
+        method_id: MethodId,
+        arguments: Vec<u8>,
+        height: Height,
+    ) -> Self {
+        Self {
+            instance_id,
+            method_id,
+            arguments,
+            height,
+        }
+    }
+}
+
+/// Events emitted during a single isolated call within a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash)]
+#[protobuf_convert(source = "pb_blockchain::CallEvents")]
+#[non_exhaustive]
+pub struct CallEvents {
+    /// Events in the order of their emission.
+    pub events: Vec<ServiceEvent>,
+}
+
 impl fmt::Display for CallInBlock {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -614,6 +844,7 @@ impl fmt::Display for CallInBlock {
             Self::AfterTransactions { id } => {
                 write!(formatter, "`after_transactions` for service with ID {}", id)
             }
+            Self::ScheduledCall { id } => write!(formatter, "scheduled call #{}", id),
         }
     }
 }
@@ -0,0 +1,441 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Simulation of transaction execution without committing its results.
+
+use exonum_crypto::{self as crypto, Hash, KeyPair, PublicKey};
+use exonum_merkledb::{
+    BinaryValue, Database, IndexAddress, ObjectHash, Patch, Snapshot, SystemSchema, TemporaryDB,
+};
+use futures::channel::oneshot;
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    blockchain::{
+        config::GenesisConfig, trace_transaction, ApiSender, Blockchain, BlockchainBuilder,
+        BlockchainMut, Query, Schema, TraceError,
+    },
+    messages::{AnyTx, Verified},
+    runtime::{CallTrace, Caller, Dispatcher, ExecutionError, ExecutionStatus},
+};
+
+/// Transaction to be simulated against the latest blockchain state.
+///
+/// Simulation executes the transaction in the same way as it would be executed in a block
+/// at the next blockchain height, but its results are never committed to the storage.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub(crate) tx: AnyTx,
+    pub(crate) tx_hash: Hash,
+    pub(crate) tx_index: u32,
+    pub(crate) caller: Caller,
+    pub(crate) is_signed: bool,
+    with_diff: bool,
+    with_trace: bool,
+}
+
+impl Simulation {
+    /// Creates a simulation of a signed transaction.
+    pub fn signed(tx: &Verified<AnyTx>) -> Self {
+        Self {
+            tx: tx.as_ref().to_owned(),
+            tx_hash: tx.object_hash(),
+            tx_index: 0,
+            caller: Caller::for_transaction(tx),
+            is_signed: true,
+            with_diff: false,
+            with_trace: false,
+        }
+    }
+
+    /// Creates a simulation of an unsigned transaction on behalf of the specified `author`.
+    ///
+    /// Unsigned transactions are simulated only if the runtime of the target service
+    /// supports [`RuntimeFeature::UnsignedSimulation`]; otherwise, the simulation
+    /// results in an error. As the transaction does not have a signature, its hash
+    /// (as reported to the service) is calculated over the transaction payload.
+    ///
+    /// [`RuntimeFeature::UnsignedSimulation`]: ../runtime/enum.RuntimeFeature.html#variant.UnsignedSimulation
+    pub fn unsigned(tx: AnyTx, author: PublicKey) -> Self {
+        Self {
+            tx_hash: crypto::hash(&tx.to_bytes()),
+            tx,
+            tx_index: 0,
+            caller: Caller::Transaction { author },
+            is_signed: false,
+            with_diff: false,
+            with_trace: false,
+        }
+    }
+
+    /// Requests the simulation to return changes in the state hashes of the touched indexes.
+    pub fn with_diff(mut self) -> Self {
+        self.with_diff = true;
+        self
+    }
+
+    /// Requests the simulation to return the trace of calls made by the transaction.
+    pub fn with_trace(mut self) -> Self {
+        self.with_trace = true;
+        self
+    }
+
+    /// Returns the hash of the simulated transaction.
+    pub fn tx_hash(&self) -> Hash {
+        self.tx_hash
+    }
+
+    fn needs_diff(&self) -> bool {
+        self.with_diff
+    }
+
+    pub(crate) fn needs_trace(&self) -> bool {
+        self.with_trace
+    }
+}
+
+/// Result of a transaction simulation.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SimulationResult {
+    /// Execution status of the transaction.
+    pub status: ExecutionStatus,
+    /// Addresses of indexes accessed by the transaction, in the order of the first access.
+    /// Indexes are recorded regardless of whether the transaction has succeeded.
+    pub touched_indexes: Vec<IndexAddress>,
+    /// Changes in the hashes of aggregated indexes caused by the transaction, keyed by
+    /// the full index name. Only indexes which hashes have changed are included.
+    ///
+    /// This field is set only if requested via [`Simulation::with_diff()`].
+    ///
+    /// [`Simulation::with_diff()`]: struct.Simulation.html#method.with_diff
+    pub diff: Option<BTreeMap<String, IndexHashChange>>,
+    /// Trace of calls made by the transaction, including storage keys read and written
+    /// by each call. The trace is absent if the transaction has failed before the call
+    /// to the service (e.g., if the service does not exist).
+    ///
+    /// This field is set only if requested via [`Simulation::with_trace()`].
+    ///
+    /// [`Simulation::with_trace()`]: struct.Simulation.html#method.with_trace
+    pub trace: Option<CallTrace>,
+}
+
+/// Change in the hash of an aggregated index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct IndexHashChange {
+    /// Index hash before the transaction, or `None` if the index did not exist.
+    pub before: Option<Hash>,
+    /// Index hash after the transaction, or `None` if the index was removed.
+    pub after: Option<Hash>,
+}
+
+impl SimulationResult {
+    pub(crate) fn new(
+        res: Result<(), ExecutionError>,
+        touched_indexes: Vec<IndexAddress>,
+        trace: Option<CallTrace>,
+    ) -> Self {
+        Self {
+            status: res.into(),
+            touched_indexes,
+            diff: None,
+            trace,
+        }
+    }
+
+    fn diff(
+        snapshot: &dyn Snapshot,
+        patch: &Patch,
+        touched_indexes: &[IndexAddress],
+    ) -> BTreeMap<String, IndexHashChange> {
+        let aggregator_before = SystemSchema::new(snapshot).state_aggregator();
+        let aggregator_after = SystemSchema::new(patch).state_aggregator();
+
+        touched_indexes
+            .iter()
+            .filter(|address| address.id_in_group().is_none())
+            .filter_map(|address| {
+                let name = address.name();
+                let change = IndexHashChange {
+                    before: aggregator_before.get(name),
+                    after: aggregator_after.get(name),
+                };
+                if change.before == change.after {
+                    None
+                } else {
+                    Some((name.to_owned(), change))
+                }
+            })
+            .collect()
+    }
+}
+
+/// Handle simulating transactions against the latest blockchain state.
+///
+/// Simulators are created with [`BlockchainMut::simulator()`] and share services with
+/// the blockchain. Unlike `BlockchainMut`, a simulator can be cloned and used from
+/// other threads; e.g., the node processes simulations outside of its event loop.
+///
+/// [`BlockchainMut::simulator()`]: struct.BlockchainMut.html#method.simulator
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    db: Arc<dyn Database>,
+    dispatcher: Arc<Mutex<Dispatcher>>,
+    genesis_config: Option<Arc<GenesisConfig>>,
+    /// Temporary blockchain with the replayed blocks, which is reused by subsequent traces.
+    /// The mutex also ensures that traces are performed one at a time.
+    replica: Arc<Mutex<Option<BlockchainMut>>>,
+}
+
+impl Simulator {
+    pub(super) fn new(
+        db: Arc<dyn Database>,
+        dispatcher: Arc<Mutex<Dispatcher>>,
+        genesis_config: Option<Arc<GenesisConfig>>,
+    ) -> Self {
+        Self {
+            db,
+            dispatcher,
+            genesis_config,
+            replica: Arc::default(),
+        }
+    }
+
+    /// Simulates execution of a transaction against the latest blockchain state.
+    ///
+    /// The transaction is executed in the same way as it would be executed in a block
+    /// at the next height. In particular, the calls scheduled for this height and
+    /// `before_transactions` hooks of the services are executed before the transaction,
+    /// and the transaction is subject to the validity window check and resource metering.
+    /// All changes are discarded after the simulation. The diff of index hashes
+    /// only reflects changes made by the transaction itself.
+    ///
+    /// The simulation blocks while the blockchain commits a block, and vice versa.
+    /// To bound the time the blockchain may wait for a simulation, resources not limited
+    /// by the consensus config are limited for simulations by default values (1,000,000 reads,
+    /// 100,000 writes, 16 MiB of written data, 1,000 nested calls and 100,000,000 units
+    /// of fuel). Thus, a simulation of a transaction exceeding these limits fails
+    /// with `CoreError::ResourceLimitExceeded` even if the transaction would succeed in a block.
+    pub fn simulate(&self, simulation: &Simulation) -> SimulationResult {
+        let dispatcher = self.lock_dispatcher();
+
+        // State of the storage after executing the block prologue, which is used as a baseline
+        // for the diff. The prologue is executed separately since a `Fork` cannot be
+        // used as a snapshot once changes are made to it.
+        let baseline = if simulation.needs_diff() {
+            let mut fork = self.db.fork();
+            dispatcher.execute_block_prologue(&mut fork);
+            Some(fork.into_patch())
+        } else {
+            None
+        };
+
+        let mut fork = self.db.fork();
+        dispatcher.execute_block_prologue(&mut fork);
+        let limits = Dispatcher::simulation_limits(&fork);
+        let mut result = dispatcher.simulate(&mut fork, simulation, limits);
+        drop(dispatcher);
+
+        if let Some(baseline) = baseline {
+            let patch = fork.into_patch();
+            let diff = SimulationResult::diff(&baseline, &patch, &result.touched_indexes);
+            result.diff = Some(diff);
+        }
+        result
+    }
+
+    /// Performs a read-only query to a service against the latest blockchain state.
+    /// See [`BlockchainMut::query()`] for details.
+    ///
+    /// Like simulations, queries block while the blockchain commits a block.
+    ///
+    /// [`BlockchainMut::query()`]: struct.BlockchainMut.html#method.query
+    pub fn query(&self, query: &Query) -> Result<Vec<u8>, ExecutionError> {
+        let snapshot = self.db.snapshot();
+        self.lock_dispatcher().query(
+            snapshot.as_ref(),
+            query.instance_id,
+            query.method_id,
+            &query.arguments,
+        )
+    }
+
+    /// Traces execution of a committed transaction with the specified hash.
+    ///
+    /// The storage does not retain historical states, so the blocks preceding
+    /// the transaction are re-executed on a temporary blockchain with replicas
+    /// of the runtimes (see [`trace_transaction()`] for details). The temporary blockchain
+    /// is retained by the simulator (and shared among its clones), so that subsequent traces
+    /// only re-execute blocks committed since the previous trace, plus the block
+    /// with the transaction. Tracing a transaction older than the replayed blocks
+    /// requires replaying the blockchain from the genesis block again.
+    ///
+    /// Traces are performed one at a time; concurrent calls block until the previous
+    /// trace is finished.
+    ///
+    /// Tracing requires the genesis config of the blockchain to be known (i.e., passed
+    /// to the `BlockchainBuilder` when building the blockchain), and all runtimes
+    /// to support [replication].
+    ///
+    /// [`trace_transaction()`]: fn.trace_transaction.html
+    /// [replication]: ../runtime/trait.Runtime.html#method.replicate
+    pub fn trace(&self, tx_hash: Hash) -> Result<SimulationResult, TraceError> {
+        let snapshot = self.db.snapshot();
+        let tx_height = Schema::new(snapshot.as_ref())
+            .transactions_locations()
+            .get(&tx_hash)
+            .ok_or(TraceError::NotCommitted(tx_hash))?
+            .block_height();
+
+        // If a previous trace has panicked, the replica may be in an inconsistent state,
+        // so it is discarded.
+        let mut replica = self.replica.lock().unwrap_or_else(|poisoned| {
+            let mut replica = poisoned.into_inner();
+            *replica = None;
+            replica
+        });
+        let is_reusable = replica.as_ref().map_or(false, |target| {
+            target.as_ref().last_block().height < tx_height
+        });
+        if !is_reusable {
+            // Drop the old replica before building a new one to limit memory usage.
+            *replica = None;
+            *replica = Some(self.create_replica()?);
+        }
+
+        let target = replica.as_mut().expect("BUG: replica is not initialized");
+        let result = trace_transaction(snapshot.as_ref(), target, tx_hash);
+        if result.is_err() {
+            *replica = None;
+        }
+        result
+    }
+
+    /// Locks the dispatcher shared with the blockchain.
+    ///
+    /// The lock is recovered if it is poisoned. Simulations and queries access the dispatcher
+    /// immutably, so a panic during them cannot leave the dispatcher in an inconsistent state;
+    /// a panic during block execution, on the other hand, halts the node.
+    fn lock_dispatcher(&self) -> MutexGuard<'_, Dispatcher> {
+        self.dispatcher
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Creates an empty temporary blockchain with replicas of the runtimes.
+    fn create_replica(&self) -> Result<BlockchainMut, TraceError> {
+        let genesis_config = self
+            .genesis_config
+            .as_ref()
+            .ok_or(TraceError::NotReplayable("genesis config is unknown"))?;
+        let runtimes = self
+            .lock_dispatcher()
+            .replicate_runtimes()
+            .ok_or(TraceError::NotReplayable("runtimes cannot be replicated"))?;
+
+        // The keypair is random, so that services do not act on behalf of a validator
+        // during the replay.
+        let blockchain =
+            Blockchain::new(TemporaryDB::new(), KeyPair::random(), ApiSender::closed());
+        let builder = BlockchainBuilder::new(blockchain)
+            .with_genesis_config(GenesisConfig::clone(genesis_config));
+        let target = runtimes
+            .into_iter()
+            .fold(builder, BlockchainBuilder::with_runtime)
+            .build();
+        Ok(target)
+    }
+}
+
+/// Request to simulate a transaction sent to the node via an [`ApiSender`].
+///
+/// [`ApiSender`]: struct.ApiSender.html
+///
+/// Besides simulations, requests are used to [trace] committed transactions.
+///
+/// [trace]: struct.Simulator.html#method.trace
+#[derive(Debug)]
+pub struct SimulationRequest {
+    inner: RequestInner,
+}
+
+#[derive(Debug)]
+enum RequestInner {
+    Simulate {
+        simulation: Simulation,
+        response: oneshot::Sender<SimulationResult>,
+    },
+    Trace {
+        tx_hash: Hash,
+        response: oneshot::Sender<Result<SimulationResult, TraceError>>,
+    },
+}
+
+impl SimulationRequest {
+    pub(super) fn new(simulation: Simulation) -> (Self, oneshot::Receiver<SimulationResult>) {
+        let (response, receiver) = oneshot::channel();
+        let inner = RequestInner::Simulate {
+            simulation,
+            response,
+        };
+        (Self { inner }, receiver)
+    }
+
+    pub(super) fn trace(
+        tx_hash: Hash,
+    ) -> (
+        Self,
+        oneshot::Receiver<Result<SimulationResult, TraceError>>,
+    ) {
+        let (response, receiver) = oneshot::channel();
+        let inner = RequestInner::Trace { tx_hash, response };
+        (Self { inner }, receiver)
+    }
+
+    /// Processes the request with the provided simulator and sends the result back
+    /// to the requester.
+    pub fn process(self, simulator: &Simulator) {
+        // The requester may have already gone; this is not an error.
+        match self.inner {
+            RequestInner::Simulate {
+                simulation,
+                response,
+            } => {
+                response.send(simulator.simulate(&simulation)).ok();
+            }
+            RequestInner::Trace { tx_hash, response } => {
+                response.send(simulator.trace(tx_hash)).ok();
+            }
+        }
+    }
+}
+
+/// Error that can occur during an asynchronous transaction simulation.
+#[derive(Debug, Error)]
+#[error("Failed to simulate transaction: the blockchain is not attached to a running node")]
+pub struct SimulationError(());
+
+impl SimulationError {
+    pub(super) fn new() -> Self {
+        Self(())
+    }
+}
@@ -12,12 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use assert_matches::assert_matches;
 use chrono::Utc;
-use exonum_crypto::{Hash, KeyPair};
+use exonum_crypto::{Hash, KeyPair, PublicKey};
 use exonum_derive::{BinaryValue, FromAccess};
 use exonum_merkledb::{
     access::{Access, FromAccess},
-    BinaryValue, Error as MerkledbError, ObjectHash, ProofListIndex, Snapshot, SystemSchema,
+    BinaryValue, Error as MerkledbError, IndexAddress, ObjectHash, ProofListIndex, Snapshot,
+    SystemSchema,
 };
 use pretty_assertions::assert_eq;
 use semver::Version;
@@ -25,13 +27,15 @@ use semver::Version;
 use std::{
     cell::RefCell,
     collections::{BTreeMap, VecDeque},
-    panic,
+    panic, thread,
 };
 
 use crate::{
     blockchain::{
         config::{ConsensusConfig, GenesisConfig, GenesisConfigBuilder, InstanceInitParams},
-        BlockParams, Blockchain, BlockchainMut, PersistentPool, Schema, TransactionCache,
+        export_genesis, replay_blocks, trace_transaction, BlockParams, Blockchain, BlockchainMut,
+        CallInBlock, GenesisState, PersistentPool, Query, ReplayError, Schema, Simulation,
+        TraceError, TransactionCache,
     },
     helpers::{Height, Round, ValidatorId},
     messages::{Precommit, Verified},
@@ -39,10 +43,10 @@ use crate::{
         catch_panic,
         migrations::{InitMigrationError, MigrationScript},
         oneshot::Receiver,
-        AnyTx, ArtifactId, CallInfo, CommonError, CoreError, Dispatcher, DispatcherSchema,
-        ErrorMatch, ExecutionContext, ExecutionError, ExecutionFail, InstanceId, InstanceSpec,
-        InstanceState, InstanceStatus, Mailbox, MethodId, Runtime, SnapshotExt, WellKnownRuntime,
-        SUPERVISOR_INSTANCE_ID,
+        AnyTx, ArtifactId, ArtifactSpec, CallInfo, Caller, CommonError, CoreError, Dispatcher,
+        DispatcherSchema, ErrorKind, ErrorMatch, ExecutionContext, ExecutionError, ExecutionFail,
+        InstanceId, InstanceSpec, InstanceState, InstanceStatus, Mailbox, MethodId, Runtime,
+        SnapshotExt, WellKnownRuntime, SUPERVISOR_INSTANCE_ID,
     },
 };
 
@@ -117,6 +121,8 @@ impl Default for InitAction {
 enum AfterTransactionsAction {
     /// Add some value to the inspector schema index.
     AddValue(u64),
+    /// Add values to the inspector schema index in the service namespace, which is metered.
+    AddServiceValues(Vec<u64>),
     /// Emit panic.
     Panic,
 }
@@ -130,6 +136,12 @@ impl Execute for AfterTransactionsAction {
                 Ok(())
             }
 
+            Self::AddServiceValues(values) => {
+                let mut schema = InspectorSchema::new(context.service_data());
+                schema.values.extend(values);
+                Ok(())
+            }
+
             Self::Panic => panic!(PANIC_STR),
         }
     }
@@ -141,6 +153,11 @@ impl Execute for AfterTransactionsAction {
 enum Transaction {
     /// Add some value to the inspector schema index.
     AddValue(u64),
+    /// Add values to the inspector schema index in the service namespace, which is metered.
+    AddServiceValues(Vec<u64>),
+    /// Add values to the inspector schema index in the service namespace, and then
+    /// return an execution error with the corresponding code.
+    AddServiceValuesAndFail(Vec<u64>, u8),
     /// Emit panic.
     Panic,
     /// Emit MerkleDb error.
@@ -153,6 +170,16 @@ enum Transaction {
     AddService(InstanceSpec, InitAction),
     /// Stop service with the specified ID.
     StopService(InstanceId),
+    /// Schedule the transaction to be executed at the specified height.
+    Schedule(Height, Box<Transaction>),
+    /// Schedule the specified number of `AddValue` calls at the specified height.
+    ScheduleMany(Height, u64),
+    /// Cancel the scheduled call with the specified ID.
+    CancelScheduled(u64),
+    /// Check that the transaction is signed by at least the specified number of keys.
+    RequireSigners(usize, Vec<PublicKey>),
+    /// Emit events with the specified payloads.
+    EmitEvents(Vec<Vec<u8>>),
 }
 
 impl Transaction {
@@ -171,6 +198,18 @@ impl Execute for Transaction {
                 Ok(())
             }
 
+            Self::AddServiceValues(values) => {
+                let mut schema = InspectorSchema::new(context.service_data());
+                schema.values.extend(values);
+                Ok(())
+            }
+
+            Self::AddServiceValuesAndFail(values, code) => {
+                let mut schema = InspectorSchema::new(context.service_data());
+                schema.values.extend(values);
+                Err(ExecutionError::service(code, "Values added"))
+            }
+
             Self::Panic => {
                 let mut schema = InspectorSchema::new(&*context.fork);
                 schema.values.push(42);
@@ -189,7 +228,7 @@ impl Execute for Transaction {
                 // Code below will panic if there is already deployed artifact with the
                 // same ID. This sort of expected behavior, since we're intentionally skipping
                 // the `start_deploy` step (which will make the test nature much more complex).
-                Dispatcher::commit_artifact(context.fork, &artifact_id, Vec::new());
+                Dispatcher::commit_artifact(context.fork, ArtifactSpec::new(artifact_id, ()));
                 Ok(())
             }
 
@@ -200,6 +239,32 @@ impl Execute for Transaction {
             Self::StopService(instance_id) => {
                 Dispatcher::initiate_stopping_service(context.fork, instance_id)
             }
+
+            Self::Schedule(height, tx) => context.schedule_call(height, 0, *tx).map(drop),
+
+            Self::ScheduleMany(height, count) => {
+                for i in 0..count {
+                    context.schedule_call(height, 0, Self::AddValue(i))?;
+                }
+                Ok(())
+            }
+
+            Self::CancelScheduled(id) => context.cancel_scheduled_call(id),
+
+            Self::RequireSigners(threshold, keys) => {
+                if context.caller().is_signed_by(&keys, threshold) {
+                    Ok(())
+                } else {
+                    Err(ExecutionError::service(0, "Not enough signatures"))
+                }
+            }
+
+            Self::EmitEvents(payloads) => {
+                for payload in payloads {
+                    context.emit_event("test", payload)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -368,6 +433,14 @@ fn create_blockchain(
     instances: Vec<InstanceInitParams>,
 ) -> BlockchainMut {
     let (config, _) = ConsensusConfig::for_tests(1);
+    create_blockchain_with_config(runtime, config, instances)
+}
+
+fn create_blockchain_with_config(
+    runtime: RuntimeInspector,
+    config: ConsensusConfig,
+    instances: Vec<InstanceInitParams>,
+) -> BlockchainMut {
     let genesis_config = instances
         .into_iter()
         .fold(
@@ -457,6 +530,714 @@ fn handling_tx_panic_error() {
     assert_eq!(InspectorSchema::new(&snapshot).values.get(0), Some(10));
 }
 
+#[test]
+fn handling_tx_exceeding_resource_limits() {
+    let keys = KeyPair::random();
+    let (config, _) = ConsensusConfig::for_tests(1);
+    let config = ConsensusConfig {
+        max_call_writes: Some(20),
+        ..config
+    };
+    let mut blockchain = create_blockchain_with_config(
+        RuntimeInspector::default(),
+        config,
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    execute_transaction(
+        &mut blockchain,
+        Transaction::AddServiceValues(vec![1, 2]).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect("Transaction within limits must succeed");
+
+    // Each added value results in at least one write.
+    let values = (0..50).collect();
+    let err = execute_transaction(
+        &mut blockchain,
+        Transaction::AddServiceValues(values).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect_err("Transaction exceeding limits must fail");
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded).with_any_description()
+    );
+
+    let snapshot = blockchain.snapshot();
+    let service_data = snapshot.for_service(TEST_SERVICE_NAME).unwrap();
+    assert_eq!(
+        InspectorSchema::new(service_data).values.len(),
+        2,
+        "Changes made by the failed transaction should be discarded"
+    );
+
+    // The transaction is aborted as soon as it exceeds the limits, so it does not
+    // get to return its own error.
+    let values = (0..50).collect();
+    let err = execute_transaction(
+        &mut blockchain,
+        Transaction::AddServiceValuesAndFail(values, 5).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect_err("Transaction exceeding limits must fail");
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded).with_any_description()
+    );
+
+    // Transactions returning an error within limits are not affected.
+    let err = execute_transaction(
+        &mut blockchain,
+        Transaction::AddServiceValuesAndFail(vec![1], 5).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect_err("Transaction must fail");
+    assert_eq!(err.description(), "Values added");
+}
+
+#[test]
+fn handling_hook_exceeding_resource_limits() {
+    let keys = KeyPair::random();
+    let (config, _) = ConsensusConfig::for_tests(1);
+    let config = ConsensusConfig {
+        max_call_writes: Some(20),
+        ..config
+    };
+    let runtime = RuntimeInspector::default()
+        // Action for the genesis block.
+        .with_after_transactions_action(AfterTransactionsAction::AddServiceValues(vec![1]))
+        .with_after_transactions_action(AfterTransactionsAction::AddServiceValues(
+            (0..50).collect(),
+        ));
+    let mut blockchain = create_blockchain_with_config(
+        runtime,
+        config,
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    execute_transaction(
+        &mut blockchain,
+        Transaction::AddValue(10).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect("Transaction must succeed");
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let records = schema.call_records(schema.height()).unwrap();
+    let err = records
+        .get(CallInBlock::after_transactions(TEST_SERVICE_ID))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded).with_any_description()
+    );
+
+    let service_data = snapshot.for_service(TEST_SERVICE_NAME).unwrap();
+    assert_eq!(
+        InspectorSchema::new(service_data).values.len(),
+        1,
+        "Changes made by the failed hook should be discarded"
+    );
+}
+
+#[test]
+fn events_are_metered() {
+    let keys = KeyPair::random();
+    let (config, _) = ConsensusConfig::for_tests(1);
+    let config = ConsensusConfig {
+        max_call_writes: Some(20),
+        ..config
+    };
+    let mut blockchain = create_blockchain_with_config(
+        RuntimeInspector::default(),
+        config,
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    let payloads = vec![vec![1], vec![2, 3]];
+    execute_transaction(
+        &mut blockchain,
+        Transaction::EmitEvents(payloads.clone()).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect("Transaction within limits must succeed");
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let records = schema.call_records(schema.height()).unwrap();
+    let events = records.get_events(CallInBlock::transaction(0));
+    let emitted_payloads: Vec<_> = events.into_iter().map(|event| event.payload).collect();
+    assert_eq!(emitted_payloads, payloads);
+
+    // Each event is charged as a write.
+    let payloads = vec![vec![]; 50];
+    let err = execute_transaction(
+        &mut blockchain,
+        Transaction::EmitEvents(payloads).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect_err("Transaction exceeding limits must fail");
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded).with_any_description()
+    );
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    let records = schema.call_records(schema.height()).unwrap();
+    assert!(records.get_events(CallInBlock::transaction(0)).is_empty());
+}
+
+#[test]
+fn simulating_tx() {
+    let keys = KeyPair::random();
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+    execute_transaction(
+        &mut blockchain,
+        Transaction::AddServiceValues(vec![1]).sign(TEST_SERVICE_ID, &keys),
+    )
+    .unwrap();
+
+    let tx = Transaction::AddServiceValues(vec![2, 3]).sign(TEST_SERVICE_ID, &keys);
+    let result = blockchain.simulate(&Simulation::signed(&tx).with_diff());
+    result.status.0.expect("Simulated transaction must succeed");
+    let values_address = IndexAddress::from_root("test_service.values");
+    assert!(result.touched_indexes.contains(&values_address));
+
+    let diff = result.diff.unwrap();
+    let change = diff["test_service.values"];
+    let snapshot = blockchain.snapshot();
+    let values = InspectorSchema::new(snapshot.for_service(TEST_SERVICE_NAME).unwrap()).values;
+    assert_eq!(change.before, Some(values.object_hash()));
+    assert_ne!(change.after, change.before);
+    assert_eq!(
+        values.len(),
+        1,
+        "Simulation should not change the blockchain state"
+    );
+
+    let result = blockchain.simulate(&Simulation::signed(&tx).with_trace());
+    let trace = result.trace.expect("Trace was not recorded");
+    assert_eq!(trace.instance_id, TEST_SERVICE_ID);
+    assert_eq!(trace.instance_name, TEST_SERVICE_NAME);
+    assert_eq!(
+        trace.caller,
+        Caller::Transaction {
+            author: keys.public_key()
+        }
+    );
+    assert_eq!(trace.arguments, tx.payload().arguments);
+    trace.status.0.expect("Traced call must succeed");
+    assert!(trace.nested_calls.is_empty());
+    assert!(trace
+        .writes
+        .iter()
+        .any(|key| key.index == "test_service.values"));
+
+    // The test runtime does not allow to simulate unsigned transactions.
+    let simulation = Simulation::unsigned(tx.payload().to_owned(), keys.public_key());
+    let err = blockchain.simulate(&simulation).status.0.unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CommonError::FeatureNotSupported).with_any_description()
+    );
+}
+
+#[test]
+fn simulating_tx_after_block_prologue() {
+    let keys = KeyPair::random();
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+    let tx = Transaction::Schedule(Height(2), Box::new(Transaction::AddServiceValues(vec![5])));
+    execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys)).unwrap();
+
+    // The simulation is performed from another thread, as it is done by the node.
+    let tx = Transaction::AddServiceValues(vec![7]).sign(TEST_SERVICE_ID, &keys);
+    let simulator = blockchain.simulator();
+    let simulation = Simulation::signed(&tx).with_diff();
+    let result = thread::spawn(move || simulator.simulate(&simulation))
+        .join()
+        .unwrap();
+    result.status.0.expect("Simulated transaction must succeed");
+
+    // The scheduled call is executed before the simulated transaction, but its changes
+    // are not included into the diff.
+    let change = result.diff.unwrap()["test_service.values"];
+    assert!(change.before.is_some());
+    let snapshot = blockchain.snapshot();
+    let values = InspectorSchema::new(snapshot.for_service(TEST_SERVICE_NAME).unwrap()).values;
+    assert!(values.is_empty());
+
+    // The simulation result matches the real execution.
+    execute_transaction(&mut blockchain, tx).unwrap();
+    let snapshot = blockchain.snapshot();
+    let values = InspectorSchema::new(snapshot.for_service(TEST_SERVICE_NAME).unwrap()).values;
+    assert_eq!(values.iter().collect::<Vec<_>>(), vec![5, 7]);
+    assert_eq!(change.after, Some(values.object_hash()));
+}
+
+#[test]
+fn simulations_are_limited_by_default() {
+    let keys = KeyPair::random();
+    let blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    // The consensus config does not limit writes, but simulations are limited
+    // to 100,000 writes by default. Each added value results in at least one write.
+    let values = (0..100_001).collect();
+    let tx = Transaction::AddServiceValues(values).sign(TEST_SERVICE_ID, &keys);
+    let err = blockchain
+        .simulate(&Simulation::signed(&tx))
+        .status
+        .0
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded)
+            .with_description_containing("storage writes")
+    );
+}
+
+#[test]
+fn query_to_runtime_without_query_support() {
+    let blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    let err = blockchain
+        .query(&Query::new(TEST_SERVICE_ID, 0, vec![]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CommonError::FeatureNotSupported).with_any_description()
+    );
+
+    let err = blockchain
+        .query(&Query::new(TEST_SERVICE_ID + 1, 0, vec![]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::IncorrectInstanceId).with_any_description()
+    );
+}
+
+#[test]
+fn executing_scheduled_calls() {
+    let keys = KeyPair::random();
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    // Transactions are executed in blocks #1..#4; scheduled calls are executed in block #4.
+    let target_height = Height(4);
+    let scheduled_txs = vec![
+        Transaction::AddValue(5),
+        Transaction::ExecutionError(1, "Scheduled error".to_owned()),
+    ];
+    for tx in scheduled_txs {
+        let tx = Transaction::Schedule(target_height, Box::new(tx));
+        execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys))
+            .expect("Cannot schedule call");
+    }
+    // Calls cannot be scheduled in the past.
+    let tx = Transaction::Schedule(Height(1), Box::new(Transaction::AddValue(1)));
+    let err = execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys)).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::IncorrectScheduledHeight).with_any_description()
+    );
+
+    let snapshot = blockchain.snapshot();
+    assert_eq!(Schema::new(&snapshot).scheduled_calls().keys().count(), 2);
+
+    // Block #4 executes the scheduled calls before the transaction.
+    execute_transaction(
+        &mut blockchain,
+        Transaction::AddValue(10).sign(TEST_SERVICE_ID, &keys),
+    )
+    .unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let values = InspectorSchema::new(&snapshot).values;
+    assert_eq!(values.iter().collect::<Vec<_>>(), vec![5, 10]);
+
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.scheduled_calls().keys().count(), 0);
+    let records = schema.call_records(target_height).unwrap();
+    records.get(CallInBlock::scheduled_call(0)).unwrap();
+    let err = records.get(CallInBlock::scheduled_call(1)).unwrap_err();
+    assert_eq!(err.description(), "Scheduled error");
+
+    // Executed calls cannot be cancelled.
+    let err = execute_transaction(
+        &mut blockchain,
+        Transaction::CancelScheduled(0).sign(TEST_SERVICE_ID, &keys),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::UnknownScheduledCall).with_any_description()
+    );
+}
+
+#[test]
+fn scheduled_calls_are_limited() {
+    let keys = KeyPair::random();
+    let (config, _) = ConsensusConfig::for_tests(1);
+    let config = ConsensusConfig {
+        max_call_writes: Some(20),
+        ..config
+    };
+    let mut blockchain = create_blockchain_with_config(
+        RuntimeInspector::default(),
+        config,
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    // Each scheduled call is charged as a write.
+    let tx = Transaction::ScheduleMany(Height(10), 50);
+    let err = execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys)).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded).with_any_description()
+    );
+    let snapshot = blockchain.snapshot();
+    assert_eq!(Schema::new(&snapshot).scheduled_calls().keys().count(), 0);
+
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+    let max_calls = ExecutionContext::MAX_SCHEDULED_CALLS_PER_HEIGHT;
+    let tx = Transaction::ScheduleMany(Height(10), max_calls);
+    execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys))
+        .expect("Cannot schedule calls");
+
+    // No more calls can be scheduled at the same height...
+    let tx = Transaction::Schedule(Height(10), Box::new(Transaction::AddValue(1)));
+    let err = execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys)).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::TooManyScheduledCalls).with_any_description()
+    );
+    // ...but they can be scheduled at other heights.
+    let tx = Transaction::Schedule(Height(11), Box::new(Transaction::AddValue(1)));
+    execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys))
+        .expect("Cannot schedule call");
+
+    // Cancelling a call frees up space at its height.
+    execute_transaction(
+        &mut blockchain,
+        Transaction::CancelScheduled(0).sign(TEST_SERVICE_ID, &keys),
+    )
+    .unwrap();
+    let tx = Transaction::Schedule(Height(10), Box::new(Transaction::AddValue(1)));
+    execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys))
+        .expect("Cannot schedule call");
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.scheduled_calls_count(Height(10)), max_calls);
+    assert_eq!(schema.scheduled_calls_count(Height(11)), 1);
+}
+
+#[test]
+fn cancelling_scheduled_call() {
+    let keys = KeyPair::random();
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    let tx = Transaction::Schedule(Height(3), Box::new(Transaction::AddValue(5)));
+    execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys)).unwrap();
+    execute_transaction(
+        &mut blockchain,
+        Transaction::CancelScheduled(0).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect("Cannot cancel scheduled call");
+    execute_transaction(
+        &mut blockchain,
+        Transaction::AddValue(10).sign(TEST_SERVICE_ID, &keys),
+    )
+    .unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let values = InspectorSchema::new(&snapshot).values;
+    assert_eq!(values.iter().collect::<Vec<_>>(), vec![10]);
+    assert_eq!(Schema::new(&snapshot).scheduled_calls().keys().count(), 0);
+}
+
+#[test]
+fn executing_multisig_tx() {
+    let keys: Vec<_> = (0..3).map(|_| KeyPair::random()).collect();
+    let public_keys: Vec<_> = keys.iter().map(KeyPair::public_key).collect();
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+
+    let tx = Transaction::RequireSigners(2, public_keys.clone());
+    let err = execute_transaction(&mut blockchain, tx.clone().sign(TEST_SERVICE_ID, &keys[0]))
+        .unwrap_err();
+    assert_eq!(err.description(), "Not enough signatures");
+
+    let tx = AnyTx::new(CallInfo::new(TEST_SERVICE_ID, 0), tx.into_bytes())
+        .sign_with_cosigners(&keys[2], &keys[..1]);
+    let mut signers = vec![public_keys[0], public_keys[2]];
+    signers.sort();
+    assert_eq!(
+        Caller::for_transaction(&tx),
+        Caller::Multisig {
+            signers: signers.clone()
+        }
+    );
+    execute_transaction(&mut blockchain, tx).expect("Multisig transaction must succeed");
+
+    let caller = Caller::Multisig { signers };
+    assert_eq!(
+        Caller::from_bytes(caller.to_bytes().into()).unwrap(),
+        caller
+    );
+    assert_eq!(caller.author(), None);
+}
+
+#[test]
+fn exporting_and_restoring_genesis() {
+    let keys = KeyPair::random();
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance()],
+    );
+    execute_transaction(
+        &mut blockchain,
+        Transaction::AddServiceValues(vec![1, 2, 3]).sign(TEST_SERVICE_ID, &keys),
+    )
+    .expect("Transaction must succeed");
+
+    let snapshot = blockchain.snapshot();
+    let height = Schema::new(&snapshot).height();
+    let err = export_genesis(snapshot.as_ref(), height.next()).unwrap_err();
+    assert!(err.to_string().contains("latest committed height"));
+
+    let (genesis_config, genesis_state) = export_genesis(snapshot.as_ref(), height).unwrap();
+    assert_eq!(genesis_config.artifacts.len(), 1);
+    assert_eq!(genesis_config.builtin_instances.len(), 1);
+    assert_eq!(genesis_state.source_height, height);
+    assert_eq!(genesis_state.indexes.len(), 1);
+    let json = serde_json::to_string(&genesis_state).unwrap();
+    let genesis_state: GenesisState = serde_json::from_str(&json).unwrap();
+
+    // The exported instance has no constructor arguments, on which the service constructor
+    // would fail. Thus, the blockchain is created only if the service data is restored
+    // instead of being initialized.
+    let new_blockchain = Blockchain::build_for_tests()
+        .into_mut(genesis_config)
+        .with_genesis_state(genesis_state)
+        .with_runtime(RuntimeInspector::default())
+        .build();
+    let new_snapshot = new_blockchain.snapshot();
+    let instance = DispatcherSchema::new(&new_snapshot)
+        .get_instance(TEST_SERVICE_ID)
+        .unwrap();
+    assert_eq!(instance.status, Some(InstanceStatus::Active));
+
+    let old_values = InspectorSchema::new(snapshot.for_service(TEST_SERVICE_NAME).unwrap()).values;
+    let new_values =
+        InspectorSchema::new(new_snapshot.for_service(TEST_SERVICE_NAME).unwrap()).values;
+    assert_eq!(new_values.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(new_values.object_hash(), old_values.object_hash());
+}
+
+#[test]
+fn exporting_genesis_with_overlapping_service_names() {
+    const OTHER_SERVICE_ID: InstanceId = TEST_SERVICE_ID + 1;
+    const OTHER_SERVICE_NAME: &str = "test_service.v2";
+
+    let keys = KeyPair::random();
+    let other_instance = InstanceInitParams::new(
+        OTHER_SERVICE_ID,
+        OTHER_SERVICE_NAME,
+        RuntimeInspector::default_artifact_id(),
+        InitAction::Noop,
+    );
+    let mut blockchain = create_blockchain(
+        RuntimeInspector::default(),
+        vec![InitAction::Noop.into_default_instance(), other_instance],
+    );
+    let tx = Transaction::AddServiceValues(vec![1, 2]).sign(TEST_SERVICE_ID, &keys);
+    execute_transaction(&mut blockchain, tx).unwrap();
+    let tx = Transaction::AddServiceValues(vec![3]).sign(OTHER_SERVICE_ID, &keys);
+    execute_transaction(&mut blockchain, tx).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let height = Schema::new(&snapshot).height();
+    let (genesis_config, genesis_state) = export_genesis(snapshot.as_ref(), height).unwrap();
+    // Each index is exported once, although `test_service.v2.values` starts
+    // with `test_service.`.
+    let mut index_names: Vec<_> = genesis_state
+        .indexes
+        .iter()
+        .map(|dump| dump.address.name())
+        .collect();
+    index_names.sort();
+    assert_eq!(
+        index_names,
+        vec!["test_service.v2.values", "test_service.values"]
+    );
+
+    let new_blockchain = Blockchain::build_for_tests()
+        .into_mut(genesis_config)
+        .with_genesis_state(genesis_state)
+        .with_runtime(RuntimeInspector::default())
+        .build();
+    let new_snapshot = new_blockchain.snapshot();
+    let values = |name: &str| {
+        let schema = InspectorSchema::new(new_snapshot.for_service(name).unwrap());
+        schema.values.iter().collect::<Vec<_>>()
+    };
+    assert_eq!(values(TEST_SERVICE_NAME), vec![1, 2]);
+    assert_eq!(values(OTHER_SERVICE_NAME), vec![3]);
+}
+
+#[test]
+fn replaying_blocks() {
+    let keys = KeyPair::random();
+    let (config, _) = ConsensusConfig::for_tests(1);
+    let create_blockchain = |config: &ConsensusConfig| {
+        create_blockchain_with_config(
+            RuntimeInspector::default(),
+            config.clone(),
+            vec![InitAction::Noop.into_default_instance()],
+        )
+    };
+
+    let mut blockchain = create_blockchain(&config);
+    let transactions = vec![
+        Transaction::AddValue(1),
+        Transaction::ExecutionError(0, "Service error".to_owned()),
+        Transaction::AddServiceValues(vec![2, 3]),
+    ];
+    for tx in transactions {
+        execute_transaction(&mut blockchain, tx.sign(TEST_SERVICE_ID, &keys)).ok();
+    }
+    let snapshot = blockchain.snapshot();
+    let height = Schema::new(&snapshot).height();
+
+    let mut target = create_blockchain(&config);
+    let replayed_height = replay_blocks(snapshot.as_ref(), &mut target).unwrap();
+    assert_eq!(replayed_height, height);
+    assert_eq!(
+        target.as_ref().last_block(),
+        blockchain.as_ref().last_block()
+    );
+    // Replaying the same blocks once again is a no-op.
+    assert_eq!(
+        replay_blocks(snapshot.as_ref(), &mut target).unwrap(),
+        height
+    );
+
+    // Genesis blocks with different consensus configs do not match.
+    let (other_config, _) = ConsensusConfig::for_tests(1);
+    let mut target = create_blockchain(&other_config);
+    let err = replay_blocks(snapshot.as_ref(), &mut target).unwrap_err();
+    match err {
+        ReplayError::Mismatch(mismatch) => {
+            assert_eq!(mismatch.height, Height(0));
+            assert_ne!(mismatch.expected.state_hash, mismatch.actual.state_hash);
+            assert!(mismatch.calls.is_empty());
+        }
+        other => panic!("Unexpected error: {}", other),
+    }
+
+    // Tamper with the transaction stored in the source database.
+    let tx_hash = Schema::new(&snapshot)
+        .block_transactions(Height(1))
+        .get(0)
+        .unwrap();
+    let fork = blockchain.fork();
+    Schema::new(&fork)
+        .transactions()
+        .put(&tx_hash, Transaction::Panic.sign(TEST_SERVICE_ID, &keys));
+    blockchain.merge(fork.into_patch()).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let mut target = create_blockchain(&config);
+    let err = replay_blocks(snapshot.as_ref(), &mut target).unwrap_err();
+    let err_string = err.to_string();
+    match err {
+        ReplayError::Mismatch(mismatch) => {
+            assert_eq!(mismatch.height, Height(1));
+            assert_eq!(mismatch.expected.tx_hash, mismatch.actual.tx_hash);
+            assert_ne!(mismatch.expected.state_hash, mismatch.actual.state_hash);
+            assert_ne!(mismatch.expected.error_hash, mismatch.actual.error_hash);
+
+            let call = CallInBlock::transaction(0);
+            let (expected, actual) = &mismatch.calls[&call];
+            assert!(expected.is_none());
+            assert_eq!(actual.as_ref().unwrap().description(), PANIC_STR);
+        }
+        other => panic!("Unexpected error: {}", other),
+    }
+    assert!(err_string.contains("at height 1"));
+    assert!(err_string.contains("state_hash: expected"));
+    assert!(err_string.contains("result of transaction #1: expected success"));
+    // The mismatched block is not committed.
+    assert_eq!(target.as_ref().last_block().height, Height(0));
+}
+
+#[test]
+fn tracing_committed_tx() {
+    let keys = KeyPair::random();
+    let (config, _) = ConsensusConfig::for_tests(1);
+    let create_blockchain = |config: &ConsensusConfig| {
+        create_blockchain_with_config(
+            RuntimeInspector::default(),
+            config.clone(),
+            vec![InitAction::Noop.into_default_instance()],
+        )
+    };
+
+    let mut blockchain = create_blockchain(&config);
+    let transactions = vec![
+        Transaction::AddServiceValues(vec![1]),
+        Transaction::AddServiceValuesAndFail(vec![2], 5),
+        Transaction::AddServiceValues(vec![3]),
+    ];
+    let tx_hashes: Vec<_> = transactions
+        .into_iter()
+        .map(|tx| {
+            let tx = tx.sign(TEST_SERVICE_ID, &keys);
+            let tx_hash = tx.object_hash();
+            execute_transaction(&mut blockchain, tx).ok();
+            tx_hash
+        })
+        .collect();
+    let snapshot = blockchain.snapshot();
+
+    let mut target = create_blockchain(&config);
+    let result = trace_transaction(snapshot.as_ref(), &mut target, tx_hashes[1]).unwrap();
+    let err = result.status.0.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 5 });
+    let trace = result.trace.unwrap();
+    assert_eq!(trace.instance_id, TEST_SERVICE_ID);
+    assert!(trace
+        .writes
+        .iter()
+        .any(|key| key.index == "test_service.values"));
+    // Only the blocks preceding the transaction block are committed.
+    assert_eq!(target.as_ref().last_block().height, Height(1));
+
+    let mut target = create_blockchain(&config);
+    let unknown_hash = Transaction::AddValue(1)
+        .sign(TEST_SERVICE_ID, &keys)
+        .object_hash();
+    let err = trace_transaction(snapshot.as_ref(), &mut target, unknown_hash).unwrap_err();
+    assert_matches!(err, TraceError::NotCommitted(hash) if hash == unknown_hash);
+}
+
 #[test]
 #[should_panic]
 fn handling_tx_merkledb_error() {
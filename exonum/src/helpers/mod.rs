@@ -122,3 +122,22 @@ pub mod pb_version {
         value.to_string()
     }
 }
+
+/// Module for serializing `semver::VersionReq` to Protobuf. It can be used with
+/// `ProtobufConvert` derive macro in the same way as [`pb_version`].
+///
+/// [`pb_version`]: pb_version/index.html
+pub mod pb_version_req {
+    use semver::VersionReq;
+
+    /// Deserializes `semver::VersionReq` from string.
+    #[allow(clippy::needless_pass_by_value)] // False positive, we need a `String` type here.
+    pub fn from_pb(pb: String) -> anyhow::Result<VersionReq> {
+        pb.parse().map_err(From::from)
+    }
+
+    /// Serializes `semver::VersionReq` to string.
+    pub fn to_pb(value: &VersionReq) -> String {
+        value.to_string()
+    }
+}
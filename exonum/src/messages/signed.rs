@@ -24,10 +24,11 @@ use std::{
     borrow::Cow,
     convert::{TryFrom, TryInto},
     fmt::{self, Debug},
+    iter,
 };
 
 use crate::{
-    crypto::{self, Hash, PublicKey, SecretKey},
+    crypto::{self, Hash, KeyPair, PublicKey, SecretKey, Signature},
     messages::types::SignedMessage,
     proto::schema,
 };
@@ -38,15 +39,116 @@ impl SignedMessage {
     where
         T: TryFrom<Self>,
     {
+        self.check_signers()?;
         // Verifies message signature
+        let data = self.signed_data();
         ensure!(
-            crypto::verify(&self.signature, &self.payload, &self.author),
+            crypto::verify(&self.signature, &data, &self.author),
             "Failed to verify signature."
         );
-        // Deserializes message.
+        for cosignature in &self.cosignatures {
+            let public_key = &cosignature.public_key;
+            ensure!(
+                crypto::verify(&cosignature.signature, &data, public_key),
+                "Failed to verify cosignature of {:?}.",
+                public_key
+            );
+        }
+        self.decode()
+    }
+
+    /// Verifies signatures of several messages and returns the corresponding checked messages.
+    ///
+    /// Signatures of all messages (including cosignatures) are verified in a single batch,
+    /// which is considerably faster than verifying messages one by one. Signers of each message
+    /// are checked beforehand; messages failing the check are not included into the batch.
+    /// If the batch fails, messages are verified individually to find the invalid ones,
+    /// so the result for each message is the same as the result of [`into_verified`].
+    ///
+    /// [`into_verified`]: #method.into_verified
+    pub fn into_verified_batch<T>(messages: Vec<Self>) -> Vec<anyhow::Result<Verified<T>>>
+    where
+        T: TryFrom<Self>,
+    {
+        let messages: Vec<_> = messages
+            .into_iter()
+            .map(|message| message.check_signers().map(|()| message))
+            .collect();
+        let signed_data: Vec<_> = messages
+            .iter()
+            .map(|message| message.as_ref().ok().map(Self::signed_data))
+            .collect();
+        let batch: Vec<_> = messages
+            .iter()
+            .zip(&signed_data)
+            .filter_map(|(message, data)| Some((message.as_ref().ok()?, data.as_deref()?)))
+            .flat_map(|(message, data)| message.signatures(data))
+            .collect();
+        if crypto::verify_batch(&batch) {
+            messages
+                .into_iter()
+                .map(|message| message?.decode())
+                .collect()
+        } else {
+            messages
+                .into_iter()
+                .map(|message| message?.into_verified())
+                .collect()
+        }
+    }
+
+    /// Returns all signatures of the message together with the signed data and public keys
+    /// of the signers.
+    fn signatures<'a>(
+        &'a self,
+        data: &'a [u8],
+    ) -> impl Iterator<Item = (&'a Signature, &'a [u8], &'a PublicKey)> + 'a {
+        let cosignatures = self
+            .cosignatures
+            .iter()
+            .map(move |cosig| (&cosig.signature, data, &cosig.public_key));
+        iter::once((&self.signature, data, &self.author)).chain(cosignatures)
+    }
+
+    /// Checks that the number of cosignatures does not exceed the limit, cosignatures
+    /// are strictly sorted by the public key, and the author does not cosign the message.
+    /// The latter two conditions ensure that each signer signs the message only once
+    /// and that the set of signers has a single encoding.
+    fn check_signers(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.cosignatures.len() <= Self::MAX_COSIGNATURES,
+            "Message has {} cosignatures, while the maximum is {}.",
+            self.cosignatures.len(),
+            Self::MAX_COSIGNATURES
+        );
+        for cosignature in &self.cosignatures {
+            ensure!(
+                cosignature.public_key != self.author,
+                "Message is signed by {:?} several times.",
+                self.author
+            );
+        }
+        for pair in self.cosignatures.windows(2) {
+            ensure!(
+                pair[0].public_key != pair[1].public_key,
+                "Message is signed by {:?} several times.",
+                pair[0].public_key
+            );
+            ensure!(
+                pair[0].public_key < pair[1].public_key,
+                "Cosignatures are not sorted by the public key."
+            );
+        }
+        Ok(())
+    }
+
+    /// Deserializes the message payload. Signatures are assumed to be verified.
+    fn decode<T>(self) -> anyhow::Result<Verified<T>>
+    where
+        T: TryFrom<Self>,
+    {
         let inner = T::try_from(self.clone())
             .map_err(|_| anyhow::format_err!("Failed to decode message from payload."))?;
-
         Ok(Verified { raw: self, inner })
     }
 }
@@ -101,6 +203,13 @@ impl<T> Verified<T> {
         self.raw.author
     }
 
+    /// Returns public keys of all signers of the message: the author, followed by cosigners
+    /// sorted by their public keys.
+    pub fn signers(&self) -> Vec<PublicKey> {
+        let cosigners = self.raw.cosignatures.iter().map(|cosig| cosig.public_key);
+        std::iter::once(self.raw.author).chain(cosigners).collect()
+    }
+
     /// Downcasts this message to a more specific type. This is only appropriate if the target
     /// type retains all information about the message.
     pub fn downcast_map<U>(self, map_fn: impl FnOnce(T) -> U) -> Verified<U>
@@ -155,6 +264,25 @@ where
         };
         Self { raw, inner }
     }
+
+    /// Signs the specified value by the author and cosigners and creates a new verified
+    /// message from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`SignedMessage::with_cosigners()`].
+    ///
+    /// [`SignedMessage::with_cosigners()`]: struct.SignedMessage.html#method.with_cosigners
+    pub fn from_value_with_cosigners(inner: T, author: &KeyPair, cosigners: &[KeyPair]) -> Self {
+        let container: T::Container = inner.into();
+        let raw = SignedMessage::with_cosigners(container.to_bytes(), author, cosigners);
+        let inner: T = if let Ok(inner) = container.try_into() {
+            inner
+        } else {
+            unreachable!("We can safely convert `ExonumMessage` back to the inner type.")
+        };
+        Self { raw, inner }
+    }
 }
 
 impl<'de, T> Deserialize<'de> for Verified<T>
@@ -256,7 +384,7 @@ mod tests {
     use super::*;
     use crate::{
         helpers::{Height, Round, ValidatorId},
-        messages::Precommit,
+        messages::{Cosignature, Precommit},
         runtime::{AnyTx, CallInfo},
     };
 
@@ -282,6 +410,131 @@ mod tests {
         assert_eq!(msg, from_pb);
     }
 
+    #[test]
+    fn test_verified_any_tx_with_cosignatures() {
+        let author = crypto::KeyPair::random();
+        let cosigner = crypto::KeyPair::random();
+
+        let msg = AnyTx::new(CallInfo::new(5, 2), vec![1, 2, 3, 4])
+            .sign_with_cosigners(&author, &[cosigner.clone()]);
+        assert_eq!(
+            msg.signers(),
+            vec![author.public_key(), cosigner.public_key()]
+        );
+
+        let raw = msg.clone().into_raw();
+        let verified = raw.into_verified::<AnyTx>().unwrap();
+        assert_eq!(verified, msg);
+
+        // The author cannot cosign the message.
+        let mut raw = msg.clone().into_raw();
+        raw.cosignatures[0].public_key = author.public_key();
+        let err = raw.into_verified::<AnyTx>().unwrap_err();
+        assert!(err.to_string().contains("signed by"), "{}", err);
+
+        // Cosignature not matching the key is rejected.
+        let other_keypair = crypto::KeyPair::random();
+        let mut raw = msg.clone().into_raw();
+        raw.cosignatures[0].public_key = other_keypair.public_key();
+        let err = raw.into_verified::<AnyTx>().unwrap_err();
+        assert_eq!(err.to_string(), "Failed to verify signature.");
+
+        // Cosignatures cannot be stripped since signatures cover the set of signers.
+        let mut raw = msg.into_raw();
+        raw.cosignatures.clear();
+        let err = raw.into_verified::<AnyTx>().unwrap_err();
+        assert_eq!(err.to_string(), "Failed to verify signature.");
+    }
+
+    #[test]
+    fn test_cosignatures_order_and_limit() {
+        let author = crypto::KeyPair::random();
+        let cosigners: Vec<_> = (0..3).map(|_| crypto::KeyPair::random()).collect();
+        let msg = AnyTx::new(CallInfo::new(5, 2), vec![1, 2, 3, 4])
+            .sign_with_cosigners(&author, &cosigners);
+        let raw = msg.into_raw();
+        let keys: Vec<_> = raw.cosignatures.iter().map(|c| c.public_key).collect();
+        let mut sorted_keys = keys.clone();
+        sorted_keys.sort();
+        assert_eq!(keys, sorted_keys);
+
+        // Cosignatures in the wrong order are rejected even if the signatures are valid.
+        let mut unsorted = raw.clone();
+        unsorted.cosignatures.swap(0, 2);
+        let err = unsorted.into_verified::<AnyTx>().unwrap_err();
+        assert!(err.to_string().contains("not sorted"), "{}", err);
+
+        // Duplicate cosignatures are rejected.
+        let mut duplicated = raw;
+        let cosignature = duplicated.cosignatures[0].clone();
+        duplicated.cosignatures.insert(0, cosignature);
+        let err = duplicated.into_verified::<AnyTx>().unwrap_err();
+        assert!(err.to_string().contains("signed by"), "{}", err);
+
+        // The number of cosignatures is limited.
+        let cosigners: Vec<_> = (0..=SignedMessage::MAX_COSIGNATURES)
+            .map(|_| crypto::KeyPair::random())
+            .collect();
+        let payload = AnyTx::new(CallInfo::new(5, 2), vec![1, 2, 3, 4]);
+        let mut raw = SignedMessage::with_cosigners(payload, &author, &cosigners[1..]);
+        raw.cosignatures.push(Cosignature {
+            public_key: cosigners[0].public_key(),
+            signature: Signature::zero(),
+        });
+        let err = raw.into_verified::<AnyTx>().unwrap_err();
+        assert!(err.to_string().contains("maximum"), "{}", err);
+    }
+
+    #[test]
+    fn test_verified_batch() {
+        let messages: Vec<_> = (0..8)
+            .map(|i| {
+                let keypair = crypto::KeyPair::random();
+                AnyTx::new(CallInfo::new(5, 2), vec![i; 4]).sign_with_keypair(&keypair)
+            })
+            .collect();
+        let cosigner = crypto::KeyPair::random();
+        let cosigned = AnyTx::new(CallInfo::new(5, 2), vec![8; 4])
+            .sign_with_cosigners(&crypto::KeyPair::random(), &[cosigner]);
+
+        let mut raw: Vec<_> = messages.iter().cloned().map(Verified::into_raw).collect();
+        raw.push(cosigned.clone().into_raw());
+        let verified = SignedMessage::into_verified_batch::<AnyTx>(raw.clone());
+        let verified: Vec<_> = verified.into_iter().map(Result::unwrap).collect();
+        assert_eq!(verified[..8], messages[..]);
+        assert_eq!(verified[8], cosigned);
+
+        // Invalid signatures are found by the fallback verification.
+        raw[2].signature = Signature::zero();
+        raw[5].payload = vec![1, 2, 3];
+        raw[8].cosignatures[0].public_key = raw[8].author;
+        let verified = SignedMessage::into_verified_batch::<AnyTx>(raw);
+        for (i, res) in verified.into_iter().enumerate() {
+            match i {
+                2 | 5 => assert_eq!(res.unwrap_err().to_string(), "Failed to verify signature."),
+                8 => assert!(res.unwrap_err().to_string().contains("signed by")),
+                i => assert_eq!(res.unwrap(), messages[i]),
+            }
+        }
+
+        // Signers are checked before the batch verification.
+        let cosigners: Vec<_> = (0..=SignedMessage::MAX_COSIGNATURES)
+            .map(|_| crypto::KeyPair::random())
+            .collect();
+        let payload = AnyTx::new(CallInfo::new(5, 2), vec![1, 2, 3, 4]);
+        let mut overloaded =
+            SignedMessage::with_cosigners(payload, &crypto::KeyPair::random(), &cosigners[1..]);
+        overloaded.cosignatures.push(Cosignature {
+            public_key: cosigners[0].public_key(),
+            signature: Signature::zero(),
+        });
+        let raw = vec![messages[0].clone().into_raw(), overloaded];
+        let verified = SignedMessage::into_verified_batch::<AnyTx>(raw);
+        assert_eq!(*verified[0].as_ref().unwrap(), messages[0]);
+        let err = verified[1].as_ref().unwrap_err();
+        assert!(err.to_string().contains("maximum"), "{}", err);
+    }
+
     #[test]
     #[should_panic(expected = "Failed to verify signature.")]
     fn test_precommit_serde_wrong_signature() {
@@ -19,10 +19,10 @@ use exonum_derive::{BinaryValue, ObjectHash};
 use exonum_merkledb::BinaryValue;
 use exonum_proto::ProtobufConvert;
 
-use std::convert::TryFrom;
+use std::{borrow::Cow, convert::TryFrom};
 
 use crate::{
-    crypto::{self, Hash, PublicKey, SecretKey, Signature},
+    crypto::{self, Hash, KeyPair, PublicKey, SecretKey, Signature},
     helpers::{Height, Round, ValidatorId},
     proto::schema::messages,
 };
@@ -38,10 +38,22 @@ pub struct SignedMessage {
     pub payload: Vec<u8>,
     /// `PublicKey` of the author of the message.
     pub author: PublicKey,
-    /// Digital signature over `payload` created with the secret key of the author of the message.
+    /// Digital signature over [signed data] created with the secret key of the author
+    /// of the message. For messages without cosignatures, the signed data is `payload`.
+    ///
+    /// [signed data]: #method.signed_data
     pub signature: Signature,
+    /// Additional signatures over [signed data] created by other parties, sorted
+    /// by the public key of the cosigner. Empty for messages with a single signer.
+    ///
+    /// [signed data]: #method.signed_data
+    pub cosignatures: Vec<Cosignature>,
 }
 
+/// Prefix of the data signed by signers of messages with cosignatures. The prefix separates
+/// such data from the payloads of messages signed by a single key.
+const MULTISIG_PREFIX: &[u8] = b"exonum.multisig:";
+
 impl SignedMessage {
     /// Creates a new signed message from the given binary value.
     pub fn new(payload: impl BinaryValue, author: PublicKey, secret_key: &SecretKey) -> Self {
@@ -51,8 +63,114 @@ impl SignedMessage {
             payload,
             author,
             signature,
+            cosignatures: vec![],
         }
     }
+
+    /// Maximum number of cosignatures in a message.
+    pub const MAX_COSIGNATURES: usize = 15;
+
+    /// Creates a new message from the given binary value signed by the author and cosigners.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of cosigners exceeds [`MAX_COSIGNATURES`], or if any key
+    /// is specified more than once.
+    ///
+    /// [`MAX_COSIGNATURES`]: #associatedconstant.MAX_COSIGNATURES
+    pub fn with_cosigners(
+        payload: impl BinaryValue,
+        author: &KeyPair,
+        cosigners: &[KeyPair],
+    ) -> Self {
+        assert!(
+            cosigners.len() <= Self::MAX_COSIGNATURES,
+            "Number of cosigners exceeds the maximum of {}",
+            Self::MAX_COSIGNATURES
+        );
+        let mut cosigners: Vec<_> = cosigners.iter().collect();
+        cosigners.sort_by_key(|keypair| keypair.public_key());
+        let cosigner_keys: Vec<_> = cosigners
+            .iter()
+            .map(|keypair| keypair.public_key())
+            .collect();
+        assert!(
+            cosigner_keys.windows(2).all(|keys| keys[0] != keys[1])
+                && !cosigner_keys.contains(&author.public_key()),
+            "Message cannot be signed by the same key several times"
+        );
+
+        let payload = payload.into_bytes();
+        let data = Self::multisig_data(&payload, author.public_key(), &cosigner_keys);
+        let signature = crypto::sign(&data, author.secret_key());
+        let cosignatures = cosigners
+            .into_iter()
+            .map(|keypair| Cosignature {
+                public_key: keypair.public_key(),
+                signature: crypto::sign(&data, keypair.secret_key()),
+            })
+            .collect();
+        Self {
+            payload,
+            author: author.public_key(),
+            signature,
+            cosignatures,
+        }
+    }
+
+    /// Returns data signed by each signer of a message with the specified payload, author
+    /// and cosigners. The data includes the keys of all signers, so that cosignatures cannot
+    /// be removed from the message or added to it without invalidating the other signatures.
+    ///
+    /// Cosigners must be sorted by their public keys. This method allows signers
+    /// of the message to sign it independently; the signatures can then be assembled
+    /// into a `SignedMessage`.
+    pub fn multisig_data(payload: &[u8], author: PublicKey, cosigners: &[PublicKey]) -> Vec<u8> {
+        let mut data = MULTISIG_PREFIX.to_vec();
+        data.extend_from_slice(&(cosigners.len() as u32).to_le_bytes());
+        data.extend_from_slice(author.as_ref());
+        for cosigner in cosigners {
+            data.extend_from_slice(cosigner.as_ref());
+        }
+        data.extend_from_slice(payload);
+        data
+    }
+
+    /// Returns data signed by the author and cosigners of the message. For messages without
+    /// cosignatures, this is the payload of the message; otherwise, the data is returned
+    /// by [`multisig_data`].
+    ///
+    /// [`multisig_data`]: #method.multisig_data
+    pub fn signed_data(&self) -> Cow<'_, [u8]> {
+        if self.cosignatures.is_empty() {
+            Cow::Borrowed(&self.payload)
+        } else {
+            let cosigners: Vec<_> = self
+                .cosignatures
+                .iter()
+                .map(|cosignature| cosignature.public_key)
+                .collect();
+            Cow::Owned(Self::multisig_data(&self.payload, self.author, &cosigners))
+        }
+    }
+}
+
+/// Additional signature over the payload of a `SignedMessage` together with the set of keys
+/// signing the message.
+///
+/// Messages with cosignatures are considered to be authorized by all signers; for transactions,
+/// this is reflected by the [`Caller::Multisig`] authorization.
+///
+/// [`Caller::Multisig`]: ../runtime/enum.Caller.html#variant.Multisig
+#[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Debug)]
+#[derive(ProtobufConvert)]
+#[protobuf_convert(source = "messages::Cosignature")]
+pub struct Cosignature {
+    /// Public key of the cosigner.
+    pub public_key: PublicKey,
+    /// Digital signature over the data signed by the message signers created with
+    /// the secret key of the cosigner.
+    pub signature: Signature,
 }
 
 /// Pre-commit for a block, essentially meaning that a validator node endorses the block.
@@ -15,7 +15,7 @@
 use exonum_merkledb::{
     access::{AsReadonly, FromAccess, Prefixed, RawAccess},
     generic::GenericRawAccess,
-    Snapshot, SystemSchema,
+    IndexInfo, Snapshot, SystemSchema,
 };
 
 use super::{
@@ -105,6 +105,31 @@ impl<T: RawAccess + AsReadonly> BlockchainData<T> {
         schema_for_service(self.access.as_readonly(), service_id)
     }
 
+    /// Returns information about all indexes of a service, such as their types, hashes
+    /// and approximate sizes. If the service with `id` does not exist or is not active,
+    /// returns `None`.
+    ///
+    /// Indexes are ordered by their names; indexes of the service data migration, if any,
+    /// are not included. See [`IndexInfo`] for more details on the returned information.
+    ///
+    /// [`IndexInfo`]: ../merkledb/struct.IndexInfo.html
+    pub fn service_indexes<'q>(&self, id: impl Into<InstanceQuery<'q>>) -> Option<Vec<IndexInfo>> {
+        let access = self.access.as_readonly();
+        let (_, spec) = mount_point_for_service(access.clone(), id)?;
+        let names: Vec<_> = DispatcherSchema::new(access).instances().keys().collect();
+
+        let prefix = [&spec.name, "."].concat();
+        let indexes = SystemSchema::new(self.access.clone())
+            .indexes_by_prefix(&prefix)
+            .into_iter()
+            .filter(|index| {
+                index_owner(index.address.name(), names.iter().map(String::as_str))
+                    == Some(spec.name.as_str())
+            })
+            .collect();
+        Some(indexes)
+    }
+
     /// Returns a mount point for the data of the executing service instance.
     /// Unlike other data, this one may be writeable provided that this `BlockchainData`
     /// wraps a `Fork`.
@@ -141,6 +166,22 @@ where
     }
 }
 
+/// Returns the name of the service instance owning the index with the specified name,
+/// or `None` if the index does not belong to any of the `instance_names`.
+///
+/// An index belongs to the instance with the longest name `name` such that the index name
+/// starts with `name.`. Thus, if there are instances `token` and `token.v2`, the index
+/// `token.v2.wallets` belongs to the latter instance rather than the former one.
+pub(crate) fn index_owner<'a>(
+    index_name: &str,
+    instance_names: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    instance_names
+        .into_iter()
+        .filter(|name| index_name.starts_with(name) && index_name[name.len()..].starts_with('.'))
+        .max_by_key(|name| name.len())
+}
+
 fn mount_point_for_service<'q, T: RawAccess>(
     access: T,
     id: impl Into<InstanceQuery<'q>>,
@@ -247,3 +288,22 @@ impl SnapshotExt for dyn Snapshot {
         schema_for_service(self, service_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::index_owner;
+
+    #[test]
+    fn index_owner_with_overlapping_names() {
+        let names = &["token", "token.v2", "tokens"];
+        let owner = |index_name| index_owner(index_name, names.iter().copied());
+
+        assert_eq!(owner("token.wallets"), Some("token"));
+        assert_eq!(owner("token.v2.wallets"), Some("token.v2"));
+        assert_eq!(owner("token.v2"), Some("token"));
+        assert_eq!(owner("tokens.wallets"), Some("tokens"));
+        assert_eq!(owner("token"), None);
+        assert_eq!(owner("tokenwallets"), None);
+        assert_eq!(owner("other.wallets"), None);
+    }
+}
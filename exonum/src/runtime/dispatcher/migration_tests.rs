@@ -63,6 +63,7 @@ impl Runtime for MigrationRuntime {
     fn is_supported(&self, feature: &RuntimeFeature) -> bool {
         match feature {
             RuntimeFeature::FreezingServices => true,
+            RuntimeFeature::UnsignedSimulation | RuntimeFeature::Queries => false,
         }
     }
 
@@ -252,7 +253,7 @@ impl Rig {
     }
 
     fn dispatcher(&mut self) -> &mut Dispatcher {
-        self.blockchain.dispatcher()
+        self.blockchain.dispatcher_mut()
     }
 
     fn migration_threads(&mut self) -> &HashMap<String, MigrationThread> {
@@ -297,7 +298,7 @@ impl Rig {
         let artifact = ArtifactId::from_raw_parts(MigrationRuntime::ID, name.into(), version);
 
         let fork = self.blockchain.fork();
-        Dispatcher::commit_artifact(&fork, &artifact, vec![]);
+        Dispatcher::commit_artifact(&fork, ArtifactSpec::new(artifact.clone(), ()));
         self.create_block(fork);
         artifact
     }
@@ -591,7 +592,7 @@ fn migration_immediate_errors() {
     );
 
     // Mark the artifact as pending.
-    Dispatcher::commit_artifact(&fork, &unknown_artifact, vec![]);
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(unknown_artifact.clone(), ()));
     let err = rig
         .dispatcher()
         .initiate_migration(&fork, unknown_artifact, &old_service.name)
@@ -31,24 +31,28 @@ use std::{
 };
 
 use crate::{
-    blockchain::{Blockchain, CallInBlock, Schema as CoreSchema},
+    blockchain::{
+        Blockchain, CallInBlock, ScheduledCall, Schema as CoreSchema, Simulation, SimulationResult,
+    },
     crypto::Hash,
-    helpers::ValidateInput,
+    helpers::{Height, ValidateInput},
     messages::{AnyTx, Verified},
     runtime::{
-        ArtifactStatus, CoreError, InstanceDescriptor, InstanceQuery, InstanceStatus,
-        RuntimeInstance,
+        ArtifactSpec, ArtifactStatus, Caller, CoreError, InstanceDescriptor, InstanceQuery,
+        InstanceStatus, RuntimeInstance,
     },
 };
 
 use self::schema::{ArtifactAction, MigrationTransition, ModifiedInstanceInfo};
 use super::{
     error::{CallSite, CallType, CommonError, ErrorKind, ExecutionError, ExecutionFail},
+    metering::{ResourceLimits, ResourceMeter},
     migrations::{
         InstanceMigration, MigrationContext, MigrationError, MigrationScript, MigrationStatus,
         MigrationType,
     },
-    ArtifactId, ExecutionContext, InstanceId, InstanceSpec, InstanceState, Runtime, RuntimeFeature,
+    ArtifactId, ExecutionContext, InstanceId, InstanceSpec, InstanceState, MethodId, Runtime,
+    RuntimeFeature,
 };
 use crate::runtime::RuntimeIdentifier;
 
@@ -306,6 +310,7 @@ impl Migrations {
 #[derive(Debug, Default)]
 pub struct TxCheckCache {
     service_states: HashMap<InstanceId, Option<InstanceStatus>>,
+    next_height: Option<Height>,
 }
 
 impl TxCheckCache {
@@ -325,11 +330,50 @@ impl TxCheckCache {
         CoreError::ServiceNotActive.with_description(msg)
     }
 
+    fn check_window_bounds(tx: &AnyTx) -> Result<(), ExecutionError> {
+        tx.validity.validate().map_err(|e| {
+            let msg = format!("Invalid transaction validity window: {}", e);
+            CoreError::TxOutsideValidityWindow.with_description(msg)
+        })
+    }
+
+    /// Checks that the transaction can be committed at `height` or later. Unlike
+    /// `check_validity`, transactions which are not valid *yet* pass this check.
+    fn check_expiration(tx: &AnyTx, height: Height) -> Result<(), ExecutionError> {
+        Self::check_window_bounds(tx)?;
+        if tx.validity.is_expired_at(height) {
+            let msg = format!(
+                "Transaction validity window has ended before height {} (validity window: {:?})",
+                height, tx.validity
+            );
+            return Err(CoreError::TxOutsideValidityWindow.with_description(msg));
+        }
+        Ok(())
+    }
+
+    fn check_validity(tx: &AnyTx, height: Height) -> Result<(), ExecutionError> {
+        Self::check_window_bounds(tx)?;
+        if !tx.validity.contains(height) {
+            let msg = format!(
+                "Transaction cannot be committed at height {} (validity window: {:?})",
+                height, tx.validity
+            );
+            return Err(CoreError::TxOutsideValidityWindow.with_description(msg));
+        }
+        Ok(())
+    }
+
     /// Creates a new empty cache for checking transaction validity.
     pub fn new() -> Self {
         Self::default()
     }
 
+    fn next_height(&mut self, snapshot: &dyn Snapshot) -> Height {
+        *self
+            .next_height
+            .get_or_insert_with(|| CoreSchema::new(snapshot).next_height())
+    }
+
     fn set_missing_service(&mut self, service_id: InstanceId) {
         self.service_states.insert(service_id, None);
     }
@@ -376,6 +420,15 @@ impl Dispatcher {
         this
     }
 
+    /// Replicates all runtimes of the dispatcher. Returns `None` if any of the runtimes
+    /// does not support replication.
+    pub(crate) fn replicate_runtimes(&self) -> Option<Vec<RuntimeInstance>> {
+        self.runtimes
+            .iter()
+            .map(|(&id, runtime)| Some(RuntimeInstance::new(id, runtime.replicate()?)))
+            .collect()
+    }
+
     /// Restore the dispatcher from the state which was saved in the specified snapshot.
     ///
     /// # Panics
@@ -428,17 +481,17 @@ impl Dispatcher {
     /// `deploy_artifact`, this method skips artifact commitment; the artifact
     /// is synchronously deployed and marked as `Active`.
     ///
+    /// Dependencies of built-in artifacts are not checked by this method;
+    /// `check_builtin_dependencies()` should be called once all artifacts are added.
+    ///
     /// # Panics
     ///
     /// This method treats errors during artifact deployment as fatal and panics on them.
-    pub(crate) fn add_builtin_artifact(
-        &mut self,
-        fork: &Fork,
-        artifact: ArtifactId,
-        payload: Vec<u8>,
-    ) {
+    pub(crate) fn add_builtin_artifact(&mut self, fork: &Fork, spec: ArtifactSpec) {
+        let artifact = spec.artifact.clone();
+        let payload = spec.payload.clone();
         Schema::new(fork)
-            .add_active_artifact(&artifact, payload.clone())
+            .add_active_artifact(spec)
             .unwrap_or_else(|err| {
                 panic!("Cannot deploy a built-in artifact: {}", err);
             });
@@ -446,6 +499,17 @@ impl Dispatcher {
             .unwrap_or_else(|err| panic!("Cannot deploy a built-in artifact: {}", err));
     }
 
+    /// Checks that dependencies of all built-in artifacts are satisfied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dependency of a built-in artifact is not satisfied.
+    pub(crate) fn check_builtin_dependencies(fork: &Fork) {
+        Schema::new(fork)
+            .check_all_dependencies()
+            .unwrap_or_else(|err| panic!("Cannot deploy a built-in artifact: {}", err));
+    }
+
     /// Add a built-in service with the predefined identifier.
     ///
     /// This method must be followed by the `start_builtin_instances()` call in order
@@ -479,6 +543,18 @@ impl Dispatcher {
         res
     }
 
+    /// Adds a built-in service instance with the data restored from the state of
+    /// another blockchain. Unlike `add_builtin_service`, the service constructor
+    /// is not invoked.
+    pub(crate) fn add_restored_service(
+        fork: &Fork,
+        spec: InstanceSpec,
+    ) -> Result<(), ExecutionError> {
+        // TODO: revise dispatcher integrity checks [ECR-3743]
+        debug_assert!(spec.validate().is_ok(), "{:?}", spec.validate());
+        Schema::new(fork).initiate_adding_service(spec)
+    }
+
     /// Starts all the built-in instances, creating a `Patch` with persisted changes.
     pub(crate) fn start_builtin_instances(&mut self, fork: Fork) -> Patch {
         // Mark services as active.
@@ -545,12 +621,17 @@ impl Dispatcher {
     ///
     /// This method assumes that `deploy_artifact` was previously called for the corresponding
     /// `ArtifactId` and deployment was completed successfully.
-    /// If any error happens within `commit_artifact`, it is considered either a bug in the
-    /// `Supervisor` service or `Dispatcher` itself, and as a result, this method will panic.
-    pub(crate) fn commit_artifact(fork: &Fork, artifact: &ArtifactId, deploy_spec: Vec<u8>) {
-        debug_assert!(artifact.validate().is_ok(), "{:?}", artifact.validate());
+    /// If any error happens within `commit_artifact` (e.g., dependencies of the artifact
+    /// are not satisfied), it is considered either a bug in the `Supervisor` service
+    /// or `Dispatcher` itself, and as a result, this method will panic.
+    pub(crate) fn commit_artifact(fork: &Fork, spec: ArtifactSpec) {
+        debug_assert!(
+            spec.artifact.validate().is_ok(),
+            "{:?}",
+            spec.artifact.validate()
+        );
         Schema::new(fork)
-            .add_pending_artifact(artifact, deploy_spec)
+            .add_pending_artifact(spec)
             .unwrap_or_else(|err| panic!("BUG: Can't commit the artifact, error: {}", err));
     }
 
@@ -673,11 +754,10 @@ impl Dispatcher {
         });
 
         if !runtime.is_supported(&RuntimeFeature::FreezingServices) {
-            let runtime_description = RuntimeIdentifier::transform(runtime_id).ok().map_or_else(
-                || format!("Runtime with ID {}", runtime_id),
-                |id| id.to_string(),
+            let msg = format!(
+                "{} does not support freezing services",
+                Self::runtime_description(runtime_id)
             );
-            let msg = format!("{} does not support freezing services", runtime_description);
             return Err(CommonError::FeatureNotSupported.with_description(msg));
         }
 
@@ -701,7 +781,9 @@ impl Dispatcher {
     ///
     /// Returned `Ok(())` value doesn't necessarily mean that transaction is correct and will be
     /// executed successfully, but returned `Err(..)` value means that this transaction is
-    /// **obviously** incorrect and should be declined as early as possible.
+    /// **obviously** incorrect and should be declined as early as possible. In particular,
+    /// a transaction with an ended validity window is rejected, while a transaction
+    /// which will become valid at a future height passes the check.
     pub(crate) fn check_tx(
         snapshot: &dyn Snapshot,
         tx: &Verified<AnyTx>,
@@ -709,14 +791,22 @@ impl Dispatcher {
     ) -> Result<(), ExecutionError> {
         let service_id = tx.as_ref().call_info.instance_id;
 
+        if !tx.as_ref().validity.is_unbounded() {
+            let next_height = if let Some(cache) = cache.as_deref_mut() {
+                cache.next_height(snapshot)
+            } else {
+                CoreSchema::new(snapshot).next_height()
+            };
+            TxCheckCache::check_expiration(tx.as_ref(), next_height)?;
+        }
+
         if let Some(cache) = cache.as_deref_mut() {
             if let Some(res) = cache.check_service_status(service_id) {
                 return res;
             }
         }
 
-        // Currently the only check is that destination service exists, but later
-        // functionality of this method can be extended.
+        // Check that destination service exists and is active.
         let instance = Schema::new(snapshot)
             .get_instance(service_id)
             .ok_or_else(|| {
@@ -771,7 +861,142 @@ impl Dispatcher {
         tx_index: u32,
         tx: &Verified<AnyTx>,
     ) -> Result<(), ExecutionError> {
-        let call_info = &tx.as_ref().call_info;
+        let meter = ResourceMeter::new(Self::resource_limits(fork));
+        let caller = Caller::for_transaction(tx);
+        let res = self.execute_with_meter(fork, tx_id, tx_index, tx.as_ref(), caller, &meter);
+        if let Err(ref err) = res {
+            Self::report_error(err, fork, CallInBlock::transaction(tx_index));
+        }
+        res
+    }
+
+    /// Simulates execution of a transaction on the provided fork with the specified
+    /// resource limits. The returned result does not contain the state diff, which is computed
+    /// by the caller.
+    ///
+    /// Unsigned transactions are only simulated if the runtime of the target service
+    /// supports [`RuntimeFeature::UnsignedSimulation`].
+    ///
+    /// [`RuntimeFeature::UnsignedSimulation`]: ../enum.RuntimeFeature.html#variant.UnsignedSimulation
+    pub(crate) fn simulate(
+        &self,
+        fork: &mut Fork,
+        simulation: &Simulation,
+        limits: ResourceLimits,
+    ) -> SimulationResult {
+        let tx = &simulation.tx;
+        if !simulation.is_signed {
+            let runtime = self.runtime_for_service(tx.call_info.instance_id);
+            if let Some((runtime_id, runtime)) = runtime {
+                if !runtime.is_supported(&RuntimeFeature::UnsignedSimulation) {
+                    let msg = format!(
+                        "{} does not support simulation of unsigned transactions",
+                        Self::runtime_description(runtime_id)
+                    );
+                    let err = CommonError::FeatureNotSupported.with_description(msg);
+                    return SimulationResult::new(Err(err), vec![], None);
+                }
+            }
+        }
+
+        let mut meter = ResourceMeter::new(limits).with_index_tracking();
+        if simulation.needs_trace() {
+            meter = meter.with_tracing();
+        }
+        let caller = simulation.caller.clone();
+        let res = self.execute_with_meter(
+            fork,
+            simulation.tx_hash,
+            simulation.tx_index,
+            tx,
+            caller,
+            &meter,
+        );
+        let (touched_indexes, trace) = meter.into_records();
+        SimulationResult::new(res, touched_indexes, trace)
+    }
+
+    /// Performs a read-only query to the service with the specified ID against the `snapshot`.
+    ///
+    /// The query is dispatched only if the service provides read access, and its runtime
+    /// supports [`RuntimeFeature::Queries`].
+    ///
+    /// [`RuntimeFeature::Queries`]: ../enum.RuntimeFeature.html#variant.Queries
+    pub(crate) fn query(
+        &self,
+        snapshot: &dyn Snapshot,
+        instance_id: InstanceId,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Result<Vec<u8>, ExecutionError> {
+        let state = Schema::new(snapshot)
+            .get_instance(instance_id)
+            .filter(|state| {
+                state
+                    .status
+                    .as_ref()
+                    .map_or(false, InstanceStatus::provides_read_access)
+            })
+            .ok_or_else(|| {
+                let msg = format!(
+                    "Cannot dispatch query to unknown or inactive service with ID {}",
+                    instance_id
+                );
+                CoreError::IncorrectInstanceId.with_description(msg)
+            })?;
+
+        let runtime_id = state.spec.artifact.runtime_id;
+        let runtime = self.runtimes[&runtime_id].as_ref();
+        if !runtime.is_supported(&RuntimeFeature::Queries) {
+            let msg = format!(
+                "{} does not support read-only queries",
+                Self::runtime_description(runtime_id)
+            );
+            return Err(CommonError::FeatureNotSupported.with_description(msg));
+        }
+        runtime.query(&state.spec.as_descriptor(), method_id, arguments, snapshot)
+    }
+
+    fn runtime_description(runtime_id: u32) -> String {
+        RuntimeIdentifier::transform(runtime_id).ok().map_or_else(
+            || format!("Runtime with ID {}", runtime_id),
+            |id| id.to_string(),
+        )
+    }
+
+    /// Returns resource limits for transactions executed on the `fork`.
+    pub(crate) fn resource_limits(fork: &Fork) -> ResourceLimits {
+        // The configuration may be absent if the dispatcher is used without the genesis block
+        // (e.g., in tests); in this case, resources are not limited.
+        CoreSchema::new(fork)
+            .consensus_config_entry()
+            .get()
+            .map_or_else(ResourceLimits::unlimited, |config| {
+                ResourceLimits::for_transactions(&config)
+            })
+    }
+
+    /// Returns resource limits for transactions simulated on the `fork`.
+    pub(crate) fn simulation_limits(fork: &Fork) -> ResourceLimits {
+        let config = CoreSchema::new(fork).consensus_config_entry().get();
+        ResourceLimits::for_simulations(config.as_ref())
+    }
+
+    fn execute_with_meter(
+        &self,
+        fork: &mut Fork,
+        tx_id: Hash,
+        tx_index: u32,
+        tx: &AnyTx,
+        caller: Caller,
+        meter: &ResourceMeter,
+    ) -> Result<(), ExecutionError> {
+        // The validity window is checked again, since the transaction may be included
+        // into a block proposal by a node not performing `check_tx` properly.
+        let height = CoreSchema::new(&*fork).next_height();
+        TxCheckCache::check_validity(tx, height)?;
+
+        let call_info = &tx.call_info;
         let (runtime_id, runtime) =
             self.runtime_for_service(call_info.instance_id)
                 .ok_or_else(|| {
@@ -790,28 +1015,125 @@ impl Dispatcher {
             CoreError::IncorrectInstanceId.with_description(msg)
         })?;
 
+        meter.enter_call(&caller, &instance, "", call_info.method_id, &tx.arguments);
         let mut should_rollback = false;
         let context = ExecutionContext::for_transaction(
             self,
             fork,
             &mut should_rollback,
             instance,
-            tx.author(),
+            caller,
             tx_id,
-        );
+            tx_index,
+        )
+        .with_meter(meter);
 
-        let mut res = runtime.execute(context, call_info.method_id, &tx.as_ref().arguments);
+        let mut res =
+            meter.run_call(|| runtime.execute(context, call_info.method_id, &tx.arguments));
         if should_rollback && res.is_ok() {
             res = Err(CoreError::IncorrectCall.into());
         }
+        meter.exit_call(&res);
 
         if let Err(ref mut err) = res {
             fork.rollback();
-
             err.set_runtime_id(runtime_id)
                 .set_call_site(|| CallSite::from_call_info(call_info, ""));
-            Self::report_error(err, fork, CallInBlock::transaction(tx_index));
         } else {
+            Self::save_events(fork, CallInBlock::transaction(tx_index), meter);
+            fork.flush();
+        }
+        res
+    }
+
+    /// Saves events buffered by the meter of a successful top-level call.
+    fn save_events(fork: &Fork, call: CallInBlock, meter: &ResourceMeter) {
+        let events = meter.take_events();
+        if !events.is_empty() {
+            let mut schema = CoreSchema::new(fork);
+            let height = schema.next_height();
+            schema.save_events(height, call, events);
+        }
+    }
+
+    /// Executes calls scheduled at the height of the block being created, isolating each call.
+    /// Calls are executed in the order of scheduling and are removed from the storage
+    /// regardless of their outcome.
+    pub(crate) fn execute_scheduled_calls(
+        &self,
+        fork: &mut Fork,
+    ) -> Vec<(CallInBlock, ExecutionError)> {
+        let height = CoreSchema::new(&*fork).next_height();
+        let ids: Vec<_> = CoreSchema::new(&*fork)
+            .scheduled_calls_at(height)
+            .iter()
+            .collect();
+
+        ids.into_iter()
+            .filter_map(|id| {
+                let scheduled_call = CoreSchema::new(&*fork)
+                    .remove_scheduled_call(id)
+                    .expect("BUG: Scheduled call is indexed by height, but is not stored");
+                // Removal of the call should not be rolled back if the call fails.
+                fork.flush();
+
+                let call = CallInBlock::scheduled_call(id);
+                let res = self.execute_scheduled_call(fork, call, &scheduled_call);
+                res.err().map(|err| {
+                    Self::report_error(&err, fork, call);
+                    (call, err)
+                })
+            })
+            .collect()
+    }
+
+    fn execute_scheduled_call(
+        &self,
+        fork: &mut Fork,
+        call: CallInBlock,
+        scheduled_call: &ScheduledCall,
+    ) -> Result<(), ExecutionError> {
+        let instance_id = scheduled_call.instance_id;
+        let method_id = scheduled_call.method_id;
+        let call_site = || {
+            let call_type = CallType::Method {
+                interface: String::new(),
+                id: method_id,
+            };
+            CallSite::new(instance_id, call_type)
+        };
+
+        let (runtime_id, runtime) = self.runtime_for_service(instance_id).ok_or_else(|| {
+            let msg = format!(
+                "Cannot dispatch scheduled call to unknown service with ID {}",
+                instance_id
+            );
+            CoreError::IncorrectInstanceId.with_description(msg)
+        })?;
+        let instance = self.get_service(instance_id).ok_or_else(|| {
+            let msg = format!(
+                "Cannot dispatch scheduled call to inactive service with ID {}",
+                instance_id
+            );
+            CoreError::IncorrectInstanceId.with_description(msg)
+        })?;
+
+        let meter = ResourceMeter::new(Self::resource_limits(fork));
+        let mut should_rollback = false;
+        let context = ExecutionContext::for_block_call(self, fork, &mut should_rollback, instance)
+            .with_call_location(call)
+            .with_meter(&meter);
+        let mut res =
+            meter.run_call(|| runtime.execute(context, method_id, &scheduled_call.arguments));
+        if should_rollback && res.is_ok() {
+            res = Err(CoreError::IncorrectCall.into());
+        }
+
+        if let Err(ref mut err) = res {
+            fork.rollback();
+            err.set_runtime_id(runtime_id).set_call_site(call_site);
+        } else {
+            Self::save_events(fork, call, &meter);
             fork.flush();
         }
         res
@@ -823,23 +1145,34 @@ impl Dispatcher {
         fork: &mut Fork,
         call_type: &CallType,
     ) -> Vec<(CallInBlock, ExecutionError)> {
+        let limits = Self::resource_limits(fork);
         self.service_infos
             .active_instances()
             .filter_map(|(instance, runtime_id)| {
+                let call = match &call_type {
+                    CallType::BeforeTransactions => CallInBlock::before_transactions(instance.id),
+                    CallType::AfterTransactions => CallInBlock::after_transactions(instance.id),
+                    _ => unreachable!(),
+                };
+                let call_fn = match &call_type {
+                    CallType::BeforeTransactions => Runtime::before_transactions,
+                    CallType::AfterTransactions => Runtime::after_transactions,
+                    _ => unreachable!(),
+                };
+
+                let meter = ResourceMeter::new(limits);
                 let mut should_rollback = false;
                 let context = ExecutionContext::for_block_call(
                     self,
                     fork,
                     &mut should_rollback,
                     instance.clone(),
-                );
-                let call_fn = match &call_type {
-                    CallType::BeforeTransactions => Runtime::before_transactions,
-                    CallType::AfterTransactions => Runtime::after_transactions,
-                    _ => unreachable!(),
-                };
+                )
+                .with_call_location(call)
+                .with_meter(&meter);
 
-                let mut res = call_fn(self.runtimes[&runtime_id].as_ref(), context);
+                let runtime = self.runtimes[&runtime_id].as_ref();
+                let mut res = meter.run_call(|| call_fn(runtime, context));
                 if should_rollback && res.is_ok() {
                     res = Err(CoreError::IncorrectCall.into());
                 }
@@ -848,17 +1181,10 @@ impl Dispatcher {
                     fork.rollback();
                     err.set_runtime_id(runtime_id)
                         .set_call_site(|| CallSite::new(instance.id, call_type.clone()));
-
-                    let call = match &call_type {
-                        CallType::BeforeTransactions => {
-                            CallInBlock::before_transactions(instance.id)
-                        }
-                        CallType::AfterTransactions => CallInBlock::after_transactions(instance.id),
-                        _ => unreachable!(),
-                    };
                     Self::report_error(&err, fork, call);
                     Some((call, err))
                 } else {
+                    Self::save_events(fork, call, &meter);
                     fork.flush();
                     None
                 }
@@ -874,6 +1200,17 @@ impl Dispatcher {
         self.call_service_hooks(fork, &CallType::BeforeTransactions)
     }
 
+    /// Executes the part of the block preceding its transactions: calls scheduled
+    /// at the height of the block and `before_transactions` hooks of the services.
+    pub(crate) fn execute_block_prologue(
+        &self,
+        fork: &mut Fork,
+    ) -> Vec<(CallInBlock, ExecutionError)> {
+        let mut errors = self.execute_scheduled_calls(fork);
+        errors.extend(self.before_transactions(fork));
+        errors
+    }
+
     /// Calls `after_transactions` for all currently active services, isolating each call.
     ///
     /// Changes the status of pending artifacts and services to active in the merkelized
@@ -28,8 +28,9 @@ use crate::{
     },
     runtime::{
         migrations::{InstanceMigration, MigrationStatus},
-        ArtifactId, ArtifactState, ArtifactStatus, CoreError, ExecutionError, ExecutionFail,
-        InstanceId, InstanceQuery, InstanceSpec, InstanceState, InstanceStatus,
+        versioning::{ArtifactReq, InterfaceReq},
+        ArtifactId, ArtifactSpec, ArtifactState, ArtifactStatus, CoreError, ExecutionError,
+        ExecutionFail, InstanceId, InstanceQuery, InstanceSpec, InstanceState, InstanceStatus,
     },
 };
 
@@ -180,6 +181,105 @@ impl<T: Access> Schema<T> {
         self.local_migration_results().get(instance_name)
     }
 
+    /// Returns the first of `dependencies` not matched by any active artifact, or `None`
+    /// if all dependencies are satisfied.
+    pub fn unsatisfied_dependency<'a>(
+        &self,
+        dependencies: &'a [ArtifactReq],
+    ) -> Option<&'a ArtifactReq> {
+        dependencies
+            .iter()
+            .find(|&req| !self.is_dependency_satisfied(req, None))
+    }
+
+    /// Returns the first of `interfaces` not provided by any active artifact, or `None`
+    /// if all interface requirements are satisfied.
+    pub fn unsatisfied_interface<'a>(
+        &self,
+        interfaces: &'a [InterfaceReq],
+    ) -> Option<&'a InterfaceReq> {
+        interfaces
+            .iter()
+            .find(|&req| !self.is_interface_satisfied(req, None))
+    }
+
+    /// Returns deployed artifacts that would have an unsatisfied dependency
+    /// (either on an artifact or on an interface) if the provided artifact were unloaded.
+    pub fn dependent_artifacts(&self, artifact: &ArtifactId) -> Vec<ArtifactId> {
+        let provided_interfaces = self
+            .artifacts()
+            .get(artifact)
+            .map_or_else(Vec::new, |state| state.provided_interfaces);
+
+        self.artifacts()
+            .iter()
+            .filter(|(dependent, state)| {
+                if dependent == artifact || state.status == ArtifactStatus::Unloading {
+                    return false;
+                }
+                let requires_artifact = state.dependencies.iter().any(|req| {
+                    req.try_match(artifact).is_ok()
+                        && !self.is_dependency_satisfied(req, Some(artifact))
+                });
+                let requires_interface = state.required_interfaces.iter().any(|req| {
+                    provided_interfaces
+                        .iter()
+                        .any(|interface| req.matches(interface))
+                        && !self.is_interface_satisfied(req, Some(artifact))
+                });
+                requires_artifact || requires_interface
+            })
+            .map(|(dependent, _)| dependent)
+            .collect()
+    }
+
+    /// Checks whether `req` is matched by an active artifact other than `excluded`.
+    fn is_dependency_satisfied(&self, req: &ArtifactReq, excluded: Option<&ArtifactId>) -> bool {
+        self.artifacts().iter().any(|(artifact, state)| {
+            state.status == ArtifactStatus::Active
+                && Some(&artifact) != excluded
+                && req.try_match(&artifact).is_ok()
+        })
+    }
+
+    /// Checks whether `req` is matched by an interface provided by an active artifact
+    /// other than `excluded`.
+    fn is_interface_satisfied(&self, req: &InterfaceReq, excluded: Option<&ArtifactId>) -> bool {
+        self.artifacts().iter().any(|(artifact, state)| {
+            state.status == ArtifactStatus::Active
+                && Some(&artifact) != excluded
+                && state
+                    .provided_interfaces
+                    .iter()
+                    .any(|interface| req.matches(interface))
+        })
+    }
+
+    /// Checks that dependencies of the artifact, both on artifacts and interfaces,
+    /// are satisfied.
+    pub(super) fn check_dependencies(
+        &self,
+        artifact: &ArtifactId,
+        dependencies: &[ArtifactReq],
+        required_interfaces: &[InterfaceReq],
+    ) -> Result<(), ExecutionError> {
+        if let Some(req) = self.unsatisfied_dependency(dependencies) {
+            let msg = format!(
+                "Dependency `{}` of artifact `{}` is not matched by any active artifact",
+                req, artifact
+            );
+            return Err(CoreError::UnsatisfiedArtifactDependency.with_description(msg));
+        }
+        if let Some(req) = self.unsatisfied_interface(required_interfaces) {
+            let msg = format!(
+                "Interface `{}` required by artifact `{}` is not provided by any active artifact",
+                req, artifact
+            );
+            return Err(CoreError::UnsatisfiedInterfaceRequirement.with_description(msg));
+        }
+        Ok(())
+    }
+
     /// Checks if the provided artifact can currently be unloaded. Returns an error if the unloading
     /// is impossible.
     pub fn check_unloading_artifact(&self, artifact: &ArtifactId) -> Result<(), ExecutionError> {
@@ -236,6 +336,15 @@ impl<T: Access> Schema<T> {
             }
         }
 
+        // Check that the artifact is not the only match for a dependency of another artifact.
+        if let Some(dependent) = self.dependent_artifacts(artifact).first() {
+            let msg = format!(
+                "Cannot unload artifact `{}`: artifact `{}` depends on it",
+                artifact, dependent
+            );
+            return Err(CoreError::CannotUnloadArtifact.with_description(msg));
+        }
+
         Ok(state)
     }
 }
@@ -257,40 +366,47 @@ impl Schema<&Fork> {
     /// Adds artifact specification to the set of the pending artifacts.
     pub(super) fn add_pending_artifact(
         &mut self,
-        artifact: &ArtifactId,
-        deploy_spec: Vec<u8>,
+        spec: ArtifactSpec,
     ) -> Result<(), ExecutionError> {
+        let artifact = spec.artifact.clone();
         // Check that the artifact is absent among the deployed artifacts.
-        if self.artifacts().contains(artifact) {
+        if self.artifacts().contains(&artifact) {
             let msg = format!("Cannot deploy artifact `{}` twice", artifact);
             return Err(CoreError::ArtifactAlreadyDeployed.with_description(msg));
         }
+        self.check_dependencies(&artifact, &spec.dependencies, &spec.required_interfaces)?;
         // Add artifact to registry with pending status.
         self.artifacts().put(
-            artifact,
-            ArtifactState::new(deploy_spec, ArtifactStatus::Deploying),
+            &artifact,
+            ArtifactState::new(spec, ArtifactStatus::Deploying),
         );
         // Add artifact to pending artifacts queue.
-        self.pending_artifacts().insert(artifact);
+        self.pending_artifacts().insert(&artifact);
         Ok(())
     }
 
     /// Adds artifact specification to the set of the active artifacts.
-    pub(super) fn add_active_artifact(
-        &mut self,
-        artifact: &ArtifactId,
-        deploy_spec: Vec<u8>,
-    ) -> Result<(), ExecutionError> {
+    ///
+    /// Dependencies of the artifact are not checked, since built-in artifacts may be added
+    /// in an arbitrary order; use `check_all_dependencies` once all artifacts are added.
+    pub(super) fn add_active_artifact(&mut self, spec: ArtifactSpec) -> Result<(), ExecutionError> {
+        let artifact = spec.artifact.clone();
         // Check that the artifact is absent among the deployed artifacts.
-        if self.artifacts().contains(artifact) {
+        if self.artifacts().contains(&artifact) {
             let msg = format!("Cannot deploy artifact `{}` twice", artifact);
             return Err(CoreError::ArtifactAlreadyDeployed.with_description(msg));
         }
 
-        self.artifacts().put(
-            artifact,
-            ArtifactState::new(deploy_spec, ArtifactStatus::Active),
-        );
+        self.artifacts()
+            .put(&artifact, ArtifactState::new(spec, ArtifactStatus::Active));
+        Ok(())
+    }
+
+    /// Checks that dependencies of all deployed artifacts are satisfied.
+    pub(super) fn check_all_dependencies(&self) -> Result<(), ExecutionError> {
+        for (artifact, state) in &self.artifacts() {
+            self.check_dependencies(&artifact, &state.dependencies, &state.required_interfaces)?;
+        }
         Ok(())
     }
 
@@ -531,6 +647,11 @@ impl Schema<&Fork> {
             );
             return Err(CoreError::ArtifactNotDeployed.with_description(msg));
         }
+        self.check_dependencies(
+            &spec.artifact,
+            &artifact_state.dependencies,
+            &artifact_state.required_interfaces,
+        )?;
 
         // Check that the instance name doesn't exist.
         if self.instances().contains(&spec.name) {
@@ -36,10 +36,11 @@ use crate::{
         dispatcher::{Action, ArtifactStatus, Dispatcher, Mailbox},
         migrations::{InitMigrationError, MigrationScript},
         oneshot::{self, Receiver},
-        ArtifactId, BlockchainData, CallInfo, CommonError, CoreError, DispatcherSchema, ErrorKind,
-        ErrorMatch, ExecutionContext, ExecutionError, InstanceDescriptor, InstanceId, InstanceSpec,
-        InstanceState, InstanceStatus, MethodId, Runtime, RuntimeFeature, RuntimeInstance,
-        SnapshotExt, TxCheckCache,
+        versioning::{ArtifactReq, InterfaceReq},
+        ArtifactId, ArtifactSpec, BlockchainData, CallInfo, CommonError, CoreError,
+        DispatcherSchema, ErrorKind, ErrorMatch, ExecutionContext, ExecutionError,
+        InstanceDescriptor, InstanceId, InstanceSpec, InstanceState, InstanceStatus, MethodId,
+        Runtime, RuntimeFeature, RuntimeInstance, SnapshotExt, TxCheckCache, TxValidity,
     },
 };
 
@@ -169,6 +170,7 @@ impl Runtime for SampleRuntime {
     fn is_supported(&self, feature: &RuntimeFeature) -> bool {
         match feature {
             RuntimeFeature::FreezingServices => self.runtime_type == SampleRuntimes::First as u32,
+            RuntimeFeature::UnsignedSimulation | RuntimeFeature::Queries => false,
         }
     }
 
@@ -334,8 +336,8 @@ fn test_dispatcher_simple() {
 
     // Check if the services are ready for deploy.
     let mut fork = db.fork();
-    dispatcher.add_builtin_artifact(&fork, rust_artifact.clone(), vec![]);
-    dispatcher.add_builtin_artifact(&fork, java_artifact.clone(), vec![]);
+    dispatcher.add_builtin_artifact(&fork, ArtifactSpec::new(rust_artifact.clone(), ()));
+    dispatcher.add_builtin_artifact(&fork, ArtifactSpec::new(java_artifact.clone(), ()));
 
     // Check if the services are ready for initiation. Note that the artifacts are pending at this
     // point.
@@ -503,7 +505,7 @@ fn blockchain_with_frozen_service(rt: SampleRuntimes) -> Result<FreezingRig, Exe
 
     // Deploy the artifact and instantiate the service.
     let mut fork = db.fork();
-    dispatcher.add_builtin_artifact(&fork, artifact.clone(), vec![]);
+    dispatcher.add_builtin_artifact(&fork, ArtifactSpec::new(artifact.clone(), ()));
     let service = InstanceSpec::from_raw_parts(SERVICE_ID, "some-service".to_owned(), artifact);
     let mut should_rollback = false;
     let mut context = ExecutionContext::for_block_call(
@@ -970,7 +972,7 @@ fn delayed_deployment() {
     // Check that we don't require the runtime to deploy the artifact again if we mark it
     // as committed.
     let fork = db.fork();
-    Dispatcher::commit_artifact(&fork, &artifact, spec);
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(artifact.clone(), spec));
     Dispatcher::activate_pending(&fork);
     let patch = dispatcher.commit_block_and_notify_runtimes(fork);
     db.merge_sync(patch).unwrap();
@@ -998,7 +1000,7 @@ fn test_failed_deployment(db: &Arc<TemporaryDB>, runtime: &DeploymentRuntime, ar
     assert_eq!(runtime.deploy_attempts(&artifact), 1);
 
     let fork = db.fork();
-    Dispatcher::commit_artifact(&fork, &artifact, spec);
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(artifact, spec));
     Dispatcher::activate_pending(&fork);
     dispatcher.commit_block_and_notify_runtimes(fork); // << should panic
 }
@@ -1040,7 +1042,7 @@ fn failed_deployment_with_node_restart() {
     let spec = 100_u64.to_bytes();
 
     let fork = db.fork();
-    Dispatcher::commit_artifact(&fork, &artifact, spec);
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(artifact.clone(), spec));
     Dispatcher::activate_pending(&fork);
     let patch = dispatcher.commit_block_and_notify_runtimes(fork);
     db.merge_sync(patch).unwrap();
@@ -1076,7 +1078,7 @@ fn recoverable_error_during_deployment() {
     assert_eq!(runtime.deploy_attempts(&artifact), 1);
 
     let fork = db.fork();
-    Dispatcher::commit_artifact(&fork, &artifact, spec);
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(artifact.clone(), spec));
     Dispatcher::activate_pending(&fork);
     dispatcher.commit_block_and_notify_runtimes(fork);
     // The dispatcher should try to deploy the artifact again despite a previous failure.
@@ -1121,7 +1123,7 @@ fn stopped_service_workflow() {
         "first".into(),
         Version::new(0, 1, 0),
     );
-    dispatcher.add_builtin_artifact(&fork, artifact.clone(), vec![]);
+    dispatcher.add_builtin_artifact(&fork, ArtifactSpec::new(artifact.clone(), ()));
 
     let service = InstanceSpec::from_raw_parts(instance_id, instance_name.into(), artifact);
     let mut should_rollback = false;
@@ -1292,7 +1294,7 @@ fn unload_artifact_workflow() {
     // Deploy the artifact.
     let fork = db.fork();
     let spec = DeploymentRuntime::SPEC.to_vec();
-    Dispatcher::commit_artifact(&fork, &artifact, spec);
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(artifact.clone(), spec));
     Dispatcher::activate_pending(&fork);
     let patch = dispatcher.commit_block_and_notify_runtimes(fork);
     db.merge_sync(patch).unwrap();
@@ -1336,6 +1338,169 @@ fn unload_artifact_workflow() {
     assert!(schema.get_artifact(&artifact).is_none());
 }
 
+#[test]
+fn artifact_dependencies_workflow() {
+    const RUNTIME_ID: u32 = 2;
+
+    let db = Arc::new(TemporaryDB::new());
+    let blockchain = Blockchain::new(
+        Arc::clone(&db) as Arc<dyn Database>,
+        gen_keypair(),
+        ApiSender::closed(),
+    );
+    let runtime = DeploymentRuntime::default();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with_runtime(RUNTIME_ID, runtime)
+        .finalize(&blockchain);
+
+    let patch = create_genesis_block(&mut dispatcher, db.fork());
+    db.merge_sync(patch).unwrap();
+
+    let base = ArtifactId::new(RUNTIME_ID, "good", Version::new(1, 2, 0)).unwrap();
+    let dependent = ArtifactId::new(RUNTIME_ID, "good", Version::new(2, 0, 0)).unwrap();
+    let spec = DeploymentRuntime::SPEC.to_vec();
+    let dependency: ArtifactReq = "good@^1.1".parse().unwrap();
+    let dependent_spec = ArtifactSpec::new(dependent.clone(), spec.clone())
+        .with_dependencies(vec![dependency.clone()]);
+
+    // Check that an artifact cannot be deployed before its dependency.
+    let fork = db.fork();
+    let err = DispatcherSchema::new(&fork)
+        .add_pending_artifact(dependent_spec.clone())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::UnsatisfiedArtifactDependency)
+            .with_description_containing("Dependency `good@^1.1` of artifact `2:good:2.0.0`")
+    );
+
+    // Check that a dependency being deployed does not satisfy the requirement.
+    Dispatcher::commit_artifact(&fork, ArtifactSpec::new(base.clone(), spec));
+    let err = DispatcherSchema::new(&fork)
+        .add_pending_artifact(dependent_spec.clone())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::UnsatisfiedArtifactDependency).with_any_description()
+    );
+    Dispatcher::activate_pending(&fork);
+    let patch = dispatcher.commit_block_and_notify_runtimes(fork);
+    db.merge_sync(patch).unwrap();
+
+    // Deploy the dependent artifact once the dependency is active.
+    let fork = db.fork();
+    Dispatcher::commit_artifact(&fork, dependent_spec);
+    // The dependency cannot be unloaded even if the dependent artifact is not active yet.
+    let err = Dispatcher::unload_artifact(&fork, &base).unwrap_err();
+    let expected_msg =
+        "Cannot unload artifact `2:good:1.2.0`: artifact `2:good:2.0.0` depends on it";
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::CannotUnloadArtifact)
+            .with_description_containing(expected_msg)
+    );
+    Dispatcher::activate_pending(&fork);
+    let patch = dispatcher.commit_block_and_notify_runtimes(fork);
+    db.merge_sync(patch).unwrap();
+
+    let snapshot = db.snapshot();
+    let schema = DispatcherSchema::new(&snapshot);
+    let state = schema.get_artifact(&dependent).unwrap();
+    assert_eq!(state.status, ArtifactStatus::Active);
+    assert_eq!(state.dependencies, vec![dependency]);
+    assert_eq!(schema.dependent_artifacts(&base), vec![dependent.clone()]);
+
+    // The dependency can be unloaded together with the dependent artifact.
+    let fork = db.fork();
+    Dispatcher::unload_artifact(&fork, &dependent).unwrap();
+    Dispatcher::unload_artifact(&fork, &base).unwrap();
+    Dispatcher::activate_pending(&fork);
+    let patch = dispatcher.commit_block_and_notify_runtimes(fork);
+    db.merge_sync(patch).unwrap();
+
+    let snapshot = db.snapshot();
+    let schema = DispatcherSchema::new(&snapshot);
+    assert!(schema.get_artifact(&base).is_none());
+    assert!(schema.get_artifact(&dependent).is_none());
+}
+
+#[test]
+fn interface_requirements_workflow() {
+    const RUNTIME_ID: u32 = 2;
+
+    let db = Arc::new(TemporaryDB::new());
+    let blockchain = Blockchain::new(
+        Arc::clone(&db) as Arc<dyn Database>,
+        gen_keypair(),
+        ApiSender::closed(),
+    );
+    let runtime = DeploymentRuntime::default();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with_runtime(RUNTIME_ID, runtime)
+        .finalize(&blockchain);
+
+    let patch = create_genesis_block(&mut dispatcher, db.fork());
+    db.merge_sync(patch).unwrap();
+
+    let spec = DeploymentRuntime::SPEC.to_vec();
+    let old_provider = ArtifactId::new(RUNTIME_ID, "good", Version::new(1, 0, 0)).unwrap();
+    let old_provider_spec = ArtifactSpec::new(old_provider.clone(), spec.clone())
+        .with_provided_interfaces(vec!["exonum.Token@1.2.0".parse().unwrap()]);
+    let provider = ArtifactId::new(RUNTIME_ID, "good", Version::new(1, 1, 0)).unwrap();
+    let provider_spec = ArtifactSpec::new(provider.clone(), spec.clone())
+        .with_provided_interfaces(vec!["exonum.Token@1.4.0".parse().unwrap()]);
+    let consumer = ArtifactId::new(RUNTIME_ID, "good", Version::new(3, 0, 0)).unwrap();
+    let requirement: InterfaceReq = "exonum.Token@^1.3".parse().unwrap();
+    let consumer_spec =
+        ArtifactSpec::new(consumer.clone(), spec).with_required_interfaces(vec![requirement]);
+
+    // Check that an artifact cannot be deployed if the interface is not provided,
+    // or is provided with an incompatible version.
+    let fork = db.fork();
+    Dispatcher::commit_artifact(&fork, old_provider_spec);
+    Dispatcher::activate_pending(&fork);
+    let err = DispatcherSchema::new(&fork)
+        .add_pending_artifact(consumer_spec.clone())
+        .unwrap_err();
+    let expected_msg = "Interface `exonum.Token@^1.3` required by artifact `2:good:3.0.0`";
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::UnsatisfiedInterfaceRequirement)
+            .with_description_containing(expected_msg)
+    );
+    Dispatcher::commit_artifact(&fork, provider_spec);
+    Dispatcher::activate_pending(&fork);
+    let patch = dispatcher.commit_block_and_notify_runtimes(fork);
+    db.merge_sync(patch).unwrap();
+
+    // Deploy the artifact once a compatible interface is provided.
+    let fork = db.fork();
+    Dispatcher::commit_artifact(&fork, consumer_spec.clone());
+    Dispatcher::activate_pending(&fork);
+    let patch = dispatcher.commit_block_and_notify_runtimes(fork);
+    db.merge_sync(patch).unwrap();
+
+    let snapshot = db.snapshot();
+    let schema = DispatcherSchema::new(&snapshot);
+    let state = schema.get_artifact(&consumer).unwrap();
+    assert_eq!(state.required_interfaces, consumer_spec.required_interfaces);
+    assert_eq!(schema.dependent_artifacts(&provider), vec![consumer]);
+    assert!(schema.dependent_artifacts(&old_provider).is_empty());
+
+    // The artifact providing the interface cannot be unloaded, unlike the artifact
+    // providing an incompatible interface version.
+    let fork = db.fork();
+    let err = Dispatcher::unload_artifact(&fork, &provider).unwrap_err();
+    let expected_msg =
+        "Cannot unload artifact `2:good:1.1.0`: artifact `2:good:3.0.0` depends on it";
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::CannotUnloadArtifact)
+            .with_description_containing(expected_msg)
+    );
+    Dispatcher::unload_artifact(&fork, &old_provider).unwrap();
+}
+
 #[test]
 fn check_tx_caching() {
     let FreezingRig {
@@ -1416,3 +1581,58 @@ fn check_tx_caching() {
         ErrorMatch::from_fail(&CoreError::IncorrectInstanceId).with_any_description()
     );
 }
+
+#[test]
+fn check_tx_validity_window() {
+    let FreezingRig { db, service, .. } =
+        blockchain_with_frozen_service(SampleRuntimes::First).unwrap();
+
+    let snapshot = db.snapshot();
+    let next_height = CoreSchema::new(&snapshot).next_height();
+    let keys = KeyPair::random();
+    let call_info = CallInfo::new(service.id, 0);
+
+    let validity = TxValidity::default().with_valid_until(next_height);
+    let tx = AnyTx::new(call_info.clone(), vec![])
+        .with_validity(validity)
+        .sign_with_keypair(&keys);
+    // The validity window is checked before the service status, so the transaction
+    // should pass the window check and fail because the service is frozen.
+    let err = Dispatcher::check_tx(&snapshot, &tx, None).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ServiceNotActive).with_any_description()
+    );
+
+    // Transactions which are not valid yet pass the window check as well.
+    let validity = TxValidity::default().with_valid_from(next_height.next());
+    let tx = AnyTx::new(call_info.clone(), vec![])
+        .with_validity(validity)
+        .sign_with_keypair(&keys);
+    let err = Dispatcher::check_tx(&snapshot, &tx, None).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::ServiceNotActive).with_any_description()
+    );
+
+    // Transactions with an ended window are rejected.
+    assert!(next_height > Height(1));
+    let validity = TxValidity::default().with_valid_until(next_height.previous());
+    let tx = AnyTx::new(call_info.clone(), vec![])
+        .with_validity(validity)
+        .sign_with_keypair(&keys);
+    let err = Dispatcher::check_tx(&snapshot, &tx, None).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::TxOutsideValidityWindow).with_any_description()
+    );
+
+    // The same result should be returned if the cache is used.
+    let mut cache = TxCheckCache::new();
+    let err = Dispatcher::check_tx(&snapshot, &tx, Some(&mut cache)).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::TxOutsideValidityWindow).with_any_description()
+    );
+    assert_eq!(cache.next_height, Some(next_height));
+}
@@ -61,6 +61,23 @@ pub enum CoreError {
     IncorrectCall = 14,
     /// Cannot unload artifact.
     CannotUnloadArtifact = 15,
+    /// Transaction cannot be committed at the current blockchain height because of
+    /// its validity window.
+    TxOutsideValidityWindow = 16,
+    /// Transaction has exceeded the limits on consumed resources set in the consensus
+    /// configuration.
+    ResourceLimitExceeded = 17,
+    /// Call cannot be scheduled at the specified height.
+    IncorrectScheduledHeight = 18,
+    /// Scheduled call with the specified identifier does not exist or was scheduled
+    /// by another service.
+    UnknownScheduledCall = 19,
+    /// Requirement declared by an artifact is not matched by any active artifact.
+    UnsatisfiedArtifactDependency = 20,
+    /// Limit on the number of calls scheduled at a single height has been reached.
+    TooManyScheduledCalls = 21,
+    /// Interface required by an artifact is not provided by any active artifact.
+    UnsatisfiedInterfaceRequirement = 22,
 }
 
 impl CoreError {
@@ -16,7 +16,7 @@
 
 use std::fmt;
 
-use super::{CallSite, CallType, ErrorKind, ExecutionError, ExecutionFail};
+use super::{CallSite, CallType, ErrorKind, ErrorPayload, ExecutionError, ExecutionFail};
 
 use crate::runtime::InstanceId;
 
@@ -54,6 +54,7 @@ pub struct ErrorMatch {
     runtime_id: Option<u32>,
     instance_id: Option<InstanceId>,
     call_type: Option<CallType>,
+    payload: Option<ErrorPayload>,
 }
 
 impl ErrorMatch {
@@ -77,6 +78,7 @@ impl ErrorMatch {
             runtime_id: None,
             instance_id: None,
             call_type: None,
+            payload: None,
         }
     }
 
@@ -87,6 +89,7 @@ impl ErrorMatch {
             runtime_id: None,
             instance_id: None,
             call_type: None,
+            payload: None,
         }
     }
 
@@ -128,6 +131,12 @@ impl ErrorMatch {
         self.call_type = Some(call_type);
         self
     }
+
+    /// Accepts an error with the specified payload.
+    pub fn with_payload(mut self, payload: ErrorPayload) -> Self {
+        self.payload = Some(payload);
+        self
+    }
 }
 
 impl PartialEq<ErrorMatch> for ExecutionError {
@@ -148,10 +157,16 @@ impl PartialEq<ErrorMatch> for ExecutionError {
             (Some(match_type), Some(CallSite { call_type, .. })) => match_type == call_type,
             _ => false,
         };
+        let payload_matches = match (&error_match.payload, &self.payload) {
+            (None, _) => true,
+            (Some(match_payload), Some(payload)) => match_payload == payload,
+            _ => false,
+        };
         kind_matches
             && runtime_matches
             && instance_matches
             && call_type_matches
+            && payload_matches
             && error_match.description.matches(&self.description)
     }
 }
@@ -210,6 +225,7 @@ mod tests {
             runtime_id: None,
             instance_id: None,
             call_type: None,
+            payload: None,
         };
         assert_eq!(error, matcher);
 
@@ -28,7 +28,7 @@ use std::{
 };
 
 use super::{
-    execution_status::serde::ExecutionStatus, ErrorKind, ErrorMatch, ExecutionError,
+    execution_status::serde::ExecutionStatus, ErrorKind, ErrorMatch, ErrorPayload, ExecutionError,
     ExecutionErrorAux,
 };
 use crate::{
@@ -46,6 +46,7 @@ impl ExecutionError {
             description: description.into(),
             runtime_id: None,
             call_site: None,
+            payload: None,
         }
     }
 
@@ -107,6 +108,17 @@ impl ExecutionError {
         self
     }
 
+    /// Attaches a structured payload to this error, replacing the previous payload if any.
+    pub fn with_payload(mut self, payload: ErrorPayload) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+
+    /// Returns the structured payload of the error, if any.
+    pub fn payload(&self) -> Option<&ErrorPayload> {
+        self.payload.as_ref()
+    }
+
     /// Returns the call site of the error.
     pub fn call_site(&self) -> Option<&CallSite> {
         self.call_site.as_ref()
@@ -189,6 +201,10 @@ impl ProtobufConvert for ExecutionError {
         } else {
             inner.set_no_call_site(Empty::new());
         }
+
+        if let Some(ref payload) = self.payload {
+            inner.set_payload(payload.to_pb());
+        }
         inner
     }
 
@@ -214,11 +230,18 @@ impl ProtobufConvert for ExecutionError {
             bail!("No call site info or no_call_site marker");
         };
 
+        let payload = if pb.has_payload() {
+            Some(ErrorPayload::from_pb(pb.take_payload())?)
+        } else {
+            None
+        };
+
         Ok(Self {
             kind,
             description: pb.take_description(),
             runtime_id,
             call_site,
+            payload,
         })
     }
 }
@@ -72,7 +72,7 @@ pub mod serde {
 
     use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
-    use crate::runtime::{CallSite, ErrorKind, ExecutionError};
+    use crate::runtime::{CallSite, ErrorKind, ErrorPayload, ExecutionError};
 
     #[derive(Debug, Serialize, Deserialize)]
     #[serde(rename_all = "snake_case")]
@@ -99,6 +99,8 @@ pub mod serde {
         runtime_id: Option<u32>,
         #[serde(skip_serializing_if = "Option::is_none", default)]
         call_site: Option<CallSite>,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        payload: Option<ErrorPayload>,
     }
 
     impl From<Result<(), &ExecutionError>> for ExecutionStatus {
@@ -118,6 +120,7 @@ pub mod serde {
                     code,
                     runtime_id: err.runtime_id,
                     call_site: err.call_site.clone(),
+                    payload: err.payload.clone(),
                 }
             } else {
                 Self {
@@ -126,6 +129,7 @@ pub mod serde {
                     code: None,
                     runtime_id: None,
                     call_site: None,
+                    payload: None,
                 }
             }
         }
@@ -165,6 +169,7 @@ pub mod serde {
                     description: self.description,
                     runtime_id: self.runtime_id,
                     call_site: self.call_site,
+                    payload: self.payload,
                 })
             })
         }
@@ -46,7 +46,7 @@ pub use self::{
 };
 
 use exonum_derive::*;
-use exonum_merkledb::Error as MerkledbError;
+use exonum_merkledb::{BinaryValue, Error as MerkledbError};
 use exonum_proto::ProtobufConvert;
 use thiserror::Error;
 
@@ -55,7 +55,7 @@ use std::{
     panic,
 };
 
-use super::{CallInfo, InstanceId, MethodId};
+use super::{metering::LimitExceeded, CallInfo, InstanceId, MethodId};
 use crate::proto::schema::errors as errors_proto;
 
 /// Trait representing an error type defined in the service or runtime code.
@@ -106,11 +106,15 @@ pub trait ExecutionFail {
 /// code. It *is* possible to inspect the call info for an error that was returned by a service
 /// though.
 ///
-/// The error kind and call info affect the blockchain state hash, while the description does not.
-/// Therefore descriptions are mostly used for developer purposes, not for interaction with users.
+/// - an optional [payload][`ErrorPayload`] with machine-readable error details
+///
+/// The error kind, call info and payload affect the blockchain state hash, while the description
+/// does not. Therefore descriptions are mostly used for developer purposes, not for interaction
+/// with users; the details needed by clients should be placed in the payload.
 ///
 /// [`ErrorKind`]: enum.ErrorKind.html
 /// [`CallSite`]: struct.CallSite.html
+/// [`ErrorPayload`]: struct.ErrorPayload.html
 #[derive(Clone, Debug, Error, BinaryValue)]
 #[cfg_attr(test, derive(PartialEq))]
 // ^-- Comparing `ExecutionError`s directly is error-prone, since the call info is not controlled
@@ -120,6 +124,40 @@ pub struct ExecutionError {
     description: String,
     runtime_id: Option<u32>,
     call_site: Option<CallSite>,
+    payload: Option<ErrorPayload>,
+}
+
+/// Structured payload of an `ExecutionError`.
+///
+/// The payload consists of a value serialized as a `BinaryValue` and a type tag,
+/// which allows clients to select the type to decode the value into. Unlike the error
+/// description, the payload affects the blockchain state hash, and thus is covered by
+/// proofs of error authenticity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(ProtobufConvert, BinaryValue)]
+#[protobuf_convert(source = "errors_proto::ErrorPayload")]
+#[non_exhaustive]
+pub struct ErrorPayload {
+    /// Tag identifying the type of the payload value.
+    pub type_tag: String,
+    /// Serialized payload value.
+    #[serde(with = "crate::runtime::tracing::hex_bytes")]
+    pub value: Vec<u8>,
+}
+
+impl ErrorPayload {
+    /// Creates a payload with the specified type tag and value.
+    pub fn new(type_tag: impl Into<String>, value: impl BinaryValue) -> Self {
+        Self {
+            type_tag: type_tag.into(),
+            value: value.into_bytes(),
+        }
+    }
+
+    /// Decodes the payload value.
+    pub fn decode<T: BinaryValue>(&self) -> anyhow::Result<T> {
+        T::from_bytes(self.value.as_slice().into())
+    }
 }
 
 /// Additional details about an `ExecutionError` that do not influence blockchain state hash.
@@ -136,7 +174,8 @@ pub(crate) struct ExecutionErrorAux {
 /// If the closure panics, it returns an `Unexpected` error with the description derived
 /// from the panic object.
 ///
-/// `merkledb`s are not caught by this method.
+/// `merkledb`s are not caught by this method. Likewise, aborts of calls which have exceeded
+/// resource limits are propagated to the core.
 pub fn catch_panic<F, T>(maybe_panic: F) -> Result<T, ExecutionError>
 where
     F: FnOnce() -> Result<T, ExecutionError>,
@@ -147,8 +186,9 @@ where
         Ok(Err(e)) => Err(e),
         // Panic.
         Err(panic) => {
-            if panic.is::<MerkledbError>() {
-                // Continue panic unwinding if the reason is MerkledbError.
+            if panic.is::<MerkledbError>() || panic.is::<LimitExceeded>() {
+                // Continue panic unwinding if the reason is MerkledbError or the call
+                // is aborted because of exceeded resource limits.
                 panic::resume_unwind(panic);
             }
             Err(ExecutionError::from_panic(panic))
@@ -166,6 +166,57 @@ fn execution_error_object_hash_description() {
     assert_ne!(error_hash(&db, &first_err), error_hash(&db, &second_err));
 }
 
+#[test]
+fn execution_error_object_hash_payload() {
+    let db = TemporaryDB::new();
+    let first_err = ExecutionError::service(1, "foo");
+    let second_err =
+        ExecutionError::service(1, "bar").with_payload(ErrorPayload::new("u64", 5_u64));
+    assert_ne!(error_hash(&db, &first_err), error_hash(&db, &second_err));
+
+    let first_err = first_err.with_payload(ErrorPayload::new("u64", 5_u64));
+    assert_eq!(error_hash(&db, &first_err), error_hash(&db, &second_err));
+
+    let first_err = first_err.with_payload(ErrorPayload::new("u64", 6_u64));
+    assert_ne!(error_hash(&db, &first_err), error_hash(&db, &second_err));
+
+    let first_err = first_err.with_payload(ErrorPayload::new("balance", 5_u64));
+    assert_ne!(error_hash(&db, &first_err), error_hash(&db, &second_err));
+}
+
+#[test]
+fn execution_error_with_payload_round_trip() {
+    let mut err = ExecutionError::service(3, "Insufficient funds")
+        .with_payload(ErrorPayload::new("u64", 1_000_u64));
+    err.set_runtime_id(0)
+        .set_call_site(|| CallSite::new(100, CallType::Constructor));
+
+    let bytes = err.to_bytes();
+    let restored = ExecutionError::from_bytes(bytes.into()).unwrap();
+    assert_eq!(restored, err);
+    assert_eq!(restored.payload().unwrap().decode::<u64>().unwrap(), 1_000);
+    assert_eq!(
+        restored,
+        ExecutionError::service(3, "Insufficient funds")
+            .to_match()
+            .with_payload(ErrorPayload::new("u64", 1_000_u64))
+    );
+    assert_ne!(
+        restored,
+        ExecutionError::service(3, "Insufficient funds")
+            .to_match()
+            .with_payload(ErrorPayload::new("u64", 999_u64))
+    );
+
+    let json = serde_json::to_value(&err).unwrap();
+    assert_eq!(
+        json["payload"],
+        json!({ "type_tag": "u64", "value": hex::encode(1_000_u64.to_bytes()) })
+    );
+    let restored: ExecutionError = serde_json::from_value(json).unwrap();
+    assert_eq!(restored, err);
+}
+
 #[test]
 fn execution_error_display() {
     let mut err = ExecutionError {
@@ -173,6 +224,7 @@ fn execution_error_display() {
         description: String::new(),
         runtime_id: Some(1),
         call_site: Some(CallSite::new(100, CallType::Constructor)),
+        payload: None,
     };
     let err_string = err.to_string();
     assert!(err_string.contains("Execution error with code `service:3`"));
@@ -219,6 +271,7 @@ fn execution_result_serde_presentation() {
         description: "Some error".to_owned(),
         runtime_id: None,
         call_site: None,
+        payload: None,
     }));
     assert_eq!(
         serde_json::to_value(result).unwrap(),
@@ -233,6 +286,7 @@ fn execution_result_serde_presentation() {
         description: String::new(),
         runtime_id: Some(1),
         call_site: Some(CallSite::new(100, CallType::Constructor)),
+        payload: None,
     }));
     assert_eq!(
         serde_json::to_value(result).unwrap(),
@@ -252,6 +306,7 @@ fn execution_result_serde_presentation() {
         description: String::new(),
         runtime_id: Some(1),
         call_site: Some(CallSite::new(100, CallType::Resume)),
+        payload: None,
     }));
     assert_eq!(
         serde_json::to_value(result).unwrap(),
@@ -277,6 +332,7 @@ fn execution_result_serde_presentation() {
                 id: 1,
             },
         )),
+        payload: None,
     }));
     assert_eq!(
         serde_json::to_value(result).unwrap(),
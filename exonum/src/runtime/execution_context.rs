@@ -13,14 +13,17 @@
 // limitations under the License.
 
 use crate::{
-    blockchain::Schema as CoreSchema,
-    crypto::{Hash, PublicKey},
+    blockchain::{CallInBlock, ScheduledCall, Schema as CoreSchema, ServiceEvent},
+    crypto::Hash,
     helpers::{Height, ValidateInput},
     merkledb::{access::Prefixed, BinaryValue, Fork},
     runtime::{
-        migrations::MigrationType, ArtifactId, BlockchainData, CallSite, CallType, Caller,
-        CoreError, Dispatcher, DispatcherSchema, ExecutionError, ExecutionFail, InstanceDescriptor,
-        InstanceId, InstanceQuery, InstanceSpec, MethodId, RuntimeFeature, SUPERVISOR_INSTANCE_ID,
+        metering::{Metered, ResourceMeter},
+        migrations::MigrationType,
+        ArtifactId, ArtifactSpec, BlockchainData, CallSite, CallType, Caller, CoreError,
+        Dispatcher, DispatcherSchema, ExecutionError, ExecutionFail, InstanceDescriptor,
+        InstanceId, InstanceQuery, InstanceSpec, MethodId, ResourceUsage, RuntimeFeature,
+        SUPERVISOR_INSTANCE_ID,
     },
 };
 
@@ -44,34 +47,47 @@ pub struct ExecutionContext<'a> {
     instance: InstanceDescriptor,
     /// Hash of the currently executing transaction, or `None` for non-transaction calls.
     transaction_hash: Option<Hash>,
+    /// Location of the top-level call within the block, or `None` for calls outside of blocks.
+    call_location: Option<CallInBlock>,
+    /// Meter of resources consumed by the top-level call, or `None` if resources
+    /// are not metered.
+    meter: Option<&'a ResourceMeter>,
     /// Reference to the dispatcher.
     dispatcher: &'a Dispatcher,
     /// Depth of the call stack.
     call_stack_depth: u64,
     /// Flag indicating an error occurred during the child call.
     has_child_call_error: &'a mut bool,
+    /// Slot for the value returned by the call, or `None` if the caller does not expect
+    /// a return value (e.g., for transactions).
+    return_value: Option<&'a mut Option<Vec<u8>>>,
 }
 
 impl<'a> ExecutionContext<'a> {
     /// Maximum depth of the call stack.
     pub const MAX_CALL_STACK_DEPTH: u64 = 128;
+    /// Maximum number of calls that can be scheduled at a single height by all services.
+    pub const MAX_SCHEDULED_CALLS_PER_HEIGHT: u64 = 1_024;
 
     pub(crate) fn for_transaction(
         dispatcher: &'a Dispatcher,
         fork: &'a mut Fork,
         has_child_call_error: &'a mut bool,
         instance: InstanceDescriptor,
-        author: PublicKey,
+        caller: Caller,
         transaction_hash: Hash,
+        transaction_index: u32,
     ) -> Self {
-        Self::new(
+        let mut context = Self::new(
             dispatcher,
             fork,
             has_child_call_error,
             instance,
-            Caller::Transaction { author },
+            caller,
             Some(transaction_hash),
-        )
+        );
+        context.call_location = Some(CallInBlock::transaction(transaction_index));
+        context
     }
 
     pub(crate) fn for_block_call(
@@ -90,6 +106,18 @@ impl<'a> ExecutionContext<'a> {
         )
     }
 
+    /// Sets the location of the top-level call within the block.
+    pub(crate) fn with_call_location(mut self, call_location: CallInBlock) -> Self {
+        self.call_location = Some(call_location);
+        self
+    }
+
+    /// Sets the meter of resources consumed by the call.
+    pub(crate) fn with_meter(mut self, meter: &'a ResourceMeter) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+
     fn new(
         dispatcher: &'a Dispatcher,
         fork: &'a mut Fork,
@@ -104,9 +132,12 @@ impl<'a> ExecutionContext<'a> {
             instance,
             caller,
             transaction_hash,
+            call_location: None,
+            meter: None,
             interface_name: "",
             call_stack_depth: 0,
             has_child_call_error,
+            return_value: None,
         }
     }
 
@@ -117,19 +148,45 @@ impl<'a> ExecutionContext<'a> {
     }
 
     /// Provides access to blockchain data.
-    pub fn data(&self) -> BlockchainData<&Fork> {
+    ///
+    /// Storage operations performed via the returned access are charged to the resource meter
+    /// of the call; see [`Metered`] for details.
+    ///
+    /// [`Metered`]: struct.Metered.html
+    pub fn data(&self) -> BlockchainData<Metered<'_, &Fork>> {
         if *self.has_child_call_error {
             panic!(ACCESS_ERROR_STR);
         }
 
-        BlockchainData::new(self.fork, &self.instance.name)
+        let access = Metered::new(&*self.fork, self.meter);
+        BlockchainData::new(access, &self.instance.name)
     }
 
     /// Provides access to the data of the executing service.
-    pub fn service_data(&self) -> Prefixed<&Fork> {
+    pub fn service_data(&self) -> Prefixed<Metered<'_, &Fork>> {
         self.data().for_executing_service()
     }
 
+    /// Returns resources consumed by the top-level call so far, including resources
+    /// consumed by nested calls. If resources are not metered for the call (e.g., for
+    /// calls during service initialization), returns zero usage.
+    pub fn resource_usage(&self) -> ResourceUsage {
+        self.meter
+            .map_or_else(ResourceUsage::default, ResourceMeter::usage)
+    }
+
+    /// Charges the specified amount of fuel to the top-level call. Runtimes executing
+    /// service code in an interpreter use fuel to meter the executed code.
+    ///
+    /// Returns [`CoreError::ResourceLimitExceeded`] if the call has exceeded the `max_call_fuel`
+    /// limit of the consensus config. In this case, the runtime should abort the call and
+    /// return the error. If resources are not metered for the call, the method does nothing.
+    ///
+    /// [`CoreError::ResourceLimitExceeded`]: enum.CoreError.html#variant.ResourceLimitExceeded
+    pub fn charge_fuel(&self, fuel: u64) -> Result<(), ExecutionError> {
+        self.meter.map_or(Ok(()), |meter| meter.charge_fuel(fuel))
+    }
+
     /// Returns the authorization information about this call.
     pub fn caller(&self) -> &Caller {
         &self.caller
@@ -146,6 +203,146 @@ impl<'a> ExecutionContext<'a> {
         core_schema.next_height() == Height(0)
     }
 
+    /// Emits an event with the specified topic and payload on behalf of the executing service.
+    ///
+    /// Events are recorded in the blockchain together with the location of the top-level call
+    /// and can be proven to external clients with an [`EventProof`]. Events are buffered
+    /// until the top-level call completes and are saved only if it succeeds; if the call ends
+    /// with an error, events emitted within it are discarded together with other changes
+    /// to the blockchain state. Each event is charged to the resource meter of the call
+    /// as a storage write of the event topic and payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::IncorrectCall` if the call is not isolated within a block,
+    /// e.g., for constructors of builtin services. Returns `CoreError::ResourceLimitExceeded`
+    /// if the event exceeds the resource limits of the call.
+    ///
+    /// [`EventProof`]: ../blockchain/struct.EventProof.html
+    pub fn emit_event(
+        &mut self,
+        topic: impl Into<String>,
+        payload: impl BinaryValue,
+    ) -> Result<(), ExecutionError> {
+        if *self.has_child_call_error {
+            panic!(ACCESS_ERROR_STR);
+        }
+
+        // Isolated calls within a block are always metered.
+        let meter = match (self.call_location, self.meter) {
+            (Some(_), Some(meter)) => meter,
+            _ => {
+                let msg = "Events can only be emitted within calls isolated in a block";
+                return Err(CoreError::IncorrectCall.with_description(msg));
+            }
+        };
+        let event = ServiceEvent::new(self.instance.id, topic, payload.into_bytes());
+        meter.record_event(event)
+    }
+
+    /// Schedules a call of the method with the specified ID in the default interface
+    /// of the executing service. The call is performed at the start of the block
+    /// at the specified `height`, before `before_transactions` hooks, with
+    /// the [`Caller::Blockchain`] authority. Returns the identifier of the scheduled call,
+    /// which can be used to cancel the call with [`cancel_scheduled_call()`].
+    ///
+    /// The result of the scheduled call is recorded in [`CallRecords`] for the target block
+    /// under [`CallInBlock::ScheduledCall`]. If the current call ends with an error,
+    /// the call is not scheduled.
+    ///
+    /// Scheduling is charged to the resource meter of the call as a storage write
+    /// of the call arguments. At most [`MAX_SCHEDULED_CALLS_PER_HEIGHT`] calls
+    /// may be scheduled at a single height.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::IncorrectScheduledHeight` if `height` is not greater
+    /// than the height of the block being executed, `CoreError::TooManyScheduledCalls`
+    /// if the limit on calls scheduled at `height` is reached, and
+    /// `CoreError::ResourceLimitExceeded` if the call exceeds its resource limits.
+    ///
+    /// [`MAX_SCHEDULED_CALLS_PER_HEIGHT`]: #associatedconstant.MAX_SCHEDULED_CALLS_PER_HEIGHT
+    /// [`Caller::Blockchain`]: enum.Caller.html#variant.Blockchain
+    /// [`cancel_scheduled_call()`]: #method.cancel_scheduled_call
+    /// [`CallRecords`]: ../blockchain/struct.CallRecords.html
+    /// [`CallInBlock::ScheduledCall`]: ../blockchain/enum.CallInBlock.html#variant.ScheduledCall
+    pub fn schedule_call(
+        &mut self,
+        height: Height,
+        method_id: MethodId,
+        arguments: impl BinaryValue,
+    ) -> Result<u64, ExecutionError> {
+        if *self.has_child_call_error {
+            panic!(ACCESS_ERROR_STR);
+        }
+
+        let mut schema = CoreSchema::new(&*self.fork);
+        let current_height = schema.next_height();
+        if height <= current_height {
+            let msg = format!(
+                "Cannot schedule call at {:?}; calls can only be scheduled after the current \
+                 block ({:?})",
+                height, current_height
+            );
+            return Err(CoreError::IncorrectScheduledHeight.with_description(msg));
+        }
+        if schema.scheduled_calls_count(height) >= Self::MAX_SCHEDULED_CALLS_PER_HEIGHT {
+            let msg = format!(
+                "Cannot schedule call at {:?}; the limit of {} calls per height is reached",
+                height,
+                Self::MAX_SCHEDULED_CALLS_PER_HEIGHT
+            );
+            return Err(CoreError::TooManyScheduledCalls.with_description(msg));
+        }
+
+        let arguments = arguments.into_bytes();
+        if let Some(meter) = self.meter {
+            meter.charge_write(arguments.len() as u64)?;
+        }
+        let call = ScheduledCall::new(self.instance.id, method_id, arguments, height);
+        Ok(schema.schedule_call(call))
+    }
+
+    /// Cancels a call with the specified identifier previously scheduled by the executing
+    /// service.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CoreError::UnknownScheduledCall` if the call does not exist (e.g., it was
+    /// already executed or cancelled), or if it was scheduled by another service.
+    pub fn cancel_scheduled_call(&mut self, id: u64) -> Result<(), ExecutionError> {
+        if *self.has_child_call_error {
+            panic!(ACCESS_ERROR_STR);
+        }
+
+        let mut schema = CoreSchema::new(&*self.fork);
+        let is_own_call = schema
+            .scheduled_calls()
+            .get(&id)
+            .map_or(false, |call| call.instance_id == self.instance.id);
+        if !is_own_call {
+            let msg = format!("Service has not scheduled a call with ID {}", id);
+            return Err(CoreError::UnknownScheduledCall.with_description(msg));
+        }
+        if let Some(meter) = self.meter {
+            meter.charge_write(0)?;
+        }
+        schema.remove_scheduled_call(id);
+        Ok(())
+    }
+
+    /// Sets the value returned by the call to the caller.
+    ///
+    /// Return values are delivered only to services making nested calls; a value returned
+    /// from a call initiated outside of the blockchain (e.g., from a transaction)
+    /// is discarded. If the method is called several times, the last value is returned.
+    /// If the call ends with an error, the value is discarded as well.
+    pub fn set_return_value(&mut self, value: impl BinaryValue) {
+        if let Some(slot) = self.return_value.as_mut() {
+            **slot = Some(value.into_bytes());
+        }
+    }
+
     /// Returns an identifier of the service interface required for the call.
     /// This identifier is always empty for the primary service interface.
     ///
@@ -214,11 +411,14 @@ impl<'a> ExecutionContext<'a> {
             fork: &mut *self.fork,
             caller: self.caller.clone(),
             transaction_hash: self.transaction_hash,
+            call_location: self.call_location,
+            meter: self.meter,
             instance,
             interface_name: self.interface_name,
             dispatcher: self.dispatcher,
             call_stack_depth: self.call_stack_depth,
             has_child_call_error: self.has_child_call_error,
+            return_value: None,
         }
     }
 
@@ -230,11 +430,14 @@ impl<'a> ExecutionContext<'a> {
     ///   will not be changed.
     /// - `false` value means that caller authorizes themselves as initiator of child call;
     ///   caller field will be changed to the initiator of this call.
+    ///
+    /// The value returned by the child call is placed into `return_value`.
     fn child_context<'s>(
         &'s mut self,
         interface_name: &'s str,
         instance: InstanceDescriptor,
         fallthrough_auth: bool,
+        return_value: &'s mut Option<Vec<u8>>,
     ) -> ExecutionContext<'s> {
         if *self.has_child_call_error {
             panic!(ACCESS_ERROR_STR);
@@ -251,12 +454,15 @@ impl<'a> ExecutionContext<'a> {
         ExecutionContext {
             caller,
             transaction_hash: self.transaction_hash,
+            call_location: self.call_location,
+            meter: self.meter,
             dispatcher: self.dispatcher,
             instance,
             fork: &mut *self.fork,
             interface_name,
             call_stack_depth: self.call_stack_depth + 1,
             has_child_call_error: self.has_child_call_error,
+            return_value: Some(return_value),
         }
     }
 
@@ -287,9 +493,14 @@ pub trait ExecutionContextUnstable {
     ///
     /// # Return value
     ///
+    /// If the call succeeds, returns the serialized value set by the called service
+    /// via [`set_return_value`], or `None` if the service has not set a return value.
+    ///
     /// If this method returns an error, the error should bubble up to the top level.
     /// In this case do not access the blockchain data through this context methods, this will
     /// lead to panic.
+    ///
+    /// [`set_return_value`]: struct.ExecutionContext.html#method.set_return_value
     fn make_child_call<'q>(
         &mut self,
         called_instance: impl Into<InstanceQuery<'q>>,
@@ -297,7 +508,13 @@ pub trait ExecutionContextUnstable {
         method_id: MethodId,
         arguments: &[u8],
         fallthrough_auth: bool,
-    ) -> Result<(), ExecutionError>;
+    ) -> Result<Option<Vec<u8>>, ExecutionError>;
+
+    /// Marks a successfully completed nested call as failed, e.g., because the value
+    /// returned by the call is malformed. As with errors in nested calls, all changes
+    /// made within the topmost call are rolled back, and accessing the blockchain data
+    /// through this context afterwards will lead to panic.
+    fn reject_child_call(&mut self);
 }
 
 impl ExecutionContextUnstable for ExecutionContext<'_> {
@@ -308,11 +525,14 @@ impl ExecutionContextUnstable for ExecutionContext<'_> {
         method_id: MethodId,
         arguments: &[u8],
         fallthrough_auth: bool,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<Option<Vec<u8>>, ExecutionError> {
         if self.call_stack_depth + 1 >= Self::MAX_CALL_STACK_DEPTH {
             let err = CoreError::stack_overflow(Self::MAX_CALL_STACK_DEPTH);
             return Err(err);
         }
+        if let Some(meter) = self.meter {
+            meter.charge_nested_call()?;
+        }
 
         let descriptor = self
             .dispatcher
@@ -324,8 +544,25 @@ impl ExecutionContextUnstable for ExecutionContext<'_> {
             .runtime_for_service(instance_id)
             .ok_or(CoreError::IncorrectRuntime)?;
 
-        let context = self.child_context(interface_name, descriptor, fallthrough_auth);
-        runtime
+        let meter = self.meter;
+        let mut return_value = None;
+        let context = self.child_context(
+            interface_name,
+            descriptor,
+            fallthrough_auth,
+            &mut return_value,
+        );
+        if let Some(meter) = meter {
+            meter.enter_call(
+                &context.caller,
+                &context.instance,
+                interface_name,
+                method_id,
+                arguments,
+            );
+        }
+
+        let res = runtime
             .execute(context, method_id, arguments)
             .map_err(|mut err| {
                 self.should_rollback();
@@ -339,7 +576,15 @@ impl ExecutionContextUnstable for ExecutionContext<'_> {
                     )
                 });
                 err
-            })
+            });
+        if let Some(meter) = meter {
+            meter.exit_call(&res);
+        }
+        res.map(|()| return_value)
+    }
+
+    fn reject_child_call(&mut self) {
+        self.should_rollback();
     }
 }
 
@@ -355,8 +600,11 @@ impl SupervisorExtensions<'_> {
     /// a requirement for all nodes in the network. A node that did not successfully
     /// deploy the artifact previously blocks until the artifact is deployed successfully.
     /// If a node cannot deploy the artifact, it panics.
-    pub fn start_artifact_registration(&self, artifact: &ArtifactId, spec: Vec<u8>) {
-        Dispatcher::commit_artifact(self.0.fork, artifact, spec);
+    ///
+    /// The caller is responsible for checking that the dependencies of the artifact
+    /// are satisfied; otherwise, this method panics.
+    pub fn start_artifact_registration(&self, spec: ArtifactSpec) {
+        Dispatcher::commit_artifact(self.0.fork, spec);
     }
 
     /// Unloads the specified artifact, making it unavailable for service deployment and other
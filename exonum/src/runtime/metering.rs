@@ -0,0 +1,388 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Accounting of resources consumed by calls to services.
+
+use exonum_merkledb::{
+    access::{AccessObserver, AsReadonly, RawAccess},
+    IndexAddress, ResolvedAddress, Snapshot,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use std::{
+    cell::{Cell, RefCell},
+    mem,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    blockchain::{ConsensusConfig, ServiceEvent},
+    runtime::{Caller, CoreError, ExecutionError, ExecutionFail, InstanceDescriptor, MethodId},
+};
+
+use super::tracing::{CallTrace, CallTracer};
+
+/// Resources consumed by a call, including resources consumed by nested calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ResourceUsage {
+    /// Number of entries read from the storage.
+    pub reads: u64,
+    /// Number of entries written to the storage or removed from it.
+    pub writes: u64,
+    /// Total length of keys and values written to the storage (in bytes).
+    pub bytes_written: u64,
+    /// Number of nested calls to services.
+    pub nested_calls: u64,
+    /// Amount of fuel charged by runtimes (e.g., for executed instructions).
+    pub fuel: u64,
+}
+
+/// Limits on resources consumed by a single transaction. `None` means that the corresponding
+/// resource is not limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ResourceLimits {
+    reads: Option<u64>,
+    writes: Option<u64>,
+    bytes_written: Option<u64>,
+    nested_calls: Option<u64>,
+    fuel: Option<u64>,
+}
+
+/// Limits applied to simulations for the resources not limited by the consensus config.
+const DEFAULT_SIMULATION_LIMITS: ResourceLimits = ResourceLimits {
+    reads: Some(1_000_000),
+    writes: Some(100_000),
+    bytes_written: Some(16 * 1024 * 1024),
+    nested_calls: Some(1_000),
+    fuel: Some(100_000_000),
+};
+
+impl ResourceLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            reads: None,
+            writes: None,
+            bytes_written: None,
+            nested_calls: None,
+            fuel: None,
+        }
+    }
+
+    pub fn for_transactions(config: &ConsensusConfig) -> Self {
+        Self {
+            reads: config.max_call_reads,
+            writes: config.max_call_writes,
+            bytes_written: config.max_call_bytes_written,
+            nested_calls: config.max_nested_calls,
+            fuel: config.max_call_fuel,
+        }
+    }
+
+    /// Returns limits for simulated transactions. Simulations are subject to the same limits
+    /// as transactions; resources not limited by the consensus config (or all resources
+    /// if the config is absent) are limited by the default values.
+    pub fn for_simulations(config: Option<&ConsensusConfig>) -> Self {
+        let limits = config.map_or_else(Self::unlimited, Self::for_transactions);
+        let defaults = DEFAULT_SIMULATION_LIMITS;
+        Self {
+            reads: limits.reads.or(defaults.reads),
+            writes: limits.writes.or(defaults.writes),
+            bytes_written: limits.bytes_written.or(defaults.bytes_written),
+            nested_calls: limits.nested_calls.or(defaults.nested_calls),
+            fuel: limits.fuel.or(defaults.fuel),
+        }
+    }
+}
+
+/// Payload of the panic used to abort a call which has exceeded resource limits.
+///
+/// Unlike other panics, this one is not converted into an error by [`catch_panic`];
+/// it is propagated to the core, which completes the top-level call with the wrapped error.
+///
+/// [`catch_panic`]: ../fn.catch_panic.html
+#[derive(Debug)]
+pub(crate) struct LimitExceeded(ExecutionError);
+
+/// Meter of resources consumed by a top-level call.
+///
+/// The meter is shared by the execution contexts of the top-level call and all its nested calls.
+/// Storage operations are charged via the [`Metered`] access wrapper. The meter also buffers
+/// events emitted during the call, which are saved to the storage once the call succeeds.
+/// Optionally, the meter can record addresses of indexes accessed during the call
+/// and the trace of the call.
+///
+/// [`Metered`]: struct.Metered.html
+#[derive(Debug)]
+pub(crate) struct ResourceMeter {
+    usage: Cell<ResourceUsage>,
+    limits: ResourceLimits,
+    events: RefCell<Vec<ServiceEvent>>,
+    touched_indexes: Option<RefCell<Vec<IndexAddress>>>,
+    tracer: Option<RefCell<CallTracer>>,
+}
+
+impl ResourceMeter {
+    pub fn new(limits: ResourceLimits) -> Self {
+        Self {
+            usage: Cell::new(ResourceUsage::default()),
+            limits,
+            events: RefCell::default(),
+            touched_indexes: None,
+            tracer: None,
+        }
+    }
+
+    /// Enables recording of accessed index addresses.
+    pub fn with_index_tracking(mut self) -> Self {
+        self.touched_indexes = Some(RefCell::new(vec![]));
+        self
+    }
+
+    /// Enables tracing of calls.
+    pub fn with_tracing(mut self) -> Self {
+        self.tracer = Some(RefCell::new(CallTracer::default()));
+        self
+    }
+
+    pub fn usage(&self) -> ResourceUsage {
+        self.usage.get()
+    }
+
+    /// Records the start of a call if tracing is enabled.
+    pub fn enter_call(
+        &self,
+        caller: &Caller,
+        instance: &InstanceDescriptor,
+        interface: &str,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.borrow_mut().enter_call(
+                caller.to_owned(),
+                instance,
+                interface,
+                method_id,
+                arguments,
+            );
+        }
+    }
+
+    /// Records the end of the innermost call if tracing is enabled.
+    pub fn exit_call(&self, result: &Result<(), ExecutionError>) {
+        if let Some(ref tracer) = self.tracer {
+            tracer.borrow_mut().exit_call(result);
+        }
+    }
+
+    /// Returns addresses of indexes accessed during the call in the order of the first access,
+    /// and the trace of the call. If index tracking is not enabled, the returned list is empty;
+    /// if tracing is not enabled, the trace is `None`.
+    pub fn into_records(self) -> (Vec<IndexAddress>, Option<CallTrace>) {
+        let touched_indexes = self
+            .touched_indexes
+            .map(RefCell::into_inner)
+            .unwrap_or_default();
+        let trace = self
+            .tracer
+            .and_then(|tracer| tracer.into_inner().into_trace());
+        (touched_indexes, trace)
+    }
+
+    /// Runs a top-level call metered by this meter.
+    ///
+    /// If the call is aborted because it has exceeded resource limits, returns the corresponding
+    /// error. If the call completes successfully, checks that the consumed resources do not
+    /// exceed the limits; this catches the case when the abort was intercepted by the runtime.
+    pub fn run_call(
+        &self,
+        call: impl FnOnce() -> Result<(), ExecutionError>,
+    ) -> Result<(), ExecutionError> {
+        match panic::catch_unwind(AssertUnwindSafe(call)) {
+            Ok(Ok(())) => self.check(),
+            Ok(Err(err)) => Err(err),
+            Err(payload) => match payload.downcast::<LimitExceeded>() {
+                Ok(abort) => {
+                    let res = Err(abort.0);
+                    // Nested calls interrupted by the abort are not exited in the usual way.
+                    if let Some(ref tracer) = self.tracer {
+                        tracer.borrow_mut().exit_nested_calls(&res);
+                    }
+                    res
+                }
+                Err(payload) => panic::resume_unwind(payload),
+            },
+        }
+    }
+
+    fn update(&self, action: impl FnOnce(&mut ResourceUsage)) {
+        let mut usage = self.usage.get();
+        action(&mut usage);
+        self.usage.set(usage);
+    }
+
+    /// Charges a storage operation, aborting the call if resource limits are exceeded.
+    fn charge(&self, action: impl FnOnce(&mut ResourceUsage)) {
+        self.update(action);
+        if let Err(err) = self.check() {
+            // `resume_unwind` does not invoke the panic hook, so the abort is not logged
+            // as a panic.
+            panic::resume_unwind(Box::new(LimitExceeded(err)));
+        }
+    }
+
+    /// Charges a nested call, returning an error if the limit on nested calls is exceeded.
+    pub fn charge_nested_call(&self) -> Result<(), ExecutionError> {
+        self.update(|usage| usage.nested_calls += 1);
+        self.check()
+    }
+
+    /// Charges an event emitted during the call as a storage write of the event topic
+    /// and payload, and buffers the event. Returns an error if resource limits are exceeded;
+    /// in this case, the event is not buffered.
+    pub fn record_event(&self, event: ServiceEvent) -> Result<(), ExecutionError> {
+        let len = (event.topic.len() + event.payload.len()) as u64;
+        self.charge_write(len)?;
+        self.events.borrow_mut().push(event);
+        Ok(())
+    }
+
+    /// Charges a write to the core schema performed on behalf of the call (e.g., scheduling
+    /// a call), returning an error if resource limits are exceeded.
+    pub fn charge_write(&self, bytes_written: u64) -> Result<(), ExecutionError> {
+        self.update(|usage| {
+            usage.writes += 1;
+            usage.bytes_written = usage.bytes_written.saturating_add(bytes_written);
+        });
+        self.check()
+    }
+
+    /// Takes events buffered during the call in the order of their emission.
+    pub fn take_events(&self) -> Vec<ServiceEvent> {
+        mem::take(&mut *self.events.borrow_mut())
+    }
+
+    /// Charges fuel, returning an error if the limit on fuel is exceeded.
+    pub fn charge_fuel(&self, fuel: u64) -> Result<(), ExecutionError> {
+        self.update(|usage| usage.fuel = usage.fuel.saturating_add(fuel));
+        self.check()
+    }
+
+    /// Checks that the consumed resources do not exceed the limits.
+    pub fn check(&self) -> Result<(), ExecutionError> {
+        let usage = self.usage.get();
+        let limits = self.limits;
+        let checks = [
+            ("storage reads", usage.reads, limits.reads),
+            ("storage writes", usage.writes, limits.writes),
+            ("bytes written", usage.bytes_written, limits.bytes_written),
+            ("nested calls", usage.nested_calls, limits.nested_calls),
+            ("fuel", usage.fuel, limits.fuel),
+        ];
+
+        for &(resource, used, limit) in &checks {
+            if let Some(limit) = limit {
+                if used > limit {
+                    let msg = format!(
+                        "Call has exceeded the limit on {} ({} > {})",
+                        resource, used, limit
+                    );
+                    return Err(CoreError::ResourceLimitExceeded.with_description(msg));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AccessObserver for ResourceMeter {
+    fn on_read(&self, address: &ResolvedAddress, key: &[u8]) {
+        self.charge(|usage| usage.reads += 1);
+        if let Some(ref tracer) = self.tracer {
+            tracer.borrow_mut().record_read(address, key);
+        }
+    }
+
+    fn on_write(&self, address: &ResolvedAddress, key: Option<&[u8]>, bytes: usize) {
+        self.charge(|usage| {
+            usage.writes += 1;
+            usage.bytes_written += bytes as u64;
+        });
+        if let Some(ref tracer) = self.tracer {
+            tracer.borrow_mut().record_write(address, key);
+        }
+    }
+
+    fn on_index_access(&self, address: &IndexAddress) {
+        if let Some(ref touched_indexes) = self.touched_indexes {
+            let mut touched_indexes = touched_indexes.borrow_mut();
+            if !touched_indexes.contains(address) {
+                touched_indexes.push(address.to_owned());
+            }
+        }
+    }
+}
+
+/// Access to the blockchain data which charges storage operations to the resource meter
+/// of the executing call.
+///
+/// Storage operations are charged for all indexes created from this access, including
+/// internal operations of the indexes (e.g., updates of Merkle tree nodes).
+/// If the call exceeds resource limits set in the [`ConsensusConfig`], it is aborted
+/// immediately (i.e., within the storage operation exceeding the limits) and fails with
+/// [`CoreError::ResourceLimitExceeded`]. All changes made by the call are rolled back.
+///
+/// The abort is implemented by unwinding the stack of the call. Services must not
+/// intercept the unwinding (e.g., with `std::panic::catch_unwind`); if they do, the call
+/// still fails once it returns control to the core.
+///
+/// [`ConsensusConfig`]: ../blockchain/struct.ConsensusConfig.html
+/// [`CoreError::ResourceLimitExceeded`]: enum.CoreError.html#variant.ResourceLimitExceeded
+#[derive(Debug, Clone, Copy)]
+pub struct Metered<'a, T> {
+    access: T,
+    meter: Option<&'a ResourceMeter>,
+}
+
+impl<'a, T: RawAccess> Metered<'a, T> {
+    pub(crate) fn new(access: T, meter: Option<&'a ResourceMeter>) -> Self {
+        Self { access, meter }
+    }
+}
+
+impl<T: RawAccess> RawAccess for Metered<'_, T> {
+    type Changes = T::Changes;
+
+    fn snapshot(&self) -> &dyn Snapshot {
+        self.access.snapshot()
+    }
+
+    fn changes(&self, address: &ResolvedAddress) -> Self::Changes {
+        self.access.changes(address)
+    }
+
+    fn observer(&self) -> Option<&dyn AccessObserver> {
+        self.meter.map(|meter| meter as &dyn AccessObserver)
+    }
+}
+
+impl<'a, T: AsReadonly> AsReadonly for Metered<'a, T> {
+    type Readonly = Metered<'a, T::Readonly>;
+
+    fn as_readonly(&self) -> Self::Readonly {
+        Metered::new(self.access.as_readonly(), self.meter)
+    }
+}
@@ -203,6 +203,9 @@
 
 #[doc(hidden)] // re-exported from the `blockchain` module
 pub use self::dispatcher::TxCheckCache;
+pub(crate) use self::{
+    blockchain_data::index_owner, dispatcher::Dispatcher, error::ExecutionErrorAux,
+};
 pub use self::{
     blockchain_data::{BlockchainData, SnapshotExt},
     dispatcher::{
@@ -211,16 +214,17 @@ pub use self::{
     },
     error::{
         catch_panic, CallSite, CallType, CommonError, CoreError, ErrorKind, ErrorMatch,
-        ExecutionError, ExecutionFail, ExecutionStatus,
+        ErrorPayload, ExecutionError, ExecutionFail, ExecutionStatus,
     },
     execution_context::{ExecutionContext, ExecutionContextUnstable, SupervisorExtensions},
+    metering::{Metered, ResourceUsage},
+    tracing::{CallTrace, StorageKey},
     types::{
         AnyTx, ArtifactId, ArtifactSpec, ArtifactState, ArtifactStatus, CallInfo, Caller,
         CallerAddress, InstanceId, InstanceQuery, InstanceSpec, InstanceState, InstanceStatus,
-        MethodId, MigrationStatus,
+        MethodId, MigrationStatus, TxValidity,
     },
 };
-pub(crate) use self::{dispatcher::Dispatcher, error::ExecutionErrorAux};
 
 pub mod migrations;
 pub mod oneshot;
@@ -238,6 +242,8 @@ mod blockchain_data;
 mod dispatcher;
 pub(crate) mod error;
 mod execution_context;
+mod metering;
+mod tracing;
 mod types;
 
 /// Persistent identifier of a supervisor service instance.
@@ -254,6 +260,10 @@ pub enum RuntimeIdentifier {
     Rust = 0,
     /// Exonum Java Binding runtime.
     Java = 1,
+    /// WebAssembly runtime.
+    Wasm = 2,
+    /// Runtime executing services in a separate process.
+    Process = 3,
 }
 
 impl From<RuntimeIdentifier> for u32 {
@@ -267,6 +277,8 @@ impl RuntimeIdentifier {
         match id {
             0 => Ok(Self::Rust),
             1 => Ok(Self::Java),
+            2 => Ok(Self::Wasm),
+            3 => Ok(Self::Process),
             _ => Err(()),
         }
     }
@@ -277,6 +289,8 @@ impl fmt::Display for RuntimeIdentifier {
         match self {
             Self::Rust => formatter.write_str("Rust runtime"),
             Self::Java => formatter.write_str("Java runtime"),
+            Self::Wasm => formatter.write_str("Wasm runtime"),
+            Self::Process => formatter.write_str("Process runtime"),
         }
     }
 }
@@ -288,12 +302,22 @@ pub enum RuntimeFeature {
     /// Freezing services: disabling APIs mutating service state (e.g., transactions)
     /// while leaving read-only APIs switched on.
     FreezingServices,
+    /// Simulating execution of unsigned transactions. If a runtime supports this feature,
+    /// services in this runtime must not rely on the transaction author being authenticated
+    /// when a transaction is simulated.
+    UnsignedSimulation,
+    /// Read-only queries to services via [`Runtime::query()`].
+    ///
+    /// [`Runtime::query()`]: trait.Runtime.html#method.query
+    Queries,
 }
 
 impl fmt::Display for RuntimeFeature {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::FreezingServices => formatter.write_str("freezing services"),
+            Self::UnsignedSimulation => formatter.write_str("unsigned transaction simulation"),
+            Self::Queries => formatter.write_str("read-only queries"),
         }
     }
 }
@@ -339,8 +363,8 @@ impl fmt::Display for RuntimeFeature {
 /// child calls among services; this is denoted as `CALL*` in the excerpt above. The child calls
 /// are executed synchronously. See the [*Service Interaction*] article for more details.
 ///
-/// The ordering for the "read-only" methods `is_artifact_deployed` and `is_supported` in relation
-/// to the lifecycle above is not specified.
+/// The ordering for the "read-only" methods `is_artifact_deployed`, `is_supported` and `query`
+/// in relation to the lifecycle above is not specified.
 ///
 /// # Consensus and Local Methods
 ///
@@ -389,6 +413,16 @@ pub trait Runtime: Send + fmt::Debug + 'static {
         false
     }
 
+    /// Creates a new instance of the runtime with the same artifacts available for deployment,
+    /// but without deployed artifacts or started services. The replica is used to re-execute
+    /// committed blocks on a separate blockchain, e.g., to trace transactions.
+    ///
+    /// The default implementation returns `None`, i.e., signals that the runtime cannot
+    /// be replicated.
+    fn replicate(&self) -> Option<Box<dyn Runtime>> {
+        None
+    }
+
     /// Notifies the runtime that the dispatcher has completed re-initialization after the
     /// node restart. Re-initialization includes restoring the deployed artifacts / started service
     /// instances for all the runtimes.
@@ -616,7 +650,14 @@ pub trait Runtime: Send + fmt::Debug + 'static {
     /// An error returned from this method will lead to the rollback of all changes
     /// in the fork enclosed in the `context`.
     ///
+    /// If the call succeeds, the runtime may pass a value returned by the service method
+    /// to the caller via [`ExecutionContext::set_return_value()`]. The value must be
+    /// serialized in the format expected by the method signature (e.g., as a `BinaryValue`
+    /// for the Rust runtime). Return values are delivered only to services making nested calls;
+    /// values returned from transactions submitted by external clients are discarded.
+    ///
     /// [*Service State Transitions*]: index.html#service-state-transitions
+    /// [`ExecutionContext::set_return_value()`]: struct.ExecutionContext.html#method.set_return_value
     fn execute(
         &self,
         context: ExecutionContext<'_>,
@@ -651,6 +692,31 @@ pub trait Runtime: Send + fmt::Debug + 'static {
     /// in the fork enclosed in the `context`.
     fn after_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError>;
 
+    /// Performs a read-only query to a service instance against the provided `snapshot`
+    /// and returns the serialized result.
+    ///
+    /// The query is identified by the numeric `method_id`; its arguments and return value
+    /// are serialized in the format defined by the runtime (e.g., as `BinaryValue`s
+    /// for the Rust runtime). Queries are dispatched by the core only to services
+    /// providing read access (i.e., active or frozen services), and only if the runtime
+    /// supports [`RuntimeFeature::Queries`].
+    ///
+    /// Queries are local to the node; they are not required to return the same result
+    /// on all nodes and cannot change the blockchain state.
+    ///
+    /// The default implementation returns a `FeatureNotSupported` error.
+    ///
+    /// [`RuntimeFeature::Queries`]: enum.RuntimeFeature.html#variant.Queries
+    fn query(
+        &self,
+        instance: &InstanceDescriptor,
+        method_id: MethodId,
+        arguments: &[u8],
+        snapshot: &dyn Snapshot,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        Err(CommonError::FeatureNotSupported.into())
+    }
+
     /// Notifies the runtime about commit of a new block.
     ///
     /// This method is called *after* all `update_service_status` calls related
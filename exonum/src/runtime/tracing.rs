@@ -0,0 +1,191 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracing of calls to services.
+
+use exonum_merkledb::ResolvedAddress;
+use serde_derive::{Deserialize, Serialize};
+
+use super::{Caller, ExecutionError, ExecutionStatus, InstanceDescriptor, InstanceId, MethodId};
+
+/// Trace of a call to a service, including all nested calls made by the service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct CallTrace {
+    /// Authorization of the call.
+    pub caller: Caller,
+    /// Numeric identifier of the called service instance.
+    pub instance_id: InstanceId,
+    /// Name of the called service instance.
+    pub instance_name: String,
+    /// Name of the called interface. The empty string corresponds to the default
+    /// service interface.
+    pub interface: String,
+    /// Identifier of the called method within the interface.
+    pub method_id: MethodId,
+    /// Serialized call arguments.
+    #[serde(with = "self::hex_bytes")]
+    pub arguments: Vec<u8>,
+    /// Result of the call.
+    pub status: ExecutionStatus,
+    /// Storage keys read by the call, in the order of access. Keys read by nested calls
+    /// are recorded in the corresponding nested traces.
+    pub reads: Vec<StorageKey>,
+    /// Storage keys written or removed by the call, in the order of access.
+    pub writes: Vec<StorageKey>,
+    /// Nested calls made by the service during the call.
+    pub nested_calls: Vec<CallTrace>,
+}
+
+impl CallTrace {
+    fn new(
+        caller: Caller,
+        instance: &InstanceDescriptor,
+        interface: &str,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Self {
+        Self {
+            caller,
+            instance_id: instance.id,
+            instance_name: instance.name.clone(),
+            interface: interface.to_owned(),
+            method_id,
+            arguments: arguments.to_vec(),
+            status: ExecutionStatus::ok(),
+            reads: vec![],
+            writes: vec![],
+            nested_calls: vec![],
+        }
+    }
+}
+
+/// Key of a storage entry accessed during a call.
+///
+/// Storage keys are low-level: the entry is identified by the storage location of the index,
+/// and keys of entries used by the index internally (e.g., Merkle tree nodes) are recorded
+/// along with the keys of user data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct StorageKey {
+    /// Full name of the index. For indexes in a group, this is the name of the group.
+    pub index: String,
+    /// Numeric identifier of the index assigned by the database. The identifier is absent
+    /// for system views (e.g., the pool of index metadata).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_id: Option<u64>,
+    /// Key of the entry within the index, or `None` if the entire index was cleared.
+    #[serde(with = "self::hex_key")]
+    pub key: Option<Vec<u8>>,
+}
+
+impl StorageKey {
+    fn new(address: &ResolvedAddress, key: Option<&[u8]>) -> Self {
+        Self {
+            index: address.name.clone(),
+            index_id: address.id.map(|id| id.get()),
+            key: key.map(<[u8]>::to_vec),
+        }
+    }
+}
+
+/// Recorder of call traces. The recorder maintains the stack of calls being executed;
+/// storage operations are attributed to the innermost call.
+#[derive(Debug, Default)]
+pub(crate) struct CallTracer {
+    stack: Vec<CallTrace>,
+    root: Option<CallTrace>,
+}
+
+impl CallTracer {
+    pub fn enter_call(
+        &mut self,
+        caller: Caller,
+        instance: &InstanceDescriptor,
+        interface: &str,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) {
+        let trace = CallTrace::new(caller, instance, interface, method_id, arguments);
+        self.stack.push(trace);
+    }
+
+    pub fn exit_call(&mut self, result: &Result<(), ExecutionError>) {
+        let mut trace = self
+            .stack
+            .pop()
+            .expect("BUG: exiting call which was not entered");
+        trace.status = result.clone().into();
+        if let Some(parent) = self.stack.last_mut() {
+            parent.nested_calls.push(trace);
+        } else {
+            self.root = Some(trace);
+        }
+    }
+
+    /// Exits all calls except for the top-level one with the specified result.
+    pub fn exit_nested_calls(&mut self, result: &Result<(), ExecutionError>) {
+        while self.stack.len() > 1 {
+            self.exit_call(result);
+        }
+    }
+
+    pub fn record_read(&mut self, address: &ResolvedAddress, key: &[u8]) {
+        if let Some(trace) = self.stack.last_mut() {
+            trace.reads.push(StorageKey::new(address, Some(key)));
+        }
+    }
+
+    pub fn record_write(&mut self, address: &ResolvedAddress, key: Option<&[u8]>) {
+        if let Some(trace) = self.stack.last_mut() {
+            trace.writes.push(StorageKey::new(address, key));
+        }
+    }
+
+    pub fn into_trace(self) -> Option<CallTrace> {
+        self.root
+    }
+}
+
+pub(super) mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'a, D: Deserializer<'a>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        hex::decode(hex).map_err(D::Error::custom)
+    }
+}
+
+mod hex_key {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        key: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        key.as_ref().map(hex::encode).serialize(serializer)
+    }
+
+    pub fn deserialize<'a, D: Deserializer<'a>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|hex| hex::decode(hex).map_err(D::Error::custom))
+            .transpose()
+    }
+}
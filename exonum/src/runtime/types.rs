@@ -29,12 +29,18 @@ use serde_derive::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     fmt::{self, Display},
+    slice,
     str::FromStr,
 };
 
-use super::InstanceDescriptor;
+use super::{
+    versioning::{ArtifactReq, InterfaceReq, ProvidedInterface},
+    InstanceDescriptor,
+};
 use crate::{
-    blockchain::config::InstanceInitParams, helpers::ValidateInput, messages::Verified,
+    blockchain::config::InstanceInitParams,
+    helpers::{Height, ValidateInput},
+    messages::Verified,
     proto::schema,
 };
 
@@ -99,14 +105,17 @@ impl CallInfo {
 /// ```
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Debug)]
 #[derive(Serialize, Deserialize)]
-#[derive(ProtobufConvert, BinaryValue)]
-#[protobuf_convert(source = "schema::base::AnyTx")]
+#[derive(BinaryValue)]
 #[non_exhaustive]
 pub struct AnyTx {
     /// Information required for the call of the corresponding executor.
     pub call_info: CallInfo,
     /// Serialized transaction arguments.
     pub arguments: Vec<u8>,
+    /// Window of blockchain heights in which the transaction may be committed.
+    /// By default, the window is unbounded.
+    #[serde(default, skip_serializing_if = "TxValidity::is_unbounded")]
+    pub validity: TxValidity,
 }
 
 impl AnyTx {
@@ -115,9 +124,16 @@ impl AnyTx {
         Self {
             call_info,
             arguments,
+            validity: TxValidity::default(),
         }
     }
 
+    /// Restricts heights at which the transaction may be committed.
+    pub fn with_validity(mut self, validity: TxValidity) -> Self {
+        self.validity = validity;
+        self
+    }
+
     /// Signs a transaction with the specified Ed25519 keys.
     pub fn sign(self, public_key: PublicKey, secret_key: &SecretKey) -> Verified<Self> {
         Verified::from_value(self, public_key, secret_key)
@@ -128,12 +144,180 @@ impl AnyTx {
         Verified::from_value(self, keypair.public_key(), keypair.secret_key())
     }
 
+    /// Signs a transaction by the author and cosigners. The transaction will be authorized
+    /// as [`Caller::Multisig`] if there is at least one cosigner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of cosigners exceeds [`SignedMessage::MAX_COSIGNATURES`],
+    /// or if any key is specified more than once.
+    ///
+    /// [`Caller::Multisig`]: enum.Caller.html#variant.Multisig
+    /// [`SignedMessage::MAX_COSIGNATURES`]: ../messages/struct.SignedMessage.html#associatedconstant.MAX_COSIGNATURES
+    pub fn sign_with_cosigners(self, author: &KeyPair, cosigners: &[KeyPair]) -> Verified<Self> {
+        Verified::from_value_with_cosigners(self, author, cosigners)
+    }
+
     /// Parse transaction arguments as a specific type.
     pub fn parse<T: BinaryValue>(&self) -> anyhow::Result<T> {
         T::from_bytes(Cow::Borrowed(&self.arguments))
     }
 }
 
+impl ProtobufConvert for AnyTx {
+    type ProtoStruct = schema::base::AnyTx;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_call_info(self.call_info.to_pb());
+        pb.set_arguments(self.arguments.clone());
+        // The validity window is omitted if it is unbounded, so that transactions
+        // without a window are serialized in the same way as before its introduction.
+        if !self.validity.is_unbounded() {
+            pb.set_validity(self.validity.to_pb());
+        }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        let call_info = CallInfo::from_pb(pb.take_call_info())?;
+        let validity = if pb.has_validity() {
+            TxValidity::from_pb(pb.take_validity())?
+        } else {
+            TxValidity::default()
+        };
+        Ok(Self {
+            call_info,
+            arguments: pb.take_arguments(),
+            validity,
+        })
+    }
+}
+
+/// Window of blockchain heights in which a transaction may be committed.
+///
+/// Both bounds of the window are inclusive and optional. A transaction with a window ending
+/// before the next blockchain height is rejected by [`Blockchain::check_tx`]; a transaction
+/// with a window starting after the next height passes the check, but is not included
+/// into blocks by honest validators until the window starts. Execution of a transaction
+/// outside of its window fails with [`CoreError::TxOutsideValidityWindow`]. Thus, a transaction
+/// with a bounded window cannot be replayed after the window ends.
+///
+/// In the serialized form, an absent bound is represented as zero. Since the genesis block
+/// does not contain transactions, zero bounds are meaningless; a window with such a bound
+/// is invalid and is rejected by the core.
+///
+/// [`Blockchain::check_tx`]: ../blockchain/struct.Blockchain.html#method.check_tx
+/// [`CoreError::TxOutsideValidityWindow`]: enum.CoreError.html#variant.TxOutsideValidityWindow
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::{helpers::Height, runtime::{AnyTx, CallInfo, TxValidity}};
+/// let validity = TxValidity::default()
+///     .with_valid_from(Height(10))
+///     .with_valid_until(Height(20));
+/// assert!(validity.contains(Height(10)));
+/// assert!(validity.contains(Height(20)));
+/// assert!(!validity.contains(Height(21)));
+/// assert!(!validity.is_expired_at(Height(5)));
+/// assert!(validity.is_expired_at(Height(21)));
+///
+/// let tx = AnyTx::new(CallInfo::new(100, 0), vec![]).with_validity(validity);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct TxValidity {
+    /// The earliest height of a block the transaction may be committed in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<Height>,
+    /// The latest height of a block the transaction may be committed in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<Height>,
+}
+
+impl TxValidity {
+    /// Sets the earliest height of a block the transaction may be committed in.
+    ///
+    /// # Panics
+    ///
+    /// - If `height` is zero.
+    pub fn with_valid_from(mut self, height: Height) -> Self {
+        assert_ne!(height, Height(0), "Validity window bounds must be positive");
+        self.valid_from = Some(height);
+        self
+    }
+
+    /// Sets the latest height of a block the transaction may be committed in.
+    ///
+    /// # Panics
+    ///
+    /// - If `height` is zero.
+    pub fn with_valid_until(mut self, height: Height) -> Self {
+        assert_ne!(height, Height(0), "Validity window bounds must be positive");
+        self.valid_until = Some(height);
+        self
+    }
+
+    /// Checks whether the window has neither of bounds.
+    pub fn is_unbounded(&self) -> bool {
+        self.valid_from.is_none() && self.valid_until.is_none()
+    }
+
+    /// Checks whether a transaction with this window may be committed in a block
+    /// with the specified height.
+    pub fn contains(&self, height: Height) -> bool {
+        let after_start = self.valid_from.map_or(true, |from| from <= height);
+        let before_end = self.valid_until.map_or(true, |until| height <= until);
+        after_start && before_end
+    }
+
+    /// Checks whether the window has ended before the specified height, i.e., whether
+    /// a transaction with this window can no longer be committed at `height` or later.
+    pub fn is_expired_at(&self, height: Height) -> bool {
+        self.valid_until.map_or(false, |until| until < height)
+    }
+}
+
+impl ValidateInput for TxValidity {
+    type Error = anyhow::Error;
+
+    /// Checks that the window bounds are positive. Zero bounds cannot be distinguished
+    /// from absent ones in the serialized form of the window.
+    fn validate(&self) -> Result<(), Self::Error> {
+        if self.valid_from == Some(Height(0)) || self.valid_until == Some(Height(0)) {
+            bail!("Validity window bounds must be positive");
+        }
+        Ok(())
+    }
+}
+
+impl ProtobufConvert for TxValidity {
+    type ProtoStruct = schema::base::TxValidity;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_valid_from(self.valid_from.map_or(0, |height| height.0));
+        pb.set_valid_until(self.valid_until.map_or(0, |height| height.0));
+        pb
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        let bound = |value: u64| {
+            if value == 0 {
+                None
+            } else {
+                Some(Height(value))
+            }
+        };
+        Ok(Self {
+            valid_from: bound(pb.get_valid_from()),
+            valid_until: bound(pb.get_valid_until()),
+        })
+    }
+}
+
 /// The artifact identifier is required to construct service instances.
 /// In other words, an artifact identifier is similar to a class name, and a specific service
 /// instance is similar to a class instance.
@@ -293,6 +477,23 @@ pub struct ArtifactSpec {
     pub artifact: ArtifactId,
     /// Runtime-specific artifact payload.
     pub payload: Vec<u8>,
+    /// Artifacts required by this artifact.
+    ///
+    /// The dispatcher checks that each requirement is matched by an active artifact
+    /// when the artifact is deployed and when services are instantiated from it.
+    /// Conversely, an artifact cannot be unloaded while it is the only match
+    /// for a requirement of another deployed artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ArtifactReq>,
+    /// Interfaces required by this artifact.
+    ///
+    /// Each requirement must be matched by an interface in `provided_interfaces`
+    /// of an active artifact. Requirements are checked in the same cases as `dependencies`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_interfaces: Vec<InterfaceReq>,
+    /// Interfaces provided by this artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provided_interfaces: Vec<ProvidedInterface>,
 }
 
 impl ArtifactSpec {
@@ -301,8 +502,38 @@ impl ArtifactSpec {
         Self {
             artifact,
             payload: deploy_spec.into_bytes(),
+            dependencies: Vec::new(),
+            required_interfaces: Vec::new(),
+            provided_interfaces: Vec::new(),
         }
     }
+
+    /// Adds requirements on other artifacts to this specification.
+    pub fn with_dependencies(
+        mut self,
+        dependencies: impl IntoIterator<Item = ArtifactReq>,
+    ) -> Self {
+        self.dependencies.extend(dependencies);
+        self
+    }
+
+    /// Adds requirements on interfaces provided by other artifacts to this specification.
+    pub fn with_required_interfaces(
+        mut self,
+        interfaces: impl IntoIterator<Item = InterfaceReq>,
+    ) -> Self {
+        self.required_interfaces.extend(interfaces);
+        self
+    }
+
+    /// Adds interfaces provided by the artifact to this specification.
+    pub fn with_provided_interfaces(
+        mut self,
+        interfaces: impl IntoIterator<Item = ProvidedInterface>,
+    ) -> Self {
+        self.provided_interfaces.extend(interfaces);
+        self
+    }
 }
 
 /// Exhaustive service instance specification.
@@ -653,14 +884,26 @@ pub struct ArtifactState {
     pub deploy_spec: Vec<u8>,
     /// Artifact deployment status.
     pub status: ArtifactStatus,
+    /// Artifacts required by this artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ArtifactReq>,
+    /// Interfaces required by this artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_interfaces: Vec<InterfaceReq>,
+    /// Interfaces provided by this artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provided_interfaces: Vec<ProvidedInterface>,
 }
 
 impl ArtifactState {
     /// Creates an artifact state with the given specification and status.
-    pub(super) fn new(deploy_spec: Vec<u8>, status: ArtifactStatus) -> Self {
+    pub(super) fn new(spec: ArtifactSpec, status: ArtifactStatus) -> Self {
         Self {
-            deploy_spec,
+            deploy_spec: spec.payload,
             status,
+            dependencies: spec.dependencies,
+            required_interfaces: spec.required_interfaces,
+            provided_interfaces: spec.provided_interfaces,
         }
     }
 }
@@ -844,7 +1087,7 @@ impl ProtobufConvert for MigrationStatus {
 /// balance).
 ///
 /// Authorization info is not purely determined by the call stack. While outermost
-/// transactions calls always have `Transaction` auth, services may make internal
+/// transactions calls always have `Transaction` or `Multisig` auth, services may make internal
 /// calls, which either inherit the parent authorization or authorize a child call in their
 /// name (`Service` auth). This is decided by the service; both kinds of auth may make sense
 /// depending on the use case. Inherited auth makes sense for "middleware" (e.g.,
@@ -856,6 +1099,7 @@ impl ProtobufConvert for MigrationStatus {
 /// or index callers without the necessity to care about all possible kinds of authorization
 /// supported by the framework.
 #[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize)]
 #[derive(BinaryValue, ObjectHash)]
 #[non_exhaustive]
 pub enum Caller {
@@ -876,10 +1120,37 @@ pub enum Caller {
     /// This kind of authorization is used for `before_transactions` / `after_transactions`
     /// calls to the service instances, and for initialization of the built-in services.
     Blockchain,
+
+    /// A transaction from the Exonum client authorized by several key pairs, i.e.,
+    /// a transaction with [cosignatures].
+    ///
+    /// [cosignatures]: ../messages/struct.Cosignature.html
+    Multisig {
+        /// Public keys of all signers of the transaction (including its author),
+        /// sorted in the ascending order.
+        signers: Vec<PublicKey>,
+    },
 }
 
 impl Caller {
+    /// Returns authorization for the specified transaction. Transactions without cosignatures
+    /// are authorized as `Transaction`, and transactions with cosignatures as `Multisig`.
+    pub(crate) fn for_transaction(tx: &Verified<AnyTx>) -> Self {
+        let mut signers = tx.signers();
+        if signers.len() == 1 {
+            Self::Transaction { author: signers[0] }
+        } else {
+            signers.sort();
+            Self::Multisig { signers }
+        }
+    }
+
     /// Returns the author's public key, if it exists.
+    ///
+    /// Note that this method returns `None` for `Multisig` callers; use [`signers()`]
+    /// to get information about the signers of such transactions.
+    ///
+    /// [`signers()`]: #method.signers
     pub fn author(&self) -> Option<PublicKey> {
         if let Self::Transaction { author } = self {
             Some(*author)
@@ -888,6 +1159,35 @@ impl Caller {
         }
     }
 
+    /// Returns public keys of all signers of the transaction, or `None` if the call
+    /// is not authorized by a transaction. For transactions with a single signature,
+    /// the returned slice consists of the author's key.
+    pub fn signers(&self) -> Option<&[PublicKey]> {
+        match self {
+            Self::Transaction { author } => Some(slice::from_ref(author)),
+            Self::Multisig { signers } => Some(signers),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the call is authorized by signatures of at least `threshold` keys
+    /// from `keys`. This allows to enforce m-of-n authorization policies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use exonum::{crypto::KeyPair, runtime::Caller};
+    /// let keys: Vec<_> = (0..3).map(|_| KeyPair::random().public_key()).collect();
+    /// let caller = Caller::Transaction { author: keys[0] };
+    /// assert!(caller.is_signed_by(&keys, 1));
+    /// assert!(!caller.is_signed_by(&keys, 2));
+    /// ```
+    pub fn is_signed_by(&self, keys: &[PublicKey], threshold: usize) -> bool {
+        let signers = self.signers().unwrap_or_default();
+        let count = signers.iter().filter(|key| keys.contains(key)).count();
+        count >= threshold
+    }
+
     /// Tries to reinterpret the caller as a service.
     pub fn as_service(&self) -> Option<InstanceId> {
         if let Self::Service { instance_id } = self {
@@ -925,6 +1225,11 @@ impl ProtobufConvert for Caller {
             Self::Transaction { author } => pb.set_transaction_author(author.to_pb()),
             Self::Service { instance_id } => pb.set_instance_id(*instance_id),
             Self::Blockchain => pb.set_blockchain(Empty::new()),
+            Self::Multisig { signers } => {
+                let mut multisig = schema::auth::MultisigSigners::new();
+                multisig.set_keys(signers.to_pb().into());
+                pb.set_multisig(multisig);
+            }
         }
         pb
     }
@@ -939,6 +1244,9 @@ impl ProtobufConvert for Caller {
             }
         } else if pb.has_blockchain() {
             Self::Blockchain
+        } else if pb.has_multisig() {
+            let signers = Vec::<PublicKey>::from_pb(pb.take_multisig().take_keys().into_vec())?;
+            Self::Multisig { signers }
         } else {
             bail!("No variant specified for `Caller`");
         })
@@ -1169,4 +1477,79 @@ mod tests {
         assert_ne!(supervisor_addr.0, crypto::hash(&[]));
         assert_ne!(blockchain_addr, supervisor_addr);
     }
+
+    #[test]
+    fn tx_validity_contains() {
+        let validity = TxValidity::default();
+        assert!(validity.is_unbounded());
+        assert!(validity.contains(Height(0)));
+        assert!(validity.contains(Height(u64::max_value())));
+
+        let validity = TxValidity::default().with_valid_from(Height(5));
+        assert!(!validity.is_unbounded());
+        assert!(!validity.contains(Height(4)));
+        assert!(validity.contains(Height(5)));
+        assert!(validity.contains(Height(1_000)));
+
+        let validity = validity.with_valid_until(Height(10));
+        assert!(validity.contains(Height(10)));
+        assert!(!validity.contains(Height(11)));
+
+        assert!(validity.validate().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Validity window bounds must be positive")]
+    fn tx_validity_with_zero_bound() {
+        TxValidity::default().with_valid_until(Height(0));
+    }
+
+    /// Zero bounds cannot be round-tripped via Protobuf, so they are considered invalid.
+    #[test]
+    fn tx_validity_zero_bounds_are_invalid() {
+        let mut validity = TxValidity::default();
+        validity.valid_until = Some(Height(0));
+        assert!(validity.validate().is_err());
+        assert_eq!(
+            TxValidity::from_pb(validity.to_pb()).unwrap(),
+            TxValidity::default()
+        );
+
+        let validity: TxValidity = serde_json::from_value(json!({ "valid_from": 0 })).unwrap();
+        assert!(validity.validate().is_err());
+    }
+
+    /// Transactions without a validity window must be serialized in the same way
+    /// as before the window was introduced.
+    #[test]
+    fn any_tx_without_validity_serialization() {
+        let tx = AnyTx::new(CallInfo::new(100, 1), vec![1, 2, 3]);
+        let pb = tx.to_pb();
+        assert!(!pb.has_validity());
+        assert_eq!(AnyTx::from_pb(pb).unwrap(), tx);
+        assert_eq!(
+            serde_json::to_value(&tx).unwrap(),
+            json!({
+                "call_info": { "instance_id": 100, "method_id": 1 },
+                "arguments": [1, 2, 3],
+            })
+        );
+
+        let validity = TxValidity::default().with_valid_until(Height(10));
+        let tx = tx.with_validity(validity);
+        let pb = tx.to_pb();
+        assert!(pb.has_validity());
+        assert_eq!(AnyTx::from_pb(pb).unwrap(), tx);
+        assert_eq!(
+            serde_json::to_value(&tx).unwrap(),
+            json!({
+                "call_info": { "instance_id": 100, "method_id": 1 },
+                "arguments": [1, 2, 3],
+                "validity": { "valid_until": 10 },
+            })
+        );
+        let tx_from_json: AnyTx =
+            serde_json::from_value(serde_json::to_value(&tx).unwrap()).unwrap();
+        assert_eq!(tx_from_json, tx);
+    }
 }
@@ -167,11 +167,16 @@
 pub use semver::{Version, VersionReq};
 
 use anyhow::format_err;
+use exonum_proto::ProtobufConvert;
+use serde_derive::{Deserialize, Serialize};
 use thiserror::Error;
 
 use std::{fmt, str::FromStr};
 
-use crate::runtime::{ArtifactId, CoreError, ExecutionError, ExecutionFail};
+use crate::{
+    proto::schema,
+    runtime::{ArtifactId, CoreError, ExecutionError, ExecutionFail},
+};
 
 /// Requirement on an artifact. Can be matched against artifact identifiers.
 ///
@@ -207,12 +212,16 @@ use crate::runtime::{ArtifactId, CoreError, ExecutionError, ExecutionFail};
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert)]
+#[protobuf_convert(source = "schema::base::ArtifactReq")]
 #[non_exhaustive]
 pub struct ArtifactReq {
     /// Artifact name.
     pub name: String,
     /// Allowed artifact versions.
+    #[protobuf_convert(with = "crate::helpers::pb_version_req")]
     pub version: VersionReq,
 }
 
@@ -263,6 +272,134 @@ impl fmt::Display for ArtifactReq {
     }
 }
 
+/// Interface provided by an artifact, together with the version of the interface.
+///
+/// Interfaces are versioned separately from the artifacts implementing them, so that
+/// several artifacts (e.g., different token implementations) may provide the same interface.
+/// The interface version should follow semantic versioning w.r.t. changes in the interface.
+/// Interfaces provided by an artifact are declared in its [`ArtifactSpec`] and are matched
+/// against [`InterfaceReq`]s of other artifacts.
+///
+/// [`ArtifactSpec`]: ../struct.ArtifactSpec.html
+/// [`InterfaceReq`]: struct.InterfaceReq.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert)]
+#[protobuf_convert(source = "schema::base::ProvidedInterface")]
+#[non_exhaustive]
+pub struct ProvidedInterface {
+    /// Interface name.
+    pub name: String,
+    /// Interface version.
+    #[protobuf_convert(with = "crate::helpers::pb_version")]
+    pub version: Version,
+}
+
+impl ProvidedInterface {
+    /// Creates a new provided interface.
+    pub fn new(name: impl Into<String>, version: Version) -> Self {
+        Self {
+            name: name.into(),
+            version,
+        }
+    }
+}
+
+impl FromStr for ProvidedInterface {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.splitn(2, '@').collect();
+        match &parts[..] {
+            [name, version] if !name.is_empty() => {
+                Ok(Self::new((*name).to_string(), version.parse()?))
+            }
+            _ => Err(format_err!(
+                "Invalid interface declaration. Use `name@version` format, \
+                 e.g., `exonum.Token@1.3.0`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ProvidedInterface {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}@{}", self.name, self.version)
+    }
+}
+
+/// Requirement on an interface. Can be matched against interfaces provided by artifacts.
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::runtime::versioning::{InterfaceReq, ProvidedInterface};
+/// # fn main() -> anyhow::Result<()> {
+/// // Requirements can be parsed from a string.
+/// let req: InterfaceReq = "exonum.Token@^1.3.0".parse()?;
+///
+/// let interface: ProvidedInterface = "exonum.Token@1.5.0".parse()?;
+/// assert!(req.matches(&interface));
+/// // This interface version is outdated.
+/// let outdated_interface: ProvidedInterface = "exonum.Token@1.2.0".parse()?;
+/// assert!(!req.matches(&outdated_interface));
+/// // This interface has wrong name.
+/// let other_interface: ProvidedInterface = "exonum.Oracle@1.5.0".parse()?;
+/// assert!(!req.matches(&other_interface));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[derive(ProtobufConvert)]
+#[protobuf_convert(source = "schema::base::InterfaceReq")]
+#[non_exhaustive]
+pub struct InterfaceReq {
+    /// Interface name.
+    pub name: String,
+    /// Allowed interface versions.
+    #[protobuf_convert(with = "crate::helpers::pb_version_req")]
+    pub version: VersionReq,
+}
+
+impl InterfaceReq {
+    /// Creates a new interface requirement.
+    pub fn new(name: impl Into<String>, version: VersionReq) -> Self {
+        Self {
+            name: name.into(),
+            version,
+        }
+    }
+
+    /// Checks whether the provided interface matches this requirement.
+    pub fn matches(&self, interface: &ProvidedInterface) -> bool {
+        interface.name == self.name && self.version.matches(&interface.version)
+    }
+}
+
+impl FromStr for InterfaceReq {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.splitn(2, '@').collect();
+        match &parts[..] {
+            [name, version] if !name.is_empty() => {
+                Ok(Self::new((*name).to_string(), version.parse()?))
+            }
+            _ => Err(format_err!(
+                "Invalid interface requirement. Use `name@version` format, \
+                 e.g., `exonum.Token@^1.3.0`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for InterfaceReq {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}@{}", self.name, self.version)
+    }
+}
+
 /// Versioned object that checks compatibility with the artifact of a service.
 ///
 /// # Examples
@@ -360,3 +497,22 @@ fn artifact_req_parsing() {
     assert_eq!(req.version, "^1.0.5".parse().unwrap());
     assert_eq!(req.to_string(), "exonum.Token@^1.0.5");
 }
+
+#[test]
+fn interface_req_parsing() {
+    use pretty_assertions::assert_eq;
+
+    let req: InterfaceReq = "exonum.Token@^1.0.5".parse().unwrap();
+    assert_eq!(req.name, "exonum.Token");
+    assert_eq!(req.version, "^1.0.5".parse().unwrap());
+    assert_eq!(req.to_string(), "exonum.Token@^1.0.5");
+
+    let interface: ProvidedInterface = "exonum.Token@1.1.0".parse().unwrap();
+    assert_eq!(interface.version, Version::new(1, 1, 0));
+    assert_eq!(interface.to_string(), "exonum.Token@1.1.0");
+    assert!(req.matches(&interface));
+
+    assert!("exonum.Token".parse::<InterfaceReq>().is_err());
+    assert!("@^1.0".parse::<InterfaceReq>().is_err());
+    assert!("exonum.Token@^1.0".parse::<ProvidedInterface>().is_err());
+}
@@ -0,0 +1,74 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Child process providing a key-value storage service. The process communicates
+//! with the node via its standard input and output; to use it, create the runtime
+//! with `Endpoint::Command(Command::new("path/to/child"))`.
+//!
+//! The service artifact is `3:key-value:1.0.0`. Arguments of the `put` method (ID 0)
+//! are a 1-byte key length followed by the key and the value; the `remove`
+//! method (ID 1) accepts the key as is.
+
+use exonum::runtime::{
+    versioning::Version, ArtifactId, CommonError, ExecutionError, MethodId, WellKnownRuntime,
+};
+use exonum_process_runtime::{
+    child::{CallContext, ChildRuntime, ChildService},
+    ProcessRuntime,
+};
+
+const INDEX_NAME: &str = "entries";
+
+#[derive(Debug)]
+struct KeyValueService;
+
+impl KeyValueService {
+    fn put(context: &mut CallContext<'_>, arguments: &[u8]) -> Result<(), ExecutionError> {
+        let (&key_len, rest) = arguments
+            .split_first()
+            .ok_or_else(|| ExecutionError::service(0, "Empty arguments"))?;
+        let key_len = usize::from(key_len);
+        if rest.len() < key_len {
+            return Err(ExecutionError::service(0, "Key is too short"));
+        }
+        let (key, value) = rest.split_at(key_len);
+        context.put(INDEX_NAME, key, value.to_vec())
+    }
+}
+
+impl ChildService for KeyValueService {
+    fn execute(
+        &self,
+        context: &mut CallContext<'_>,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Result<(), ExecutionError> {
+        match method_id {
+            0 => Self::put(context, arguments),
+            1 => context.remove(INDEX_NAME, arguments),
+            _ => Err(CommonError::NoSuchMethod.into()),
+        }
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let artifact = ArtifactId::from_raw_parts(
+        ProcessRuntime::ID,
+        "key-value".to_owned(),
+        Version::new(1, 0, 0),
+    );
+    ChildRuntime::new()
+        .with_service(artifact, KeyValueService)
+        .serve_stdio()
+}
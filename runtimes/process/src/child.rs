@@ -0,0 +1,394 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reference implementation of the child process in Rust.
+//!
+//! A child process registers the services it provides in a [`ChildRuntime`]
+//! and serves requests of the node with [`serve_stdio()`] or [`serve()`].
+//! Each service is associated with an artifact; the child can only deploy artifacts
+//! with registered services. Deploy specifications of artifacts are ignored.
+//!
+//! Services are stateless: all service data should be stored via the [`CallContext`].
+//! Panics in the service code are reported to the node as `Unexpected` errors.
+//! Note that if the child communicates with the node via the standard output, services
+//! must not write to it.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use exonum::runtime::{
+//!     versioning::Version, ArtifactId, CommonError, ExecutionError, MethodId, WellKnownRuntime,
+//! };
+//! use exonum_process_runtime::{
+//!     child::{CallContext, ChildRuntime, ChildService},
+//!     ProcessRuntime,
+//! };
+//!
+//! /// Service storing the last value passed to it.
+//! struct Storage;
+//!
+//! impl ChildService for Storage {
+//!     fn execute(
+//!         &self,
+//!         context: &mut CallContext<'_>,
+//!         method_id: MethodId,
+//!         arguments: &[u8],
+//!     ) -> Result<(), ExecutionError> {
+//!         match method_id {
+//!             0 => context.put("values", b"last", arguments.to_vec()),
+//!             _ => Err(CommonError::NoSuchMethod.into()),
+//!         }
+//!     }
+//! }
+//!
+//! # fn main() -> std::io::Result<()> {
+//! let artifact = ArtifactId::from_raw_parts(
+//!     ProcessRuntime::ID,
+//!     "storage".to_owned(),
+//!     Version::new(1, 0, 0),
+//! );
+//! ChildRuntime::new()
+//!     .with_service(artifact, Storage)
+//!     .serve_stdio()
+//! # }
+//! ```
+//!
+//! [`ChildRuntime`]: struct.ChildRuntime.html
+//! [`serve_stdio()`]: struct.ChildRuntime.html#method.serve_stdio
+//! [`serve()`]: struct.ChildRuntime.html#method.serve
+//! [`CallContext`]: struct.CallContext.html
+
+use exonum::{
+    crypto::PublicKey,
+    helpers::Height,
+    runtime::{
+        catch_panic, ArtifactId, CallType, CommonError, ErrorKind, ExecutionError, ExecutionFail,
+        InstanceSpec, MethodId,
+    },
+};
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io::{self, BufReader, BufWriter, Read, Write},
+    sync::Arc,
+};
+
+use crate::protocol::{self, Request, Response, ServiceCall, StorageRequest, StorageResponse};
+
+/// Service executed in the child process.
+///
+/// All methods except for `execute` have default implementations, which do nothing.
+pub trait ChildService: Send + Sync {
+    /// Initializes a new service instance with the given parameters.
+    fn initialize(
+        &self,
+        _context: &mut CallContext<'_>,
+        _params: &[u8],
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    /// Resumes a previously stopped service instance with the given parameters.
+    fn resume(&self, _context: &mut CallContext<'_>, _params: &[u8]) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    /// Executes a method of the default service interface.
+    fn execute(
+        &self,
+        context: &mut CallContext<'_>,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Result<(), ExecutionError>;
+
+    /// Performs the logic before transactions in a block are executed.
+    fn before_transactions(&self, _context: &mut CallContext<'_>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    /// Performs the logic after all transactions in a block are executed.
+    fn after_transactions(&self, _context: &mut CallContext<'_>) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    /// Notifies the service that a block at the specified height has been committed.
+    /// The notification is sent once per artifact.
+    fn after_commit(&self, _height: Height) {}
+}
+
+/// Connection of the child process to the node.
+struct Channel {
+    reader: BufReader<Box<dyn Read>>,
+    writer: BufWriter<Box<dyn Write>>,
+    /// I/O error that has occurred while processing a storage request.
+    failure: Option<io::Error>,
+}
+
+impl Channel {
+    fn new(reader: impl Read + 'static, writer: impl Write + 'static) -> Self {
+        let reader: Box<dyn Read> = Box::new(reader);
+        let writer: Box<dyn Write> = Box::new(writer);
+        Self {
+            reader: BufReader::new(reader),
+            writer: BufWriter::new(writer),
+            failure: None,
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<Option<Request>> {
+        protocol::read_message(&mut self.reader)
+    }
+
+    fn send(&mut self, response: &Response) -> io::Result<()> {
+        protocol::write_message(&mut self.writer, response)
+    }
+
+    fn storage_request(&mut self, request: StorageRequest) -> io::Result<StorageResponse> {
+        self.send(&Response::StorageRequest(request))?;
+        match self.receive()? {
+            Some(Request::StorageResponse(response)) => Ok(response),
+            Some(request) => {
+                let msg = format!("Expected storage response, got {:?}", request);
+                Err(io::Error::new(io::ErrorKind::InvalidData, msg))
+            }
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+/// Context of a service call in the child process.
+///
+/// Service data is represented as a collection of maps with binary keys and values;
+/// map names are relative to the service namespace. Storage errors (e.g., an attempt
+/// to access an index which is not a map) fail the call regardless of the result returned
+/// by the service; service code should propagate them to the caller.
+pub struct CallContext<'a> {
+    instance: &'a InstanceSpec,
+    author: Option<PublicKey>,
+    height: Height,
+    channel: &'a mut Channel,
+}
+
+impl fmt::Debug for CallContext<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("CallContext")
+            .field("instance", &self.instance)
+            .field("author", &self.author)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+impl CallContext<'_> {
+    /// Returns the specification of the called service instance.
+    pub fn instance(&self) -> &InstanceSpec {
+        self.instance
+    }
+
+    /// Returns the author of the transaction which has initiated the call, if any.
+    pub fn author(&self) -> Option<PublicKey> {
+        self.author
+    }
+
+    /// Returns the height of the block being executed.
+    pub fn height(&self) -> Height {
+        self.height
+    }
+
+    /// Reads a value from a map of the service.
+    pub fn get(&mut self, index: &str, key: &[u8]) -> Result<Option<Vec<u8>>, ExecutionError> {
+        self.storage_request(StorageRequest::Get {
+            index: index.to_owned(),
+            key: key.to_vec(),
+        })
+    }
+
+    /// Writes a value to a map of the service.
+    pub fn put(&mut self, index: &str, key: &[u8], value: Vec<u8>) -> Result<(), ExecutionError> {
+        self.storage_request(StorageRequest::Put {
+            index: index.to_owned(),
+            key: key.to_vec(),
+            value,
+        })
+        .map(drop)
+    }
+
+    /// Removes a value from a map of the service.
+    pub fn remove(&mut self, index: &str, key: &[u8]) -> Result<(), ExecutionError> {
+        self.storage_request(StorageRequest::Remove {
+            index: index.to_owned(),
+            key: key.to_vec(),
+        })
+        .map(drop)
+    }
+
+    fn storage_request(
+        &mut self,
+        request: StorageRequest,
+    ) -> Result<Option<Vec<u8>>, ExecutionError> {
+        let connection_err = || ExecutionError::new(ErrorKind::Unexpected, "Node is unreachable");
+        if self.channel.failure.is_some() {
+            return Err(connection_err());
+        }
+
+        match self.channel.storage_request(request) {
+            Ok(StorageResponse::Value(value)) => Ok(Some(value)),
+            Ok(StorageResponse::NoValue) => Ok(None),
+            Ok(StorageResponse::Error(description)) => {
+                Err(ExecutionError::new(ErrorKind::Unexpected, description))
+            }
+            Err(e) => {
+                self.channel.failure = Some(e);
+                Err(connection_err())
+            }
+        }
+    }
+}
+
+/// Collection of services provided by the child process.
+#[derive(Default)]
+pub struct ChildRuntime {
+    services: HashMap<ArtifactId, Arc<dyn ChildService>>,
+}
+
+impl fmt::Debug for ChildRuntime {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("ChildRuntime")
+            .field("artifacts", &self.services.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ChildRuntime {
+    /// Creates a child runtime without services.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a service associated with the specified artifact.
+    pub fn with_service(
+        mut self,
+        artifact: ArtifactId,
+        service: impl ChildService + 'static,
+    ) -> Self {
+        self.services.insert(artifact, Arc::new(service));
+        self
+    }
+
+    /// Serves requests of the node received via the standard input, writing responses
+    /// to the standard output.
+    pub fn serve_stdio(&self) -> io::Result<()> {
+        self.serve(io::stdin(), io::stdout())
+    }
+
+    /// Serves requests of the node received from `reader`, writing responses to `writer`.
+    /// Returns when the node closes the connection or if an I/O error occurs.
+    pub fn serve(
+        &self,
+        reader: impl Read + 'static,
+        writer: impl Write + 'static,
+    ) -> io::Result<()> {
+        let mut channel = Channel::new(reader, writer);
+        // Artifacts are deployed anew for each connection.
+        let mut deployed = HashSet::new();
+
+        while let Some(request) = channel.receive()? {
+            let result = match request {
+                Request::DeployArtifact { artifact, .. } => self.deploy(&mut deployed, artifact),
+                Request::UnloadArtifact(artifact) => {
+                    deployed.remove(&artifact);
+                    Ok(())
+                }
+                Request::Call(call) => self.call(&deployed, &mut channel, call),
+                Request::AfterCommit(height) => {
+                    for artifact in &deployed {
+                        self.services[artifact].after_commit(height);
+                    }
+                    Ok(())
+                }
+                Request::StorageResponse(_) => {
+                    let msg = "Unexpected storage response";
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+                }
+            };
+
+            if let Some(err) = channel.failure.take() {
+                return Err(err);
+            }
+            channel.send(&Response::Result(result))?;
+        }
+        Ok(())
+    }
+
+    fn deploy(
+        &self,
+        deployed: &mut HashSet<ArtifactId>,
+        artifact: ArtifactId,
+    ) -> Result<(), ExecutionError> {
+        if self.services.contains_key(&artifact) {
+            deployed.insert(artifact);
+            Ok(())
+        } else {
+            let msg = format!("Artifact {} is not provided by the child process", artifact);
+            Err(ExecutionError::new(ErrorKind::Unexpected, msg))
+        }
+    }
+
+    fn call(
+        &self,
+        deployed: &HashSet<ArtifactId>,
+        channel: &mut Channel,
+        call: ServiceCall,
+    ) -> Result<(), ExecutionError> {
+        let artifact = &call.instance.artifact;
+        let service = self
+            .services
+            .get(artifact)
+            .filter(|_| deployed.contains(artifact))
+            .ok_or_else(|| {
+                let msg = format!("Artifact {} is not deployed", artifact);
+                ExecutionError::new(ErrorKind::Unexpected, msg)
+            })?;
+
+        let mut context = CallContext {
+            instance: &call.instance,
+            author: call.author,
+            height: call.height,
+            channel,
+        };
+        // Panics in the service code are converted to `Unexpected` errors, which are
+        // recorded as the result of the call. Unlike crashes of the child process,
+        // such errors are deterministic.
+        catch_panic(|| match &call.call_type {
+            CallType::Constructor => service.initialize(&mut context, &call.arguments),
+            CallType::Resume => service.resume(&mut context, &call.arguments),
+            CallType::Method { interface, id } if interface.is_empty() => {
+                service.execute(&mut context, *id, &call.arguments)
+            }
+            CallType::Method { interface, .. } => {
+                let msg = format!("Service does not implement interface `{}`", interface);
+                Err(CommonError::NoSuchInterface.with_description(msg))
+            }
+            CallType::BeforeTransactions => service.before_transactions(&mut context),
+            CallType::AfterTransactions => service.after_transactions(&mut context),
+            other => {
+                let msg = format!("Unsupported call type: {}", other);
+                Err(ExecutionError::new(ErrorKind::Unexpected, msg))
+            }
+        })
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Connection of the node to the child process.
+
+use exonum::runtime::{ExecutionError, ExecutionFail};
+
+use std::{
+    fmt,
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::Shutdown,
+    os::unix::net::UnixStream,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    protocol::{self, Request, Response},
+    Error,
+};
+
+/// Endpoint used to communicate with the child process.
+#[derive(Debug)]
+pub enum Endpoint {
+    /// Child process spawned by the runtime. The protocol is spoken over the standard input
+    /// and output of the process; the standard error stream is inherited from the node.
+    Command(Command),
+    /// Child process listening on a Unix socket at the specified path. A new connection
+    /// is established each time the previous one breaks.
+    UnixSocket(PathBuf),
+}
+
+impl Endpoint {
+    fn connect(&mut self) -> io::Result<Connection> {
+        match self {
+            Self::Command(command) => {
+                let mut child = command
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::inherit())
+                    .spawn()?;
+                let writer = child.stdin.take().expect("BUG: stdin is not piped");
+                let reader = child.stdout.take().expect("BUG: stdout is not piped");
+                Ok(Connection::new(
+                    reader,
+                    writer,
+                    ConnectionHandle::Process(child),
+                ))
+            }
+
+            Self::UnixSocket(path) => {
+                let stream = UnixStream::connect(path)?;
+                let reader = stream.try_clone()?;
+                let writer = stream.try_clone()?;
+                Ok(Connection::new(
+                    reader,
+                    writer,
+                    ConnectionHandle::Socket(stream),
+                ))
+            }
+        }
+    }
+}
+
+/// Resource which should be released when the connection is closed.
+#[derive(Debug)]
+enum ConnectionHandle {
+    Process(Child),
+    Socket(UnixStream),
+}
+
+/// Result of reading a message from the child process.
+type ReadResult = io::Result<Option<Response>>;
+
+/// Connection to the child process.
+///
+/// Responses of the child are read in a dedicated thread, which allows to wait for them
+/// with a timeout regardless of the underlying transport.
+pub(crate) struct Connection {
+    writer: BufWriter<Box<dyn Write + Send>>,
+    responses: mpsc::Receiver<ReadResult>,
+    handle: ConnectionHandle,
+}
+
+impl fmt::Debug for Connection {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_struct("Connection")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl Connection {
+    fn new(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        handle: ConnectionHandle,
+    ) -> Self {
+        let (tx, responses) = mpsc::channel();
+        let mut reader = BufReader::new(reader);
+        thread::Builder::new()
+            .name("process-runtime-reader".to_owned())
+            .spawn(move || loop {
+                let result: ReadResult = protocol::read_message(&mut reader);
+                let is_final = !matches!(result, Ok(Some(_)));
+                if tx.send(result).is_err() || is_final {
+                    break;
+                }
+            })
+            .expect("Cannot spawn thread reading responses of the child process");
+
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        Self {
+            writer: BufWriter::new(writer),
+            responses,
+            handle,
+        }
+    }
+
+    /// Sends a request to the child process.
+    pub fn send(&mut self, request: &Request) -> Result<(), ExecutionError> {
+        protocol::write_message(&mut self.writer, request).map_err(|e| {
+            log::error!("Cannot send request to the child process: {}", e);
+            Error::ChildCrashed.with_description("Cannot send request to the child process")
+        })
+    }
+
+    /// Waits for a response of the child process.
+    pub fn receive(&self, timeout: Duration) -> Result<Response, ExecutionError> {
+        let closed_err =
+            || Error::ChildCrashed.with_description("Child process has closed the connection");
+
+        match self.responses.recv_timeout(timeout) {
+            Ok(Ok(Some(response))) => Ok(response),
+            Ok(Ok(None)) | Err(RecvTimeoutError::Disconnected) => Err(closed_err()),
+            Ok(Err(e)) if e.kind() == io::ErrorKind::InvalidData => {
+                log::error!("Child process has sent a malformed message: {}", e);
+                let msg = "Child process has sent a malformed message";
+                Err(Error::InvalidMessage.with_description(msg))
+            }
+            Ok(Err(e)) => {
+                log::error!("Cannot read response of the child process: {}", e);
+                Err(closed_err())
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let msg = "Child process has not responded in time";
+                Err(Error::Timeout.with_description(msg))
+            }
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // Closing the connection terminates the thread reading responses.
+        match &mut self.handle {
+            ConnectionHandle::Process(child) => {
+                child.kill().ok();
+                child.wait().ok();
+            }
+            ConnectionHandle::Socket(stream) => {
+                stream.shutdown(Shutdown::Both).ok();
+            }
+        }
+    }
+}
+
+/// Child process together with the current connection to it.
+#[derive(Debug)]
+pub(crate) struct ChildHandle {
+    endpoint: Endpoint,
+    connection: Option<Connection>,
+}
+
+impl ChildHandle {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            connection: None,
+        }
+    }
+
+    /// Returns the current connection to the child process. If there is no connection,
+    /// establishes a new one and sends the `initial_requests` over it.
+    pub fn connection(
+        &mut self,
+        initial_requests: impl IntoIterator<Item = Request>,
+        timeout: Duration,
+    ) -> Result<&mut Connection, ExecutionError> {
+        let connection = match self.connection.take() {
+            Some(connection) => connection,
+            None => self.connect(initial_requests, timeout)?,
+        };
+        Ok(self.connection.get_or_insert(connection))
+    }
+
+    fn connect(
+        &mut self,
+        initial_requests: impl IntoIterator<Item = Request>,
+        timeout: Duration,
+    ) -> Result<Connection, ExecutionError> {
+        let unavailable_err =
+            || Error::ChildUnavailable.with_description("Cannot connect to the child process");
+
+        let mut connection = self.endpoint.connect().map_err(|e| {
+            log::error!("Cannot connect to the child process: {}", e);
+            unavailable_err()
+        })?;
+        for request in initial_requests {
+            connection.send(&request)?;
+            match connection.receive(timeout)? {
+                Response::Result(Ok(())) => {}
+                response => {
+                    log::error!(
+                        "Unexpected response of the child process to {:?}: {:?}",
+                        request,
+                        response
+                    );
+                    return Err(unavailable_err());
+                }
+            }
+        }
+        Ok(connection)
+    }
+
+    /// Closes the current connection to the child process, if any.
+    pub fn disconnect(&mut self) {
+        self.connection = None;
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors specific for the process runtime.
+
+use exonum_derive::ExecutionFail;
+
+/// List of possible process runtime errors.
+///
+/// Errors related to the communication with the child process have fixed descriptions,
+/// which do not depend on the operating system or on the exact moment of a failure.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(ExecutionFail)]
+#[execution_fail(kind = "runtime")]
+#[non_exhaustive]
+pub enum Error {
+    /// Child process cannot be started or connected to.
+    ChildUnavailable = 0,
+    /// Child process has exited or closed the connection while processing a request.
+    ChildCrashed = 1,
+    /// Child process has not responded to a request in time.
+    Timeout = 2,
+    /// Child process has sent a malformed or unexpected message.
+    InvalidMessage = 3,
+    /// Storage request of the child process is invalid, e.g., it refers to an index
+    /// of a wrong type.
+    InvalidStorageRequest = 4,
+    /// Storage requests are not available for the current request.
+    StorageUnavailable = 5,
+}
@@ -0,0 +1,537 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The runtime is for running Exonum services in a separate process.
+//!
+//! The runtime forwards deployment of artifacts, service constructors, transactions
+//! and block hooks to a *child process* over a Protobuf-based protocol. Running services
+//! in a separate process isolates the node from faults in the service code and allows
+//! to implement services in any language able to speak the protocol.
+//!
+//! # Protocol
+//!
+//! The node and the child exchange Protobuf messages defined in `src/proto/protocol.proto`.
+//! Each message is prefixed with its length encoded as a 4-byte little-endian integer.
+//! The child can communicate with the node either via its standard input and output
+//! (if the child is spawned by the runtime), or via a Unix socket.
+//!
+//! The node sends `Request`s to the child one at a time and waits for the `Response`
+//! with the `ExecutionStatus` of each request. The requests are:
+//!
+//! - `deploy_artifact`, `unload_artifact`: artifact lifecycle
+//! - `call`: a service constructor, resuming routine, method or a block hook
+//!   (`before_transactions` / `after_transactions`)
+//! - `after_commit`: a notification that a block was committed
+//!
+//! While processing a service call, the child may send `StorageRequest`s to read and write
+//! the data of the called service. Service data is represented as a collection of maps
+//! with binary keys and values; map names are relative to the service namespace.
+//! Storage requests are answered with a `StorageResponse`. Reads are served from the `Fork`
+//! of the block being executed; writes are buffered and applied to the fork once the child
+//! returns the result of the call. Changes made by a failed call are rolled back together
+//! with other changes of the call.
+//!
+//! The child may report service errors, common errors (e.g., an unknown method)
+//! and unexpected errors. Errors of other kinds are replaced with
+//! [`Error::InvalidMessage`](enum.Error.html#variant.InvalidMessage).
+//!
+//! [`ChildRuntime`] is the reference implementation of the child in Rust.
+//!
+//! # Timeouts and Crashes
+//!
+//! The node waits for each response of the child for a limited time, which is configured
+//! with [`ProcessRuntime::with_timeout()`]. If the child does not respond in time, exits,
+//! closes the connection or sends a malformed message, the connection is closed
+//! (a spawned child process is killed) and the storage changes requested by the child
+//! during the request are discarded. The request is then retried with a new connection;
+//! all artifacts deployed in the runtime are deployed in the new child before it receives
+//! any other requests.
+//!
+//! The results of service calls are a part of the blockchain state, so communication
+//! failures are never recorded as call results. If the child fails to process a service call
+//! after the number of restarts configured with [`ProcessRuntime::with_max_restarts()`],
+//! the runtime panics, which halts the node. Failures of other requests (e.g., artifact
+//! deployment) are reported as the corresponding runtime [`Error`].
+//!
+//! To keep the results deterministic, errors in the service code (including panics
+//! in the [`ChildRuntime`]) must be returned as call results rather than crash the child.
+//! The timeout should be chosen with a sufficient margin.
+//!
+//! # Limitations
+//!
+//! - Services executed by the runtime cannot call other services or emit events.
+//! - Data migrations are not supported.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use exonum_process_runtime::{Endpoint, ProcessRuntime};
+//! use std::{process::Command, time::Duration};
+//!
+//! let command = Command::new("/usr/local/bin/exonum-child");
+//! let runtime = ProcessRuntime::new(Endpoint::Command(command))
+//!     .with_timeout(Duration::from_secs(5));
+//! // `runtime` can now be added to a node, e.g., with `NodeBuilder::with_runtime()`.
+//! ```
+//!
+//! [`ChildRuntime`]: child/struct.ChildRuntime.html
+//! [`ProcessRuntime::with_timeout()`]: struct.ProcessRuntime.html#method.with_timeout
+//! [`ProcessRuntime::with_max_restarts()`]: struct.ProcessRuntime.html#method.with_max_restarts
+//! [`Error`]: enum.Error.html
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+#![warn(clippy::pedantic, clippy::nursery)]
+#![allow(
+    // Next `cast_*` lints don't give alternatives.
+    clippy::cast_possible_wrap, clippy::cast_possible_truncation, clippy::cast_sign_loss,
+    // Next lints produce too much noise/false positives.
+    clippy::module_name_repetitions, clippy::similar_names, clippy::must_use_candidate,
+    // Too much work to fix.
+    clippy::missing_errors_doc, clippy::missing_const_for_fn
+)]
+
+pub use crate::{connection::Endpoint, error::Error};
+
+pub mod child;
+
+use exonum::{
+    blockchain::Schema as CoreSchema,
+    merkledb::{
+        access::{Access, FromAccess},
+        MapIndex, Snapshot,
+    },
+    runtime::{
+        migrations::{InitMigrationError, MigrationScript},
+        oneshot::Receiver,
+        versioning::Version,
+        ArtifactId, CallType, ErrorKind, ExecutionContext, ExecutionError, ExecutionFail,
+        InstanceId, InstanceSpec, InstanceState, InstanceStatus, Mailbox, MethodId, Runtime,
+        RuntimeIdentifier, WellKnownRuntime,
+    },
+};
+
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+use crate::{
+    connection::ChildHandle,
+    protocol::{Request, Response, ServiceCall, StorageRequest, StorageResponse},
+};
+
+mod connection;
+mod error;
+mod proto;
+mod protocol;
+
+/// Map with binary keys and values used to store service data.
+type RawMap<T> = MapIndex<T, [u8], Vec<u8>>;
+
+/// Runtime forwarding requests to a child process.
+///
+/// See [crate docs](index.html) for the description of the protocol.
+#[derive(Debug)]
+pub struct ProcessRuntime {
+    child: RefCell<ChildHandle>,
+    timeout: Duration,
+    max_restarts: usize,
+    deployed_artifacts: HashMap<ArtifactId, Vec<u8>>,
+    started_services: HashMap<InstanceId, ArtifactId>,
+}
+
+impl ProcessRuntime {
+    /// Process runtime name.
+    pub const NAME: &'static str = "process";
+
+    /// Default timeout for responses of the child process.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Default number of times the child process is restarted while processing a single request.
+    pub const DEFAULT_MAX_RESTARTS: usize = 3;
+
+    /// Creates a runtime communicating with the child process via the specified endpoint.
+    /// The connection is established lazily, before the first request to the child.
+    pub fn new(endpoint: Endpoint) -> Self {
+        Self {
+            child: RefCell::new(ChildHandle::new(endpoint)),
+            timeout: Self::DEFAULT_TIMEOUT,
+            max_restarts: Self::DEFAULT_MAX_RESTARTS,
+            deployed_artifacts: HashMap::new(),
+            started_services: HashMap::new(),
+        }
+    }
+
+    /// Sets the timeout for responses of the child process.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of times the child process is restarted while processing a single
+    /// request. If the child fails to process a service call after all restarts,
+    /// the node is halted.
+    pub fn with_max_restarts(mut self, max_restarts: usize) -> Self {
+        self.max_restarts = max_restarts;
+        self
+    }
+
+    /// Sends a request to the child process and waits for the result. If the child fails
+    /// to process the request, the request is retried with a new connection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the child fails to process a service call after all restarts. The result
+    /// of the call is a part of the blockchain state, so the node cannot proceed
+    /// without diverging from other nodes.
+    fn request(
+        &self,
+        request: &Request,
+        context: Option<&ExecutionContext<'_>>,
+    ) -> Result<(), ExecutionError> {
+        let mut attempt = 0;
+        loop {
+            match self.try_request(request, context) {
+                Ok(result) => break result,
+                Err(err) if attempt < self.max_restarts => {
+                    attempt += 1;
+                    log::warn!(
+                        "Child process has failed to process a request ({}), restarting it \
+                         (attempt {} of {})",
+                        err,
+                        attempt,
+                        self.max_restarts
+                    );
+                }
+                Err(err) if context.is_some() => panic!(
+                    "Child process has failed to process a service call {} times, last error: {}. \
+                     The node is halted to avoid divergence from other nodes",
+                    attempt + 1,
+                    err
+                ),
+                Err(err) => break Err(err),
+            }
+        }
+    }
+
+    /// Sends a request to the child process and waits for the result, processing storage
+    /// requests of the child in the meantime. The outer error signals a failure
+    /// of the communication with the child; in this case, the connection is closed
+    /// and the storage changes requested by the child are discarded.
+    fn try_request(
+        &self,
+        request: &Request,
+        context: Option<&ExecutionContext<'_>>,
+    ) -> Result<Result<(), ExecutionError>, ExecutionError> {
+        let mut child = self.child.borrow_mut();
+        let deploy_requests =
+            self.deployed_artifacts
+                .iter()
+                .map(|(artifact, spec)| Request::DeployArtifact {
+                    artifact: artifact.to_owned(),
+                    spec: spec.to_owned(),
+                });
+        let connection = child.connection(deploy_requests, self.timeout);
+        let mut changes = StorageChanges::default();
+        let result = connection.and_then(|connection| {
+            connection.send(request)?;
+            let mut storage_error = None;
+            loop {
+                match connection.receive(self.timeout)? {
+                    Response::Result(result) => {
+                        break Ok(storage_error.map_or_else(|| check_result(result), Err));
+                    }
+                    Response::StorageRequest(storage_request) => {
+                        let response = match changes.process_request(context, storage_request) {
+                            Ok(Some(value)) => StorageResponse::Value(value),
+                            Ok(None) => StorageResponse::NoValue,
+                            Err(err) => {
+                                let response = StorageResponse::Error(err.description().into());
+                                storage_error.get_or_insert(err);
+                                response
+                            }
+                        };
+                        connection.send(&Request::StorageResponse(response))?;
+                    }
+                }
+            }
+        });
+
+        match result {
+            Ok(result) => {
+                if let (Ok(()), Some(context)) = (&result, context) {
+                    changes.apply(context);
+                }
+                Ok(result)
+            }
+            // Errors returned by the connection mean that its state is unknown,
+            // so the connection is closed.
+            Err(err) => {
+                child.disconnect();
+                Err(err)
+            }
+        }
+    }
+
+    fn call_service(
+        &self,
+        context: &ExecutionContext<'_>,
+        artifact: &ArtifactId,
+        call_type: CallType,
+        arguments: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        let instance = context.instance();
+        let call = ServiceCall {
+            instance: InstanceSpec::from_raw_parts(
+                instance.id,
+                instance.name.clone(),
+                artifact.to_owned(),
+            ),
+            call_type,
+            arguments,
+            author: context.caller().author(),
+            height: context.data().for_core().next_height(),
+        };
+        self.request(&Request::Call(call), Some(context))
+    }
+
+    fn service_artifact(&self, context: &ExecutionContext<'_>) -> &ArtifactId {
+        self.started_services
+            .get(&context.instance().id)
+            .expect("BUG: an attempt to call unknown service")
+    }
+}
+
+/// Checks the result of a call returned by the child process. Errors can only have kinds
+/// that can be produced by the service code; the call site of errors is determined
+/// by the dispatcher.
+fn check_result(result: Result<(), ExecutionError>) -> Result<(), ExecutionError> {
+    result.map_err(|err| match err.kind() {
+        ErrorKind::Unexpected | ErrorKind::Service { .. } | ErrorKind::Common { .. } => {
+            let checked_err = ExecutionError::new(err.kind(), err.description());
+            if let Some(payload) = err.payload() {
+                checked_err.with_payload(payload.to_owned())
+            } else {
+                checked_err
+            }
+        }
+        kind => {
+            let msg = format!("Child process has returned an error with kind {}", kind);
+            Error::InvalidMessage.with_description(msg)
+        }
+    })
+}
+
+fn open_map<T: Access>(access: T, name: &str) -> Result<RawMap<T::Base>, ExecutionError> {
+    MapIndex::from_access(access, name.into())
+        .map_err(|e| Error::InvalidStorageRequest.with_description(e))
+}
+
+/// Changes of the service data requested by the child process during a call.
+/// The changes are applied to the fork only after the child has returned the result
+/// of the call, so that an interrupted call does not affect the blockchain state.
+#[derive(Debug, Default)]
+struct StorageChanges {
+    changes: Vec<(String, Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl StorageChanges {
+    /// Processes a storage request of the child process. Values changed during the call
+    /// are read from the buffered changes.
+    fn process_request(
+        &mut self,
+        context: Option<&ExecutionContext<'_>>,
+        request: StorageRequest,
+    ) -> Result<Option<Vec<u8>>, ExecutionError> {
+        let context = context.ok_or_else(|| {
+            let msg = "Storage requests are only available during service calls";
+            Error::StorageUnavailable.with_description(msg)
+        })?;
+
+        let data = context.service_data();
+        match request {
+            StorageRequest::Get { index, key } => {
+                if let Some(value) = self.changed_value(&index, &key) {
+                    return Ok(value.clone());
+                }
+                Ok(open_map(data, &index)?.get(&key))
+            }
+            StorageRequest::Put { index, key, value } => {
+                open_map(data, &index)?;
+                self.changes.push((index, key, Some(value)));
+                Ok(None)
+            }
+            StorageRequest::Remove { index, key } => {
+                open_map(data, &index)?;
+                self.changes.push((index, key, None));
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the latest value written to the map during the call, or `None`
+    /// if the entry has not been changed.
+    fn changed_value(&self, index: &str, key: &[u8]) -> Option<&Option<Vec<u8>>> {
+        self.changes
+            .iter()
+            .rev()
+            .find(|(changed_index, changed_key, _)| changed_index == index && changed_key == key)
+            .map(|(_, _, value)| value)
+    }
+
+    /// Applies the changes to the data of the called service.
+    fn apply(self, context: &ExecutionContext<'_>) {
+        for (index, key, value) in self.changes {
+            let mut map = open_map(context.service_data(), &index)
+                .expect("BUG: map is checked when processing storage requests");
+            match value {
+                Some(value) => map.put(&key, value),
+                None => map.remove(key.as_slice()),
+            }
+        }
+    }
+}
+
+impl WellKnownRuntime for ProcessRuntime {
+    const ID: u32 = RuntimeIdentifier::Process as u32;
+}
+
+impl Runtime for ProcessRuntime {
+    fn deploy_artifact(&mut self, artifact: ArtifactId, deploy_spec: Vec<u8>) -> Receiver {
+        if self.deployed_artifacts.contains_key(&artifact) {
+            panic!(
+                "BUG: Core requested deploy of already deployed artifact {:?}",
+                artifact
+            );
+        }
+
+        let request = Request::DeployArtifact {
+            artifact: artifact.clone(),
+            spec: deploy_spec.clone(),
+        };
+        let result = self.request(&request, None);
+        if result.is_ok() {
+            log::info!("Deployed artifact {} in the child process", artifact);
+            self.deployed_artifacts.insert(artifact, deploy_spec);
+        }
+        Receiver::with_result(result)
+    }
+
+    fn is_artifact_deployed(&self, artifact: &ArtifactId) -> bool {
+        self.deployed_artifacts.contains_key(artifact)
+    }
+
+    fn unload_artifact(&mut self, artifact: &ArtifactId) {
+        let was_present = self.deployed_artifacts.remove(artifact).is_some();
+        debug_assert!(
+            was_present,
+            "Requested to unload non-existing artifact `{}`",
+            artifact
+        );
+
+        let request = Request::UnloadArtifact(artifact.to_owned());
+        if let Err(err) = self.request(&request, None) {
+            log::warn!(
+                "Cannot unload artifact {} in the child process: {}",
+                artifact,
+                err
+            );
+        }
+    }
+
+    fn initiate_adding_service(
+        &self,
+        context: ExecutionContext<'_>,
+        artifact: &ArtifactId,
+        parameters: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        self.call_service(&context, artifact, CallType::Constructor, parameters)
+    }
+
+    fn initiate_resuming_service(
+        &self,
+        context: ExecutionContext<'_>,
+        artifact: &ArtifactId,
+        parameters: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        self.call_service(&context, artifact, CallType::Resume, parameters)
+    }
+
+    fn update_service_status(&mut self, _snapshot: &dyn Snapshot, state: &InstanceState) {
+        let provides_read_access = state
+            .status
+            .as_ref()
+            .map_or(false, InstanceStatus::provides_read_access);
+
+        match state.associated_artifact() {
+            Some(artifact) if provides_read_access => {
+                assert!(
+                    self.deployed_artifacts.contains_key(artifact),
+                    "BUG: service {} is associated with a non-deployed artifact {}",
+                    state.spec.name,
+                    artifact
+                );
+                self.started_services
+                    .insert(state.spec.id, artifact.to_owned());
+            }
+            _ => {
+                self.started_services.remove(&state.spec.id);
+            }
+        }
+    }
+
+    fn migrate(
+        &self,
+        _new_artifact: &ArtifactId,
+        _data_version: &Version,
+    ) -> Result<Option<MigrationScript>, InitMigrationError> {
+        Err(InitMigrationError::NotSupported)
+    }
+
+    fn execute(
+        &self,
+        context: ExecutionContext<'_>,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Result<(), ExecutionError> {
+        let artifact = self.service_artifact(&context);
+        let call_type = CallType::Method {
+            interface: context.interface_name().to_owned(),
+            id: method_id,
+        };
+        self.call_service(&context, artifact, call_type, arguments.to_vec())
+    }
+
+    fn before_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError> {
+        let artifact = self.service_artifact(&context);
+        self.call_service(&context, artifact, CallType::BeforeTransactions, vec![])
+    }
+
+    fn after_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError> {
+        let artifact = self.service_artifact(&context);
+        self.call_service(&context, artifact, CallType::AfterTransactions, vec![])
+    }
+
+    fn after_commit(&mut self, snapshot: &dyn Snapshot, _mailbox: &mut Mailbox) {
+        let height = CoreSchema::new(snapshot).height();
+        if let Err(err) = self.request(&Request::AfterCommit(height), None) {
+            log::warn!(
+                "Child process has failed to process committed block: {}",
+                err
+            );
+        }
+    }
+}
@@ -0,0 +1,332 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Messages of the protocol between the node and the child process.
+//!
+//! Messages are encoded with Protobuf and are framed on the wire with a 4-byte little-endian
+//! length prefix.
+
+use anyhow::format_err;
+use exonum::{
+    crypto::PublicKey,
+    helpers::Height,
+    proto::schema::errors::CallSite_Type,
+    runtime::{ArtifactId, CallType, ExecutionError, ExecutionStatus, InstanceSpec},
+};
+use exonum_derive::BinaryValue;
+use exonum_merkledb::BinaryValue;
+use exonum_proto::ProtobufConvert;
+use protobuf::well_known_types::Empty;
+
+use std::io::{self, Read, Write};
+
+use crate::proto::{self, Request_oneof_kind, Response_oneof_kind, StorageRequest_Type};
+
+/// Maximum size of a message in bytes.
+const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Message sent by the node to the child process.
+#[derive(Debug, Clone, BinaryValue)]
+pub(crate) enum Request {
+    DeployArtifact { artifact: ArtifactId, spec: Vec<u8> },
+    UnloadArtifact(ArtifactId),
+    Call(ServiceCall),
+    AfterCommit(Height),
+    StorageResponse(StorageResponse),
+}
+
+impl ProtobufConvert for Request {
+    type ProtoStruct = proto::Request;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        match self {
+            Self::DeployArtifact { artifact, spec } => {
+                let mut inner = proto::DeployArtifact::new();
+                inner.set_artifact(artifact.to_pb());
+                inner.set_spec(spec.clone());
+                pb.set_deploy_artifact(inner);
+            }
+            Self::UnloadArtifact(artifact) => pb.set_unload_artifact(artifact.to_pb()),
+            Self::Call(call) => pb.set_call(call.to_pb()),
+            Self::AfterCommit(height) => {
+                let mut inner = proto::AfterCommit::new();
+                inner.set_height(height.0);
+                pb.set_after_commit(inner);
+            }
+            Self::StorageResponse(response) => pb.set_storage_response(response.to_pb()),
+        }
+        pb
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        let kind = pb
+            .kind
+            .ok_or_else(|| format_err!("Request kind is not set"))?;
+        Ok(match kind {
+            Request_oneof_kind::deploy_artifact(mut inner) => Self::DeployArtifact {
+                artifact: ArtifactId::from_pb(inner.take_artifact())?,
+                spec: inner.take_spec(),
+            },
+            Request_oneof_kind::unload_artifact(artifact) => {
+                Self::UnloadArtifact(ArtifactId::from_pb(artifact)?)
+            }
+            Request_oneof_kind::call(call) => Self::Call(ServiceCall::from_pb(call)?),
+            Request_oneof_kind::after_commit(inner) => {
+                Self::AfterCommit(Height(inner.get_height()))
+            }
+            Request_oneof_kind::storage_response(response) => {
+                Self::StorageResponse(StorageResponse::from_pb(response)?)
+            }
+        })
+    }
+}
+
+/// Call to a service instance.
+#[derive(Debug, Clone)]
+pub(crate) struct ServiceCall {
+    pub instance: InstanceSpec,
+    pub call_type: CallType,
+    pub arguments: Vec<u8>,
+    pub author: Option<PublicKey>,
+    pub height: Height,
+}
+
+impl ProtobufConvert for ServiceCall {
+    type ProtoStruct = proto::ServiceCall;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        pb.set_instance(self.instance.to_pb());
+        let call_type = match &self.call_type {
+            CallType::Constructor => CallSite_Type::CONSTRUCTOR,
+            CallType::Resume => CallSite_Type::RESUME,
+            CallType::Method { interface, id } => {
+                pb.set_interface(interface.clone());
+                pb.set_method_id(*id);
+                CallSite_Type::METHOD
+            }
+            CallType::BeforeTransactions => CallSite_Type::BEFORE_TRANSACTIONS,
+            CallType::AfterTransactions => CallSite_Type::AFTER_TRANSACTIONS,
+            other => unreachable!("BUG: unsupported call type: {}", other),
+        };
+        pb.set_call_type(call_type);
+        pb.set_arguments(self.arguments.clone());
+        if let Some(author) = self.author {
+            pb.set_author(author.to_pb());
+        }
+        pb.set_height(self.height.0);
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        let call_type = match pb.get_call_type() {
+            CallSite_Type::CONSTRUCTOR => CallType::Constructor,
+            CallSite_Type::RESUME => CallType::Resume,
+            CallSite_Type::METHOD => CallType::Method {
+                interface: pb.take_interface(),
+                id: pb.get_method_id(),
+            },
+            CallSite_Type::BEFORE_TRANSACTIONS => CallType::BeforeTransactions,
+            CallSite_Type::AFTER_TRANSACTIONS => CallType::AfterTransactions,
+        };
+        let author = if pb.has_author() {
+            Some(PublicKey::from_pb(pb.take_author())?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            instance: InstanceSpec::from_pb(pb.take_instance())?,
+            call_type,
+            arguments: pb.take_arguments(),
+            author,
+            height: Height(pb.get_height()),
+        })
+    }
+}
+
+/// Response of the node to a storage request.
+#[derive(Debug, Clone)]
+pub(crate) enum StorageResponse {
+    Value(Vec<u8>),
+    NoValue,
+    Error(String),
+}
+
+impl ProtobufConvert for StorageResponse {
+    type ProtoStruct = proto::StorageResponse;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        match self {
+            Self::Value(value) => pb.set_value(value.clone()),
+            Self::NoValue => pb.set_no_value(Empty::new()),
+            Self::Error(description) => pb.set_error(description.clone()),
+        }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        Ok(if pb.has_value() {
+            Self::Value(pb.take_value())
+        } else if pb.has_no_value() {
+            Self::NoValue
+        } else if pb.has_error() {
+            Self::Error(pb.take_error())
+        } else {
+            return Err(format_err!("Storage response result is not set"));
+        })
+    }
+}
+
+/// Message sent by the child process to the node.
+#[derive(Debug, Clone, BinaryValue)]
+pub(crate) enum Response {
+    Result(Result<(), ExecutionError>),
+    StorageRequest(StorageRequest),
+}
+
+impl ProtobufConvert for Response {
+    type ProtoStruct = proto::Response;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        match self {
+            Self::Result(result) => pb.set_result(ExecutionStatus(result.clone()).to_pb()),
+            Self::StorageRequest(request) => pb.set_storage_request(request.to_pb()),
+        }
+        pb
+    }
+
+    fn from_pb(pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        let kind = pb
+            .kind
+            .ok_or_else(|| format_err!("Response kind is not set"))?;
+        Ok(match kind {
+            Response_oneof_kind::result(status) => {
+                Self::Result(ExecutionStatus::from_pb(status)?.0)
+            }
+            Response_oneof_kind::storage_request(request) => {
+                Self::StorageRequest(StorageRequest::from_pb(request)?)
+            }
+        })
+    }
+}
+
+/// Request of the child process to access the data of the called service.
+#[derive(Debug, Clone)]
+pub(crate) enum StorageRequest {
+    Get {
+        index: String,
+        key: Vec<u8>,
+    },
+    Put {
+        index: String,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Remove {
+        index: String,
+        key: Vec<u8>,
+    },
+}
+
+impl ProtobufConvert for StorageRequest {
+    type ProtoStruct = proto::StorageRequest;
+
+    fn to_pb(&self) -> Self::ProtoStruct {
+        let mut pb = Self::ProtoStruct::new();
+        match self {
+            Self::Get { index, key } => {
+                pb.set_request_type(StorageRequest_Type::GET);
+                pb.set_index(index.clone());
+                pb.set_key(key.clone());
+            }
+            Self::Put { index, key, value } => {
+                pb.set_request_type(StorageRequest_Type::PUT);
+                pb.set_index(index.clone());
+                pb.set_key(key.clone());
+                pb.set_value(value.clone());
+            }
+            Self::Remove { index, key } => {
+                pb.set_request_type(StorageRequest_Type::REMOVE);
+                pb.set_index(index.clone());
+                pb.set_key(key.clone());
+            }
+        }
+        pb
+    }
+
+    fn from_pb(mut pb: Self::ProtoStruct) -> anyhow::Result<Self> {
+        let index = pb.take_index();
+        let key = pb.take_key();
+        Ok(match pb.get_request_type() {
+            StorageRequest_Type::GET => Self::Get { index, key },
+            StorageRequest_Type::PUT => Self::Put {
+                index,
+                key,
+                value: pb.take_value(),
+            },
+            StorageRequest_Type::REMOVE => Self::Remove { index, key },
+        })
+    }
+}
+
+/// Writes a length-prefixed message to the stream.
+pub(crate) fn write_message<M: BinaryValue>(
+    writer: &mut (impl Write + ?Sized),
+    message: &M,
+) -> io::Result<()> {
+    let bytes = message.to_bytes();
+    if bytes.len() > MAX_MESSAGE_SIZE {
+        let msg = format!("Message size {} exceeds the limit", bytes.len());
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    }
+
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+/// Reads a length-prefixed message from the stream. Returns `None` if the stream
+/// is closed before the start of the message.
+///
+/// Malformed messages are reported as errors with the `InvalidData` kind.
+pub(crate) fn read_message<M: BinaryValue>(
+    reader: &mut (impl Read + ?Sized),
+) -> io::Result<Option<M>> {
+    let mut len_bytes = [0_u8; 4];
+    let mut read_bytes = 0;
+    while read_bytes < len_bytes.len() {
+        match reader.read(&mut len_bytes[read_bytes..]) {
+            Ok(0) if read_bytes == 0 => return Ok(None),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(len) => read_bytes += len,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        let msg = format!("Message size {} exceeds the limit", len);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+    }
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes)?;
+    M::from_bytes(bytes.into())
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
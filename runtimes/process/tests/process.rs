@@ -0,0 +1,344 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! High-level tests for the process runtime. The child process is emulated by threads
+//! serving connections to a Unix socket.
+
+use exonum::{
+    crypto::KeyPair,
+    helpers::Height,
+    merkledb::access::AccessExt,
+    runtime::{
+        versioning::Version, AnyTx, ArtifactId, CallInfo, CommonError, ErrorKind, ErrorMatch,
+        ExecutionError, InstanceId, MethodId, Runtime, SnapshotExt, WellKnownRuntime,
+    },
+};
+use exonum_rust_runtime::spec::ForeignSpec;
+use exonum_testkit::{TestKit, TestKitBuilder};
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+use std::{
+    convert::TryInto,
+    net::Shutdown,
+    os::unix::net::{UnixListener, UnixStream},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use exonum_process_runtime::{
+    child::{CallContext, ChildRuntime, ChildService},
+    Endpoint, ProcessRuntime,
+};
+
+const COUNTER_ID: InstanceId = 100;
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+fn parse_u64(bytes: &[u8]) -> Result<u64, ExecutionError> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| ExecutionError::service(0, "Invalid arguments"))?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Counter service stored in the `values` map.
+#[derive(Debug)]
+struct Counter {
+    committed_blocks: Arc<AtomicU64>,
+    /// Flag shared among all connections, which is set if the next call to a failing method
+    /// should fail.
+    fail_once: Arc<AtomicBool>,
+    /// Connection served by this service instance.
+    stream: UnixStream,
+}
+
+impl Counter {
+    fn add(&self, context: &mut CallContext<'_>, arguments: &[u8]) -> Result<(), ExecutionError> {
+        let delta = parse_u64(arguments)?;
+        if delta == 0 {
+            return Err(ExecutionError::service(1, "Cannot add zero"));
+        }
+        let value = context
+            .get("values", b"value")?
+            .map_or(Ok(0), |bytes| parse_u64(&bytes))?;
+        context.put("values", b"value", (value + delta).to_le_bytes().to_vec())
+    }
+
+    /// Emulates a crash of the child process.
+    fn crash(&self) {
+        self.stream.shutdown(Shutdown::Both).unwrap();
+    }
+}
+
+impl ChildService for Counter {
+    fn initialize(
+        &self,
+        context: &mut CallContext<'_>,
+        params: &[u8],
+    ) -> Result<(), ExecutionError> {
+        parse_u64(params)?;
+        context.put("values", b"value", params.to_vec())
+    }
+
+    fn execute(
+        &self,
+        context: &mut CallContext<'_>,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Result<(), ExecutionError> {
+        match method_id {
+            0 => self.add(context, arguments),
+            1 => {
+                let author = context.author().expect("No transaction author");
+                context.put("values", b"owner", author.as_ref().to_vec())?;
+                context.remove("values", b"value")
+            }
+            2 => {
+                self.add(context, &1_u64.to_le_bytes())?;
+                if self.fail_once.swap(false, Ordering::SeqCst) {
+                    thread::sleep(TIMEOUT * 4);
+                }
+                Ok(())
+            }
+            3 => {
+                self.add(context, &1_u64.to_le_bytes())?;
+                if self.fail_once.swap(false, Ordering::SeqCst) {
+                    self.crash();
+                }
+                Ok(())
+            }
+            4 => {
+                self.add(context, &1_u64.to_le_bytes())?;
+                self.crash();
+                Ok(())
+            }
+            5 => panic!("Service panic"),
+            _ => Err(CommonError::NoSuchMethod.into()),
+        }
+    }
+
+    fn after_transactions(&self, context: &mut CallContext<'_>) -> Result<(), ExecutionError> {
+        let blocks = context
+            .get("values", b"blocks")?
+            .map_or(Ok(0), |bytes| parse_u64(&bytes))?;
+        context.put("values", b"blocks", (blocks + 1).to_le_bytes().to_vec())
+    }
+
+    fn after_commit(&self, _height: Height) {
+        self.committed_blocks.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn counter_artifact() -> ArtifactId {
+    ArtifactId::from_raw_parts(
+        ProcessRuntime::ID,
+        "counter".to_owned(),
+        Version::new(0, 1, 0),
+    )
+}
+
+/// Emulates a child process, which serves each connection to the socket in a separate thread.
+/// Returns the endpoint to connect to the child.
+fn spawn_child(dir: &TempDir, committed_blocks: &Arc<AtomicU64>) -> Endpoint {
+    let path = dir.path().join("child.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let committed_blocks = Arc::clone(committed_blocks);
+    let fail_once = Arc::new(AtomicBool::new(true));
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let counter = Counter {
+                committed_blocks: Arc::clone(&committed_blocks),
+                fail_once: Arc::clone(&fail_once),
+                stream: stream.try_clone().unwrap(),
+            };
+            thread::spawn(move || {
+                let reader = stream.try_clone().unwrap();
+                ChildRuntime::new()
+                    .with_service(counter_artifact(), counter)
+                    .serve(reader, stream)
+            });
+        }
+    });
+    Endpoint::UnixSocket(path)
+}
+
+fn create_testkit(endpoint: Endpoint) -> (TestKit, KeyPair) {
+    let runtime = ProcessRuntime::new(endpoint).with_timeout(TIMEOUT);
+    let counter = ForeignSpec::new(counter_artifact()).with_instance(
+        COUNTER_ID,
+        "counter",
+        10_u64.to_le_bytes().to_vec(),
+    );
+    let testkit = TestKitBuilder::validator()
+        .with_additional_runtime(runtime)
+        .with(counter)
+        .build();
+    let keys = testkit.us().service_keypair();
+    (testkit, keys)
+}
+
+fn stored_value(testkit: &TestKit, key: &str) -> Option<Vec<u8>> {
+    let snapshot = testkit.snapshot();
+    snapshot
+        .for_service("counter")
+        .unwrap()
+        .get_map::<_, [u8], Vec<u8>>("values")
+        .get(key.as_bytes())
+}
+
+fn stored_u64(testkit: &TestKit, key: &str) -> Option<u64> {
+    let bytes = stored_value(testkit, key)?;
+    Some(parse_u64(&bytes).unwrap())
+}
+
+fn execute(
+    testkit: &mut TestKit,
+    keys: &KeyPair,
+    method_id: u32,
+    arguments: Vec<u8>,
+) -> Result<(), ExecutionError> {
+    let tx = AnyTx::new(CallInfo::new(COUNTER_ID, method_id), arguments).sign_with_keypair(keys);
+    let block = testkit.create_block_with_transaction(tx);
+    block[0].status().map_err(Clone::clone)
+}
+
+#[test]
+fn service_initialization_and_hooks() {
+    let dir = TempDir::new().unwrap();
+    let committed_blocks = Arc::new(AtomicU64::new(0));
+    let (mut testkit, _) = create_testkit(spawn_child(&dir, &committed_blocks));
+    assert_eq!(stored_u64(&testkit, "value"), Some(10));
+    assert_eq!(stored_u64(&testkit, "blocks"), Some(1));
+
+    testkit.create_blocks_until(Height(3));
+    assert_eq!(stored_u64(&testkit, "blocks"), Some(4));
+    assert_eq!(committed_blocks.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn transactions_modify_service_data() {
+    let dir = TempDir::new().unwrap();
+    let (mut testkit, keys) = create_testkit(spawn_child(&dir, &Arc::default()));
+
+    execute(&mut testkit, &keys, 0, 5_u64.to_le_bytes().to_vec()).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), Some(15));
+
+    execute(&mut testkit, &keys, 1, vec![]).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), None);
+    let owner = stored_value(&testkit, "owner").unwrap();
+    assert_eq!(owner, keys.public_key().as_ref());
+}
+
+#[test]
+fn service_errors() {
+    let dir = TempDir::new().unwrap();
+    let (mut testkit, keys) = create_testkit(spawn_child(&dir, &Arc::default()));
+
+    let err = execute(&mut testkit, &keys, 0, 0_u64.to_le_bytes().to_vec()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 1 });
+    assert_eq!(err.description(), "Cannot add zero");
+
+    let err = execute(&mut testkit, &keys, 0, vec![1]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 0 });
+    assert_eq!(err.description(), "Invalid arguments");
+
+    let err = execute(&mut testkit, &keys, 10, vec![]).unwrap_err();
+    let expected_err = ErrorMatch::from_fail(&CommonError::NoSuchMethod)
+        .with_any_description()
+        .for_service(COUNTER_ID);
+    assert_eq!(err, expected_err);
+
+    // Changes made by failed transactions are rolled back.
+    assert_eq!(stored_u64(&testkit, "value"), Some(10));
+}
+
+#[test]
+fn service_panics_are_reported_as_errors() {
+    let dir = TempDir::new().unwrap();
+    let (mut testkit, keys) = create_testkit(spawn_child(&dir, &Arc::default()));
+
+    let err = execute(&mut testkit, &keys, 5, vec![]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unexpected);
+    assert_eq!(err.description(), "Service panic");
+
+    // The child process continues serving requests over the same connection.
+    execute(&mut testkit, &keys, 0, 5_u64.to_le_bytes().to_vec()).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), Some(15));
+}
+
+#[test]
+fn child_timeout_is_retried() {
+    let dir = TempDir::new().unwrap();
+    let (mut testkit, keys) = create_testkit(spawn_child(&dir, &Arc::default()));
+
+    // The runtime reconnects to the child, redeploys artifacts and retries the call.
+    // Changes made during the interrupted attempt are discarded.
+    execute(&mut testkit, &keys, 2, vec![]).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), Some(11));
+
+    execute(&mut testkit, &keys, 0, 5_u64.to_le_bytes().to_vec()).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), Some(16));
+}
+
+#[test]
+fn child_crash_is_retried() {
+    let dir = TempDir::new().unwrap();
+    let (mut testkit, keys) = create_testkit(spawn_child(&dir, &Arc::default()));
+
+    execute(&mut testkit, &keys, 3, vec![]).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), Some(11));
+
+    execute(&mut testkit, &keys, 0, 5_u64.to_le_bytes().to_vec()).unwrap();
+    assert_eq!(stored_u64(&testkit, "value"), Some(16));
+}
+
+#[test]
+#[should_panic(expected = "The node is halted")]
+fn persistent_child_failure_halts_node() {
+    let dir = TempDir::new().unwrap();
+    let (mut testkit, keys) = create_testkit(spawn_child(&dir, &Arc::default()));
+    execute(&mut testkit, &keys, 4, vec![]).ok();
+}
+
+#[test]
+fn unknown_artifacts_are_not_deployed() {
+    let dir = TempDir::new().unwrap();
+    let mut runtime = ProcessRuntime::new(spawn_child(&dir, &Arc::default()));
+
+    let artifact = ArtifactId::from_raw_parts(
+        ProcessRuntime::ID,
+        "unknown".to_owned(),
+        Version::new(1, 0, 0),
+    );
+    runtime.deploy_artifact(artifact.clone(), vec![]);
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    runtime.deploy_artifact(counter_artifact(), vec![]);
+    assert!(runtime.is_artifact_deployed(&counter_artifact()));
+}
+
+#[test]
+fn unavailable_child() {
+    let dir = TempDir::new().unwrap();
+    let endpoint = Endpoint::UnixSocket(dir.path().join("missing.sock"));
+    let mut runtime = ProcessRuntime::new(endpoint);
+    runtime.deploy_artifact(counter_artifact(), vec![]);
+    assert!(!runtime.is_artifact_deployed(&counter_artifact()));
+}
@@ -27,4 +27,6 @@ pub enum Error {
     /// Unable to deploy artifact with the specified identifier, it is not listed
     /// among available artifacts.
     UnableToDeploy = 1,
+    /// Service called by another service has not returned a value of the expected type.
+    MalformedReturnValue = 2,
 }
@@ -217,6 +217,7 @@
 //! | `(PublicKey, SecretKey)` | Generates signed transactions |
 //! | [`Broadcaster`] | Broadcasts transactions signed by the service keys of the node |
 //! | [`ExecutionContext`] | Calls methods of another service during transaction execution **(1)** |
+//! | [`Returning`] | Same as `ExecutionContext`, but decodes the value returned by the called method **(1)** |
 //!
 //! 1. Beware that this is experimental functionality which is subject to change in next releases.
 //!
@@ -237,7 +238,58 @@
 //! to mutate their state when processing the calls. Hence, the mutable trait should be
 //! exported from the crate along with the original "immutable" trait.
 //!
+//! ## Return values
+//!
+//! Interface methods called by other services may return a value to the caller. The value
+//! can be any type implementing `BinaryValue`; its type is declared for each method
+//! with the `returns` field of the `interface_method` attribute, e.g.,
+//! `#[interface_method(id = 0, returns = "u64")]`. The value is set by the called service with
+//! [`ExecutionContext::set_return_value()`]. For interfaces with such methods, the macro
+//! generates one more trait with the `Returning` suffix, in which the methods return
+//! the declared types; this trait is implemented by the [`Returning`] stub, which decodes
+//! returned values on the caller side. Values returned by methods invoked by transactions
+//! from external clients are discarded.
+//!
+//! ## Queries
+//!
+//! Services may expose read-only queries, which are dispatched by the core via
+//! `Runtime::query()` and are available to any client (e.g., via the explorer API)
+//! without knowing the HTTP API of the service. Queries are declared as an interface
+//! with the `query` attribute and take a [`QueryContext`] as the context. Query methods return
+//! serialized values.
+//!
+//! ```
+//! # use exonum::{merkledb::{access::AccessExt, BinaryValue}, runtime::ExecutionError};
+//! # use exonum_derive::*;
+//! # use exonum_rust_runtime::{QueryContext, Service};
+//! #[exonum_interface(query)]
+//! pub trait Queries<Ctx> {
+//!     type Output;
+//!     #[interface_method(id = 0)]
+//!     fn balance(&self, context: Ctx, wallet: String) -> Self::Output;
+//! }
+//!
+//! #[derive(Debug, ServiceDispatcher, ServiceFactory)]
+//! #[service_dispatcher(queries = "Queries")]
+//! #[service_factory(artifact_name = "wallets", artifact_version = "1.0.0")]
+//! pub struct WalletService;
+//!
+//! impl Queries<QueryContext<'_>> for WalletService {
+//!     type Output = Result<Vec<u8>, ExecutionError>;
+//!
+//!     fn balance(&self, context: QueryContext<'_>, wallet: String) -> Self::Output {
+//!         let balances = context.service_data().get_map::<_, String, u64>("balances");
+//!         Ok(balances.get(&wallet).unwrap_or_default().into_bytes())
+//!     }
+//! }
+//!
+//! impl Service for WalletService {}
+//! ```
+//!
+//! [`QueryContext`]: struct.QueryContext.html
 //! [`TxStub`]: struct.TxStub.html
+//! [`Returning`]: struct.Returning.html
+//! [`ExecutionContext::set_return_value()`]: struct.ExecutionContext.html#method.set_return_value
 //! [`Broadcaster`]: struct.Broadcaster.html
 //! [`ExecutionContext`]: struct.ExecutionContext.html
 //! [`GenericCall`]: trait.GenericCall.html
@@ -325,10 +377,13 @@ pub use self::{
     error::Error,
     runtime_api::{ArtifactProtobufSpec, ProtoSourceFile, ProtoSourcesQuery},
     service::{
-        AfterCommitContext, Broadcaster, DefaultInstance, Service, ServiceDispatcher,
+        AfterCommitContext, Broadcaster, DefaultInstance, QueryContext, Service, ServiceDispatcher,
         ServiceFactory,
     },
-    stubs::{FallthroughAuth, GenericCall, GenericCallMut, Interface, MethodDescriptor, TxStub},
+    stubs::{
+        FallthroughAuth, GenericCall, GenericCallMut, GenericCallReturning, Interface,
+        MethodDescriptor, QueryInterface, Returning, TxStub,
+    },
 };
 
 pub mod api;
@@ -337,14 +392,14 @@ pub mod spec;
 use exonum::{
     blockchain::{Blockchain, Schema as CoreSchema},
     helpers::Height,
-    merkledb::Snapshot,
+    merkledb::{access::AccessExt, Snapshot},
     runtime::{
         catch_panic,
         migrations::{InitMigrationError, MigrateData, MigrationScript},
         oneshot::Receiver,
         versioning::Version,
-        ArtifactId, ExecutionError, ExecutionFail, InstanceDescriptor, InstanceId, InstanceSpec,
-        InstanceState, InstanceStatus, Mailbox, MethodId, Runtime, RuntimeFeature,
+        ArtifactId, CoreError, ExecutionError, ExecutionFail, InstanceDescriptor, InstanceId,
+        InstanceSpec, InstanceState, InstanceStatus, Mailbox, MethodId, Runtime, RuntimeFeature,
         RuntimeIdentifier, WellKnownRuntime,
     },
 };
@@ -352,7 +407,10 @@ use exonum_api::{ApiBuilder, UpdateEndpoints};
 use futures::{channel::mpsc, executor, SinkExt};
 use log::trace;
 
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex, MutexGuard},
+};
 
 use self::api::ServiceApiBuilder;
 
@@ -366,6 +424,7 @@ pub mod _reexports {
     //! Types necessary for `ServiceDispatcher` and `ServiceFactory` derive macros to work.
 
     pub use exonum::runtime::{
+        versioning::{ArtifactReq, InterfaceReq, ProvidedInterface},
         ArtifactId, CommonError, ExecutionContext, ExecutionError, MethodId, RuntimeIdentifier,
     };
 }
@@ -401,6 +460,45 @@ impl<T> MigrateData for WithoutMigrations<T> {
     }
 }
 
+/// Service factory shared by the runtime and its replicas.
+#[derive(Debug, Clone)]
+struct SharedFactory(Arc<Mutex<Box<dyn FactoryWithMigrations>>>);
+
+impl SharedFactory {
+    fn new(factory: Box<dyn FactoryWithMigrations>) -> Self {
+        Self(Arc::new(Mutex::new(factory)))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Box<dyn FactoryWithMigrations>> {
+        self.0
+            .lock()
+            .expect("BUG: Service factory lock is poisoned")
+    }
+}
+
+impl ServiceFactory for SharedFactory {
+    fn artifact_id(&self) -> ArtifactId {
+        self.lock().artifact_id()
+    }
+
+    fn artifact_protobuf_spec(&self) -> ArtifactProtobufSpec {
+        self.lock().artifact_protobuf_spec()
+    }
+
+    fn create_instance(&self) -> Box<dyn Service> {
+        self.lock().create_instance()
+    }
+}
+
+impl MigrateData for SharedFactory {
+    fn migration_scripts(
+        &self,
+        start_version: &Version,
+    ) -> Result<Vec<MigrationScript>, InitMigrationError> {
+        self.lock().migration_scripts(start_version)
+    }
+}
+
 /// Rust runtime entity.
 ///
 /// [Detailed description of the Rust runtime](index.html).
@@ -408,7 +506,7 @@ impl<T> MigrateData for WithoutMigrations<T> {
 pub struct RustRuntime {
     blockchain: Option<Blockchain>,
     api_notifier: mpsc::Sender<UpdateEndpoints>,
-    available_artifacts: HashMap<ArtifactId, Box<dyn FactoryWithMigrations>>,
+    available_artifacts: HashMap<ArtifactId, SharedFactory>,
     deployed_artifacts: HashSet<ArtifactId>,
     started_services: BTreeMap<InstanceId, Instance>,
     started_services_by_name: HashMap<String, InstanceId>,
@@ -485,20 +583,22 @@ impl RustRuntimeBuilder {
         self
     }
 
+    /// Returns Protobuf specifications of all artifacts available in the runtime.
+    pub fn artifact_protobuf_specs(&self) -> HashMap<ArtifactId, ArtifactProtobufSpec> {
+        self.available_artifacts
+            .iter()
+            .map(|(artifact_id, factory)| (artifact_id.clone(), factory.artifact_protobuf_spec()))
+            .collect()
+    }
+
     /// Completes the build process, converting the builder into a `RustRuntime`.
     pub fn build(self, api_notifier: mpsc::Sender<UpdateEndpoints>) -> RustRuntime {
-        RustRuntime {
-            blockchain: None,
-            api_notifier,
-            available_artifacts: self.available_artifacts,
-            deployed_artifacts: HashSet::new(),
-            started_services: BTreeMap::new(),
-            started_services_by_name: HashMap::new(),
-            changed_services_since_last_block: true,
-            // ^-- We set this flag to `true` to propagate initial changes to API (which always
-            // include the runtime API) after the runtime is resumed or the genesis block
-            // is created.
-        }
+        let available_artifacts = self
+            .available_artifacts
+            .into_iter()
+            .map(|(artifact, factory)| (artifact, SharedFactory::new(factory)))
+            .collect();
+        RustRuntime::new(available_artifacts, api_notifier)
     }
 
     /// Builds the Rust runtime without connection to the HTTP API. As the name implies,
@@ -511,6 +611,29 @@ impl RustRuntimeBuilder {
 impl RustRuntime {
     /// Rust runtime name.
     pub const NAME: &'static str = "rust";
+    /// Maximum number of expired entries purged from the [expiring indexes] of a single service
+    /// in a block.
+    ///
+    /// [expiring indexes]: trait.Service.html#method.expiring_indexes
+    pub const MAX_PURGED_ENTRIES_PER_BLOCK: usize = 256;
+
+    fn new(
+        available_artifacts: HashMap<ArtifactId, SharedFactory>,
+        api_notifier: mpsc::Sender<UpdateEndpoints>,
+    ) -> Self {
+        Self {
+            blockchain: None,
+            api_notifier,
+            available_artifacts,
+            deployed_artifacts: HashSet::new(),
+            started_services: BTreeMap::new(),
+            started_services_by_name: HashMap::new(),
+            changed_services_since_last_block: true,
+            // ^-- We set this flag to `true` to propagate initial changes to API (which always
+            // include the runtime API) after the runtime is resumed or the genesis block
+            // is created.
+        }
+    }
 
     /// Returns a new builder for the runtime.
     pub fn builder() -> RustRuntimeBuilder {
@@ -662,6 +785,29 @@ impl RustRuntime {
         self.changed_services_since_last_block = false;
     }
 
+    /// Purges expired entries in the expiring indexes the service has opted in for.
+    /// Purging is charged to the resource meter of the hook, so the number of purged entries
+    /// is bounded to keep the hook within resource limits if many entries expire at once.
+    fn purge_expiring_indexes(service: &dyn Service, context: &ExecutionContext<'_>) {
+        let index_names = service.expiring_indexes();
+        if index_names.is_empty() {
+            return;
+        }
+
+        let height = context.data().for_core().next_height();
+        let service_data = context.service_data();
+        let mut budget = Self::MAX_PURGED_ENTRIES_PER_BLOCK;
+        for name in index_names {
+            if budget == 0 {
+                break;
+            }
+            // Keys and values are not decoded during purging, so we may use raw types.
+            budget -= service_data
+                .get_expiring_map::<_, [u8], Vec<u8>>(name)
+                .purge_expired_limited(height.0, budget);
+        }
+    }
+
     fn artifacts_to_pretty_string(&self) -> String {
         if self.available_artifacts.is_empty() {
             return "None".to_string();
@@ -686,11 +832,19 @@ impl Runtime for RustRuntime {
 
     fn is_supported(&self, feature: &RuntimeFeature) -> bool {
         match feature {
-            RuntimeFeature::FreezingServices => true,
+            RuntimeFeature::FreezingServices
+            | RuntimeFeature::UnsignedSimulation
+            | RuntimeFeature::Queries => true,
             _ => false,
         }
     }
 
+    fn replicate(&self) -> Option<Box<dyn Runtime>> {
+        // The replica does not serve HTTP API, so the receiver of endpoint updates is dropped.
+        let replica = Self::new(self.available_artifacts.clone(), mpsc::channel(1).0);
+        Some(Box::new(replica))
+    }
+
     // Propagates changes in the services immediately after initialization.
     fn on_resume(&mut self) {
         self.push_api_changes();
@@ -844,7 +998,29 @@ impl Runtime for RustRuntime {
             .get(&context.instance().id)
             .expect("`after_transactions` called with non-existing `instance_id`");
 
-        catch_panic(|| instance.as_ref().after_transactions(context))
+        catch_panic(|| {
+            let service = instance.as_ref();
+            Self::purge_expiring_indexes(service, &context);
+            service.after_transactions(context)
+        })
+    }
+
+    fn query(
+        &self,
+        instance: &InstanceDescriptor,
+        method_id: MethodId,
+        arguments: &[u8],
+        snapshot: &dyn Snapshot,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        // The service may be absent even if its status provides read access; e.g., this is
+        // the case for a migrating service not associated with a deployed artifact.
+        let service = self.started_services.get(&instance.id).ok_or_else(|| {
+            let msg = format!("Service `{}` is not started", instance);
+            CoreError::IncorrectInstanceId.with_description(msg)
+        })?;
+
+        let context = QueryContext::new(instance.to_owned(), snapshot);
+        catch_panic(|| service.as_ref().query(context, method_id, arguments))
     }
 
     fn after_commit(&mut self, snapshot: &dyn Snapshot, mailbox: &mut Mailbox) {
@@ -18,8 +18,10 @@ use exonum::{
     helpers::{Height, ValidatorId},
     merkledb::{access::Prefixed, BinaryValue, ObjectHash, Snapshot},
     runtime::{
-        ArtifactId, BlockchainData, DispatcherAction, ExecutionContext, ExecutionError,
-        InstanceDescriptor, InstanceId, InstanceStatus, Mailbox, MethodId, SnapshotExt,
+        versioning::{ArtifactReq, InterfaceReq, ProvidedInterface},
+        ArtifactId, BlockchainData, CommonError, DispatcherAction, ExecutionContext,
+        ExecutionError, InstanceDescriptor, InstanceId, InstanceStatus, Mailbox, MethodId,
+        SnapshotExt,
     },
 };
 use futures::{
@@ -44,6 +46,21 @@ pub trait ServiceDispatcher: Send {
         method: MethodId,
         payload: &[u8],
     ) -> Result<(), ExecutionError>;
+
+    /// Dispatches a read-only query within the specified context and returns
+    /// the serialized result.
+    ///
+    /// The default implementation returns a `NoSuchInterface` error, meaning that the service
+    /// does not support queries. Queries can be dispatched by the `ServiceDispatcher` derive macro
+    /// via the `queries` attribute.
+    fn query(
+        &self,
+        _context: QueryContext<'_>,
+        _method: MethodId,
+        _payload: &[u8],
+    ) -> Result<Vec<u8>, ExecutionError> {
+        Err(CommonError::NoSuchInterface.into())
+    }
 }
 
 /// Describes an Exonum service instance.
@@ -127,6 +144,28 @@ pub trait Service: ServiceDispatcher + Debug + 'static {
         Ok(())
     }
 
+    /// Returns names of [`ExpiringMapIndex`]es within the service namespace, expired entries
+    /// of which should be purged automatically. The indexes are purged by the runtime
+    /// in each block immediately before [`after_transactions`](#method.after_transactions);
+    /// the entries with the expiration height less or equal to the height of the block
+    /// are removed.
+    ///
+    /// To keep the work in each block bounded, at most
+    /// [`RustRuntime::MAX_PURGED_ENTRIES_PER_BLOCK`] entries are purged per service in a block.
+    /// If more entries have expired, the remaining entries are purged in the following blocks,
+    /// and remain accessible until then; thus, the service should check
+    /// the expiration height of entries if it is important for the service logic.
+    /// Purging is charged to the resource limits of `after_transactions`.
+    ///
+    /// The default implementation returns an empty list, meaning that the service purges
+    /// its expiring indexes (if any) itself.
+    ///
+    /// [`ExpiringMapIndex`]: https://docs.rs/exonum-merkledb/latest/exonum_merkledb/indexes/struct.ExpiringMapIndex.html
+    /// [`RustRuntime::MAX_PURGED_ENTRIES_PER_BLOCK`]: struct.RustRuntime.html#associatedconstant.MAX_PURGED_ENTRIES_PER_BLOCK
+    fn expiring_indexes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Handles block commit event.
     ///
     /// This handler is a callback which is invoked by the blockchain
@@ -159,6 +198,43 @@ pub trait ServiceFactory: Send + Debug + 'static {
     fn artifact_protobuf_spec(&self) -> ArtifactProtobufSpec;
     /// Creates a new service instance.
     fn create_instance(&self) -> Box<dyn Service>;
+
+    /// Returns requirements on other artifacts, which must be active for this artifact
+    /// to be deployed or instantiated. By default, the artifact has no dependencies.
+    ///
+    /// Dependencies are included into the artifact specification when the artifact
+    /// is added to the genesis block via [`Spec`]. For the artifacts deployed
+    /// via the supervisor, the dependencies need to be specified in the deploy request.
+    ///
+    /// If the service calls an interface of another service and does not care which
+    /// artifact implements it, use [`required_interfaces`] instead.
+    ///
+    /// [`Spec`]: spec/struct.Spec.html
+    /// [`required_interfaces`]: #method.required_interfaces
+    fn artifact_dependencies(&self) -> Vec<ArtifactReq> {
+        Vec::new()
+    }
+
+    /// Returns requirements on interfaces, which must be provided by active artifacts
+    /// for this artifact to be deployed or instantiated. By default, the artifact
+    /// requires no interfaces.
+    ///
+    /// Similar to [`artifact_dependencies`], the requirements are included into
+    /// the artifact specification when the artifact is added to the genesis block
+    /// via [`Spec`]; for the artifacts deployed via the supervisor, they need
+    /// to be specified in the deploy request.
+    ///
+    /// [`artifact_dependencies`]: #method.artifact_dependencies
+    /// [`Spec`]: spec/struct.Spec.html
+    fn required_interfaces(&self) -> Vec<InterfaceReq> {
+        Vec::new()
+    }
+
+    /// Returns versioned interfaces provided by the services of this artifact.
+    /// By default, the artifact declares no interfaces.
+    fn provided_interfaces(&self) -> Vec<ProvidedInterface> {
+        Vec::new()
+    }
 }
 
 #[allow(clippy::use_self)] // false positive
@@ -185,6 +261,46 @@ pub trait DefaultInstance: ServiceFactory {
     }
 }
 
+/// Provide context for read-only queries to the service.
+///
+/// Queries are executed against the latest blockchain state and cannot modify it.
+pub struct QueryContext<'a> {
+    /// Descriptor of the queried service.
+    instance: InstanceDescriptor,
+    /// Read-only snapshot of the current blockchain state.
+    snapshot: &'a dyn Snapshot,
+}
+
+impl<'a> QueryContext<'a> {
+    /// Creates a new query context.
+    pub(crate) fn new(instance: InstanceDescriptor, snapshot: &'a dyn Snapshot) -> Self {
+        Self { instance, snapshot }
+    }
+
+    /// Returns the descriptor of the queried service.
+    pub fn instance(&self) -> &InstanceDescriptor {
+        &self.instance
+    }
+
+    /// Returns blockchain data for the snapshot associated with this context.
+    pub fn data(&self) -> BlockchainData<&'a dyn Snapshot> {
+        BlockchainData::new(self.snapshot, &self.instance.name)
+    }
+
+    /// Returns snapshot of the data for the queried service.
+    pub fn service_data(&self) -> Prefixed<&'a dyn Snapshot> {
+        self.data().for_executing_service()
+    }
+}
+
+impl Debug for QueryContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryContext")
+            .field("instance", &self.instance)
+            .finish()
+    }
+}
+
 /// Provide context for the `after_commit` handler.
 pub struct AfterCommitContext<'a> {
     /// Reference to the dispatcher mailbox.
@@ -23,7 +23,11 @@
 use exonum::{
     blockchain::config::{GenesisConfigBuilder, InstanceInitParams},
     merkledb::BinaryValue,
-    runtime::{migrations::MigrateData, ArtifactId, InstanceId, RuntimeIdentifier},
+    runtime::{
+        migrations::MigrateData,
+        versioning::{ArtifactReq, InterfaceReq, ProvidedInterface},
+        ArtifactId, ArtifactSpec, InstanceId, RuntimeIdentifier,
+    },
 };
 
 use std::{marker::PhantomData, mem};
@@ -43,6 +47,15 @@ pub trait Deploy: Sealed {
     fn deploy(self, genesis: &mut GenesisConfigBuilder, rt: &mut RustRuntimeBuilder);
 }
 
+/// Creates an artifact specification including the dependencies and interfaces declared
+/// by the factory.
+fn artifact_spec(factory: &impl ServiceFactory) -> ArtifactSpec {
+    ArtifactSpec::new(factory.artifact_id(), ())
+        .with_dependencies(factory.artifact_dependencies())
+        .with_required_interfaces(factory.required_interfaces())
+        .with_provided_interfaces(factory.provided_interfaces())
+}
+
 /// Marker type for artifact deployment without migration support.
 #[derive(Debug)]
 pub struct Simple(());
@@ -151,7 +164,7 @@ impl<T: ServiceFactory> Sealed for Spec<T, Simple> {}
 
 impl<T: ServiceFactory> Deploy for Spec<T, Simple> {
     fn deploy(self, genesis: &mut GenesisConfigBuilder, rt: &mut RustRuntimeBuilder) {
-        let mut new_config = mem::take(genesis).with_artifact(self.service.artifact_id());
+        let mut new_config = mem::take(genesis).with_artifact_spec(artifact_spec(&self.service));
         let instances = self.default_instance.into_iter().chain(self.instances);
         for instance in instances {
             new_config = new_config.with_instance(instance);
@@ -166,7 +179,7 @@ impl<T: ServiceFactory> Sealed for Spec<T, Migrating> {}
 
 impl<T: ServiceFactory + MigrateData> Deploy for Spec<T, Migrating> {
     fn deploy(self, genesis: &mut GenesisConfigBuilder, rt: &mut RustRuntimeBuilder) {
-        let mut new_config = mem::take(genesis).with_artifact(self.service.artifact_id());
+        let mut new_config = mem::take(genesis).with_artifact_spec(artifact_spec(&self.service));
         let instances = self.default_instance.into_iter().chain(self.instances);
         for instance in instances {
             new_config = new_config.with_instance(instance);
@@ -253,6 +266,9 @@ impl<T: ServiceFactory + MigrateData> Deploy for JustFactory<T, Migrating> {
 pub struct ForeignSpec {
     artifact: ArtifactId,
     deploy_spec: Option<Vec<u8>>,
+    dependencies: Vec<ArtifactReq>,
+    required_interfaces: Vec<InterfaceReq>,
+    provided_interfaces: Vec<ProvidedInterface>,
     instances: Vec<InstanceInitParams>,
 }
 
@@ -269,6 +285,9 @@ impl ForeignSpec {
         Self {
             artifact,
             deploy_spec: None,
+            dependencies: vec![],
+            required_interfaces: vec![],
+            provided_interfaces: vec![],
             instances: vec![],
         }
     }
@@ -279,6 +298,35 @@ impl ForeignSpec {
         self
     }
 
+    /// Adds requirements on other artifacts, which must be deployed together
+    /// with this artifact.
+    pub fn with_dependencies(
+        mut self,
+        dependencies: impl IntoIterator<Item = ArtifactReq>,
+    ) -> Self {
+        self.dependencies.extend(dependencies);
+        self
+    }
+
+    /// Adds requirements on interfaces, which must be provided by the artifacts
+    /// deployed together with this artifact.
+    pub fn with_required_interfaces(
+        mut self,
+        interfaces: impl IntoIterator<Item = InterfaceReq>,
+    ) -> Self {
+        self.required_interfaces.extend(interfaces);
+        self
+    }
+
+    /// Adds interfaces provided by this artifact.
+    pub fn with_provided_interfaces(
+        mut self,
+        interfaces: impl IntoIterator<Item = ProvidedInterface>,
+    ) -> Self {
+        self.provided_interfaces.extend(interfaces);
+        self
+    }
+
     /// Adds a new built-in service instance to instantiate at the genesis block.
     pub fn with_instance(
         mut self,
@@ -300,11 +348,12 @@ impl Sealed for ForeignSpec {}
 
 impl Deploy for ForeignSpec {
     fn deploy(self, genesis: &mut GenesisConfigBuilder, _: &mut RustRuntimeBuilder) {
-        let mut new_config = if let Some(deploy_spec) = self.deploy_spec {
-            mem::take(genesis).with_parametric_artifact(self.artifact, deploy_spec)
-        } else {
-            mem::take(genesis).with_artifact(self.artifact)
-        };
+        let deploy_spec = self.deploy_spec.unwrap_or_default();
+        let spec = ArtifactSpec::new(self.artifact, deploy_spec)
+            .with_dependencies(self.dependencies)
+            .with_required_interfaces(self.required_interfaces)
+            .with_provided_interfaces(self.provided_interfaces);
+        let mut new_config = mem::take(genesis).with_artifact_spec(spec);
 
         for instance in self.instances {
             new_config = new_config.with_instance(instance);
@@ -19,13 +19,16 @@
 
 use exonum::{
     crypto::{KeyPair, PublicKey, SecretKey},
+    merkledb::BinaryValue,
     messages::Verified,
     runtime::{
-        AnyTx, CallInfo, ExecutionContext, ExecutionContextUnstable, ExecutionError, InstanceId,
-        InstanceQuery, MethodId,
+        AnyTx, CallInfo, ExecutionContext, ExecutionContextUnstable, ExecutionError, ExecutionFail,
+        InstanceId, InstanceQuery, MethodId,
     },
 };
 
+use crate::{Error, QueryContext};
+
 /// Descriptor of a method declared as a part of the service interface.
 #[derive(Debug, Clone, Copy)]
 pub struct MethodDescriptor<'a> {
@@ -67,6 +70,19 @@ pub trait Interface<'a> {
     ) -> Result<(), ExecutionError>;
 }
 
+/// A read-only query interface specification.
+///
+/// Query interfaces are declared with the `#[exonum_interface(query)]` attribute.
+pub trait QueryInterface<'a> {
+    /// Invokes the specified query handler of the service instance.
+    fn dispatch_query(
+        &self,
+        context: QueryContext<'a>,
+        method: MethodId,
+        payload: &[u8],
+    ) -> Result<Vec<u8>, ExecutionError>;
+}
+
 /// Generic / low-level stub implementation which is defined for any method in any interface.
 pub trait GenericCall<Ctx> {
     /// Type of values output by the stub.
@@ -96,6 +112,21 @@ pub trait GenericCallMut<Ctx> {
     ) -> Self::Output;
 }
 
+/// Generic / low-level stub implementation which decodes values returned by the called methods.
+///
+/// Interface methods declaring the returned type with `#[interface_method(returns = "...")]`
+/// can be called with any type implementing this trait via the `*Returning` trait generated
+/// by the `exonum_interface` macro.
+pub trait GenericCallReturning<Ctx> {
+    /// Calls a stub method and decodes the value returned by it.
+    fn generic_call_returning<T: BinaryValue>(
+        &mut self,
+        context: Ctx,
+        method: MethodDescriptor<'_>,
+        args: Vec<u8>,
+    ) -> Result<T, ExecutionError>;
+}
+
 /// Stub that creates unsigned transactions.
 ///
 /// # Examples
@@ -291,6 +322,99 @@ where
             args.as_ref(),
             false,
         )
+        .map(drop)
+    }
+}
+
+/// Stub which calls methods of another service and decodes the value returned by the called
+/// service. Values are returned by services via [`ExecutionContext::set_return_value()`].
+///
+/// The stub can call interface methods declaring the returned type
+/// with `#[interface_method(returns = "...")]`. Such methods are available in the trait
+/// with the `Returning` suffix generated by the `exonum_interface` macro.
+///
+/// If the called service has not returned a value, or the value cannot be decoded
+/// as the declared type, the call results in [`Error::MalformedReturnValue`]. In this case,
+/// the nested call is treated as failed: changes made by the called service are rolled back
+/// together with other changes in the topmost call, and the error should bubble up
+/// to the top level as with other errors in nested calls.
+///
+/// [`ExecutionContext::set_return_value()`]: struct.ExecutionContext.html#method.set_return_value
+/// [`Error::MalformedReturnValue`]: enum.Error.html#variant.MalformedReturnValue
+///
+/// # Examples
+///
+/// ```
+/// # use exonum::runtime::ExecutionError;
+/// # use exonum_derive::*;
+/// use exonum_rust_runtime::{ExecutionContext, Returning};
+///
+/// #[exonum_interface]
+/// trait Counter<Ctx> {
+///     type Output;
+///     #[interface_method(id = 0, returns = "u64")]
+///     fn increment(&self, ctx: Ctx, by: u64) -> Self::Output;
+/// }
+///
+/// // Implementation of the interface by the callee service.
+/// # struct CounterService;
+/// impl Counter<ExecutionContext<'_>> for CounterService {
+///     type Output = Result<(), ExecutionError>;
+///
+///     fn increment(&self, mut ctx: ExecutionContext<'_>, by: u64) -> Self::Output {
+///         let new_value = // ...
+/// #           by;
+///         ctx.set_return_value(new_value);
+///         Ok(())
+///     }
+/// }
+///
+/// // Calling the interface from another service.
+/// fn increment_counter(ctx: &mut ExecutionContext<'_>) -> Result<(), ExecutionError> {
+///     let new_value: u64 = Returning::new(ctx).increment("counter", 1)?;
+///     // Use `new_value`...
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Returning<'c, 'a> {
+    context: &'c mut ExecutionContext<'a>,
+}
+
+impl<'c, 'a> Returning<'c, 'a> {
+    /// Creates a stub which makes calls within the provided context.
+    pub fn new(context: &'c mut ExecutionContext<'a>) -> Self {
+        Self { context }
+    }
+}
+
+impl<'c, 'a, I> GenericCallReturning<I> for Returning<'c, 'a>
+where
+    I: Into<InstanceQuery<'a>>,
+{
+    fn generic_call_returning<T: BinaryValue>(
+        &mut self,
+        called_instance: I,
+        method: MethodDescriptor<'_>,
+        args: Vec<u8>,
+    ) -> Result<T, ExecutionError> {
+        let bytes = self.context.make_child_call(
+            called_instance,
+            method.interface_name,
+            method.id,
+            args.as_ref(),
+            false,
+        )?;
+
+        let value = bytes
+            .ok_or_else(|| "Called service has not returned a value".to_owned())
+            .and_then(|bytes| T::from_bytes(bytes.into()).map_err(|err| err.to_string()));
+        value.map_err(|msg| {
+            // The called service has completed successfully, but its changes must not
+            // be committed since the caller cannot process the result of the call.
+            self.context.reject_child_call();
+            Error::MalformedReturnValue.with_description(msg)
+        })
     }
 }
 
@@ -311,12 +435,14 @@ where
         method: MethodDescriptor<'_>,
         args: Vec<u8>,
     ) -> Self::Output {
-        self.0.make_child_call(
-            called_instance,
-            method.interface_name,
-            method.id,
-            args.as_ref(),
-            true,
-        )
+        self.0
+            .make_child_call(
+                called_instance,
+                method.interface_name,
+                method.id,
+                args.as_ref(),
+                true,
+            )
+            .map(drop)
     }
 }
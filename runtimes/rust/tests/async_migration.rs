@@ -14,7 +14,7 @@
 
 use assert_matches::assert_matches;
 use exonum::{
-    blockchain::{ApiSender, Blockchain, BlockchainBuilder, BlockchainMut},
+    blockchain::{ApiSender, Blockchain, BlockchainBuilder, BlockchainMut, Query},
     crypto::KeyPair,
     helpers::Height,
     merkledb::{
@@ -558,6 +558,15 @@ fn two_step_migration_without_intermediate_update() {
     // Since the service is not associated with a deployed artifact, service endpoints
     // should remain switched off.
     assert_no_endpoint_update(&mut endpoints_rx);
+
+    // Queries to the service should fail for the same reason.
+    let query = Query::new(CounterFactory::INSTANCE_ID, 0, vec![]);
+    let err = blockchain.query(&query).unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::IncorrectInstanceId)
+            .with_description_containing("`100:counter` is not started")
+    );
 }
 
 #[test]
@@ -20,15 +20,17 @@ use exonum::{
     helpers::Height,
     merkledb::{access::AccessExt, BinaryValue, SystemSchema},
     runtime::{
-        Caller, CommonError, CoreError, ErrorMatch, ExecutionContext, ExecutionError,
-        InstanceStatus, SnapshotExt,
+        versioning::ArtifactReq, ArtifactSpec, Caller, CommonError, CoreError, ErrorMatch,
+        ExecutionContext, ExecutionError, InstanceStatus, SnapshotExt,
     },
 };
 use exonum_derive::{exonum_interface, BinaryValue, ServiceDispatcher, ServiceFactory};
 use pretty_assertions::assert_eq;
 use serde_derive::*;
 
-use exonum_rust_runtime::{DefaultInstance, RustRuntimeBuilder, Service, ServiceFactory};
+use exonum_rust_runtime::{
+    DefaultInstance, RustRuntime, RustRuntimeBuilder, Service, ServiceFactory,
+};
 
 use self::inspected::{
     create_block_with_transactions, create_genesis_config_builder, execute_transaction,
@@ -200,6 +202,51 @@ impl DefaultInstance for DependentServiceImpl {
     }
 }
 
+#[derive(Debug, ServiceDispatcher, ServiceFactory)]
+#[service_dispatcher(implements())]
+#[service_factory(
+    artifact_name = "requiring_service",
+    artifact_version = "0.1.0",
+    requires = "test_service@^0.1",
+    requires = "toy_supervisor@>=0.1.0, <1.0.0"
+)]
+pub struct RequiringServiceImpl;
+
+impl Service for RequiringServiceImpl {}
+
+#[derive(Debug, ServiceDispatcher, ServiceFactory)]
+#[service_dispatcher(implements())]
+#[service_factory(artifact_name = "expiring_service", artifact_version = "0.1.0")]
+pub struct ExpiringServiceImpl;
+
+impl Service for ExpiringServiceImpl {
+    fn initialize(
+        &self,
+        context: ExecutionContext<'_>,
+        _params: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        let mut nonces = context.service_data().get_expiring_map("nonces");
+        nonces.put(&1_u64, "first".to_owned(), 1);
+        nonces.put(&2_u64, "second".to_owned(), 3);
+
+        // Entries in this index cannot be purged in a single block.
+        let mut receipts = context.service_data().get_expiring_map("receipts");
+        for i in 0..=RustRuntime::MAX_PURGED_ENTRIES_PER_BLOCK as u64 {
+            receipts.put(&i, (), 1);
+        }
+        Ok(())
+    }
+
+    fn expiring_indexes(&self) -> Vec<String> {
+        vec!["nonces".to_owned(), "receipts".to_owned()]
+    }
+}
+
+impl DefaultInstance for ExpiringServiceImpl {
+    const INSTANCE_ID: u32 = DependentServiceImpl::INSTANCE_ID + 1;
+    const INSTANCE_NAME: &'static str = "expiring-service";
+}
+
 fn create_genesis_config_with_supervisor() -> GenesisConfig {
     create_genesis_config_builder()
         .with_artifact(ToySupervisorService.artifact_id())
@@ -217,6 +264,8 @@ fn create_runtime(
             .with_factory(TestServiceImplV2)
             .with_factory(ToySupervisorService)
             .with_factory(DependentServiceImpl)
+            .with_factory(RequiringServiceImpl)
+            .with_factory(ExpiringServiceImpl)
             .build_for_tests(),
     );
     let events_handle = inspected.events.clone();
@@ -783,6 +832,47 @@ fn dependent_builtin_service_with_incorrect_order() {
     create_runtime(Blockchain::build_for_tests(), genesis_config);
 }
 
+fn requiring_service_spec() -> ArtifactSpec {
+    let service = RequiringServiceImpl;
+    ArtifactSpec::new(service.artifact_id(), ()).with_dependencies(service.artifact_dependencies())
+}
+
+#[test]
+fn builtin_artifact_with_dependencies() {
+    let dependencies = RequiringServiceImpl.artifact_dependencies();
+    let expected_dependencies: Vec<ArtifactReq> = vec![
+        "test_service@^0.1".parse().unwrap(),
+        "toy_supervisor@>=0.1.0, <1.0.0".parse().unwrap(),
+    ];
+    assert_eq!(dependencies, expected_dependencies);
+
+    // The order of artifacts in the genesis config does not matter.
+    let genesis_config = create_genesis_config_builder()
+        .with_artifact_spec(requiring_service_spec())
+        .with_artifact(ToySupervisorService.artifact_id())
+        .with_artifact(TestServiceImpl.artifact_id())
+        .build();
+    let (blockchain, _) = create_runtime(Blockchain::build_for_tests(), genesis_config);
+
+    let snapshot = blockchain.snapshot();
+    let state = snapshot
+        .for_dispatcher()
+        .get_artifact(&RequiringServiceImpl.artifact_id())
+        .unwrap();
+    assert_eq!(state.dependencies, expected_dependencies);
+}
+
+#[test]
+#[should_panic(expected = "Dependency `test_service@^0.1` of artifact `0:requiring_service:0.1.0`")]
+fn builtin_artifact_with_unsatisfied_dependencies() {
+    let genesis_config = create_genesis_config_builder()
+        .with_artifact_spec(requiring_service_spec())
+        .with_artifact(ToySupervisorService.artifact_id())
+        .with_artifact(TestServiceImplV2.artifact_id())
+        .build();
+    create_runtime(Blockchain::build_for_tests(), genesis_config);
+}
+
 #[test]
 fn dependent_service_with_no_dependency() {
     let (mut blockchain, _) = create_runtime(
@@ -1093,3 +1183,45 @@ fn unloading_artifact() {
         ]
     );
 }
+
+#[test]
+fn expiring_indexes_are_purged_by_runtime() {
+    fn nonce_keys(blockchain: &BlockchainMut) -> Vec<u64> {
+        let snapshot = blockchain.snapshot();
+        snapshot
+            .for_service(ExpiringServiceImpl::INSTANCE_NAME)
+            .unwrap()
+            .get_expiring_map::<_, u64, String>("nonces")
+            .keys()
+            .collect()
+    }
+
+    fn receipts_count(blockchain: &BlockchainMut) -> usize {
+        let snapshot = blockchain.snapshot();
+        snapshot
+            .for_service(ExpiringServiceImpl::INSTANCE_NAME)
+            .unwrap()
+            .get_expiring_map::<_, u64, ()>("receipts")
+            .keys()
+            .count()
+    }
+
+    let genesis_config = create_genesis_config_builder()
+        .with_artifact(ExpiringServiceImpl.artifact_id())
+        .with_instance(ExpiringServiceImpl.default_instance())
+        .build();
+    let (mut blockchain, _) = create_runtime(Blockchain::build_for_tests(), genesis_config);
+    assert_eq!(nonce_keys(&blockchain), vec![1, 2]);
+    let receipts_len = RustRuntime::MAX_PURGED_ENTRIES_PER_BLOCK + 1;
+    assert_eq!(receipts_count(&blockchain), receipts_len);
+
+    // The number of entries purged in a block is bounded; the first block purges
+    // one entry from `nonces` and the remaining budget from `receipts`.
+    let expected = vec![(vec![2], 2), (vec![2], 0), (vec![], 0)];
+    for (expected_keys, expected_receipts) in expected {
+        let patch = create_block_with_transactions(&mut blockchain, vec![]);
+        blockchain.commit(patch, vec![]).unwrap();
+        assert_eq!(nonce_keys(&blockchain), expected_keys);
+        assert_eq!(receipts_count(&blockchain), expected_receipts);
+    }
+}
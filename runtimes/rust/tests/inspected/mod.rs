@@ -27,9 +27,9 @@ use exonum::{
         migrations::{InitMigrationError, MigrationScript},
         oneshot,
         versioning::Version,
-        ArtifactId, ExecutionContext, ExecutionError, InstanceId, InstanceSpec, InstanceState,
-        InstanceStatus, Mailbox, MethodId, Runtime, RuntimeFeature, SnapshotExt, WellKnownRuntime,
-        SUPERVISOR_INSTANCE_ID,
+        ArtifactId, ArtifactSpec, ExecutionContext, ExecutionError, InstanceDescriptor, InstanceId,
+        InstanceSpec, InstanceState, InstanceStatus, Mailbox, MethodId, Runtime, RuntimeFeature,
+        SnapshotExt, WellKnownRuntime, SUPERVISOR_INSTANCE_ID,
     },
 };
 use exonum_api::UpdateEndpoints;
@@ -265,6 +265,16 @@ impl<T: Runtime> Runtime for Inspected<T> {
         self.runtime.execute(context, method_id, arguments)
     }
 
+    fn query(
+        &self,
+        instance: &InstanceDescriptor,
+        method_id: MethodId,
+        arguments: &[u8],
+        snapshot: &dyn Snapshot,
+    ) -> Result<Vec<u8>, ExecutionError> {
+        self.runtime.query(instance, method_id, arguments, snapshot)
+    }
+
     fn before_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError> {
         let height = context.data().for_core().next_height();
         self.events.push(RuntimeEvent::BeforeTransactions(
@@ -358,9 +368,10 @@ impl ToySupervisor<ExecutionContext<'_>> for ToySupervisorService {
         mut context: ExecutionContext<'_>,
         request: DeployArtifact,
     ) -> Self::Output {
+        let spec = ArtifactSpec::new(request.test_service_artifact, request.spec);
         context
             .supervisor_extensions()
-            .start_artifact_registration(&request.test_service_artifact, request.spec);
+            .start_artifact_registration(spec);
         Ok(())
     }
 
@@ -0,0 +1,38 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors specific for the Wasm runtime.
+
+use exonum_derive::ExecutionFail;
+
+/// List of possible Wasm runtime errors.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(ExecutionFail)]
+#[execution_fail(kind = "runtime")]
+#[non_exhaustive]
+pub enum Error {
+    /// Deploy specification of the artifact is not a valid Wasm module, or the module
+    /// cannot be instantiated (e.g., it imports unknown host functions).
+    InvalidModule = 0,
+    /// Module does not have an export required by the runtime, or the export has
+    /// an unexpected signature.
+    MissingExport = 1,
+    /// Execution of the Wasm code has trapped.
+    Trap = 2,
+    /// Host function has accessed memory of the module out of bounds.
+    MemoryAccess = 3,
+    /// Host function was called with invalid arguments, or is not available in the current
+    /// execution environment.
+    InvalidHostCall = 4,
+}
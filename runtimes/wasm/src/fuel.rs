@@ -0,0 +1,216 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Instrumentation of Wasm modules with fuel metering.
+//!
+//! The code of each function is split into linear segments, i.e., sequences of instructions
+//! which are entered only at the start. Each segment is prepended with a call to the `fuel`
+//! host function, which charges the number of instructions in the segment. Thus, the fuel
+//! charged for a call is deterministic and is not less than the number of executed instructions.
+//!
+//! Before the instrumentation, the module is checked to declare a bounded linear memory,
+//! so that the memory of an instance cannot grow past [`WasmRuntime::MAX_MEMORY_PAGES`].
+//!
+//! [`WasmRuntime::MAX_MEMORY_PAGES`]: ../struct.WasmRuntime.html#associatedconstant.MAX_MEMORY_PAGES
+
+use exonum::runtime::{ExecutionError, ExecutionFail};
+use parity_wasm::elements::{
+    deserialize_buffer, External, FunctionType, ImportEntry, ImportSection, Instruction,
+    Instructions, Internal, Module, Section, Type, TypeSection, ValueType,
+};
+
+use std::{convert::TryFrom, mem};
+
+use crate::{host::HOST_MODULE, Error, WasmRuntime};
+
+/// Name of the host function charging fuel. The function is imported by the instrumented
+/// module; it cannot be imported by the original module.
+pub(crate) const FUEL_FUNCTION: &str = "fuel";
+
+/// Parses the module, checks its memory limits and instruments it with fuel metering.
+pub(crate) fn instrument(bytecode: &[u8]) -> Result<wasmi::Module, ExecutionError> {
+    let module: Module =
+        deserialize_buffer(bytecode).map_err(|e| Error::InvalidModule.with_description(e))?;
+
+    let imports_fuel = module.import_section().map_or(false, |section| {
+        section
+            .entries()
+            .iter()
+            .any(|entry| entry.module() == HOST_MODULE && entry.field() == FUEL_FUNCTION)
+    });
+    if imports_fuel {
+        let msg = format!(
+            "Module imports `{}` host function, which is reserved for the runtime",
+            FUEL_FUNCTION
+        );
+        return Err(Error::InvalidModule.with_description(msg));
+    }
+    check_memory(&module)?;
+
+    let module = inject_fuel_metering(module);
+    wasmi::Module::from_parity_wasm_module(module)
+        .map_err(|e| Error::InvalidModule.with_description(e))
+}
+
+/// Checks that all memories defined by the module declare a maximum size
+/// not exceeding the runtime limit.
+fn check_memory(module: &Module) -> Result<(), ExecutionError> {
+    let memories = module
+        .memory_section()
+        .map_or(&[][..], |section| section.entries());
+    for memory in memories {
+        let max_pages = memory.limits().maximum().ok_or_else(|| {
+            Error::InvalidModule.with_description("Module memory does not declare maximum size")
+        })?;
+        if max_pages > WasmRuntime::MAX_MEMORY_PAGES {
+            let msg = format!(
+                "Maximum size of the module memory ({} pages) exceeds the limit of {} pages",
+                max_pages,
+                WasmRuntime::MAX_MEMORY_PAGES
+            );
+            return Err(Error::InvalidModule.with_description(msg));
+        }
+    }
+    Ok(())
+}
+
+fn inject_fuel_metering(mut module: Module) -> Module {
+    let fuel_type = fuel_function_type(&mut module);
+
+    // The fuel function is imported after other imported functions, so it gets the index
+    // equal to the number of imported functions. Indexes of the functions defined
+    // in the module are shifted by one.
+    let fuel_function = imported_functions_count(&module);
+    let shift = |index: &mut u32| {
+        if *index >= fuel_function {
+            *index += 1;
+        }
+    };
+
+    let import = ImportEntry::new(
+        HOST_MODULE.to_owned(),
+        FUEL_FUNCTION.to_owned(),
+        External::Function(fuel_type),
+    );
+    if let Some(section) = module.import_section_mut() {
+        section.entries_mut().push(import);
+    } else {
+        let section = Section::Import(ImportSection::with_entries(vec![import]));
+        module
+            .insert_section(section)
+            .expect("BUG: module already contains import section");
+    }
+
+    if let Some(section) = module.export_section_mut() {
+        for entry in section.entries_mut() {
+            if let Internal::Function(index) = entry.internal_mut() {
+                shift(index);
+            }
+        }
+    }
+    if let Some(section) = module.elements_section_mut() {
+        for segment in section.entries_mut() {
+            segment.members_mut().iter_mut().for_each(shift);
+        }
+    }
+    if let Some(mut start) = module.start_section() {
+        shift(&mut start);
+        module.set_start_section(start);
+    }
+    if let Some(section) = module.code_section_mut() {
+        for body in section.bodies_mut() {
+            let code = body.code_mut();
+            for instruction in code.elements_mut() {
+                if let Instruction::Call(index) = instruction {
+                    shift(index);
+                }
+            }
+            charge_segments(code, fuel_function);
+        }
+    }
+    module
+}
+
+/// Returns the index of the `(i32) -> ()` function type, adding the type if necessary.
+fn fuel_function_type(module: &mut Module) -> u32 {
+    let fuel_type = Type::Function(FunctionType::new(vec![ValueType::I32], None));
+    if module.type_section().is_none() {
+        let section = Section::Type(TypeSection::with_types(vec![]));
+        module
+            .insert_section(section)
+            .expect("BUG: module already contains type section");
+    }
+
+    let types = module.type_section_mut().unwrap().types_mut();
+    let index = types
+        .iter()
+        .position(|ty| *ty == fuel_type)
+        .unwrap_or_else(|| {
+            types.push(fuel_type);
+            types.len() - 1
+        });
+    index as u32
+}
+
+fn imported_functions_count(module: &Module) -> u32 {
+    module.import_section().map_or(0, |section| {
+        let functions = section
+            .entries()
+            .iter()
+            .filter(|entry| matches!(entry.external(), External::Function(_)));
+        functions.count() as u32
+    })
+}
+
+/// Checks whether the control flow may enter the code right after the instruction
+/// not only from the instruction itself (or cannot enter it from the instruction at all).
+fn ends_segment(instruction: &Instruction) -> bool {
+    match instruction {
+        Instruction::Block(_)
+        | Instruction::Loop(_)
+        | Instruction::If(_)
+        | Instruction::Else
+        | Instruction::End
+        | Instruction::Br(_)
+        | Instruction::BrIf(_)
+        | Instruction::BrTable(_)
+        | Instruction::Return
+        | Instruction::Unreachable => true,
+        _ => false,
+    }
+}
+
+fn charge_segments(code: &mut Instructions, fuel_function: u32) {
+    let instructions = mem::take(code.elements_mut());
+    let charged_code = code.elements_mut();
+    let mut segment = vec![];
+    for instruction in instructions {
+        let is_last = ends_segment(&instruction);
+        segment.push(instruction);
+        if is_last {
+            charge_segment(charged_code, &mut segment, fuel_function);
+        }
+    }
+    charge_segment(charged_code, &mut segment, fuel_function);
+}
+
+fn charge_segment(code: &mut Vec<Instruction>, segment: &mut Vec<Instruction>, fuel_function: u32) {
+    if segment.is_empty() {
+        return;
+    }
+    let fuel = i32::try_from(segment.len()).unwrap_or(i32::max_value());
+    code.push(Instruction::I32Const(fuel));
+    code.push(Instruction::Call(fuel_function));
+    code.append(segment);
+}
@@ -0,0 +1,468 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Host functions provided to Wasm modules and instantiation of modules.
+
+use exonum::{
+    merkledb::{
+        access::{Access, FromAccess},
+        MapIndex,
+    },
+    runtime::{
+        migrations::MigrationContext, CoreError, ExecutionContext, ExecutionContextUnstable,
+        ExecutionError, ExecutionFail,
+    },
+};
+use wasmi::{
+    Externals, FuncInstance, FuncRef, HostError, ImportsBuilder, MemoryRef, Module,
+    ModuleImportResolver, ModuleInstance, ModuleRef, NotStartedModuleRef, RuntimeArgs,
+    RuntimeValue, Signature, Trap, ValueType,
+};
+
+use std::{convert::TryFrom, fmt};
+
+use crate::{fuel::FUEL_FUNCTION, Error};
+
+/// Name of the module host functions are imported from.
+pub(crate) const HOST_MODULE: &str = "env";
+/// Name of the exported linear memory of a Wasm module.
+const MEMORY_EXPORT: &str = "memory";
+/// Name of the exported function used to allocate memory for arguments.
+const ALLOC_EXPORT: &str = "alloc";
+
+/// Host functions together with their parameter and return types. The position of a function
+/// in this list is used as its index when the function is invoked by the module.
+const HOST_FUNCTIONS: &[(&str, &[ValueType], Option<ValueType>)] = &[
+    ("storage_read", &[ValueType::I32; 6], Some(ValueType::I32)),
+    ("storage_write", &[ValueType::I32; 6], None),
+    ("storage_remove", &[ValueType::I32; 4], None),
+    ("service_read", &[ValueType::I32; 8], Some(ValueType::I32)),
+    ("instance_id", &[], Some(ValueType::I32)),
+    ("height", &[], Some(ValueType::I64)),
+    ("caller", &[ValueType::I32; 2], Some(ValueType::I32)),
+    ("emit_event", &[ValueType::I32; 4], None),
+    ("set_return_value", &[ValueType::I32; 2], None),
+    ("call_service", &[ValueType::I32; 6], Some(ValueType::I32)),
+    ("fail", &[ValueType::I32; 3], None),
+    (FUEL_FUNCTION, &[ValueType::I32], None),
+];
+
+/// Environment a Wasm module is executed in.
+pub(crate) enum HostEnv<'a> {
+    /// Call within a block: a transaction, a service constructor or a block hook.
+    Call(ExecutionContext<'a>),
+    /// Data migration script. Reads are performed from the old service data, and writes
+    /// go to the migrated data.
+    Migration(&'a MigrationContext),
+}
+
+/// Error raised by a host function. The error aborts execution of the Wasm code
+/// and is returned to the caller as is.
+#[derive(Debug)]
+struct HostFailure(ExecutionError);
+
+impl fmt::Display for HostFailure {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, formatter)
+    }
+}
+
+impl HostError for HostFailure {}
+
+fn abort(err: ExecutionError) -> Trap {
+    Trap::from(HostFailure(err))
+}
+
+fn invalid_call(description: impl fmt::Display) -> Trap {
+    abort(Error::InvalidHostCall.with_description(description))
+}
+
+fn convert_error(err: wasmi::Error) -> ExecutionError {
+    let host_failure = err
+        .as_host_error()
+        .and_then(|host_err| host_err.downcast_ref::<HostFailure>());
+    if let Some(HostFailure(err)) = host_failure {
+        return err.clone();
+    }
+
+    match err {
+        wasmi::Error::Trap(trap) => Error::Trap.with_description(format!("{:?}", trap.kind())),
+        wasmi::Error::Function(description) => Error::MissingExport.with_description(description),
+        other => Error::InvalidModule.with_description(other),
+    }
+}
+
+/// Map with binary keys and values used to store service data.
+type RawMap<T> = MapIndex<T, [u8], Vec<u8>>;
+
+fn open_map<T: Access>(access: T, name: &str) -> Result<RawMap<T::Base>, Trap> {
+    MapIndex::from_access(access, name.into()).map_err(invalid_call)
+}
+
+/// Resolver of the host functions imported by Wasm modules.
+#[derive(Debug)]
+struct HostResolver;
+
+impl ModuleImportResolver for HostResolver {
+    fn resolve_func(
+        &self,
+        field_name: &str,
+        signature: &Signature,
+    ) -> Result<FuncRef, wasmi::Error> {
+        let (index, (_, params, return_type)) = HOST_FUNCTIONS
+            .iter()
+            .enumerate()
+            .find(|(_, (name, ..))| *name == field_name)
+            .ok_or_else(|| {
+                let msg = format!("Unknown host function `{}`", field_name);
+                wasmi::Error::Instantiation(msg)
+            })?;
+
+        let host_signature = Signature::new(*params, *return_type);
+        if *signature != host_signature {
+            let msg = format!(
+                "Host function `{}` imported with signature {:?}, expected {:?}",
+                field_name, signature, host_signature
+            );
+            return Err(wasmi::Error::Instantiation(msg));
+        }
+        Ok(FuncInstance::alloc_host(host_signature, index))
+    }
+}
+
+/// Instantiates the module, linking it with host functions, without running
+/// its start function.
+fn instantiate(module: &Module) -> Result<(NotStartedModuleRef<'_>, MemoryRef), ExecutionError> {
+    let imports = ImportsBuilder::new().with_resolver(HOST_MODULE, &HostResolver);
+    let instance = ModuleInstance::new(module, &imports)
+        .map_err(|e| Error::InvalidModule.with_description(e))?;
+    let memory = instance
+        .not_started_instance()
+        .export_by_name(MEMORY_EXPORT)
+        .and_then(|export| export.as_memory().cloned())
+        .ok_or_else(|| {
+            let msg = format!("Module does not export `{}`", MEMORY_EXPORT);
+            Error::MissingExport.with_description(msg)
+        })?;
+    Ok((instance, memory))
+}
+
+/// Checks that the module can be instantiated by the runtime.
+pub(crate) fn check_module(module: &Module) -> Result<(), ExecutionError> {
+    instantiate(module).map(drop)
+}
+
+/// Checks whether the module exports a function with the specified name. The start function
+/// of the module is not executed.
+pub(crate) fn exports_function(module: &Module, name: &str) -> Result<bool, ExecutionError> {
+    let (instance, _) = instantiate(module)?;
+    let has_function = instance
+        .not_started_instance()
+        .export_by_name(name)
+        .map_or(false, |export| export.as_func().is_some());
+    Ok(has_function)
+}
+
+/// Implementation of host functions for a specific module instance.
+struct Host<'a> {
+    env: HostEnv<'a>,
+    memory: MemoryRef,
+    /// Maximum fuel that can be consumed by the instance.
+    max_fuel: u64,
+    /// Fuel consumed by the instance so far.
+    consumed_fuel: u64,
+}
+
+impl<'a> Host<'a> {
+    fn context(&mut self) -> Result<&mut ExecutionContext<'a>, Trap> {
+        match &mut self.env {
+            HostEnv::Call(context) => Ok(context),
+            HostEnv::Migration(_) => Err(invalid_call(
+                "Function is not available in data migration scripts",
+            )),
+        }
+    }
+
+    fn read_bytes(&self, args: &RuntimeArgs<'_>, idx: usize) -> Result<Vec<u8>, Trap> {
+        let ptr: u32 = args.nth_checked(idx)?;
+        let len: u32 = args.nth_checked(idx + 1)?;
+        self.memory
+            .get(ptr, len as usize)
+            .map_err(|e| abort(Error::MemoryAccess.with_description(e)))
+    }
+
+    fn read_str(&self, args: &RuntimeArgs<'_>, idx: usize) -> Result<String, Trap> {
+        let bytes = self.read_bytes(args, idx)?;
+        String::from_utf8(bytes).map_err(invalid_call)
+    }
+
+    fn write_bytes(&self, ptr: u32, bytes: &[u8]) -> Result<(), Trap> {
+        self.memory
+            .set(ptr, bytes)
+            .map_err(|e| abort(Error::MemoryAccess.with_description(e)))
+    }
+
+    /// Copies `value` into the module memory, truncating it to the buffer capacity,
+    /// and returns the full length of the value, or -1 if the value is absent.
+    fn write_value(
+        &self,
+        value: Option<&[u8]>,
+        args: &RuntimeArgs<'_>,
+        idx: usize,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        let ptr: u32 = args.nth_checked(idx)?;
+        let capacity: u32 = args.nth_checked(idx + 1)?;
+        let len = if let Some(value) = value {
+            let copied_len = value.len().min(capacity as usize);
+            self.write_bytes(ptr, &value[..copied_len])?;
+            value.len() as i32
+        } else {
+            -1
+        };
+        Ok(Some(RuntimeValue::I32(len)))
+    }
+
+    fn storage_read(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let name = self.read_str(args, 0)?;
+        let key = self.read_bytes(args, 2)?;
+        let value = match &self.env {
+            HostEnv::Call(context) => open_map(context.service_data(), &name)?.get(&key),
+            HostEnv::Migration(context) => open_map(context.helper.old_data(), &name)?.get(&key),
+        };
+        self.write_value(value.as_deref(), args, 4)
+    }
+
+    fn storage_write(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let name = self.read_str(args, 0)?;
+        let key = self.read_bytes(args, 2)?;
+        let value = self.read_bytes(args, 4)?;
+        match &self.env {
+            HostEnv::Call(context) => {
+                open_map(context.service_data(), &name)?.put(&key, value);
+            }
+            HostEnv::Migration(context) => {
+                open_map(context.helper.new_data(), &name)?.put(&key, value);
+            }
+        }
+        Ok(None)
+    }
+
+    fn storage_remove(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let name = self.read_str(args, 0)?;
+        let key = self.read_bytes(args, 2)?;
+        match &self.env {
+            HostEnv::Call(context) => {
+                open_map(context.service_data(), &name)?.remove(key.as_slice());
+            }
+            HostEnv::Migration(context) => {
+                open_map(context.helper.new_data(), &name)?.remove(key.as_slice());
+            }
+        }
+        Ok(None)
+    }
+
+    fn service_read(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let service = self.read_str(args, 0)?;
+        let name = self.read_str(args, 2)?;
+        let key = self.read_bytes(args, 4)?;
+        let value = {
+            let data = self.context()?.data();
+            let access = data
+                .for_service(service.as_str())
+                .ok_or_else(|| invalid_call(format!("Service `{}` does not exist", service)))?;
+            open_map(access, &name)?.get(&key)
+        };
+        self.write_value(value.as_deref(), args, 6)
+    }
+
+    fn instance_id(&mut self) -> Result<Option<RuntimeValue>, Trap> {
+        let instance_id = self.context()?.instance().id;
+        Ok(Some(RuntimeValue::I32(instance_id as i32)))
+    }
+
+    fn height(&mut self) -> Result<Option<RuntimeValue>, Trap> {
+        let height = self.context()?.data().for_core().next_height();
+        Ok(Some(RuntimeValue::I64(height.0 as i64)))
+    }
+
+    fn caller(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let signers = self.context()?.caller().signers().map(|signers| {
+            signers
+                .iter()
+                .flat_map(|key| key.as_ref().iter().copied())
+                .collect::<Vec<_>>()
+        });
+        self.write_value(signers.as_deref(), args, 0)
+    }
+
+    fn emit_event(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let topic = self.read_str(args, 0)?;
+        let payload = self.read_bytes(args, 2)?;
+        self.context()?.emit_event(topic, payload).map_err(abort)?;
+        Ok(None)
+    }
+
+    fn set_return_value(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let value = self.read_bytes(args, 0)?;
+        self.context()?.set_return_value(value);
+        Ok(None)
+    }
+
+    fn call_service(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let instance_id: u32 = args.nth_checked(0)?;
+        let method_id: u32 = args.nth_checked(1)?;
+        let arguments = self.read_bytes(args, 2)?;
+        let return_value = self
+            .context()?
+            .make_child_call(instance_id, "", method_id, &arguments, false)
+            .map_err(abort)?;
+        self.write_value(return_value.as_deref(), args, 4)
+    }
+
+    fn fail(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let code: u32 = args.nth_checked(0)?;
+        let description = self.read_str(args, 1)?;
+        let err = if let Ok(code) = u8::try_from(code) {
+            ExecutionError::service(code, description)
+        } else {
+            let msg = format!("Service error code {} does not fit into `u8`", code);
+            Error::InvalidHostCall.with_description(msg)
+        };
+        Err(abort(err))
+    }
+
+    fn fuel(&mut self, args: &RuntimeArgs<'_>) -> Result<Option<RuntimeValue>, Trap> {
+        let fuel = u64::from(args.nth_checked::<u32>(0)?);
+        // Data migrations are not covered by the resource meter of the call,
+        // but are subject to the fuel ceiling of the runtime.
+        if let HostEnv::Call(context) = &self.env {
+            context.charge_fuel(fuel).map_err(abort)?;
+        }
+
+        self.consumed_fuel = self.consumed_fuel.saturating_add(fuel);
+        if self.consumed_fuel > self.max_fuel {
+            let msg = format!(
+                "Wasm instance has exceeded the fuel ceiling of the runtime ({} > {})",
+                self.consumed_fuel, self.max_fuel
+            );
+            let err = CoreError::ResourceLimitExceeded.with_description(msg);
+            return Err(abort(err));
+        }
+        Ok(None)
+    }
+}
+
+impl Externals for Host<'_> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs<'_>,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        match HOST_FUNCTIONS[index].0 {
+            "storage_read" => self.storage_read(&args),
+            "storage_write" => self.storage_write(&args),
+            "storage_remove" => self.storage_remove(&args),
+            "service_read" => self.service_read(&args),
+            "instance_id" => self.instance_id(),
+            "height" => self.height(),
+            "caller" => self.caller(&args),
+            "emit_event" => self.emit_event(&args),
+            "set_return_value" => self.set_return_value(&args),
+            "call_service" => self.call_service(&args),
+            "fail" => self.fail(&args),
+            FUEL_FUNCTION => self.fuel(&args),
+            name => unreachable!("Unknown host function `{}`", name),
+        }
+    }
+}
+
+/// Instance of a Wasm module bound to an execution environment.
+pub(crate) struct Instance<'a> {
+    module: ModuleRef,
+    host: Host<'a>,
+}
+
+impl<'a> Instance<'a> {
+    /// Instantiates the module and runs its start function, if any. The instance
+    /// (including its start function) can consume at most `max_fuel` units of fuel.
+    pub fn new(module: &Module, env: HostEnv<'a>, max_fuel: u64) -> Result<Self, ExecutionError> {
+        let (instance, memory) = instantiate(module)?;
+        let mut host = Host {
+            env,
+            memory,
+            max_fuel,
+            consumed_fuel: 0,
+        };
+        let module = instance
+            .run_start(&mut host)
+            .map_err(|trap| convert_error(trap.into()))?;
+        Ok(Self { module, host })
+    }
+
+    /// Checks whether the module exports a function with the specified name.
+    pub fn has_function(&self, name: &str) -> bool {
+        self.module
+            .export_by_name(name)
+            .map_or(false, |export| export.as_func().is_some())
+    }
+
+    /// Invokes an exported function without arguments.
+    pub fn invoke(&mut self, name: &str) -> Result<(), ExecutionError> {
+        self.invoke_export(name, &[]).map(drop)
+    }
+
+    /// Invokes an exported function with the specified serialized arguments. The arguments
+    /// are copied into memory allocated with the `alloc` export of the module, and the function
+    /// receives a pointer to them and their length.
+    pub fn invoke_with_bytes(
+        &mut self,
+        name: &str,
+        arguments: &[u8],
+    ) -> Result<(), ExecutionError> {
+        let ptr = if arguments.is_empty() {
+            0
+        } else {
+            self.alloc(arguments)?
+        };
+        let args = [
+            RuntimeValue::I32(ptr as i32),
+            RuntimeValue::I32(arguments.len() as i32),
+        ];
+        self.invoke_export(name, &args).map(drop)
+    }
+
+    fn alloc(&mut self, bytes: &[u8]) -> Result<u32, ExecutionError> {
+        let ptr = self
+            .invoke_export(ALLOC_EXPORT, &[RuntimeValue::I32(bytes.len() as i32)])?
+            .and_then(RuntimeValue::try_into::<u32>)
+            .ok_or_else(|| {
+                let msg = format!("`{}` export should return `i32`", ALLOC_EXPORT);
+                Error::MissingExport.with_description(msg)
+            })?;
+        self.host
+            .write_bytes(ptr, bytes)
+            .map_err(|trap| convert_error(trap.into()))?;
+        Ok(ptr)
+    }
+
+    fn invoke_export(
+        &mut self,
+        name: &str,
+        args: &[RuntimeValue],
+    ) -> Result<Option<RuntimeValue>, ExecutionError> {
+        self.module
+            .invoke_export(name, args, &mut self.host)
+            .map_err(convert_error)
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The runtime is for running Exonum services compiled to WebAssembly.
+//!
+//! Wasm modules are executed by [`wasmi`], an interpreter written in pure Rust. Unlike
+//! the Rust runtime, the set of artifacts in the Wasm runtime is not fixed at compile time:
+//! an artifact is deployed by supplying the bytecode of a Wasm module as the deploy
+//! specification of the artifact. Service code is isolated from the node; it can only interact
+//! with the blockchain via the host functions described below.
+//!
+//! Each call to a service is executed in a fresh instance of the artifact module. Thus, services
+//! cannot keep state between calls in the Wasm memory; all state should be persisted
+//! in the blockchain storage.
+//!
+//! # Module Interface
+//!
+//! A module must export its linear memory as `memory`. If any service method receives
+//! arguments, the module must also export `alloc(len: i32) -> i32`, which allocates `len` bytes
+//! in the memory and returns a pointer to them. The runtime uses this function to pass
+//! arguments to the service.
+//!
+//! The module may export the following functions:
+//!
+//! | Export | Signature | Description |
+//! |--------|-----------|-------------|
+//! | `method_{id}` | `(args_ptr: i32, args_len: i32)` | Method of the default service interface with the numeric identifier `id` |
+//! | `initialize` | `(args_ptr: i32, args_len: i32)` | Service constructor |
+//! | `resume` | `(args_ptr: i32, args_len: i32)` | Handler of resuming a stopped service |
+//! | `before_transactions` | `()` | Hook called at the start of each block |
+//! | `after_transactions` | `()` | Hook called at the end of each block |
+//! | `migrate` | `(version_ptr: i32, version_len: i32)` | Data migration script |
+//!
+//! All exports except for `method_{id}` are optional. A method without arguments receives
+//! a null pointer and zero length. A call fails if the Wasm code traps or if the service
+//! signals an error via the `fail` host function.
+//!
+//! If an artifact exports `migrate`, the runtime migrates data of services to the version
+//! of the artifact by invoking this function with the string representation of the current
+//! data version. Otherwise, the data of services is considered compatible with the artifact,
+//! i.e., no data migration is required.
+//!
+//! # Host Functions
+//!
+//! Host functions are imported from the `env` module. All pointers and lengths are `i32`s.
+//! Functions returning values of variable length write the value to the buffer provided
+//! by the caller, truncating it to the buffer capacity if necessary, and return the full
+//! length of the value, or -1 if the value is absent.
+//!
+//! Service data is represented as a collection of maps with binary keys and values; map names
+//! are relative to the service namespace.
+//!
+//! | Function | Signature | Description |
+//! |----------|-----------|-------------|
+//! | `storage_read` | `(name_ptr, name_len, key_ptr, key_len, value_ptr, value_cap) -> i32` | Reads a value from a map of the service |
+//! | `storage_write` | `(name_ptr, name_len, key_ptr, key_len, value_ptr, value_len)` | Writes a value to a map of the service |
+//! | `storage_remove` | `(name_ptr, name_len, key_ptr, key_len)` | Removes a value from a map of the service |
+//! | `service_read` | `(service_ptr, service_len, name_ptr, name_len, key_ptr, key_len, value_ptr, value_cap) -> i32` | Reads a value from a map of another service |
+//! | `instance_id` | `() -> i32` | Returns the identifier of the executing service |
+//! | `height` | `() -> i64` | Returns the height of the block being executed |
+//! | `caller` | `(keys_ptr, keys_cap) -> i32` | Writes 32-byte public keys of all signers of the transaction, sorted in the ascending order; returns -1 if the call is not authorized by a transaction |
+//! | `emit_event` | `(topic_ptr, topic_len, payload_ptr, payload_len)` | Emits an event |
+//! | `set_return_value` | `(value_ptr, value_len)` | Sets the value returned to the calling service |
+//! | `call_service` | `(instance_id, method_id, args_ptr, args_len, ret_ptr, ret_cap) -> i32` | Calls a method of another service and returns the value set by it |
+//! | `fail` | `(code, description_ptr, description_len)` | Aborts the call with a service error |
+//!
+//! Strings (map and service names, event topics and error descriptions) must be UTF-8 encoded.
+//!
+//! In data migration scripts, only `storage_*` functions are available. `storage_read` reads
+//! the old service data, while `storage_write` and `storage_remove` modify the migrated data.
+//! Note that a map modified during the migration replaces the old map with the same name
+//! completely.
+//!
+//! For transactions with a single signature, `caller` writes the key of the transaction author.
+//! For transactions with [cosignatures], keys of all signers (including the author) are written
+//! one after another, so the service can check the authorization with an m-of-n policy.
+//!
+//! # Fuel Metering
+//!
+//! When an artifact is deployed, its module is instrumented to charge fuel for executed
+//! instructions: each instruction costs one unit of fuel. Fuel consumed by a call is added
+//! to the resources consumed by the top-level call, including storage operations and nested
+//! calls, and is limited by the `max_call_fuel` field of the consensus config. A call exceeding
+//! the limit is aborted and fails with `CoreError::ResourceLimitExceeded`. The `fuel` name
+//! in the `env` module is reserved for the instrumentation and cannot be imported by modules.
+//!
+//! Independently of the consensus config, each module instance (i.e., each call of a service,
+//! excluding nested calls, or each run of a data migration script) is limited by the fuel
+//! ceiling of the runtime, which is [`DEFAULT_MAX_FUEL`] unless overridden with
+//! [`with_max_fuel()`]. Thus, calls are bounded even if `max_call_fuel` is not set
+//! or resources are not metered for the call, and data migration scripts, which are executed
+//! outside of transactions, cannot run indefinitely. Exceeding the ceiling aborts execution
+//! with `CoreError::ResourceLimitExceeded`.
+//!
+//! # Memory Limits
+//!
+//! Modules must declare the maximum size of their linear memory, which cannot exceed
+//! [`MAX_MEMORY_PAGES`] pages of 64 KiB. Modules without the maximum size or exceeding
+//! the limit are not deployed.
+//!
+//! [`DEFAULT_MAX_FUEL`]: struct.WasmRuntime.html#associatedconstant.DEFAULT_MAX_FUEL
+//! [`with_max_fuel()`]: struct.WasmRuntime.html#method.with_max_fuel
+//! [`MAX_MEMORY_PAGES`]: struct.WasmRuntime.html#associatedconstant.MAX_MEMORY_PAGES
+//!
+//! [cosignatures]: https://docs.rs/exonum/latest/exonum/messages/struct.SignedMessage.html
+//!
+//! [`wasmi`]: https://docs.rs/wasmi/
+
+#![warn(
+    missing_debug_implementations,
+    missing_docs,
+    unsafe_code,
+    bare_trait_objects
+)]
+#![warn(clippy::pedantic, clippy::nursery)]
+#![allow(
+    // Next `cast_*` lints don't give alternatives.
+    clippy::cast_possible_wrap, clippy::cast_possible_truncation, clippy::cast_sign_loss,
+    // Next lints produce too much noise/false positives.
+    clippy::module_name_repetitions, clippy::similar_names, clippy::must_use_candidate,
+    // Too much work to fix.
+    clippy::missing_errors_doc, clippy::missing_const_for_fn
+)]
+
+pub use crate::error::Error;
+
+use exonum::{
+    merkledb::Snapshot,
+    runtime::{
+        migrations::{InitMigrationError, MigrationContext, MigrationError, MigrationScript},
+        oneshot::Receiver,
+        versioning::Version,
+        ArtifactId, CommonError, ExecutionContext, ExecutionError, ExecutionFail, InstanceId,
+        InstanceState, InstanceStatus, Mailbox, MethodId, Runtime, RuntimeFeature,
+        RuntimeIdentifier, WellKnownRuntime,
+    },
+};
+use wasmi::Module;
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use crate::host::{HostEnv, Instance};
+
+mod error;
+mod fuel;
+mod host;
+
+const INITIALIZE_EXPORT: &str = "initialize";
+const RESUME_EXPORT: &str = "resume";
+const BEFORE_TRANSACTIONS_EXPORT: &str = "before_transactions";
+const AFTER_TRANSACTIONS_EXPORT: &str = "after_transactions";
+const MIGRATE_EXPORT: &str = "migrate";
+
+/// Deployed Wasm artifact.
+struct Artifact {
+    module: Arc<Module>,
+}
+
+impl fmt::Debug for Artifact {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_struct("Artifact").finish()
+    }
+}
+
+/// Runtime for services compiled to WebAssembly.
+///
+/// See [crate docs](index.html) for the interface Wasm modules should implement.
+#[derive(Debug)]
+pub struct WasmRuntime {
+    deployed_artifacts: HashMap<ArtifactId, Artifact>,
+    started_services: HashMap<InstanceId, ArtifactId>,
+    max_fuel: u64,
+}
+
+impl Default for WasmRuntime {
+    fn default() -> Self {
+        Self {
+            deployed_artifacts: HashMap::new(),
+            started_services: HashMap::new(),
+            max_fuel: Self::DEFAULT_MAX_FUEL,
+        }
+    }
+}
+
+impl WasmRuntime {
+    /// Wasm runtime name.
+    pub const NAME: &'static str = "wasm";
+
+    /// Default fuel ceiling for a single module instance.
+    pub const DEFAULT_MAX_FUEL: u64 = 100_000_000;
+
+    /// Maximum size of the linear memory of a module, in 64 KiB pages (i.e., 16 MiB).
+    pub const MAX_MEMORY_PAGES: u32 = 256;
+
+    /// Creates a new runtime without deployed artifacts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the fuel ceiling for a single module instance. See [crate docs](index.html)
+    /// for details.
+    ///
+    /// The ceiling influences the outcome of transactions, so it must be the same
+    /// on all nodes of the blockchain.
+    pub fn with_max_fuel(mut self, max_fuel: u64) -> Self {
+        self.max_fuel = max_fuel;
+        self
+    }
+
+    fn deploy(&mut self, artifact: ArtifactId, spec: &[u8]) -> Result<(), ExecutionError> {
+        if self.deployed_artifacts.contains_key(&artifact) {
+            panic!(
+                "BUG: Core requested deploy of already deployed artifact {:?}",
+                artifact
+            );
+        }
+
+        let module = fuel::instrument(spec)?;
+        host::check_module(&module)?;
+        log::info!("Deployed Wasm artifact {}", artifact);
+        let artifact_module = Artifact {
+            module: Arc::new(module),
+        };
+        self.deployed_artifacts.insert(artifact, artifact_module);
+        Ok(())
+    }
+
+    fn artifact(&self, artifact: &ArtifactId) -> &Artifact {
+        self.deployed_artifacts
+            .get(artifact)
+            .unwrap_or_else(|| panic!("BUG: artifact {} is not deployed", artifact))
+    }
+
+    fn service_artifact(&self, context: &ExecutionContext<'_>) -> &Artifact {
+        let artifact = self
+            .started_services
+            .get(&context.instance().id)
+            .expect("BUG: an attempt to call unknown service");
+        self.artifact(artifact)
+    }
+
+    /// Instantiates the artifact and invokes its export with the specified arguments
+    /// if the export is present.
+    fn invoke_optional(
+        &self,
+        artifact: &Artifact,
+        context: ExecutionContext<'_>,
+        export: &str,
+        arguments: Option<&[u8]>,
+    ) -> Result<(), ExecutionError> {
+        let mut instance = Instance::new(&artifact.module, HostEnv::Call(context), self.max_fuel)?;
+        if !instance.has_function(export) {
+            return Ok(());
+        }
+
+        if let Some(arguments) = arguments {
+            instance.invoke_with_bytes(export, arguments)
+        } else {
+            instance.invoke(export)
+        }
+    }
+}
+
+impl WellKnownRuntime for WasmRuntime {
+    const ID: u32 = RuntimeIdentifier::Wasm as u32;
+}
+
+impl Runtime for WasmRuntime {
+    fn is_supported(&self, feature: &RuntimeFeature) -> bool {
+        match feature {
+            RuntimeFeature::FreezingServices => true,
+            _ => false,
+        }
+    }
+
+    fn deploy_artifact(&mut self, artifact: ArtifactId, deploy_spec: Vec<u8>) -> Receiver {
+        Receiver::with_result(self.deploy(artifact, &deploy_spec))
+    }
+
+    fn is_artifact_deployed(&self, artifact: &ArtifactId) -> bool {
+        self.deployed_artifacts.contains_key(artifact)
+    }
+
+    fn unload_artifact(&mut self, artifact: &ArtifactId) {
+        let was_present = self.deployed_artifacts.remove(artifact).is_some();
+        debug_assert!(
+            was_present,
+            "Requested to unload non-existing artifact `{}`",
+            artifact
+        );
+    }
+
+    fn initiate_adding_service(
+        &self,
+        context: ExecutionContext<'_>,
+        artifact: &ArtifactId,
+        parameters: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        let artifact = self.artifact(artifact);
+        self.invoke_optional(
+            artifact,
+            context,
+            INITIALIZE_EXPORT,
+            Some(parameters.as_slice()),
+        )
+    }
+
+    fn initiate_resuming_service(
+        &self,
+        context: ExecutionContext<'_>,
+        artifact: &ArtifactId,
+        parameters: Vec<u8>,
+    ) -> Result<(), ExecutionError> {
+        let artifact = self.artifact(artifact);
+        self.invoke_optional(
+            artifact,
+            context,
+            RESUME_EXPORT,
+            Some(parameters.as_slice()),
+        )
+    }
+
+    fn update_service_status(&mut self, _snapshot: &dyn Snapshot, state: &InstanceState) {
+        let provides_read_access = state
+            .status
+            .as_ref()
+            .map_or(false, InstanceStatus::provides_read_access);
+
+        match state.associated_artifact() {
+            Some(artifact) if provides_read_access => {
+                assert!(
+                    self.deployed_artifacts.contains_key(artifact),
+                    "BUG: service {} is associated with a non-deployed artifact {}",
+                    state.spec.name,
+                    artifact
+                );
+                self.started_services
+                    .insert(state.spec.id, artifact.to_owned());
+            }
+            _ => {
+                self.started_services.remove(&state.spec.id);
+            }
+        }
+    }
+
+    fn migrate(
+        &self,
+        new_artifact: &ArtifactId,
+        _data_version: &Version,
+    ) -> Result<Option<MigrationScript>, InitMigrationError> {
+        let module = Arc::clone(&self.artifact(new_artifact).module);
+        // Deployed modules are checked to be instantiable, so the check cannot fail.
+        let has_migration = host::exports_function(&module, MIGRATE_EXPORT)
+            .expect("BUG: cannot instantiate a deployed module");
+        if !has_migration {
+            return Ok(None);
+        }
+
+        let max_fuel = self.max_fuel;
+        let script = move |context: &mut MigrationContext| {
+            let data_version = context.data_version.to_string();
+            let mut instance = Instance::new(&module, HostEnv::Migration(context), max_fuel)
+                .map_err(MigrationError::new)?;
+            instance
+                .invoke_with_bytes(MIGRATE_EXPORT, data_version.as_bytes())
+                .map_err(MigrationError::new)
+        };
+        Ok(Some(MigrationScript::new(
+            script,
+            new_artifact.version.clone(),
+        )))
+    }
+
+    fn execute(
+        &self,
+        context: ExecutionContext<'_>,
+        method_id: MethodId,
+        arguments: &[u8],
+    ) -> Result<(), ExecutionError> {
+        if !context.interface_name().is_empty() {
+            let msg = format!(
+                "Wasm services only implement the default interface, requested `{}`",
+                context.interface_name()
+            );
+            return Err(CommonError::NoSuchInterface.with_description(msg));
+        }
+
+        let artifact = self.service_artifact(&context);
+        let export = format!("method_{}", method_id);
+        let mut instance = Instance::new(&artifact.module, HostEnv::Call(context), self.max_fuel)?;
+        if !instance.has_function(&export) {
+            let msg = format!("Service does not have method with ID {}", method_id);
+            return Err(CommonError::NoSuchMethod.with_description(msg));
+        }
+        instance.invoke_with_bytes(&export, arguments)
+    }
+
+    fn before_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError> {
+        let artifact = self.service_artifact(&context);
+        self.invoke_optional(artifact, context, BEFORE_TRANSACTIONS_EXPORT, None)
+    }
+
+    fn after_transactions(&self, context: ExecutionContext<'_>) -> Result<(), ExecutionError> {
+        let artifact = self.service_artifact(&context);
+        self.invoke_optional(artifact, context, AFTER_TRANSACTIONS_EXPORT, None)
+    }
+
+    fn after_commit(&mut self, _snapshot: &dyn Snapshot, _mailbox: &mut Mailbox) {}
+}
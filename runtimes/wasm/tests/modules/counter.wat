@@ -0,0 +1,101 @@
+;; Counter service. The counter value and the number of processed blocks are stored
+;; in the `counter` map as little-endian `u64`s.
+(module
+  (import "env" "storage_read" (func $storage_read (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (import "env" "storage_write" (func $storage_write (param i32 i32 i32 i32 i32 i32)))
+  (import "env" "caller" (func $caller (param i32 i32) (result i32)))
+  (import "env" "emit_event" (func $emit_event (param i32 i32 i32 i32)))
+  (import "env" "set_return_value" (func $set_return_value (param i32 i32)))
+  (import "env" "fail" (func $fail (param i32 i32 i32)))
+
+  ;; Memory layout: 0..128 - constant strings, 128..136 - value buffer,
+  ;; 160..192 - key buffer, 512..1024 - signers buffer, 1024.. - arguments.
+  (memory (export "memory") 1 1)
+  (data (i32.const 0) "counter")
+  (data (i32.const 16) "value")
+  (data (i32.const 24) "blocks")
+  (data (i32.const 32) "owner")
+  (data (i32.const 48) "added")
+  (data (i32.const 64) "Cannot add zero")
+  (data (i32.const 96) "Invalid arguments")
+  (data (i32.const 120) "signers")
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 1024))
+
+  (func $load (param $key i32) (param $key_len i32) (result i64)
+    (if (i32.lt_s
+          (call $storage_read
+            (i32.const 0) (i32.const 7)
+            (local.get $key) (local.get $key_len)
+            (i32.const 128) (i32.const 8))
+          (i32.const 0))
+      (then (return (i64.const 0))))
+    (i64.load (i32.const 128)))
+
+  (func $store (param $key i32) (param $key_len i32) (param $value i64)
+    (i64.store (i32.const 128) (local.get $value))
+    (call $storage_write
+      (i32.const 0) (i32.const 7)
+      (local.get $key) (local.get $key_len)
+      (i32.const 128) (i32.const 8)))
+
+  (func $check_u64_arg (param $len i32)
+    (if (i32.ne (local.get $len) (i32.const 8))
+      (then (call $fail (i32.const 0) (i32.const 96) (i32.const 17)))))
+
+  ;; Sets the initial counter value.
+  (func (export "initialize") (param $ptr i32) (param $len i32)
+    (call $check_u64_arg (local.get $len))
+    (call $store (i32.const 16) (i32.const 5) (i64.load (local.get $ptr))))
+
+  ;; Increments the counter by a non-zero value and emits an event.
+  (func (export "method_0") (param $ptr i32) (param $len i32)
+    (local $value i64)
+    (call $check_u64_arg (local.get $len))
+    (local.set $value (i64.load (local.get $ptr)))
+    (if (i64.eqz (local.get $value))
+      (then (call $fail (i32.const 1) (i32.const 64) (i32.const 15))))
+    (call $store (i32.const 16) (i32.const 5)
+      (i64.add (call $load (i32.const 16) (i32.const 5)) (local.get $value)))
+    (call $emit_event (i32.const 48) (i32.const 5) (local.get $ptr) (local.get $len)))
+
+  ;; Resets the counter and records the transaction author.
+  (func (export "method_1") (param $ptr i32) (param $len i32)
+    (call $store (i32.const 16) (i32.const 5) (i64.const 0))
+    (if (i32.eq (call $caller (i32.const 160) (i32.const 32)) (i32.const 32))
+      (then
+        (call $storage_write
+          (i32.const 0) (i32.const 7)
+          (i32.const 32) (i32.const 5)
+          (i32.const 160) (i32.const 32)))))
+
+  ;; Traps.
+  (func (export "method_2") (param $ptr i32) (param $len i32)
+    (unreachable))
+
+  ;; Returns the counter value.
+  (func (export "method_3") (param $ptr i32) (param $len i32)
+    (i64.store (i32.const 128) (call $load (i32.const 16) (i32.const 5)))
+    (call $set_return_value (i32.const 128) (i32.const 8)))
+
+  ;; Records keys of all signers of the transaction.
+  (func (export "method_4") (param $ptr i32) (param $len i32)
+    (local $signers_len i32)
+    (local.set $signers_len (call $caller (i32.const 512) (i32.const 512)))
+    (if (i32.gt_s (local.get $signers_len) (i32.const 0))
+      (then
+        (call $storage_write
+          (i32.const 0) (i32.const 7)
+          (i32.const 120) (i32.const 7)
+          (i32.const 512) (local.get $signers_len)))))
+
+  ;; Never terminates.
+  (func (export "method_5") (param $ptr i32) (param $len i32)
+    (loop $forever (br $forever)))
+
+  ;; Counts processed blocks.
+  (func (export "after_transactions")
+    (call $store (i32.const 24) (i32.const 6)
+      (i64.add (call $load (i32.const 24) (i32.const 6)) (i64.const 1))))
+)
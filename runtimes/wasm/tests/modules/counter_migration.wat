@@ -0,0 +1,30 @@
+;; Newer version of the counter service, which doubles the counter value during migration.
+(module
+  (import "env" "storage_read" (func $storage_read (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (import "env" "storage_write" (func $storage_write (param i32 i32 i32 i32 i32 i32)))
+
+  (memory (export "memory") 1 1)
+  (data (i32.const 0) "counter")
+  (data (i32.const 16) "value")
+  (data (i32.const 32) "0.1.0")
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 1024))
+
+  ;; Migrates data from version 0.1.0.
+  (func (export "migrate") (param $ptr i32) (param $len i32)
+    (if (i32.ne (local.get $len) (i32.const 5))
+      (then (unreachable)))
+    (if (i64.ne (i64.load (local.get $ptr)) (i64.load (i32.const 32)))
+      (then (unreachable)))
+    (drop
+      (call $storage_read
+        (i32.const 0) (i32.const 7)
+        (i32.const 16) (i32.const 5)
+        (i32.const 128) (i32.const 8)))
+    (i64.store (i32.const 128) (i64.mul (i64.load (i32.const 128)) (i64.const 2)))
+    (call $storage_write
+      (i32.const 0) (i32.const 7)
+      (i32.const 16) (i32.const 5)
+      (i32.const 128) (i32.const 8)))
+)
@@ -0,0 +1,27 @@
+;; Service reading the value of a counter service via a nested call.
+(module
+  (import "env" "storage_write" (func $storage_write (param i32 i32 i32 i32 i32 i32)))
+  (import "env" "call_service" (func $call_service (param i32 i32 i32 i32 i32 i32) (result i32)))
+
+  (memory (export "memory") 1 1)
+  (data (i32.const 0) "proxy")
+  (data (i32.const 16) "value")
+
+  (func (export "alloc") (param $len i32) (result i32)
+    (i32.const 1024))
+
+  ;; Calls the "get value" method of the counter with the ID passed as a little-endian `u32`
+  ;; and stores the returned value.
+  (func (export "method_0") (param $ptr i32) (param $len i32)
+    (if (i32.ne
+          (call $call_service
+            (i32.load (local.get $ptr)) (i32.const 3)
+            (i32.const 0) (i32.const 0)
+            (i32.const 128) (i32.const 8))
+          (i32.const 8))
+      (then (unreachable)))
+    (call $storage_write
+      (i32.const 0) (i32.const 5)
+      (i32.const 16) (i32.const 5)
+      (i32.const 128) (i32.const 8)))
+)
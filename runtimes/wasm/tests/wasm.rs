@@ -0,0 +1,410 @@
+// Copyright 2020 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! High-level tests for the Wasm runtime.
+
+use exonum::{
+    blockchain::{
+        config::{GenesisConfigBuilder, InstanceInitParams},
+        Blockchain, CallInBlock, ConsensusConfig, Schema, ServiceEvent, Simulation,
+    },
+    crypto::KeyPair,
+    helpers::Height,
+    merkledb::{access::AccessExt, migration::MigrationHelper, Database, TemporaryDB},
+    runtime::{
+        migrations::MigrationContext, versioning::Version, AnyTx, ArtifactId, ArtifactSpec,
+        CallInfo, CommonError, CoreError, ErrorKind, ErrorMatch, ExecutionError, InstanceId,
+        InstanceSpec, Runtime, RuntimeFeature, SnapshotExt, WellKnownRuntime,
+    },
+};
+use exonum_rust_runtime::spec::ForeignSpec;
+use exonum_testkit::{TestKit, TestKitBuilder};
+use pretty_assertions::assert_eq;
+
+use std::{convert::TryInto, sync::Arc};
+
+use exonum_wasm_runtime::{Error, WasmRuntime};
+
+const COUNTER_WAT: &str = include_str!("modules/counter.wat");
+const COUNTER_MIGRATION_WAT: &str = include_str!("modules/counter_migration.wat");
+const PROXY_WAT: &str = include_str!("modules/proxy.wat");
+
+const COUNTER_ID: InstanceId = 100;
+const PROXY_ID: InstanceId = 101;
+
+fn compile(source: &str) -> Vec<u8> {
+    wat::parse_str(source).expect("Cannot compile Wasm module")
+}
+
+fn artifact(name: &str, version: Version) -> ArtifactId {
+    ArtifactId::new(WasmRuntime::ID, name, version).unwrap()
+}
+
+fn create_testkit() -> (TestKit, KeyPair) {
+    let counter = ForeignSpec::new(artifact("counter", Version::new(0, 1, 0)))
+        .with_deploy_spec(compile(COUNTER_WAT))
+        .with_instance(COUNTER_ID, "counter", 10_u64.to_le_bytes().to_vec());
+    let proxy = ForeignSpec::new(artifact("proxy", Version::new(0, 1, 0)))
+        .with_deploy_spec(compile(PROXY_WAT))
+        .with_instance(PROXY_ID, "proxy", vec![]);
+
+    let testkit = TestKitBuilder::validator()
+        .with_additional_runtime(WasmRuntime::new())
+        .with(counter)
+        .with(proxy)
+        .build();
+    let keys = testkit.us().service_keypair();
+    (testkit, keys)
+}
+
+/// Reads a value from the map of the service named the same as the service.
+fn stored_value(testkit: &TestKit, service: &str, key: &str) -> Option<Vec<u8>> {
+    let snapshot = testkit.snapshot();
+    snapshot
+        .for_service(service)
+        .unwrap()
+        .get_map::<_, [u8], Vec<u8>>(service)
+        .get(key.as_bytes())
+}
+
+fn stored_u64(testkit: &TestKit, service: &str, key: &str) -> Option<u64> {
+    let bytes = stored_value(testkit, service, key)?;
+    Some(u64::from_le_bytes(bytes.as_slice().try_into().unwrap()))
+}
+
+fn execute(
+    testkit: &mut TestKit,
+    keys: &KeyPair,
+    instance_id: InstanceId,
+    method_id: u32,
+    arguments: Vec<u8>,
+) -> Result<(), ExecutionError> {
+    let tx = AnyTx::new(CallInfo::new(instance_id, method_id), arguments).sign_with_keypair(keys);
+    let block = testkit.create_block_with_transaction(tx);
+    block[0].status().map_err(Clone::clone)
+}
+
+#[test]
+fn service_initialization_and_hooks() {
+    let (mut testkit, _) = create_testkit();
+    assert_eq!(stored_u64(&testkit, "counter", "value"), Some(10));
+    // `after_transactions` is called for built-in services in the genesis block.
+    assert_eq!(stored_u64(&testkit, "counter", "blocks"), Some(1));
+
+    testkit.create_blocks_until(Height(3));
+    assert_eq!(stored_u64(&testkit, "counter", "blocks"), Some(4));
+}
+
+#[test]
+fn transactions_modify_service_data() {
+    let (mut testkit, keys) = create_testkit();
+    let arguments = 5_u64.to_le_bytes().to_vec();
+    let tx = AnyTx::new(CallInfo::new(COUNTER_ID, 0), arguments.clone()).sign_with_keypair(&keys);
+    let block = testkit.create_block_with_transaction(tx);
+    block[0].status().unwrap();
+    assert_eq!(stored_u64(&testkit, "counter", "value"), Some(15));
+
+    let snapshot = testkit.snapshot();
+    let events = Schema::new(&snapshot)
+        .call_records(testkit.height())
+        .unwrap()
+        .get_events(CallInBlock::transaction(0));
+    assert_eq!(
+        events,
+        vec![ServiceEvent::new(COUNTER_ID, "added", arguments)]
+    );
+
+    execute(&mut testkit, &keys, COUNTER_ID, 1, vec![]).unwrap();
+    assert_eq!(stored_u64(&testkit, "counter", "value"), Some(0));
+    let owner = stored_value(&testkit, "counter", "owner").unwrap();
+    assert_eq!(owner, keys.public_key().as_ref());
+}
+
+#[test]
+fn caller_exposes_all_signers() {
+    let (mut testkit, keys) = create_testkit();
+    let cosigner = KeyPair::random();
+    let tx = AnyTx::new(CallInfo::new(COUNTER_ID, 4), vec![])
+        .sign_with_cosigners(&keys, &[cosigner.clone()]);
+    let block = testkit.create_block_with_transaction(tx);
+    block[0].status().unwrap();
+
+    let mut signers = vec![keys.public_key(), cosigner.public_key()];
+    signers.sort();
+    let expected_signers: Vec<u8> = signers
+        .iter()
+        .flat_map(|key| key.as_ref().iter().copied())
+        .collect();
+    let stored_signers = stored_value(&testkit, "counter", "signers").unwrap();
+    assert_eq!(stored_signers, expected_signers);
+
+    // A multisig transaction is not authorized by a single key, so the owner is not recorded.
+    let tx =
+        AnyTx::new(CallInfo::new(COUNTER_ID, 1), vec![]).sign_with_cosigners(&keys, &[cosigner]);
+    let block = testkit.create_block_with_transaction(tx);
+    block[0].status().unwrap();
+    assert_eq!(stored_value(&testkit, "counter", "owner"), None);
+}
+
+#[test]
+fn execution_is_limited_by_fuel() {
+    let (mut consensus_config, _) = ConsensusConfig::for_tests(1);
+    consensus_config.max_call_fuel = Some(10_000);
+    let counter = artifact("counter", Version::new(0, 1, 0));
+    let genesis_config = GenesisConfigBuilder::with_consensus_config(consensus_config)
+        .with_artifact_spec(ArtifactSpec::new(counter.clone(), compile(COUNTER_WAT)))
+        .with_instance(InstanceInitParams::new(
+            COUNTER_ID,
+            "counter",
+            counter,
+            10_u64.to_le_bytes().to_vec(),
+        ))
+        .build();
+    let blockchain = Blockchain::build_for_tests()
+        .into_mut(genesis_config)
+        .with_runtime(WasmRuntime::new())
+        .build();
+
+    let keys = KeyPair::random();
+    let simulate = |method_id, arguments: Vec<u8>| {
+        let tx =
+            AnyTx::new(CallInfo::new(COUNTER_ID, method_id), arguments).sign_with_keypair(&keys);
+        blockchain.simulate(&Simulation::signed(&tx)).status.0
+    };
+    simulate(0, 5_u64.to_le_bytes().to_vec()).expect("Call within limits must succeed");
+
+    let err = simulate(5, vec![]).expect_err("Infinite loop must be aborted");
+    let expected_err = ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded)
+        .with_description_containing("fuel")
+        .for_service(COUNTER_ID);
+    assert_eq!(err, expected_err);
+}
+
+#[test]
+fn execution_is_limited_by_runtime_fuel_ceiling() {
+    // `max_call_fuel` is not set in the consensus config.
+    let counter = ForeignSpec::new(artifact("counter", Version::new(0, 1, 0)))
+        .with_deploy_spec(compile(COUNTER_WAT))
+        .with_instance(COUNTER_ID, "counter", 10_u64.to_le_bytes().to_vec());
+    let mut testkit = TestKitBuilder::validator()
+        .with_additional_runtime(WasmRuntime::new().with_max_fuel(10_000))
+        .with(counter)
+        .build();
+    let keys = testkit.us().service_keypair();
+
+    execute(
+        &mut testkit,
+        &keys,
+        COUNTER_ID,
+        0,
+        5_u64.to_le_bytes().to_vec(),
+    )
+    .expect("Call within limits must succeed");
+    let err = execute(&mut testkit, &keys, COUNTER_ID, 5, vec![])
+        .expect_err("Infinite loop must be aborted");
+    let expected_err = ErrorMatch::from_fail(&CoreError::ResourceLimitExceeded)
+        .with_description_containing("fuel ceiling")
+        .for_service(COUNTER_ID);
+    assert_eq!(err, expected_err);
+}
+
+#[test]
+fn service_errors() {
+    let (mut testkit, keys) = create_testkit();
+
+    let err = execute(
+        &mut testkit,
+        &keys,
+        COUNTER_ID,
+        0,
+        0_u64.to_le_bytes().to_vec(),
+    );
+    let err = err.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 1 });
+    assert_eq!(err.description(), "Cannot add zero");
+
+    let err = execute(&mut testkit, &keys, COUNTER_ID, 0, vec![1]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 0 });
+    assert_eq!(err.description(), "Invalid arguments");
+
+    // Changes made by failed transactions are rolled back.
+    assert_eq!(stored_u64(&testkit, "counter", "value"), Some(10));
+}
+
+#[test]
+fn traps_and_unknown_methods() {
+    let (mut testkit, keys) = create_testkit();
+
+    let err = execute(&mut testkit, &keys, COUNTER_ID, 2, vec![]).unwrap_err();
+    let expected_err = ErrorMatch::from_fail(&Error::Trap)
+        .with_description_containing("Unreachable")
+        .in_runtime(WasmRuntime::ID)
+        .for_service(COUNTER_ID);
+    assert_eq!(err, expected_err);
+
+    let err = execute(&mut testkit, &keys, COUNTER_ID, 10, vec![]).unwrap_err();
+    let expected_err = ErrorMatch::from_fail(&CommonError::NoSuchMethod)
+        .with_any_description()
+        .for_service(COUNTER_ID);
+    assert_eq!(err, expected_err);
+}
+
+#[test]
+fn nested_calls() {
+    let (mut testkit, keys) = create_testkit();
+    execute(
+        &mut testkit,
+        &keys,
+        PROXY_ID,
+        0,
+        COUNTER_ID.to_le_bytes().to_vec(),
+    )
+    .unwrap();
+    assert_eq!(stored_u64(&testkit, "proxy", "value"), Some(10));
+
+    // Errors in nested calls are propagated to the caller.
+    let err = execute(
+        &mut testkit,
+        &keys,
+        PROXY_ID,
+        0,
+        1_000_u32.to_le_bytes().to_vec(),
+    );
+    let expected_err = ErrorMatch::from_fail(&CoreError::IncorrectInstanceId)
+        .with_any_description()
+        .for_service(PROXY_ID);
+    assert_eq!(err.unwrap_err(), expected_err);
+}
+
+#[test]
+fn invalid_modules_are_not_deployed() {
+    let mut runtime = WasmRuntime::new();
+    assert!(runtime.is_supported(&RuntimeFeature::FreezingServices));
+
+    let artifact = artifact("invalid", Version::new(1, 0, 0));
+    runtime.deploy_artifact(artifact.clone(), b"not a module".to_vec());
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    // Module importing an unknown host function.
+    let module =
+        compile(r#"(module (import "env" "unknown" (func)) (memory (export "memory") 1 1))"#);
+    runtime.deploy_artifact(artifact.clone(), module);
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    // Module without exported memory.
+    runtime.deploy_artifact(artifact.clone(), compile("(module)"));
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    // Module importing the host function reserved for fuel metering.
+    let module = compile(
+        r#"(module (import "env" "fuel" (func (param i32))) (memory (export "memory") 1 1))"#,
+    );
+    runtime.deploy_artifact(artifact.clone(), module);
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    // Module with unbounded memory.
+    let module = compile(r#"(module (memory (export "memory") 1))"#);
+    runtime.deploy_artifact(artifact.clone(), module);
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    // Module with memory exceeding the runtime limit.
+    let max_pages = WasmRuntime::MAX_MEMORY_PAGES + 1;
+    let module = compile(&format!(
+        r#"(module (memory (export "memory") 1 {}))"#,
+        max_pages
+    ));
+    runtime.deploy_artifact(artifact.clone(), module);
+    assert!(!runtime.is_artifact_deployed(&artifact));
+
+    // Module with bounded memory is deployed.
+    let max_pages = WasmRuntime::MAX_MEMORY_PAGES;
+    let module = compile(&format!(
+        r#"(module (memory (export "memory") 1 {}))"#,
+        max_pages
+    ));
+    runtime.deploy_artifact(artifact.clone(), module);
+    assert!(runtime.is_artifact_deployed(&artifact));
+}
+
+#[test]
+fn data_migration() {
+    let mut runtime = WasmRuntime::new();
+    let old_artifact = artifact("counter", Version::new(0, 1, 0));
+    let new_artifact = artifact("counter", Version::new(0, 2, 0));
+    runtime.deploy_artifact(old_artifact.clone(), compile(COUNTER_WAT));
+    runtime.deploy_artifact(new_artifact.clone(), compile(COUNTER_MIGRATION_WAT));
+    assert!(runtime.is_artifact_deployed(&new_artifact));
+
+    // The old artifact does not define a data migration.
+    let script = runtime
+        .migrate(&old_artifact, &Version::new(0, 0, 1))
+        .unwrap();
+    assert!(script.is_none());
+
+    let db: Arc<dyn Database> = Arc::new(TemporaryDB::new());
+    let fork = db.fork();
+    fork.get_map::<_, [u8], Vec<u8>>("counter.counter")
+        .put(b"value".as_ref(), 21_u64.to_le_bytes().to_vec());
+    db.merge(fork.into_patch()).unwrap();
+
+    let data_version = Version::new(0, 1, 0);
+    let script = runtime
+        .migrate(&new_artifact, &data_version)
+        .unwrap()
+        .expect("Migration script");
+    assert_eq!(*script.end_version(), new_artifact.version);
+
+    let spec = InstanceSpec::from_raw_parts(COUNTER_ID, "counter".to_owned(), old_artifact);
+    let helper = MigrationHelper::new(Arc::clone(&db), "counter");
+    let mut context = MigrationContext::new(helper, spec, data_version);
+    script.execute(&mut context).unwrap();
+
+    let value = context
+        .helper
+        .new_data()
+        .get_map::<_, [u8], Vec<u8>>("counter")
+        .get(b"value".as_ref())
+        .unwrap();
+    assert_eq!(value, 42_u64.to_le_bytes().to_vec());
+}
+
+#[test]
+fn data_migration_is_limited_by_fuel() {
+    let mut runtime = WasmRuntime::new().with_max_fuel(10_000);
+    let new_artifact = artifact("counter", Version::new(0, 2, 0));
+    let module = compile(
+        r#"(module
+            (memory (export "memory") 1 1)
+            (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+            (func (export "migrate") (param i32 i32) (loop $forever (br $forever))))"#,
+    );
+    runtime.deploy_artifact(new_artifact.clone(), module);
+    assert!(runtime.is_artifact_deployed(&new_artifact));
+
+    let data_version = Version::new(0, 1, 0);
+    let script = runtime
+        .migrate(&new_artifact, &data_version)
+        .unwrap()
+        .expect("Migration script");
+
+    let db: Arc<dyn Database> = Arc::new(TemporaryDB::new());
+    let old_artifact = artifact("counter", data_version.clone());
+    let spec = InstanceSpec::from_raw_parts(COUNTER_ID, "counter".to_owned(), old_artifact);
+    let helper = MigrationHelper::new(db, "counter");
+    let mut context = MigrationContext::new(helper, spec, data_version);
+    let err = script
+        .execute(&mut context)
+        .expect_err("Infinite loop must be aborted");
+    assert!(err.to_string().contains("fuel ceiling"), "{}", err);
+}
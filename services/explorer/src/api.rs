@@ -12,21 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! HTTP API for the explorer service. All APIs are accessible from the public HTTP server
-//! of the node.
+//! HTTP API for the explorer service. All APIs except for [tracing transactions]
+//! are accessible from the public HTTP server of the node; tracing is accessible
+//! from the private HTTP server only.
+//!
+//! [tracing transactions]: #trace-transaction
 //!
 //! # Table of Contents
 //!
 //! - [List blocks](#list-blocks)
 //! - [Get specific block](#get-specific-block)
 //! - [Get transaction by hash](#transaction-by-hash)
+//! - [Get transaction receipt](#transaction-receipt)
 //! - Call status:
 //!
 //!     - [for transactions](#call-status-for-transaction)
 //!     - [for `before_transactions` hook](#call-status-for-before_transactions-hook)
 //!     - [for `after_transactions` hook](#call-status-for-after_transactions-hook)
 //!
+//! - [Call events](#call-events)
 //! - [Submit transaction](#submit-transaction)
+//! - [List service indexes](#list-service-indexes)
 //!
 //! # Transaction Processing
 //!
@@ -212,6 +218,77 @@
 //! # }
 //! ```
 //!
+//! # Transaction Receipt
+//!
+//! | Property    | Value |
+//! |-------------|-------|
+//! | Path        | `/api/explorer/v1/transactions/receipt` |
+//! | Method      | GET   |
+//! | Query type  | [`TransactionQuery`] |
+//! | Return type | [`TransactionReceipt`] |
+//!
+//! Returns a receipt for a committed transaction, which proves that the transaction
+//! was committed with a certain execution status. The receipt can be verified by
+//! the client knowing the consensus keys of the validators and serialized
+//! in the Protobuf format for storage.
+//!
+//! [`TransactionReceipt`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.TransactionReceipt.html
+//!
+//! ```
+//! # use exonum::{
+//! #     blockchain::TransactionReceipt, crypto::gen_keypair, merkledb::ObjectHash,
+//! #     runtime::ExecutionError,
+//! # };
+//! # use exonum_rust_runtime::{ExecutionContext, DefaultInstance, Service, ServiceFactory};
+//! # use exonum_derive::*;
+//! # use exonum_explorer_service::{api::TransactionQuery, ExplorerFactory};
+//! # use exonum_testkit::{Spec, TestKitBuilder};
+//! #[exonum_interface]
+//! trait ServiceInterface<Ctx> {
+//!     type Output;
+//!     #[interface_method(id = 0)]
+//!     fn do_nothing(&self, ctx: Ctx, _seed: u32) -> Self::Output;
+//! }
+//!
+//! #[derive(Debug, ServiceDispatcher, ServiceFactory)]
+//! # #[service_factory(artifact_name = "my-service")]
+//! #[service_dispatcher(implements("ServiceInterface"))]
+//! struct MyService;
+//! // Some implementations skipped for `MyService`...
+//! # impl ServiceInterface<ExecutionContext<'_>> for MyService {
+//! #    type Output = Result<(), ExecutionError>;
+//! #    fn do_nothing(&self, ctx: ExecutionContext<'_>, _seed: u32) -> Self::Output { Ok(()) }
+//! # }
+//! # impl DefaultInstance for MyService {
+//! #     const INSTANCE_ID: u32 = 100;
+//! #     const INSTANCE_NAME: &'static str = "my-service";
+//! # }
+//! # impl Service for MyService {}
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let mut testkit = TestKitBuilder::validator()
+//!    .with(Spec::new(ExplorerFactory).with_default_instance())
+//!    .with(Spec::new(MyService).with_default_instance())
+//!    .build();
+//! let tx = gen_keypair().do_nothing(MyService::INSTANCE_ID, 0);
+//! testkit.create_block_with_transaction(tx.clone());
+//!
+//! let api = testkit.api();
+//! let receipt: TransactionReceipt = reqwest::Client::new()
+//!     .get(&api.public_url("api/explorer/v1/transactions/receipt"))
+//!     .query(&TransactionQuery::new(tx.object_hash()))
+//!     .send().await?
+//!     .error_for_status()?
+//!     .json().await?;
+//! let validator_keys = [testkit.us().public_keys().consensus_key];
+//! let (tx_hash, status) = receipt.verify(&validator_keys)?;
+//! assert_eq!(tx_hash, tx.object_hash());
+//! assert!(status.0.is_ok());
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! # Call Status for Transaction
 //!
 //! | Property    | Value |
@@ -352,6 +429,76 @@
 //! Same as the [previous endpoint](#call-status-for-before_transactions-hook), only
 //! for a hook executing after all transactions in a block.
 //!
+//! # Call Events
+//!
+//! | Property    | Value |
+//! |-------------|-------|
+//! | Path        | `/api/explorer/v1/call_events/transaction` |
+//! | Method      | GET   |
+//! | Query type  | [`TransactionStatusQuery`] |
+//! | Return type | [`CallEventsResponse`] |
+//!
+//! Returns events emitted by services during execution of a transaction, in the order
+//! of their emission. Events emitted during `before_transactions` and `after_transactions`
+//! hooks can be retrieved with the `/api/explorer/v1/call_events/before_transactions` and
+//! `/api/explorer/v1/call_events/after_transactions` endpoints correspondingly;
+//! these endpoints accept [`CallStatusQuery`] as the query type.
+//!
+//! If the query requests a proof, the events are returned together with
+//! a cryptographic proof of authenticity.
+//!
+//! [`CallEventsResponse`]: enum.CallEventsResponse.html
+//!
+//! ```
+//! # use exonum::{helpers::Height, runtime::ExecutionError};
+//! # use exonum_rust_runtime::{ExecutionContext, DefaultInstance, Service, ServiceFactory};
+//! # use exonum_derive::*;
+//! # use exonum_explorer_service::{
+//! #     api::{CallEventsResponse, CallStatusQuery}, ExplorerFactory,
+//! # };
+//! # use exonum_testkit::{Spec, TestKitBuilder};
+//! #[derive(Debug, ServiceDispatcher, ServiceFactory)]
+//! # #[service_factory(artifact_name = "my-service")]
+//! struct MyService;
+//! // Some implementations skipped for `MyService`...
+//! # impl DefaultInstance for MyService {
+//! #     const INSTANCE_ID: u32 = 100;
+//! #     const INSTANCE_NAME: &'static str = "my-service";
+//! # }
+//! impl Service for MyService {
+//!     fn after_transactions(&self, mut ctx: ExecutionContext<'_>) -> Result<(), ExecutionError> {
+//!         ctx.emit_event("tick", vec![1_u8, 2, 3])
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let mut testkit = TestKitBuilder::validator()
+//!    .with(Spec::new(ExplorerFactory).with_default_instance())
+//!    .with(Spec::new(MyService).with_default_instance())
+//!    .build();
+//! testkit.create_blocks_until(Height(5));
+//!
+//! let api = testkit.api();
+//! let query = CallStatusQuery::new(Height(2), MyService::INSTANCE_ID).with_proof();
+//! let response: CallEventsResponse = reqwest::Client::new()
+//!     .get(&api.public_url("api/explorer/v1/call_events/after_transactions"))
+//!     .query(&query)
+//!     .send().await?
+//!     .error_for_status()?
+//!     .json().await?;
+//! let proof = match response {
+//!     CallEventsResponse::Proof(proof) => proof,
+//!     _ => unreachable!(),
+//! };
+//! let validator_keys = [testkit.us().public_keys().consensus_key];
+//! let (_, events) = proof.verify(&validator_keys)?;
+//! assert_eq!(events[0].topic, "tick");
+//! assert_eq!(events[0].payload, vec![1, 2, 3]);
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! # Submit Transaction
 //!
 //! | Property    | Value |
@@ -420,25 +567,170 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! # Simulate Transaction
+//!
+//! | Property    | Value |
+//! |-------------|-------|
+//! | Path        | `/api/explorer/v1/transactions/simulate` |
+//! | Method      | POST   |
+//! | Query type  | [`SimulationQuery`] |
+//! | Return type | [`SimulationResponse`] |
+//!
+//! Executes a transaction against the latest blockchain state without committing it
+//! and returns the execution status together with the indexes touched by the transaction.
+//! If the `with_diff` flag is set, the response also contains changes in the hashes
+//! of the touched indexes. If the `with_trace` flag is set, the response contains the trace
+//! of calls made by the transaction (see [*Trace Transaction*](#trace-transaction)).
+//!
+//! The transaction may be unsigned; in this case, the query must specify the transaction
+//! author, and the simulation succeeds only if the runtime of the target service supports
+//! unsigned simulation. The simulation is performed by the node, so the endpoint is only
+//! available if the explorer is run by a node.
+//!
+//! [`SimulationQuery`]: struct.SimulationQuery.html
+//! [`SimulationResponse`]: struct.SimulationResponse.html
+//!
+//! # Trace Transaction
+//!
+//! | Property    | Value |
+//! |-------------|-------|
+//! | Path        | `/api/explorer/v1/transactions/trace` (private) |
+//! | Method      | GET   |
+//! | Query type  | [`TransactionQuery`] |
+//! | Return type | [`TransactionTrace`] |
+//!
+//! Re-executes a committed transaction and returns the tree of calls made by the transaction,
+//! together with the call results and storage keys read and written by each call.
+//!
+//! The storage does not retain historical blockchain states, so the node re-executes blocks
+//! preceding the transaction on a temporary blockchain, and then re-executes the block
+//! with the transaction up to the transaction itself. Thus, the transaction is traced against
+//! the same state as it was originally executed against. The replayed blocks are retained
+//! by the node, so that subsequent traces only re-execute newly committed blocks; however,
+//! tracing a transaction older than the previously traced one replays the blockchain
+//! from the genesis block. Traces are performed one at a time. Since tracing can be
+//! expensive, the endpoint is available only via the private API.
+//!
+//! The response contains both the execution status recorded in the blockchain
+//! and the status of re-execution.
+//! Like simulation, tracing is only available if the explorer is run by a node.
+//!
+//! [`TransactionQuery`]: struct.TransactionQuery.html
+//! [`TransactionTrace`]: struct.TransactionTrace.html
+//!
+//! # Query Service
+//!
+//! | Property    | Value |
+//! |-------------|-------|
+//! | Path        | `/api/explorer/v1/services/query` |
+//! | Method      | GET   |
+//! | Query type  | [`ServiceQuery`] |
+//! | Return type | [`ServiceQueryResponse`] |
+//!
+//! Performs a read-only query to a service identified by its numeric ID and returns
+//! the hex-encoded value returned by the service. The query method is identified
+//! by its numeric ID, and its arguments are passed as a hex-encoded string; the format
+//! of arguments and the returned value is defined by the runtime of the service
+//! (e.g., `BinaryValue` encoding for the Rust runtime). The query is dispatched
+//! to any runtime supporting read-only queries. Like simulation, queries are only available
+//! if the explorer is run by a node.
+//!
+//! [`ServiceQuery`]: struct.ServiceQuery.html
+//! [`ServiceQueryResponse`]: struct.ServiceQueryResponse.html
+//!
+//! # List Service Indexes
+//!
+//! | Property    | Value |
+//! |-------------|-------|
+//! | Path        | `/api/explorer/v1/indexes` |
+//! | Method      | GET   |
+//! | Query type  | [`ServiceIndexesQuery`] |
+//! | Return type | [`ServiceIndexes`] |
+//!
+//! Lists all indexes of an active service, together with their types, hashes
+//! (for Merkelized indexes) and approximate number of entries. This allows to inspect
+//! the data stored by a service without knowing the names of its indexes in advance.
+//!
+//! [`ServiceIndexesQuery`]: struct.ServiceIndexesQuery.html
+//! [`ServiceIndexes`]: struct.ServiceIndexes.html
+//!
+//! ```
+//! # use exonum::merkledb::IndexType;
+//! # use exonum_explorer_service::{
+//! #     api::{ServiceIndexes, ServiceIndexesQuery}, ExplorerFactory,
+//! # };
+//! # use exonum_rust_runtime::{DefaultInstance, Service, ServiceFactory};
+//! # use exonum_derive::*;
+//! # use exonum_testkit::{Spec, TestKitBuilder};
+//! #[derive(Debug, ServiceDispatcher, ServiceFactory)]
+//! # #[service_factory(artifact_name = "my-service")]
+//! struct MyService;
+//! // Some implementations skipped for `MyService`...
+//! # impl DefaultInstance for MyService {
+//! #     const INSTANCE_ID: u32 = 100;
+//! #     const INSTANCE_NAME: &'static str = "my-service";
+//! # }
+//! # impl Service for MyService {
+//! #     fn initialize(
+//! #         &self,
+//! #         ctx: exonum_rust_runtime::ExecutionContext<'_>,
+//! #         _params: Vec<u8>,
+//! #     ) -> Result<(), exonum::runtime::ExecutionError> {
+//! #         use exonum::merkledb::access::AccessExt;
+//! #         ctx.service_data().get_proof_entry("config").set(42_u64);
+//! #         Ok(())
+//! #     }
+//! # }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let mut testkit = TestKitBuilder::validator()
+//!    .with(Spec::new(ExplorerFactory).with_default_instance())
+//!    .with(Spec::new(MyService).with_default_instance())
+//!    .build();
+//!
+//! let api = testkit.api();
+//! let response: ServiceIndexes = reqwest::Client::new()
+//!     .get(&api.public_url("api/explorer/v1/indexes"))
+//!     .query(&ServiceIndexesQuery::new(MyService::INSTANCE_ID))
+//!     .send().await?
+//!     .error_for_status()?
+//!     .json().await?;
+//! assert_eq!(response.service_name, "my-service");
+//! let index = &response.indexes[0];
+//! assert_eq!(index.name, "my-service.config");
+//! assert_eq!(index.index_type, IndexType::ProofEntry);
+//! assert!(index.is_proof);
+//! assert_eq!(index.approx_len, 1);
+//! # Ok(())
+//! # }
+//! ```
 
 pub use exonum_explorer::{
     api::websocket::{
-        CommittedTransactionSummary, Notification, SubscriptionType, TransactionFilter,
+        CommittedTransactionSummary, EventFilter, EventSummary, Notification, SubscriptionType,
+        TransactionFilter,
     },
     api::{
-        BlockInfo, BlockQuery, BlocksQuery, BlocksRange, CallStatusQuery, CallStatusResponse,
-        TransactionHex, TransactionQuery, TransactionResponse, TransactionStatusQuery,
+        BlockInfo, BlockQuery, BlocksQuery, BlocksRange, CallEventsResponse, CallStatusQuery,
+        CallStatusResponse, ServiceIndexInfo, ServiceIndexes, ServiceIndexesQuery, ServiceQuery,
+        ServiceQueryResponse, SimulationQuery, SimulationResponse, TouchedIndex, TransactionHex,
+        TransactionQuery, TransactionResponse, TransactionStatusQuery, TransactionTrace,
         MAX_BLOCKS_PER_REQUEST,
     },
     TransactionInfo,
 };
 
 use exonum::{
-    blockchain::{ApiSender, Blockchain, CallInBlock, Schema, TxCheckCache},
+    blockchain::{
+        ApiSender, Blockchain, CallInBlock, Query, QueryError, Schema, Simulation,
+        TransactionReceipt, TxCheckCache,
+    },
     helpers::Height,
-    merkledb::{ObjectHash, Snapshot},
+    merkledb::{BinaryValue, ObjectHash, Snapshot},
     messages::SignedMessage,
-    runtime::{ExecutionStatus, SnapshotExt},
+    runtime::{AnyTx, BlockchainData, ExecutionStatus, SnapshotExt},
 };
 use exonum_explorer::BlockchainExplorer;
 use exonum_rust_runtime::api::{self, ServiceApiScope};
@@ -563,6 +855,17 @@ impl ExplorerApi {
             })
     }
 
+    fn transaction_receipt(
+        schema: &Schema<&dyn Snapshot>,
+        query: &TransactionQuery,
+    ) -> api::Result<TransactionReceipt> {
+        schema.transaction_receipt(&query.hash).ok_or_else(|| {
+            api::Error::not_found()
+                .title("Transaction not committed")
+                .detail(format!("Unknown transaction hash ({})", query.hash))
+        })
+    }
+
     fn get_status(
         schema: &Schema<&dyn Snapshot>,
         block_height: Height,
@@ -623,6 +926,94 @@ impl ExplorerApi {
         Self::get_status(schema, query.height, call_in_block, query.with_proof)
     }
 
+    fn get_events(
+        schema: &Schema<&dyn Snapshot>,
+        block_height: Height,
+        call_in_block: CallInBlock,
+        with_proof: bool,
+    ) -> api::Result<CallEventsResponse> {
+        let records = schema.call_records(block_height).ok_or_else(|| {
+            api::Error::not_found()
+                .title("Block not found")
+                .detail(format!(
+                    "Block with height {} is not yet created",
+                    block_height
+                ))
+        })?;
+
+        Ok(if with_proof {
+            let proof = records.get_events_proof(call_in_block);
+            CallEventsResponse::Proof(proof)
+        } else {
+            CallEventsResponse::Simple(records.get_events(call_in_block))
+        })
+    }
+
+    /// Returns events emitted during execution of a transaction.
+    fn transaction_events(
+        schema: &Schema<&dyn Snapshot>,
+        query: &TransactionStatusQuery,
+    ) -> api::Result<CallEventsResponse> {
+        let tx_location = schema
+            .transactions_locations()
+            .get(&query.hash)
+            .ok_or_else(|| {
+                api::Error::not_found()
+                    .title("Transaction not committed")
+                    .detail(format!("Unknown transaction hash ({})", query.hash))
+            })?;
+
+        let call_in_block = CallInBlock::transaction(tx_location.position_in_block());
+        let block_height = tx_location.block_height();
+        Self::get_events(schema, block_height, call_in_block, query.with_proof)
+    }
+
+    /// Returns events emitted by the `before_transactions` hook.
+    fn before_transactions_events(
+        schema: &Schema<&dyn Snapshot>,
+        query: &CallStatusQuery,
+    ) -> api::Result<CallEventsResponse> {
+        let call_in_block = CallInBlock::before_transactions(query.service_id);
+        Self::get_events(schema, query.height, call_in_block, query.with_proof)
+    }
+
+    /// Returns events emitted by the `after_transactions` hook.
+    fn after_transactions_events(
+        schema: &Schema<&dyn Snapshot>,
+        query: &CallStatusQuery,
+    ) -> api::Result<CallEventsResponse> {
+        let call_in_block = CallInBlock::after_transactions(query.service_id);
+        Self::get_events(schema, query.height, call_in_block, query.with_proof)
+    }
+
+    /// Returns information about indexes of a service.
+    fn service_indexes(
+        data: &BlockchainData<&dyn Snapshot>,
+        query: &ServiceIndexesQuery,
+    ) -> api::Result<ServiceIndexes> {
+        let not_found = || {
+            api::Error::not_found()
+                .title("Service not found")
+                .detail(format!(
+                    "Active service with ID {} does not exist",
+                    query.service_id
+                ))
+        };
+
+        let indexes = data
+            .service_indexes(query.service_id)
+            .ok_or_else(not_found)?;
+        let instance = data
+            .for_dispatcher()
+            .get_instance(query.service_id)
+            .ok_or_else(not_found)?;
+        Ok(ServiceIndexes::new(
+            query.service_id,
+            instance.spec.name,
+            indexes,
+        ))
+    }
+
     fn add_transaction(
         snapshot: &dyn Snapshot,
         sender: &ApiSender,
@@ -663,6 +1054,112 @@ impl ExplorerApi {
             .right_future()
     }
 
+    fn simulate_transaction(
+        blockchain: &Blockchain,
+        query: SimulationQuery,
+    ) -> impl Future<Output = api::Result<SimulationResponse>> {
+        let parse_query = |query: SimulationQuery| -> anyhow::Result<_> {
+            let simulation = match (query.tx_body, query.unsigned_tx, query.author) {
+                (Some(hex), None, None) => {
+                    let verified = SignedMessage::from_hex(hex)?.into_verified()?;
+                    Simulation::signed(&verified)
+                }
+                (None, Some(hex), Some(author)) => {
+                    let tx = AnyTx::from_bytes(hex::decode(hex)?.into())?;
+                    Simulation::unsigned(tx, author)
+                }
+                _ => anyhow::bail!(
+                    "Query should contain either a signed transaction, \
+                     or an unsigned transaction together with its author"
+                ),
+            };
+            let simulation = if query.with_diff {
+                simulation.with_diff()
+            } else {
+                simulation
+            };
+            Ok(if query.with_trace {
+                simulation.with_trace()
+            } else {
+                simulation
+            })
+        };
+
+        let simulation = match parse_query(query) {
+            Ok(simulation) => simulation,
+            Err(err) => {
+                let err = api::Error::bad_request()
+                    .title("Failed to simulate transaction")
+                    .detail(err.to_string());
+                return future::err(err).left_future();
+            }
+        };
+
+        let tx_hash = simulation.tx_hash();
+        blockchain
+            .simulate(simulation)
+            .map_ok(move |result| SimulationResponse::new(tx_hash, result))
+            .map_err(|err| api::Error::internal(err).title("Failed to simulate transaction"))
+            .right_future()
+    }
+
+    fn trace_transaction(
+        blockchain: &Blockchain,
+        schema: Schema<&dyn Snapshot>,
+        query: &TransactionQuery,
+    ) -> impl Future<Output = api::Result<TransactionTrace>> {
+        let committed = BlockchainExplorer::from_schema(schema)
+            .transaction(&query.hash)
+            .and_then(|info| match info {
+                TransactionInfo::Committed(committed) => Some(committed),
+                _ => None,
+            });
+        let committed = match committed {
+            Some(committed) => committed,
+            None => {
+                let err = api::Error::not_found()
+                    .title("Failed to trace transaction")
+                    .detail(format!("Transaction {:?} is not committed", query.hash));
+                return future::err(err).left_future();
+            }
+        };
+
+        let location = *committed.location();
+        let committed_status = ExecutionStatus(committed.status().map_err(Clone::clone));
+        blockchain
+            .trace(query.hash)
+            .map_ok(move |result| TransactionTrace::new(location, committed_status, result))
+            .map_err(|err| api::Error::internal(err).title("Failed to trace transaction"))
+            .right_future()
+    }
+
+    fn query_service(
+        blockchain: &Blockchain,
+        query: ServiceQuery,
+    ) -> impl Future<Output = api::Result<ServiceQueryResponse>> {
+        let arguments = match hex::decode(&query.arguments) {
+            Ok(arguments) => arguments,
+            Err(err) => {
+                let err = api::Error::bad_request()
+                    .title("Failed to query service")
+                    .detail(format!("Cannot decode query arguments: {}", err));
+                return future::err(err).left_future();
+            }
+        };
+
+        let query = Query::new(query.instance_id, query.method_id, arguments);
+        blockchain
+            .query(query)
+            .map_ok(|result| ServiceQueryResponse::new(&result))
+            .map_err(|err| match err {
+                QueryError::Execution(err) => api::Error::bad_request()
+                    .title("Failed to query service")
+                    .detail(err.to_string()),
+                err => api::Error::internal(err).title("Failed to query service"),
+            })
+            .right_future()
+    }
+
     /// Adds explorer API endpoints to the corresponding scope.
     pub fn wire_rest(&self, api_scope: &mut ServiceApiScope) -> &Self {
         api_scope
@@ -687,14 +1184,53 @@ impl ExplorerApi {
                     &query,
                 ))
             })
+            .endpoint("v1/call_events/transaction", |state, query| {
+                future::ready(Self::transaction_events(&state.data().for_core(), &query))
+            })
+            .endpoint("v1/call_events/after_transactions", |state, query| {
+                future::ready(Self::after_transactions_events(
+                    &state.data().for_core(),
+                    &query,
+                ))
+            })
+            .endpoint("v1/call_events/before_transactions", |state, query| {
+                future::ready(Self::before_transactions_events(
+                    &state.data().for_core(),
+                    &query,
+                ))
+            })
             .endpoint("v1/transactions", |state, query| {
                 future::ready(Self::transaction_info(state.data().for_core(), &query))
+            })
+            .endpoint("v1/transactions/receipt", |state, query| {
+                future::ready(Self::transaction_receipt(&state.data().for_core(), &query))
+            })
+            .endpoint("v1/indexes", |state, query| {
+                future::ready(Self::service_indexes(&state.data(), &query))
             });
 
+        let blockchain = self.blockchain.clone();
+        api_scope.endpoint("v1/services/query", move |_state, query| {
+            Self::query_service(&blockchain, query)
+        });
+
         let tx_sender = self.blockchain.sender().to_owned();
         api_scope.endpoint_mut("v1/transactions", move |state, query| {
             Self::add_transaction(state.snapshot(), &tx_sender, query)
         });
+        let blockchain = self.blockchain.clone();
+        api_scope.endpoint_mut("v1/transactions/simulate", move |_state, query| {
+            Self::simulate_transaction(&blockchain, query)
+        });
+        self
+    }
+
+    /// Adds private explorer API endpoints to the corresponding scope.
+    pub fn wire_private_rest(&self, api_scope: &mut ServiceApiScope) -> &Self {
+        let blockchain = self.blockchain.clone();
+        api_scope.endpoint("v1/transactions/trace", move |state, query| {
+            Self::trace_transaction(&blockchain, state.data().for_core(), &query)
+        });
         self
     }
 }
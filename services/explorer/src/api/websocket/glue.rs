@@ -31,7 +31,7 @@ use futures::{future, FutureExt};
 
 use std::sync::Arc;
 
-use super::{Session, SharedStateRef, SubscriptionType, TransactionFilter};
+use super::{EventFilter, Session, SharedStateRef, SubscriptionType, TransactionFilter};
 use crate::api::ExplorerApi;
 
 impl ExplorerApi {
@@ -95,6 +95,27 @@ impl ExplorerApi {
                     .unwrap_or(SubscriptionType::None)
             },
         );
+        // Default subscription for service events.
+        Self::handle_ws(
+            "v1/events/subscribe",
+            api_scope.web_backend(),
+            self.blockchain.clone(),
+            shared_state.clone(),
+            |request| {
+                if request.query_string().is_empty() {
+                    return SubscriptionType::Events { filter: None };
+                }
+
+                let extract: future::Ready<_> = Query::<EventFilter>::extract(request);
+                extract
+                    .now_or_never()
+                    .expect("`Ready` futures always have their output immediately available")
+                    .map(|query| SubscriptionType::Events {
+                        filter: Some(query.into_inner()),
+                    })
+                    .unwrap_or(SubscriptionType::None)
+            },
+        );
         // Default websocket connection.
         Self::handle_ws(
             "v1/ws",
@@ -19,10 +19,11 @@
 //! All communication via WebSockets uses JSON encoding.
 //!
 //! The API follows the publisher-subscriber pattern. Clients can subscribe to events. There are
-//! two types of events encapsulated in [`Notification`]:
+//! three types of events encapsulated in [`Notification`]:
 //!
 //! - block creation
 //! - commitment of a transaction
+//! - emission of a service event
 //!
 //! Subscription types are encapsulated in [`SubscriptionType`]. A single client may have
 //! multiple subscriptions.
@@ -32,12 +33,14 @@
 //! responds to each `IncomingMessage` with a [`Response`], which
 //! wraps the response type (`()` for subscriptions, [`TransactionResponse`] for transactions).
 //!
-//! There are three WS endpoints, which differ by the initial subscription for the client:
+//! There are four WS endpoints, which differ by the initial subscription for the client:
 //!
 //! - `api/explorer/v1/ws` does not set any subscriptions
 //! - `api/explorer/v1/blocks/subscribe` sets subscription to blocks
 //! - `api/explorer/v1/transactions/subscribe` sets subscription to transactions. The parameters
 //!   of the subscription are encoded in the query as [`TransactionFilter`]
+//! - `api/explorer/v1/events/subscribe` sets subscription to service events. The parameters
+//!   of the subscription are encoded in the query as [`EventFilter`]
 //!
 //! [`IncomingMessage`]: enum.IncomingMessage.html
 //! [`Response`]: enum.Response.html
@@ -46,6 +49,7 @@
 //! [`TransactionHex`]: ../struct.TransactionHex.html
 //! [`TransactionResponse`]: ../struct.TransactionResponse.html
 //! [`TransactionFilter`]: struct.TransactionFilter.html
+//! [`EventFilter`]: struct.EventFilter.html
 //!
 //! # Examples
 //!
@@ -191,8 +195,8 @@
 //! ```
 
 pub use exonum_explorer::api::websocket::{
-    CommittedTransactionSummary, IncomingMessage, Notification, Response, SubscriptionType,
-    TransactionFilter,
+    CommittedTransactionSummary, EventFilter, EventSummary, IncomingMessage, Notification,
+    Response, SubscriptionType, TransactionFilter,
 };
 
 use actix::prelude::*;
@@ -523,6 +527,26 @@ impl Handler<Broadcast> for Server {
                 &data,
             );
         }
+
+        // Notify about events emitted within the block.
+        for event in EventSummary::all_in_block(&schema, height) {
+            let instance_id = event.event.instance_id;
+            let topic = event.event.topic.clone();
+            let data = Notification::Event(event);
+            self.broadcast_message(SubscriptionType::Events { filter: None }, &data);
+            self.broadcast_message(
+                SubscriptionType::Events {
+                    filter: Some(EventFilter::new(instance_id, None)),
+                },
+                &data,
+            );
+            self.broadcast_message(
+                SubscriptionType::Events {
+                    filter: Some(EventFilter::new(instance_id, Some(topic))),
+                },
+                &data,
+            );
+        }
     }
 }
 
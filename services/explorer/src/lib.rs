@@ -132,11 +132,11 @@ impl Service for ExplorerService {
 
     fn wire_api(&self, builder: &mut ServiceApiBuilder) {
         let blockchain = builder.blockchain().to_owned();
-        let scope = builder
-            .with_root_path(ExplorerFactory::INSTANCE_NAME)
-            .public_scope();
-        ExplorerApi::new(blockchain)
-            .wire_rest(scope)
+        let api = ExplorerApi::new(blockchain);
+        let builder = builder.with_root_path(ExplorerFactory::INSTANCE_NAME);
+        api.wire_private_rest(builder.private_scope());
+        let scope = builder.public_scope();
+        api.wire_rest(scope)
             .wire_ws(self.shared_state.get_ref(), scope);
     }
 }
@@ -14,11 +14,13 @@
 
 use assert_matches::assert_matches;
 use exonum::{
-    blockchain::{AdditionalHeaders, CallInBlock, Epoch, ProposerId},
+    blockchain::{
+        AdditionalHeaders, CallInBlock, Epoch, ProposerId, Query, Simulation, TransactionReceipt,
+    },
     crypto::{Hash, KeyPair},
     helpers::{Height, ValidatorId},
-    merkledb::{BinaryValue, HashTag, ObjectHash},
-    runtime::{ErrorKind, ExecutionError, ExecutionStatus},
+    merkledb::{BinaryValue, HashTag, IndexAddress, IndexType, ObjectHash},
+    runtime::{CommonError, CoreError, ErrorKind, ErrorMatch, ExecutionError, ExecutionStatus},
 };
 use exonum_api as api;
 use exonum_explorer::{api::*, BlockchainExplorer, TransactionInfo};
@@ -650,6 +652,68 @@ async fn test_explorer_api_with_transaction_error() {
     assert!(execution_error
         .description()
         .contains("Adding zero does nothing!"));
+    let payload = execution_error.payload().unwrap();
+    assert_eq!(payload.type_tag, "u64");
+    assert_eq!(payload.decode::<u64>().unwrap(), 0);
+
+    let response: Value = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionStatusQuery::new(tx.object_hash()))
+        .get("v1/call_status/transaction")
+        .await
+        .expect("Explorer Api unexpectedly failed");
+    assert_eq!(
+        response["payload"],
+        json!({ "type_tag": "u64", "value": hex::encode(0_u64.to_bytes()) })
+    );
+    let status: ExecutionStatus = serde_json::from_value(response).unwrap();
+    let payload = status.0.unwrap_err().payload().cloned().unwrap();
+    assert_eq!(payload.decode::<u64>().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_explorer_transaction_receipt() {
+    let (mut testkit, api) = init_testkit();
+    let key_pair = KeyPair::random();
+    let ok_tx = key_pair.increment(SERVICE_ID, 5);
+    let err_tx = key_pair.increment(SERVICE_ID, 0);
+    testkit.create_block_with_transactions(vec![ok_tx.clone(), err_tx.clone()]);
+    let validator_keys = [testkit.us().public_keys().consensus_key];
+
+    let receipt: TransactionReceipt = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(ok_tx.object_hash()))
+        .get("v1/transactions/receipt")
+        .await
+        .expect("Explorer Api unexpectedly failed");
+    assert_eq!(receipt.location.block_height(), Height(1));
+    let (tx_hash, status) = receipt.verify(&validator_keys).unwrap();
+    assert_eq!(tx_hash, ok_tx.object_hash());
+    assert!(status.0.is_ok());
+
+    let receipt: TransactionReceipt = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(err_tx.object_hash()))
+        .get("v1/transactions/receipt")
+        .await
+        .expect("Explorer Api unexpectedly failed");
+    // Check that the receipt survives Protobuf serialization.
+    let receipt = TransactionReceipt::from_bytes(receipt.to_bytes().into()).unwrap();
+    let (tx_hash, status) = receipt.verify(&validator_keys).unwrap();
+    assert_eq!(tx_hash, err_tx.object_hash());
+    let execution_error = status.0.unwrap_err();
+    assert!(execution_error
+        .description()
+        .contains("Adding zero does nothing!"));
+
+    let err = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(Hash::zero()))
+        .get::<TransactionReceipt>("v1/transactions/receipt")
+        .await
+        .expect_err("Expected an error for unknown transaction");
+    assert_eq!(err.http_code, api::HttpStatusCode::NOT_FOUND);
+    assert_eq!(err.body.title, "Transaction not committed");
 }
 
 #[tokio::test]
@@ -689,3 +753,286 @@ async fn test_explorer_api_with_after_transactions_error() {
         .description()
         .contains("What's the question?"));
 }
+
+#[tokio::test]
+async fn test_explorer_call_events() {
+    let (mut testkit, api) = init_testkit();
+    let alice = KeyPair::random();
+    let tx = alice.increment(SERVICE_ID, 5);
+    // This transaction panics after emitting an event.
+    let panicking_tx = alice.increment(SERVICE_ID, u64::max_value() - 3);
+    let block = testkit.create_block_with_transactions(vec![tx.clone(), panicking_tx.clone()]);
+    assert!(block[1].status().is_err());
+
+    // Events of the erroneous transaction should be discarded.
+    let events: Vec<_> = block.events().collect();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].0, CallInBlock::transaction(0));
+    assert_eq!(events[0].1.topic, "increment");
+    assert_eq!(events[0].1.payload, 5_u64.into_bytes());
+    assert_ne!(block.header.events_hash(), Some(HashTag::empty_map_hash()));
+
+    let response: CallEventsResponse = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionStatusQuery::new(tx.object_hash()))
+        .get("v1/call_events/transaction")
+        .await
+        .expect("Explorer Api unexpectedly failed");
+    let events = match response {
+        CallEventsResponse::Simple(events) => events,
+        other => panic!("Unexpected response format: {:?}", other),
+    };
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].instance_id, SERVICE_ID);
+
+    let validator_keys = [testkit.us().public_keys().consensus_key];
+    for (hash, expected_len) in &[(tx.object_hash(), 1), (panicking_tx.object_hash(), 0)] {
+        let response: CallEventsResponse = api
+            .public(ApiKind::Explorer)
+            .query(&TransactionStatusQuery::new(*hash).with_proof())
+            .get("v1/call_events/transaction")
+            .await
+            .expect("Explorer Api unexpectedly failed");
+        let proof = match response {
+            CallEventsResponse::Proof(proof) => proof,
+            other => panic!("Unexpected response format: {:?}", other),
+        };
+        let (_, events) = proof.verify(&validator_keys).unwrap();
+        assert_eq!(events.len(), *expected_len);
+    }
+}
+
+#[tokio::test]
+async fn test_explorer_service_indexes() {
+    let (mut testkit, api) = init_testkit();
+    let tx = KeyPair::random().increment(SERVICE_ID, 5);
+    testkit.create_block_with_transaction(tx);
+
+    let response: ServiceIndexes = api
+        .public(ApiKind::Explorer)
+        .query(&ServiceIndexesQuery::new(SERVICE_ID))
+        .get("v1/indexes")
+        .await
+        .unwrap();
+    assert_eq!(response.service_id, SERVICE_ID);
+    assert_eq!(response.service_name, "counter");
+    assert_eq!(response.indexes.len(), 1);
+
+    let index = &response.indexes[0];
+    assert_eq!(index.name, "counter.counter");
+    assert_eq!(index.id_in_group, None);
+    assert_eq!(index.index_type, IndexType::ProofEntry);
+    assert!(index.is_proof);
+    assert_eq!(index.object_hash, Some(5_u64.object_hash()));
+    assert_eq!(index.approx_len, 1);
+
+    let err = api
+        .public(ApiKind::Explorer)
+        .query(&ServiceIndexesQuery::new(SERVICE_ID + 1))
+        .get::<ServiceIndexes>("v1/indexes")
+        .await
+        .expect_err("Expected an error for unknown service");
+    assert_eq!(err.http_code, api::HttpStatusCode::NOT_FOUND);
+    assert_eq!(err.body.title, "Service not found");
+}
+
+#[tokio::test]
+async fn test_explorer_simulate_transaction() {
+    let (mut testkit, api) = init_testkit();
+    let keypair = KeyPair::random();
+    testkit.create_block_with_transaction(keypair.increment(SERVICE_ID, 5));
+
+    let tx = keypair.increment(SERVICE_ID, 3);
+    let result = testkit.simulate(&Simulation::signed(&tx).with_diff());
+    assert!(result.status.0.is_ok());
+    assert!(result
+        .touched_indexes
+        .contains(&IndexAddress::from_root("counter.counter")));
+    let diff = result.diff.unwrap();
+    let change = diff["counter.counter"];
+    assert_eq!(change.before, Some(5_u64.object_hash()));
+    assert_eq!(change.after, Some(8_u64.object_hash()));
+
+    // The Rust runtime allows to simulate unsigned transactions.
+    let simulation = Simulation::unsigned(tx.payload().to_owned(), keypair.public_key());
+    let result = testkit.simulate(&simulation);
+    assert!(result.status.0.is_ok());
+    assert!(result.diff.is_none());
+
+    // Failed transactions are simulated as well.
+    let tx = keypair.increment(SERVICE_ID, 0);
+    let result = testkit.simulate(&Simulation::signed(&tx));
+    let err = result.status.0.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 0 });
+
+    // The simulation does not change the blockchain state.
+    let response: ServiceIndexes = api
+        .public(ApiKind::Explorer)
+        .query(&ServiceIndexesQuery::new(SERVICE_ID))
+        .get("v1/indexes")
+        .await
+        .unwrap();
+    assert_eq!(response.indexes[0].object_hash, Some(5_u64.object_hash()));
+
+    // Simulations are available via HTTP API.
+    let tx = keypair.increment(SERVICE_ID, 3);
+    let response: SimulationResponse = api
+        .public(ApiKind::Explorer)
+        .query(&SimulationQuery::signed(&tx).with_diff())
+        .post("v1/transactions/simulate")
+        .await
+        .unwrap();
+    assert_eq!(response.tx_hash, tx.object_hash());
+    assert!(response.status.0.is_ok());
+    let change = response.diff.unwrap()["counter.counter"];
+    assert_eq!(change.before, Some(5_u64.object_hash()));
+    assert_eq!(change.after, Some(8_u64.object_hash()));
+
+    let simulation = SimulationQuery::unsigned(tx.payload(), keypair.public_key()).with_trace();
+    let response: SimulationResponse = api
+        .public(ApiKind::Explorer)
+        .query(&simulation)
+        .post("v1/transactions/simulate")
+        .await
+        .unwrap();
+    assert!(response.status.0.is_ok());
+    assert_eq!(response.trace.unwrap().instance_name, "counter");
+
+    let err = api
+        .public(ApiKind::Explorer)
+        .query(&json!({ "author": keypair.public_key() }))
+        .post::<SimulationResponse>("v1/transactions/simulate")
+        .await
+        .expect_err("Expected an error for incomplete query");
+    assert_eq!(err.http_code, api::HttpStatusCode::BAD_REQUEST);
+    assert_eq!(err.body.title, "Failed to simulate transaction");
+}
+
+#[tokio::test]
+async fn test_explorer_query_service() {
+    let (mut testkit, api) = init_testkit();
+    testkit.create_block_with_transaction(KeyPair::random().increment(SERVICE_ID, 5));
+
+    let result = testkit.query(&Query::new(SERVICE_ID, 0, ().into_bytes()));
+    assert_eq!(u64::from_bytes(result.unwrap().into()).unwrap(), 5);
+    let result = testkit.query(&Query::new(SERVICE_ID, 1, 3_u64.into_bytes()));
+    assert_eq!(u64::from_bytes(result.unwrap().into()).unwrap(), 8);
+
+    let err = testkit
+        .query(&Query::new(SERVICE_ID, 2, vec![]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CommonError::NoSuchMethod).with_any_description()
+    );
+    let err = testkit
+        .query(&Query::new(SERVICE_ID + 1, 0, vec![]))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&CoreError::IncorrectInstanceId).with_any_description()
+    );
+
+    // Asynchronous queries are not available in the testkit.
+    let err = api
+        .public(ApiKind::Explorer)
+        .query(&ServiceQuery::new(SERVICE_ID, 0, ()))
+        .get::<ServiceQueryResponse>("v1/services/query")
+        .await
+        .expect_err("Expected an error for detached blockchain");
+    assert_eq!(err.http_code, api::HttpStatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(err.body.title, "Failed to query service");
+
+    let err = api
+        .public(ApiKind::Explorer)
+        .query(&json!({ "instance_id": SERVICE_ID, "method_id": 0, "arguments": "??" }))
+        .get::<ServiceQueryResponse>("v1/services/query")
+        .await
+        .expect_err("Expected an error for malformed arguments");
+    assert_eq!(err.http_code, api::HttpStatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_explorer_trace_transaction() {
+    let (testkit, api) = init_testkit();
+    let tx = KeyPair::random().increment(SERVICE_ID, 5);
+
+    let result = testkit.simulate(&Simulation::signed(&tx).with_trace());
+    let trace = result.trace.unwrap();
+    assert_eq!(trace.instance_name, "counter");
+    assert!(trace.status.0.is_ok());
+    assert!(trace
+        .writes
+        .iter()
+        .any(|key| key.index == "counter.counter"));
+
+    // Uncommitted transactions cannot be traced.
+    let err = api
+        .private(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.object_hash()))
+        .get::<TransactionTrace>("v1/transactions/trace")
+        .await
+        .expect_err("Expected an error for unknown transaction");
+    assert_eq!(err.http_code, api::HttpStatusCode::NOT_FOUND);
+    assert_eq!(err.body.title, "Failed to trace transaction");
+
+    // Tracing is not available via the public API.
+    let err = api
+        .public(ApiKind::Explorer)
+        .query(&TransactionQuery::new(tx.object_hash()))
+        .get::<TransactionTrace>("v1/transactions/trace")
+        .await
+        .expect_err("Expected an error for public API");
+    assert_eq!(err.http_code, api::HttpStatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_explorer_trace_committed_transaction() {
+    let (mut testkit, api) = init_testkit();
+    let keypair = KeyPair::random();
+    let first_tx = keypair.increment(SERVICE_ID, 5);
+    testkit.create_block_with_transaction(first_tx.clone());
+    let failing_tx = keypair.increment(SERVICE_ID, 0);
+    let block_txs = vec![keypair.increment(SERVICE_ID, 3), failing_tx.clone()];
+    testkit.create_block_with_transactions(block_txs);
+    testkit.create_block_with_transaction(keypair.increment(SERVICE_ID, 10));
+
+    let trace: TransactionTrace = api
+        .private(ApiKind::Explorer)
+        .query(&TransactionQuery::new(failing_tx.object_hash()))
+        .get("v1/transactions/trace")
+        .await
+        .unwrap();
+    assert_eq!(trace.location.block_height(), Height(2));
+    assert_eq!(trace.location.position_in_block(), 1);
+
+    // The transaction is re-executed against the state preceding it in its block,
+    // so the error payload is the same as in the committed status.
+    let committed_err = trace.committed_status.0.unwrap_err();
+    let err = trace.status.0.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Service { code: 0 });
+    let payload = err.payload().unwrap();
+    assert_eq!(payload.decode::<u64>().unwrap(), 8);
+    assert_eq!(Some(payload), committed_err.payload());
+
+    let call = trace.trace.unwrap();
+    assert_eq!(call.instance_name, "counter");
+    assert_eq!(call.arguments, failing_tx.payload().arguments);
+    assert!(call.reads.iter().any(|key| key.index == "counter.counter"));
+    assert!(call.writes.is_empty());
+
+    // Transactions from later blocks are traced on top of the replayed blocks,
+    // and earlier transactions lead to replaying the blockchain anew.
+    let later_tx = keypair.increment(SERVICE_ID, 1);
+    testkit.create_block_with_transaction(later_tx.clone());
+    for tx in &[later_tx, first_tx] {
+        let trace: TransactionTrace = api
+            .private(ApiKind::Explorer)
+            .query(&TransactionQuery::new(tx.object_hash()))
+            .get("v1/transactions/trace")
+            .await
+            .unwrap();
+        assert!(trace.status.0.is_ok());
+        assert!(trace.committed_status.0.is_ok());
+    }
+}
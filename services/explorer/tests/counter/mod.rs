@@ -16,12 +16,12 @@
 use exonum::{
     merkledb::{
         access::{Access, FromAccess, RawAccessMut},
-        ProofEntry,
+        BinaryValue, ProofEntry,
     },
     runtime::{ExecutionContext, ExecutionError, InstanceId},
 };
 use exonum_derive::*;
-use exonum_rust_runtime::{DefaultInstance, Service};
+use exonum_rust_runtime::{DefaultInstance, QueryContext, Service};
 
 pub const SERVICE_NAME: &str = "counter";
 pub const SERVICE_ID: InstanceId = 100;
@@ -57,6 +57,7 @@ where
 // // // // Transactions // // // //
 
 #[derive(Debug, ExecutionFail)]
+#[execution_fail(payload = "u64")]
 pub enum Error {
     /// Adding zero does nothing!
     AddingZero = 0,
@@ -79,28 +80,57 @@ pub trait CounterInterface<Ctx> {
 impl CounterInterface<ExecutionContext<'_>> for CounterService {
     type Output = Result<(), ExecutionError>;
 
-    fn increment(&self, context: ExecutionContext<'_>, by: u64) -> Self::Output {
+    fn increment(&self, mut context: ExecutionContext<'_>, by: u64) -> Self::Output {
         if by == 0 {
-            return Err(Error::AddingZero.into());
+            let schema = CounterSchema::new(context.service_data());
+            let counter = schema.counter.get().unwrap_or_default();
+            return Err(Error::AddingZero.with_payload(counter));
         }
 
+        context.emit_event("increment", by)?;
         let mut schema = CounterSchema::new(context.service_data());
         schema.inc_counter(by);
         Ok(())
     }
 
-    fn reset(&self, context: ExecutionContext<'_>, _: ()) -> Self::Output {
+    fn reset(&self, mut context: ExecutionContext<'_>, _: ()) -> Self::Output {
+        context.emit_event("reset", ())?;
         let mut schema = CounterSchema::new(context.service_data());
         schema.counter.set(0);
         Ok(())
     }
 }
 
+// // // // Queries // // // //
+
+#[exonum_interface(query, auto_ids)]
+pub trait CounterQueries<Ctx> {
+    type Output;
+
+    fn counter(&self, ctx: Ctx, _: ()) -> Self::Output;
+    fn counter_plus(&self, ctx: Ctx, by: u64) -> Self::Output;
+}
+
+impl CounterQueries<QueryContext<'_>> for CounterService {
+    type Output = Result<Vec<u8>, ExecutionError>;
+
+    fn counter(&self, context: QueryContext<'_>, _: ()) -> Self::Output {
+        let schema = CounterSchema::new(context.service_data());
+        Ok(schema.counter.get().unwrap_or_default().into_bytes())
+    }
+
+    fn counter_plus(&self, context: QueryContext<'_>, by: u64) -> Self::Output {
+        let schema = CounterSchema::new(context.service_data());
+        let count = schema.counter.get().unwrap_or_default();
+        Ok(count.saturating_add(by).into_bytes())
+    }
+}
+
 // // // // Service // // // //
 
 #[derive(Debug, ServiceDispatcher, ServiceFactory)]
 #[service_factory(artifact_name = "counter-service", artifact_version = "1.0.0")]
-#[service_dispatcher(implements("CounterInterface"))]
+#[service_dispatcher(implements("CounterInterface"), queries = "CounterQueries")]
 pub struct CounterService;
 
 impl DefaultInstance for CounterService {
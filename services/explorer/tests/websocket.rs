@@ -17,7 +17,7 @@
 use actix_web_actors::ws::CloseCode;
 use assert_matches::assert_matches;
 use exonum::{
-    crypto::KeyPair, helpers::Height, merkledb::ObjectHash,
+    blockchain::CallInBlock, crypto::KeyPair, helpers::Height, merkledb::ObjectHash,
     runtime::SUPERVISOR_INSTANCE_ID as SUPERVISOR_ID,
 };
 use exonum_explorer::api::websocket::Notification;
@@ -217,6 +217,33 @@ fn test_transactions_subscription_with_filter() {
     assert_no_message(&mut client);
 }
 
+#[test]
+fn test_events_subscription_with_filter() {
+    let (mut testkit, api) = init_testkit();
+    let url = format!(
+        "api/explorer/v1/events/subscribe?instance_id={}&topic=reset",
+        SERVICE_ID
+    );
+    let url = api.public_url(&url);
+    let mut client = create_ws_client(&url);
+
+    let alice = KeyPair::random();
+    let inc_tx = alice.increment(SERVICE_ID, 3);
+    let reset_tx = alice.reset(SERVICE_ID, ());
+    testkit.create_block_with_transactions(vec![inc_tx, reset_tx]);
+
+    let notification: Notification = receive_message(&mut client).unwrap();
+    let event_summary = match notification {
+        Notification::Event(summary) => summary,
+        notification => panic!("Unexpected notification: {:?}", notification),
+    };
+    assert_eq!(event_summary.height, Height(1));
+    assert_eq!(event_summary.location, CallInBlock::transaction(1));
+    assert_eq!(event_summary.event.instance_id, SERVICE_ID);
+    assert_eq!(event_summary.event.topic, "reset");
+    assert_no_message(&mut client);
+}
+
 #[test]
 fn test_transactions_subscribe_with_partial_filter() {
     let (mut testkit, api) = init_testkit();
@@ -618,7 +618,7 @@ use exonum_rust_runtime::{
 };
 use serde_derive::{Deserialize, Serialize};
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, str::FromStr};
 
 use super::{
     schema::SchemaImpl, transactions::SupervisorInterface, AsyncEventState, ConfigProposalWithHash,
@@ -642,6 +642,43 @@ pub struct DeployInfoQuery {
     /// Seed to distinguish among deploys with the same params.
     #[serde(default)]
     pub seed: u64,
+    /// Artifact dependencies in the `name@version` form separated by semicolons,
+    /// e.g., `exonum.Token@^1.3.0;exonum.Oracle@>=0.2.0, <0.4.0`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub dependencies: String,
+    /// Interfaces required by the artifact in the `name@version` form separated by semicolons,
+    /// e.g., `Token@^1.3.0;Oracle@>=0.2.0, <0.4.0`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub required_interfaces: String,
+    /// Interfaces provided by the artifact in the `name@version` form separated by semicolons,
+    /// e.g., `Token@1.4.0;Oracle@0.3.1`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub provided_interfaces: String,
+}
+
+/// Parses a list of semicolon-separated items from a query parameter.
+fn parse_list<T>(list: &str) -> Result<Vec<T>, api::Error>
+where
+    T: FromStr<Err = anyhow::Error>,
+{
+    list.split(';')
+        .filter(|item| !item.is_empty())
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .map_err(|err| {
+            api::Error::bad_request()
+                .title("Invalid deploy request query")
+                .detail(err.to_string())
+        })
+}
+
+/// Joins items into a semicolon-separated list suitable for a query parameter.
+fn join_list<T: ToString>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(";")
 }
 
 impl TryFrom<DeployInfoQuery> for DeployRequest {
@@ -659,12 +696,18 @@ impl TryFrom<DeployInfoQuery> for DeployRequest {
                 .detail(err.to_string())
         })?;
         let deadline_height = Height(query.deadline_height);
+        let dependencies = parse_list(&query.dependencies)?;
+        let required_interfaces = parse_list(&query.required_interfaces)?;
+        let provided_interfaces = parse_list(&query.provided_interfaces)?;
 
         let request = Self {
             artifact,
             spec,
             deadline_height,
             seed: query.seed,
+            dependencies,
+            required_interfaces,
+            provided_interfaces,
         };
 
         Ok(request)
@@ -676,12 +719,14 @@ impl From<DeployRequest> for DeployInfoQuery {
         let artifact = request.artifact.to_string();
         let spec = base64::encode_config(&request.spec, base64::URL_SAFE_NO_PAD);
         let deadline_height = request.deadline_height.0;
-
         Self {
             artifact,
             spec,
             deadline_height,
             seed: request.seed,
+            dependencies: join_list(&request.dependencies),
+            required_interfaces: join_list(&request.required_interfaces),
+            provided_interfaces: join_list(&request.provided_interfaces),
         }
     }
 }
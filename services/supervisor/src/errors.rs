@@ -45,6 +45,12 @@ pub enum ArtifactError {
     DeployRequestNotRegistered = 19,
     /// Start request contains unknown artifact.
     UnknownArtifact = 20,
+    /// Artifact dependency is not matched by any active artifact.
+    UnsatisfiedDependency = 21,
+    /// Artifact cannot be unloaded because other artifacts depend on it.
+    HasDependents = 22,
+    /// Interface required by the artifact is not provided by any active artifact.
+    UnsatisfiedInterfaceRequirement = 23,
 }
 
 /// Instance-related errors group.
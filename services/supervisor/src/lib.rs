@@ -153,7 +153,8 @@ pub use self::{
     proto_structures::{
         ConfigChange, ConfigProposalWithHash, ConfigPropose, ConfigVote, DeployRequest,
         DeployResult, FreezeService, MigrationRequest, MigrationResult, ResumeService,
-        ServiceConfig, StartService, StopService, SupervisorConfig, UnloadArtifact,
+        RotateConsensusKey, ServiceConfig, StartService, StopService, SupervisorConfig,
+        UnloadArtifact,
     },
     schema::Schema,
     transactions::SupervisorInterface,
@@ -309,6 +310,25 @@ fn update_configs(
                     .supervisor_extensions()
                     .unload_artifact(&unload_artifact.artifact_id)?;
             }
+
+            ConfigChange::RotateConsensusKey(rotation) => {
+                log::trace!(
+                    "Rotating consensus key {} to {}",
+                    rotation.old_key,
+                    rotation.new_key
+                );
+
+                let core_schema = context.supervisor_extensions().writeable_core_schema();
+                let mut config = core_schema.consensus_config();
+                // The rotation was verified, so the validator is guaranteed to exist.
+                let validator_keys = config
+                    .validator_keys
+                    .iter_mut()
+                    .find(|keys| keys.consensus_key == rotation.old_key)
+                    .expect("Validator with the rotated key is absent");
+                validator_keys.consensus_key = rotation.new_key;
+                core_schema.consensus_config_entry().set(config);
+            }
         }
     }
     Ok(())
@@ -14,10 +14,13 @@
 
 use exonum::{
     blockchain::ConsensusConfig,
-    crypto::Hash,
+    crypto::{Hash, PublicKey},
     helpers::Height,
     merkledb::{impl_binary_key_for_binary_value, BinaryValue, ObjectHash},
-    runtime::{ArtifactId, ExecutionStatus, InstanceId, InstanceSpec, MigrationStatus},
+    runtime::{
+        versioning::{ArtifactReq, InterfaceReq, ProvidedInterface},
+        ArtifactId, ExecutionStatus, InstanceId, InstanceSpec, MigrationStatus,
+    },
 };
 use exonum_derive::{BinaryValue, ObjectHash};
 use exonum_proto::{ProtobufBase64, ProtobufConvert};
@@ -63,6 +66,20 @@ pub struct DeployRequest {
     /// Seed to allow several deployments with the same params.
     #[serde(default)]
     pub seed: u64,
+
+    /// Requirements for other artifacts, which must be active when the artifact
+    /// is deployed and for as long as it stays active.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<ArtifactReq>,
+
+    /// Requirements for interfaces, which must be provided by active artifacts
+    /// when the artifact is deployed and for as long as it stays active.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_interfaces: Vec<InterfaceReq>,
+
+    /// Interfaces provided by the artifact.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provided_interfaces: Vec<ProvidedInterface>,
 }
 
 impl DeployRequest {
@@ -73,6 +90,9 @@ impl DeployRequest {
             deadline_height,
             spec: Vec::new(),
             seed: 0,
+            dependencies: Vec::new(),
+            required_interfaces: Vec::new(),
+            provided_interfaces: Vec::new(),
         }
     }
 
@@ -81,6 +101,33 @@ impl DeployRequest {
         self.spec = spec;
         self
     }
+
+    /// Adds dependencies of the artifact to this request.
+    pub fn with_dependencies(
+        mut self,
+        dependencies: impl IntoIterator<Item = ArtifactReq>,
+    ) -> Self {
+        self.dependencies.extend(dependencies);
+        self
+    }
+
+    /// Adds interfaces required by the artifact to this request.
+    pub fn with_required_interfaces(
+        mut self,
+        interfaces: impl IntoIterator<Item = InterfaceReq>,
+    ) -> Self {
+        self.required_interfaces.extend(interfaces);
+        self
+    }
+
+    /// Adds interfaces provided by the artifact to this request.
+    pub fn with_provided_interfaces(
+        mut self,
+        interfaces: impl IntoIterator<Item = ProvidedInterface>,
+    ) -> Self {
+        self.provided_interfaces.extend(interfaces);
+        self
+    }
 }
 
 /// Confirmation that artifact deployment has ended for a validator.
@@ -183,6 +230,22 @@ pub struct UnloadArtifact {
     pub artifact_id: ArtifactId,
 }
 
+/// Request to rotate the consensus key of a validator.
+///
+/// The validator is identified by its current consensus key; the service key
+/// of the validator is left intact. The node should have the new key staged
+/// beforehand in order to continue participating in consensus.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(ProtobufConvert, BinaryValue, ObjectHash, Serialize, Deserialize)]
+#[protobuf_convert(source = "proto::RotateConsensusKey")]
+#[non_exhaustive]
+pub struct RotateConsensusKey {
+    /// Current consensus key of the validator.
+    pub old_key: PublicKey,
+    /// New consensus key of the validator.
+    pub new_key: PublicKey,
+}
+
 /// Configuration parameters of the certain service instance.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[derive(ProtobufConvert, BinaryValue, ObjectHash, Serialize, Deserialize)]
@@ -229,6 +292,8 @@ pub enum ConfigChange {
     FreezeService(FreezeService),
     /// Request to unload an unused artifact.
     UnloadArtifact(UnloadArtifact),
+    /// Request to rotate the consensus key of a validator.
+    RotateConsensusKey(RotateConsensusKey),
 }
 
 /// Request for the configuration change
@@ -322,6 +387,16 @@ impl ConfigPropose {
             .push(ConfigChange::UnloadArtifact(UnloadArtifact { artifact_id }));
         self
     }
+
+    /// Adds a request to rotate the consensus key of a validator to this proposal.
+    pub fn rotate_consensus_key(mut self, old_key: PublicKey, new_key: PublicKey) -> Self {
+        self.changes
+            .push(ConfigChange::RotateConsensusKey(RotateConsensusKey {
+                old_key,
+                new_key,
+            }));
+        self
+    }
 }
 
 /// Confirmation vote for the configuration change.
@@ -18,8 +18,8 @@ use exonum::{
 };
 use exonum_derive::*;
 use exonum_merkledb::{
-    access::{Access, FromAccess, Prefixed},
-    Entry, Fork, ProofEntry, ProofMapIndex, ValueSetIndex,
+    access::{Access, FromAccess, RawAccessMut},
+    Entry, ProofEntry, ProofMapIndex, ValueSetIndex,
 };
 
 use super::{
@@ -108,7 +108,11 @@ impl<T: Access> SchemaImpl<T> {
     }
 }
 
-impl SchemaImpl<Prefixed<&Fork>> {
+impl<T> SchemaImpl<T>
+where
+    T: Access,
+    T::Base: RawAccessMut,
+{
     /// Increases the stored configuration number.
     pub fn increase_configuration_number(&mut self) {
         let new_configuration_number = self.get_configuration_number() + 1;
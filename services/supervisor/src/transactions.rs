@@ -16,7 +16,9 @@ use exonum::{
     crypto::{Hash, PublicKey},
     helpers::{Height, ValidateInput},
     runtime::{
-        migrations::MigrationType, CommonError, ExecutionContext, ExecutionError, ExecutionFail,
+        migrations::MigrationType,
+        versioning::{ArtifactReq, InterfaceReq},
+        ArtifactId, ArtifactSpec, CommonError, ExecutionContext, ExecutionError, ExecutionFail,
         InstanceId, InstanceSpec, InstanceState, InstanceStatus, RuntimeFeature,
     },
 };
@@ -29,8 +31,8 @@ use super::{
     configure::ConfigureMut, migration_state::MigrationState, ArtifactError, AsyncEventState,
     CommonError as SupervisorCommonError, ConfigChange, ConfigProposalWithHash, ConfigPropose,
     ConfigVote, ConfigurationError, DeployRequest, DeployResult, FreezeService, MigrationError,
-    MigrationRequest, MigrationResult, ResumeService, SchemaImpl, ServiceError, StartService,
-    StopService, Supervisor, UnloadArtifact,
+    MigrationRequest, MigrationResult, ResumeService, RotateConsensusKey, SchemaImpl, ServiceError,
+    StartService, StopService, Supervisor, UnloadArtifact,
 };
 use exonum::runtime::ArtifactStatus;
 
@@ -150,6 +152,12 @@ impl StartService {
             );
             return Err(ArtifactError::UnknownArtifact.with_description(msg));
         }
+        check_dependencies(
+            context,
+            &self.artifact,
+            &artifact_state.dependencies,
+            &artifact_state.required_interfaces,
+        )?;
 
         // Check that there is no instance with the same name.
         if dispatcher_data.get_instance(self.name.as_str()).is_some() {
@@ -216,16 +224,82 @@ impl ResumeService {
     }
 }
 
+impl RotateConsensusKey {
+    fn validate(&self, context: &ExecutionContext<'_>) -> Result<(), ExecutionError> {
+        let config = context.data().for_core().consensus_config();
+        if config
+            .find_validator(|keys| keys.consensus_key == self.old_key)
+            .is_none()
+        {
+            let msg = format!(
+                "Discarded rotation of consensus key {}: no validator uses this key",
+                self.old_key
+            );
+            return Err(ConfigurationError::malformed_propose(msg));
+        }
+
+        let new_key = self.new_key;
+        if config
+            .find_validator(|keys| keys.consensus_key == new_key || keys.service_key == new_key)
+            .is_some()
+        {
+            let msg = format!(
+                "Discarded rotation of consensus key {}: new key {} is already used \
+                 by a validator",
+                self.old_key, new_key
+            );
+            return Err(ConfigurationError::malformed_propose(msg));
+        }
+        Ok(())
+    }
+}
+
 impl UnloadArtifact {
     fn validate(&self, context: &ExecutionContext<'_>) -> Result<(), ExecutionError> {
-        context
-            .data()
-            .for_dispatcher()
+        let dispatcher_data = context.data().for_dispatcher();
+        if let Some(dependent) = dispatcher_data
+            .dependent_artifacts(&self.artifact_id)
+            .first()
+        {
+            let msg = format!(
+                "Discarded unload of artifact `{}`: artifact `{}` depends on it",
+                self.artifact_id, dependent
+            );
+            return Err(ArtifactError::HasDependents.with_description(msg));
+        }
+
+        dispatcher_data
             .check_unloading_artifact(&self.artifact_id)
             .map_err(|e| ConfigurationError::malformed_propose(e.description()))
     }
 }
 
+/// Checks that all dependencies of the artifact are matched by active artifacts,
+/// and that all interfaces required by the artifact are provided by active artifacts.
+fn check_dependencies(
+    context: &ExecutionContext<'_>,
+    artifact: &ArtifactId,
+    dependencies: &[ArtifactReq],
+    required_interfaces: &[InterfaceReq],
+) -> Result<(), ExecutionError> {
+    let dispatcher_data = context.data().for_dispatcher();
+    if let Some(dependency) = dispatcher_data.unsatisfied_dependency(dependencies) {
+        let msg = format!(
+            "Dependency `{}` of artifact `{}` is not matched by any active artifact",
+            dependency, artifact
+        );
+        return Err(ArtifactError::UnsatisfiedDependency.with_description(msg));
+    }
+    if let Some(interface) = dispatcher_data.unsatisfied_interface(required_interfaces) {
+        let msg = format!(
+            "Interface `{}` required by artifact `{}` is not provided by any active artifact",
+            interface, artifact
+        );
+        return Err(ArtifactError::UnsatisfiedInterfaceRequirement.with_description(msg));
+    }
+    Ok(())
+}
+
 /// Checks if method was called by transaction, and transaction author is a validator.
 fn get_validator(context: &ExecutionContext<'_>) -> Result<PublicKey, ExecutionError> {
     let author = context
@@ -452,6 +526,13 @@ impl SupervisorInterface<ExecutionContext<'_>> for Supervisor {
             let msg = format!("Artifact `{}` is already deployed", deploy.artifact);
             return Err(ArtifactError::AlreadyDeployed.with_description(msg));
         }
+        // Verify that the artifact dependencies are active.
+        check_dependencies(
+            &context,
+            &deploy.artifact,
+            &deploy.dependencies,
+            &deploy.required_interfaces,
+        )?;
 
         // If deployment is already registered, check whether the request is new.
         if schema.pending_deployments.contains(&deploy.artifact) {
@@ -699,6 +780,8 @@ impl Supervisor {
         // To prevent starting services with an unloaded artifact.
         let mut artifacts_for_started_services = HashSet::new();
         let mut unloaded_artifacts = HashSet::new();
+        // To prevent several rotations concerning the same key.
+        let mut rotated_keys = HashSet::new();
 
         // Perform config verification.
         for change in changes {
@@ -766,9 +849,28 @@ impl Supervisor {
                     }
                     unload_artifact.validate(context)?;
                 }
+
+                ConfigChange::RotateConsensusKey(rotation) => {
+                    for key in &[rotation.old_key, rotation.new_key] {
+                        if !rotated_keys.insert(*key) {
+                            let msg = format!(
+                                "Discarded multiple rotations concerning consensus key {}",
+                                key
+                            );
+                            return Err(ConfigurationError::malformed_propose(msg));
+                        }
+                    }
+                    rotation.validate(context)?;
+                }
             }
         }
 
+        if consensus_propose_added && !rotated_keys.is_empty() {
+            let msg = "Discarded proposal which both changes consensus config \
+                       and rotates consensus keys";
+            return Err(ConfigurationError::malformed_propose(msg));
+        }
+
         let mut intersection = unloaded_artifacts.intersection(&artifacts_for_started_services);
         if let Some(&artifact) = intersection.next() {
             let msg = format!(
@@ -800,21 +902,40 @@ impl Supervisor {
             .deploy_confirmations
             .intersect_with_validators(&deploy_request, validator_keys)
         {
+            drop(schema);
+            // Dependencies of the artifact could have been unloaded since the deploy request
+            // was accepted; in this case, the deployment fails.
+            let dependencies_check = check_dependencies(
+                &context,
+                &deploy_request.artifact,
+                &deploy_request.dependencies,
+                &deploy_request.required_interfaces,
+            );
+            if let Err(error) = dependencies_check {
+                Self::fail_deploy(&context, &deploy_request, error);
+                return Ok(());
+            }
+
             log::trace!(
                 "Registering deployed artifact in dispatcher {:?}",
                 deploy_request.artifact
             );
 
             // Remove artifact from pending deployments.
+            let mut schema = SchemaImpl::new(context.service_data());
             schema
                 .deploy_states
                 .put(&deploy_request, AsyncEventState::Succeed);
             drop(schema);
             // We have enough confirmations to register the deployed artifact in the dispatcher;
             // if this action fails, this transaction will be canceled.
+            let spec = ArtifactSpec::new(deploy_request.artifact, deploy_request.spec)
+                .with_dependencies(deploy_request.dependencies)
+                .with_required_interfaces(deploy_request.required_interfaces)
+                .with_provided_interfaces(deploy_request.provided_interfaces);
             context
                 .supervisor_extensions()
-                .start_artifact_registration(&deploy_request.artifact, deploy_request.spec);
+                .start_artifact_registration(spec);
         }
         Ok(())
     }
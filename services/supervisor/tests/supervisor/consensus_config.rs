@@ -12,11 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use exonum::{helpers::ValidatorId, merkledb::ObjectHash};
+use exonum::{
+    crypto::KeyPair,
+    helpers::ValidatorId,
+    merkledb::ObjectHash,
+    runtime::{ErrorMatch, SUPERVISOR_INSTANCE_ID},
+};
 use exonum_testkit::TestKitBuilder;
 
 use crate::utils::*;
-use exonum_supervisor::Supervisor;
+use exonum_supervisor::{ConfigPropose, ConfigurationError, Supervisor};
 
 #[test]
 fn test_add_nodes_to_validators() {
@@ -152,3 +157,79 @@ fn test_change_our_validator_id() {
     assert_eq!(&testkit.network().validators()[1], testkit.network().us());
     assert_eq!(testkit.consensus_config(), new_consensus_config);
 }
+
+#[test]
+fn test_rotate_consensus_key() {
+    let mut testkit = testkit_with_supervisor(4);
+    let initiator_id = testkit.network().us().validator_id().unwrap();
+
+    let old_key = testkit.consensus_config().validator_keys[1].consensus_key;
+    let new_key = testkit.network_mut().add_node().public_keys().consensus_key;
+    let expected_config = {
+        let mut cfg = testkit.consensus_config();
+        cfg.validator_keys[1].consensus_key = new_key;
+        cfg
+    };
+
+    let config_proposal =
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT).rotate_consensus_key(old_key, new_key);
+    let proposal_hash = config_proposal.object_hash();
+    let block = testkit.create_block_with_transaction(sign_config_propose_transaction(
+        &testkit,
+        config_proposal,
+        initiator_id,
+    ));
+    block.transactions[0].status().unwrap();
+
+    let signed_txs = build_confirmation_transactions(&testkit, proposal_hash, initiator_id);
+    testkit.create_block_with_transactions(signed_txs);
+
+    testkit.create_blocks_until(CFG_CHANGE_HEIGHT);
+
+    assert_eq!(config_propose_entry(&testkit), None);
+    assert_eq!(testkit.consensus_config(), expected_config);
+    assert_eq!(
+        testkit.network().consensus_public_key_of(ValidatorId(1)),
+        Some(new_key)
+    );
+    assert_eq!(testkit.network().us().validator_id(), Some(initiator_id));
+}
+
+#[test]
+fn test_rotate_consensus_key_malformed() {
+    let mut testkit = testkit_with_supervisor(4);
+    let config = testkit.consensus_config();
+    let old_key = config.validator_keys[1].consensus_key;
+    let new_key = KeyPair::random().public_key();
+
+    let proposals = vec![
+        // Key not used by any validator.
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT).rotate_consensus_key(new_key, old_key),
+        // New key is already used by another validator.
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+            .rotate_consensus_key(old_key, config.validator_keys[2].consensus_key),
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+            .rotate_consensus_key(old_key, config.validator_keys[2].service_key),
+        // Several rotations concerning the same key.
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+            .rotate_consensus_key(old_key, new_key)
+            .rotate_consensus_key(config.validator_keys[2].consensus_key, new_key),
+        // Rotation together with consensus config change.
+        ConfigPropose::new(0, CFG_CHANGE_HEIGHT)
+            .rotate_consensus_key(old_key, new_key)
+            .consensus_config(consensus_config_propose_first_variant(&testkit)),
+    ];
+
+    for proposal in proposals {
+        let signed_proposal = sign_config_propose_transaction(&testkit, proposal, ValidatorId(0));
+        let block = testkit.create_block_with_transaction(signed_proposal);
+        let err = block.transactions[0].status().unwrap_err();
+        assert_eq!(
+            *err,
+            ErrorMatch::from_fail(&ConfigurationError::MalformedConfigPropose)
+                .for_service(SUPERVISOR_INSTANCE_ID)
+                .with_any_description()
+        );
+        assert_eq!(config_propose_entry(&testkit), None);
+    }
+}
@@ -70,7 +70,11 @@ pub trait IncInterface<Ctx> {
 /// Basically, it just counts how many time a transaction was received.
 #[derive(Clone, Default, Debug, ServiceFactory, ServiceDispatcher)]
 #[service_dispatcher(implements("IncInterface", raw = "Configure<Params = String>"))]
-#[service_factory(artifact_name = "inc", artifact_version = "1.0.0")]
+#[service_factory(
+    artifact_name = "inc",
+    artifact_version = "1.0.0",
+    provides_interface = "IncInterface@1.0.0"
+)]
 pub struct IncService;
 
 impl IncInterface<ExecutionContext<'_>> for IncService {
@@ -22,9 +22,9 @@ use exonum::{
     runtime::{
         migrations::{InitMigrationError, MigrationScript},
         oneshot::Receiver,
-        versioning::Version,
-        ArtifactId, ErrorMatch, ExecutionError, InstanceState, InstanceStatus, Mailbox, Runtime,
-        SnapshotExt, WellKnownRuntime, SUPERVISOR_INSTANCE_ID,
+        versioning::{ArtifactReq, InterfaceReq, Version},
+        ArtifactId, ArtifactStatus, ErrorMatch, ExecutionError, InstanceState, InstanceStatus,
+        Mailbox, Runtime, SnapshotExt, WellKnownRuntime, SUPERVISOR_INSTANCE_ID,
     },
 };
 use exonum_rust_runtime::{
@@ -35,7 +35,8 @@ use exonum_testkit::{ApiKind, TestKit, TestKitBuilder};
 
 use crate::inc::IncService;
 use exonum_supervisor::{
-    ArtifactError, ConfigPropose, ConfigurationError, Supervisor, SupervisorInterface,
+    ArtifactError, ConfigPropose, ConfigurationError, DeployRequest, Supervisor,
+    SupervisorInterface,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -146,6 +147,18 @@ fn create_testkit_with_additional_runtime() -> TestKit {
         .build()
 }
 
+/// Creates a testkit with an artifact of the additional runtime depending on `IncService`.
+fn create_testkit_with_dependent_artifact() -> TestKit {
+    let dependency: ArtifactReq = "inc@^1.0".parse().unwrap();
+    let artifact = RuntimeWithoutFreeze::artifact();
+    TestKitBuilder::validator()
+        .with_additional_runtime(RuntimeWithoutFreeze)
+        .with(ForeignSpec::new(artifact).with_dependencies(vec![dependency]))
+        .with(Supervisor::simple())
+        .with(Spec::new(IncService))
+        .build()
+}
+
 /// Starts service instance and gets its ID
 fn start_inc_service(testkit: &mut TestKit) -> InstanceState {
     // Start `inc` service instance
@@ -477,3 +490,111 @@ fn freeze_without_runtime_support() {
             .with_description_containing("Cannot freeze service `100:test`")
     );
 }
+
+#[test]
+fn deploy_artifact_with_dependencies() {
+    let mut testkit = create_testkit_with_dependent_artifact();
+    let keypair = testkit.us().service_keypair();
+    let artifact = ArtifactId::from_raw_parts(
+        RuntimeWithoutFreeze::ID,
+        "dependent".to_owned(),
+        Version::new(1, 0, 0),
+    );
+
+    // The artifact requires a version of `IncService` which is not deployed.
+    let request = DeployRequest::new(artifact.clone(), Height(5))
+        .with_dependencies(vec!["inc@^2.0".parse().unwrap()]);
+    let tx = keypair.request_artifact_deploy(SUPERVISOR_INSTANCE_ID, request);
+    let err = execute_transaction(&mut testkit, tx).unwrap_err();
+    let expected_msg =
+        "Dependency `inc@^2.0` of artifact `5:dependent:1.0.0` is not matched by any active artifact";
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&ArtifactError::UnsatisfiedDependency)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+            .with_description_containing(expected_msg)
+    );
+
+    let dependency: ArtifactReq = "inc@^1.0".parse().unwrap();
+    let request =
+        DeployRequest::new(artifact.clone(), Height(5)).with_dependencies(vec![dependency.clone()]);
+    let tx = keypair.request_artifact_deploy(SUPERVISOR_INSTANCE_ID, request);
+    execute_transaction(&mut testkit, tx).expect("Cannot request artifact deploy");
+    testkit.create_blocks_until(Height(5));
+
+    let snapshot = testkit.snapshot();
+    let state = snapshot.for_dispatcher().get_artifact(&artifact).unwrap();
+    assert_eq!(state.status, ArtifactStatus::Active);
+    assert_eq!(state.dependencies, vec![dependency]);
+}
+
+#[test]
+fn deploy_artifact_with_required_interfaces() {
+    let mut testkit = create_testkit_with_dependent_artifact();
+    let keypair = testkit.us().service_keypair();
+    let artifact = ArtifactId::from_raw_parts(
+        RuntimeWithoutFreeze::ID,
+        "dependent".to_owned(),
+        Version::new(1, 0, 0),
+    );
+
+    // `IncService` provides `IncInterface@1.0.0`, which does not match the requirement.
+    let request = DeployRequest::new(artifact.clone(), Height(5))
+        .with_required_interfaces(vec!["IncInterface@^2.0".parse().unwrap()]);
+    let tx = keypair.request_artifact_deploy(SUPERVISOR_INSTANCE_ID, request);
+    let err = execute_transaction(&mut testkit, tx).unwrap_err();
+    let expected_msg = "Interface `IncInterface@^2.0` required by artifact `5:dependent:1.0.0` \
+                        is not provided by any active artifact";
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&ArtifactError::UnsatisfiedInterfaceRequirement)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+            .with_description_containing(expected_msg)
+    );
+
+    let requirement: InterfaceReq = "IncInterface@^1.0".parse().unwrap();
+    let request = DeployRequest::new(artifact.clone(), Height(5))
+        .with_required_interfaces(vec![requirement.clone()]);
+    let tx = keypair.request_artifact_deploy(SUPERVISOR_INSTANCE_ID, request);
+    execute_transaction(&mut testkit, tx).expect("Cannot request artifact deploy");
+    testkit.create_blocks_until(Height(5));
+
+    let snapshot = testkit.snapshot();
+    let state = snapshot.for_dispatcher().get_artifact(&artifact).unwrap();
+    assert_eq!(state.status, ArtifactStatus::Active);
+    assert_eq!(state.required_interfaces, vec![requirement]);
+}
+
+#[test]
+fn unloading_artifact_with_dependents() {
+    let mut testkit = create_testkit_with_dependent_artifact();
+    let keypair = testkit.us().service_keypair();
+
+    let change = ConfigPropose::immediate(0).unload_artifact(IncService.artifact_id());
+    let change = keypair.propose_config_change(SUPERVISOR_INSTANCE_ID, change);
+    let err = execute_transaction(&mut testkit, change).unwrap_err();
+    let expected_msg =
+        "Discarded unload of artifact `0:inc:1.0.0`: artifact `5:some-service:1.0.0` depends on it";
+    assert_eq!(
+        err,
+        ErrorMatch::from_fail(&ArtifactError::HasDependents)
+            .for_service(SUPERVISOR_INSTANCE_ID)
+            .with_description_containing(expected_msg)
+    );
+
+    // Once the dependent artifact is unloaded, the dependency can be unloaded as well.
+    let change = ConfigPropose::immediate(0).unload_artifact(RuntimeWithoutFreeze::artifact());
+    let change = keypair.propose_config_change(SUPERVISOR_INSTANCE_ID, change);
+    execute_transaction(&mut testkit, change).expect("Cannot unload dependent artifact");
+
+    let change = ConfigPropose::immediate(1).unload_artifact(IncService.artifact_id());
+    let change = keypair.propose_config_change(SUPERVISOR_INSTANCE_ID, change);
+    execute_transaction(&mut testkit, change).expect("Cannot unload artifact");
+
+    let snapshot = testkit.snapshot();
+    let dispatcher = snapshot.for_dispatcher();
+    assert!(dispatcher.get_artifact(&IncService.artifact_id()).is_none());
+    assert!(dispatcher
+        .get_artifact(&RuntimeWithoutFreeze::artifact())
+        .is_none());
+}
@@ -122,13 +122,14 @@ pub use exonum_rust_runtime::spec::Spec;
 use exonum::{
     blockchain::{
         config::GenesisConfig, ApiSender, BlockParams, Blockchain, BlockchainBuilder,
-        BlockchainMut, ConsensusConfig,
+        BlockchainMut, ConsensusConfig, Query, Simulation, SimulationRequest, SimulationResult,
+        Simulator,
     },
     crypto::{self, Hash},
     helpers::{byzantine_quorum, Height, ValidatorId},
     merkledb::{BinaryValue, Database, ObjectHash, Snapshot, TemporaryDB},
     messages::{AnyTx, Verified},
-    runtime::{InstanceId, RuntimeInstance, SnapshotExt},
+    runtime::{ExecutionError, InstanceId, RuntimeInstance, SnapshotExt},
 };
 use exonum_api::{
     ApiAccess, ApiAggregator, ApiManager, ApiManagerConfig, UpdateEndpoints, WebServerConfig,
@@ -136,7 +137,8 @@ use exonum_api::{
 use exonum_explorer::{BlockWithTransactions, BlockchainExplorer};
 use exonum_rust_runtime::{RustRuntimeBuilder, ServiceFactory};
 use futures::{
-    channel::mpsc,
+    channel::{mpsc, oneshot},
+    executor, future,
     prelude::*,
     stream::{self, BoxStream},
     StreamExt,
@@ -150,6 +152,7 @@ use std::{
     fmt, iter, mem,
     net::SocketAddr,
     sync::{Arc, Mutex},
+    thread,
 };
 
 use crate::{
@@ -207,6 +210,8 @@ pub struct TestKit {
     processing_lock: Arc<Mutex<()>>,
     network: TestNetwork,
     api_sender: ApiSender,
+    // Dropping the sender stops the thread processing simulation requests.
+    _simulations_stop: oneshot::Sender<()>,
     api_notifier_channel: ApiNotifierChannel,
     api_aggregator: ApiAggregator,
     #[cfg(feature = "exonum-node")]
@@ -251,11 +256,13 @@ impl TestKit {
         let db = database.into();
         let db_handler = db.handler();
         let db = Arc::new(db);
+        let simulations_channel = mpsc::channel(1_000);
         let blockchain = Blockchain::new(
             Arc::clone(&db) as Arc<dyn Database>,
             network.us().service_keypair(),
             api_sender.clone(),
-        );
+        )
+        .with_simulation_sender(ApiSender::new(simulations_channel.0));
 
         let mut builder = BlockchainBuilder::new(blockchain);
         if let Some(genesis_config) = genesis_config {
@@ -265,6 +272,8 @@ impl TestKit {
             builder = builder.with_runtime(runtime);
         }
         let blockchain = builder.build();
+        let simulations_stop =
+            Self::spawn_simulation_thread(blockchain.simulator(), simulations_channel.1);
 
         let processing_lock = Arc::new(Mutex::new(()));
         let processing_lock_ = Arc::clone(&processing_lock);
@@ -287,6 +296,7 @@ impl TestKit {
             blockchain,
             db_handler,
             api_sender,
+            _simulations_stop: simulations_stop,
             events_stream: events_stream.boxed(),
             processing_lock,
             network,
@@ -299,6 +309,24 @@ impl TestKit {
         }
     }
 
+    /// Spawns a thread processing simulation requests, like a real node does. The thread
+    /// is stopped once the returned sender is dropped; the request stream itself never
+    /// terminates, since the services shared with the simulator hold request senders.
+    fn spawn_simulation_thread(
+        simulator: Simulator,
+        requests: mpsc::Receiver<SimulationRequest>,
+    ) -> oneshot::Sender<()> {
+        let (stop_tx, stop_rx) = oneshot::channel();
+        thread::spawn(move || {
+            let processing = requests.for_each(|request| {
+                request.process(&simulator);
+                future::ready(())
+            });
+            executor::block_on(future::select(processing, stop_rx));
+        });
+        stop_tx
+    }
+
     /// Needs to be called immediately after node creation.
     #[cfg(feature = "exonum-node")]
     pub(crate) fn set_plugins(&mut self, plugins: Vec<Box<dyn NodePlugin>>) {
@@ -378,6 +406,28 @@ impl TestKit {
         self.blockchain.as_ref().to_owned()
     }
 
+    /// Simulates execution of a transaction against the current blockchain state without
+    /// committing its results.
+    ///
+    /// Asynchronous simulation via [`Blockchain::simulate`] (and thus the corresponding
+    /// explorer endpoint) is available as well; such simulations are processed in a separate
+    /// thread, like in a real node.
+    ///
+    /// [`Blockchain::simulate`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.Blockchain.html#method.simulate
+    pub fn simulate(&self, simulation: &Simulation) -> SimulationResult {
+        self.blockchain.simulate(simulation)
+    }
+
+    /// Performs a read-only query to a service against the current blockchain state.
+    ///
+    /// Note that asynchronous queries via [`Blockchain::query`] (and thus the
+    /// corresponding explorer endpoint) are not available in the testkit.
+    ///
+    /// [`Blockchain::query`]: https://docs.rs/exonum/latest/exonum/blockchain/struct.Blockchain.html#method.query
+    pub fn query(&self, query: &Query) -> Result<Vec<u8>, ExecutionError> {
+        self.blockchain.query(query)
+    }
+
     /// Sets a checkpoint for a future [`rollback`](#method.rollback).
     pub fn checkpoint(&mut self) {
         self.db_handler.checkpoint()
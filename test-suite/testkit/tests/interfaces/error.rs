@@ -26,4 +26,6 @@ pub enum Error {
     WrongInterfaceCaller = 2,
     /// Issuer is not authorized.
     UnauthorizedIssuer = 3,
+    /// Wallet balance limit exceeded.
+    BalanceLimitExceeded = 4,
 }
@@ -30,5 +30,7 @@ pub struct Issue {
 #[exonum_interface(interface = "IssueReceiver", auto_ids)]
 pub trait IssueReceiver<Ctx> {
     type Output;
+    /// Returns the updated balance of the wallet.
+    #[interface_method(returns = "u64")]
     fn issue(&self, ctx: Ctx, arg: Issue) -> Self::Output;
 }
@@ -29,7 +29,8 @@ use crate::{
     schema::{Wallet, WalletSchema},
     services::{
         AnyCall, AnyCallService, CallAny, CustomCall, CustomCallInterface, CustomCallService,
-        DepositInterface, DepositService, Issue, TxIssue, WalletInterface, WalletService,
+        DepositInterface, DepositService, Issue, TxCappedIssue, TxIssue, WalletInterface,
+        WalletService,
     },
 };
 
@@ -176,6 +177,83 @@ fn test_deposit_ok() {
     );
 }
 
+#[test]
+fn test_deposit_with_return_value() {
+    let mut testkit = testkit_with_interfaces();
+    let keypair = KeyPair::random();
+
+    execute_transaction(
+        &mut testkit,
+        keypair.create_wallet(WalletService::ID, "Alice".into()),
+    )
+    .expect("Unable to create wallet");
+
+    let arg = TxCappedIssue {
+        to: keypair.public_key(),
+        amount: 10_000,
+        max_balance: 15_000,
+    };
+    execute_transaction(
+        &mut testkit,
+        keypair.deposit_capped(DepositService::ID, arg.clone()),
+    )
+    .expect("Unable to deposit wallet");
+
+    // The second deposit should fail since the balance returned by the wallet service
+    // exceeds the limit.
+    let err = execute_transaction(
+        &mut testkit,
+        keypair.deposit_capped(DepositService::ID, arg),
+    )
+    .unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&Error::BalanceLimitExceeded));
+
+    let snapshot = testkit.snapshot();
+    assert_eq!(
+        WalletService::get_schema(&snapshot)
+            .wallets
+            .get(&keypair.public_key())
+            .unwrap()
+            .balance,
+        10_000
+    );
+}
+
+#[test]
+fn test_deposit_with_malformed_return_value() {
+    let mut testkit = testkit_with_interfaces();
+    let keypair = KeyPair::random();
+
+    execute_transaction(
+        &mut testkit,
+        keypair.create_wallet(WalletService::ID, "Alice".into()),
+    )
+    .expect("Unable to create wallet");
+
+    let arg = TxIssue {
+        to: keypair.public_key(),
+        amount: 10_000,
+    };
+    // The caller ignores the error, but the call is failed nevertheless.
+    let err = execute_transaction(
+        &mut testkit,
+        keypair.deposit_ignoring_return_value(DepositService::ID, arg),
+    )
+    .unwrap_err();
+    assert_eq!(err, ErrorMatch::from_fail(&CoreError::IncorrectCall));
+
+    // Changes made by the wallet service are rolled back.
+    let snapshot = testkit.snapshot();
+    assert_eq!(
+        WalletService::get_schema(&snapshot)
+            .wallets
+            .get(&keypair.public_key())
+            .unwrap()
+            .balance,
+        0
+    );
+}
+
 #[test]
 fn test_deposit_invalid_auth() {
     let mut testkit = testkit_with_interfaces();
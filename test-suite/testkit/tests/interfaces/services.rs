@@ -17,13 +17,14 @@
 pub use crate::interface::Issue;
 
 use exonum::{
-    crypto::PublicKey,
+    crypto::{Hash, PublicKey},
     runtime::{AnyTx, CallInfo, ExecutionContext, ExecutionError, InstanceId, SnapshotExt},
 };
 use exonum_derive::*;
 use exonum_merkledb::{access::Access, BinaryValue, Snapshot};
 use exonum_rust_runtime::{
-    DefaultInstance, FallthroughAuth, GenericCallMut, MethodDescriptor, Service,
+    DefaultInstance, FallthroughAuth, GenericCallMut, GenericCallReturning, MethodDescriptor,
+    Returning, Service,
 };
 use serde_derive::{Deserialize, Serialize};
 
@@ -78,7 +79,7 @@ impl WalletInterface<ExecutionContext<'_>> for WalletService {
 impl IssueReceiver<ExecutionContext<'_>> for WalletService {
     type Output = Result<(), ExecutionError>;
 
-    fn issue(&self, ctx: ExecutionContext<'_>, arg: Issue) -> Self::Output {
+    fn issue(&self, mut ctx: ExecutionContext<'_>, arg: Issue) -> Self::Output {
         let instance_id = ctx
             .caller()
             .as_service()
@@ -87,10 +88,16 @@ impl IssueReceiver<ExecutionContext<'_>> for WalletService {
             return Err(Error::UnauthorizedIssuer.into());
         }
 
-        let mut schema = WalletSchema::new(ctx.service_data());
-        let mut wallet = schema.wallets.get(&arg.to).ok_or(Error::WalletNotFound)?;
-        wallet.balance += arg.amount;
-        schema.wallets.put(&arg.to, wallet);
+        let balance = {
+            let mut schema = WalletSchema::new(ctx.service_data());
+            let mut wallet = schema.wallets.get(&arg.to).ok_or(Error::WalletNotFound)?;
+            wallet.balance += arg.amount;
+            let balance = wallet.balance;
+            schema.wallets.put(&arg.to, wallet);
+            balance
+        };
+        // Return the updated balance to the caller.
+        ctx.set_return_value(balance);
         Ok(())
     }
 }
@@ -109,10 +116,22 @@ pub struct TxIssue {
     pub amount: u64,
 }
 
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[derive(BinaryValue, ObjectHash)]
+#[binary_value(codec = "bincode")]
+pub struct TxCappedIssue {
+    pub to: PublicKey,
+    pub amount: u64,
+    pub max_balance: u64,
+}
+
 #[exonum_interface(auto_ids)]
 pub trait DepositInterface<Ctx> {
     type Output;
     fn deposit(&self, context: Ctx, arg: TxIssue) -> Self::Output;
+    fn deposit_capped(&self, context: Ctx, arg: TxCappedIssue) -> Self::Output;
+    fn deposit_ignoring_return_value(&self, context: Ctx, arg: TxIssue) -> Self::Output;
 }
 
 #[derive(Debug, ServiceDispatcher, ServiceFactory)]
@@ -145,6 +164,49 @@ impl DepositInterface<ExecutionContext<'_>> for DepositService {
             },
         )
     }
+
+    fn deposit_capped(&self, mut ctx: ExecutionContext<'_>, arg: TxCappedIssue) -> Self::Output {
+        use crate::interface::IssueReceiverReturning;
+
+        if ctx.caller().author() != Some(arg.to) {
+            return Err(Error::UnauthorizedIssuer.into());
+        }
+        // The wallet service returns the updated balance of the wallet.
+        let issue = Issue {
+            to: arg.to,
+            amount: arg.amount,
+        };
+        let balance: u64 = Returning::new(&mut ctx).issue(WalletService::ID, issue)?;
+        if balance > arg.max_balance {
+            return Err(Error::BalanceLimitExceeded.into());
+        }
+        Ok(())
+    }
+
+    fn deposit_ignoring_return_value(
+        &self,
+        mut ctx: ExecutionContext<'_>,
+        arg: TxIssue,
+    ) -> Self::Output {
+        if ctx.caller().author() != Some(arg.to) {
+            return Err(Error::UnauthorizedIssuer.into());
+        }
+        // The wallet service returns `u64`, which cannot be decoded as a hash.
+        let issue = Issue {
+            to: arg.to,
+            amount: arg.amount,
+        };
+        let method = MethodDescriptor::new("IssueReceiver", 0);
+        let res = Returning::new(&mut ctx).generic_call_returning::<Hash>(
+            WalletService::ID,
+            method,
+            issue.into_bytes(),
+        );
+        // The error is ignored; nevertheless, the changes made by the wallet service
+        // should be rolled back.
+        assert!(res.is_err());
+        Ok(())
+    }
 }
 
 impl DefaultInstance for DepositService {